@@ -0,0 +1,43 @@
+//! Fingerprints the generator sources, so that cached files and simulation results are
+//! invalidated whenever the code that produced them changes.
+
+use std::path::{Path, PathBuf};
+
+/// The inputs whose contents determine the generated cells.
+const INPUTS: [&str; 3] = ["src", "Cargo.toml", "Cargo.lock"];
+
+fn main() {
+    let root = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let mut files = Vec::new();
+    for input in INPUTS {
+        println!("cargo:rerun-if-changed={input}");
+        collect_files(&root.join(input), &mut files);
+    }
+    files.sort();
+
+    // A 64-bit FNV-1a hash of each path and its contents, matching `crate::stable_hash`.
+    let mut hash = 0xcbf29ce484222325u64;
+    for file in files {
+        let path = file
+            .strip_prefix(&root)
+            .unwrap()
+            .to_string_lossy()
+            .into_owned();
+        let contents = std::fs::read(&file).unwrap();
+        for &byte in path.as_bytes().iter().chain([0].iter()).chain(&contents) {
+            hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+    println!("cargo:rustc-env=UCIEANALOG_SOURCE_HASH={hash:016x}");
+}
+
+/// Appends `path` to `files` if it is a file, or every file beneath it if it is a directory.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path).unwrap() {
+            collect_files(&entry.unwrap().path(), files);
+        }
+    } else if path.is_file() {
+        files.push(path.to_path_buf());
+    }
+}
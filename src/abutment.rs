@@ -0,0 +1,231 @@
+//! Electrically-aware abutment of lane macros.
+//!
+//! Lanes are arrayed edge to edge with no spacing, which is only legal if the geometry
+//! on each pair of facing edges is compatible. Each lane macro implements [`Abutment`]
+//! to declare what runs along its edges, and [`tile_lanes`] places lanes next to each
+//! other after checking that every pair of facing edges matches.
+
+use atoll::{Instance, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use substrate::geometry::align::AlignMode;
+use substrate::geometry::dir::Dir;
+use substrate::geometry::rect::Rect;
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+use crate::tiles::TileKind;
+
+/// An edge of a tile.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Edge {
+    /// The left edge.
+    Left,
+    /// The right edge.
+    Right,
+    /// The bottom edge.
+    Bottom,
+    /// The top edge.
+    Top,
+}
+
+impl Edge {
+    /// The edges that face each other when tiles are arrayed along `dir`,
+    /// as the edge of the first tile followed by the edge of the next.
+    pub fn facing(dir: Dir) -> (Self, Self) {
+        match dir {
+            Dir::Horiz => (Edge::Right, Edge::Left),
+            Dir::Vert => (Edge::Top, Edge::Bottom),
+        }
+    }
+}
+
+/// A supply rail that can be shared across an edge.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Rail {
+    /// The VDD rail.
+    Vdd,
+    /// The VSS rail.
+    Vss,
+}
+
+/// The geometry along an edge of a tile.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// All geometry is kept inside the tile outline, so the edge can abut any other
+    /// clear edge.
+    Clear,
+    /// A rail and the well or substrate beneath it are drawn up to the edge,
+    /// to be shared with a neighbor that has the same rail over the same well.
+    Rail {
+        /// The rail on the edge.
+        rail: Rail,
+        /// The kind of tap under the rail.
+        well: TileKind,
+    },
+}
+
+impl Edge {
+    /// The alignment modes that place a tile just outside this edge of a rectangle,
+    /// as the mode across the edge followed by the mode along it.
+    ///
+    /// Tiles placed along the left or right edge are bottom-aligned; tiles placed along
+    /// the top or bottom edge are left-aligned.
+    pub fn outside(&self) -> (AlignMode, AlignMode) {
+        match self {
+            Edge::Left => (AlignMode::ToTheLeft, AlignMode::Bottom),
+            Edge::Right => (AlignMode::ToTheRight, AlignMode::Bottom),
+            Edge::Bottom => (AlignMode::Beneath, AlignMode::Left),
+            Edge::Top => (AlignMode::Above, AlignMode::Left),
+        }
+    }
+}
+
+impl EdgeKind {
+    /// Whether an edge of this kind can abut an edge of kind `other` with no spacing.
+    pub fn abuts(&self, other: &EdgeKind) -> bool {
+        self == other
+    }
+}
+
+/// A tile that declares the geometry along its edges.
+pub trait Abutment {
+    /// The geometry along `edge`.
+    fn edge(&self, edge: Edge) -> EdgeKind;
+}
+
+/// An error from checking abutting edges.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AbutmentError {
+    /// Two adjacent lanes have incompatible facing edges.
+    #[error("lane {index} cannot abut the next lane: {this:?} faces {next:?}")]
+    Incompatible {
+        /// The index of the first of the two lanes.
+        index: usize,
+        /// The facing edge of the first lane.
+        this: EdgeKind,
+        /// The facing edge of the next lane.
+        next: EdgeKind,
+    },
+}
+
+/// Checks that every pair of adjacent lanes, arrayed in order along `dir`,
+/// has compatible facing edges.
+pub fn check_abutment<'a, A: Abutment + ?Sized + 'a>(
+    lanes: impl IntoIterator<Item = &'a A>,
+    dir: Dir,
+) -> Result<(), AbutmentError> {
+    let (this_edge, next_edge) = Edge::facing(dir);
+    let lanes = lanes.into_iter().collect::<Vec<_>>();
+    for (index, pair) in lanes.windows(2).enumerate() {
+        let (this, next) = (pair[0].edge(this_edge), pair[1].edge(next_edge));
+        if !this.abuts(&next) {
+            return Err(AbutmentError::Incompatible { index, this, next });
+        }
+    }
+    Ok(())
+}
+
+/// Generates `lanes` and arrays them with no spacing, from left to right if `dir` is
+/// [`Dir::Horiz`] or from bottom to top if `dir` is [`Dir::Vert`].
+///
+/// The first lane is left where it was generated. The returned instances are placed but
+/// not drawn.
+pub fn tile_lanes<PDK, B>(
+    cell: &mut TileBuilder<'_, PDK>,
+    lanes: &[B],
+    dir: Dir,
+) -> Result<Vec<Instance<B>>, AbutmentError>
+where
+    PDK: Pdk + Schema + Sized,
+    B: Tile<PDK> + Abutment + Clone,
+{
+    check_abutment(lanes, dir)?;
+    let mut instances: Vec<Instance<B>> = Vec::with_capacity(lanes.len());
+    for lane in lanes {
+        let mut inst = cell.generate(lane.clone());
+        if let Some(prev) = instances.last() {
+            abut(&mut inst, prev.lcm_bounds(), dir);
+        }
+        instances.push(inst);
+    }
+    Ok(instances)
+}
+
+/// Places `inst` against `prev` with no spacing, as [`tile_lanes`] places each lane
+/// against the one before it.
+///
+/// Used to continue an array with lanes of a different type. Check the facing edges
+/// with [`check_abutment`] first.
+pub fn abut<B: ExportsNestedData + ExportsLayoutData>(
+    inst: &mut Instance<B>,
+    prev: Rect,
+    dir: Dir,
+) {
+    let (along, across) = match dir {
+        Dir::Horiz => (AlignMode::ToTheRight, AlignMode::Bottom),
+        Dir::Vert => (AlignMode::Above, AlignMode::Left),
+    };
+    inst.align_rect_mut(prev, along, 0);
+    inst.align_rect_mut(prev, across, 0);
+}
+
+/// Places `inst` just outside `edge` of `bounds`, `spacing` away from it.
+///
+/// Used to attach peripheral macros, such as a configuration chain along the digital
+/// edge of a lane, without overlapping the lane outline.
+pub fn place_along_edge<B: ExportsNestedData + ExportsLayoutData>(
+    inst: &mut Instance<B>,
+    bounds: Rect,
+    edge: Edge,
+    spacing: i64,
+) {
+    let (across, along) = edge.outside();
+    inst.align_rect_mut(bounds, across, spacing);
+    inst.align_rect_mut(bounds, along, 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Lane(EdgeKind, EdgeKind);
+
+    impl Abutment for Lane {
+        fn edge(&self, edge: Edge) -> EdgeKind {
+            match edge {
+                Edge::Left | Edge::Bottom => self.0,
+                Edge::Right | Edge::Top => self.1,
+            }
+        }
+    }
+
+    #[test]
+    fn abutment_requires_matching_edges() {
+        let vss = EdgeKind::Rail {
+            rail: Rail::Vss,
+            well: TileKind::P,
+        };
+        let vdd = EdgeKind::Rail {
+            rail: Rail::Vdd,
+            well: TileKind::N,
+        };
+        let lanes = [
+            Lane(EdgeKind::Clear, vss),
+            Lane(vss, vdd),
+            Lane(vdd, EdgeKind::Clear),
+        ];
+        assert_eq!(check_abutment(&lanes, Dir::Horiz), Ok(()));
+
+        let lanes = [Lane(EdgeKind::Clear, vss), Lane(vdd, EdgeKind::Clear)];
+        assert_eq!(
+            check_abutment(&lanes, Dir::Vert),
+            Err(AbutmentError::Incompatible {
+                index: 0,
+                this: vss,
+                next: vdd,
+            })
+        );
+    }
+}
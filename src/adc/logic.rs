@@ -0,0 +1,331 @@
+//! Successive-approximation logic.
+
+use crate::abutment::abut;
+use crate::buffer::{BufferIoSchematic, Inverter, InverterImpl};
+use crate::gates::{Dff, DffIoSchematic, DffParams, Gate2, Gate2IoSchematic, Gate2Params};
+use crate::strongarm::InputKind;
+use atoll::{Instance, IoBuilder, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::geometry::align::AlignMode;
+use substrate::geometry::dir::Dir;
+use substrate::geometry::rect::Rect;
+use substrate::io::{Array, DiffPair, InOut, Input, Io, Output, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+/// The interface to a [`SarLogic`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct SarLogicIo {
+    /// The comparator clock.
+    pub clk: Input<Signal>,
+    /// Resets the register while high.
+    pub sample: Input<Signal>,
+    /// The comparator outputs.
+    pub comp: Input<DiffPair>,
+    /// The DAC code, least significant bit first.
+    ///
+    /// Each bit is high while it is under trial or once it has been kept.
+    pub dac: Array<Output<Signal>>,
+    /// The conversion result, least significant bit first.
+    pub code: Array<Output<Signal>>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of the [`SarLogic`] layout generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct SarLogicParams {
+    /// The resolution in bits.
+    pub bits: usize,
+    /// The device sizes of the flip-flops.
+    ///
+    /// The other NAND gates and inverters use the sizes of the flip-flop NAND gates
+    /// and clock inverter.
+    pub dff: DffParams,
+    /// The input kind of the comparator, which sets the clock phase in which the
+    /// comparator resets and the level of its outputs while it does.
+    pub comparator_input: InputKind,
+}
+
+/// The successive-approximation register and sequencer of a [`SarAdc`](super::SarAdc).
+///
+/// A one-hot token shifts from a start flip-flop through one flip-flop per bit, most
+/// significant bit first, on each edge at which the comparator starts to reset. The
+/// bit holding the token is under trial, and the edge that moves the token on loads
+/// the comparator decision into that bit of the code register. While `sample` is high,
+/// each edge loads the token into the start flip-flop and clears the rest of the
+/// sequencer and the code register, so the DAC code is zero while the input is sampled.
+///
+/// The comparator outputs are held in an SR latch through each reset phase, so the
+/// decision is stable when the register loads it. A conversion takes `bits + 1`
+/// comparator clock cycles after `sample` falls, the first of which moves the token
+/// into the most significant bit.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct SarLogic<T>(
+    SarLogicParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> SarLogic<T> {
+    /// Creates a new [`SarLogic`].
+    pub fn new(params: SarLogicParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The logic parameters.
+    pub fn params(&self) -> SarLogicParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for SarLogic<T> {
+    type Io = SarLogicIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("sar_logic")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("sar_logic", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        SarLogicIo {
+            dac: Array::new(self.0.bits, Default::default()),
+            code: Array::new(self.0.bits, Default::default()),
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Any> ExportsNestedData for SarLogic<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for SarLogic<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for SarLogic<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let bits = self.0.bits;
+        let (vdd, vss) = (io.schematic.vdd, io.schematic.vss);
+        let inv = Inverter::<T>::new(self.0.dff.clk_inv);
+        let nand = Gate2::<T>::new(Gate2Params::nand(self.0.dff.gate));
+        let dff = Dff::<T>::new(self.0.dff);
+
+        let sample_b = cell.signal("sample_b", Signal);
+        let decision = cell.signal("decision", Signal);
+        let decision_b = cell.signal("decision_b", Signal);
+        let token = cell.signal("token", Array::new(bits + 1, Signal));
+        let token_b = cell.signal("token_b", Array::new(bits, Signal));
+        let code_b = cell.signal("code_b", Array::new(bits, Signal));
+
+        // The first row holds the shared logic: the sample inverter, the clock inverter
+        // if the comparator resets while the clock is low, the decision latch, and the
+        // start flip-flop.
+        let mut rows = Rows::default();
+        let mut sample_inv = cell.generate_connected(
+            inv,
+            BufferIoSchematic {
+                din: io.schematic.sample,
+                dout: sample_b,
+                vdd,
+                vss,
+            },
+        );
+        rows.place(&mut sample_inv);
+        let sample_inv = cell.draw(sample_inv)?;
+        io.layout.sample.merge(sample_inv.layout.io().din);
+        io.layout.vdd.merge(sample_inv.layout.io().vdd);
+        io.layout.vss.merge(sample_inv.layout.io().vss);
+
+        let reset_clk = match self.0.comparator_input {
+            // A PMOS input comparator evaluates while the clock is low.
+            InputKind::P => io.schematic.clk,
+            InputKind::N => {
+                let clk_b = cell.signal("clk_b", Signal);
+                let mut clk_inv = cell.generate_connected(
+                    inv,
+                    BufferIoSchematic {
+                        din: io.schematic.clk,
+                        dout: clk_b,
+                        vdd,
+                        vss,
+                    },
+                );
+                rows.place(&mut clk_inv);
+                let clk_inv = cell.draw(clk_inv)?;
+                io.layout.clk.merge(clk_inv.layout.io().din);
+                clk_b
+            }
+        };
+
+        // The comparator outputs rest high after a PMOS input latch and low after an
+        // NMOS input latch, so the decision latch is cross-coupled NANDs or NORs.
+        let latch = match self.0.comparator_input {
+            InputKind::P => Gate2Params::nand(self.0.dff.gate),
+            InputKind::N => Gate2Params::nor(self.0.dff.gate),
+        };
+        for (a, b, y, pin) in [
+            (
+                io.schematic.comp.n,
+                decision_b,
+                decision,
+                &mut io.layout.comp.n,
+            ),
+            (
+                io.schematic.comp.p,
+                decision,
+                decision_b,
+                &mut io.layout.comp.p,
+            ),
+        ] {
+            let mut gate = cell.generate_connected(
+                Gate2::<T>::new(latch),
+                Gate2IoSchematic { a, b, y, vdd, vss },
+            );
+            rows.place(&mut gate);
+            let gate = cell.draw(gate)?;
+            pin.merge(gate.layout.io().a);
+        }
+
+        let mut start = cell.generate_connected(
+            dff,
+            DffIoSchematic {
+                d: io.schematic.sample,
+                clk: reset_clk,
+                q: token[bits],
+                vdd,
+                vss,
+            },
+        );
+        rows.place(&mut start);
+        let start = cell.draw(start)?;
+        if self.0.comparator_input == InputKind::P {
+            io.layout.clk.merge(start.layout.io().clk);
+        }
+
+        // One row per bit, most significant bit first.
+        for bit in (0..bits).rev() {
+            let name = |net: &str| format!("{net}_{bit}");
+            let code = io.schematic.code[bit];
+            let token_next_b = cell.signal(name("token_next_b"), Signal);
+            let token_d = cell.signal(name("token_d"), Signal);
+            let keep_b = cell.signal(name("keep_b"), Signal);
+            let hold_b = cell.signal(name("hold_b"), Signal);
+            let code_next = cell.signal(name("code_next"), Signal);
+            let code_next_b = cell.signal(name("code_next_b"), Signal);
+            let code_d = cell.signal(name("code_d"), Signal);
+            rows.new_row();
+
+            for (a, b, y) in [
+                // The token moves on unless the register is being reset.
+                (token[bit + 1], sample_b, token_next_b),
+                // The bit under trial loads the decision and every other bit holds.
+                (token[bit], decision, keep_b),
+                (token_b[bit], code, hold_b),
+                (keep_b, hold_b, code_next),
+                (code_next, sample_b, code_next_b),
+                // The DAC sees the bit while it is under trial or once it has been kept.
+                (code_b[bit], token_b[bit], io.schematic.dac[bit]),
+            ] {
+                let mut gate =
+                    cell.generate_connected(nand, Gate2IoSchematic { a, b, y, vdd, vss });
+                rows.place(&mut gate);
+                let gate = cell.draw(gate)?;
+                if y == io.schematic.dac[bit] {
+                    io.layout.dac[bit].merge(gate.layout.io().y);
+                }
+            }
+            for (din, dout) in [
+                (token_next_b, token_d),
+                (token[bit], token_b[bit]),
+                (code_next_b, code_d),
+                (code, code_b[bit]),
+            ] {
+                let mut inverter = cell.generate_connected(
+                    inv,
+                    BufferIoSchematic {
+                        din,
+                        dout,
+                        vdd,
+                        vss,
+                    },
+                );
+                rows.place(&mut inverter);
+                cell.draw(inverter)?;
+            }
+            for (d, q) in [(token_d, token[bit]), (code_d, code)] {
+                let mut ff = cell.generate_connected(
+                    dff,
+                    DffIoSchematic {
+                        d,
+                        clk: reset_clk,
+                        q,
+                        vdd,
+                        vss,
+                    },
+                );
+                rows.place(&mut ff);
+                let ff = cell.draw(ff)?;
+                if q == code {
+                    io.layout.code[bit].merge(ff.layout.io().q);
+                }
+            }
+        }
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        Ok(((), ()))
+    }
+}
+
+/// Places instances in rows from left to right, starting each row beneath the first
+/// instance of the previous row.
+#[derive(Debug, Default)]
+struct Rows {
+    row_start: Option<Rect>,
+    left: Option<Rect>,
+}
+
+impl Rows {
+    /// Starts a new row.
+    fn new_row(&mut self) {
+        self.left = None;
+    }
+
+    /// Places `inst` at the end of the current row.
+    fn place<B: ExportsNestedData + ExportsLayoutData>(&mut self, inst: &mut Instance<B>) {
+        match self.left {
+            Some(left) => abut(inst, left, Dir::Horiz),
+            None => {
+                if let Some(row_start) = self.row_start {
+                    inst.align_rect_mut(row_start, AlignMode::Left, 0);
+                    inst.align_rect_mut(row_start, AlignMode::Beneath, 0);
+                }
+                self.row_start = Some(inst.lcm_bounds());
+            }
+        }
+        self.left = Some(inst.lcm_bounds());
+    }
+}
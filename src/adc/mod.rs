@@ -0,0 +1,606 @@
+//! Housekeeping ADC generators.
+
+use crate::buffer::{BufferIoSchematic, Inverter, InverterImpl, InverterParams};
+use crate::gates::DffParams;
+use crate::lane::{LaneImpl, MomCap, MomCapParams};
+use crate::params::{check_at_least, check_at_most, check_positive, setters, ParamsError};
+use crate::strongarm::{
+    ClockedDiffComparatorIoSchematic, StrongArmImpl, StrongArmParams, StrongArmWithOutputBuffers,
+};
+use crate::tiles::{
+    CapacitorIoSchematic, MosKind, MosLengthRules, MosTileParams, TapTileParams, TileKind,
+};
+use atoll::{IoBuilder, Tile, TileBuilder};
+use logic::{SarLogic, SarLogicIoSchematic, SarLogicParams};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::geometry::align::AlignMode;
+use substrate::io::{Array, DiffPair, InOut, Input, Io, MosIoSchematic, Output, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+pub mod logic;
+pub mod tb;
+
+/// The interface to a [`SarDac`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct SarDacIo {
+    /// The input voltage.
+    pub vin: Input<Signal>,
+    /// The DAC reference, which sets the width of the input range.
+    pub vref: Input<Signal>,
+    /// Samples the input onto the DAC while high.
+    pub sample: Input<Signal>,
+    /// The DAC code, least significant bit first.
+    ///
+    /// Setting a bit switches the bottom plate of its capacitor from `vref` to VSS.
+    pub code: Array<Input<Signal>>,
+    /// The DAC top plate.
+    pub dac: Output<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The interface to a [`SarAdc`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct SarAdcIo {
+    /// The input voltage.
+    pub vin: Input<Signal>,
+    /// The comparator threshold, which is also the bottom of the input range.
+    pub vcm: Input<Signal>,
+    /// The DAC reference, which sets the width of the input range.
+    pub vref: Input<Signal>,
+    /// Samples the input onto the DAC while high.
+    pub sample: Input<Signal>,
+    /// The comparator clock.
+    pub clk: Input<Signal>,
+    /// The conversion result, least significant bit first.
+    pub code: Array<Output<Signal>>,
+    /// The DAC top plate, for characterization.
+    ///
+    /// Leave unconnected in normal operation, since any load adds to the DAC capacitance.
+    pub dac: Output<Signal>,
+    /// High if the DAC output is above `vcm`, so the bit under trial should be kept.
+    pub comp: Output<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of the [`SarAdc`] generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct SarAdcParams {
+    /// The resolution in bits.
+    pub bits: usize,
+    /// The unit capacitor of the DAC.
+    pub unit_cap: MomCapParams,
+    /// The parameters of each bottom-plate driver.
+    pub dac_driver: InverterParams,
+    /// The width of the NMOS sampling switch.
+    pub sample_w: i64,
+    /// The comparator parameters.
+    pub comparator: StrongArmParams,
+    /// The parameters of the comparator output buffers.
+    pub comparator_buf: InverterParams,
+    /// The device sizes of the successive-approximation logic.
+    pub logic: DffParams,
+}
+
+impl SarAdcParams {
+    /// The largest resolution accepted by the builder.
+    pub const MAX_BITS: usize = 12;
+
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> SarAdcParamsBuilder {
+        SarAdcParamsBuilder::default()
+    }
+
+    /// Returns an error if the parameters would be rejected by
+    /// [`SarAdcParamsBuilder::build`].
+    pub fn validate(&self) -> std::result::Result<(), ParamsError> {
+        SarAdcParamsBuilder { params: *self }.build().map(|_| ())
+    }
+
+    /// Returns an error if the MOS tiles of technology `T` do not support one of the
+    /// channel lengths.
+    pub fn check_lengths<T: MosLengthRules>(&self) -> std::result::Result<(), ParamsError> {
+        self.dac().check_lengths::<T>()?;
+        self.comparator.check_lengths::<T>()?;
+        self.comparator_buf.check_lengths::<T>()?;
+        self.logic.gate.check_lengths::<T>()?;
+        self.logic.clk_inv.check_lengths::<T>()
+    }
+
+    /// The parameters of the capacitor DAC.
+    pub fn dac(&self) -> SarDacParams {
+        SarDacParams {
+            bits: self.bits,
+            unit_cap: self.unit_cap,
+            driver: self.dac_driver,
+            sample_w: self.sample_w,
+        }
+    }
+
+    /// The parameters of the successive-approximation logic.
+    pub fn logic(&self) -> SarLogicParams {
+        SarLogicParams {
+            bits: self.bits,
+            dff: self.logic,
+            comparator_input: self.comparator.input_kind,
+        }
+    }
+}
+
+/// A builder for [`SarAdcParams`].
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct SarAdcParamsBuilder {
+    params: SarAdcParams,
+}
+
+impl Default for SarAdcParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: SarAdcParams {
+                bits: 6,
+                unit_cap: MomCapParams {
+                    fingers: 2,
+                    finger_length: 5_000,
+                },
+                dac_driver: InverterParams::builder().build().unwrap(),
+                sample_w: 2_000,
+                comparator: StrongArmParams::builder().build().unwrap(),
+                comparator_buf: InverterParams::builder().build().unwrap(),
+                logic: DffParams::builder().build().unwrap(),
+            },
+        }
+    }
+}
+
+impl SarAdcParamsBuilder {
+    setters! {
+        /// Sets the resolution in bits.
+        bits: usize,
+        /// Sets the unit capacitor of the DAC.
+        unit_cap: MomCapParams,
+        /// Sets the parameters of each bottom-plate driver.
+        dac_driver: InverterParams,
+        /// Sets the width of the NMOS sampling switch.
+        sample_w: i64,
+        /// Sets the comparator parameters.
+        comparator: StrongArmParams,
+        /// Sets the parameters of the comparator output buffers.
+        comparator_buf: InverterParams,
+        /// Sets the device sizes of the successive-approximation logic.
+        logic: DffParams,
+    }
+
+    /// Validates and returns the parameters.
+    ///
+    /// Channel lengths are checked against a technology with
+    /// [`SarAdcParams::check_lengths`].
+    pub fn build(self) -> std::result::Result<SarAdcParams, ParamsError> {
+        let p = &self.params;
+        p.dac().validate()?;
+        p.comparator.validate()?;
+        p.comparator_buf.validate()?;
+        p.logic.gate.validate()?;
+        p.logic.clk_inv.validate()?;
+        Ok(self.params)
+    }
+}
+
+/// The parameters of the [`SarDac`] generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct SarDacParams {
+    /// The resolution in bits.
+    pub bits: usize,
+    /// The unit capacitor.
+    pub unit_cap: MomCapParams,
+    /// The parameters of each bottom-plate driver.
+    pub driver: InverterParams,
+    /// The width of the NMOS sampling switch.
+    pub sample_w: i64,
+}
+
+impl SarDacParams {
+    /// Returns an error if the resolution is not between 1 and
+    /// [`SarAdcParams::MAX_BITS`], or if a device or capacitor size is invalid.
+    pub fn validate(&self) -> std::result::Result<(), ParamsError> {
+        check_at_least("bits", self.bits as i64, 1)?;
+        check_at_most("bits", self.bits as i64, SarAdcParams::MAX_BITS as i64)?;
+        check_at_least("unit_cap.fingers", self.unit_cap.fingers, 2)?;
+        // The most significant capacitor has `2^(bits - 1)` times the gaps of the
+        // unit capacitor.
+        check_at_most(
+            "unit_cap.fingers",
+            self.unit_cap.fingers,
+            (i64::MAX - 1) / (1 << (self.bits - 1)) + 1,
+        )?;
+        check_positive("unit_cap.finger_length", self.unit_cap.finger_length)?;
+        check_positive("sample_w", self.sample_w)?;
+        self.driver.validate()
+    }
+
+    /// Returns an error if the MOS tiles of technology `T` do not support one of the
+    /// channel lengths.
+    pub fn check_lengths<T: MosLengthRules>(&self) -> std::result::Result<(), ParamsError> {
+        self.driver.check_lengths::<T>()
+    }
+
+    /// The capacitor of bit `bit`.
+    ///
+    /// Bit `i` has `2^i` times the gaps of the unit capacitor, so its capacitance
+    /// is binary weighted up to the fringing at the ends of each comb. Returns `None`
+    /// if `bit` is not a bit of the DAC or if its finger count overflows.
+    pub fn bit_cap(&self, bit: usize) -> Option<MomCapParams> {
+        if bit >= self.bits {
+            return None;
+        }
+        let fingers = (self.unit_cap.fingers - 1)
+            .checked_mul(2i64.checked_pow(bit.try_into().ok()?)?)?
+            .checked_add(1)?;
+        Some(MomCapParams {
+            fingers,
+            finger_length: self.unit_cap.finger_length,
+        })
+    }
+}
+
+/// Returns the code found by a successive-approximation search over `bits` bits.
+///
+/// `keep(code)` reports whether the bit most recently set in `code` should be kept,
+/// which is the decision made by the comparator of a [`SarAdc`]. Returns `None` if
+/// the code does not fit in a `u32`.
+pub fn sar_search(bits: usize, mut keep: impl FnMut(u32) -> bool) -> Option<u32> {
+    if bits > u32::BITS as usize {
+        return None;
+    }
+    Some((0..bits).rev().fold(0, |code, bit| {
+        let trial = code | (1 << bit);
+        if keep(trial) {
+            trial
+        } else {
+            code
+        }
+    }))
+}
+
+/// A SAR ADC implementation.
+pub trait SarAdcImpl<PDK: Pdk + Schema>: LaneImpl<PDK> {
+    /// The top ATOLL layer of the ADC.
+    ///
+    /// Must be at least the layer on which [`MomCap`] draws the DAC capacitors.
+    const TOP_LAYER: usize;
+}
+
+/// The binary-weighted capacitor DAC and sampling switch of a [`SarAdc`].
+///
+/// The input is sampled onto the top plate of the DAC while the bottom plates sit at
+/// `vref`. Setting bit `i` of `code` switches its bottom plate to VSS, lowering the
+/// top plate by `vref * 2^i / 2^bits`. A dummy unit capacitor tied to VSS makes the
+/// total DAC capacitance `2^bits` units.
+///
+/// The schematic only models the capacitance between the fingers of each [`MomCap`],
+/// so linearity is best characterized on an extracted view.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct SarDac<T>(
+    SarDacParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> SarDac<T> {
+    /// Creates a new [`SarDac`].
+    pub fn new(params: SarDacParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The DAC parameters.
+    pub fn params(&self) -> SarDacParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for SarDac<T> {
+    type Io = SarDacIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("sar_dac")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("sar_dac", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        SarDacIo {
+            code: Array::new(self.0.bits, Default::default()),
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Any> ExportsNestedData for SarDac<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for SarDac<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: SarAdcImpl<PDK> + Any> Tile<PDK> for SarDac<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        self.0
+            .validate()
+            .and_then(|()| self.0.check_lengths::<T>())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let top = io.schematic.dac;
+        let bot = cell.signal("bot", Array::new(self.0.bits, Signal));
+
+        // Capacitors are stacked downward from the dummy to the most significant bit,
+        // each with its bottom-plate driver to its left.
+        let mut prev = None;
+        let mut caps = Vec::new();
+        let mut drivers = Vec::new();
+        for bit in 0..=self.0.bits {
+            let (params, n) = match bit.checked_sub(1) {
+                None => (self.0.unit_cap, io.schematic.vss),
+                // Validation guarantees that every bit capacitor fits.
+                Some(bit) => (self.0.bit_cap(bit).unwrap(), bot[bit]),
+            };
+            let mut cap = cell
+                .generate_connected(MomCap::<T>::new(params), CapacitorIoSchematic { p: top, n });
+            if let Some(prev) = prev {
+                cap.align_rect_mut(prev, AlignMode::Left, 0);
+                cap.align_rect_mut(prev, AlignMode::Beneath, -T::INPUT_BUFFER_SPACING);
+            }
+            prev = Some(cap.lcm_bounds());
+            if let Some(bit) = bit.checked_sub(1) {
+                let driver = cell
+                    .generate_connected(
+                        Inverter::<T>::new(self.0.driver),
+                        BufferIoSchematic {
+                            din: io.schematic.code[bit],
+                            dout: bot[bit],
+                            vdd: io.schematic.vref,
+                            vss: io.schematic.vss,
+                        },
+                    )
+                    .align(&cap, AlignMode::CenterVertical, 0)
+                    .align(&cap, AlignMode::ToTheLeft, -T::INPUT_BUFFER_SPACING);
+                drivers.push(driver);
+            }
+            caps.push(cap);
+        }
+        let msb = caps.last().unwrap().lcm_bounds();
+
+        let mut switch = cell.generate_connected(
+            <T as InverterImpl<PDK>>::mos(
+                MosTileParams::new(MosKind::Nom, TileKind::N, self.0.sample_w)
+                    .snapped(<T as InverterImpl<PDK>>::snap_width),
+            ),
+            MosIoSchematic {
+                d: io.schematic.vin,
+                g: io.schematic.sample,
+                s: top,
+                b: io.schematic.vss,
+            },
+        );
+        switch.align_rect_mut(msb, AlignMode::Top, 0);
+        switch.align_rect_mut(msb, AlignMode::ToTheRight, T::INPUT_BUFFER_SPACING);
+        let ptap = cell
+            .generate(<T as InverterImpl<PDK>>::tap(TapTileParams::new(
+                TileKind::P,
+                1,
+            )))
+            .align(&switch, AlignMode::Left, 0)
+            .align(&switch, AlignMode::Beneath, 0);
+        cell.connect(ptap.io().x, io.schematic.vss);
+
+        let caps = caps
+            .into_iter()
+            .map(|cap| cell.draw(cap))
+            .collect::<substrate::error::Result<Vec<_>>>()?;
+        let drivers = drivers
+            .into_iter()
+            .map(|driver| cell.draw(driver))
+            .collect::<substrate::error::Result<Vec<_>>>()?;
+        let switch = cell.draw(switch)?;
+        let ptap = cell.draw(ptap)?;
+
+        cell.set_top_layer(T::TOP_LAYER);
+        cell.set_router(crate::route::router(
+            cell.ctx(),
+            <T as InverterImpl<PDK>>::ROUTER,
+        ));
+        cell.set_via_maker(<T as InverterImpl<PDK>>::via_maker());
+
+        for (bit, driver) in drivers.iter().enumerate() {
+            io.layout.code[bit].merge(driver.layout.io().din);
+            io.layout.vref.merge(driver.layout.io().vdd);
+            io.layout.vss.merge(driver.layout.io().vss);
+        }
+        io.layout.dac.merge(caps[0].layout.io().p);
+        io.layout.vin.merge(switch.layout.io().d);
+        io.layout.sample.merge(switch.layout.io().g);
+        io.layout.vss.merge(ptap.layout.io().x);
+
+        Ok(((), ()))
+    }
+}
+
+/// A charge-redistribution SAR ADC for slow on-die monitoring.
+///
+/// A [`SarDac`] samples the input while `sample` is high, and a StrongARM compares
+/// its top plate against `vcm` on each cycle of `clk`. A [`SarLogic`] runs
+/// [`sar_search`] on the comparator decisions, so that `bits + 1` cycles after
+/// `sample` falls, `code` holds the input above `vcm` in units of `vref / 2^bits`.
+///
+/// Use a PMOS input comparator when `vcm` is near VSS.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct SarAdc<T>(
+    SarAdcParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> SarAdc<T> {
+    /// Creates a new [`SarAdc`].
+    pub fn new(params: SarAdcParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The ADC parameters.
+    pub fn params(&self) -> SarAdcParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for SarAdc<T> {
+    type Io = SarAdcIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("sar_adc")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("sar_adc", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        SarAdcIo {
+            code: Array::new(self.0.bits, Default::default()),
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Any> ExportsNestedData for SarAdc<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for SarAdc<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: SarAdcImpl<PDK> + Any> Tile<PDK> for SarAdc<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        self.0
+            .validate()
+            .and_then(|()| self.0.check_lengths::<T>())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let dac_code = cell.signal("dac_code", Array::new(self.0.bits, Signal));
+        let input = cell.signal("input", DiffPair::default());
+        let output = cell.signal("output", DiffPair::default());
+        cell.connect(input.p, io.schematic.dac);
+        cell.connect(input.n, io.schematic.vcm);
+        cell.connect(output.p, io.schematic.comp);
+
+        let dac = cell.generate_connected(
+            SarDac::<T>::new(self.0.dac()),
+            SarDacIoSchematic {
+                vin: io.schematic.vin,
+                vref: io.schematic.vref,
+                sample: io.schematic.sample,
+                code: dac_code.clone(),
+                dac: io.schematic.dac,
+                vss: io.schematic.vss,
+            },
+        );
+        let comparator = cell
+            .generate_connected(
+                StrongArmWithOutputBuffers::<T>::new(self.0.comparator, self.0.comparator_buf),
+                ClockedDiffComparatorIoSchematic {
+                    input,
+                    output: output.clone(),
+                    clock: io.schematic.clk,
+                    vdd: io.schematic.vdd,
+                    vss: io.schematic.vss,
+                },
+            )
+            .align(&dac, AlignMode::Top, 0)
+            .align(&dac, AlignMode::ToTheRight, T::INPUT_BUFFER_SPACING);
+        let logic = cell
+            .generate_connected(
+                SarLogic::<T>::new(self.0.logic()),
+                SarLogicIoSchematic {
+                    clk: io.schematic.clk,
+                    sample: io.schematic.sample,
+                    comp: output,
+                    dac: dac_code,
+                    code: io.schematic.code.clone(),
+                    vdd: io.schematic.vdd,
+                    vss: io.schematic.vss,
+                },
+            )
+            .align(&comparator, AlignMode::Left, 0)
+            .align(&comparator, AlignMode::Beneath, -T::INPUT_BUFFER_SPACING);
+
+        let dac = cell.draw(dac)?;
+        let comparator = cell.draw(comparator)?;
+        let logic = cell.draw(logic)?;
+
+        cell.set_top_layer(T::TOP_LAYER);
+        cell.set_router(crate::route::router(
+            cell.ctx(),
+            <T as StrongArmImpl<PDK>>::ROUTER,
+        ));
+        cell.set_via_maker(<T as StrongArmImpl<PDK>>::via_maker());
+
+        io.layout.vin.merge(dac.layout.io().vin);
+        io.layout.vref.merge(dac.layout.io().vref);
+        io.layout.sample.merge(dac.layout.io().sample);
+        io.layout.dac.merge(dac.layout.io().dac);
+        io.layout.vss.merge(dac.layout.io().vss);
+        io.layout.vcm.merge(comparator.layout.io().input.n);
+        io.layout.clk.merge(comparator.layout.io().clock);
+        io.layout.comp.merge(comparator.layout.io().output.p);
+        io.layout.vdd.merge(comparator.layout.io().vdd);
+        io.layout.vss.merge(comparator.layout.io().vss);
+        for bit in 0..self.0.bits {
+            io.layout.code[bit].merge(logic.layout.io().code[bit].clone());
+        }
+
+        Ok(((), ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sar_search_converges_on_input() {
+        // An ideal 8-bit converter with a 1 V range, keeping bits while the DAC
+        // has not passed the input.
+        let vin = 0.3;
+        let code = sar_search(8, |code| code as f64 / 256. <= vin);
+        assert_eq!(code, Some(76));
+        assert_eq!(sar_search(8, |_| true), Some(255));
+        assert_eq!(sar_search(8, |_| false), Some(0));
+        assert_eq!(sar_search(32, |_| true), Some(u32::MAX));
+        assert_eq!(sar_search(33, |_| true), None);
+    }
+}
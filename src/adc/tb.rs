@@ -0,0 +1,372 @@
+//! SAR ADC linearity characterization.
+//!
+//! The transition levels of a SAR ADC are the levels of its capacitor DAC referred to
+//! the input, so [`SarAdcDacTb`] steps the DAC through every code in one transient and
+//! [`AdcLinearity`] computes the DNL and INL of the resulting staircase.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::{Pulse, Vsource};
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Node};
+use substrate::io::{Array, FlatLen, Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::adc::SarDacIo;
+use crate::report::SimArtifact;
+use crate::spec::{ComplianceReport, Limit, Spec};
+use crate::tb::pi::Samples;
+
+/// The rise and fall time of the control inputs.
+const EDGE: Decimal = dec!(50e-12);
+
+/// A transient testbench that samples `vcm` onto a [`SarDac`](crate::adc::SarDac)
+/// and then counts its code up from 0 to full scale, holding each code for `step`.
+///
+/// The schematic of a [`SarDac`](crate::adc::SarDac) only models the capacitance
+/// between the fingers of each capacitor, so an extracted view of the device-under-test
+/// also captures the mismatch due to fringing and wiring.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct SarAdcDacTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: T,
+    /// The time each code is held, in seconds.
+    pub step: Decimal,
+    /// The sampled input, in volts.
+    pub vcm: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+    /// The DAC reference, in volts, or the supply voltage if `None`.
+    ///
+    /// The DAC output falls by up to the reference from `vcm`, so the reference should
+    /// be below `vcm` to keep the sampling switch from forward biasing.
+    #[serde(default)]
+    pub vref: Option<Decimal>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> SarAdcDacTb<T, PDK, C> {
+    /// Creates a new [`SarAdcDacTb`].
+    pub fn new(dut: T, step: Decimal, vcm: Decimal, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            step,
+            vcm,
+            pvt,
+            vref: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Drives the DAC reference with `vref` instead of the supply.
+    pub fn with_vref(mut self, vref: Decimal) -> Self {
+        self.vref = Some(vref);
+        self
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for SarAdcDacTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("sar_adc_dac_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("sar_adc_dac_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`SarAdcDacTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct SarAdcDacTbNodes {
+    dac: Node,
+}
+
+impl<T, PDK, C> ExportsNestedData for SarAdcDacTb<T, PDK, C>
+where
+    SarAdcDacTb<T, PDK, C>: Block,
+{
+    type NestedData = SarAdcDacTbNodes;
+}
+
+impl<T: Block<Io = SarDacIo> + Schematic<PDK> + Clone, PDK: Schema, C: Copy> Schematic<Spectre>
+    for SarAdcDacTb<T, PDK, C>
+where
+    SarAdcDacTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vref = cell.signal("vref", Signal);
+        let vcm = cell.signal("vcm", Signal);
+        let sample = cell.signal("sample", Signal);
+
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
+        cell.connect(dut.io().vref, vref);
+        cell.connect(dut.io().vss, io.vss);
+        cell.connect(dut.io().vin, vcm);
+        cell.connect(dut.io().sample, sample);
+
+        cell.instantiate_connected(
+            Vsource::dc(self.vref.unwrap_or(self.pvt.voltage)),
+            TwoTerminalIoSchematic { p: vref, n: io.vss },
+        );
+        cell.instantiate_connected(
+            Vsource::dc(self.vcm),
+            TwoTerminalIoSchematic { p: vcm, n: io.vss },
+        );
+        // Sample for the first step, then hold for the rest of the simulation.
+        cell.instantiate_connected(
+            Vsource::pulse(Pulse {
+                val0: self.pvt.voltage,
+                val1: dec!(0),
+                period: None,
+                width: None,
+                delay: Some(self.step - EDGE),
+                rise: Some(EDGE),
+                fall: Some(EDGE),
+            }),
+            TwoTerminalIoSchematic {
+                p: sample,
+                n: io.vss,
+            },
+        );
+        // Bit `i` toggles every `2^i` steps, so code `k` is applied during step `k + 1`.
+        let code = cell.signal("code", Array::new(dut.io().code.len(), Signal));
+        for i in 0..code.len() {
+            let half = self.step * Decimal::from(1u64 << i);
+            cell.connect(&dut.io().code[i], &code[i]);
+            cell.instantiate_connected(
+                Vsource::pulse(Pulse {
+                    val0: dec!(0),
+                    val1: self.pvt.voltage,
+                    period: Some(dec!(2) * half),
+                    width: Some(half - EDGE),
+                    delay: Some(self.step + half),
+                    rise: Some(EDGE),
+                    fall: Some(EDGE),
+                }),
+                TwoTerminalIoSchematic {
+                    p: code[i],
+                    n: io.vss,
+                },
+            );
+        }
+
+        let dac = cell.signal("dac", Signal);
+        cell.connect(dut.io().dac, dac);
+
+        Ok(SarAdcDacTbNodes { dac })
+    }
+}
+
+/// The resulting waveforms of a [`SarAdcDacTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct SarAdcDacSim {
+    /// The simulation time.
+    pub t: tran::Time,
+    /// The DAC output.
+    pub dac: tran::Voltage,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, SarAdcDacSim> for SarAdcDacTb<T, PDK, C>
+where
+    SarAdcDacTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <SarAdcDacSim as FromSaved<Spectre, Tran>>::SavedKey {
+        SarAdcDacSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            dac: tran::Voltage::save(ctx, &cell.dac, opts),
+        }
+    }
+}
+
+impl<T: Block<Io = SarDacIo>, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre>
+    for SarAdcDacTb<T, PDK, C>
+where
+    SarAdcDacTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = AdcLinearity;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let codes = 1usize << self.dut.io().code.len();
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: SarAdcDacSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: self.step * Decimal::from(codes + 1),
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+        let step = self.step.to_f64().unwrap();
+        let samples = Samples {
+            t: &wav.t,
+            v: &wav.dac,
+        };
+        // Measure each code just before the next one is applied.
+        AdcLinearity::from_levels(
+            &(0..codes)
+                .map(|k| samples.value_at(step * (k as f64 + 1.9)))
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+/// The static linearity of an ADC, from the DAC level at each code.
+///
+/// Errors are in LSB relative to the line through the first and last levels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdcLinearity {
+    /// The average step between adjacent levels, in volts.
+    ///
+    /// Negative if the levels fall with increasing code.
+    pub lsb: f64,
+    /// The differential nonlinearity of each step, from code `k` to `k + 1`.
+    pub dnl: Vec<f64>,
+    /// The integral nonlinearity of each code.
+    pub inl: Vec<f64>,
+}
+
+impl AdcLinearity {
+    /// Computes the linearity of the DAC levels of consecutive codes starting at 0.
+    pub fn from_levels(levels: &[f64]) -> Self {
+        assert!(levels.len() >= 2, "need at least two levels");
+        let n = levels.len();
+        let lsb = (levels[n - 1] - levels[0]) / (n - 1) as f64;
+        Self {
+            lsb,
+            dnl: levels
+                .windows(2)
+                .map(|w| (w[1] - w[0]) / lsb - 1.)
+                .collect(),
+            inl: levels
+                .iter()
+                .enumerate()
+                .map(|(k, level)| (level - levels[0]) / lsb - k as f64)
+                .collect(),
+        }
+    }
+
+    /// The largest DNL magnitude, in LSB.
+    pub fn max_dnl(&self) -> f64 {
+        self.dnl.iter().map(|dnl| dnl.abs()).fold(0., f64::max)
+    }
+
+    /// The largest INL magnitude, in LSB.
+    pub fn max_inl(&self) -> f64 {
+        self.inl.iter().map(|inl| inl.abs()).fold(0., f64::max)
+    }
+
+    /// The codes that can never be produced, because the step into them has a DNL
+    /// of -1 LSB or less.
+    pub fn missing_codes(&self) -> Vec<usize> {
+        self.dnl
+            .iter()
+            .enumerate()
+            .filter(|(_, &dnl)| dnl <= -1.)
+            .map(|(k, _)| k + 1)
+            .collect()
+    }
+
+    /// Checks the DNL and INL against the given limits, in LSB.
+    pub fn compliance(&self, max_dnl: f64, max_inl: f64) -> ComplianceReport {
+        ComplianceReport::evaluate(
+            &[
+                Spec::new("dnl", Limit::Max(max_dnl), "LSB"),
+                Spec::new("inl", Limit::Max(max_inl), "LSB"),
+            ],
+            [("dnl", self.max_dnl()), ("inl", self.max_inl())],
+        )
+    }
+}
+
+impl SimArtifact for AdcLinearity {
+    fn csv_header(&self) -> Vec<String> {
+        ["code", "dnl", "inl"].map(String::from).to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.inl
+            .iter()
+            .enumerate()
+            .map(|(k, inl)| {
+                // The first code has no step into it.
+                let dnl = k.checked_sub(1).map_or(0., |k| self.dnl[k]);
+                vec![k.to_string(), dnl.to_string(), inl.to_string()]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linearity_of_a_falling_staircase() {
+        // A 2-bit DAC whose second step is half an LSB too wide.
+        let levels = [1.0, 0.9, 0.75, 0.7];
+        let lin = AdcLinearity::from_levels(&levels);
+        assert!((lin.lsb + 0.1).abs() < 1e-12);
+        assert!((lin.dnl[1] - 0.5).abs() < 1e-9);
+        assert!((lin.inl[2] - 0.5).abs() < 1e-9);
+        assert!((lin.max_dnl() - 0.5).abs() < 1e-9);
+        assert!(lin.missing_codes().is_empty());
+
+        let missing = AdcLinearity::from_levels(&[0., 0.2, 0.2, 0.3]);
+        assert_eq!(missing.missing_codes(), vec![2]);
+    }
+}
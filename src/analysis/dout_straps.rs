@@ -0,0 +1,273 @@
+//! Parasitic estimation of the `dout` strap network of a horizontal driver.
+//!
+//! The `dout` straps tying the banks together and the bump rectangle of each bank are
+//! taken from the drawn geometry of a [`HorizontalDriver`]. Each rectangle is treated as
+//! a straight conductor along its longer side. Its resistance comes from the layer's
+//! sheet resistance, its capacitance from the layer's area and fringe capacitance to the
+//! substrate, and its partial self-inductance from the closed-form expression for a
+//! rectangular bar. Mutual inductance between conductors is ignored.
+
+use std::any::Any;
+
+use atoll::TileWrapper;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use substrate::context::PdkContext;
+use substrate::geometry::dir::Dir;
+use substrate::geometry::rect::Rect;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+
+use crate::analysis::ir_drop::MetalStack;
+use crate::driver::{
+    DriverLayerMap, DriverParams, HorizontalDriver, HorizontalDriverImpl,
+    HorizontalDriverLayoutData,
+};
+use crate::report::SimArtifact;
+
+/// The permeability of free space divided by 2π, in henries per nanometer.
+const MU0_OVER_2PI: f64 = 2e-16;
+
+/// Capacitance and thickness of the metal layers of a technology's ATOLL layer stack.
+pub trait WireParasitics: MetalStack {
+    /// The area capacitance of ATOLL layer `layer` to the substrate,
+    /// in attofarads per square micron.
+    fn area_capacitance(layer: usize) -> f64;
+    /// The fringe capacitance of ATOLL layer `layer` to the substrate,
+    /// in attofarads per micron of edge.
+    fn fringe_capacitance(layer: usize) -> f64;
+    /// The thickness of ATOLL layer `layer`, in nanometers.
+    fn thickness(layer: usize) -> f64;
+}
+
+/// The parasitics of a single conductor of the strap network.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct StrapSegment {
+    /// The ATOLL layer of the conductor.
+    pub layer: usize,
+    /// The drawn geometry.
+    pub rect: Rect,
+    /// The direction in which the conductor runs.
+    pub dir: Dir,
+    /// The end-to-end resistance, in ohms.
+    pub r: f64,
+    /// The partial self-inductance, in henries.
+    pub l: f64,
+    /// The capacitance to the substrate, in farads.
+    pub c: f64,
+}
+
+impl StrapSegment {
+    /// Estimates the parasitics of `rect` drawn on ATOLL layer `layer`.
+    pub fn new<T: WireParasitics>(layer: usize, rect: Rect) -> Self {
+        let dir = if rect.width() >= rect.height() {
+            Dir::Horiz
+        } else {
+            Dir::Vert
+        };
+        let len = rect.span(dir).length() as f64;
+        let width = rect.span(dir.other()).length() as f64;
+        let wt = width + T::thickness(layer);
+        let area_um2 = len * width * 1e-6;
+        let perimeter_um = 2. * (len + width) * 1e-3;
+        Self {
+            layer,
+            rect,
+            dir,
+            r: T::sheet_resistance(layer) * len / width,
+            l: MU0_OVER_2PI * len * ((2. * len / wt).ln() + 0.5 + 0.2235 * wt / len),
+            c: (T::area_capacitance(layer) * area_um2
+                + T::fringe_capacitance(layer) * perimeter_um)
+                * 1e-18,
+        }
+    }
+}
+
+/// A lumped model of the `dout` strap network for back-annotation into testbenches.
+///
+/// Values are stored as decimals so that the model can be part of hashable
+/// testbench parameters.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct DoutLumpedModel {
+    /// The series resistance, in ohms.
+    pub r: Decimal,
+    /// The series inductance, in henries.
+    pub l: Decimal,
+    /// The total capacitance to ground, in farads.
+    pub c: Decimal,
+}
+
+/// The estimated parasitics of the `dout` strap network of a horizontal driver.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DoutStrapParasitics {
+    /// The conductors of the network, straps first.
+    pub segments: Vec<StrapSegment>,
+}
+
+impl DoutStrapParasitics {
+    /// Estimates the parasitics of the strap network in `data`, drawn with layer map `layers`.
+    pub fn new<T: WireParasitics>(
+        data: &HorizontalDriverLayoutData,
+        layers: DriverLayerMap,
+    ) -> Self {
+        let segments = data
+            .dout_straps
+            .iter()
+            .map(|&rect| StrapSegment::new::<T>(layers.strap + 1, rect))
+            .chain(
+                data.dout_bumps
+                    .iter()
+                    .map(|&rect| StrapSegment::new::<T>(layers.bump, rect)),
+            )
+            .collect();
+        Self { segments }
+    }
+
+    /// Combines the segments on each layer in parallel, then the layers in series.
+    fn series_of_parallel(&self, value: impl Fn(&StrapSegment) -> f64) -> f64 {
+        let mut layers = self.segments.iter().map(|s| s.layer).collect::<Vec<_>>();
+        layers.sort();
+        layers.dedup();
+        layers
+            .into_iter()
+            .map(|layer| {
+                let g = self
+                    .segments
+                    .iter()
+                    .filter(|s| s.layer == layer)
+                    .map(|s| 1. / value(s))
+                    .sum::<f64>();
+                1. / g
+            })
+            .sum()
+    }
+
+    /// The series resistance of the network, in ohms.
+    ///
+    /// The conductors on each layer are taken to be in parallel, and the layers in series.
+    /// Current enters the conductors along their length rather than at one end, so this
+    /// is an upper bound.
+    pub fn resistance(&self) -> f64 {
+        self.series_of_parallel(|s| s.r)
+    }
+
+    /// The series inductance of the network, in henries.
+    ///
+    /// Combined like [`DoutStrapParasitics::resistance`], and likewise an upper bound.
+    pub fn inductance(&self) -> f64 {
+        self.series_of_parallel(|s| s.l)
+    }
+
+    /// The total capacitance of the network to the substrate, in farads.
+    pub fn capacitance(&self) -> f64 {
+        self.segments.iter().map(|s| s.c).sum()
+    }
+
+    /// Returns a lumped model of the network.
+    pub fn lumped(&self) -> DoutLumpedModel {
+        let decimal = |x: f64| Decimal::from_f64(x).unwrap_or_default();
+        DoutLumpedModel {
+            r: decimal(self.resistance()),
+            l: decimal(self.inductance()),
+            c: decimal(self.capacitance()),
+        }
+    }
+}
+
+impl SimArtifact for DoutStrapParasitics {
+    fn csv_header(&self) -> Vec<String> {
+        ["layer", "dir", "length", "width", "r", "l", "c"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.segments
+            .iter()
+            .map(|s| {
+                vec![
+                    s.layer.to_string(),
+                    format!("{:?}", s.dir),
+                    s.rect.span(s.dir).length().to_string(),
+                    s.rect.span(s.dir.other()).length().to_string(),
+                    s.r.to_string(),
+                    s.l.to_string(),
+                    s.c.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Generates the layout of a [`HorizontalDriver`] with `params` and estimates the
+/// parasitics of its `dout` strap network.
+pub fn horizontal_driver_dout_parasitics<T, PDK>(
+    ctx: &PdkContext<PDK>,
+    params: DriverParams,
+) -> DoutStrapParasitics
+where
+    T: HorizontalDriverImpl<PDK> + WireParasitics + Any,
+    PDK: Pdk + Schema + Sized,
+{
+    let cell = ctx.generate_layout(TileWrapper::new(HorizontalDriver::<T>::new(params)));
+    DoutStrapParasitics::new::<T>(cell.data(), T::LAYER_MAP)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Stack;
+
+    impl MetalStack for Stack {
+        fn sheet_resistance(_layer: usize) -> f64 {
+            0.02
+        }
+
+        fn via_resistance(_below: usize) -> f64 {
+            0.5
+        }
+    }
+
+    impl WireParasitics for Stack {
+        fn area_capacitance(_layer: usize) -> f64 {
+            10.
+        }
+
+        fn fringe_capacitance(_layer: usize) -> f64 {
+            40.
+        }
+
+        fn thickness(_layer: usize) -> f64 {
+            1_000.
+        }
+    }
+
+    #[test]
+    fn combines_straps_in_parallel_and_layers_in_series() {
+        let layers = DriverLayerMap::HORIZONTAL;
+        let data = HorizontalDriverLayoutData {
+            dout_straps: vec![
+                Rect::from_sides(0, 0, 1_000, 100_000),
+                Rect::from_sides(10_000, 0, 11_000, 100_000),
+            ],
+            dout_bumps: vec![Rect::from_sides(0, 0, 50_000, 5_000)],
+        };
+        let parasitics = DoutStrapParasitics::new::<Stack>(&data, layers);
+
+        let strap = parasitics.segments[0];
+        assert_eq!(strap.dir, Dir::Vert);
+        assert!((strap.r - 2.).abs() < 1e-12);
+        // 100 um long, 1 um wide: 1000 aF of area and 8080 aF of fringe capacitance.
+        assert!((strap.c - 9.08e-15).abs() < 1e-20);
+        // About 1 nH per millimeter for a narrow conductor.
+        assert!(strap.l > 50e-12 && strap.l < 150e-12);
+
+        // Two 2 ohm straps in parallel, in series with a 0.2 ohm bump rectangle.
+        assert!((parasitics.resistance() - 1.2).abs() < 1e-12);
+        // The bump rectangle adds 2500 aF of area and 4400 aF of fringe capacitance.
+        assert!((parasitics.capacitance() - 25.06e-15).abs() < 1e-20);
+        assert_eq!(parasitics.csv_rows().len(), 3);
+    }
+}
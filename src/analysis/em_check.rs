@@ -0,0 +1,298 @@
+//! Electromigration checks of driver output and supply wiring.
+//!
+//! Currents are estimated from the driver's output resistance, termination, load,
+//! and data rate rather than simulated. The driver is assumed to be terminated to
+//! VSS, so that the termination current flows while driving a one. Random data is
+//! assumed, so that the driver drives a one half the time and transitions every
+//! other unit interval on average.
+
+use crate::analysis::ir_drop::{horizontal_driver_supply_mesh, MeshLayer, MetalStack};
+use crate::driver::{DriverLayerMap, DriverParams, VDD_STRAP_OFFSET, VSS_STRAP_OFFSET};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use substrate::geometry::rect::Rect;
+
+/// An electromigration current limit.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct EmLimit {
+    /// The maximum RMS current.
+    pub rms: f64,
+    /// The maximum peak current.
+    pub peak: f64,
+}
+
+/// Electromigration limits of a technology's ATOLL layer stack.
+pub trait EmRules {
+    /// The current limit of ATOLL layer `layer`, in amps per micron of wire width.
+    fn wire_limit(layer: usize) -> EmLimit;
+    /// The current limit of a single via between ATOLL layers `below` and `below + 1`, in amps.
+    fn via_limit(below: usize) -> EmLimit;
+}
+
+/// A current estimate.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Current {
+    /// The RMS current, in amps.
+    pub rms: f64,
+    /// The peak current, in amps.
+    pub peak: f64,
+}
+
+impl Current {
+    /// Divides the current evenly among `n` parallel paths.
+    pub fn split(self, n: usize) -> Self {
+        let n = n.max(1) as f64;
+        Self {
+            rms: self.rms / n,
+            peak: self.peak / n,
+        }
+    }
+
+    /// Returns the number of copies of `limit` needed to carry this current.
+    fn required(&self, limit: EmLimit) -> f64 {
+        (self.rms / limit.rms).max(self.peak / limit.peak)
+    }
+}
+
+/// Electromigration check parameters.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct EmParams {
+    /// The driver parameters.
+    pub driver: DriverParams,
+    /// The output resistance of a single enabled driver unit, in ohms.
+    pub unit_resistance: f64,
+    /// The supply voltage, in volts.
+    pub vdd: f64,
+    /// The termination resistance, in ohms.
+    pub r_term: f64,
+    /// The load capacitance on `dout`, in farads.
+    pub c_load: f64,
+    /// The data rate, in bits per second.
+    pub data_rate: f64,
+}
+
+impl EmParams {
+    /// The number of driver units connected in parallel to `dout`.
+    pub fn num_units(&self) -> usize {
+        self.driver.num_segments * self.driver.banks
+    }
+
+    /// Estimates the total `dout` current with all units enabled.
+    ///
+    /// The same current is drawn from VDD.
+    pub fn output_current(&self) -> Current {
+        let r_out = self.unit_resistance / self.num_units().max(1) as f64;
+        let i_term = self.vdd / (r_out + self.r_term);
+        // Charging the load within a single unit interval.
+        let i_cap_peak = self.c_load * self.vdd * self.data_rate;
+        // Average charging current at a transition density of one half.
+        let i_cap = i_cap_peak / 2.;
+        Current {
+            rms: (i_term * i_term / 2. + i_cap * i_cap).sqrt(),
+            peak: i_term + i_cap_peak,
+        }
+    }
+}
+
+/// A wire or via that exceeds its electromigration limit.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum EmViolation {
+    /// A via array with too few vias.
+    Via {
+        /// The net the via array belongs to.
+        net: String,
+        /// The ATOLL layer below the via.
+        below: usize,
+        /// The number of vias in the array.
+        count: usize,
+        /// The current through the array.
+        current: Current,
+        /// The minimum number of vias needed to carry the current.
+        min_count: usize,
+    },
+    /// A strap that is too narrow.
+    Strap {
+        /// The net the strap belongs to.
+        net: String,
+        /// The ATOLL layer of the strap.
+        layer: usize,
+        /// The strap width, in nanometers.
+        width: i64,
+        /// The current through the strap.
+        current: Current,
+        /// The minimum strap width needed to carry the current, in nanometers.
+        min_width: i64,
+    },
+}
+
+impl Display for EmViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmViolation::Via {
+                net,
+                below,
+                count,
+                current,
+                min_count,
+            } => write!(
+                f,
+                "{net}: {count} via(s) above layer {below} carry {:e} A rms, {:e} A peak; \
+                 at least {min_count} required",
+                current.rms, current.peak
+            ),
+            EmViolation::Strap {
+                net,
+                layer,
+                width,
+                current,
+                min_width,
+            } => write!(
+                f,
+                "{net}: {width} nm strap on layer {layer} carries {:e} A rms, {:e} A peak; \
+                 at least {min_width} nm required",
+                current.rms, current.peak
+            ),
+        }
+    }
+}
+
+/// The results of an electromigration check.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct EmReport {
+    /// The wires and vias exceeding their limits.
+    pub violations: Vec<EmViolation>,
+}
+
+impl EmReport {
+    /// Returns `true` if no limits are exceeded.
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Checks a stack of via arrays running from ATOLL layer `bot` up to layer `top`,
+/// with `count` vias at each level.
+pub fn check_via_stack<T: EmRules>(
+    net: &str,
+    bot: usize,
+    top: usize,
+    count: usize,
+    current: Current,
+) -> Vec<EmViolation> {
+    (bot..top)
+        .filter_map(|below| {
+            let min_count = current.required(T::via_limit(below)).ceil() as usize;
+            (min_count > count).then(|| EmViolation::Via {
+                net: net.to_string(),
+                below,
+                count,
+                current,
+                min_count,
+            })
+        })
+        .collect()
+}
+
+/// Checks the straps of a mesh whose bottom layer is ATOLL layer `first_layer`.
+///
+/// The current is assumed to divide evenly among the straps of each layer within `bounds`.
+pub fn check_mesh<T: EmRules>(
+    net: &str,
+    first_layer: usize,
+    mesh: &[MeshLayer],
+    bounds: Rect,
+    current: Current,
+) -> Vec<EmViolation> {
+    mesh.iter()
+        .enumerate()
+        .filter_map(|(i, strap)| {
+            let layer = first_layer + i;
+            let current = current.split(strap.positions(bounds).len());
+            let min_width = (current.required(T::wire_limit(layer)) * 1_000.).ceil() as i64;
+            (min_width > strap.width).then(|| EmViolation::Strap {
+                net: net.to_string(),
+                layer,
+                width: strap.width,
+                current,
+                min_width,
+            })
+        })
+        .collect()
+}
+
+/// Checks the `dout` via stacks and supply straps of a horizontal driver occupying `bounds`.
+///
+/// `tracks` gives the track pitch and line width of each ATOLL layer, and `supply_periods`
+/// gives the planned supply strap periods on layer 2 and above.
+/// Each driver unit has a single via at each level from its `dout` pin up to the bump layer.
+pub fn check_horizontal_driver<T: MetalStack + EmRules>(
+    params: &EmParams,
+    tracks: &[(i64, i64)],
+    layers: DriverLayerMap,
+    supply_periods: &[i64],
+    bounds: Rect,
+) -> EmReport {
+    let current = params.output_current();
+    let mut violations = check_via_stack::<T>(
+        "dout",
+        layers.pin,
+        layers.bump,
+        1,
+        current.split(params.num_units()),
+    );
+    for (net, offset) in [("vdd", VDD_STRAP_OFFSET), ("vss", VSS_STRAP_OFFSET)] {
+        let (mesh, _) = horizontal_driver_supply_mesh::<T>(tracks, layers, supply_periods, offset);
+        violations.extend(check_mesh::<T>(net, 2, &mesh, bounds, current));
+    }
+    EmReport { violations }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Rules;
+
+    impl EmRules for Rules {
+        fn wire_limit(_layer: usize) -> EmLimit {
+            EmLimit {
+                rms: 1e-3,
+                peak: 10e-3,
+            }
+        }
+
+        fn via_limit(below: usize) -> EmLimit {
+            let rms = if below < 3 { 0.2e-3 } else { 1e-3 };
+            EmLimit {
+                rms,
+                peak: 10. * rms,
+            }
+        }
+    }
+
+    #[test]
+    fn suggests_via_count() {
+        let current = Current {
+            rms: 0.5e-3,
+            peak: 1e-3,
+        };
+        let violations = check_via_stack::<Rules>("dout", 1, 5, 1, current);
+        assert_eq!(violations.len(), 2);
+        assert!(matches!(
+            violations[0],
+            EmViolation::Via {
+                below: 1,
+                min_count: 3,
+                ..
+            }
+        ));
+        assert!(matches!(
+            violations[1],
+            EmViolation::Via {
+                below: 2,
+                min_count: 3,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,160 @@
+//! Selection of transmit de-emphasis and receive DFE settings from a pulse response.
+//!
+//! The transmitter is modeled as a two-tap FFE: of its driver segments, `k` drive the
+//! previous bit inverted, so the main and post-cursor coefficients are `(n - k) / n` and
+//! `-k / n`. The DFE then subtracts a quantized estimate of each remaining post-cursor.
+//! Eye height is the worst-case (peak distortion) opening of the equalized pulse.
+
+use crate::spec::{ComplianceReport, Limit, Spec};
+use crate::tb::pulse::PulseResponse;
+use serde::{Deserialize, Serialize};
+
+/// The equalizer settings available to [`optimize_equalization`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct EqualizerSpace {
+    /// The total number of transmit driver segments.
+    pub tx_segments: usize,
+    /// The largest number of segments that may be allocated to de-emphasis.
+    pub max_deemphasis_segments: usize,
+    /// The number of DFE taps.
+    pub dfe_taps: usize,
+    /// The DFE tap step, in volts per code.
+    pub dfe_lsb: f64,
+    /// The largest DFE code magnitude.
+    pub dfe_max_code: i64,
+}
+
+/// Recommended equalizer settings.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EqualizerSolution {
+    /// The number of transmit segments driving the inverted previous bit.
+    pub deemphasis_segments: usize,
+    /// The code of each DFE tap, first post-cursor first.
+    pub dfe_codes: Vec<i64>,
+    /// The equalized main cursor, in volts.
+    pub cursor: f64,
+    /// The sum of the residual inter-symbol interference magnitudes, in volts.
+    pub residual_isi: f64,
+}
+
+impl EqualizerSolution {
+    /// The worst-case eye height, in volts.
+    ///
+    /// The eye spans from the main cursor reduced by the residual ISI down to the
+    /// opposite symbol, so its height is twice the remaining opening.
+    pub fn eye_height(&self) -> f64 {
+        2. * (self.cursor - self.residual_isi)
+    }
+
+    /// Checks the equalized eye height against `min_eye_height`, in volts.
+    pub fn compliance(&self, min_eye_height: f64) -> ComplianceReport {
+        ComplianceReport::evaluate(
+            &[Spec::new("eye_height", Limit::Min(min_eye_height), "V")],
+            [("eye_height", self.eye_height())],
+        )
+    }
+}
+
+/// Applies the two-tap transmit FFE with `k` of `n` segments on de-emphasis to the cursors `taps`.
+///
+/// The returned response has one more post-cursor than `taps`.
+fn apply_ffe(taps: &[f64], n: usize, k: usize) -> Vec<f64> {
+    let c0 = (n - k) as f64 / n as f64;
+    let c1 = -(k as f64) / n as f64;
+    (0..=taps.len())
+        .map(|i| {
+            let cur = taps.get(i).copied().unwrap_or(0.);
+            let prev = i.checked_sub(1).map_or(0., |i| taps[i]);
+            c0 * cur + c1 * prev
+        })
+        .collect()
+}
+
+/// Evaluates the given de-emphasis setting with the best DFE codes for it.
+fn evaluate(pulse: &PulseResponse, space: &EqualizerSpace, k: usize) -> EqualizerSolution {
+    let main = pulse.pre.len();
+    let eq = apply_ffe(&pulse.taps(), space.tx_segments, k);
+    let cursor = eq[main];
+    let dfe_codes = eq[main + 1..]
+        .iter()
+        .take(space.dfe_taps)
+        .map(|h| {
+            ((h / space.dfe_lsb).round() as i64).clamp(-space.dfe_max_code, space.dfe_max_code)
+        })
+        .collect::<Vec<_>>();
+    let pre_isi = eq[..main].iter().map(|h| h.abs()).sum::<f64>();
+    let post_isi = eq[main + 1..]
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            let dfe = dfe_codes
+                .get(i)
+                .map_or(0., |&code| code as f64 * space.dfe_lsb);
+            (h - dfe).abs()
+        })
+        .sum::<f64>();
+    EqualizerSolution {
+        deemphasis_segments: k,
+        dfe_codes,
+        cursor,
+        residual_isi: pre_isi + post_isi,
+    }
+}
+
+/// Searches de-emphasis segment allocations and DFE codes for the largest eye height.
+///
+/// For each de-emphasis setting, each DFE tap is set to the code nearest the post-cursor
+/// it cancels. Ties in eye height favor less de-emphasis.
+pub fn optimize_equalization(pulse: &PulseResponse, space: &EqualizerSpace) -> EqualizerSolution {
+    assert!(space.tx_segments > 0, "transmitter must have a segment");
+    assert!(space.dfe_lsb > 0., "DFE step must be positive");
+    (0..=space.max_deemphasis_segments.min(space.tx_segments - 1))
+        .map(|k| evaluate(pulse, space, k))
+        .reduce(|best, next| {
+            if next.eye_height() > best.eye_height() {
+                next
+            } else {
+                best
+            }
+        })
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equalization_opens_a_closed_eye() {
+        let pulse = PulseResponse {
+            ui: 1e-10,
+            cursor_time: 0.,
+            cursor: 0.4,
+            pre: vec![0.02],
+            post: vec![0.25, 0.15, 0.08],
+        };
+        let unequalized = EqualizerSpace {
+            tx_segments: 8,
+            max_deemphasis_segments: 0,
+            dfe_taps: 0,
+            dfe_lsb: 0.01,
+            dfe_max_code: 15,
+        };
+        let none = optimize_equalization(&pulse, &unequalized);
+        assert!(none.eye_height() < 0.);
+        assert!(!none.compliance(0.1).passed());
+
+        let best = optimize_equalization(
+            &pulse,
+            &EqualizerSpace {
+                max_deemphasis_segments: 3,
+                dfe_taps: 2,
+                ..unequalized
+            },
+        );
+        assert!(best.deemphasis_segments > 0);
+        assert_eq!(best.dfe_codes.len(), 2);
+        assert!(best.eye_height() > 0.3);
+        assert!(best.compliance(0.1).passed());
+    }
+}
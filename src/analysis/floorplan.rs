@@ -0,0 +1,264 @@
+//! Floorplan estimation from bump and macro areas.
+//!
+//! A UCIe module is as wide as its bump map along the die edge. [`Floorplan::estimate`]
+//! compares the area under the bump map with the area needed by the lane macros at a
+//! target placement utilization. If the bumps need more area, the module is pad-limited
+//! and its depth is set by the bump map. Otherwise it is core-limited and grows deeper
+//! than the bump map to fit the macros.
+//!
+//! Macro areas come from an [`AreaReport`], so the estimate can be made from a few
+//! generated blocks before generating the full module.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{PackageType, PhyConfig};
+use crate::report::area::AreaReport;
+use crate::report::SimArtifact;
+
+/// The area of one kind of macro placed in a module.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MacroArea {
+    /// The role of the macro, such as `"tx_driver"`.
+    pub role: String,
+    /// The bounding box area of one instance, in square nanometers.
+    pub area: i64,
+    /// The number of instances in the module.
+    pub count: usize,
+}
+
+impl MacroArea {
+    /// Creates a new [`MacroArea`].
+    pub fn new(role: impl Into<String>, area: i64, count: usize) -> Self {
+        Self {
+            role: role.into(),
+            area,
+            count,
+        }
+    }
+
+    /// The area of `count` instances of the cell summarized by `report`.
+    pub fn from_report(role: impl Into<String>, report: &AreaReport, count: usize) -> Self {
+        Self::new(role, report.total_area, count)
+    }
+
+    /// The total area of all instances, in square nanometers.
+    pub fn total_area(&self) -> i64 {
+        self.area * self.count as i64
+    }
+}
+
+/// The bump map and placement assumptions of a module.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Floorplan {
+    /// The number of data lanes.
+    pub lanes: usize,
+    /// The bump pitch, in nanometers.
+    pub bump_pitch: i64,
+    /// The number of bumps from the die edge inward.
+    pub bump_rows: usize,
+    /// The number of signal bumps per data lane.
+    pub bumps_per_lane: usize,
+    /// The number of signal bumps shared by the module, for clocks, valid, track,
+    /// sideband, and redundant lanes.
+    pub shared_bumps: usize,
+    /// The fraction of all bumps used for power and ground.
+    pub supply_fraction: f64,
+    /// The fraction of the core area that macros can occupy.
+    pub utilization: f64,
+}
+
+impl Floorplan {
+    /// Creates a [`Floorplan`] for a module of `lanes` lanes in `package` with bumps at
+    /// `bump_pitch`.
+    ///
+    /// Assumes a TX and an RX bump per lane, a bump map ten bumps deep, a quarter of the
+    /// bumps used for supplies, and 70% placement utilization. Advanced packages add
+    /// four redundant lanes in each direction to the shared bumps.
+    pub fn new(package: PackageType, lanes: usize, bump_pitch: i64) -> Self {
+        // Forwarded clock pair, valid, and track in each direction, plus the sideband
+        // data and clock in each direction.
+        let shared_bumps = 2 * (2 + 1 + 1) + 4;
+        let redundant_bumps = match package {
+            PackageType::Standard => 0,
+            PackageType::Advanced => 2 * 4,
+        };
+        Self {
+            lanes,
+            bump_pitch,
+            bump_rows: 10,
+            bumps_per_lane: 2,
+            shared_bumps: shared_bumps + redundant_bumps,
+            supply_fraction: 0.25,
+            utilization: 0.7,
+        }
+    }
+
+    /// Creates a [`Floorplan`] for the module described by `config`.
+    pub fn from_config(config: &PhyConfig, bump_pitch: i64) -> Self {
+        Self::new(config.package, config.lanes, bump_pitch)
+    }
+
+    /// The total number of bumps, including supply bumps.
+    pub fn bumps(&self) -> usize {
+        let signal = self.lanes * self.bumps_per_lane + self.shared_bumps;
+        (signal as f64 / (1. - self.supply_fraction)).ceil() as usize
+    }
+
+    /// The width and depth of the bump map, in nanometers.
+    pub fn bump_map(&self) -> (i64, i64) {
+        let columns = self.bumps().div_ceil(self.bump_rows);
+        (
+            columns as i64 * self.bump_pitch,
+            self.bump_rows as i64 * self.bump_pitch,
+        )
+    }
+
+    /// Estimates the floorplan of a module containing `macros`.
+    pub fn estimate(&self, macros: &[MacroArea]) -> FloorplanEstimate {
+        let (width, pad_depth) = self.bump_map();
+        let pad_area = width * pad_depth;
+        let macro_area: i64 = macros.iter().map(MacroArea::total_area).sum();
+        let core_area = (macro_area as f64 / self.utilization).ceil() as i64;
+
+        let (limit, depth) = if core_area > pad_area {
+            (FloorplanLimit::Core, core_area.div_ceil(width))
+        } else {
+            (FloorplanLimit::Pad, pad_depth)
+        };
+
+        // Each lane gets an equal share of the module along the die edge.
+        let lane_area = (width * depth) as f64 / self.lanes.max(1) as f64;
+        let lane_utilization = macros
+            .iter()
+            .map(|m| {
+                let area_per_lane = m.total_area() as f64 / self.lanes.max(1) as f64;
+                LaneUtilization {
+                    role: m.role.clone(),
+                    area_per_lane,
+                    utilization: area_per_lane / lane_area,
+                }
+            })
+            .collect();
+
+        FloorplanEstimate {
+            limit,
+            bumps: self.bumps(),
+            width,
+            depth,
+            pad_area,
+            core_area,
+            lane_utilization,
+        }
+    }
+}
+
+/// What determines the area of a module.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum FloorplanLimit {
+    /// The bump map needs more area than the macros.
+    Pad,
+    /// The macros need more area than the bump map.
+    Core,
+}
+
+/// The share of a lane's slice of the module occupied by one kind of macro.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LaneUtilization {
+    /// The role of the macro.
+    pub role: String,
+    /// The area of the macro per lane, in square nanometers.
+    pub area_per_lane: f64,
+    /// The area per lane as a fraction of the area of each lane's slice.
+    pub utilization: f64,
+}
+
+/// The estimated floorplan of a module.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FloorplanEstimate {
+    /// What determines the module area.
+    pub limit: FloorplanLimit,
+    /// The total number of bumps.
+    pub bumps: usize,
+    /// The module width along the die edge, in nanometers.
+    pub width: i64,
+    /// The module depth from the die edge, in nanometers.
+    pub depth: i64,
+    /// The area under the bump map, in square nanometers.
+    pub pad_area: i64,
+    /// The area needed by the macros at the target utilization, in square nanometers.
+    pub core_area: i64,
+    /// The per-lane utilization of each kind of macro.
+    pub lane_utilization: Vec<LaneUtilization>,
+}
+
+impl FloorplanEstimate {
+    /// The module area, in square nanometers.
+    pub fn area(&self) -> i64 {
+        self.width * self.depth
+    }
+
+    /// The fraction of each lane's slice occupied by macros.
+    pub fn total_lane_utilization(&self) -> f64 {
+        self.lane_utilization.iter().map(|u| u.utilization).sum()
+    }
+}
+
+impl SimArtifact for FloorplanEstimate {
+    fn csv_header(&self) -> Vec<String> {
+        ["role", "area_per_lane_nm2", "utilization"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        let mut rows = self
+            .lane_utilization
+            .iter()
+            .map(|u| {
+                vec![
+                    u.role.clone(),
+                    u.area_per_lane.to_string(),
+                    u.utilization.to_string(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        rows.push(vec![
+            "total".to_string(),
+            self.lane_utilization
+                .iter()
+                .map(|u| u.area_per_lane)
+                .sum::<f64>()
+                .to_string(),
+            self.total_lane_utilization().to_string(),
+        ]);
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distinguishes_pad_and_core_limits() {
+        let floorplan = Floorplan::new(PackageType::Standard, 16, 100_000);
+        // 44 signal bumps, of which supplies are a quarter of the total.
+        assert_eq!(floorplan.bumps(), 59);
+        assert_eq!(floorplan.bump_map(), (600_000, 1_000_000));
+
+        let small = [
+            MacroArea::new("tx_driver", 5_000_000_000, 16),
+            MacroArea::new("rx_sampler", 2_000_000_000, 16),
+        ];
+        let estimate = floorplan.estimate(&small);
+        assert_eq!(estimate.limit, FloorplanLimit::Pad);
+        assert_eq!(estimate.depth, 1_000_000);
+        assert!((estimate.lane_utilization[0].utilization - 5e9 / 37.5e9).abs() < 1e-12);
+
+        let large = [MacroArea::new("tx_driver", 50_000_000_000, 16)];
+        let estimate = floorplan.estimate(&large);
+        assert_eq!(estimate.limit, FloorplanLimit::Core);
+        assert!(estimate.area() >= estimate.core_area);
+        assert!((estimate.total_lane_utilization() - 0.7).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,396 @@
+//! IR-drop analysis of strapped power meshes.
+//!
+//! A mesh is modeled as a stack of layers, each containing parallel straps at a
+//! fixed pitch. Adjacent layers run in perpendicular directions and are connected
+//! by a via at every strap crossing. Straps on the top layer are assumed to be
+//! tied to an ideal supply, and load currents are drawn from the bottom layer.
+
+use crate::driver::DriverLayerMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use substrate::geometry::dir::Dir;
+use substrate::geometry::point::Point;
+use substrate::geometry::rect::Rect;
+
+/// Metal and via resistances of a technology's ATOLL layer stack.
+pub trait MetalStack {
+    /// The sheet resistance of ATOLL layer `layer`, in ohms per square.
+    fn sheet_resistance(layer: usize) -> f64;
+    /// The resistance of a single via between ATOLL layers `below` and `below + 1`, in ohms.
+    fn via_resistance(below: usize) -> f64;
+}
+
+/// A layer of parallel straps.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct MeshLayer {
+    /// The direction in which the straps run.
+    pub dir: Dir,
+    /// The distance between adjacent straps, in nanometers.
+    pub pitch: i64,
+    /// The position of the first strap relative to the lower/left edge of the mesh.
+    pub offset: i64,
+    /// The width of each strap, in nanometers.
+    pub width: i64,
+    /// The sheet resistance, in ohms per square.
+    pub sheet_resistance: f64,
+}
+
+impl MeshLayer {
+    /// Creates a mesh layer for ATOLL layer `layer` with straps placed every `period` tracks,
+    /// starting at track `offset`.
+    ///
+    /// Odd ATOLL layers run horizontally and even layers run vertically.
+    pub fn from_tracks<T: MetalStack>(
+        layer: usize,
+        track_pitch: i64,
+        line_width: i64,
+        offset: i64,
+        period: i64,
+    ) -> Self {
+        Self {
+            dir: if layer % 2 == 1 {
+                Dir::Horiz
+            } else {
+                Dir::Vert
+            },
+            pitch: period * track_pitch,
+            offset: offset * track_pitch + track_pitch / 2,
+            width: line_width,
+            sheet_resistance: T::sheet_resistance(layer),
+        }
+    }
+
+    /// The coordinates of the strap centerlines, perpendicular to the strap direction.
+    pub(crate) fn positions(&self, bounds: Rect) -> Vec<i64> {
+        let span = bounds.span(self.dir.other());
+        (0..)
+            .map(|i| span.start() + self.offset + i * self.pitch)
+            .take_while(|&x| x <= span.stop())
+            .collect()
+    }
+}
+
+/// A current drawn from the bottom layer of the mesh.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Load {
+    /// The region over which the current is drawn.
+    pub rect: Rect,
+    /// The current, in amps.
+    pub current: f64,
+}
+
+/// IR-drop analysis parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IrDropParams {
+    /// The extent of the mesh.
+    pub bounds: Rect,
+    /// The mesh layers, from bottom to top.
+    pub layers: Vec<MeshLayer>,
+    /// The via resistance between each pair of adjacent layers, in ohms.
+    pub via_resistance: Vec<f64>,
+    /// The load currents.
+    pub loads: Vec<Load>,
+}
+
+/// The results of an IR-drop analysis.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IrDropReport {
+    /// The largest voltage drop from the supply anywhere on the bottom layer, in volts.
+    pub worst_droop: f64,
+    /// The location of the largest voltage drop.
+    pub worst_location: Point,
+    /// The average voltage drop seen by each load, in the order the loads were given.
+    pub load_droops: Vec<f64>,
+}
+
+type NodeKey = (usize, i64, i64);
+
+/// A resistive network with some nodes tied to the ideal supply.
+#[derive(Default)]
+struct Network {
+    nodes: HashMap<NodeKey, usize>,
+    locations: Vec<Point>,
+    fixed: Vec<bool>,
+    edges: Vec<Vec<(usize, f64)>>,
+}
+
+impl Network {
+    fn node(&mut self, key: NodeKey) -> usize {
+        let n = self.locations.len();
+        *self.nodes.entry(key).or_insert_with(|| {
+            self.locations.push(Point::new(key.1, key.2));
+            self.fixed.push(false);
+            self.edges.push(Vec::new());
+            n
+        })
+    }
+
+    fn connect(&mut self, a: usize, b: usize, r: f64) {
+        let g = 1. / r;
+        self.edges[a].push((b, g));
+        self.edges[b].push((a, g));
+    }
+
+    /// Returns the voltage drop at each node given the current drawn from each node.
+    ///
+    /// Solves the nodal equations with the conjugate gradient method.
+    fn solve(&self, currents: &[f64]) -> Vec<f64> {
+        // A small conductance to the supply at every node keeps isolated nodes solvable.
+        const G_LEAK: f64 = 1e-12;
+        let n = self.locations.len();
+        let apply = |v: &[f64]| -> Vec<f64> {
+            (0..n)
+                .map(|i| {
+                    if self.fixed[i] {
+                        return v[i];
+                    }
+                    let mut out = G_LEAK * v[i];
+                    for &(j, g) in self.edges[i].iter() {
+                        out += g * v[i];
+                        if !self.fixed[j] {
+                            out -= g * v[j];
+                        }
+                    }
+                    out
+                })
+                .collect()
+        };
+        let dot = |a: &[f64], b: &[f64]| a.iter().zip(b).map(|(a, b)| a * b).sum::<f64>();
+
+        let b = (0..n)
+            .map(|i| if self.fixed[i] { 0. } else { currents[i] })
+            .collect::<Vec<_>>();
+        let mut x = vec![0.; n];
+        let mut r = b.clone();
+        let mut p = r.clone();
+        let mut rr = dot(&r, &r);
+        let tol = 1e-24 * dot(&b, &b).max(f64::MIN_POSITIVE);
+        for _ in 0..10 * n.max(1) {
+            if rr <= tol {
+                break;
+            }
+            let ap = apply(&p);
+            let alpha = rr / dot(&p, &ap);
+            for i in 0..n {
+                x[i] += alpha * p[i];
+                r[i] -= alpha * ap[i];
+            }
+            let rr_next = dot(&r, &r);
+            for i in 0..n {
+                p[i] = r[i] + rr_next / rr * p[i];
+            }
+            rr = rr_next;
+        }
+        x
+    }
+}
+
+/// Runs an IR-drop analysis.
+pub fn analyze(params: &IrDropParams) -> IrDropReport {
+    let n_layers = params.layers.len();
+    assert!(n_layers >= 2, "a mesh requires at least two layers");
+    assert_eq!(
+        params.via_resistance.len(),
+        n_layers - 1,
+        "a via resistance must be given for each pair of adjacent layers"
+    );
+    for pair in params.layers.windows(2) {
+        assert_ne!(
+            pair[0].dir, pair[1].dir,
+            "adjacent mesh layers must run in perpendicular directions"
+        );
+    }
+
+    let positions = params
+        .layers
+        .iter()
+        .map(|layer| layer.positions(params.bounds))
+        .collect::<Vec<_>>();
+    // The point at which a strap on `layer` at `pos` crosses a perpendicular strap at `cross`.
+    let point = |layer: usize, pos: i64, cross: i64| match params.layers[layer].dir {
+        Dir::Horiz => (cross, pos),
+        Dir::Vert => (pos, cross),
+    };
+
+    let mut net = Network::default();
+    for (i, layer) in params.layers.iter().enumerate() {
+        for &pos in positions[i].iter() {
+            let mut crossings = Vec::new();
+            if i > 0 {
+                crossings.extend(positions[i - 1].iter().copied());
+            }
+            if i + 1 < n_layers {
+                crossings.extend(positions[i + 1].iter().copied());
+            }
+            crossings.sort();
+            crossings.dedup();
+            let nodes = crossings
+                .iter()
+                .map(|&cross| {
+                    let (x, y) = point(i, pos, cross);
+                    (net.node((i, x, y)), cross)
+                })
+                .collect::<Vec<_>>();
+            for pair in nodes.windows(2) {
+                let len = (pair[1].1 - pair[0].1) as f64;
+                net.connect(
+                    pair[0].0,
+                    pair[1].0,
+                    layer.sheet_resistance * len / layer.width as f64,
+                );
+            }
+            if i + 1 < n_layers {
+                for &cross in positions[i + 1].iter() {
+                    let (x, y) = point(i, pos, cross);
+                    let (a, b) = (net.node((i, x, y)), net.node((i + 1, x, y)));
+                    net.connect(a, b, params.via_resistance[i]);
+                }
+            }
+        }
+    }
+    for (&(layer, _, _), &idx) in net.nodes.iter() {
+        net.fixed[idx] = layer == n_layers - 1;
+    }
+
+    let bottom = net
+        .nodes
+        .iter()
+        .filter(|((layer, _, _), _)| *layer == 0)
+        .map(|(_, &idx)| idx)
+        .collect::<Vec<_>>();
+    assert!(
+        !bottom.is_empty(),
+        "the bottom layer of the mesh has no nodes"
+    );
+
+    // Distribute each load evenly over the bottom-layer nodes it covers,
+    // or assign it to the nearest node if it covers none.
+    let mut currents = vec![0.; net.locations.len()];
+    let mut load_nodes = Vec::with_capacity(params.loads.len());
+    for load in params.loads.iter() {
+        let mut covered = bottom
+            .iter()
+            .copied()
+            .filter(|&idx| covers(load.rect, net.locations[idx]))
+            .collect::<Vec<_>>();
+        if covered.is_empty() {
+            let center = load.rect.center();
+            let nearest = bottom
+                .iter()
+                .copied()
+                .min_by_key(|&idx| {
+                    let p = net.locations[idx];
+                    (p.x - center.x).abs() + (p.y - center.y).abs()
+                })
+                .unwrap();
+            covered.push(nearest);
+        }
+        for &idx in covered.iter() {
+            currents[idx] += load.current / covered.len() as f64;
+        }
+        load_nodes.push(covered);
+    }
+
+    let droop = net.solve(&currents);
+    let worst = bottom
+        .iter()
+        .copied()
+        .max_by(|&a, &b| droop[a].total_cmp(&droop[b]))
+        .unwrap();
+    IrDropReport {
+        worst_droop: droop[worst],
+        worst_location: net.locations[worst],
+        load_droops: load_nodes
+            .iter()
+            .map(|nodes| nodes.iter().map(|&idx| droop[idx]).sum::<f64>() / nodes.len() as f64)
+            .collect(),
+    }
+}
+
+/// Returns `true` if `p` lies within or on the boundary of `rect`.
+fn covers(rect: Rect, p: Point) -> bool {
+    (rect.left()..=rect.right()).contains(&p.x) && (rect.bot()..=rect.top()).contains(&p.y)
+}
+
+/// Builds the supply mesh strapped over a horizontal driver.
+///
+/// `tracks` gives the track pitch and line width of each ATOLL layer, and `periods` gives
+/// the planned strap periods on layer 2 and above (see [`crate::analysis::straps::StrapPlanner`]).
+/// `offset` is the strap track offset of the supply being analyzed (see
+/// [`crate::driver::VDD_STRAP_OFFSET`] and [`crate::driver::VSS_STRAP_OFFSET`]). The mesh spans
+/// [`DriverLayerMap::bank_strap_layers`].
+pub fn horizontal_driver_supply_mesh<T: MetalStack>(
+    tracks: &[(i64, i64)],
+    layers: DriverLayerMap,
+    periods: &[i64],
+    offset: i64,
+) -> (Vec<MeshLayer>, Vec<f64>) {
+    let strap_layers = layers.bank_strap_layers();
+    let vias_start = strap_layers.start;
+    let mesh = strap_layers
+        .zip(periods.iter().copied())
+        .map(|(layer, period)| {
+            let (pitch, width) = tracks[layer];
+            MeshLayer::from_tracks::<T>(layer, pitch, width, offset, period)
+        })
+        .collect::<Vec<_>>();
+    let vias = (vias_start..vias_start + mesh.len().saturating_sub(1))
+        .map(T::via_resistance)
+        .collect();
+    (mesh, vias)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(dir: Dir, pitch: i64) -> MeshLayer {
+        MeshLayer {
+            dir,
+            pitch,
+            offset: 0,
+            width: 100,
+            sheet_resistance: 0.1,
+        }
+    }
+
+    #[test]
+    fn single_crossing_droop_is_via_drop() {
+        let report = analyze(&IrDropParams {
+            bounds: Rect::from_sides(0, 0, 100, 100),
+            layers: vec![layer(Dir::Horiz, 1_000), layer(Dir::Vert, 1_000)],
+            via_resistance: vec![2.],
+            loads: vec![Load {
+                rect: Rect::from_sides(0, 0, 100, 100),
+                current: 1e-3,
+            }],
+        });
+        assert!((report.worst_droop - 2e-3).abs() < 1e-9);
+        assert!((report.load_droops[0] - 2e-3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn droop_is_worst_at_load() {
+        let report = analyze(&IrDropParams {
+            bounds: Rect::from_sides(0, 0, 10_000, 10_000),
+            layers: vec![
+                layer(Dir::Horiz, 20_000),
+                layer(Dir::Vert, 1_000),
+                MeshLayer {
+                    offset: 10_000,
+                    ..layer(Dir::Horiz, 20_000)
+                },
+            ],
+            via_resistance: vec![1., 1.],
+            loads: vec![Load {
+                rect: Rect::from_sides(0, 0, 0, 0),
+                current: 1e-3,
+            }],
+        });
+        assert_eq!(report.worst_location, Point::new(0, 0));
+        // Current spreads along the bottom strap, so the droop is below that
+        // of the single 12 ohm path directly above the load.
+        assert!(report.worst_droop > 0.);
+        assert!(report.worst_droop < 12e-3);
+    }
+}
@@ -0,0 +1,357 @@
+//! Decomposition of transmit jitter from a pattern-driven transient.
+//!
+//! The pad waveform is driven by a pattern repeated several times. Each edge is
+//! compared against the ideal UI grid to get its time interval error (TIE), and the
+//! TIEs of the same edge of the pattern are averaged across repeats. The averaged TIEs
+//! separate into duty cycle distortion (DCD), the mean offset between rising and
+//! falling edges, and data-dependent jitter (DDJ), the remaining peak-to-peak spread.
+//! What is left of each edge after removing its average is random jitter (RJ), whose
+//! standard deviation is found by fitting a Gaussian to the tails of its distribution.
+//! All jitter is expressed in unit intervals (UI).
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::report::{ArtifactMetadata, ArtifactPaths, SimArtifact};
+use crate::spec::{ComplianceReport, Limit, Spec};
+use crate::tb::pi::Samples;
+
+/// The fraction of the RJ distribution in each tail used for the Gaussian fit.
+const TAIL_FRACTION: f64 = 0.16;
+
+/// The UCIe transmit jitter budget, in UI.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct TxJitterBudget {
+    /// The maximum peak-to-peak data-dependent jitter.
+    pub max_ddj: f64,
+    /// The maximum magnitude of the duty cycle distortion.
+    pub max_dcd: f64,
+    /// The maximum RMS random jitter.
+    pub max_rj: f64,
+    /// The maximum total jitter at [`TxJitterBudget::ber`].
+    pub max_tj: f64,
+    /// The bit error ratio at which total jitter is evaluated.
+    pub ber: f64,
+}
+
+/// The average timing of one edge of the pattern across repeats.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PatternEdge {
+    /// The index of the bit the edge starts.
+    pub position: usize,
+    /// Whether the edge is rising.
+    pub rising: bool,
+    /// The average time interval error of the edge, in UI.
+    pub mean_tie: f64,
+    /// The number of repeats in which the edge was found.
+    pub count: usize,
+}
+
+/// The jitter of a pattern-driven waveform, decomposed into its components.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JitterDecomposition {
+    /// The average timing of each edge of the pattern, in pattern order.
+    ///
+    /// Time interval errors are relative to the average delay of all edges.
+    pub edges: Vec<PatternEdge>,
+    /// The peak-to-peak data-dependent jitter, excluding DCD, in UI.
+    pub ddj: f64,
+    /// The mean delay of rising edges relative to falling edges, in UI.
+    pub dcd: f64,
+    /// The standard deviation of the random jitter, in UI.
+    pub rj: f64,
+}
+
+impl JitterDecomposition {
+    /// Decomposes the jitter of `wave`, which is driven by `pattern` repeated back to back
+    /// starting at `t0`, with a bit period of `ui` seconds.
+    ///
+    /// Edges are detected where `wave` crosses `threshold`. Only repeats that lie entirely
+    /// within the simulated waveform are used; the first bit of the first repeat is
+    /// skipped since the level before it is unknown.
+    pub fn measure(wave: Samples<'_>, pattern: &[bool], t0: f64, ui: f64, threshold: f64) -> Self {
+        let crossings = wave.crossings(threshold);
+        let n = pattern.len();
+        let end = wave.t.last().copied().unwrap_or(t0);
+        let repeats = if n == 0 {
+            0
+        } else {
+            ((end - t0) / (ui * n as f64)).floor().max(0.) as usize
+        };
+
+        // The TIE of each edge found, keyed by its position in the pattern.
+        let mut ties = vec![Vec::new(); n];
+        for bit in 1..repeats * n {
+            let position = bit % n;
+            let rising = pattern[position];
+            if rising == pattern[(bit - 1) % n] {
+                continue;
+            }
+            let ideal = t0 + bit as f64 * ui;
+            let nearest = crossings
+                .iter()
+                .filter(|(t, r)| *r == rising && (t - ideal).abs() < ui / 2.)
+                .map(|(t, _)| (t - ideal) / ui)
+                .min_by(|a, b| a.abs().total_cmp(&b.abs()));
+            if let Some(tie) = nearest {
+                ties[position].push(tie);
+            }
+        }
+        Self::from_ties(pattern, &ties)
+    }
+
+    /// Decomposes jitter given the TIEs, in UI, of each edge of `pattern` across repeats.
+    fn from_ties(pattern: &[bool], ties: &[Vec<f64>]) -> Self {
+        let latency = mean(ties.iter().flatten().copied());
+        let mut edges = ties
+            .iter()
+            .enumerate()
+            .filter(|(_, ties)| !ties.is_empty())
+            .map(|(position, ties)| PatternEdge {
+                position,
+                rising: pattern[position],
+                mean_tie: mean(ties.iter().copied()) - latency,
+                count: ties.len(),
+            })
+            .collect::<Vec<_>>();
+
+        let edge_mean = |rising: bool| {
+            mean(
+                edges
+                    .iter()
+                    .filter(|e| e.rising == rising)
+                    .map(|e| e.mean_tie),
+            )
+        };
+        let dcd = match (edge_mean(true), edge_mean(false)) {
+            (rise, fall) if rise.is_nan() || fall.is_nan() => 0.,
+            (rise, fall) => rise - fall,
+        };
+        let ddj = spread(edges.iter().map(|e| {
+            if e.rising {
+                e.mean_tie - dcd / 2.
+            } else {
+                e.mean_tie + dcd / 2.
+            }
+        }));
+
+        let mut residuals = ties
+            .iter()
+            .filter(|ties| !ties.is_empty())
+            .flat_map(|ties| {
+                let avg = mean(ties.iter().copied());
+                ties.iter().map(move |tie| tie - avg)
+            })
+            .collect::<Vec<_>>();
+        let rj = tail_fit_sigma(&mut residuals);
+
+        edges.sort_by_key(|e| e.position);
+        Self {
+            edges,
+            ddj,
+            dcd,
+            rj,
+        }
+    }
+
+    /// The peak-to-peak deterministic jitter, including DCD, in UI.
+    pub fn dj(&self) -> f64 {
+        spread(self.edges.iter().map(|e| e.mean_tie))
+    }
+
+    /// The total jitter at bit error ratio `ber`, in UI.
+    ///
+    /// Uses the dual-Dirac model: the deterministic jitter plus the random jitter
+    /// extended to the Q-factor of `ber` on both sides.
+    pub fn tj(&self, ber: f64) -> f64 {
+        self.dj() - 2. * normal_quantile(ber) * self.rj
+    }
+
+    /// Checks each jitter component against `budget`.
+    pub fn compliance(&self, budget: &TxJitterBudget) -> ComplianceReport {
+        ComplianceReport::evaluate(
+            &[
+                Spec::new("tx_ddj", Limit::Max(budget.max_ddj), "UI"),
+                Spec::new("tx_dcd", Limit::Max(budget.max_dcd), "UI"),
+                Spec::new("tx_rj", Limit::Max(budget.max_rj), "UI"),
+                Spec::new("tx_tj", Limit::Max(budget.max_tj), "UI"),
+            ],
+            [
+                ("tx_ddj", self.ddj),
+                ("tx_dcd", self.dcd.abs()),
+                ("tx_rj", self.rj),
+                ("tx_tj", self.tj(budget.ber)),
+            ],
+        )
+    }
+
+    /// Writes the per-edge timing and its compliance report against `budget`
+    /// to `dir`, with the report named `<name>_compliance`.
+    pub fn write_report(
+        &self,
+        dir: impl AsRef<Path>,
+        metadata: &ArtifactMetadata,
+        budget: &TxJitterBudget,
+    ) -> std::io::Result<(ArtifactPaths, ArtifactPaths)> {
+        let dir = dir.as_ref();
+        let edges = self.write_artifact(dir, metadata)?;
+        let compliance = self.compliance(budget).write_artifact(
+            dir,
+            &ArtifactMetadata {
+                name: format!("{}_compliance", metadata.name),
+                ..metadata.clone()
+            },
+        )?;
+        Ok((edges, compliance))
+    }
+}
+
+impl SimArtifact for JitterDecomposition {
+    fn csv_header(&self) -> Vec<String> {
+        ["position", "rising", "mean_tie_ui", "count"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.edges
+            .iter()
+            .map(|e| {
+                vec![
+                    e.position.to_string(),
+                    e.rising.to_string(),
+                    e.mean_tie.to_string(),
+                    e.count.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// The mean of `values`, or NaN if there are none.
+fn mean(values: impl IntoIterator<Item = f64>) -> f64 {
+    let (sum, n) = values
+        .into_iter()
+        .fold((0., 0usize), |(sum, n), x| (sum + x, n + 1));
+    sum / n as f64
+}
+
+/// The difference between the largest and smallest of `values`, or zero if there are none.
+fn spread(values: impl IntoIterator<Item = f64>) -> f64 {
+    let (min, max) = values
+        .into_iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+            (min.min(x), max.max(x))
+        });
+    if min > max {
+        0.
+    } else {
+        max - min
+    }
+}
+
+/// Estimates the standard deviation of zero-mean `samples` from their tails.
+///
+/// The sorted samples are regressed against the standard normal quantiles of their
+/// plotting positions, using only the outer [`TAIL_FRACTION`] on each side, so that
+/// bounded jitter near the center of the distribution does not inflate the estimate.
+/// Falls back to the RMS when there are too few samples to populate the tails.
+fn tail_fit_sigma(samples: &mut [f64]) -> f64 {
+    if samples.is_empty() {
+        return 0.;
+    }
+    samples.sort_by(f64::total_cmp);
+    let n = samples.len() as f64;
+    let (zr, zz) = samples
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| (normal_quantile((i as f64 + 0.5) / n), x))
+        .filter(|(z, _)| z.abs() >= -normal_quantile(TAIL_FRACTION))
+        .fold((0., 0.), |(zr, zz), (z, x)| (zr + z * x, zz + z * z));
+    if zz > 0. {
+        zr / zz
+    } else {
+        mean(samples.iter().map(|x| x * x)).sqrt()
+    }
+}
+
+/// The quantile of the standard normal distribution at probability `p`.
+///
+/// Uses the rational approximation of Abramowitz and Stegun 26.2.23, which is accurate
+/// to within 4.5e-4.
+fn normal_quantile(p: f64) -> f64 {
+    if p > 0.5 {
+        return -normal_quantile(1. - p);
+    }
+    let t = (-2. * p.ln()).sqrt();
+    let num = 2.515517 + 0.802853 * t + 0.010328 * t * t;
+    let den = 1. + 1.432788 * t + 0.189269 * t * t + 0.001308 * t * t * t;
+    num / den - t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tb::ssc::prbs7;
+
+    #[test]
+    fn separates_jitter_components() {
+        let ui = 1e-10;
+        let (dcd, ddj, sigma) = (0.04, 0.06, 0.01);
+        let pattern = prbs7(127);
+        let repeats = 40;
+
+        // A deterministic sequence of standard normal samples (Box-Muller over an LCG).
+        let mut state = 12345u64;
+        let mut uniform = || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+        };
+        let mut gaussian =
+            || (-2. * uniform().ln()).sqrt() * (2. * std::f64::consts::PI * uniform()).cos();
+
+        // Edges after a run of two or more bits arrive `ddj` UI late.
+        let (mut t, mut v) = (vec![0.], vec![if pattern[0] { 1. } else { 0. }]);
+        let rise = 0.05 * ui;
+        for bit in 1..repeats * pattern.len() {
+            let (cur, prev) = (pattern[bit % 127], pattern[(bit - 1) % 127]);
+            if cur == prev {
+                continue;
+            }
+            let long_run = pattern[(bit + 125) % 127] == prev;
+            let offset = if cur { dcd / 2. } else { -dcd / 2. }
+                + if long_run { ddj } else { 0. }
+                + sigma * gaussian();
+            let edge = (bit as f64 + offset) * ui;
+            let (from, to) = if cur { (0., 1.) } else { (1., 0.) };
+            t.extend([edge - rise, edge + rise]);
+            v.extend([from, to]);
+        }
+        t.push((repeats * pattern.len()) as f64 * ui);
+        v.push(*v.last().unwrap());
+
+        let jitter = JitterDecomposition::measure(Samples { t: &t, v: &v }, &pattern, 0., ui, 0.5);
+        assert!((jitter.dcd - dcd).abs() < 0.005, "dcd = {}", jitter.dcd);
+        assert!((jitter.ddj - ddj).abs() < 0.01, "ddj = {}", jitter.ddj);
+        assert!((jitter.rj - sigma).abs() < 0.002, "rj = {}", jitter.rj);
+        assert_eq!(jitter.edges.len(), jitter.csv_rows().len());
+
+        let budget = TxJitterBudget {
+            max_ddj: 0.1,
+            max_dcd: 0.05,
+            max_rj: 0.02,
+            max_tj: 0.4,
+            ber: 1e-15,
+        };
+        assert!(jitter.compliance(&budget).passed());
+        assert!(!jitter
+            .compliance(&TxJitterBudget {
+                max_dcd: 0.02,
+                ..budget
+            })
+            .passed());
+    }
+}
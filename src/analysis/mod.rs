@@ -0,0 +1,10 @@
+//! Post-layout analyses of generated macros.
+
+pub mod dout_straps;
+pub mod em_check;
+pub mod equalization;
+pub mod floorplan;
+pub mod ir_drop;
+pub mod jitter;
+pub mod straps;
+pub mod tap_density;
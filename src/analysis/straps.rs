@@ -0,0 +1,402 @@
+//! Supply strap planning from a current budget.
+//!
+//! Rather than hand-tuning strap periods, [`StrapPlanner`] picks the sparsest strap
+//! period on each layer that keeps the layer within its electromigration limits and
+//! its share of an IR-drop budget. Sparser straps leave more tracks free for signal
+//! routing.
+//!
+//! Each layer is checked independently. The straps of a layer are assumed to share
+//! the budgeted current equally, with the load distributed uniformly along each strap
+//! and both ends of each strap tied to the supply. The worst-case drop along a strap
+//! is then one eighth of its current times its end-to-end resistance.
+
+use std::ops::Range;
+
+use atoll::straps::LayerStrappingParams;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use substrate::geometry::dir::Dir;
+use substrate::geometry::rect::Rect;
+
+use crate::analysis::em_check::{Current, EmRules};
+use crate::analysis::ir_drop::MetalStack;
+
+/// The current and IR drop a supply mesh must support.
+///
+/// Values are stored as decimals so that the budget can be part of hashable
+/// generator parameters.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct StrapBudget {
+    /// The RMS current drawn through the mesh, in amps.
+    pub rms_current: Decimal,
+    /// The peak current drawn through the mesh, in amps.
+    pub peak_current: Decimal,
+    /// The maximum IR drop across the whole mesh, in volts.
+    pub max_ir_drop: Decimal,
+}
+
+impl StrapBudget {
+    /// Returns an error if no strap plan can meet this budget.
+    ///
+    /// The currents must not be negative, and the IR-drop budget must be positive.
+    pub fn check(&self) -> Result<(), StrapPlanError> {
+        for (field, value) in [
+            ("rms_current", self.rms_current),
+            ("peak_current", self.peak_current),
+        ] {
+            if value < Decimal::ZERO {
+                return Err(StrapPlanError::InvalidBudget { field, value });
+            }
+        }
+        if self.max_ir_drop <= Decimal::ZERO {
+            return Err(StrapPlanError::InvalidBudget {
+                field: "max_ir_drop",
+                value: self.max_ir_drop,
+            });
+        }
+        Ok(())
+    }
+
+    /// The budgeted current.
+    pub fn current(&self) -> Result<Current, StrapPlanError> {
+        Ok(Current {
+            rms: to_f64("rms_current", self.rms_current)?,
+            peak: to_f64("peak_current", self.peak_current)?,
+        })
+    }
+
+    /// Divides the current evenly among `n` parallel meshes with the same IR-drop budget.
+    pub fn split(&self, n: usize) -> Self {
+        let n = Decimal::from(n.max(1));
+        Self {
+            rms_current: self.rms_current / n,
+            peak_current: self.peak_current / n,
+            max_ir_drop: self.max_ir_drop,
+        }
+    }
+}
+
+/// Converts the budget field `field` to a finite float.
+fn to_f64(field: &'static str, value: Decimal) -> Result<f64, StrapPlanError> {
+    value
+        .to_f64()
+        .filter(|v| v.is_finite())
+        .ok_or(StrapPlanError::InvalidBudget { field, value })
+}
+
+/// An error produced when no strap period satisfies the budget.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum StrapPlanError {
+    /// A budget field is out of range.
+    #[error("invalid strap budget: {field} cannot be {value}")]
+    InvalidBudget {
+        /// The name of the field.
+        field: &'static str,
+        /// The value of the field.
+        value: Decimal,
+    },
+    /// Straps on a layer cannot carry the budgeted current even at the minimum period.
+    #[error(
+        "layer {layer} cannot meet the budget with straps every {min_period} tracks \
+         (needs {required_straps} straps, fits {available_straps})"
+    )]
+    InsufficientMetal {
+        /// The ATOLL layer.
+        layer: usize,
+        /// The minimum strap period, in tracks.
+        min_period: i64,
+        /// The number of straps needed to meet the budget.
+        required_straps: usize,
+        /// The number of straps that fit at the minimum period.
+        available_straps: usize,
+    },
+}
+
+/// Plans supply strap periods over a rectangular region.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StrapPlanner {
+    /// The track pitch and line width of each ATOLL layer, in nanometers.
+    pub tracks: Vec<(i64, i64)>,
+    /// The region covered by the straps.
+    pub bounds: Rect,
+    /// The minimum strap period, in tracks.
+    ///
+    /// Must be at least 2 so that VDD and VSS straps at adjacent offsets do not collide.
+    pub min_period: i64,
+}
+
+impl StrapPlanner {
+    /// Creates a new [`StrapPlanner`] with a minimum period of 3 tracks.
+    pub fn new(tracks: Vec<(i64, i64)>, bounds: Rect) -> Self {
+        Self {
+            tracks,
+            bounds,
+            min_period: 3,
+        }
+    }
+
+    /// The direction of the straps on ATOLL layer `layer`.
+    ///
+    /// Matches [`MeshLayer::from_tracks`](crate::analysis::ir_drop::MeshLayer::from_tracks).
+    fn dir(layer: usize) -> Dir {
+        if layer % 2 == 1 {
+            Dir::Horiz
+        } else {
+            Dir::Vert
+        }
+    }
+
+    /// The number of straps on `layer` that fit within the bounds at `period`.
+    fn straps(&self, layer: usize, period: i64) -> usize {
+        let (pitch, _) = self.tracks[layer];
+        let span = self.bounds.span(Self::dir(layer).other()).length();
+        (span / (period * pitch)).max(1) as usize
+    }
+
+    /// The number of straps needed on `layer` to meet the EM limits and an IR-drop
+    /// allowance of `max_drop` volts.
+    fn required_straps<T: MetalStack + EmRules>(
+        &self,
+        layer: usize,
+        current: Current,
+        max_drop: f64,
+    ) -> usize {
+        let (_, width) = self.tracks[layer];
+        let length = self.bounds.span(Self::dir(layer)).length();
+        // Each strap carries `current / n`, so the limits give a lower bound on `n`.
+        let limit = T::wire_limit(layer);
+        let width_um = width as f64 / 1_000.;
+        let em = (current.rms / (limit.rms * width_um)).max(current.peak / (limit.peak * width_um));
+        let resistance = T::sheet_resistance(layer) * length as f64 / width as f64;
+        let ir = current.rms * resistance / (8. * max_drop);
+        em.max(ir).ceil().max(1.) as usize
+    }
+
+    /// Returns the largest strap period on each layer in `layers` that meets `budget`.
+    ///
+    /// The IR-drop budget is divided equally among the layers.
+    pub fn plan<T: MetalStack + EmRules>(
+        &self,
+        layers: Range<usize>,
+        budget: &StrapBudget,
+    ) -> Result<Vec<i64>, StrapPlanError> {
+        budget.check()?;
+        let current = budget.current()?;
+        let max_drop = to_f64("max_ir_drop", budget.max_ir_drop)? / layers.len().max(1) as f64;
+        layers
+            .map(|layer| {
+                let required = self.required_straps::<T>(layer, current, max_drop);
+                let (pitch, _) = self.tracks[layer];
+                let span = self.bounds.span(Self::dir(layer).other()).length();
+                let max_period = (span / pitch).max(self.min_period);
+                (self.min_period..=max_period)
+                    .rev()
+                    .find(|&period| self.straps(layer, period) >= required)
+                    .ok_or(StrapPlanError::InsufficientMetal {
+                        layer,
+                        min_period: self.min_period,
+                        required_straps: required,
+                        available_straps: self.straps(layer, self.min_period),
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Converts strap periods into ATOLL strapping parameters with a common track offset.
+///
+/// The offset is reduced modulo each period, so it may exceed the shortest period.
+pub fn offset_period_straps(periods: &[i64], offset: i64) -> Vec<LayerStrappingParams> {
+    periods
+        .iter()
+        .map(|&period| LayerStrappingParams::OffsetPeriod {
+            offset: offset.rem_euclid(period),
+            period,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::analysis::em_check::EmLimit;
+
+    struct Stack;
+
+    impl MetalStack for Stack {
+        fn sheet_resistance(_layer: usize) -> f64 {
+            0.1
+        }
+
+        fn via_resistance(_below: usize) -> f64 {
+            1.
+        }
+    }
+
+    impl EmRules for Stack {
+        fn wire_limit(_layer: usize) -> EmLimit {
+            EmLimit {
+                rms: 1e-3,
+                peak: 10e-3,
+            }
+        }
+
+        fn via_limit(_below: usize) -> EmLimit {
+            EmLimit {
+                rms: 1e-3,
+                peak: 10e-3,
+            }
+        }
+    }
+
+    #[test]
+    fn larger_currents_need_denser_straps() {
+        // 100 tracks of 200 nm pitch and 100 nm width in each direction.
+        let planner =
+            StrapPlanner::new(vec![(200, 100); 4], Rect::from_sides(0, 0, 20_000, 20_000));
+        let budget = |rms_current| StrapBudget {
+            rms_current,
+            peak_current: rms_current,
+            max_ir_drop: dec!(0.01),
+        };
+
+        let light = planner.plan::<Stack>(2..4, &budget(dec!(1e-3))).unwrap();
+        let heavy = planner.plan::<Stack>(2..4, &budget(dec!(2e-3))).unwrap();
+        assert!(heavy.iter().zip(&light).all(|(h, l)| h < l));
+        assert!(matches!(
+            planner.plan::<Stack>(2..4, &budget(dec!(1))),
+            Err(StrapPlanError::InsufficientMetal { layer: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn small_currents_use_the_sparsest_straps() {
+        let planner =
+            StrapPlanner::new(vec![(200, 100); 4], Rect::from_sides(0, 0, 20_000, 20_000));
+        let budget = StrapBudget {
+            rms_current: dec!(0),
+            peak_current: dec!(0),
+            max_ir_drop: dec!(0.01),
+        };
+        // A single strap suffices, so each layer gets one period spanning all 100 tracks.
+        assert_eq!(planner.plan::<Stack>(1..4, &budget).unwrap(), vec![100; 3]);
+    }
+
+    #[test]
+    fn periods_respect_the_minimum() {
+        let mut planner =
+            StrapPlanner::new(vec![(200, 100); 4], Rect::from_sides(0, 0, 20_000, 20_000));
+        planner.min_period = 40;
+        let budget = StrapBudget {
+            rms_current: dec!(1e-3),
+            peak_current: dec!(1e-3),
+            max_ir_drop: dec!(0.01),
+        };
+        assert!(matches!(
+            planner.plan::<Stack>(2..4, &budget),
+            Err(StrapPlanError::InsufficientMetal {
+                min_period: 40,
+                available_straps: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn longer_straps_need_denser_straps() {
+        let budget = StrapBudget {
+            rms_current: dec!(1e-4),
+            peak_current: dec!(1e-4),
+            max_ir_drop: dec!(0.0001),
+        };
+        // Layer 2 runs vertically, so a taller region lengthens its straps.
+        let short = StrapPlanner::new(vec![(200, 100); 3], Rect::from_sides(0, 0, 20_000, 20_000))
+            .plan::<Stack>(2..3, &budget)
+            .unwrap();
+        let long = StrapPlanner::new(vec![(200, 100); 3], Rect::from_sides(0, 0, 20_000, 80_000))
+            .plan::<Stack>(2..3, &budget)
+            .unwrap();
+        assert!(long[0] < short[0]);
+    }
+
+    #[test]
+    fn rejects_invalid_budgets() {
+        let planner =
+            StrapPlanner::new(vec![(200, 100); 4], Rect::from_sides(0, 0, 20_000, 20_000));
+        let valid = StrapBudget {
+            rms_current: dec!(1e-3),
+            peak_current: dec!(1e-3),
+            max_ir_drop: dec!(0.01),
+        };
+        assert_eq!(valid.check(), Ok(()));
+
+        for budget in [
+            StrapBudget {
+                max_ir_drop: dec!(0),
+                ..valid
+            },
+            StrapBudget {
+                max_ir_drop: dec!(-0.01),
+                ..valid
+            },
+        ] {
+            assert!(matches!(
+                planner.plan::<Stack>(2..4, &budget),
+                Err(StrapPlanError::InvalidBudget {
+                    field: "max_ir_drop",
+                    ..
+                })
+            ));
+        }
+        assert!(matches!(
+            StrapBudget {
+                rms_current: dec!(-1e-3),
+                ..valid
+            }
+            .check(),
+            Err(StrapPlanError::InvalidBudget {
+                field: "rms_current",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn split_divides_current_but_not_ir_drop() {
+        let budget = StrapBudget {
+            rms_current: dec!(4e-3),
+            peak_current: dec!(8e-3),
+            max_ir_drop: dec!(0.01),
+        };
+        assert_eq!(
+            budget.split(4),
+            StrapBudget {
+                rms_current: dec!(1e-3),
+                peak_current: dec!(2e-3),
+                max_ir_drop: dec!(0.01),
+            }
+        );
+        assert_eq!(budget.split(0), budget);
+    }
+
+    #[test]
+    fn offsets_wrap_within_each_period() {
+        let straps = offset_period_straps(&[3, 8], 5);
+        assert!(matches!(
+            straps[..],
+            [
+                LayerStrappingParams::OffsetPeriod {
+                    offset: 2,
+                    period: 3
+                },
+                LayerStrappingParams::OffsetPeriod {
+                    offset: 5,
+                    period: 8
+                },
+            ]
+        ));
+    }
+}
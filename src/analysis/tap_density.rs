@@ -0,0 +1,153 @@
+//! Latch-up tap density checks.
+//!
+//! Every point of a diffusion region must lie within a technology-specific distance of
+//! a well or substrate tap. Distances are measured along either axis independently, so
+//! a tap covers the square region obtained by expanding it by the maximum distance.
+
+use crate::driver::HorizontalDriverUnitLayoutData;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use substrate::geometry::rect::Rect;
+
+/// Tap density rules of a technology.
+pub trait TapRules {
+    /// The maximum distance from any point of a diffusion region to the nearest tap, in nanometers.
+    const MAX_DIFF_TAP_DISTANCE: i64;
+}
+
+/// A region of diffusion that is too far from every tap.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TapViolation {
+    /// The diffusion region being checked.
+    pub diff: Rect,
+    /// The part of `diff` not covered by any tap.
+    pub uncovered: Rect,
+}
+
+impl Display for TapViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "diffusion at ({}, {}) to ({}, {}) is uncovered between ({}, {}) and ({}, {})",
+            self.diff.left(),
+            self.diff.bot(),
+            self.diff.right(),
+            self.diff.top(),
+            self.uncovered.left(),
+            self.uncovered.bot(),
+            self.uncovered.right(),
+            self.uncovered.top(),
+        )
+    }
+}
+
+/// The results of a tap density check.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TapReport {
+    /// The uncovered diffusion regions.
+    pub violations: Vec<TapViolation>,
+}
+
+impl TapReport {
+    /// Returns `true` if all diffusion is covered by taps.
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Returns the parts of `rect` that do not overlap `hole`.
+fn subtract(rect: Rect, hole: Rect) -> Vec<Rect> {
+    let l = hole.left().max(rect.left());
+    let r = hole.right().min(rect.right());
+    let b = hole.bot().max(rect.bot());
+    let t = hole.top().min(rect.top());
+    if l >= r || b >= t {
+        return vec![rect];
+    }
+    [
+        Rect::from_sides(rect.left(), rect.bot(), l, rect.top()),
+        Rect::from_sides(r, rect.bot(), rect.right(), rect.top()),
+        Rect::from_sides(l, rect.bot(), r, b),
+        Rect::from_sides(l, t, r, rect.top()),
+    ]
+    .into_iter()
+    .filter(|piece| piece.left() < piece.right() && piece.bot() < piece.top())
+    .collect()
+}
+
+/// Returns the parts of `diff` farther than `max_distance` from every tap in `taps`.
+pub fn uncovered(diff: Rect, taps: &[Rect], max_distance: i64) -> Vec<Rect> {
+    taps.iter().fold(vec![diff], |pieces, tap| {
+        let covered = tap.expand_all(max_distance);
+        pieces
+            .into_iter()
+            .flat_map(|piece| subtract(piece, covered))
+            .collect()
+    })
+}
+
+/// Returns `true` if some part of `diff` is farther than `max_distance` from every tap.
+pub fn needs_tap(diff: Rect, taps: &[Rect], max_distance: i64) -> bool {
+    !uncovered(diff, taps, max_distance).is_empty()
+}
+
+/// Checks that every diffusion region in `diffs` is covered by `taps`.
+pub fn check_tap_density(diffs: &[Rect], taps: &[Rect], max_distance: i64) -> TapReport {
+    TapReport {
+        violations: diffs
+            .iter()
+            .flat_map(|&diff| {
+                uncovered(diff, taps, max_distance)
+                    .into_iter()
+                    .map(move |uncovered| TapViolation { diff, uncovered })
+            })
+            .collect(),
+    }
+}
+
+/// Checks the driver transistors of a horizontal driver unit against its driver taps.
+///
+/// Transistor bounding boxes are used in place of their diffusion, so the check is conservative.
+pub fn check_horizontal_driver_unit<T: TapRules>(
+    data: &HorizontalDriverUnitLayoutData,
+) -> TapReport {
+    let mut report = check_tap_density(
+        &[data.driver_pu_bbox],
+        &data.driver_ntap_bboxes,
+        T::MAX_DIFF_TAP_DISTANCE,
+    );
+    report.violations.extend(
+        check_tap_density(
+            &[data.driver_pd_bbox],
+            &data.driver_ptap_bboxes,
+            T::MAX_DIFF_TAP_DISTANCE,
+        )
+        .violations,
+    );
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uncovered_diffusion_is_reported() {
+        let diff = Rect::from_sides(0, 0, 10_000, 1_000);
+        let left_tap = Rect::from_sides(-500, 0, 0, 1_000);
+        let right_tap = Rect::from_sides(10_000, 0, 10_500, 1_000);
+
+        assert_eq!(
+            uncovered(diff, &[left_tap], 4_000),
+            vec![Rect::from_sides(4_000, 0, 10_000, 1_000)]
+        );
+        assert!(check_tap_density(&[diff], &[left_tap, right_tap], 5_000).passed());
+        assert_eq!(
+            check_tap_density(&[diff], &[left_tap, right_tap], 4_000).violations,
+            vec![TapViolation {
+                diff,
+                uncovered: Rect::from_sides(4_000, 0, 6_000, 1_000),
+            }]
+        );
+    }
+}
@@ -0,0 +1,569 @@
+//! Current-steering DAC generators.
+
+use crate::buffer::InverterImpl;
+use crate::code::ThermometerCode;
+use crate::params::{check_at_most, check_positive, setters, ParamsError};
+use crate::tiles::{MosKind, MosLengthRules, MosTileParams, TapTileParams, TileKind};
+use atoll::{IoBuilder, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::error::Result;
+use substrate::geometry::align::AlignMode;
+use substrate::io::{Array, InOut, Input, Io, MosIoSchematic, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+/// The interface to an [`IdacUnit`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct IdacUnitIo {
+    /// The mirror gate.
+    pub bias: Input<Signal>,
+    /// The cascode gate.
+    pub vcas: Input<Signal>,
+    /// The switch gate.
+    pub en: Input<Signal>,
+    /// The output current.
+    pub out: InOut<Signal>,
+    /// The rail to which the mirror device is connected.
+    pub rail: InOut<Signal>,
+}
+
+/// The interface to an [`Idac`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct IdacIo {
+    /// The reference current, which flows into a diode-connected unit cell.
+    pub iref: InOut<Signal>,
+    /// The cascode bias voltage.
+    pub vcas: Input<Signal>,
+    /// The binary-weighted LSB controls, least significant bit first.
+    pub binary: Array<Input<Signal>>,
+    /// The thermometer-coded MSB controls.
+    ///
+    /// Each line enables `2^binary_bits` unit cells. Lines should be enabled in order.
+    pub thermometer: Array<Input<Signal>>,
+    /// The output current.
+    pub iout: InOut<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// An error produced when converting a code to [`Idac`] controls.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum IdacCodeError {
+    /// The code does not fit in the bits of the DAC.
+    #[error("code {code} does not fit in {bits} bits")]
+    OutOfRange {
+        /// The requested code.
+        code: usize,
+        /// The number of bits of the DAC.
+        bits: usize,
+    },
+}
+
+/// The parameters of the [`Idac`] generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct IdacParams {
+    /// Whether the DAC sinks current with NMOS devices or sources it with PMOS devices.
+    ///
+    /// Controls are active high for [`TileKind::N`] and active low for [`TileKind::P`].
+    pub kind: TileKind,
+    /// The number of binary-weighted LSBs.
+    pub binary_bits: usize,
+    /// The number of thermometer-coded MSBs.
+    pub thermometer_bits: usize,
+    /// The width of each unit mirror device.
+    pub mirror_w: i64,
+    /// The channel length of each unit mirror device,
+    /// or the technology minimum if `None`.
+    #[serde(default)]
+    pub mirror_l: Option<i64>,
+    /// The width of each unit cascode device.
+    pub cascode_w: i64,
+    /// The width of each unit switch.
+    pub switch_w: i64,
+}
+
+impl IdacParams {
+    /// The largest number of thermometer-coded MSBs accepted by the builder.
+    pub const MAX_THERMOMETER_BITS: usize = 8;
+    /// The largest total number of bits accepted by the builder.
+    pub const MAX_BITS: usize = 16;
+
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> IdacParamsBuilder {
+        IdacParamsBuilder::default()
+    }
+
+    /// Returns an error if the MOS tiles of technology `T` do not support the mirror
+    /// channel length.
+    pub fn check_lengths<T: MosLengthRules>(&self) -> std::result::Result<(), ParamsError> {
+        T::check_length("mirror_l", self.mirror_l)
+    }
+
+    /// The total number of bits.
+    pub fn bits(&self) -> usize {
+        self.binary_bits + self.thermometer_bits
+    }
+
+    /// The number of thermometer control lines.
+    pub fn thermometer_lines(&self) -> usize {
+        (1 << self.thermometer_bits) - 1
+    }
+
+    /// The number of unit cells switched by each control, binary controls first,
+    /// excluding the reference cell.
+    pub fn units_per_control(&self) -> Vec<usize> {
+        (0..self.binary_bits)
+            .map(|bit| 1 << bit)
+            .chain(std::iter::repeat(1 << self.binary_bits).take(self.thermometer_lines()))
+            .collect()
+    }
+
+    /// The binary control levels for `code`, where `true` enables the corresponding
+    /// cells, and the thermometer code for its MSBs.
+    ///
+    /// Returns an error if `code` does not fit in [`IdacParams::bits`] bits.
+    pub fn controls(
+        &self,
+        code: usize,
+    ) -> std::result::Result<(Vec<bool>, ThermometerCode), IdacCodeError> {
+        let out_of_range = IdacCodeError::OutOfRange {
+            code,
+            bits: self.bits(),
+        };
+        if code >> self.bits() != 0 {
+            return Err(out_of_range);
+        }
+        let thermometer = ThermometerCode::new(code >> self.binary_bits, self.thermometer_lines())
+            .map_err(|_| out_of_range)?;
+        Ok((
+            (0..self.binary_bits)
+                .map(|bit| code >> bit & 1 == 1)
+                .collect(),
+            thermometer,
+        ))
+    }
+}
+
+/// A builder for [`IdacParams`].
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct IdacParamsBuilder {
+    params: IdacParams,
+}
+
+impl Default for IdacParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: IdacParams {
+                kind: TileKind::N,
+                binary_bits: 3,
+                thermometer_bits: 2,
+                mirror_w: 1_000,
+                mirror_l: None,
+                cascode_w: 1_000,
+                switch_w: 1_000,
+            },
+        }
+    }
+}
+
+impl IdacParamsBuilder {
+    setters! {
+        /// Sets whether the DAC sinks or sources current.
+        kind: TileKind,
+        /// Sets the number of binary-weighted LSBs.
+        binary_bits: usize,
+        /// Sets the number of thermometer-coded MSBs.
+        thermometer_bits: usize,
+        /// Sets the width of the unit mirror devices.
+        mirror_w: i64,
+        /// Sets the channel length of the unit mirror devices.
+        mirror_l: Option<i64>,
+        /// Sets the width of the unit cascode devices.
+        cascode_w: i64,
+        /// Sets the width of the unit switches.
+        switch_w: i64,
+    }
+
+    /// Validates and returns the parameters.
+    ///
+    /// Channel lengths are checked against a technology with
+    /// [`IdacParams::check_lengths`].
+    pub fn build(self) -> std::result::Result<IdacParams, ParamsError> {
+        // Larger counts overflow the unit cell counts and produce unusably large arrays.
+        check_at_most(
+            "thermometer_bits",
+            self.params.thermometer_bits as i64,
+            IdacParams::MAX_THERMOMETER_BITS as i64,
+        )?;
+        check_at_most(
+            "binary_bits",
+            self.params.binary_bits as i64,
+            IdacParams::MAX_BITS as i64,
+        )?;
+        check_positive("bits", self.params.bits() as i64)?;
+        check_at_most(
+            "bits",
+            self.params.bits() as i64,
+            IdacParams::MAX_BITS as i64,
+        )?;
+        for (field, value) in [
+            ("mirror_w", self.params.mirror_w),
+            ("cascode_w", self.params.cascode_w),
+            ("switch_w", self.params.switch_w),
+        ] {
+            check_positive(field, value)?;
+        }
+        self.params
+            .mirror_l
+            .map_or(Ok(()), |value| check_positive("mirror_l", value))?;
+        Ok(self.params)
+    }
+}
+
+/// A cascoded, switched current mirror cell.
+///
+/// The mirror, cascode, and switch are stacked from `rail` to `out`,
+/// with a tap on the `rail` side.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct IdacUnit<T>(
+    IdacParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> IdacUnit<T> {
+    /// Creates a new [`IdacUnit`].
+    ///
+    /// Only the device sizes and kind of `params` are used.
+    pub fn new(params: IdacParams) -> Self {
+        Self(params, PhantomData)
+    }
+}
+
+impl<T: Any> Block for IdacUnit<T> {
+    type Io = IdacUnitIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("idac_unit")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name(
+            "idac_unit",
+            &(
+                self.0.kind,
+                self.0.mirror_w,
+                self.0.mirror_l,
+                self.0.cascode_w,
+                self.0.switch_w,
+            ),
+        )
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for IdacUnit<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for IdacUnit<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for IdacUnit<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let kind = self.0.kind;
+        let rail = io.schematic.rail;
+        let x = cell.signal("x", Signal);
+        let y = cell.signal("y", Signal);
+
+        let params = |w, l| {
+            MosTileParams::new(MosKind::Nom, kind, w)
+                .with_length(l)
+                .snapped(T::snap_width)
+        };
+        let mut devices = [
+            (
+                params(self.0.mirror_w, self.0.mirror_l),
+                x,
+                io.schematic.bias,
+                rail,
+            ),
+            (params(self.0.cascode_w, None), y, io.schematic.vcas, x),
+            (
+                params(self.0.switch_w, None),
+                io.schematic.out,
+                io.schematic.en,
+                y,
+            ),
+        ]
+        .into_iter()
+        .map(|(params, d, g, s)| {
+            cell.generate_connected(T::mos(params), MosIoSchematic { d, g, s, b: rail })
+        })
+        .collect::<Vec<_>>();
+        let mut tap = cell.generate(T::tap(TapTileParams::new(kind, 1)));
+        cell.connect(tap.io().x, rail);
+
+        // NMOS cells hang down from the output to a substrate tap at the bottom,
+        // and PMOS cells hang down from a well tap at the top to the output.
+        let mut prev = tap.lcm_bounds();
+        if kind == TileKind::N {
+            devices.reverse();
+            prev = devices[0].lcm_bounds();
+        }
+        for (i, mos) in devices.iter_mut().enumerate() {
+            if kind == TileKind::P || i > 0 {
+                mos.align_rect_mut(prev, AlignMode::Left, 0);
+                mos.align_rect_mut(prev, AlignMode::Beneath, 0);
+            }
+            prev = mos.lcm_bounds();
+        }
+        if kind == TileKind::N {
+            tap.align_rect_mut(prev, AlignMode::Left, 0);
+            tap.align_rect_mut(prev, AlignMode::Beneath, 0);
+        }
+
+        let devices = devices
+            .into_iter()
+            .map(|mos| cell.draw(mos))
+            .collect::<Result<Vec<_>>>()?;
+        let tap = cell.draw(tap)?;
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        // Devices are ordered from the output to the rail for NMOS cells,
+        // and from the rail to the output for PMOS cells.
+        let (mirror, cascode, switch) = match kind {
+            TileKind::N => (&devices[2], &devices[1], &devices[0]),
+            TileKind::P => (&devices[0], &devices[1], &devices[2]),
+        };
+        io.layout.bias.merge(mirror.layout.io().g);
+        io.layout.vcas.merge(cascode.layout.io().g);
+        io.layout.en.merge(switch.layout.io().g);
+        io.layout.out.merge(switch.layout.io().d);
+        io.layout.rail.merge(tap.layout.io().x);
+
+        Ok(((), ()))
+    }
+}
+
+/// A segmented current-steering DAC.
+///
+/// A diode-connected reference cell mirrors `iref` into every unit cell, so one LSB of
+/// output current equals `iref`. The binary controls switch `2^i` unit cells each and
+/// each thermometer line switches `2^binary_bits` unit cells, which keeps the largest
+/// switched segment small and the MSB transitions monotonic.
+///
+/// The reference cell is diode-connected through its cascode, so `vcas` must leave the
+/// reference mirror device in saturation.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct Idac<T>(
+    IdacParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> Idac<T> {
+    /// Creates a new [`Idac`].
+    pub fn new(params: IdacParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The DAC parameters.
+    pub fn params(&self) -> IdacParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for Idac<T> {
+    type Io = IdacIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("idac")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("idac", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        IdacIo {
+            binary: Array::new(self.0.binary_bits, Default::default()),
+            thermometer: Array::new(self.0.thermometer_lines(), Default::default()),
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Any> ExportsNestedData for Idac<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for Idac<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for Idac<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        self.0
+            .check_lengths::<T>()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let (rail, on) = match self.0.kind {
+            TileKind::N => (io.schematic.vss, io.schematic.vdd),
+            TileKind::P => (io.schematic.vdd, io.schematic.vss),
+        };
+        let controls = io
+            .schematic
+            .binary
+            .iter()
+            .chain(io.schematic.thermometer.iter())
+            .copied()
+            .collect::<Vec<_>>();
+
+        // The reference cell comes first, followed by the cells of each control in order.
+        let cells = std::iter::once((on, io.schematic.iref))
+            .chain(
+                self.0
+                    .units_per_control()
+                    .into_iter()
+                    .zip(controls)
+                    .flat_map(|(n, en)| std::iter::repeat((en, io.schematic.iout)).take(n)),
+            )
+            .collect::<Vec<_>>();
+        let mut units = cells
+            .into_iter()
+            .map(|(en, out)| {
+                cell.generate_connected(
+                    IdacUnit::<T>::new(self.0),
+                    IdacUnitIoSchematic {
+                        bias: io.schematic.iref,
+                        vcas: io.schematic.vcas,
+                        en,
+                        out,
+                        rail,
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut prev = units[0].lcm_bounds();
+        for unit in units.iter_mut().skip(1) {
+            unit.align_rect_mut(prev, AlignMode::Bottom, 0);
+            unit.align_rect_mut(prev, AlignMode::ToTheRight, 0);
+            prev = unit.lcm_bounds();
+        }
+
+        let units = units
+            .into_iter()
+            .map(|unit| cell.draw(unit))
+            .collect::<Result<Vec<_>>>()?;
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        io.layout.iref.merge(units[0].layout.io().out);
+        io.layout.vcas.merge(units[0].layout.io().vcas);
+        let mut unit = 1;
+        for (control, n) in self.0.units_per_control().into_iter().enumerate() {
+            let pin = if control < self.0.binary_bits {
+                &mut io.layout.binary[control]
+            } else {
+                &mut io.layout.thermometer[control - self.0.binary_bits]
+            };
+            pin.merge(units[unit].layout.io().en);
+            io.layout.iout.merge(units[unit].layout.io().out);
+            unit += n;
+        }
+        let rail_pin = match self.0.kind {
+            TileKind::N => &mut io.layout.vss,
+            TileKind::P => &mut io.layout.vdd,
+        };
+        rail_pin.merge(units[0].layout.io().rail);
+
+        Ok(((), ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segmented_controls() {
+        let params = IdacParams::builder()
+            .binary_bits(2)
+            .thermometer_bits(2)
+            .build()
+            .unwrap();
+        assert_eq!(params.units_per_control(), vec![1, 2, 4, 4, 4]);
+        assert_eq!(
+            params.controls(0b1001),
+            Ok((vec![true, false], ThermometerCode::new(2, 3).unwrap()))
+        );
+        assert_eq!(
+            params.controls(0),
+            Ok((vec![false; 2], ThermometerCode::none(3)))
+        );
+        assert_eq!(
+            params.controls(0b10000),
+            Err(IdacCodeError::OutOfRange {
+                code: 0b10000,
+                bits: 4
+            })
+        );
+    }
+
+    #[test]
+    fn bounds_bit_counts() {
+        assert_eq!(
+            IdacParams::builder().thermometer_bits(64).build(),
+            Err(ParamsError::TooLarge {
+                field: "thermometer_bits",
+                value: 64,
+                max: 8
+            })
+        );
+        assert_eq!(
+            IdacParams::builder()
+                .binary_bits(12)
+                .thermometer_bits(8)
+                .build(),
+            Err(ParamsError::TooLarge {
+                field: "bits",
+                value: 20,
+                max: 16
+            })
+        );
+    }
+}
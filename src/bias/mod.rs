@@ -0,0 +1,437 @@
+//! Bias and sensing generators.
+
+use crate::params::{check_at_least, check_positive, setters, ParamsError};
+use crate::route::RouterKind;
+use crate::tb::psrr::ReferenceIo;
+use crate::tiles::{
+    BjtIo, BjtIoSchematic, MosKind, MosLengthRules, MosTileParams, ResistorConn, ResistorIo,
+    ResistorIoSchematic, TapIo, TapTileParams, TileKind, WidthSpec,
+};
+use atoll::route::ViaMaker;
+use atoll::{IoBuilder, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::error::Result;
+use substrate::geometry::align::AlignMode;
+use substrate::io::{MosIo, MosIoSchematic, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+pub mod idac;
+pub mod tb;
+
+/// The parameters of the [`TempSensor`] layout generator.
+///
+/// All resistors are built from legs of the same width and length, so the
+/// PTAT gain `out_res_legs / core_res_legs` is set by the leg counts alone.
+///
+/// Channel lengths default to the technology minimum. Check any other lengths with
+/// [`TempSensorParams::check_lengths`] before generating.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct TempSensorParams {
+    /// The emitter area of the large bipolar relative to the unit bipolar.
+    pub pnp_ratio: i64,
+    /// The width of each PMOS current mirror device.
+    pub mirror_w: i64,
+    /// The channel length of each PMOS current mirror device,
+    /// or the technology minimum if `None`.
+    #[serde(default)]
+    pub mirror_l: Option<i64>,
+    /// The width of each NMOS device of the PTAT core.
+    pub core_w: i64,
+    /// The channel length of each NMOS device of the PTAT core,
+    /// or the technology minimum if `None`.
+    #[serde(default)]
+    pub core_l: Option<i64>,
+    /// The width of each resistor leg.
+    pub res_w: i64,
+    /// The length of each resistor leg.
+    pub res_l: i64,
+    /// The number of series legs in the resistor that sets the PTAT current.
+    pub core_res_legs: i64,
+    /// The number of series legs in the resistor that converts the PTAT current to a voltage.
+    pub out_res_legs: i64,
+    /// The width of the start-up device.
+    pub startup_w: i64,
+    /// The channel length of the start-up device, or the technology minimum if `None`.
+    #[serde(default)]
+    pub startup_l: Option<i64>,
+    /// Whether to drive the output through a PMOS source follower.
+    ///
+    /// The follower lets the sensor drive a test pad or a sampling ADC input
+    /// without loading the PTAT output resistor.
+    pub buffer: bool,
+}
+
+impl TempSensorParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> TempSensorParamsBuilder {
+        TempSensorParamsBuilder::default()
+    }
+
+    /// Returns an error if the MOS tiles of technology `T` do not support one of the
+    /// channel lengths.
+    pub fn check_lengths<T: MosLengthRules>(&self) -> std::result::Result<(), ParamsError> {
+        T::check_length("mirror_l", self.mirror_l)?;
+        T::check_length("core_l", self.core_l)?;
+        T::check_length("startup_l", self.startup_l)
+    }
+
+    /// The ratio of the output voltage to the PTAT voltage across the core resistor,
+    /// ignoring the output buffer.
+    pub fn gain(&self) -> f64 {
+        self.out_res_legs as f64 / self.core_res_legs as f64
+    }
+}
+
+/// A builder for [`TempSensorParams`].
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct TempSensorParamsBuilder {
+    params: TempSensorParams,
+}
+
+impl Default for TempSensorParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: TempSensorParams {
+                pnp_ratio: 8,
+                mirror_w: 2_000,
+                mirror_l: None,
+                core_w: 2_000,
+                core_l: None,
+                res_w: 1_000,
+                res_l: 4_000,
+                core_res_legs: 4,
+                out_res_legs: 16,
+                startup_w: 420,
+                startup_l: None,
+                buffer: true,
+            },
+        }
+    }
+}
+
+impl TempSensorParamsBuilder {
+    setters! {
+        /// Sets the emitter area ratio of the bipolars.
+        pnp_ratio: i64,
+        /// Sets the width of the PMOS current mirror devices.
+        mirror_w: i64,
+        /// Sets the channel length of the PMOS current mirror devices.
+        mirror_l: Option<i64>,
+        /// Sets the width of the NMOS core devices.
+        core_w: i64,
+        /// Sets the channel length of the NMOS core devices.
+        core_l: Option<i64>,
+        /// Sets the width of each resistor leg.
+        res_w: i64,
+        /// Sets the length of each resistor leg.
+        res_l: i64,
+        /// Sets the number of legs in the core resistor.
+        core_res_legs: i64,
+        /// Sets the number of legs in the output resistor.
+        out_res_legs: i64,
+        /// Sets the width of the start-up device.
+        startup_w: i64,
+        /// Sets the channel length of the start-up device.
+        startup_l: Option<i64>,
+        /// Sets whether the output is buffered.
+        buffer: bool,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<TempSensorParams, ParamsError> {
+        // Equal emitter areas produce no PTAT voltage.
+        check_at_least("pnp_ratio", self.params.pnp_ratio, 2)?;
+        for (field, value) in [
+            ("mirror_w", self.params.mirror_w),
+            ("core_w", self.params.core_w),
+            ("res_w", self.params.res_w),
+            ("res_l", self.params.res_l),
+            ("core_res_legs", self.params.core_res_legs),
+            ("out_res_legs", self.params.out_res_legs),
+            ("startup_w", self.params.startup_w),
+        ] {
+            check_positive(field, value)?;
+        }
+        for (field, value) in [
+            ("mirror_l", self.params.mirror_l),
+            ("core_l", self.params.core_l),
+            ("startup_l", self.params.startup_l),
+        ] {
+            value.map_or(Ok(()), |value| check_positive(field, value))?;
+        }
+        Ok(self.params)
+    }
+}
+
+/// A temperature sensor implementation.
+pub trait TempSensorImpl<PDK: Pdk + Schema>: MosLengthRules {
+    /// The MOS tile.
+    type MosTile: Tile<PDK> + Block<Io = MosIo> + Clone;
+    /// The tap tile.
+    type TapTile: Tile<PDK> + Block<Io = TapIo> + Clone;
+    /// The resistor tile.
+    type ResistorTile: Tile<PDK> + Block<Io = ResistorIo> + Clone;
+    /// The substrate PNP tile, also used by bandgap references.
+    type PnpTile: Tile<PDK> + Block<Io = BjtIo> + Clone;
+    /// A PDK-specific via maker.
+    type ViaMaker: ViaMaker<PDK>;
+    /// The router used by the temperature sensor.
+    const ROUTER: RouterKind = RouterKind::Greedy;
+
+    /// Creates an instance of the MOS tile.
+    fn mos(params: MosTileParams) -> Self::MosTile;
+    /// Creates an instance of the tap tile.
+    fn tap(params: TapTileParams) -> Self::TapTile;
+    /// Creates an instance of the resistor tile.
+    fn resistor(legs: i64, w: i64, l: i64, conn: ResistorConn) -> Self::ResistorTile;
+    /// Creates a substrate PNP with `multiplier` unit emitters in parallel.
+    fn pnp(multiplier: i64) -> Self::PnpTile;
+    /// Creates a PDK-specific via maker.
+    fn via_maker() -> Self::ViaMaker;
+    /// Snaps a requested MOS width to a legal value, returning the achieved width.
+    fn snap_width(_kind: TileKind, width: WidthSpec) -> WidthSpec {
+        width
+    }
+}
+
+/// An on-die temperature sensor.
+///
+/// A self-biased PTAT core forces the difference of the emitter-base voltages of a unit
+/// substrate PNP and one `pnp_ratio` times larger across the core resistor, giving a
+/// current of `VT ln(pnp_ratio) / R`. A third mirror device copies that current into the
+/// output resistor, so the output rises linearly with absolute temperature.
+///
+/// A minimum-width NMOS, always on, pulls the mirror gate low so that the core cannot
+/// settle in its zero-current state. Its current adds an offset to the PTAT current,
+/// so it should be given a long channel where the MOS tiles support one.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct TempSensor<T>(
+    TempSensorParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> TempSensor<T> {
+    /// Creates a new [`TempSensor`].
+    pub fn new(params: TempSensorParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The temperature sensor parameters.
+    pub fn params(&self) -> TempSensorParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for TempSensor<T> {
+    type Io = ReferenceIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("temp_sensor")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("temp_sensor", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for TempSensor<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for TempSensor<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: TempSensorImpl<PDK> + Any> Tile<PDK> for TempSensor<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        self.0
+            .check_lengths::<T>()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let vdd = io.schematic.vdd;
+        let vss = io.schematic.vss;
+        let pbias = cell.signal("pbias", Signal);
+        let nbias = cell.signal("nbias", Signal);
+        let e_unit = cell.signal("e_unit", Signal);
+        let e_ratio = cell.signal("e_ratio", Signal);
+        let r_top = cell.signal("r_top", Signal);
+        let vptat = if self.0.buffer {
+            cell.signal("vptat", Signal)
+        } else {
+            io.schematic.vout
+        };
+
+        let mirror_params = MosTileParams::new(MosKind::Nom, TileKind::P, self.0.mirror_w)
+            .with_length(self.0.mirror_l)
+            .snapped(T::snap_width);
+        let core_params = MosTileParams::new(MosKind::Nom, TileKind::N, self.0.core_w)
+            .with_length(self.0.core_l)
+            .snapped(T::snap_width);
+        let startup_params = MosTileParams::new(MosKind::Nom, TileKind::N, self.0.startup_w)
+            .with_length(self.0.startup_l)
+            .snapped(T::snap_width);
+
+        // PMOS row: the diode-connected mirror reference, the core and output mirror
+        // devices, then the bias and follower devices of the output buffer.
+        let mut pmos_conns = vec![
+            (pbias, pbias, vdd),
+            (nbias, pbias, vdd),
+            (vptat, pbias, vdd),
+        ];
+        if self.0.buffer {
+            pmos_conns.extend([
+                (io.schematic.vout, pbias, vdd),
+                (vss, vptat, io.schematic.vout),
+            ]);
+        }
+        let mut pmos = pmos_conns
+            .into_iter()
+            .map(|(d, g, s)| {
+                cell.generate_connected(T::mos(mirror_params), MosIoSchematic { d, g, s, b: vdd })
+            })
+            .collect::<Vec<_>>();
+
+        // NMOS row: the diode-connected core device over the unit PNP, its partner over
+        // the core resistor, and the start-up device.
+        let mut nmos = [
+            (core_params, nbias, nbias, e_unit),
+            (core_params, pbias, nbias, r_top),
+            (startup_params, pbias, vdd, vss),
+        ]
+        .into_iter()
+        .map(|(params, d, g, s)| {
+            cell.generate_connected(T::mos(params), MosIoSchematic { d, g, s, b: vss })
+        })
+        .collect::<Vec<_>>();
+
+        let mut resistors = [
+            (self.0.core_res_legs, r_top, e_ratio),
+            (self.0.out_res_legs, vptat, vss),
+        ]
+        .into_iter()
+        .map(|(legs, p, n)| {
+            cell.generate_connected(
+                T::resistor(legs, self.0.res_w, self.0.res_l, ResistorConn::Series),
+                ResistorIoSchematic { p, n, b: vdd },
+            )
+        })
+        .collect::<Vec<_>>();
+
+        let mut pnps = [(1, e_unit), (self.0.pnp_ratio, e_ratio)]
+            .into_iter()
+            .map(|(multiplier, e)| {
+                cell.generate_connected(T::pnp(multiplier), BjtIoSchematic { c: vss, b: vss, e })
+            })
+            .collect::<Vec<_>>();
+
+        let ntap = cell.generate(T::tap(TapTileParams::new(TileKind::N, pmos.len() as i64)));
+        let mut ptap = cell.generate(T::tap(TapTileParams::new(TileKind::P, nmos.len() as i64)));
+        cell.connect(ntap.io().x, vdd);
+        cell.connect(ptap.io().x, vss);
+
+        let mut prev = ntap.lcm_bounds();
+        for row in [&mut pmos, &mut nmos] {
+            let mut left_rect = None;
+            for inst in row.iter_mut() {
+                match left_rect {
+                    None => {
+                        inst.align_rect_mut(prev, AlignMode::Left, 0);
+                        inst.align_rect_mut(prev, AlignMode::Beneath, 0);
+                        prev = inst.lcm_bounds();
+                    }
+                    Some(left_rect) => {
+                        inst.align_rect_mut(left_rect, AlignMode::Bottom, 0);
+                        inst.align_rect_mut(left_rect, AlignMode::ToTheRight, 0);
+                    }
+                }
+                left_rect = Some(inst.lcm_bounds());
+            }
+        }
+        ptap.align_rect_mut(prev, AlignMode::Left, 0);
+        ptap.align_rect_mut(prev, AlignMode::Beneath, 0);
+        prev = ptap.lcm_bounds();
+
+        // The resistors and bipolars each form one more row beneath the substrate tap.
+        let mut left_rect = None;
+        for inst in resistors.iter_mut() {
+            match left_rect {
+                None => {
+                    inst.align_rect_mut(prev, AlignMode::Left, 0);
+                    inst.align_rect_mut(prev, AlignMode::Beneath, 0);
+                    prev = inst.lcm_bounds();
+                }
+                Some(left_rect) => {
+                    inst.align_rect_mut(left_rect, AlignMode::Bottom, 0);
+                    inst.align_rect_mut(left_rect, AlignMode::ToTheRight, 0);
+                }
+            }
+            left_rect = Some(inst.lcm_bounds());
+        }
+        let mut left_rect = None;
+        for inst in pnps.iter_mut() {
+            match left_rect {
+                None => {
+                    inst.align_rect_mut(prev, AlignMode::Left, 0);
+                    inst.align_rect_mut(prev, AlignMode::Beneath, 0);
+                }
+                Some(left_rect) => {
+                    inst.align_rect_mut(left_rect, AlignMode::Bottom, 0);
+                    inst.align_rect_mut(left_rect, AlignMode::ToTheRight, 0);
+                }
+            }
+            left_rect = Some(inst.lcm_bounds());
+        }
+
+        let pmos = pmos
+            .into_iter()
+            .map(|inst| cell.draw(inst))
+            .collect::<Result<Vec<_>>>()?;
+        for inst in nmos {
+            cell.draw(inst)?;
+        }
+        let resistors = resistors
+            .into_iter()
+            .map(|inst| cell.draw(inst))
+            .collect::<Result<Vec<_>>>()?;
+        for inst in pnps {
+            cell.draw(inst)?;
+        }
+        let ntap = cell.draw(ntap)?;
+        let ptap = cell.draw(ptap)?;
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        if self.0.buffer {
+            io.layout.vout.merge(pmos[3].layout.io().d);
+            io.layout.vout.merge(pmos[4].layout.io().s);
+        } else {
+            io.layout.vout.merge(pmos[2].layout.io().d);
+            io.layout.vout.merge(resistors[1].layout.io().p);
+        }
+        io.layout.vdd.merge(ntap.layout.io().x);
+        io.layout.vss.merge(ptap.layout.io().x);
+
+        Ok(((), ()))
+    }
+}
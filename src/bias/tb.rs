@@ -0,0 +1,864 @@
+//! Bias generator characterization.
+//!
+//! [`TempSensorTb`] measures the settled output of a temperature sensor at one PVT corner.
+//! Run it across temperatures with a [`CornerSweep`](crate::sweep::CornerSweep), then fit
+//! the results with [`TempSensorSummary`] to find the sensitivity and nonlinearity of the
+//! sensor at each process corner and supply voltage.
+//!
+//! [`IdacLinearityTb`] steps a current DAC through every code to find its DNL and INL, and
+//! [`IdacComplianceTb`] sweeps its output voltage to find the range over which the
+//! full-scale current holds.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::{Isource, Pulse, Vsource};
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Node, Terminal};
+use substrate::io::{Array, FlatLen, Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::adc::tb::AdcLinearity;
+use crate::bias::idac::{Idac, IdacIoSchematic};
+use crate::report::SimArtifact;
+use crate::sweep::CornerSweepOutput;
+use crate::tb::pi::Samples;
+use crate::tb::psrr::ReferenceIo;
+use crate::tiles::TileKind;
+
+/// The time over which the supply ramps up.
+const RAMP: Decimal = dec!(1e-6);
+/// The simulation stop time, which leaves the output time to settle after the ramp.
+const STOP: Decimal = dec!(20e-6);
+/// The rise and fall time of the IDAC control inputs.
+const EDGE: Decimal = dec!(100e-12);
+/// The duration of the IDAC output voltage sweep.
+const SWEEP: Decimal = dec!(10e-6);
+
+/// A transient testbench that ramps up the supply of a temperature sensor
+/// and reports its settled output voltage.
+///
+/// Ramping the supply from zero also checks that the sensor starts up.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct TempSensorTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: T,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> TempSensorTb<T, PDK, C> {
+    /// Creates a new [`TempSensorTb`].
+    pub fn new(dut: T, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for TempSensorTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("temp_sensor_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("temp_sensor_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`TempSensorTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct TempSensorTbNodes {
+    vout: Node,
+}
+
+impl<T, PDK, C> ExportsNestedData for TempSensorTb<T, PDK, C>
+where
+    TempSensorTb<T, PDK, C>: Block,
+{
+    type NestedData = TempSensorTbNodes;
+}
+
+impl<T: Block<Io = ReferenceIo> + Schematic<PDK> + Clone, PDK: Schema, C: Copy> Schematic<Spectre>
+    for TempSensorTb<T, PDK, C>
+where
+    TempSensorTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vdd = cell.signal("vdd", Signal);
+        let vout = cell.signal("vout", Signal);
+
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
+        cell.connect(dut.io().vdd, vdd);
+        cell.connect(dut.io().vss, io.vss);
+        cell.connect(dut.io().vout, vout);
+
+        cell.instantiate_connected(
+            Vsource::pulse(Pulse {
+                val0: dec!(0),
+                val1: self.pvt.voltage,
+                period: None,
+                width: None,
+                delay: Some(dec!(0)),
+                rise: Some(RAMP),
+                fall: Some(RAMP),
+            }),
+            TwoTerminalIoSchematic { p: vdd, n: io.vss },
+        );
+
+        Ok(TempSensorTbNodes { vout })
+    }
+}
+
+/// The resulting waveforms of a [`TempSensorTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct TempSensorSim {
+    /// The simulation time.
+    pub t: tran::Time,
+    /// The sensor output.
+    pub vout: tran::Voltage,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, TempSensorSim> for TempSensorTb<T, PDK, C>
+where
+    TempSensorTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <TempSensorSim as FromSaved<Spectre, Tran>>::SavedKey {
+        TempSensorSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            vout: tran::Voltage::save(ctx, &cell.vout, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for TempSensorTb<T, PDK, C>
+where
+    TempSensorTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    /// The settled output voltage in volts.
+    type Output = f64;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: TempSensorSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: STOP,
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+        *wav.vout.last().expect("no output samples")
+    }
+}
+
+/// The transfer characteristic of a temperature sensor at one process corner
+/// and supply voltage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TempSensorCurve {
+    /// The process corner.
+    pub corner: String,
+    /// The supply voltage, in volts.
+    pub voltage: f64,
+    /// The sensitivity from a least-squares line fit, in V/K.
+    pub sensitivity: f64,
+    /// The output of the fitted line at 0 C, in volts.
+    pub offset: f64,
+    /// The largest deviation of the output from the fitted line,
+    /// referred to temperature, in kelvin.
+    pub nonlinearity: f64,
+}
+
+impl TempSensorCurve {
+    /// Fits a line to `(temperature, output)` samples, with temperatures in degrees Celsius.
+    ///
+    /// Returns `None` if fewer than two distinct temperatures are given.
+    pub fn fit(
+        corner: String,
+        voltage: f64,
+        samples: impl IntoIterator<Item = (f64, f64)>,
+    ) -> Option<Self> {
+        let samples = samples.into_iter().collect::<Vec<_>>();
+        let n = samples.len() as f64;
+        let t_mean = samples.iter().map(|(t, _)| t).sum::<f64>() / n;
+        let v_mean = samples.iter().map(|(_, v)| v).sum::<f64>() / n;
+        let t_var = samples
+            .iter()
+            .map(|(t, _)| (t - t_mean).powi(2))
+            .sum::<f64>();
+        if samples.len() < 2 || t_var == 0. {
+            return None;
+        }
+        let sensitivity = samples
+            .iter()
+            .map(|(t, v)| (t - t_mean) * (v - v_mean))
+            .sum::<f64>()
+            / t_var;
+        let offset = v_mean - sensitivity * t_mean;
+        let nonlinearity = samples
+            .iter()
+            .map(|(t, v)| (v - offset - sensitivity * t).abs())
+            .fold(0., f64::max)
+            / sensitivity.abs();
+        Some(Self {
+            corner,
+            voltage,
+            sensitivity,
+            offset,
+            nonlinearity,
+        })
+    }
+
+    /// Converts a sensor output back to a temperature in degrees Celsius using the fitted line.
+    pub fn temperature(&self, vout: f64) -> f64 {
+        (vout - self.offset) / self.sensitivity
+    }
+}
+
+/// The transfer characteristics of a temperature sensor across PVT corners.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TempSensorSummary {
+    /// The fitted curve at each process corner and supply voltage
+    /// with at least two temperatures.
+    pub curves: Vec<TempSensorCurve>,
+}
+
+impl TempSensorSummary {
+    /// Summarizes sensor outputs measured at a set of PVT corners.
+    pub fn new<C: Copy + Debug + Hash + Eq>(
+        points: impl IntoIterator<Item = (Pvt<C>, f64)>,
+    ) -> Self {
+        let mut groups: HashMap<(C, _), Vec<(f64, f64)>> = HashMap::new();
+        for (pvt, vout) in points {
+            groups
+                .entry((pvt.corner, pvt.voltage))
+                .or_default()
+                .push((pvt.temp.to_f64().unwrap(), vout));
+        }
+        let mut curves = groups
+            .into_iter()
+            .filter_map(|((corner, voltage), samples)| {
+                TempSensorCurve::fit(format!("{corner:?}"), voltage.to_f64().unwrap(), samples)
+            })
+            .collect::<Vec<_>>();
+        curves.sort_by(|a, b| {
+            a.corner
+                .cmp(&b.corner)
+                .then(a.voltage.total_cmp(&b.voltage))
+        });
+        Self { curves }
+    }
+
+    /// Summarizes the output of a corner sweep of [`TempSensorTb`].
+    pub fn from_sweep<C: Copy + Debug + Hash + Eq>(sweep: &CornerSweepOutput<C, f64>) -> Self {
+        Self::new(sweep.outputs.iter().map(|(pvt, vout)| (*pvt, *vout)))
+    }
+
+    /// The largest nonlinearity of any curve, in kelvin.
+    pub fn worst_nonlinearity(&self) -> f64 {
+        self.curves
+            .iter()
+            .map(|curve| curve.nonlinearity)
+            .fold(0., f64::max)
+    }
+}
+
+impl SimArtifact for TempSensorSummary {
+    fn csv_header(&self) -> Vec<String> {
+        [
+            "corner",
+            "voltage",
+            "sensitivity_v_per_k",
+            "offset_v",
+            "nonlinearity_k",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.curves
+            .iter()
+            .map(|curve| {
+                vec![
+                    curve.corner.clone(),
+                    curve.voltage.to_string(),
+                    curve.sensitivity.to_string(),
+                    curve.offset.to_string(),
+                    curve.nonlinearity.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Instantiates an IDAC with its supply, cascode bias, and reference current,
+/// returning the supply node.
+fn bias_idac<T>(
+    cell: &mut CellBuilder<Spectre>,
+    dut: &Idac<T>,
+    dut_io: &IdacIoSchematic,
+    vss: Node,
+    vdd: Decimal,
+    iref: Decimal,
+    vcas: Decimal,
+) -> Node {
+    let vdd_node = cell.signal("vdd", Signal);
+    let vcas_node = cell.signal("vcas", Signal);
+    cell.connect(dut_io.vdd, vdd_node);
+    cell.connect(dut_io.vss, vss);
+    cell.connect(dut_io.vcas, vcas_node);
+
+    cell.instantiate_connected(
+        Vsource::dc(vdd),
+        TwoTerminalIoSchematic {
+            p: vdd_node,
+            n: vss,
+        },
+    );
+    cell.instantiate_connected(
+        Vsource::dc(vcas),
+        TwoTerminalIoSchematic {
+            p: vcas_node,
+            n: vss,
+        },
+    );
+    // An NMOS DAC sinks the reference current from VDD and a PMOS DAC sources it to VSS.
+    let (p, n) = match dut.params().kind {
+        TileKind::N => (vdd_node, dut_io.iref),
+        TileKind::P => (dut_io.iref, vss),
+    };
+    cell.instantiate_connected(Isource::dc(iref), TwoTerminalIoSchematic { p, n });
+    vdd_node
+}
+
+/// A transient testbench that counts the code of an [`Idac`] up from 0 to full scale,
+/// holding each code for `step`, and reports the linearity of the output current.
+///
+/// The output is held at `vout` by a voltage source, through which the current is measured.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq; C)]
+#[derive(Serialize, Deserialize)]
+pub struct IdacLinearityTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: Idac<T>,
+    /// The reference current, in amperes.
+    pub iref: Decimal,
+    /// The cascode bias voltage, in volts.
+    pub vcas: Decimal,
+    /// The output voltage, in volts.
+    pub vout: Decimal,
+    /// The time each code is held, in seconds.
+    pub step: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> IdacLinearityTb<T, PDK, C> {
+    /// Creates a new [`IdacLinearityTb`].
+    pub fn new(
+        dut: Idac<T>,
+        iref: Decimal,
+        vcas: Decimal,
+        vout: Decimal,
+        step: Decimal,
+        pvt: Pvt<C>,
+    ) -> Self {
+        Self {
+            dut,
+            iref,
+            vcas,
+            vout,
+            step,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        T: Any,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for IdacLinearityTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("idac_linearity_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("idac_linearity_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`IdacLinearityTb`] and [`IdacComplianceTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct IdacTbNodes {
+    vout: Node,
+    vout_src: Terminal,
+}
+
+impl<T, PDK, C> ExportsNestedData for IdacLinearityTb<T, PDK, C>
+where
+    IdacLinearityTb<T, PDK, C>: Block,
+{
+    type NestedData = IdacTbNodes;
+}
+
+impl<T, PDK: Schema, C: Copy> Schematic<Spectre> for IdacLinearityTb<T, PDK, C>
+where
+    IdacLinearityTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Idac<T>: Schematic<PDK>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let params = self.dut.params();
+        let vout = cell.signal("vout", Signal);
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut);
+        bias_idac(
+            cell,
+            &self.dut,
+            dut.io(),
+            io.vss,
+            self.pvt.voltage,
+            self.iref,
+            self.vcas,
+        );
+        cell.connect(dut.io().iout, vout);
+        let vout_src = cell.instantiate(Vsource::dc(self.vout));
+        cell.connect(vout_src.io().p, vout);
+        cell.connect(vout_src.io().n, io.vss);
+
+        let (off, on) = match params.kind {
+            TileKind::N => (dec!(0), self.pvt.voltage),
+            TileKind::P => (self.pvt.voltage, dec!(0)),
+        };
+        // Binary bit `i` toggles every `2^i` steps and thermometer line `j` turns on
+        // for good once the code reaches `(j + 1) * 2^binary_bits`, so code `k` is
+        // applied during step `k + 1`.
+        let binary = cell.signal("binary", Array::new(params.binary_bits, Signal));
+        for i in 0..binary.len() {
+            let half = self.step * Decimal::from(1u64 << i);
+            cell.connect(&dut.io().binary[i], &binary[i]);
+            cell.instantiate_connected(
+                Vsource::pulse(Pulse {
+                    val0: off,
+                    val1: on,
+                    period: Some(dec!(2) * half),
+                    width: Some(half - EDGE),
+                    delay: Some(self.step + half),
+                    rise: Some(EDGE),
+                    fall: Some(EDGE),
+                }),
+                TwoTerminalIoSchematic {
+                    p: binary[i],
+                    n: io.vss,
+                },
+            );
+        }
+        let thermometer = cell.signal(
+            "thermometer",
+            Array::new(params.thermometer_lines(), Signal),
+        );
+        for j in 0..thermometer.len() {
+            let codes = Decimal::from((j as u64 + 1) << params.binary_bits);
+            cell.connect(&dut.io().thermometer[j], &thermometer[j]);
+            cell.instantiate_connected(
+                Vsource::pulse(Pulse {
+                    val0: off,
+                    val1: on,
+                    period: None,
+                    width: None,
+                    delay: Some(self.step * (codes + dec!(1)) - EDGE),
+                    rise: Some(EDGE),
+                    fall: Some(EDGE),
+                }),
+                TwoTerminalIoSchematic {
+                    p: thermometer[j],
+                    n: io.vss,
+                },
+            );
+        }
+
+        Ok(IdacTbNodes {
+            vout,
+            vout_src: vout_src.io().p,
+        })
+    }
+}
+
+/// The resulting waveforms of an [`IdacLinearityTb`] or [`IdacComplianceTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct IdacSim {
+    /// The simulation time.
+    pub t: tran::Time,
+    /// The output voltage.
+    pub vout: tran::Voltage,
+    /// The current into the output voltage source.
+    pub iout: tran::Current,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, IdacSim> for IdacLinearityTb<T, PDK, C>
+where
+    IdacLinearityTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <IdacSim as FromSaved<Spectre, Tran>>::SavedKey {
+        IdacSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            vout: tran::Voltage::save(ctx, &cell.vout, opts),
+            iout: tran::Current::save(ctx, &cell.vout_src, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for IdacLinearityTb<T, PDK, C>
+where
+    IdacLinearityTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = AdcLinearity;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let codes = 1usize << self.dut.params().bits();
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: IdacSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: self.step * Decimal::from(codes + 1),
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+        let step = self.step.to_f64().unwrap();
+        let samples = Samples {
+            t: &wav.t,
+            v: &wav.iout,
+        };
+        // Measure each code just before the next one is applied.
+        AdcLinearity::from_levels(
+            &(0..codes)
+                .map(|k| samples.value_at(step * (k as f64 + 1.9)))
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+/// A transient testbench that enables every cell of an [`Idac`] and slowly
+/// ramps its output from VSS to VDD.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq; C)]
+#[derive(Serialize, Deserialize)]
+pub struct IdacComplianceTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: Idac<T>,
+    /// The reference current, in amperes.
+    pub iref: Decimal,
+    /// The cascode bias voltage, in volts.
+    pub vcas: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> IdacComplianceTb<T, PDK, C> {
+    /// Creates a new [`IdacComplianceTb`].
+    pub fn new(dut: Idac<T>, iref: Decimal, vcas: Decimal, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            iref,
+            vcas,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        T: Any,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for IdacComplianceTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("idac_compliance_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("idac_compliance_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T, PDK, C> ExportsNestedData for IdacComplianceTb<T, PDK, C>
+where
+    IdacComplianceTb<T, PDK, C>: Block,
+{
+    type NestedData = IdacTbNodes;
+}
+
+impl<T, PDK: Schema, C: Copy> Schematic<Spectre> for IdacComplianceTb<T, PDK, C>
+where
+    IdacComplianceTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Idac<T>: Schematic<PDK>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vout = cell.signal("vout", Signal);
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut);
+        let vdd = bias_idac(
+            cell,
+            &self.dut,
+            dut.io(),
+            io.vss,
+            self.pvt.voltage,
+            self.iref,
+            self.vcas,
+        );
+        cell.connect(dut.io().iout, vout);
+
+        let on = match self.dut.params().kind {
+            TileKind::N => vdd,
+            TileKind::P => io.vss,
+        };
+        for control in dut.io().binary.iter().chain(dut.io().thermometer.iter()) {
+            cell.connect(control, on);
+        }
+
+        let vout_src = cell.instantiate(Vsource::pulse(Pulse {
+            val0: dec!(0),
+            val1: self.pvt.voltage,
+            period: None,
+            width: None,
+            delay: Some(dec!(0)),
+            rise: Some(SWEEP),
+            fall: Some(SWEEP),
+        }));
+        cell.connect(vout_src.io().p, vout);
+        cell.connect(vout_src.io().n, io.vss);
+
+        Ok(IdacTbNodes {
+            vout,
+            vout_src: vout_src.io().p,
+        })
+    }
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, IdacSim> for IdacComplianceTb<T, PDK, C>
+where
+    IdacComplianceTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <IdacSim as FromSaved<Spectre, Tran>>::SavedKey {
+        IdacSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            vout: tran::Voltage::save(ctx, &cell.vout, opts),
+            iout: tran::Current::save(ctx, &cell.vout_src, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for IdacComplianceTb<T, PDK, C>
+where
+    IdacComplianceTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = IdacCompliance;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: IdacSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: SWEEP,
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+        IdacCompliance {
+            v: wav.vout.to_vec(),
+            i: wav.iout.to_vec(),
+        }
+    }
+}
+
+/// The full-scale output current of an IDAC as a function of its output voltage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdacCompliance {
+    /// The output voltages, in increasing order, in volts.
+    pub v: Vec<f64>,
+    /// The current into the output source at each voltage, in amperes.
+    pub i: Vec<f64>,
+}
+
+impl IdacCompliance {
+    /// The contiguous output voltage range around `vnom` over which the current stays
+    /// within a fraction `tolerance` of its value at `vnom`.
+    ///
+    /// Returns `None` if `vnom` is above the swept range.
+    pub fn range(&self, vnom: f64, tolerance: f64) -> Option<(f64, f64)> {
+        let k = self.v.iter().position(|&v| v >= vnom)?;
+        let inom = self.i[k];
+        let within = |j: &usize| (self.i[*j] - inom).abs() <= tolerance * inom.abs();
+        let lo = (0..=k).rev().take_while(within).last()?;
+        let hi = (k..self.v.len()).take_while(within).last()?;
+        Some((self.v[lo], self.v[hi]))
+    }
+}
+
+impl SimArtifact for IdacCompliance {
+    fn csv_header(&self) -> Vec<String> {
+        ["vout", "iout"].map(String::from).to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.v
+            .iter()
+            .zip(&self.i)
+            .map(|(v, i)| vec![v.to_string(), i.to_string()])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_sensitivity_and_nonlinearity() {
+        // 1 mV/K with a 0.5 mV bow at 25 C.
+        let samples = [(-40., 0.233), (25., 0.2985), (125., 0.398)];
+        let curve = TempSensorCurve::fit("tt".to_string(), 1.8, samples).unwrap();
+        assert!((curve.sensitivity - 1e-3).abs() < 2e-5);
+        assert!(curve.nonlinearity > 0.2 && curve.nonlinearity < 0.5);
+        assert!((curve.temperature(curve.offset + 100. * curve.sensitivity) - 100.).abs() < 1e-9);
+
+        assert!(TempSensorCurve::fit("tt".to_string(), 1.8, [(25., 0.3)]).is_none());
+    }
+
+    #[test]
+    fn idac_compliance_range() {
+        // A sink that needs 0.3 V of headroom and loses output impedance above 1.5 V.
+        let compliance = IdacCompliance {
+            v: vec![0., 0.2, 0.4, 0.8, 1.2, 1.6, 1.8],
+            i: vec![0., 80e-6, 99e-6, 100e-6, 101e-6, 110e-6, 150e-6],
+        };
+        assert_eq!(compliance.range(0.9, 0.02), Some((0.4, 1.2)));
+        assert_eq!(compliance.range(0.9, 0.3), Some((0.2, 1.6)));
+        assert_eq!(compliance.range(2.0, 0.02), None);
+    }
+}
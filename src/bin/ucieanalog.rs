@@ -0,0 +1,401 @@
+//! Command-line interface for generating and characterizing UCIe analog blocks.
+//!
+//! Each subcommand takes a block name and a TOML file containing the block's parameters.
+//! For example, a StrongARM parameter file looks like:
+//!
+//! ```toml
+//! nmos_kind = "Nom"
+//! pmos_kind = "Nom"
+//! half_tail_w = 1000
+//! input_pair_w = 1000
+//! inv_input_w = 1000
+//! inv_precharge_w = 1000
+//! precharge_w = 1000
+//! input_kind = "P"
+//! ```
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sky130pdk::Sky130Pdk;
+use spectre::Spectre;
+use substrate::block::Block;
+use substrate::context::PdkContext;
+use substrate::layout::Layout;
+use substrate::schematic::Schematic;
+use ucieanalog::cache::GenerationCache;
+use ucieanalog::export::gds::{self, GdsExportError, GdsExportOptions};
+use ucieanalog::export::lef::write_lef;
+use ucieanalog::export::netlist;
+use ucieanalog::frontend::{self, BlockKind, FrontendParams};
+use ucieanalog::report::snapshot::{diff_snapshots, snapshot_block, Snapshot};
+use ucieanalog::tech::sky130::Sky130Ucie;
+use ucieanalog::verification::drc::{run_drc, DrcParams, DrcTool};
+use ucieanalog::verification::quick_drc::quick_drc;
+use ucieanalog::{try_sky130_ctx, try_sky130_open_ctx, with_sky130_block, ContextError};
+
+#[derive(Parser)]
+#[command(
+    name = "ucieanalog",
+    about = "Generate and characterize UCIe analog blocks"
+)]
+struct Cli {
+    /// Use the open-source SKY130A PDK instead of the commercial SKY130 PDK.
+    #[arg(long, global = true)]
+    open: bool,
+
+    /// Reuse GDS and netlist files generated by earlier runs from this directory.
+    ///
+    /// Defaults to the value of `UCIEANALOG_CACHE_DIR`, if set.
+    #[arg(long, global = true)]
+    cache_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Writes the layout of a block to a GDS file.
+    Gds {
+        /// The block to generate.
+        block: BlockKind,
+        /// The TOML parameter file.
+        params: PathBuf,
+        /// The output GDS file.
+        #[arg(short, long)]
+        output: PathBuf,
+        /// A prefix added to every cell name.
+        #[arg(long, default_value = "")]
+        prefix: String,
+        /// A suffix added to every cell name.
+        #[arg(long, default_value = "")]
+        suffix: String,
+        /// The name of the top cell.
+        #[arg(long)]
+        top_name: Option<String>,
+        /// Label top-level pins with their names on the pin label layers.
+        #[arg(long)]
+        pin_labels: bool,
+        /// Check the layout with the built-in quick DRC before writing it.
+        #[arg(long)]
+        quick_drc: bool,
+    },
+    /// Writes a LEF abstract of a block.
+    Lef {
+        /// The block to generate.
+        block: BlockKind,
+        /// The TOML parameter file.
+        params: PathBuf,
+        /// The output LEF file.
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Writes the schematic of a block to a SPICE netlist.
+    Netlist {
+        /// The block to generate.
+        block: BlockKind,
+        /// The TOML parameter file.
+        params: PathBuf,
+        /// The output netlist file.
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Name every subckt after its contents and write the instance tree
+        /// to a `.hier` file next to the netlist.
+        #[arg(long)]
+        hierarchy: bool,
+    },
+    /// Simulates a block across process corners and writes the results as JSON.
+    ///
+    /// Only StrongARM blocks are supported.
+    Characterize {
+        /// The block to simulate.
+        block: BlockKind,
+        /// The TOML parameter file.
+        params: PathBuf,
+        /// The positive input voltage.
+        #[arg(long, default_value = "0.65")]
+        vinp: Decimal,
+        /// The negative input voltage.
+        #[arg(long, default_value = "0.55")]
+        vinn: Decimal,
+        /// The simulation directory.
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Generates the layout of a block and runs Magic DRC on it.
+    Drc {
+        /// The block to generate.
+        block: BlockKind,
+        /// The TOML parameter file.
+        params: PathBuf,
+        /// The `.magicrc` file that loads the technology.
+        #[arg(long)]
+        deck: PathBuf,
+        /// The DRC run directory.
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Records the structure of a block's layout in a JSON snapshot file.
+    ///
+    /// Snapshots of other blocks already in the file are kept.
+    Snapshot {
+        /// The block to generate.
+        block: BlockKind,
+        /// The TOML parameter file.
+        params: PathBuf,
+        /// The snapshot file.
+        #[arg(short, long)]
+        output: PathBuf,
+        /// The label under which to record the block.
+        ///
+        /// Defaults to the name of the block kind.
+        #[arg(long)]
+        label: Option<String>,
+    },
+    /// Prints the structural differences between two snapshot files.
+    DiffSnapshots {
+        /// The snapshot file of the old version.
+        old: PathBuf,
+        /// The snapshot file of the new version.
+        new: PathBuf,
+    },
+}
+
+/// Reads and validates the parameters in the TOML file at `path`.
+fn read_params<P: FrontendParams>(path: &Path) -> Result<P, Box<dyn Error>> {
+    let params: P = toml::from_str(&std::fs::read_to_string(path)?)?;
+    params
+        .validate()
+        .map_err(|e| format!("invalid parameters in {path:?}: {e}"))?;
+    Ok(params)
+}
+
+fn write_cached_netlist<B: Block + Serialize + Schematic<Sky130Pdk>>(
+    ctx: &PdkContext<Sky130Pdk>,
+    cache: Option<&GenerationCache>,
+    open: bool,
+    block: B,
+    output: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let Some(cache) = cache else {
+        return Ok(frontend::write_netlist(ctx, open, block, output)?);
+    };
+    let key = GenerationCache::key(ctx, &block, "netlist");
+    if cache.get_or_write(&key, output, |output| {
+        frontend::write_netlist(ctx, open, block, output)
+    })? {
+        println!("reused cached netlist");
+    }
+    Ok(())
+}
+
+fn write_gds<B: Block + Serialize + Layout<Sky130Pdk>>(
+    ctx: &PdkContext<Sky130Pdk>,
+    cache: Option<&GenerationCache>,
+    block: B,
+    output: &Path,
+    options: &GdsExportOptions,
+) -> Result<(), Box<dyn Error>> {
+    if options.quick_drc {
+        // Print the violations here, since the export error only counts them.
+        let report = quick_drc::<Sky130Ucie, _, _>(ctx, block.clone());
+        for violation in report.violations.iter() {
+            println!("{violation}");
+        }
+        if !report.is_clean() {
+            return Err(GdsExportError::QuickDrc(report).into());
+        }
+    }
+    let Some(cache) = cache else {
+        let top = gds::write_gds::<Sky130Ucie, _, _>(ctx, block, output, options)?;
+        println!("wrote {top} to {output:?}");
+        return Ok(());
+    };
+    // Labels are part of the cached file, so that cache hits need no layout generation.
+    let artifact = if options.pin_labels {
+        "gds-labeled"
+    } else {
+        "gds"
+    };
+    let labeled = GdsExportOptions {
+        pin_labels: options.pin_labels,
+        ..Default::default()
+    };
+    let key = GenerationCache::key(ctx, &block, artifact);
+    if cache.get_or_write(&key, output, |output| {
+        gds::write_gds::<Sky130Ucie, _, _>(ctx, block, output, &labeled).map(|_| ())
+    })? {
+        println!("reused cached layout");
+    }
+    if !options.is_identity() {
+        gds::rename_cells_in_file(output, options)?;
+    }
+    println!("wrote {output:?}");
+    Ok(())
+}
+
+/// Returns the SKY130 context selected by the `--open` flag.
+///
+/// Only subcommands that generate or simulate blocks build a context,
+/// so that the others work without a PDK.
+fn sky130_ctx(open: bool) -> Result<PdkContext<Sky130Pdk>, ContextError> {
+    if open {
+        try_sky130_open_ctx()
+    } else {
+        try_sky130_ctx()
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    ucieanalog::init_tracing();
+    let cache = cli
+        .cache_dir
+        .map(GenerationCache::new)
+        .or_else(GenerationCache::from_env);
+
+    match cli.command {
+        Command::Gds {
+            block,
+            params,
+            output,
+            prefix,
+            suffix,
+            top_name,
+            pin_labels,
+            quick_drc,
+        } => {
+            let options = GdsExportOptions {
+                prefix,
+                suffix,
+                top_name,
+                pin_labels,
+                quick_drc,
+            };
+            let ctx = sky130_ctx(cli.open)?;
+            with_sky130_block!(block, read_params, &params, |block| write_gds(
+                &ctx,
+                cache.as_ref(),
+                block,
+                &output,
+                &options
+            ))?;
+        }
+        Command::Lef {
+            block,
+            params,
+            output,
+        } => {
+            let ctx = sky130_ctx(cli.open)?;
+            with_sky130_block!(block, read_params, &params, |block| write_lef::<
+                Sky130Ucie,
+                _,
+                _,
+            >(
+                &ctx, block, &output
+            ))?;
+        }
+        Command::Netlist {
+            block,
+            params,
+            output,
+            hierarchy,
+        } => {
+            let ctx = sky130_ctx(cli.open)?;
+            with_sky130_block!(block, read_params, &params, |block| write_cached_netlist(
+                &ctx,
+                cache.as_ref(),
+                cli.open,
+                block,
+                &output
+            ))?;
+            if hierarchy {
+                netlist::export_hierarchy_in_file(&output)?;
+                println!("wrote {:?}", netlist::hierarchy_path(&output));
+            }
+        }
+        Command::Characterize {
+            block,
+            params,
+            vinp,
+            vinn,
+            output,
+        } => {
+            if cli.open {
+                return Err("characterization requires Spectre and the commercial PDK".into());
+            }
+            if !matches!(block, BlockKind::Strongarm) {
+                return Err(format!("characterization of {block:?} is not supported").into());
+            }
+            let ctx = sky130_ctx(cli.open)?;
+            let sweep = frontend::strongarm_characterization(read_params(&params)?, vinp, vinn);
+            let results = sweep.run::<Spectre, _>(&ctx, &output);
+            let path = output.join("results.json");
+            std::fs::write(&path, serde_json::to_string_pretty(&results)?)?;
+            println!("wrote {path:?}");
+        }
+        Command::Drc {
+            block,
+            params,
+            deck,
+            output,
+        } => {
+            let ctx = sky130_ctx(cli.open)?;
+            let gds = output.join("layout.gds");
+            with_sky130_block!(block, read_params, &params, |block| write_gds(
+                &ctx,
+                cache.as_ref(),
+                block,
+                &gds,
+                &GdsExportOptions::default()
+            ))?;
+            let drc = run_drc(&DrcParams::new(
+                DrcTool::Magic,
+                deck,
+                gds,
+                output.join("drc"),
+            ))?;
+            for violation in drc.violations.iter() {
+                println!("{violation}");
+            }
+            if !drc.is_clean() {
+                return Err(format!("found {} DRC violations", drc.violations.len()).into());
+            }
+            println!("DRC clean");
+        }
+        Command::Snapshot {
+            block: kind,
+            params,
+            output,
+            label,
+        } => {
+            let ctx = sky130_ctx(cli.open)?;
+            let mut snapshot = Snapshot::read_or_default(&output)?;
+            let label = label.unwrap_or_else(|| format!("{kind:?}"));
+            let block = with_sky130_block!(kind, read_params, &params, |block| snapshot_block::<
+                Sky130Ucie,
+                _,
+                _,
+            >(
+                &ctx, block
+            ));
+            snapshot.insert(label, block);
+            snapshot.write(&output)?;
+            println!("wrote {output:?}");
+        }
+        Command::DiffSnapshots { old, new } => {
+            let changes = diff_snapshots(&Snapshot::read(old)?, &Snapshot::read(new)?);
+            for change in changes.iter() {
+                println!("{change}");
+            }
+            if changes.is_empty() {
+                println!("no structural changes");
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,7 +1,11 @@
 //! Buffer layout generators.
 
-use crate::tiles::{MosKind, MosTileParams, TapIo, TapTileParams, TileKind};
-use atoll::route::{GreedyRouter, ViaMaker};
+use crate::params::{check_positive, setters, ParamsError};
+use crate::route::RouterKind;
+use crate::tiles::{
+    MosKind, MosLengthRules, MosTileParams, TapIo, TapTileParams, TileKind, WidthSpec,
+};
+use atoll::route::ViaMaker;
 use atoll::{IoBuilder, Orientation, Tile, TileBuilder};
 use serde::{Deserialize, Serialize};
 use std::any::Any;
@@ -10,12 +14,16 @@ use substrate::arcstr::ArcStr;
 use substrate::block::Block;
 use substrate::error::Result;
 use substrate::geometry::align::AlignMode;
+use substrate::geometry::point::Point;
+use substrate::geometry::transform::{TransformMut, Transformation, TranslateMut};
 use substrate::io::{InOut, Input, Io, MosIo, MosIoSchematic, Output, Signal};
-use substrate::layout::ExportsLayoutData;
+use substrate::layout::{ExportsLayoutData, LayoutData};
 use substrate::pdk::Pdk;
 use substrate::schematic::schema::Schema;
 use substrate::schematic::ExportsNestedData;
 
+pub mod tb;
+
 /// The interface to a buffer.
 #[derive(Debug, Default, Clone, Io)]
 pub struct BufferIo {
@@ -37,19 +45,121 @@ pub struct InverterParams {
     /// The PMOS device flavor.
     pub pmos_kind: MosKind,
     /// The width of the NMOS.
-    pub nmos_w: i64,
+    pub nmos_w: WidthSpec,
     /// The width of the PMOS.
-    pub pmos_w: i64,
+    pub pmos_w: WidthSpec,
+    /// The channel length of the NMOS, or the technology minimum if `None`.
+    #[serde(default)]
+    pub nmos_l: Option<i64>,
+    /// The channel length of the PMOS, or the technology minimum if `None`.
+    #[serde(default)]
+    pub pmos_l: Option<i64>,
+}
+
+impl InverterParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> InverterParamsBuilder {
+        InverterParamsBuilder::default()
+    }
+
+    /// Returns an error if the parameters would be rejected by
+    /// [`InverterParamsBuilder::build`].
+    pub fn validate(&self) -> std::result::Result<(), ParamsError> {
+        InverterParamsBuilder { params: *self }.build().map(|_| ())
+    }
+
+    /// Returns an error if the MOS tiles of technology `T` do not support one of the
+    /// channel lengths.
+    pub fn check_lengths<T: MosLengthRules>(&self) -> std::result::Result<(), ParamsError> {
+        T::check_length("nmos_l", self.nmos_l)?;
+        T::check_length("pmos_l", self.pmos_l)
+    }
+
+    /// Snaps both MOS widths with `snap`, returning parameters that record the
+    /// achieved widths.
+    pub fn snapped(self, snap: impl Fn(TileKind, WidthSpec) -> WidthSpec) -> Self {
+        Self {
+            nmos_w: snap(TileKind::N, self.nmos_w),
+            pmos_w: snap(TileKind::P, self.pmos_w),
+            ..self
+        }
+    }
+}
+
+impl TranslateMut for InverterParams {
+    fn translate_mut(&mut self, _p: Point) {}
+}
+
+impl TransformMut for InverterParams {
+    fn transform_mut(&mut self, _trans: Transformation) {}
+}
+
+/// A builder for [`InverterParams`].
+///
+/// Defaults to 1 um nominal-Vt devices, which are appropriate for SKY130.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct InverterParamsBuilder {
+    params: InverterParams,
+}
+
+impl Default for InverterParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: InverterParams {
+                nmos_kind: MosKind::Nom,
+                pmos_kind: MosKind::Nom,
+                nmos_w: WidthSpec::Nm(1_000),
+                pmos_w: WidthSpec::Nm(1_000),
+                nmos_l: None,
+                pmos_l: None,
+            },
+        }
+    }
+}
+
+impl InverterParamsBuilder {
+    setters! {
+        /// Sets the NMOS device flavor.
+        nmos_kind: MosKind,
+        /// Sets the PMOS device flavor.
+        pmos_kind: MosKind,
+        /// Sets the width of the NMOS.
+        nmos_w: impl Into<WidthSpec>,
+        /// Sets the width of the PMOS.
+        pmos_w: impl Into<WidthSpec>,
+        /// Sets the channel length of the NMOS.
+        nmos_l: Option<i64>,
+        /// Sets the channel length of the PMOS.
+        pmos_l: Option<i64>,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<InverterParams, ParamsError> {
+        check_positive("nmos_w", self.params.nmos_w.value())?;
+        check_positive("pmos_w", self.params.pmos_w.value())?;
+        for (field, value) in [
+            ("nmos_l", self.params.nmos_l),
+            ("pmos_l", self.params.pmos_l),
+        ] {
+            value.map_or(Ok(()), |value| check_positive(field, value))?;
+        }
+        Ok(self.params)
+    }
 }
 
 /// An inverter implementation.
-pub trait InverterImpl<PDK: Pdk + Schema> {
+///
+/// Inverters reject channel lengths that the [`MosLengthRules`] of the implementation
+/// do not support before generating any tiles.
+pub trait InverterImpl<PDK: Pdk + Schema>: MosLengthRules {
     /// The MOS tile used to implement the pull-up and pull-down transistors.
     type MosTile: Tile<PDK> + Block<Io = MosIo> + Clone;
     /// The tap tile.
     type TapTile: Tile<PDK> + Block<Io = TapIo> + Clone;
     /// A PDK-specific via maker.
     type ViaMaker: ViaMaker<PDK>;
+    /// The router used by inverters and buffers.
+    const ROUTER: RouterKind = RouterKind::Greedy;
 
     /// Creates an instance of the MOS tile.
     fn mos(params: MosTileParams) -> Self::MosTile;
@@ -57,6 +167,10 @@ pub trait InverterImpl<PDK: Pdk + Schema> {
     fn tap(params: TapTileParams) -> Self::TapTile;
     /// Creates a PDK-specific via maker.
     fn via_maker() -> Self::ViaMaker;
+    /// Snaps a requested MOS width to a legal value, returning the achieved width.
+    fn snap_width(_kind: TileKind, width: WidthSpec) -> WidthSpec {
+        width
+    }
     /// Additional layout hooks to run after the inverter layout is complete.
     fn post_layout_hooks(_cell: &mut TileBuilder<'_, PDK>) -> Result<()> {
         Ok(())
@@ -76,6 +190,11 @@ impl<T> Inverter<T> {
     pub fn new(params: InverterParams) -> Self {
         Self(params, PhantomData)
     }
+
+    /// The inverter parameters.
+    pub fn params(&self) -> InverterParams {
+        self.0
+    }
 }
 
 impl<T: Any> Block for Inverter<T> {
@@ -85,9 +204,8 @@ impl<T: Any> Block for Inverter<T> {
         substrate::arcstr::literal!("inverter")
     }
 
-    // todo: include parameters in name
     fn name(&self) -> ArcStr {
-        substrate::arcstr::literal!("inverter")
+        crate::block_name("inverter", &self.0)
     }
 
     fn io(&self) -> Self::Io {
@@ -99,11 +217,19 @@ impl<T: Any> ExportsNestedData for Inverter<T> {
     type NestedData = ();
 }
 
+/// Layout data returned by the [`Inverter`] layout generator.
+#[derive(LayoutData)]
+pub struct InverterLayoutData {
+    /// The parameters with both MOS widths replaced by the achieved widths.
+    pub params: InverterParams,
+}
+
 impl<T: Any> ExportsLayoutData for Inverter<T> {
-    type LayoutData = ();
+    type LayoutData = InverterLayoutData;
 }
 
 impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for Inverter<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
     fn tile<'a>(
         &self,
         io: IoBuilder<'a, Self>,
@@ -112,8 +238,14 @@ impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for Invert
         <Self as ExportsNestedData>::NestedData,
         <Self as ExportsLayoutData>::LayoutData,
     )> {
-        let nmos_params = MosTileParams::new(self.0.nmos_kind, TileKind::N, self.0.nmos_w);
-        let pmos_params = MosTileParams::new(self.0.pmos_kind, TileKind::P, self.0.pmos_w);
+        self.0
+            .check_lengths::<T>()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let snapped = self.0.snapped(T::snap_width);
+        let nmos_params = MosTileParams::new(self.0.nmos_kind, TileKind::N, snapped.nmos_w)
+            .with_length(self.0.nmos_l);
+        let pmos_params = MosTileParams::new(self.0.pmos_kind, TileKind::P, snapped.pmos_w)
+            .with_length(self.0.pmos_l);
 
         let mut nmos = cell
             .generate_connected(
@@ -158,7 +290,7 @@ impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for Invert
         let ntap = cell.draw(ntap)?;
 
         cell.set_top_layer(1);
-        cell.set_router(GreedyRouter::new());
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
         cell.set_via_maker(T::via_maker());
 
         io.layout.din.merge(nmos.layout.io().g);
@@ -170,7 +302,7 @@ impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for Invert
 
         T::post_layout_hooks(cell)?;
 
-        Ok(((), ()))
+        Ok(((), InverterLayoutData { params: snapped }))
     }
 }
 
@@ -187,6 +319,11 @@ impl<T> Buffer<T> {
     pub fn new(params: InverterParams) -> Self {
         Self(params, PhantomData)
     }
+
+    /// The parameters of each inverter in the buffer.
+    pub fn params(&self) -> InverterParams {
+        self.0
+    }
 }
 
 impl<T: Any> Block for Buffer<T> {
@@ -196,9 +333,8 @@ impl<T: Any> Block for Buffer<T> {
         substrate::arcstr::literal!("buffer")
     }
 
-    // todo: include parameters in name
     fn name(&self) -> ArcStr {
-        substrate::arcstr::literal!("buffer")
+        crate::block_name("buffer", &self.0)
     }
 
     fn io(&self) -> Self::Io {
@@ -215,6 +351,7 @@ impl<T: Any> ExportsLayoutData for Buffer<T> {
 }
 
 impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for Buffer<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
     fn tile<'a>(
         &self,
         io: IoBuilder<'a, Self>,
@@ -250,7 +387,7 @@ impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for Buffer
         let inv2 = cell.draw(inv2)?;
 
         cell.set_top_layer(1);
-        cell.set_router(GreedyRouter::new());
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
         cell.set_via_maker(T::via_maker());
 
         io.layout.vdd.merge(inv1.layout.io().vdd);
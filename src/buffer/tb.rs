@@ -0,0 +1,313 @@
+//! Buffer testbenches.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::{Pulse, Vsource};
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Node, Terminal};
+use substrate::io::{Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::primitives::Capacitor;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::waveform::{EdgeDir, TimeWaveform, WaveformRef};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::buffer::BufferIo;
+
+/// The time at which the input first begins to rise.
+const START: Decimal = dec!(1e-9);
+/// The time the input is held at each level.
+const HALF_PERIOD: Decimal = dec!(5e-9);
+
+/// A transient testbench that drives the input of a buffer with a ramp of a given slew
+/// and measures the delay, output transition, and switching energy into a capacitive load.
+///
+/// The input rises and then falls once. Each output edge is allowed 5 ns to settle.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct BufferTimingTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: T,
+    /// The 10-90% input transition time, in seconds.
+    pub slew: Decimal,
+    /// The output load capacitance, in farads.
+    pub load: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> BufferTimingTb<T, PDK, C> {
+    /// Creates a new [`BufferTimingTb`].
+    pub fn new(dut: T, slew: Decimal, load: Decimal, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            slew,
+            load,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for BufferTimingTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("buffer_timing_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("buffer_timing_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes and terminals measured by [`BufferTimingTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct BufferTimingTbNodes {
+    din: Node,
+    dout: Node,
+    vdd_src: Terminal,
+    din_src: Terminal,
+}
+
+impl<T, PDK, C> ExportsNestedData for BufferTimingTb<T, PDK, C>
+where
+    BufferTimingTb<T, PDK, C>: Block,
+{
+    type NestedData = BufferTimingTbNodes;
+}
+
+impl<T: Block<Io = BufferIo> + Schematic<PDK> + Clone, PDK: Schema, C: Copy> Schematic<Spectre>
+    for BufferTimingTb<T, PDK, C>
+where
+    BufferTimingTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let din = cell.signal("din", Signal);
+        let dout = cell.signal("dout", Signal);
+        let vdd = cell.signal("vdd", Signal);
+
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
+        cell.connect(dut.io().din, din);
+        cell.connect(dut.io().dout, dout);
+        cell.connect(dut.io().vdd, vdd);
+        cell.connect(dut.io().vss, io.vss);
+
+        let vdd_src = cell.instantiate(Vsource::dc(self.pvt.voltage));
+        cell.connect(vdd_src.io().p, vdd);
+        cell.connect(vdd_src.io().n, io.vss);
+
+        // Extrapolate the 10-90% slew to a full-swing ramp.
+        let ramp = self.slew / dec!(0.8);
+        let din_src = cell.instantiate(Vsource::pulse(Pulse {
+            val0: dec!(0),
+            val1: self.pvt.voltage,
+            period: Some(dec!(2) * HALF_PERIOD),
+            width: Some(HALF_PERIOD - ramp),
+            delay: Some(START),
+            rise: Some(ramp),
+            fall: Some(ramp),
+        }));
+        cell.connect(din_src.io().p, din);
+        cell.connect(din_src.io().n, io.vss);
+
+        cell.instantiate_connected(
+            Capacitor::new(self.load),
+            TwoTerminalIoSchematic { p: dout, n: io.vss },
+        );
+
+        Ok(BufferTimingTbNodes {
+            din,
+            dout,
+            vdd_src: vdd_src.io().p,
+            din_src: din_src.io().p,
+        })
+    }
+}
+
+/// The resulting waveforms of a [`BufferTimingTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct BufferTimingSim {
+    t: tran::Time,
+    din: tran::Voltage,
+    dout: tran::Voltage,
+    idd: tran::Current,
+    iin: tran::Current,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, BufferTimingSim> for BufferTimingTb<T, PDK, C>
+where
+    BufferTimingTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <BufferTimingSim as FromSaved<Spectre, Tran>>::SavedKey {
+        BufferTimingSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            din: tran::Voltage::save(ctx, cell.data().din, opts),
+            dout: tran::Voltage::save(ctx, cell.data().dout, opts),
+            idd: tran::Current::save(ctx, &cell.data().vdd_src, opts),
+            iin: tran::Current::save(ctx, &cell.data().din_src, opts),
+        }
+    }
+}
+
+/// The timing and energy of a buffer for one input slew and output load.
+///
+/// Rise and fall refer to the direction of the output transition.
+/// Times are in seconds, energies in joules, and capacitances in farads.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BufferTiming {
+    /// The delay from the 50% point of the input to the 50% point of a rising output.
+    pub rise_delay: f64,
+    /// The delay from the 50% point of the input to the 50% point of a falling output.
+    pub fall_delay: f64,
+    /// The 10-90% transition time of a rising output.
+    pub rise_transition: f64,
+    /// The 90-10% transition time of a falling output.
+    pub fall_transition: f64,
+    /// The energy drawn from VDD during a rising output transition,
+    /// excluding the energy stored on the load.
+    pub rise_energy: f64,
+    /// The energy drawn from VDD during a falling output transition.
+    pub fall_energy: f64,
+    /// The input capacitance, estimated from the charge drawn from the input source
+    /// while the input rises.
+    pub input_cap: f64,
+}
+
+impl BufferTimingSim {
+    fn timing(&self, vdd: f64, load: f64) -> BufferTiming {
+        let din = WaveformRef::new(&self.t, &self.din);
+        let dout = WaveformRef::new(&self.t, &self.dout);
+        let start = START.to_f64().unwrap();
+        let half = HALF_PERIOD.to_f64().unwrap();
+        let mut timing = BufferTiming {
+            rise_delay: f64::NAN,
+            fall_delay: f64::NAN,
+            rise_transition: f64::NAN,
+            fall_transition: f64::NAN,
+            rise_energy: f64::NAN,
+            fall_energy: f64::NAN,
+            input_cap: integrate(&self.t, &self.iin, start, start + half).abs() / vdd,
+        };
+
+        for (edge_start, input) in [(start, EdgeDir::Rising), (start + half, EdgeDir::Falling)] {
+            let in_window = |t: f64| t >= edge_start && t < edge_start + half;
+            let crossing = |wav: &WaveformRef<'_>, frac: f64, dir: EdgeDir| {
+                wav.edges(frac * vdd)
+                    .find(|e| e.dir() == dir && in_window(e.t()))
+                    .map(|e| e.t())
+            };
+            let Some(t_in) = crossing(&din, 0.5, input) else {
+                continue;
+            };
+            let Some((t_out, output)) = dout
+                .edges(0.5 * vdd)
+                .find(|e| in_window(e.t()))
+                .map(|e| (e.t(), e.dir()))
+            else {
+                continue;
+            };
+            let t10 = crossing(&dout, 0.1, output);
+            let t90 = crossing(&dout, 0.9, output);
+            let transition = match (t10, t90) {
+                (Some(t10), Some(t90)) => (t90 - t10).abs(),
+                _ => f64::NAN,
+            };
+            // Power is delivered when current flows out of the positive terminal of the supply.
+            let energy = -vdd * integrate(&self.t, &self.idd, edge_start, edge_start + half);
+            if output == EdgeDir::Rising {
+                timing.rise_delay = t_out - t_in;
+                timing.rise_transition = transition;
+                timing.rise_energy = energy - load * vdd * vdd;
+            } else {
+                timing.fall_delay = t_out - t_in;
+                timing.fall_transition = transition;
+                timing.fall_energy = energy;
+            }
+        }
+        timing
+    }
+}
+
+/// Integrates `y` over `t` between `start` and `stop` using the trapezoidal rule.
+pub(crate) fn integrate(t: &[f64], y: &[f64], start: f64, stop: f64) -> f64 {
+    t.windows(2)
+        .zip(y.windows(2))
+        .filter(|(t, _)| t[0] >= start && t[1] <= stop)
+        .map(|(t, y)| (t[1] - t[0]) * (y[0] + y[1]) / 2.)
+        .sum()
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for BufferTimingTb<T, PDK, C>
+where
+    BufferTimingTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = BufferTiming;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: BufferTimingSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: START + dec!(2) * HALF_PERIOD,
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+        wav.timing(
+            self.pvt.voltage.to_f64().unwrap(),
+            self.load.to_f64().unwrap(),
+        )
+    }
+}
@@ -0,0 +1,179 @@
+//! A persistent on-disk cache of generated files.
+//!
+//! Substrate only caches generated cells within a single context, so every run of the CLI
+//! or the test suite regenerates every cell. A [`GenerationCache`] stores one exported file,
+//! such as a GDS layout or netlist, per generated block, keyed by a hash of the block, the
+//! [generator fingerprint](crate::GENERATOR_FINGERPRINT), the
+//! [`PdkFlavor`](crate::PdkFlavor), and the [`GenerationOptions`](crate::GenerationOptions)
+//! of the context. Files for unchanged blocks are then copied from the cache rather than
+//! regenerated, and any change to the generator sources or dependencies invalidates them.
+//!
+//! [`generate_from_config`](crate::config::generate_from_config) caches the TX and RX
+//! modules separately, so a change that only affects one of them reuses the other.
+//! A block that misses the cache is generated in full, including its subcells, since
+//! ATOLL cannot place a tile from a previously exported layout.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use substrate::block::Block;
+use substrate::context::{Context, PdkContext};
+use substrate::layout::Layout;
+use substrate::pdk::Pdk;
+
+/// The environment variable that sets the directory used by [`GenerationCache::from_env`].
+pub const CACHE_DIR_ENV: &str = "UCIEANALOG_CACHE_DIR";
+
+/// An error produced while generating a cached file.
+#[derive(Debug, thiserror::Error)]
+pub enum CacheError<E> {
+    /// An I/O error while reading or writing the cache.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file could not be generated.
+    #[error("{0}")]
+    Generation(E),
+}
+
+/// A directory of generated files keyed by block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GenerationCache {
+    dir: PathBuf,
+}
+
+impl GenerationCache {
+    /// Creates a cache stored in `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Creates a cache stored in the directory given by [`CACHE_DIR_ENV`], if it is set.
+    pub fn from_env() -> Option<Self> {
+        std::env::var_os(CACHE_DIR_ENV).map(Self::new)
+    }
+
+    /// The cache directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the cache key of the file `artifact` generated from `block` in `ctx`.
+    ///
+    /// `artifact` distinguishes different files generated from the same block,
+    /// such as `"gds"` or `"netlist"`. The key includes the type name of the block,
+    /// which names its technology, and the PDK flavor of `ctx`, so that identical
+    /// parameters in different technologies or PDK flavors map to different files.
+    pub fn key<B: Block + Serialize>(ctx: &Context, block: &B, artifact: &str) -> String {
+        let data = serde_json::to_vec(&(
+            crate::GENERATOR_FINGERPRINT,
+            B::id().as_str(),
+            std::any::type_name::<B>(),
+            crate::pdk_flavor(ctx).map(|flavor| flavor.0),
+            artifact,
+            crate::generation_options(ctx),
+            block,
+        ))
+        .expect("failed to serialize block");
+        format!("{:016x}", crate::stable_hash(&data))
+    }
+
+    /// Writes the file with key `key` to `path`.
+    ///
+    /// On a cache hit, the cached file is copied to `path`. Otherwise, `generate` writes
+    /// the file to `path` and the result is added to the cache.
+    /// Returns `true` on a cache hit.
+    pub fn get_or_write<E>(
+        &self,
+        key: &str,
+        path: impl AsRef<Path>,
+        generate: impl FnOnce(&Path) -> Result<(), E>,
+    ) -> Result<bool, CacheError<E>> {
+        let path = path.as_ref();
+        let cached = self.dir.join(key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if cached.is_file() {
+            tracing::debug!(key, "reusing cached file");
+            std::fs::copy(&cached, path)?;
+            return Ok(true);
+        }
+
+        generate(path).map_err(CacheError::Generation)?;
+        // Copy to a temporary file first so that concurrent readers never see a partial file.
+        std::fs::create_dir_all(&self.dir)?;
+        let tmp = self.dir.join(format!("{key}.{}.tmp", std::process::id()));
+        std::fs::copy(path, &tmp)?;
+        std::fs::rename(&tmp, &cached)?;
+        Ok(false)
+    }
+
+    /// Writes the layout of `block` to `path`, reusing a cached GDS file if one exists.
+    pub fn write_layout<PDK: Pdk, B: Block + Serialize + Layout<PDK>>(
+        &self,
+        ctx: &PdkContext<PDK>,
+        block: B,
+        path: impl AsRef<Path>,
+    ) -> Result<bool, CacheError<String>> {
+        let key = Self::key(ctx, &block, "gds");
+        self.get_or_write(&key, path, |path| {
+            ctx.write_layout(block, path)
+                .map_err(|e| format!("failed to write layout: {e:?}"))
+        })
+    }
+
+    /// Removes every file from the cache.
+    pub fn clear(&self) -> std::io::Result<()> {
+        match std::fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use atoll::TileWrapper;
+
+    use super::*;
+    use crate::buffer::{Inverter, InverterParams};
+    use crate::tech::sky130::Sky130Ucie;
+    use crate::PdkFlavor;
+
+    #[test]
+    fn reuses_cached_files() {
+        let root = std::env::temp_dir().join(format!("ucieanalog_cache_{}", std::process::id()));
+        let cache = GenerationCache::new(root.join("cache"));
+        let out = root.join("out.txt");
+
+        let write = |path: &Path| std::fs::write(path, "generated");
+        assert!(!cache.get_or_write("key", &out, write).unwrap());
+        std::fs::remove_file(&out).unwrap();
+        let hit = cache
+            .get_or_write("key", &out, |_| -> std::io::Result<()> {
+                panic!("cached file should be reused")
+            })
+            .unwrap();
+        assert!(hit);
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "generated");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn keys_distinguish_pdk_flavors() {
+        let block = TileWrapper::new(Inverter::<Sky130Ucie>::new(
+            InverterParams::builder().build().unwrap(),
+        ));
+        let key = |flavor| {
+            let ctx = Context::builder().install(PdkFlavor(flavor)).build();
+            GenerationCache::key(&ctx, &block, "gds")
+        };
+        assert_eq!(key("sky130-open"), key("sky130-open"));
+        assert_ne!(key("sky130-open"), key("sky130-commercial"));
+        assert_ne!(
+            key("sky130-open"),
+            GenerationCache::key(&Context::builder().build(), &block, "gds")
+        );
+    }
+}
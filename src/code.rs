@@ -0,0 +1,176 @@
+//! Digital control codes.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// An error produced when constructing or using a [`ThermometerCode`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ThermometerCodeError {
+    /// The level exceeds the number of lines.
+    #[error("level {level} exceeds width {width}")]
+    OutOfRange {
+        /// The requested level.
+        level: usize,
+        /// The number of lines.
+        width: usize,
+    },
+    /// An enabled line follows a disabled line.
+    #[error("line {index} is enabled after a disabled line")]
+    NotMonotonic {
+        /// The index of the first enabled line after a disabled line.
+        index: usize,
+    },
+    /// The code does not have the width of the lines it drives.
+    #[error("code has width {actual}, expected {expected}")]
+    WidthMismatch {
+        /// The number of lines being driven.
+        expected: usize,
+        /// The width of the code.
+        actual: usize,
+    },
+}
+
+/// A thermometer code driving `width` control lines, of which the first `level` are
+/// enabled.
+///
+/// Serializes as the level of each line, first line first.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[serde(try_from = "Vec<bool>", into = "Vec<bool>")]
+pub struct ThermometerCode {
+    level: usize,
+    width: usize,
+}
+
+impl ThermometerCode {
+    /// Creates a new [`ThermometerCode`] that enables the first `level` of `width`
+    /// lines.
+    pub fn new(level: usize, width: usize) -> Result<Self, ThermometerCodeError> {
+        if level > width {
+            return Err(ThermometerCodeError::OutOfRange { level, width });
+        }
+        Ok(Self { level, width })
+    }
+
+    /// A code that enables all `width` lines.
+    pub fn all(width: usize) -> Self {
+        Self {
+            level: width,
+            width,
+        }
+    }
+
+    /// A code that disables all `width` lines.
+    pub fn none(width: usize) -> Self {
+        Self { level: 0, width }
+    }
+
+    /// Parses a code from the level of each line, first line first.
+    pub fn from_bits(bits: &[bool]) -> Result<Self, ThermometerCodeError> {
+        let level = bits.iter().take_while(|&&bit| bit).count();
+        if let Some(index) = bits[level..].iter().position(|&bit| bit) {
+            return Err(ThermometerCodeError::NotMonotonic {
+                index: level + index,
+            });
+        }
+        Ok(Self {
+            level,
+            width: bits.len(),
+        })
+    }
+
+    /// The number of enabled lines.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// The number of lines.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Whether line `index` is enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not less than the width of the code.
+    pub fn is_enabled(&self, index: usize) -> bool {
+        assert!(
+            index < self.width,
+            "line {index} out of range for width {}",
+            self.width
+        );
+        index < self.level
+    }
+
+    /// The level of each line, first line first.
+    pub fn bits(&self) -> Vec<bool> {
+        (0..self.width).map(|index| index < self.level).collect()
+    }
+
+    /// Returns an error unless this code drives exactly `width` lines.
+    pub fn check_width(&self, width: usize) -> Result<(), ThermometerCodeError> {
+        if self.width == width {
+            Ok(())
+        } else {
+            Err(ThermometerCodeError::WidthMismatch {
+                expected: width,
+                actual: self.width,
+            })
+        }
+    }
+}
+
+impl TryFrom<Vec<bool>> for ThermometerCode {
+    type Error = ThermometerCodeError;
+
+    fn try_from(bits: Vec<bool>) -> Result<Self, Self::Error> {
+        Self::from_bits(&bits)
+    }
+}
+
+impl From<ThermometerCode> for Vec<bool> {
+    fn from(code: ThermometerCode) -> Self {
+        code.bits()
+    }
+}
+
+impl Display for ThermometerCode {
+    /// Formats the code as one digit per line, first line first.
+    ///
+    /// For example, level 2 of width 4 is `1100`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for bit in self.bits() {
+            write!(f, "{}", bit as u8)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thermometer_code_checks_validity() {
+        let code = ThermometerCode::new(2, 4).unwrap();
+        assert_eq!(code.bits(), vec![true, true, false, false]);
+        assert_eq!(code.to_string(), "1100");
+        assert_eq!(ThermometerCode::from_bits(&code.bits()), Ok(code));
+        assert_eq!(
+            ThermometerCode::new(5, 4),
+            Err(ThermometerCodeError::OutOfRange { level: 5, width: 4 })
+        );
+        assert_eq!(
+            ThermometerCode::from_bits(&[true, false, true]),
+            Err(ThermometerCodeError::NotMonotonic { index: 2 })
+        );
+        assert_eq!(
+            code.check_width(3),
+            Err(ThermometerCodeError::WidthMismatch {
+                expected: 3,
+                actual: 4
+            })
+        );
+        assert_eq!(ThermometerCode::none(3).bits(), vec![false; 3]);
+    }
+}
@@ -0,0 +1,527 @@
+//! Configuration-driven generation of a complete PHY.
+//!
+//! A [`PhyConfig`] is typically checked in as a TOML or JSON file so that
+//! the exact configuration used for a tapeout can be regenerated.
+
+use std::any::Any;
+use std::path::{Path, PathBuf};
+
+use atoll::TileWrapper;
+use serde::{Deserialize, Serialize};
+use substrate::block::Block;
+use substrate::context::PdkContext;
+use substrate::layout::Layout;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+
+use crate::buffer::InverterParams;
+use crate::cache::GenerationCache;
+use crate::driver::DriverParams;
+use crate::esd::RxEsdParams;
+use crate::lane::{
+    AcCouplingParams, ClockLaneParams, ControlRxLaneParams, ControlTxLaneParams, LaneImpl,
+    LaneRole, RxModule, RxModuleParams, TxModule, TxModuleParams,
+};
+use crate::params::ParamsError;
+use crate::strongarm::StrongArmParams;
+use crate::tiles::MosLengthRules;
+use crate::GenerationOptions;
+
+/// An error produced while loading a configuration or generating from it.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// An I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// A JSON configuration could not be parsed.
+    #[error("failed to parse JSON configuration: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A TOML configuration could not be parsed.
+    #[error("failed to parse TOML configuration: {0}")]
+    Toml(#[from] toml::de::Error),
+    /// The configuration file extension was not `.toml` or `.json`.
+    #[error("unrecognized configuration file extension: {0:?}")]
+    UnknownFormat(PathBuf),
+    /// The lane count is not supported by the package type.
+    #[error("{package:?} packages do not support {lanes} lanes")]
+    InvalidLanes {
+        /// The requested lane count.
+        lanes: usize,
+        /// The package type.
+        package: PackageType,
+    },
+    /// The context was built with generation options other than those of the configuration.
+    #[error("the context generates with {context:?}, but the configuration requests {config:?}")]
+    GenerationOptions {
+        /// The options requested by the configuration.
+        config: GenerationOptions,
+        /// The options installed in the context.
+        context: GenerationOptions,
+    },
+    /// The lanes enable more driver segments than the driver has.
+    #[error("cannot enable {enabled} of {segments} driver segments")]
+    EnabledSegments {
+        /// The requested number of enabled segments.
+        enabled: usize,
+        /// The number of driver segments.
+        segments: usize,
+    },
+    /// The technology cannot build lanes that run at the configured data rate.
+    #[error("lanes run at most at {max:?} in this technology, but the configuration requests {data_rate:?}")]
+    DataRate {
+        /// The configured data rate.
+        data_rate: DataRate,
+        /// The fastest data rate supported by the technology.
+        max: DataRate,
+    },
+    /// A block parameter is not supported by the technology.
+    #[error("invalid block parameters: {0}")]
+    Params(#[from] ParamsError),
+    /// A block failed to generate.
+    #[error("failed to generate {role}: {message}")]
+    Generation {
+        /// The role of the block that failed.
+        role: &'static str,
+        /// A description of the failure.
+        message: String,
+    },
+}
+
+/// The result type returned by configuration functions.
+pub type Result<T> = std::result::Result<T, ConfigError>;
+
+/// A UCIe data rate.
+///
+/// Data rates are ordered from slowest to fastest.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DataRate {
+    /// 4 GT/s.
+    Gt4,
+    /// 8 GT/s.
+    Gt8,
+    /// 12 GT/s.
+    Gt12,
+    /// 16 GT/s.
+    Gt16,
+    /// 24 GT/s.
+    Gt24,
+    /// 32 GT/s.
+    Gt32,
+}
+
+impl DataRate {
+    /// The data rate in bits per second per lane.
+    pub fn bits_per_second(&self) -> f64 {
+        let gt = match self {
+            DataRate::Gt4 => 4,
+            DataRate::Gt8 => 8,
+            DataRate::Gt12 => 12,
+            DataRate::Gt16 => 16,
+            DataRate::Gt24 => 24,
+            DataRate::Gt32 => 32,
+        };
+        gt as f64 * 1e9
+    }
+
+    /// The unit interval, in seconds.
+    pub fn unit_interval(&self) -> f64 {
+        1. / self.bits_per_second()
+    }
+}
+
+/// A UCIe package type.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum PackageType {
+    /// A standard package, with x16 or degraded x8 modules.
+    Standard,
+    /// An advanced package, with x64 or x32 modules.
+    Advanced,
+}
+
+impl PackageType {
+    /// The data lane counts supported by a module in this package.
+    pub fn lane_counts(&self) -> &'static [usize] {
+        match self {
+            PackageType::Standard => &[8, 16],
+            PackageType::Advanced => &[32, 64],
+        }
+    }
+}
+
+/// Parameters of the receiver sampler.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct SamplerConfig {
+    /// The StrongARM latch parameters.
+    pub strongarm: StrongArmParams,
+    /// The output buffer parameters.
+    pub buffer: InverterParams,
+}
+
+/// Parameters of the forwarded clock, valid, and track lanes.
+///
+/// The lanes reuse the data lane driver and sampler so that they are pitch-matched
+/// to the data lanes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct LaneConfig {
+    /// The number of driver segments enabled in the clock, valid, and track transmit lanes.
+    pub enabled_segments: usize,
+    /// The parameters of each inverter in the transmit lane input buffers.
+    pub tx_buf: InverterParams,
+    /// The AC-coupling network of the valid and track receive lanes, if any.
+    #[serde(default)]
+    pub ac_coupling: Option<AcCouplingParams>,
+    /// The ESD network of the valid and track receive lanes, if any.
+    #[serde(default)]
+    pub esd: Option<RxEsdParams>,
+}
+
+/// Parameters of each block in a lane.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct BlockConfig {
+    /// The transmit driver parameters.
+    pub driver: DriverParams,
+    /// The receive sampler parameters.
+    pub sampler: SamplerConfig,
+    /// The forwarded clock, valid, and track lane parameters.
+    pub lanes: LaneConfig,
+}
+
+/// The configuration of a complete PHY module.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct PhyConfig {
+    /// The number of data lanes.
+    pub lanes: usize,
+    /// The data rate.
+    pub data_rate: DataRate,
+    /// The package type.
+    pub package: PackageType,
+    /// The per-block parameters.
+    pub blocks: BlockConfig,
+    /// Options applied to every generator.
+    #[serde(default)]
+    pub generation: GenerationOptions,
+}
+
+impl PhyConfig {
+    /// Loads a configuration from a `.toml` or `.json` file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        let config: Self = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents)?,
+            Some("json") => serde_json::from_str(&contents)?,
+            _ => return Err(ConfigError::UnknownFormat(path.to_path_buf())),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks that the configuration is consistent.
+    pub fn validate(&self) -> Result<()> {
+        if !self.package.lane_counts().contains(&self.lanes) {
+            return Err(ConfigError::InvalidLanes {
+                lanes: self.lanes,
+                package: self.package,
+            });
+        }
+        let driver = self.blocks.driver;
+        let segments = driver.num_segments * driver.banks;
+        if self.blocks.lanes.enabled_segments > segments {
+            return Err(ConfigError::EnabledSegments {
+                enabled: self.blocks.lanes.enabled_segments,
+                segments,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that the MOS tiles of technology `T` support the channel lengths of
+    /// every block.
+    pub fn check_lengths<T: MosLengthRules>(&self) -> Result<()> {
+        let BlockConfig {
+            driver,
+            sampler,
+            lanes,
+        } = self.blocks;
+        driver.unit.check_lengths::<T>()?;
+        sampler.strongarm.check_lengths::<T>()?;
+        sampler.buffer.check_lengths::<T>()?;
+        lanes.tx_buf.check_lengths::<T>()?;
+        Ok(())
+    }
+
+    /// The parameters of the transmit half of the module.
+    pub fn tx_module(&self) -> TxModuleParams {
+        let BlockConfig { driver, lanes, .. } = self.blocks;
+        let control = |role| ControlTxLaneParams {
+            role,
+            driver,
+            enabled_segments: lanes.enabled_segments,
+            buf: lanes.tx_buf,
+        };
+        TxModuleParams {
+            data_lanes: self.lanes,
+            driver,
+            clock: ClockLaneParams {
+                driver,
+                enabled_segments: lanes.enabled_segments,
+                clk_buf: lanes.tx_buf,
+            },
+            valid: control(LaneRole::Valid),
+            track: control(LaneRole::Track),
+        }
+    }
+
+    /// The parameters of the receive half of the module.
+    pub fn rx_module(&self) -> RxModuleParams {
+        let BlockConfig { sampler, lanes, .. } = self.blocks;
+        let control = |role| ControlRxLaneParams {
+            role,
+            sampler: sampler.strongarm,
+            buf: sampler.buffer,
+            ac_coupling: lanes.ac_coupling,
+            esd: lanes.esd,
+        };
+        RxModuleParams {
+            data_lanes: self.lanes,
+            sampler: sampler.strongarm,
+            buf: sampler.buffer,
+            valid: control(LaneRole::Valid),
+            track: control(LaneRole::Track),
+        }
+    }
+}
+
+/// A cell produced by [`generate_from_config`].
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct GeneratedCell {
+    /// The role of the cell in the module, such as `"tx_module"`.
+    pub role: String,
+    /// The name of the top cell.
+    pub cell: String,
+    /// The GDS file containing the cell.
+    pub gds: PathBuf,
+}
+
+/// A record of the cells generated from a [`PhyConfig`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PhyManifest {
+    /// The configuration the cells were generated from.
+    pub config: PhyConfig,
+    /// The generated cells.
+    pub cells: Vec<GeneratedCell>,
+}
+
+/// Generates the module described by `config` using the technology `T`.
+///
+/// Writes the transmit and receive halves of the module, each with the configured
+/// number of data lanes followed by the forwarded clock, valid, and track lanes, to
+/// GDS files in `out_dir`, along with a `manifest.json` recording the configuration
+/// and the generated cells. The two halves are generated on separate threads.
+/// `ctx` must be built with the [`PhyConfig::generation`] options; contexts without
+/// installed options generate with the defaults.
+///
+/// Reuses module GDS files from the cache in
+/// [`CACHE_DIR_ENV`](crate::cache::CACHE_DIR_ENV), if it is set.
+/// See [`generate_from_config_with_cache`] to use another cache.
+///
+/// Fails if the lanes of `T` cannot run at the configured data rate.
+pub fn generate_from_config<PDK, T>(
+    ctx: &PdkContext<PDK>,
+    config: &PhyConfig,
+    out_dir: impl AsRef<Path>,
+) -> Result<PhyManifest>
+where
+    PDK: Pdk + Schema,
+    T: LaneImpl<PDK> + MosLengthRules + Any,
+    TileWrapper<TxModule<T>>: Block + Serialize + Layout<PDK>,
+    TileWrapper<RxModule<T>>: Block + Serialize + Layout<PDK>,
+{
+    generate_from_config_with_cache::<PDK, T>(
+        ctx,
+        config,
+        out_dir,
+        GenerationCache::from_env().as_ref(),
+    )
+}
+
+/// Generates the module described by `config` like [`generate_from_config`], reusing
+/// module GDS files from `cache` if it is set.
+///
+/// The TX and RX modules are cached separately, so a configuration change that affects
+/// only one of them regenerates only that module.
+pub fn generate_from_config_with_cache<PDK, T>(
+    ctx: &PdkContext<PDK>,
+    config: &PhyConfig,
+    out_dir: impl AsRef<Path>,
+    cache: Option<&GenerationCache>,
+) -> Result<PhyManifest>
+where
+    PDK: Pdk + Schema,
+    T: LaneImpl<PDK> + MosLengthRules + Any,
+    TileWrapper<TxModule<T>>: Block + Serialize + Layout<PDK>,
+    TileWrapper<RxModule<T>>: Block + Serialize + Layout<PDK>,
+{
+    config.validate()?;
+    config.check_lengths::<T>()?;
+    if config.data_rate > T::MAX_DATA_RATE {
+        return Err(ConfigError::DataRate {
+            data_rate: config.data_rate,
+            max: T::MAX_DATA_RATE,
+        });
+    }
+    let context = crate::generation_options(ctx);
+    if context != config.generation {
+        return Err(ConfigError::GenerationOptions {
+            config: config.generation,
+            context,
+        });
+    }
+    let out_dir = out_dir.as_ref();
+    std::fs::create_dir_all(out_dir)?;
+
+    // The modules are independent top-level cells, so they are generated concurrently.
+    // Substrate's context cache is shared between threads, so the tiles both modules
+    // use are still generated once. Within each module, the horizontal drivers also
+    // generate their distinct banks and units concurrently.
+    let span = tracing::Span::current();
+    let (tx_module, rx_module) = std::thread::scope(|s| {
+        let tx_module = s.spawn(|| {
+            span.in_scope(|| {
+                write_cell(
+                    ctx,
+                    cache,
+                    "tx_module",
+                    TileWrapper::new(TxModule::<T>::new(config.tx_module())),
+                    out_dir,
+                )
+            })
+        });
+        let rx_module = write_cell(
+            ctx,
+            cache,
+            "rx_module",
+            TileWrapper::new(RxModule::<T>::new(config.rx_module())),
+            out_dir,
+        );
+        let tx_module = tx_module.join().unwrap_or_else(|panic| {
+            Err(ConfigError::Generation {
+                role: "tx_module",
+                message: panic_message(panic),
+            })
+        });
+        (tx_module, rx_module)
+    });
+    let cells = vec![tx_module?, rx_module?];
+
+    let manifest = PhyManifest {
+        config: *config,
+        cells,
+    };
+    std::fs::write(
+        out_dir.join("manifest.json"),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+    Ok(manifest)
+}
+
+/// Returns the message of a panic payload returned by [`std::thread::ScopedJoinHandle::join`].
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => match panic.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "generator panicked".to_string(),
+        },
+    }
+}
+
+fn write_cell<PDK: Pdk, B: Block + Serialize + Layout<PDK>>(
+    ctx: &PdkContext<PDK>,
+    cache: Option<&GenerationCache>,
+    role: &'static str,
+    block: B,
+    out_dir: &Path,
+) -> Result<GeneratedCell> {
+    let gds = out_dir.join(format!("{role}.gds"));
+    let cell = block.name().to_string();
+    let written = match cache {
+        Some(cache) => cache
+            .write_layout(ctx, block, &gds)
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+        None => ctx.write_layout(block, &gds).map_err(|e| format!("{e:?}")),
+    };
+    written.map_err(|message| ConfigError::Generation { role, message })?;
+    Ok(GeneratedCell {
+        role: role.to_string(),
+        cell,
+        gds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::driver::{DriverUnitParams, SegmentPlacement};
+
+    use super::*;
+
+    #[test]
+    fn validates_lane_count() {
+        let mut config = PhyConfig {
+            lanes: 16,
+            data_rate: DataRate::Gt16,
+            package: PackageType::Standard,
+            blocks: BlockConfig {
+                driver: DriverParams {
+                    unit: DriverUnitParams::builder().build().unwrap(),
+                    num_segments: 16,
+                    banks: 1,
+                    supply_budget: None,
+                    spare_segment: false,
+                    esd: None,
+                    placement: SegmentPlacement::Sequential,
+                },
+                sampler: SamplerConfig {
+                    strongarm: StrongArmParams::builder().build().unwrap(),
+                    buffer: InverterParams::builder().build().unwrap(),
+                },
+                lanes: LaneConfig {
+                    enabled_segments: 8,
+                    tx_buf: InverterParams::builder().build().unwrap(),
+                    ac_coupling: None,
+                    esd: None,
+                },
+            },
+            generation: GenerationOptions::default(),
+        };
+        assert!(config.validate().is_ok());
+
+        let tx = config.tx_module();
+        assert_eq!(tx.data_lanes, 16);
+        assert_eq!(tx.clock.driver, config.blocks.driver);
+        assert_eq!(tx.clock.enabled_segments, 8);
+        assert_eq!(
+            (tx.valid.role, tx.track.role),
+            (LaneRole::Valid, LaneRole::Track)
+        );
+        let rx = config.rx_module();
+        assert_eq!(rx.data_lanes, 16);
+        assert_eq!(rx.valid.sampler, config.blocks.sampler.strongarm);
+
+        config.blocks.lanes.enabled_segments = 17;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::EnabledSegments {
+                enabled: 17,
+                segments: 16
+            })
+        ));
+        config.blocks.lanes.enabled_segments = 8;
+        config.package = PackageType::Advanced;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidLanes { lanes: 16, .. })
+        ));
+    }
+}
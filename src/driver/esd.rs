@@ -0,0 +1,264 @@
+//! CDM secondary protection between the driver and its pad.
+
+use crate::driver::HorizontalDriverImpl;
+use crate::params::{check_positive, setters, ParamsError};
+use crate::tiles::{ResistorConn, ResistorIoSchematic, TapIoSchematic, TileKind};
+use atoll::{IoBuilder, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::geometry::align::AlignMode;
+use substrate::geometry::bbox::Bbox;
+use substrate::geometry::rect::Rect;
+use substrate::io::{InOut, Io, MosIoSchematic, Signal};
+use substrate::layout::element::Shape;
+use substrate::layout::tracks::RoundingMode;
+use substrate::layout::{ExportsLayoutData, LayoutData};
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+/// The interface to an [`EsdSeries`] protection cell.
+#[derive(Debug, Default, Clone, Io)]
+pub struct EsdSeriesIo {
+    /// The pad side of the series resistor.
+    pub pad: InOut<Signal>,
+    /// The driver side of the series resistor, protected by the secondary clamp.
+    pub core: InOut<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of the [`EsdSeries`] layout generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct EsdSeriesParams {
+    /// The number of legs of the series resistor.
+    pub res_legs: i64,
+    /// The width of the series resistor.
+    pub res_w: i64,
+    /// The length of the series resistor.
+    pub res_l: i64,
+    /// The connection type of the series resistor legs.
+    pub res_conn: ResistorConn,
+    /// The width of the grounded-gate NMOS secondary clamp.
+    pub clamp_w: i64,
+}
+
+impl EsdSeriesParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> EsdSeriesParamsBuilder {
+        EsdSeriesParamsBuilder::default()
+    }
+}
+
+/// A builder for [`EsdSeriesParams`].
+///
+/// Defaults to two 1 um wide, 1 um long resistor legs in parallel and a 4 um clamp,
+/// which adds a few hundred ohms in series with the pad.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct EsdSeriesParamsBuilder {
+    params: EsdSeriesParams,
+}
+
+impl Default for EsdSeriesParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: EsdSeriesParams {
+                res_legs: 2,
+                res_w: 1_000,
+                res_l: 1_000,
+                res_conn: ResistorConn::Parallel,
+                clamp_w: 4_000,
+            },
+        }
+    }
+}
+
+impl EsdSeriesParamsBuilder {
+    setters! {
+        /// Sets the number of legs of the series resistor.
+        res_legs: i64,
+        /// Sets the width of the series resistor.
+        res_w: i64,
+        /// Sets the length of the series resistor.
+        res_l: i64,
+        /// Sets the connection type of the series resistor legs.
+        res_conn: ResistorConn,
+        /// Sets the width of the secondary clamp.
+        clamp_w: i64,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<EsdSeriesParams, ParamsError> {
+        let p = &self.params;
+        for (field, value) in [
+            ("res_legs", p.res_legs),
+            ("res_w", p.res_w),
+            ("res_l", p.res_l),
+            ("clamp_w", p.clamp_w),
+        ] {
+            check_positive(field, value)?;
+        }
+        Ok(self.params)
+    }
+}
+
+/// A series resistor from the pad to the driver output network, with a grounded-gate
+/// NMOS clamp on the driver side.
+///
+/// During a CDM event the resistor limits the current into the driver side while the
+/// clamp holds that node near VSS, protecting the driver and predriver devices behind
+/// it. The resistor sits on top with the clamp and its substrate tap beneath.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct EsdSeries<T>(
+    EsdSeriesParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> EsdSeries<T> {
+    /// Creates a new [`EsdSeries`].
+    pub fn new(params: EsdSeriesParams) -> Self {
+        Self(params, PhantomData)
+    }
+}
+
+impl<T: Any> Block for EsdSeries<T> {
+    type Io = EsdSeriesIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("esd_series")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("esd_series", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for EsdSeries<T> {
+    type NestedData = ();
+}
+
+/// Layout data returned by the [`EsdSeries`] layout generator.
+#[derive(LayoutData)]
+pub struct EsdSeriesLayoutData {
+    /// The `pad` pin geometry located on the driver pin layer.
+    pub pad: Rect,
+}
+
+impl<T: Any> ExportsLayoutData for EsdSeries<T> {
+    type LayoutData = EsdSeriesLayoutData;
+}
+
+impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK> for EsdSeries<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let p = self.0;
+        let nf = T::nf(p.res_legs, p.res_w);
+        let vss = io.schematic.vss;
+
+        let res = cell.generate_connected(
+            T::resistor(p.res_legs, p.res_w, p.res_l, p.res_conn),
+            ResistorIoSchematic {
+                p: io.schematic.pad,
+                n: io.schematic.core,
+                b: io.schematic.vdd,
+            },
+        );
+        let mut clamp = cell.generate_connected(
+            T::mos(TileKind::N, nf, p.clamp_w, None),
+            MosIoSchematic {
+                d: io.schematic.core,
+                g: vss,
+                s: vss,
+                b: vss,
+            },
+        );
+        let mut ptap = cell.generate_connected(T::tap(TileKind::P, nf), TapIoSchematic { x: vss });
+
+        clamp.align_mut(&res, AlignMode::Left, 0);
+        clamp.align_mut(&res, AlignMode::Beneath, 0);
+        ptap.align_mut(&clamp, AlignMode::Left, 0);
+        ptap.align_mut(&clamp, AlignMode::Beneath, 0);
+
+        let res = cell.draw(res)?;
+        cell.draw(clamp)?;
+        let ptap = cell.draw(ptap)?;
+
+        let pin = T::LAYER_MAP.pin;
+        cell.set_top_layer(pin);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        // Bring `pad` up to the pin layer above the resistor so that the driver can
+        // drop its bump vias onto it, as it does onto the `dout` pin of each unit.
+        let center = res.layout.io().p.bbox_rect().center();
+        let x_track = cell.layer_stack.layers[pin - 1]
+            .inner
+            .tracks()
+            .to_track_idx(center.x, RoundingMode::Nearest);
+        let y_track = cell.layer_stack.layers[pin]
+            .inner
+            .tracks()
+            .to_track_idx(center.y, RoundingMode::Nearest);
+        let pad = Rect::from_spans(
+            cell.layer_stack.layers[pin - 1].inner.tracks().get(x_track),
+            cell.layer_stack.layers[pin].inner.tracks().get(y_track),
+        );
+        cell.assign_grid_points(
+            Some(io.schematic.pad),
+            pin,
+            cell.layer_stack
+                .slice(0..pin + 1)
+                .shrink_to_lcm_units(pad)
+                .unwrap(),
+        );
+        cell.layout
+            .draw(Shape::new(cell.layer_stack.layers[pin].id, pad))?;
+
+        io.layout.pad.merge(res.layout.io().p);
+        io.layout.core.merge(res.layout.io().n);
+        io.layout.vdd.merge(res.layout.io().b);
+        io.layout.vss.merge(ptap.layout.io().x);
+
+        T::post_layout_hooks(cell)?;
+
+        Ok(((), EsdSeriesLayoutData { pad }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legs_must_be_positive() {
+        let params = EsdSeriesParams::builder().build().unwrap();
+        assert_eq!(
+            (params.res_legs, params.res_conn),
+            (2, ResistorConn::Parallel)
+        );
+        assert!(matches!(
+            EsdSeriesParams::builder().res_legs(0).build(),
+            Err(ParamsError::NonPositive {
+                field: "res_legs",
+                value: 0
+            })
+        ));
+    }
+}
@@ -1,21 +1,34 @@
 //! Driver layout generators.
 
+pub mod esd;
 pub mod tb;
 
+use crate::analysis::straps::{offset_period_straps, StrapBudget, StrapPlanError, StrapPlanner};
+use crate::analysis::tap_density::needs_tap;
+use crate::driver::esd::{EsdSeries, EsdSeriesIoSchematic, EsdSeriesParams};
+use crate::params::{check_positive, setters, ParamsError};
+use crate::report::SimArtifact;
+use crate::route::RouterKind;
+use crate::tb::probe::{ProbePoints, Probes};
 use crate::tiles::{
-    MosKind, MosTileParams, ResistorConn, ResistorIo, ResistorIoSchematic, ResistorTileParams,
-    TapIo, TapIoSchematic, TapTileParams, TileKind,
+    MosKind, MosLengthRules, MosTileParams, ResistorConn, ResistorIo, ResistorIoSchematic,
+    ResistorTileParams, TapIo, TapIoSchematic, TapTileParams, TileKind, WidthSpec,
 };
+use crate::via::{self, NetClass};
+use crate::wells::WellMerger;
 use atoll::abs::TrackCoord;
 use atoll::grid::AtollLayer;
-use atoll::route::{GreedyRouter, ViaMaker};
+use atoll::route::ViaMaker;
 use atoll::straps::{GreedyStrapper, LayerStrappingParams, StrappingParams};
-use atoll::{IoBuilder, Orientation, Tile, TileBuilder};
+use atoll::{IoBuilder, Orientation, Tile, TileBuilder, TileWrapper};
+use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 use std::any::Any;
 use std::marker::PhantomData;
+use std::ops::Range;
 use substrate::arcstr::ArcStr;
 use substrate::block::Block;
+use substrate::context::PdkContext;
 use substrate::error::Result;
 use substrate::geometry::align::{AlignMode, AlignRect};
 use substrate::geometry::bbox::Bbox;
@@ -25,8 +38,9 @@ use substrate::geometry::point::Point;
 use substrate::geometry::rect::Rect;
 use substrate::geometry::sign::Sign;
 use substrate::geometry::span::Span;
-use substrate::geometry::transform::Translate;
+use substrate::geometry::transform::{TransformMut, Transformation, Translate, TranslateMut};
 use substrate::io::layout::IoShape;
+use substrate::io::schematic::Node;
 use substrate::io::{Array, InOut, Input, Io, MosIo, MosIoSchematic, Output, Signal};
 use substrate::layout::bbox::LayerBbox;
 use substrate::layout::element::Shape;
@@ -55,19 +69,67 @@ pub struct DriverUnitIo {
     pub vss: InOut<Signal>,
 }
 
+/// The level at which a segment control input enables its segment.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum CtlPolarity {
+    /// The segment is enabled when the control input is high.
+    ActiveHigh,
+    /// The segment is enabled when the control input is low.
+    ActiveLow,
+}
+
+impl CtlPolarity {
+    /// Returns `true` if the control input must be high for the segment to be `enabled`.
+    pub fn level(&self, enabled: bool) -> bool {
+        match self {
+            CtlPolarity::ActiveHigh => enabled,
+            CtlPolarity::ActiveLow => !enabled,
+        }
+    }
+}
+
+/// How a driver unit sets its output impedance.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum DriverTermination {
+    /// Series resistors between the driver transistors and `dout` set most of the
+    /// output impedance.
+    #[default]
+    Series,
+    /// The driver transistors drive `dout` directly, without series resistors.
+    ///
+    /// Used for the unterminated, low-swing mode of advanced packages, in which the
+    /// on-resistance of the driver transistors alone sets the output impedance.
+    Unterminated {
+        /// Half of the width of the driver pull-down transistor, replacing
+        /// [`DriverUnitParams::driver_pd_w`].
+        driver_pd_w: WidthSpec,
+        /// Half of the width of the driver pull-up transistor, replacing
+        /// [`DriverUnitParams::driver_pu_w`].
+        driver_pu_w: WidthSpec,
+    },
+}
+
+fn pu_ctl_polarity_default() -> CtlPolarity {
+    CtlPolarity::ActiveHigh
+}
+
+fn pd_ctl_polarity_default() -> CtlPolarity {
+    CtlPolarity::ActiveLow
+}
+
 /// The parameters of a driver unit schematic/layout generator.
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub struct DriverUnitParams {
     /// The width of the enable pull-up transistor of the NOR gate.
-    pub nor_pu_en_w: i64,
+    pub nor_pu_en_w: WidthSpec,
     /// The width of the data pull-up transistor of the NOR gate.
-    pub nor_pu_data_w: i64,
+    pub nor_pu_data_w: WidthSpec,
     /// The width of the enable pull-down transistor of the NOR gate.
-    pub nor_pd_en_w: i64,
+    pub nor_pd_en_w: WidthSpec,
     /// The width of the data pull-down transistor of the NOR gate.
-    pub nor_pd_data_w: i64,
+    pub nor_pd_data_w: WidthSpec,
     /// Half of the width of the driver pull-down transistor.
-    pub driver_pd_w: i64,
+    pub driver_pd_w: WidthSpec,
     /// The number of legs of the resistors.
     pub res_legs: i64,
     /// The width of the resistors.
@@ -81,15 +143,281 @@ pub struct DriverUnitParams {
     /// The connection type of the pull-up resistor.
     pub pu_res_conn: ResistorConn,
     /// Half of the width of the driver pull-up transistor.
-    pub driver_pu_w: i64,
+    pub driver_pu_w: WidthSpec,
     /// The width of the enable pull-up transistor of the NAND gate.
-    pub nand_pu_en_w: i64,
+    pub nand_pu_en_w: WidthSpec,
     /// The width of the data pull-up transistor of the NAND gate.
-    pub nand_pu_data_w: i64,
+    pub nand_pu_data_w: WidthSpec,
     /// The width of the enable pull-down transistor of the NAND gate.
-    pub nand_pd_en_w: i64,
+    pub nand_pd_en_w: WidthSpec,
     /// The width of the data pull-down transistor of the NAND gate.
-    pub nand_pd_data_w: i64,
+    pub nand_pd_data_w: WidthSpec,
+    /// The channel length of the NOR gate transistors.
+    ///
+    /// Like the other lengths, defaults to the technology minimum if `None`.
+    #[serde(default)]
+    pub nor_l: Option<i64>,
+    /// The channel length of the driver pull-down transistor.
+    #[serde(default)]
+    pub driver_pd_l: Option<i64>,
+    /// The channel length of the driver pull-up transistor.
+    #[serde(default)]
+    pub driver_pu_l: Option<i64>,
+    /// The channel length of the NAND gate transistors.
+    #[serde(default)]
+    pub nand_l: Option<i64>,
+    /// The polarity of `pu_ctl`.
+    ///
+    /// The NAND gate natively takes an active-high enable. Active-low adds an
+    /// inverter on `pu_ctl` sized like the NAND enable transistors.
+    #[serde(default = "pu_ctl_polarity_default")]
+    pub pu_ctl_polarity: CtlPolarity,
+    /// The polarity of `pd_ctlb`.
+    ///
+    /// The NOR gate natively takes an active-low enable. Active-high adds an
+    /// inverter on `pd_ctlb` sized like the NOR enable transistors.
+    #[serde(default = "pd_ctl_polarity_default")]
+    pub pd_ctl_polarity: CtlPolarity,
+    /// How the unit sets its output impedance.
+    ///
+    /// Without series resistors, the resistor rows of a [`HorizontalDriverUnit`] are
+    /// left empty except for a `dout` strap between the driver transistors, so the unit
+    /// keeps its footprint.
+    #[serde(default)]
+    pub termination: DriverTermination,
+}
+
+impl DriverUnitParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> DriverUnitParamsBuilder {
+        DriverUnitParamsBuilder::default()
+    }
+
+    /// Returns whether `pu_ctl` and `pd_ctlb` must be high for a segment to be `enabled`.
+    pub fn ctl_levels(&self, enabled: bool) -> (bool, bool) {
+        (
+            self.pu_ctl_polarity.level(enabled),
+            self.pd_ctl_polarity.level(enabled),
+        )
+    }
+
+    /// Returns whether the unit has series resistors.
+    pub fn is_terminated(&self) -> bool {
+        self.termination == DriverTermination::Series
+    }
+
+    /// Returns an error if a [`VerticalDriverUnit`] cannot be generated with these
+    /// parameters.
+    ///
+    /// The vertical unit has no control inverters and no unterminated variant, so it
+    /// requires an active-high `pu_ctl`, an active-low `pd_ctlb`, and series termination.
+    pub fn check_vertical(&self) -> std::result::Result<(), ParamsError> {
+        let unsupported = |field| {
+            Err(ParamsError::Unsupported {
+                field,
+                generator: "vertical driver unit",
+            })
+        };
+        if self.pu_ctl_polarity != CtlPolarity::ActiveHigh {
+            return unsupported("pu_ctl_polarity");
+        }
+        if self.pd_ctl_polarity != CtlPolarity::ActiveLow {
+            return unsupported("pd_ctl_polarity");
+        }
+        if !self.is_terminated() {
+            return unsupported("termination");
+        }
+        Ok(())
+    }
+
+    /// Returns an error if the MOS tiles of technology `T` do not support one of the
+    /// channel lengths.
+    pub fn check_lengths<T: MosLengthRules>(&self) -> std::result::Result<(), ParamsError> {
+        T::check_length("nor_l", self.nor_l)?;
+        T::check_length("driver_pd_l", self.driver_pd_l)?;
+        T::check_length("driver_pu_l", self.driver_pu_l)?;
+        T::check_length("nand_l", self.nand_l)
+    }
+
+    /// Half of the widths of the driver pull-up and pull-down transistors.
+    pub fn driver_widths(&self) -> (WidthSpec, WidthSpec) {
+        match self.termination {
+            DriverTermination::Series => (self.driver_pu_w, self.driver_pd_w),
+            DriverTermination::Unterminated {
+                driver_pu_w,
+                driver_pd_w,
+            } => (driver_pu_w, driver_pd_w),
+        }
+    }
+
+    /// Snaps every MOS width with `snap`, returning parameters that record the
+    /// achieved widths.
+    pub fn snapped(self, snap: impl Fn(TileKind, WidthSpec) -> WidthSpec) -> Self {
+        let termination = match self.termination {
+            DriverTermination::Series => DriverTermination::Series,
+            DriverTermination::Unterminated {
+                driver_pd_w,
+                driver_pu_w,
+            } => DriverTermination::Unterminated {
+                driver_pd_w: snap(TileKind::N, driver_pd_w),
+                driver_pu_w: snap(TileKind::P, driver_pu_w),
+            },
+        };
+        Self {
+            nor_pu_en_w: snap(TileKind::P, self.nor_pu_en_w),
+            nor_pu_data_w: snap(TileKind::P, self.nor_pu_data_w),
+            nor_pd_en_w: snap(TileKind::N, self.nor_pd_en_w),
+            nor_pd_data_w: snap(TileKind::N, self.nor_pd_data_w),
+            driver_pd_w: snap(TileKind::N, self.driver_pd_w),
+            driver_pu_w: snap(TileKind::P, self.driver_pu_w),
+            nand_pu_en_w: snap(TileKind::P, self.nand_pu_en_w),
+            nand_pu_data_w: snap(TileKind::P, self.nand_pu_data_w),
+            nand_pd_en_w: snap(TileKind::N, self.nand_pd_en_w),
+            nand_pd_data_w: snap(TileKind::N, self.nand_pd_data_w),
+            termination,
+            ..self
+        }
+    }
+}
+
+impl TranslateMut for DriverUnitParams {
+    fn translate_mut(&mut self, _p: Point) {}
+}
+
+impl TransformMut for DriverUnitParams {
+    fn transform_mut(&mut self, _trans: Transformation) {}
+}
+
+/// A builder for [`DriverUnitParams`].
+///
+/// Defaults to 1 um predriver devices, 2 um (per half) driver devices,
+/// and four 1 um wide, 2 um long series resistor legs on each side.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct DriverUnitParamsBuilder {
+    params: DriverUnitParams,
+}
+
+impl Default for DriverUnitParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: DriverUnitParams {
+                nor_pu_en_w: WidthSpec::Nm(1_000),
+                nor_pu_data_w: WidthSpec::Nm(1_000),
+                nor_pd_en_w: WidthSpec::Nm(1_000),
+                nor_pd_data_w: WidthSpec::Nm(1_000),
+                driver_pd_w: WidthSpec::Nm(2_000),
+                res_legs: 4,
+                res_w: 1_000,
+                pd_res_l: 2_000,
+                pd_res_conn: ResistorConn::Series,
+                pu_res_l: 2_000,
+                pu_res_conn: ResistorConn::Series,
+                driver_pu_w: WidthSpec::Nm(2_000),
+                nand_pu_en_w: WidthSpec::Nm(1_000),
+                nand_pu_data_w: WidthSpec::Nm(1_000),
+                nand_pd_en_w: WidthSpec::Nm(1_000),
+                nand_pd_data_w: WidthSpec::Nm(1_000),
+                nor_l: None,
+                driver_pd_l: None,
+                driver_pu_l: None,
+                nand_l: None,
+                pu_ctl_polarity: pu_ctl_polarity_default(),
+                pd_ctl_polarity: pd_ctl_polarity_default(),
+                termination: DriverTermination::Series,
+            },
+        }
+    }
+}
+
+impl DriverUnitParamsBuilder {
+    setters! {
+        /// Sets the width of the enable pull-up transistor of the NOR gate.
+        nor_pu_en_w: impl Into<WidthSpec>,
+        /// Sets the width of the data pull-up transistor of the NOR gate.
+        nor_pu_data_w: impl Into<WidthSpec>,
+        /// Sets the width of the enable pull-down transistor of the NOR gate.
+        nor_pd_en_w: impl Into<WidthSpec>,
+        /// Sets the width of the data pull-down transistor of the NOR gate.
+        nor_pd_data_w: impl Into<WidthSpec>,
+        /// Sets half of the width of the driver pull-down transistor.
+        driver_pd_w: impl Into<WidthSpec>,
+        /// Sets the number of legs of the resistors.
+        res_legs: i64,
+        /// Sets the width of the resistors.
+        res_w: i64,
+        /// Sets the length of the pull-down resistor.
+        pd_res_l: i64,
+        /// Sets the connection type of the pull-down resistor.
+        pd_res_conn: ResistorConn,
+        /// Sets the length of the pull-up resistor.
+        pu_res_l: i64,
+        /// Sets the connection type of the pull-up resistor.
+        pu_res_conn: ResistorConn,
+        /// Sets half of the width of the driver pull-up transistor.
+        driver_pu_w: impl Into<WidthSpec>,
+        /// Sets the width of the enable pull-up transistor of the NAND gate.
+        nand_pu_en_w: impl Into<WidthSpec>,
+        /// Sets the width of the data pull-up transistor of the NAND gate.
+        nand_pu_data_w: impl Into<WidthSpec>,
+        /// Sets the width of the enable pull-down transistor of the NAND gate.
+        nand_pd_en_w: impl Into<WidthSpec>,
+        /// Sets the width of the data pull-down transistor of the NAND gate.
+        nand_pd_data_w: impl Into<WidthSpec>,
+        /// Sets the channel length of the NOR gate transistors.
+        nor_l: Option<i64>,
+        /// Sets the channel length of the driver pull-down transistor.
+        driver_pd_l: Option<i64>,
+        /// Sets the channel length of the driver pull-up transistor.
+        driver_pu_l: Option<i64>,
+        /// Sets the channel length of the NAND gate transistors.
+        nand_l: Option<i64>,
+        /// Sets the polarity of `pu_ctl`.
+        pu_ctl_polarity: CtlPolarity,
+        /// Sets the polarity of `pd_ctlb`.
+        pd_ctl_polarity: CtlPolarity,
+        /// Sets how the unit sets its output impedance.
+        termination: DriverTermination,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<DriverUnitParams, ParamsError> {
+        let p = &self.params;
+        for (field, value) in [
+            ("nor_pu_en_w", p.nor_pu_en_w.value()),
+            ("nor_pu_data_w", p.nor_pu_data_w.value()),
+            ("nor_pd_en_w", p.nor_pd_en_w.value()),
+            ("nor_pd_data_w", p.nor_pd_data_w.value()),
+            ("driver_pd_w", p.driver_pd_w.value()),
+            ("res_legs", p.res_legs),
+            ("res_w", p.res_w),
+            ("pd_res_l", p.pd_res_l),
+            ("pu_res_l", p.pu_res_l),
+            ("driver_pu_w", p.driver_pu_w.value()),
+            ("nand_pu_en_w", p.nand_pu_en_w.value()),
+            ("nand_pu_data_w", p.nand_pu_data_w.value()),
+            ("nand_pd_en_w", p.nand_pd_en_w.value()),
+            ("nand_pd_data_w", p.nand_pd_data_w.value()),
+        ] {
+            check_positive(field, value)?;
+        }
+        for (field, value) in [
+            ("nor_l", p.nor_l),
+            ("driver_pd_l", p.driver_pd_l),
+            ("driver_pu_l", p.driver_pu_l),
+            ("nand_l", p.nand_l),
+        ] {
+            value.map_or(Ok(()), |value| check_positive(field, value))?;
+        }
+        if let DriverTermination::Unterminated {
+            driver_pd_w,
+            driver_pu_w,
+        } = p.termination
+        {
+            check_positive("termination.driver_pd_w", driver_pd_w.value())?;
+            check_positive("termination.driver_pu_w", driver_pu_w.value())?;
+        }
+        Ok(self.params)
+    }
 }
 
 /// The interface to a driver.
@@ -103,6 +431,10 @@ pub struct DriverWithGuardRingRailsIo {
     pub pu_ctl: Array<Input<Signal>>,
     /// The pull-down control (inverted).
     pub pd_ctlb: Array<Input<Signal>>,
+    /// The pull-up control of each spare segment.
+    pub spare_pu_ctl: Array<Input<Signal>>,
+    /// The pull-down control (inverted) of each spare segment.
+    pub spare_pd_ctlb: Array<Input<Signal>>,
     /// The VDD rail.
     pub vdd: InOut<Signal>,
     /// The VSS rail.
@@ -124,12 +456,112 @@ pub struct DriverIo {
     pub pu_ctl: Array<Input<Signal>>,
     /// The pull-down control (inverted).
     pub pd_ctlb: Array<Input<Signal>>,
+    /// The pull-up control of each spare segment.
+    pub spare_pu_ctl: Array<Input<Signal>>,
+    /// The pull-down control (inverted) of each spare segment.
+    pub spare_pd_ctlb: Array<Input<Signal>>,
     /// The VDD rail.
     pub vdd: InOut<Signal>,
     /// The VSS rail.
     pub vss: InOut<Signal>,
 }
 
+/// The order in which the segments of a driver bank are placed.
+///
+/// Slots are numbered left to right in a [`HorizontalDriver`] and top to bottom in a
+/// [`VerticalDriver`]. The spare segment, if any, always occupies the last slot.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum SegmentPlacement {
+    /// The segment controlled by line `i` is placed in slot `i`.
+    #[default]
+    Sequential,
+    /// Thermometer segments are placed center-out.
+    ///
+    /// Line 0 is placed in the center slot and each following line in the nearest
+    /// free slot, so any number of enabled segments is centered on the bank and
+    /// linear process gradients average out.
+    CenterOut,
+    /// The segments of each binary weight are spread evenly across the bank.
+    ///
+    /// Consecutive control lines form the binary weights: line 0 has weight 1, lines
+    /// 1 and 2 form weight 2, lines 3 to 6 form weight 4, and so on. Every weight is
+    /// centered on the bank, so linear process gradients do not skew the weight
+    /// ratios.
+    BinaryInterleaved,
+}
+
+impl SegmentPlacement {
+    /// The control line of the segment in each of `n` slots.
+    pub fn order(&self, n: usize) -> Vec<usize> {
+        match self {
+            SegmentPlacement::Sequential => (0..n).collect(),
+            SegmentPlacement::CenterOut => {
+                let mut slots = (0..n).collect::<Vec<_>>();
+                slots.sort_by_key(|&slot| (2 * slot).abs_diff(n.saturating_sub(1)));
+                let mut order = vec![0; n];
+                for (line, slot) in slots.into_iter().enumerate() {
+                    order[slot] = line;
+                }
+                order
+            }
+            SegmentPlacement::BinaryInterleaved => {
+                // Each line targets the center of its share of the bank, as a fraction
+                // `(2j + 1) / (2 * size)` for the `j`th of `size` lines in its weight.
+                let target = |line: usize| {
+                    let first = (1 << (line + 1).ilog2()) - 1;
+                    let size = (first + 1).min(n - first);
+                    (2 * (line - first) + 1, 2 * size)
+                };
+                let mut order = (0..n).collect::<Vec<_>>();
+                order.sort_by(|&a, &b| {
+                    let ((na, da), (nb, db)) = (target(a), target(b));
+                    (na * db).cmp(&(nb * da))
+                });
+                order
+            }
+        }
+    }
+}
+
+/// The location of the segment driven by a control line of a driver.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct CtlPinLocation {
+    /// The index of the `pu_ctl` and `pd_ctlb` pins.
+    pub ctl: usize,
+    /// The bank containing the segment.
+    pub bank: usize,
+    /// The slot of the segment within its bank.
+    pub slot: usize,
+}
+
+/// The segment driven by each control line of a driver, excluding spares.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct CtlPinMap {
+    /// The placement that produced this map.
+    pub placement: SegmentPlacement,
+    /// One location per control line, in pin order.
+    pub pins: Vec<CtlPinLocation>,
+}
+
+impl SimArtifact for CtlPinMap {
+    fn csv_header(&self) -> Vec<String> {
+        ["ctl", "bank", "slot"].map(String::from).to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.pins
+            .iter()
+            .map(|pin| {
+                vec![
+                    pin.ctl.to_string(),
+                    pin.bank.to_string(),
+                    pin.slot.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
 /// The parameters of the horizontal and vertical driver generators.
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub struct DriverParams {
@@ -139,10 +571,277 @@ pub struct DriverParams {
     pub num_segments: usize,
     /// Number of banks.
     pub banks: usize,
+    /// The current budget from which supply strap periods are planned.
+    ///
+    /// If `None`, the horizontal driver uses [`DEFAULT_SUPPLY_BUDGET`].
+    #[serde(default)]
+    pub supply_budget: Option<StrapBudget>,
+    /// Whether to add a spare segment to each bank.
+    ///
+    /// A spare is identical to the main segments but is controlled by its own
+    /// `spare_pu_ctl` and `spare_pd_ctlb` pins, so it can stand in for a defective
+    /// segment after fabrication without changing the impedance step. Spares should
+    /// be disabled in normal operation.
+    #[serde(default)]
+    pub spare_segment: bool,
+    /// CDM secondary protection between the driver units and the pad, if any.
+    ///
+    /// Adds an [`EsdSeries`] resistor and clamp between `dout` and the outputs of the
+    /// driver units. Only supported by [`HorizontalDriver`].
+    #[serde(default)]
+    pub esd: Option<EsdSeriesParams>,
+    /// The order in which the segments of each bank are placed.
+    #[serde(default)]
+    pub placement: SegmentPlacement,
+}
+
+impl DriverParams {
+    /// The number of spare segments in each bank.
+    pub fn spares_per_bank(&self) -> usize {
+        self.spare_segment as usize
+    }
+
+    /// The number of segments in each bank, including the spare.
+    pub fn segments_per_bank(&self) -> usize {
+        self.num_segments + self.spares_per_bank()
+    }
+
+    /// The supply current budget of the whole horizontal driver.
+    pub fn supply_budget(&self) -> StrapBudget {
+        self.supply_budget.unwrap_or(DEFAULT_SUPPLY_BUDGET)
+    }
+
+    /// Returns an error if a [`VerticalDriver`] cannot be generated with these parameters.
+    ///
+    /// The unit parameters must pass [`DriverUnitParams::check_vertical`], and ESD series
+    /// protection, which only the horizontal driver supports, must not be requested.
+    pub fn check_vertical(&self) -> std::result::Result<(), ParamsError> {
+        self.unit.check_vertical()?;
+        if self.esd.is_some() {
+            return Err(ParamsError::Unsupported {
+                field: "esd",
+                generator: "vertical driver",
+            });
+        }
+        Ok(())
+    }
+
+    /// The control line of the segment in each main slot of a bank.
+    pub fn segment_order(&self) -> Vec<usize> {
+        self.placement.order(self.num_segments)
+    }
+
+    /// The segment driven by each control line of a [`HorizontalDriver`].
+    ///
+    /// A [`VerticalDriver`] has a single bank, so its map is the first
+    /// `num_segments` entries.
+    pub fn ctl_pin_map(&self) -> CtlPinMap {
+        let mut slots = vec![0; self.num_segments];
+        for (slot, line) in self.segment_order().into_iter().enumerate() {
+            slots[line] = slot;
+        }
+        CtlPinMap {
+            placement: self.placement,
+            pins: (0..self.banks)
+                .flat_map(|bank| {
+                    slots
+                        .iter()
+                        .enumerate()
+                        .map(move |(line, &slot)| CtlPinLocation {
+                            ctl: self.num_segments * bank + line,
+                            bank,
+                            slot,
+                        })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// ATOLL layer indices used by the driver generators.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct DriverLayerMap {
+    /// The layer of the `dout` pin of each driver unit.
+    ///
+    /// Control pins are placed on the layer directly beneath.
+    pub pin: usize,
+    /// The layer on which `dout` is strapped across driver units.
+    pub strap: usize,
+    /// The layer of the bump rectangle.
+    pub bump: usize,
+    /// The top routing layer of the driver.
+    pub top: usize,
+}
+
+impl DriverLayerMap {
+    /// The default layer map of the horizontal driver.
+    pub const HORIZONTAL: Self = Self {
+        pin: 3,
+        strap: 7,
+        bump: 9,
+        top: 9,
+    };
+    /// The default layer map of the vertical driver.
+    pub const VERTICAL: Self = Self {
+        pin: 2,
+        strap: 3,
+        bump: 8,
+        top: 3,
+    };
+
+    /// The layers of the guard ring, `din`, and supply straps planned over each bank of
+    /// a [`HorizontalDriver`].
+    ///
+    /// Spans layer 2 up to, but not including, the strap layer.
+    pub fn bank_strap_layers(&self) -> Range<usize> {
+        2.min(self.strap)..self.strap
+    }
+
+    /// The layers of the `din` and supply straps planned across the banks of a
+    /// [`HorizontalDriver`].
+    ///
+    /// Spans the layer beneath the strap layer and the strap layer itself, dropping any
+    /// layers at or above the top layer.
+    pub fn driver_strap_layers(&self) -> Range<usize> {
+        let start = self.strap.saturating_sub(1);
+        start..(self.strap + 1).min(self.top).max(start)
+    }
+}
+
+/// The supply current budget of the horizontal driver when
+/// [`DriverParams::supply_budget`] is not given.
+///
+/// Each bank carries an equal share of the budget.
+pub const DEFAULT_SUPPLY_BUDGET: StrapBudget = StrapBudget {
+    rms_current: dec!(0.02),
+    peak_current: dec!(0.08),
+    max_ir_drop: dec!(0.02),
+};
+/// The current budget of the guard ring straps of each horizontal driver bank.
+///
+/// Guard rings only carry substrate and well current.
+pub const GUARD_RING_STRAP_BUDGET: StrapBudget = StrapBudget {
+    rms_current: dec!(0.0001),
+    peak_current: dec!(0.001),
+    max_ir_drop: dec!(0.01),
+};
+/// The current budget of the `din` straps of the horizontal driver.
+///
+/// `din` only charges the gates of the pre-driver inverters.
+pub const DIN_STRAP_BUDGET: StrapBudget = StrapBudget {
+    rms_current: dec!(0.001),
+    peak_current: dec!(0.01),
+    max_ir_drop: dec!(0.02),
+};
+/// The track offset of the VSS straps covering the horizontal driver.
+pub const VSS_STRAP_OFFSET: i64 = 0;
+/// The track offset of the VDD straps covering the horizontal driver.
+pub const VDD_STRAP_OFFSET: i64 = 1;
+/// The track offset of the `din` straps covering the horizontal driver.
+pub const DIN_STRAP_OFFSET: i64 = 2;
+/// The track offset of the guard ring straps of the horizontal driver.
+pub const GUARD_RING_STRAP_OFFSET: i64 = 3;
+
+/// Creates the strapping parameters for a net strapped down to layer 0 with vias every
+/// `via_period` tracks on layer 1, given the strap periods on layer 2 and above.
+fn planned_strapping(via_period: i64, periods: &[i64], offset: i64, top: usize) -> StrappingParams {
+    strapping_below(
+        1,
+        std::iter::once(LayerStrappingParams::ViaDown {
+            min_period: via_period,
+        })
+        .chain(offset_period_straps(periods, offset))
+        .collect(),
+        top,
+    )
+}
+
+/// Plans the periods of straps on `layers` over `bounds` to meet `budget`.
+///
+/// A budget the metal cannot meet is returned as an
+/// [`InvalidInput`](std::io::ErrorKind::InvalidInput) I/O error wrapping the
+/// [`StrapPlanError`], since layout generators can only return Substrate errors.
+fn plan_straps<PDK: Pdk + Schema, T: HorizontalDriverImpl<PDK>>(
+    cell: &TileBuilder<'_, PDK>,
+    bounds: Rect,
+    layers: Range<usize>,
+    budget: &StrapBudget,
+) -> Result<Vec<i64>> {
+    let tracks = (0..layers.end)
+        .map(|i| {
+            (
+                cell.layer_stack.layer(i).pitch(),
+                cell.layer_stack.layers[i].inner.tracks().get(0).length(),
+            )
+        })
+        .collect();
+    let planner = StrapPlanner::new(tracks, bounds);
+    let periods = T::plan_straps(&planner, layers, budget)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    Ok(periods)
+}
+
+/// Creates strapping parameters starting at layer `start`,
+/// dropping any layers at or above layer `top`.
+fn strapping_below(start: usize, layers: Vec<LayerStrappingParams>, top: usize) -> StrappingParams {
+    let n = top.saturating_sub(start);
+    StrappingParams::new(start, layers.into_iter().take(n).collect())
+}
+
+/// Straps `net` according to `params`, unless the
+/// [`GenerationOptions`](crate::GenerationOptions) of the context request schematics only.
+fn set_strapping<PDK: Pdk + Schema>(
+    cell: &mut TileBuilder<'_, PDK>,
+    net: Node,
+    params: StrappingParams,
+) {
+    if !crate::generation_options(cell.ctx()).schematic_only {
+        cell.set_strapping(net, params);
+    }
+}
+
+/// Generates the layouts of the distinct `tiles` concurrently, so that the placement
+/// loop that instantiates them finds their cells already in the context cache.
+///
+/// Substrate caches cells by block, so identical tiles are generated once however many
+/// times they are instantiated, while each instance still connects to its own nets.
+/// Generation errors are left to the instances, which report them when they are drawn.
+fn pregenerate<PDK, B>(ctx: &PdkContext<PDK>, tiles: impl IntoIterator<Item = B>)
+where
+    PDK: Pdk + Schema,
+    B: Block + Clone,
+    TileWrapper<B>: Layout<PDK>,
+{
+    let mut distinct = Vec::new();
+    for tile in tiles {
+        if !distinct.contains(&tile) {
+            distinct.push(tile);
+        }
+    }
+    if crate::generation_options(ctx).schematic_only {
+        return;
+    }
+    let span = tracing::Span::current();
+    std::thread::scope(|s| {
+        let handles = distinct
+            .iter()
+            .map(|tile| {
+                let span = &span;
+                s.spawn(move || {
+                    span.in_scope(|| {
+                        ctx.generate_layout(TileWrapper::new(tile.clone()));
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            let _ = handle.join();
+        }
+    });
 }
 
 /// A horizontal driver implementation.
-pub trait HorizontalDriverImpl<PDK: Pdk + Schema> {
+pub trait HorizontalDriverImpl<PDK: Pdk + Schema>: MosLengthRules {
     /// The MOS tile.
     type MosTile: Tile<PDK> + Block<Io = MosIo> + Clone;
     /// The tap tile.
@@ -161,11 +860,33 @@ pub trait HorizontalDriverImpl<PDK: Pdk + Schema> {
     const GUARD_RING_ANNULAR_HEIGHT: i64;
     /// Width of the bump rectangle.
     const BUMP_RECT_WIDTH: i64;
+    /// The layers used by the driver.
+    const LAYER_MAP: DriverLayerMap = DriverLayerMap::HORIZONTAL;
+    /// The router used by the driver unit, the most congested tile in the driver.
+    ///
+    /// [`RouterKind::Pathfinder`] avoids the need to manually block grid points when the
+    /// greedy router fails to complete the unit.
+    const ROUTER: RouterKind = RouterKind::Greedy;
 
-    /// Creates an instance of the MOS tile.
-    fn mos(kind: TileKind, max_nf: i64, w: i64) -> Self::MosTile;
+    /// Creates an instance of the MOS tile with width `w`, either in nanometers or as a
+    /// [`WidthSpec`], and channel length `l`, or the technology minimum if `None`.
+    fn mos(kind: TileKind, max_nf: i64, w: impl Into<WidthSpec>, l: Option<i64>) -> Self::MosTile;
     /// Creates an instance of the MOS tile for the driver transistors.
-    fn driver_mos(kind: TileKind, max_nf: i64, w: i64) -> Self::MosTile;
+    fn driver_mos(
+        kind: TileKind,
+        max_nf: i64,
+        w: impl Into<WidthSpec>,
+        l: Option<i64>,
+    ) -> Self::MosTile;
+    /// Snaps a requested MOS width to a legal value, returning the achieved width.
+    fn snap_width(_kind: TileKind, width: WidthSpec) -> WidthSpec {
+        width
+    }
+    /// Snaps a requested width of a MOS tile with `max_nf` fingers to a legal value,
+    /// returning the total width that [`HorizontalDriverImpl::mos`] achieves.
+    fn snap_width_nf(kind: TileKind, _max_nf: i64, width: WidthSpec) -> WidthSpec {
+        Self::snap_width(kind, width)
+    }
     /// Creates an instance of the tap tile.
     fn tap(kind: TileKind, nf: i64) -> Self::TapTile;
     /// The number of fingers needed for the MOS tile to match the width of the resistor tile.
@@ -190,10 +911,16 @@ pub trait HorizontalDriverImpl<PDK: Pdk + Schema> {
         cell: &mut TileBuilder<'_, PDK>,
         kind: TileKind,
         nf: i64,
-        w: i64,
+        w: WidthSpec,
         loc: Point,
         orientation: Orientation,
     ) -> Result<()>;
+    /// Plans the periods of the straps on `layers` to meet `budget`.
+    fn plan_straps(
+        planner: &StrapPlanner,
+        layers: Range<usize>,
+        budget: &StrapBudget,
+    ) -> std::result::Result<Vec<i64>, StrapPlanError>;
     /// Additional layout hooks to run after the inverter layout is complete.
     fn post_layout_hooks(_cell: &mut TileBuilder<'_, PDK>) -> Result<()> {
         Ok(())
@@ -201,7 +928,7 @@ pub trait HorizontalDriverImpl<PDK: Pdk + Schema> {
 }
 
 /// A vertical driver implementation.
-pub trait VerticalDriverImpl<PDK: Pdk + Schema> {
+pub trait VerticalDriverImpl<PDK: Pdk + Schema>: MosLengthRules {
     /// The MOS tile used to implement the pull-up and pull-down transistors.
     type MosTile: Tile<PDK> + Block<Io = MosIo> + Clone;
     /// The tap tile.
@@ -212,6 +939,27 @@ pub trait VerticalDriverImpl<PDK: Pdk + Schema> {
     type ViaMaker: ViaMaker<PDK>;
     /// The `din`/`dout` pin layer for driver unit cells.
     type Pin: HasPin;
+    /// The layers used by the driver.
+    ///
+    /// The bump strap is drawn on [`DriverLayerMap::bump`].
+    const LAYER_MAP: DriverLayerMap = DriverLayerMap::VERTICAL;
+    /// Width of the bump strap.
+    const BUMP_RECT_WIDTH: i64 = 1_080;
+    /// The direction in which the bump strap runs.
+    ///
+    /// A vertical strap spans the full height of the driver, centered on the `dout` pins.
+    /// A horizontal strap is drawn across the full width of the driver for each unit,
+    /// centered on that unit's `dout` pin.
+    const BUMP_RECT_DIR: Dir = Dir::Vert;
+    /// Minimum n-well spacing. Narrower gaps between the wells of adjacent tiles are filled.
+    const NWELL_MIN_SPACING: i64 = 1_270;
+    /// The maximum distance from any point of a diffusion region to the nearest tap.
+    ///
+    /// An extra tap is placed on the far side of a driver transistor that is too wide
+    /// to be covered by the tap on its near side.
+    const MAX_DIFF_TAP_DISTANCE: i64;
+    /// The router used by the driver unit.
+    const ROUTER: RouterKind = RouterKind::Greedy;
 
     /// Creates an instance of the MOS tile.
     fn mos(params: MosTileParams) -> Self::MosTile;
@@ -229,6 +977,10 @@ pub trait VerticalDriverImpl<PDK: Pdk + Schema> {
     }
     /// Returns the `din`/`dout` pin layer.
     fn pin(layers: &PdkLayers<PDK>) -> Self::Pin;
+    /// Snaps a requested MOS width to a legal value, returning the achieved width.
+    fn snap_width(_kind: TileKind, width: WidthSpec) -> WidthSpec {
+        width
+    }
     /// Additional layout hooks to run after the inverter layout is complete.
     fn post_layout_hooks(_cell: &mut TileBuilder<'_, PDK>) -> Result<()> {
         Ok(())
@@ -236,6 +988,10 @@ pub trait VerticalDriverImpl<PDK: Pdk + Schema> {
 }
 
 /// A horizontal driver unit.
+///
+/// Exports the gate drives of the output devices (`pd_en` and `pu_en`) and the nodes
+/// between the output devices and their resistors (`pd_x` and `pu_x`) as
+/// [probe points](crate::tb::probe). Without termination, `pd_x` and `pu_x` are `dout`.
 #[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 #[derive(Serialize, Deserialize)]
 pub struct HorizontalDriverUnit<T>(
@@ -254,7 +1010,15 @@ pub struct HorizontalDriverUnitLayoutData {
     pub driver_ntap_bboxes: Vec<Rect>,
     /// Bounding boxes of the driver p-taps.
     pub driver_ptap_bboxes: Vec<Rect>,
-    /// The `dout` pin geometry located on layer 3.
+    /// Bounding boxes of the NMOS, PMOS, and n-tap of the `pu_ctl` polarity inverter.
+    ///
+    /// Empty unless `pu_ctl` is active low.
+    pub pu_ctl_inv_bboxes: Vec<Rect>,
+    /// Bounding boxes of the NMOS, PMOS, and p-tap of the `pd_ctlb` polarity inverter.
+    ///
+    /// Empty unless `pd_ctlb` is active high.
+    pub pd_ctl_inv_bboxes: Vec<Rect>,
+    /// The `dout` pin geometry located on the pin layer.
     pub dout: Rect,
     /// Bounding boxes of geometry that requires fillers on the edges
     /// (i.e. not surrounded by guard ring).
@@ -262,6 +1026,8 @@ pub struct HorizontalDriverUnitLayoutData {
     /// Bounding boxes of geometry that requires n-well fillers on the edges
     /// (i.e. not surrounded by guard ring).
     pub nwell_filler_bboxes: Vec<Rect>,
+    /// The parameters with every MOS width replaced by the achieved width.
+    pub params: DriverUnitParams,
 }
 
 impl<T> HorizontalDriverUnit<T> {
@@ -278,9 +1044,8 @@ impl<T: Any> Block for HorizontalDriverUnit<T> {
         substrate::arcstr::literal!("horizontal_driver_unit")
     }
 
-    // todo: include parameters in name
     fn name(&self) -> ArcStr {
-        substrate::arcstr::literal!("horizontal_driver_unit")
+        crate::block_name("horizontal_driver_unit", &self.0)
     }
 
     fn io(&self) -> Self::Io {
@@ -289,7 +1054,11 @@ impl<T: Any> Block for HorizontalDriverUnit<T> {
 }
 
 impl<T: Any> ExportsNestedData for HorizontalDriverUnit<T> {
-    type NestedData = ();
+    type NestedData = Probes;
+}
+
+impl<T: Any> ProbePoints for HorizontalDriverUnit<T> {
+    const PROBES: &'static [&'static str] = &["pd_en", "pu_en", "pd_x", "pu_x"];
 }
 
 impl<T: Any> ExportsLayoutData for HorizontalDriverUnit<T> {
@@ -299,6 +1068,7 @@ impl<T: Any> ExportsLayoutData for HorizontalDriverUnit<T> {
 impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
     for HorizontalDriverUnit<T>
 {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
     fn tile<'a>(
         &self,
         io: IoBuilder<'a, Self>,
@@ -307,7 +1077,13 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
         <Self as ExportsNestedData>::NestedData,
         <Self as ExportsLayoutData>::LayoutData,
     )> {
+        self.0
+            .check_lengths::<T>()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
         let nf = T::nf(self.0.res_legs, self.0.res_w);
+        let terminated = self.0.is_terminated();
+        let snapped = self.0.snapped(|kind, w| T::snap_width_nf(kind, nf, w));
+        let (driver_pu_w, driver_pd_w) = snapped.driver_widths();
 
         // Intermediate nodes in the NOR/NAND gates.
         let nor_x = cell.signal("nor_x", Signal::new());
@@ -318,19 +1094,88 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
         let pu_en = cell.signal("pu_en", Signal::new());
 
         // Intermediate signals between pull-up/pull-down transistors and resistors.
-        let pd_x = cell.signal("pd_x", Signal::new());
-        let pu_x = cell.signal("pu_x", Signal::new());
-
-        let mos = |kind, w| T::mos(kind, nf, w);
-        let driver_mos = |kind, w| T::driver_mos(kind, nf, w);
+        // Without resistors, the transistors drive `dout` directly.
+        let (pd_x, pu_x) = if terminated {
+            (
+                cell.signal("pd_x", Signal::new()),
+                cell.signal("pu_x", Signal::new()),
+            )
+        } else {
+            (io.schematic.dout, io.schematic.dout)
+        };
+
+        let mos = |kind, w: WidthSpec, l| T::mos(kind, nf, w, l);
+        let driver_mos = |kind, w: WidthSpec, l| T::driver_mos(kind, nf, w, l);
+
+        // Control inputs whose polarity differs from that of their gate are inverted
+        // by an inverter stacked at the outer end of the gate, with its own tap.
+        let vdd = io.schematic.vdd;
+        let vss = io.schematic.vss;
+        let ctl_inverter = |cell: &mut TileBuilder<'a, PDK>, input, output, n_w, p_w, l| {
+            let n = cell.generate_connected(
+                mos(TileKind::N, n_w, l),
+                MosIoSchematic {
+                    d: output,
+                    g: input,
+                    s: vss,
+                    b: vss,
+                },
+            );
+            let p = cell
+                .generate_connected(
+                    mos(TileKind::P, p_w, l),
+                    MosIoSchematic {
+                        d: output,
+                        g: input,
+                        s: vdd,
+                        b: vdd,
+                    },
+                )
+                .orient(Orientation::ReflectVert);
+            (n, p)
+        };
+        let (pu_ctl, mut pu_ctl_inv) = match self.0.pu_ctl_polarity {
+            CtlPolarity::ActiveHigh => (io.schematic.pu_ctl, None),
+            CtlPolarity::ActiveLow => {
+                let pu_ctl = cell.signal("pu_ctl_int", Signal::new());
+                let (n, p) = ctl_inverter(
+                    cell,
+                    io.schematic.pu_ctl,
+                    pu_ctl,
+                    snapped.nand_pd_en_w,
+                    snapped.nand_pu_en_w,
+                    self.0.nand_l,
+                );
+                let tap =
+                    cell.generate_connected(T::tap(TileKind::N, nf), TapIoSchematic { x: vdd });
+                (pu_ctl, Some((n, p, tap)))
+            }
+        };
+        let (pd_ctlb, mut pd_ctl_inv) = match self.0.pd_ctl_polarity {
+            CtlPolarity::ActiveLow => (io.schematic.pd_ctlb, None),
+            CtlPolarity::ActiveHigh => {
+                let pd_ctlb = cell.signal("pd_ctlb_int", Signal::new());
+                let (n, p) = ctl_inverter(
+                    cell,
+                    io.schematic.pd_ctlb,
+                    pd_ctlb,
+                    snapped.nor_pd_en_w,
+                    snapped.nor_pu_en_w,
+                    self.0.nor_l,
+                );
+                let tap =
+                    cell.generate_connected(T::tap(TileKind::P, nf), TapIoSchematic { x: vss });
+                (pd_ctlb, Some((n, p, tap)))
+            }
+        };
 
         // Instantiate all transistors.
         let mut nor_pu_en = cell
             .generate_connected(
-                mos(TileKind::P, self.0.nor_pu_en_w),
+                mos(TileKind::P, snapped.nor_pu_en_w, self.0.nor_l),
                 MosIoSchematic {
                     d: io.schematic.vdd,
-                    g: io.schematic.pd_ctlb,
+                    g: pd_ctlb,
                     s: nor_x,
                     b: io.schematic.vdd,
                 },
@@ -338,7 +1183,7 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
             .orient(Orientation::ReflectVert);
         let mut nor_pu_data = cell
             .generate_connected(
-                mos(TileKind::P, self.0.nor_pu_data_w),
+                mos(TileKind::P, snapped.nor_pu_data_w, self.0.nor_l),
                 MosIoSchematic {
                     d: nor_x,
                     g: io.schematic.din,
@@ -348,16 +1193,16 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
             )
             .orient(Orientation::ReflectVert);
         let mut nor_pd_en = cell.generate_connected(
-            mos(TileKind::N, self.0.nor_pd_en_w),
+            mos(TileKind::N, snapped.nor_pd_en_w, self.0.nor_l),
             MosIoSchematic {
                 d: pd_en,
-                g: io.schematic.pd_ctlb,
+                g: pd_ctlb,
                 s: io.schematic.vss,
                 b: io.schematic.vss,
             },
         );
         let mut nor_pd_data = cell.generate_connected(
-            mos(TileKind::N, self.0.nor_pd_data_w),
+            mos(TileKind::N, snapped.nor_pd_data_w, self.0.nor_l),
             MosIoSchematic {
                 d: pd_en,
                 g: io.schematic.din,
@@ -366,7 +1211,7 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
             },
         );
         let mut driver_pd = cell.generate_connected(
-            driver_mos(TileKind::N, self.0.driver_pd_w),
+            driver_mos(TileKind::N, driver_pd_w, self.0.driver_pd_l),
             MosIoSchematic {
                 d: pd_x,
                 g: pd_en,
@@ -374,37 +1219,32 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
                 b: io.schematic.vss,
             },
         );
-        let mut pd_res = cell.generate_connected(
-            T::resistor(
+        // Without series resistors, the resistors are placed but not drawn, reserving
+        // their rows as a channel for `dout`.
+        let mut pd_res = cell.generate(T::resistor(
+            self.0.res_legs,
+            self.0.res_w,
+            self.0.pd_res_l,
+            self.0.pd_res_conn,
+        ));
+        let mut pu_res = cell
+            .generate(T::resistor(
                 self.0.res_legs,
                 self.0.res_w,
-                self.0.pd_res_l,
-                self.0.pd_res_conn,
-            ),
-            ResistorIoSchematic {
-                p: io.schematic.dout,
-                n: pd_x,
-                b: io.schematic.vdd,
-            },
-        );
-        let mut pu_res = cell
-            .generate_connected(
-                T::resistor(
-                    self.0.res_legs,
-                    self.0.res_w,
-                    self.0.pu_res_l,
-                    self.0.pu_res_conn,
-                ),
-                ResistorIoSchematic {
-                    p: io.schematic.dout,
-                    n: pu_x,
-                    b: io.schematic.vdd,
-                },
-            )
+                self.0.pu_res_l,
+                self.0.pu_res_conn,
+            ))
             .orient(Orientation::ReflectVert);
+        if terminated {
+            for (res, x) in [(&pd_res, pd_x), (&pu_res, pu_x)] {
+                cell.connect(res.io().p, io.schematic.dout);
+                cell.connect(res.io().n, x);
+                cell.connect(res.io().b, io.schematic.vdd);
+            }
+        }
         let mut driver_pu = cell
             .generate_connected(
-                driver_mos(TileKind::P, self.0.driver_pu_w),
+                driver_mos(TileKind::P, driver_pu_w, self.0.driver_pu_l),
                 MosIoSchematic {
                     d: pu_x,
                     g: pu_en,
@@ -415,10 +1255,10 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
             .orient(Orientation::ReflectVert);
         let mut nand_pu_en = cell
             .generate_connected(
-                mos(TileKind::P, self.0.nand_pu_en_w),
+                mos(TileKind::P, snapped.nand_pu_en_w, self.0.nand_l),
                 MosIoSchematic {
                     d: pu_en,
-                    g: io.schematic.pu_ctl,
+                    g: pu_ctl,
                     s: io.schematic.vdd,
                     b: io.schematic.vdd,
                 },
@@ -426,7 +1266,7 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
             .orient(Orientation::ReflectVert);
         let mut nand_pu_data = cell
             .generate_connected(
-                mos(TileKind::P, self.0.nand_pu_data_w),
+                mos(TileKind::P, snapped.nand_pu_data_w, self.0.nand_l),
                 MosIoSchematic {
                     d: pu_en,
                     g: io.schematic.din,
@@ -436,16 +1276,16 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
             )
             .orient(Orientation::ReflectVert);
         let mut nand_pd_en = cell.generate_connected(
-            mos(TileKind::N, self.0.nand_pd_en_w),
+            mos(TileKind::N, snapped.nand_pd_en_w, self.0.nand_l),
             MosIoSchematic {
                 d: io.schematic.vss,
-                g: io.schematic.pu_ctl,
+                g: pu_ctl,
                 s: nand_x,
                 b: io.schematic.vss,
             },
         );
         let mut nand_pd_data = cell.generate_connected(
-            mos(TileKind::N, self.0.nand_pd_data_w),
+            mos(TileKind::N, snapped.nand_pd_data_w, self.0.nand_l),
             MosIoSchematic {
                 d: nand_x,
                 g: io.schematic.din,
@@ -530,6 +1370,24 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
         ntap_nor.align_mut(&nor_pu_en, AlignMode::Left, 0);
         ntap_nor.align_mut(&nor_pu_en, AlignMode::Beneath, 0);
 
+        // Place control inverters.
+        if let Some((n, p, ntap)) = &mut pu_ctl_inv {
+            n.align_mut(&ptap_nand, AlignMode::Left, 0);
+            n.align_mut(&ptap_nand, AlignMode::Above, 0);
+            p.align_mut(&*n, AlignMode::Left, 0);
+            p.align_mut(&*n, AlignMode::Above, 0);
+            ntap.align_mut(&*p, AlignMode::Left, 0);
+            ntap.align_mut(&*p, AlignMode::Above, 0);
+        }
+        if let Some((n, p, ptap)) = &mut pd_ctl_inv {
+            p.align_mut(&ntap_nor, AlignMode::Left, 0);
+            p.align_mut(&ntap_nor, AlignMode::Beneath, 0);
+            n.align_mut(&*p, AlignMode::Left, 0);
+            n.align_mut(&*p, AlignMode::Beneath, 0);
+            ptap.align_mut(&*n, AlignMode::Left, 0);
+            ptap.align_mut(&*n, AlignMode::Beneath, 0);
+        }
+
         // Block layer 0 where guard ring will be present.
         for (top, bot) in [
             (ntap_nand.lcm_bounds(), ntap_driver_top.lcm_bounds()),
@@ -550,8 +1408,11 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
         let _nor_pu_en = cell.draw(nor_pu_en)?;
         let nor_pu_data = cell.draw(nor_pu_data)?;
         let driver_pd = cell.draw(driver_pd)?;
-        let pd_res = cell.draw(pd_res)?;
-        let pu_res = cell.draw(pu_res)?;
+        let res = if terminated {
+            Some((cell.draw(pd_res)?, cell.draw(pu_res)?))
+        } else {
+            None
+        };
         let driver_pu = cell.draw(driver_pu)?;
         let _nand_pd_en = cell.draw(nand_pd_en)?;
         let nand_pd_data = cell.draw(nand_pd_data)?;
@@ -567,49 +1428,90 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
         let ntap_driver_bot = cell.draw(ntap_driver_bot)?;
         let ntap_nand = cell.draw(ntap_nand)?;
         let ptap_nand = cell.draw(ptap_nand)?;
-
-        cell.set_top_layer(3);
-        cell.set_router(GreedyRouter::with_seed([1; 32]));
+        let pu_ctl_inv = match pu_ctl_inv {
+            Some((n, p, ntap)) => Some([
+                cell.draw(n)?.layout.bbox_rect(),
+                cell.draw(p)?.layout.bbox_rect(),
+                cell.draw(ntap)?.layout.bbox_rect(),
+            ]),
+            None => None,
+        };
+        let pd_ctl_inv = match pd_ctl_inv {
+            Some((n, p, ptap)) => Some([
+                cell.draw(n)?.layout.bbox_rect(),
+                cell.draw(p)?.layout.bbox_rect(),
+                cell.draw(ptap)?.layout.bbox_rect(),
+            ]),
+            None => None,
+        };
+
+        let layers = T::LAYER_MAP;
+        let (pin, ctl) = (layers.pin, layers.pin - 1);
+
+        cell.set_top_layer(pin);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
         cell.set_via_maker(T::via_maker());
 
-        // Route `dout` to layer 3.
+        // Route `dout` to the pin layer.
         let virtual_layers = cell.layout.ctx.install_layers::<atoll::VirtualLayers>();
         let bbox = cell.layout.layer_bbox(virtual_layers.outline.id()).unwrap();
-        let center_track_y = cell.layer_stack.layers[3]
+        let center_track_y = cell.layer_stack.layers[pin]
             .inner
             .tracks()
             .to_track_idx(bbox.center().y, RoundingMode::Nearest);
-        let center_track_x = cell.layer_stack.layers[2]
+        let center_track_x = cell.layer_stack.layers[ctl]
             .inner
             .tracks()
             .to_track_idx(bbox.center().x, RoundingMode::Nearest);
         let dout_rect = Rect::from_spans(
-            cell.layer_stack.layers[2]
+            cell.layer_stack.layers[ctl]
                 .inner
                 .tracks()
                 .get(center_track_x),
-            cell.layer_stack.layers[3]
+            cell.layer_stack.layers[pin]
                 .inner
                 .tracks()
                 .get(center_track_y),
         );
         cell.assign_grid_points(
             Some(io.schematic.dout),
-            3,
+            pin,
             cell.layer_stack
-                .slice(0..4)
+                .slice(0..pin + 1)
                 .shrink_to_lcm_units(dout_rect)
                 .unwrap(),
         );
         cell.layout
-            .draw(Shape::new(cell.layer_stack.layers[3].id, dout_rect))?;
+            .draw(Shape::new(cell.layer_stack.layers[pin].id, dout_rect))?;
+
+        // Without series resistors, strap the driver transistor drains together through
+        // the resistor channel.
+        if !terminated {
+            let strap = Rect::from_spans(
+                dout_rect.hspan(),
+                Span::new(
+                    driver_pd.layout.bbox_rect().center().y,
+                    driver_pu.layout.bbox_rect().center().y,
+                ),
+            );
+            cell.layout
+                .draw(Shape::new(cell.layer_stack.layers[ctl].id, strap))?;
+            cell.assign_grid_points(
+                Some(io.schematic.dout),
+                ctl,
+                cell.layer_stack
+                    .slice(0..pin + 1)
+                    .shrink_to_lcm_units(strap)
+                    .unwrap(),
+            );
+        }
 
-        // Route `pu_ctl` and `pd_ctlb` to layer 2 at bottom of unit.
-        let bot_track_y = cell.layer_stack.layers[3]
+        // Route `pu_ctl` and `pd_ctlb` to the layer beneath the pin layer at bottom of unit.
+        let bot_track_y = cell.layer_stack.layers[pin]
             .inner
             .tracks()
             .to_track_idx(bbox.bot(), RoundingMode::Up);
-        let left_track_x = cell.layer_stack.layers[2]
+        let left_track_x = cell.layer_stack.layers[ctl]
             .inner
             .tracks()
             .to_track_idx(bbox.left(), RoundingMode::Up);
@@ -623,15 +1525,15 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
         {
             let y_track_idx = bot_track_y + 1;
             let x_track_idx = left_track_x + 1 + i as i64;
-            let y_track = cell.layer_stack.layers[3].inner.tracks().get(y_track_idx);
-            let x_track = cell.layer_stack.layers[2].inner.tracks().get(x_track_idx);
+            let y_track = cell.layer_stack.layers[pin].inner.tracks().get(y_track_idx);
+            let x_track = cell.layer_stack.layers[ctl].inner.tracks().get(x_track_idx);
             cell.layout.draw(Shape::new(
-                cell.layer_stack.layers[2].id,
+                cell.layer_stack.layers[ctl].id,
                 Rect::from_spans(x_track, y_track),
             ))?;
             cell.assign_grid_points(
                 Some(port),
-                2,
+                ctl,
                 Rect::from_point(Point::new(x_track_idx, y_track_idx)),
             );
             layout.push(IoShape::with_layers(
@@ -641,7 +1543,10 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
         }
 
         io.layout.din.merge(nor_pd_data.layout.io().g);
-        io.layout.dout.merge(pu_res.layout.io().p);
+        match &res {
+            Some((_, pu_res)) => io.layout.dout.merge(pu_res.layout.io().p),
+            None => io.layout.dout.merge(driver_pu.layout.io().d),
+        }
         io.layout.vdd.merge(ntap_driver_top.layout.io().x);
         io.layout.vss.merge(ptap_driver_bot.layout.io().x);
 
@@ -652,8 +1557,15 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
 
         T::post_layout_hooks(cell)?;
 
+        let probes = Probes::new::<Self>([
+            ("pd_en", pd_en),
+            ("pu_en", pu_en),
+            ("pd_x", pd_x),
+            ("pu_x", pu_x),
+        ]);
+
         Ok((
-            (),
+            probes,
             HorizontalDriverUnitLayoutData {
                 driver_pd_bbox: driver_pd.layout.bbox_rect(),
                 driver_pu_bbox: driver_pu.layout.bbox_rect(),
@@ -665,6 +1577,8 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
                     ptap_driver_bot.layout.bbox_rect(),
                     ptap_driver_top.layout.bbox_rect(),
                 ],
+                pu_ctl_inv_bboxes: pu_ctl_inv.into_iter().flatten().collect(),
+                pd_ctl_inv_bboxes: pd_ctl_inv.into_iter().flatten().collect(),
                 dout: dout_rect,
                 filler_bboxes: [
                     (
@@ -678,21 +1592,27 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
                 ]
                 .into_iter()
                 .map(|(a, b)| a.union(*b))
+                .chain(pu_ctl_inv.map(|[n, _, _]| n))
+                .chain(pd_ctl_inv.map(|[n, _, ptap]| n.union(ptap)))
                 .collect(),
                 nwell_filler_bboxes: [
                     (
-                        &ntap_nand.layout.bbox_rect(),
-                        &nand_pu_data.layout.bbox_rect(),
+                        ntap_nand.layout.bbox_rect(),
+                        nand_pu_data.layout.bbox_rect(),
                     ),
-                    (
-                        &nor_pu_data.layout.bbox_rect(),
-                        &ntap_nor.layout.bbox_rect(),
-                    ),
-                    (&pu_res.layout.bbox_rect(), &pd_res.layout.bbox_rect()),
+                    (nor_pu_data.layout.bbox_rect(), ntap_nor.layout.bbox_rect()),
                 ]
                 .into_iter()
-                .map(|(a, b)| a.union(*b))
+                .chain(
+                    res.as_ref().map(|(pd_res, pu_res)| {
+                        (pu_res.layout.bbox_rect(), pd_res.layout.bbox_rect())
+                    }),
+                )
+                .map(|(a, b)| a.union(b))
+                .chain(pu_ctl_inv.map(|[_, p, ntap]| p.union(ntap)))
+                .chain(pd_ctl_inv.map(|[_, p, _]| p))
                 .collect(),
+                params: snapped,
             },
         ))
     }
@@ -720,9 +1640,8 @@ impl<T: Any> Block for HorizontalDriverWithGuardRingRails<T> {
         substrate::arcstr::literal!("horizontal_driver")
     }
 
-    // todo: include parameters in name
     fn name(&self) -> ArcStr {
-        substrate::arcstr::literal!("horizontal_driver")
+        crate::block_name("horizontal_driver_with_guard_ring_rails", &self.0)
     }
 
     fn io(&self) -> Self::Io {
@@ -731,6 +1650,8 @@ impl<T: Any> Block for HorizontalDriverWithGuardRingRails<T> {
             dout: Default::default(),
             pu_ctl: Array::new(self.0.num_segments, Default::default()),
             pd_ctlb: Array::new(self.0.num_segments, Default::default()),
+            spare_pu_ctl: Array::new(self.0.spares_per_bank(), Default::default()),
+            spare_pd_ctlb: Array::new(self.0.spares_per_bank(), Default::default()),
             vdd: Default::default(),
             vss: Default::default(),
             guard_ring_vdd: Default::default(),
@@ -746,7 +1667,7 @@ impl<T: Any> ExportsNestedData for HorizontalDriverWithGuardRingRails<T> {
 /// Layout data returned by the [`HorizontalDriverWithGuardRingRails`] layout generator.
 #[derive(LayoutData)]
 pub struct HorizontalDriverWithGuardRingRailsLayoutData {
-    /// The `dout` pin geometry located on layer 7.
+    /// The `dout` pin geometry located on the strap layer.
     pub dout: Vec<Rect>,
 }
 
@@ -757,6 +1678,7 @@ impl<T: Any> ExportsLayoutData for HorizontalDriverWithGuardRingRails<T> {
 impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
     for HorizontalDriverWithGuardRingRails<T>
 {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
     fn tile<'a>(
         &self,
         io: IoBuilder<'a, Self>,
@@ -766,15 +1688,29 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
         <Self as ExportsLayoutData>::LayoutData,
     )> {
         let mut units = Vec::new();
-        // Instantiate driver units.
-        for i in 0..self.0.num_segments {
+        // Instantiate driver units. The spare, if any, is the last unit.
+        let segments = self.0.segments_per_bank();
+        let n = self.0.num_segments;
+        let order = self.0.segment_order();
+        let blocks = vec![HorizontalDriverUnit::<T>::new(self.0.unit); segments];
+        // Identical units share one cell, generated before any of them are placed,
+        // while each instance keeps its own control nets.
+        pregenerate(cell.ctx(), blocks.iter().copied());
+        for (i, block) in blocks.into_iter().enumerate() {
+            let (pu_ctl, pd_ctlb) = match i.checked_sub(n) {
+                None => (
+                    io.schematic.pu_ctl[order[i]],
+                    io.schematic.pd_ctlb[order[i]],
+                ),
+                Some(j) => (io.schematic.spare_pu_ctl[j], io.schematic.spare_pd_ctlb[j]),
+            };
             let mut unit = cell.generate_connected(
-                HorizontalDriverUnit::<T>::new(self.0.unit),
+                block,
                 DriverUnitIoSchematic {
                     din: io.schematic.din,
                     dout: io.schematic.dout,
-                    pu_ctl: io.schematic.pu_ctl[i],
-                    pd_ctlb: io.schematic.pd_ctlb[i],
+                    pu_ctl,
+                    pd_ctlb,
                     vdd: io.schematic.vdd,
                     vss: io.schematic.vss,
                 },
@@ -792,56 +1728,81 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
             .enumerate()
             .map(|(i, unit)| {
                 let unit = cell.draw(unit)?;
+                let (pu_ctl_pin, pd_ctlb_pin) = match i.checked_sub(n) {
+                    None => (
+                        &mut io.layout.pu_ctl[order[i]],
+                        &mut io.layout.pd_ctlb[order[i]],
+                    ),
+                    Some(j) => (
+                        &mut io.layout.spare_pu_ctl[j],
+                        &mut io.layout.spare_pd_ctlb[j],
+                    ),
+                };
+                pu_ctl_pin.merge(unit.layout.io().pu_ctl);
+                pd_ctlb_pin.merge(unit.layout.io().pd_ctlb);
                 io.layout.din.merge(unit.layout.io().din);
                 io.layout.dout.merge(unit.layout.io().dout);
-                io.layout.pu_ctl[i].merge(unit.layout.io().pu_ctl);
-                io.layout.pd_ctlb[i].merge(unit.layout.io().pd_ctlb);
                 io.layout.vdd.merge(unit.layout.io().vdd);
                 io.layout.vss.merge(unit.layout.io().vss);
                 Ok(unit)
             })
             .collect::<Result<Vec<_>>>()?;
 
-        // Fill in extra dummies and taps for continuous diffusion for pull-up/pull-down transistors.
+        // Fill in extra dummies and taps for continuous diffusion for pull-up/pull-down
+        // transistors and for the control polarity inverters, if any.
         let nf = T::nf(self.0.unit.res_legs, self.0.unit.res_w);
-        for unit in units.iter().take(self.0.num_segments - 1) {
-            // Draw dummy transistors.
-            let pu_bbox = unit.layout.data().driver_pu_bbox;
-            let pu_loc = Rect::from_xy(pu_bbox.right(), pu_bbox.center().y);
-            T::draw_dummy_mos(
-                cell,
-                TileKind::P,
-                2,
-                self.0.unit.driver_pu_w,
-                pu_loc.center(),
-                Orientation::ReflectVert,
-            )?;
-            let pd_bbox = unit.layout.data().driver_pd_bbox;
-            let pd_loc = Rect::from_xy(pd_bbox.right(), pd_bbox.center().y);
-            T::draw_dummy_mos(
-                cell,
-                TileKind::N,
-                2,
-                self.0.unit.driver_pd_w,
-                pd_loc.center(),
-                Orientation::R0,
-            )?;
-
-            // Draw additional taps.
-            for (tap_bbox, kind, node) in unit
-                .layout
-                .data()
+        let (driver_pu_w, driver_pd_w) = units[0].layout.data().params.driver_widths();
+        for unit in units.iter().take(segments - 1) {
+            let data = unit.layout.data();
+            let params = data.params;
+            let mut dummies = vec![
+                (
+                    TileKind::P,
+                    driver_pu_w,
+                    data.driver_pu_bbox,
+                    Orientation::ReflectVert,
+                ),
+                (
+                    TileKind::N,
+                    driver_pd_w,
+                    data.driver_pd_bbox,
+                    Orientation::R0,
+                ),
+            ];
+            let mut taps = data
                 .driver_ntap_bboxes
                 .iter()
                 .map(|bbox| (*bbox, TileKind::N, io.schematic.vdd))
                 .chain(
-                    unit.layout
-                        .data()
-                        .driver_ptap_bboxes
+                    data.driver_ptap_bboxes
                         .iter()
                         .map(|bbox| (*bbox, TileKind::P, io.schematic.vss)),
                 )
-            {
+                .collect::<Vec<_>>();
+            if let [n, p, ntap] = data.pu_ctl_inv_bboxes[..] {
+                dummies.push((TileKind::N, params.nand_pd_en_w, n, Orientation::R0));
+                dummies.push((
+                    TileKind::P,
+                    params.nand_pu_en_w,
+                    p,
+                    Orientation::ReflectVert,
+                ));
+                taps.push((ntap, TileKind::N, io.schematic.vdd));
+            }
+            if let [n, p, ptap] = data.pd_ctl_inv_bboxes[..] {
+                dummies.push((TileKind::N, params.nor_pd_en_w, n, Orientation::R0));
+                dummies.push((TileKind::P, params.nor_pu_en_w, p, Orientation::ReflectVert));
+                taps.push((ptap, TileKind::P, io.schematic.vss));
+            }
+
+            // Draw dummy transistors.
+            for (kind, w, bbox, orientation) in dummies {
+                let loc = Rect::from_xy(bbox.right(), bbox.center().y);
+                T::draw_dummy_mos(cell, kind, 2, w, loc.center(), orientation)?;
+            }
+
+            // Draw additional taps.
+            for (tap_bbox, kind, node) in taps {
                 let tap_loc = cell
                     .layer_stack
                     .slice(0..2)
@@ -860,7 +1821,7 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
         for sign in [Sign::Neg, Sign::Pos] {
             let unit = &units[match sign {
                 Sign::Neg => 0,
-                Sign::Pos => self.0.num_segments - 1,
+                Sign::Pos => segments - 1,
             }];
             for (bbox, kind) in unit
                 .layout
@@ -901,12 +1862,12 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
             .layout
             .data()
             .driver_pu_bbox
-            .union(units[self.0.num_segments - 1].layout.data().driver_pu_bbox);
+            .union(units[segments - 1].layout.data().driver_pu_bbox);
         let pd_bbox = units[0]
             .layout
             .data()
             .driver_pd_bbox
-            .union(units[self.0.num_segments - 1].layout.data().driver_pd_bbox);
+            .union(units[segments - 1].layout.data().driver_pd_bbox);
 
         // Draw pull-up and pull-down guard rings.
         let mut guard_rings = Vec::new();
@@ -919,7 +1880,7 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
                 .generate_connected(
                     T::guard_ring(
                         kind,
-                        self.0.num_segments as i64,
+                        segments as i64,
                         nf,
                         bbox.height() / cell.layer_stack.layer(1).pitch(),
                     ),
@@ -935,27 +1896,25 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
         io.layout.guard_ring_vss.merge(guard_ring_p.layout.io().x);
 
         let via_maker = T::via_maker();
-
-        // Via up `dout` to layer 7.
-        let mut via_stack: Vec<(usize, Shape)> = Vec::new();
-        for layer in 4..8 {
-            via_stack.extend(
-                via_maker
-                    .draw_via(cell.ctx().clone(), TrackCoord { layer, x: 0, y: 0 })
-                    .into_iter()
-                    .map(|shape| (layer, shape)),
-            );
-        }
+        let layers = T::LAYER_MAP;
+
+        // Via up `dout` to the strap layer.
+        let via_stack = via::via_stack(
+            &via_maker,
+            cell.ctx(),
+            layers.pin + 1..layers.strap + 1,
+            NetClass::Dout,
+        );
         let mut dout = Vec::new();
         for unit in units.iter() {
             let mut unit_dout = Vec::new();
             // Draw vias.
             for (layer, shape) in &via_stack {
-                let shape = shape.clone().translate(
-                    unit.layout.data().dout.bbox_rect().center() - shape.bbox_rect().center(),
-                );
+                let shape = shape
+                    .clone()
+                    .translate(unit.layout.data().dout.bbox_rect().center());
                 cell.layout.draw(shape.clone())?;
-                if shape.layer() == cell.layer_stack.layers[7].id {
+                if shape.layer() == cell.layer_stack.layers[layers.strap].id {
                     unit_dout.push(shape.bbox_rect());
                 }
 
@@ -982,7 +1941,7 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
             dout.push(unit_dout.bbox_rect());
         }
 
-        let top_slice = cell.layer_stack.slice(0..8);
+        let top_slice = cell.layer_stack.slice(0..layers.strap + 1);
         let overall_bbox = top_slice.expand_to_lcm_units(cell.layout.bbox_rect());
         let physical_overall_bbox = top_slice.lcm_to_physical_rect(overall_bbox);
 
@@ -991,8 +1950,8 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
             .draw(Shape::new(virtual_layers.outline, physical_overall_bbox))?;
 
         // Extend ctl pins to edge.
-        for i in 0..self.0.num_segments {
-            for port in [units[i].layout.io().pu_ctl, units[i].layout.io().pd_ctlb] {
+        for unit in units.iter() {
+            for port in [unit.layout.io().pu_ctl, unit.layout.io().pd_ctlb] {
                 let pin_rect = port.primary.bbox_rect();
                 let pin_rect =
                     pin_rect.with_vspan(pin_rect.vspan().add_point(physical_overall_bbox.bot()));
@@ -1000,13 +1959,15 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
                     .draw(Shape::new(port.primary.layer().drawing(), pin_rect))?;
                 cell.assign_grid_points(
                     None,
-                    2,
-                    cell.layer_stack.slice(0..3).expand_to_lcm_units(pin_rect),
+                    layers.pin - 1,
+                    cell.layer_stack
+                        .slice(0..layers.pin)
+                        .expand_to_lcm_units(pin_rect),
                 );
             }
         }
 
-        let top_slice = cell.layer_stack.slice(0..8);
+        let top_slice = cell.layer_stack.slice(0..layers.strap + 1);
 
         // Determine strapping domains.
         let guard_ring_p_bbox = top_slice
@@ -1043,162 +2004,114 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
             .translate(Point::zero() - overall_bbox.corner(Corner::LowerLeft));
 
         // Strap guard ring rails only over the appropriate rings.
-        cell.set_strapping(
-            io.schematic.guard_ring_vss,
-            StrappingParams::new(
-                1,
-                vec![
-                    LayerStrappingParams::ViaDown { min_period: 3 },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 3,
-                        period: 5,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 0,
-                        period: 7,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 5,
-                        period: 9,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 0,
-                        period: 2,
-                    },
-                ],
-            )
-            .with_bounds(guard_ring_p_bbox),
-        );
-        cell.set_strapping(
-            io.schematic.guard_ring_vdd,
-            StrappingParams::new(
-                1,
-                vec![
-                    LayerStrappingParams::ViaDown { min_period: 3 },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 3,
-                        period: 5,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 0,
-                        period: 7,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 5,
-                        period: 9,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 0,
-                        period: 2,
-                    },
-                ],
-            )
-            .with_bounds(guard_ring_n_bbox),
-        );
+        let strap_layers = layers.bank_strap_layers();
+        for (net, ring, bbox) in [
+            (
+                io.schematic.guard_ring_vss,
+                &guard_ring_p,
+                guard_ring_p_bbox,
+            ),
+            (
+                io.schematic.guard_ring_vdd,
+                &guard_ring_n,
+                guard_ring_n_bbox,
+            ),
+        ] {
+            let bounds = Rect::from_spans(
+                cell.layout.bbox_rect().hspan(),
+                ring.layout.bbox_rect().vspan(),
+            );
+            let periods = plan_straps::<PDK, T>(
+                cell,
+                bounds,
+                strap_layers.clone(),
+                &GUARD_RING_STRAP_BUDGET,
+            )?;
+            set_strapping(
+                cell,
+                net,
+                planned_strapping(3, &periods, GUARD_RING_STRAP_OFFSET, layers.strap)
+                    .with_bounds(bbox),
+            );
+        }
 
         // Strap `din`.
-        cell.set_strapping(
+        let din_periods = plan_straps::<PDK, T>(
+            cell,
+            cell.layout.bbox_rect(),
+            strap_layers.clone(),
+            &DIN_STRAP_BUDGET,
+        )?;
+        set_strapping(
+            cell,
             io.schematic.din,
-            StrappingParams::new(
-                1,
-                vec![
-                    LayerStrappingParams::ViaDown { min_period: 1 },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 2,
-                        period: 10,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 8,
-                        period: 22,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 8,
-                        period: 18,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 8,
-                        period: 13,
-                    },
-                ],
-            ),
+            planned_strapping(1, &din_periods, DIN_STRAP_OFFSET, layers.strap),
         );
 
+        // Each bank carries an equal share of the driver's supply current.
+        let supply_periods = plan_straps::<PDK, T>(
+            cell,
+            cell.layout.bbox_rect(),
+            strap_layers.clone(),
+            &self.0.supply_budget().split(self.0.banks),
+        )?;
+
         // Strap VSS with high density on layer 1 over the pull-up/pull-down networks.
-        cell.set_strapping(
+        set_strapping(
+            cell,
             io.schematic.vss,
-            StrappingParams::new(1, vec![LayerStrappingParams::ViaDown { min_period: 1 }])
-                .with_bounds(pu_network_bbox),
+            strapping_below(
+                1,
+                vec![LayerStrappingParams::ViaDown { min_period: 1 }],
+                layers.strap,
+            )
+            .with_bounds(pu_network_bbox),
         );
-        cell.set_strapping(
+        set_strapping(
+            cell,
             io.schematic.vss,
-            StrappingParams::new(1, vec![LayerStrappingParams::ViaDown { min_period: 1 }])
-                .with_bounds(pd_network_bbox),
+            strapping_below(
+                1,
+                vec![LayerStrappingParams::ViaDown { min_period: 1 }],
+                layers.strap,
+            )
+            .with_bounds(pd_network_bbox),
         );
         // Strap VSS over the entire driver.
-        cell.set_strapping(
+        set_strapping(
+            cell,
             io.schematic.vss,
-            StrappingParams::new(
-                1,
-                vec![
-                    LayerStrappingParams::ViaDown { min_period: 3 },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 0,
-                        period: 5,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 0,
-                        period: 11,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 0,
-                        period: 9,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 0,
-                        period: 13,
-                    },
-                ],
-            ),
+            planned_strapping(3, &supply_periods, VSS_STRAP_OFFSET, layers.strap),
         );
         // Strap VDD with high density on layer 1 over the pull-up/pull-down networks.
-        cell.set_strapping(
+        set_strapping(
+            cell,
             io.schematic.vdd,
-            StrappingParams::new(1, vec![LayerStrappingParams::ViaDown { min_period: 1 }])
-                .with_bounds(pu_network_bbox),
+            strapping_below(
+                1,
+                vec![LayerStrappingParams::ViaDown { min_period: 1 }],
+                layers.strap,
+            )
+            .with_bounds(pu_network_bbox),
         );
-        cell.set_strapping(
+        set_strapping(
+            cell,
             io.schematic.vdd,
-            StrappingParams::new(1, vec![LayerStrappingParams::ViaDown { min_period: 1 }])
-                .with_bounds(pd_network_bbox),
+            strapping_below(
+                1,
+                vec![LayerStrappingParams::ViaDown { min_period: 1 }],
+                layers.strap,
+            )
+            .with_bounds(pd_network_bbox),
         );
         // Strap VDD over the entire driver.
-        cell.set_strapping(
+        set_strapping(
+            cell,
             io.schematic.vdd,
-            StrappingParams::new(
-                1,
-                vec![
-                    LayerStrappingParams::ViaDown { min_period: 3 },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 1,
-                        period: 5,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 1,
-                        period: 11,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 1,
-                        period: 9,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 1,
-                        period: 13,
-                    },
-                ],
-            ),
+            planned_strapping(3, &supply_periods, VDD_STRAP_OFFSET, layers.strap),
         );
 
-        cell.set_top_layer(7);
+        cell.set_top_layer(layers.strap);
         cell.set_strapper(GreedyStrapper);
         cell.set_via_maker(via_maker);
 
@@ -1221,6 +2134,11 @@ impl<T> HorizontalDriver<T> {
     pub fn new(params: DriverParams) -> Self {
         Self(params, PhantomData)
     }
+
+    /// The driver parameters.
+    pub fn params(&self) -> DriverParams {
+        self.0
+    }
 }
 
 impl<T: Any> Block for HorizontalDriver<T> {
@@ -1230,9 +2148,8 @@ impl<T: Any> Block for HorizontalDriver<T> {
         substrate::arcstr::literal!("horizontal_driver")
     }
 
-    // todo: include parameters in name
     fn name(&self) -> ArcStr {
-        substrate::arcstr::literal!("horizontal_driver")
+        crate::block_name("horizontal_driver", &self.0)
     }
 
     fn io(&self) -> Self::Io {
@@ -1241,6 +2158,8 @@ impl<T: Any> Block for HorizontalDriver<T> {
             dout: Default::default(),
             pu_ctl: Array::new(self.0.num_segments * self.0.banks, Default::default()),
             pd_ctlb: Array::new(self.0.num_segments * self.0.banks, Default::default()),
+            spare_pu_ctl: Array::new(self.0.spares_per_bank() * self.0.banks, Default::default()),
+            spare_pd_ctlb: Array::new(self.0.spares_per_bank() * self.0.banks, Default::default()),
             vdd: Default::default(),
             vss: Default::default(),
         }
@@ -1251,13 +2170,27 @@ impl<T: Any> ExportsNestedData for HorizontalDriver<T> {
     type NestedData = ();
 }
 
+/// Layout data returned by the [`HorizontalDriver`] layout generator.
+#[derive(LayoutData)]
+pub struct HorizontalDriverLayoutData {
+    /// The straps tying `dout` together across banks, one per unit slot,
+    /// drawn on the layer above [`DriverLayerMap::strap`].
+    pub dout_straps: Vec<Rect>,
+    /// The bump rectangle of each bank, drawn on [`DriverLayerMap::bump`].
+    ///
+    /// Empty with ESD protection, since the banks then drive the core side of the
+    /// series resistor and only the pad side reaches the bump.
+    pub dout_bumps: Vec<Rect>,
+}
+
 impl<T: Any> ExportsLayoutData for HorizontalDriver<T> {
-    type LayoutData = ();
+    type LayoutData = HorizontalDriverLayoutData;
 }
 
 impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
     for HorizontalDriver<T>
 {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
     fn tile<'a>(
         &self,
         io: IoBuilder<'a, Self>,
@@ -1266,32 +2199,49 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
         <Self as ExportsNestedData>::NestedData,
         <Self as ExportsLayoutData>::LayoutData,
     )> {
-        let mut layer8_vias = vec![Vec::new(); self.0.num_segments];
+        let layers = T::LAYER_MAP;
+        let mut bank_strap_vias = vec![Vec::new(); self.0.segments_per_bank()];
+        let mut dout_bumps = Vec::new();
         let mut prev_bounds: Option<Rect> = None;
-        // Instantiate and draw banks.
-        for i in 0..self.0.banks {
-            let mut driver = cell
-                .generate(HorizontalDriverWithGuardRingRails::<T>::new(self.0))
-                .orient(if i % 2 == 0 {
-                    Orientation::R0
-                } else {
-                    Orientation::ReflectVert
-                });
+        let mut first_bounds: Option<Rect> = None;
+        // With ESD protection, the banks drive an internal node and only the pad side of
+        // the series resistor reaches the bump.
+        let dout = match self.0.esd {
+            Some(_) => cell.signal("dout_core", Signal::new()),
+            None => io.schematic.dout,
+        };
+        let via_top = match self.0.esd {
+            Some(_) => layers.strap + 1,
+            None => layers.bump,
+        };
+        // Instantiate and draw banks. The distinct banks are generated concurrently
+        // before any are placed, and identical banks share one cell.
+        let blocks = vec![HorizontalDriverWithGuardRingRails::<T>::new(self.0); self.0.banks];
+        pregenerate(cell.ctx(), blocks.iter().copied());
+        for (i, block) in blocks.into_iter().enumerate() {
+            let mut driver = cell.generate(block).orient(if i % 2 == 0 {
+                Orientation::R0
+            } else {
+                Orientation::ReflectVert
+            });
             if let Some(prev_bounds) = prev_bounds {
                 driver.align_rect_mut(prev_bounds, AlignMode::Above, 1);
             }
             prev_bounds = Some(driver.lcm_bounds());
+            first_bounds.get_or_insert(driver.lcm_bounds());
 
             let driver = cell.draw(driver)?;
 
             cell.connect(driver.schematic.io().din, io.schematic.din);
-            cell.connect(driver.schematic.io().dout, io.schematic.dout);
+            cell.connect(driver.schematic.io().dout, dout);
             cell.connect(driver.schematic.io().vdd, io.schematic.vdd);
             cell.connect(driver.schematic.io().vss, io.schematic.vss);
             cell.connect(driver.schematic.io().guard_ring_vdd, io.schematic.vdd);
             cell.connect(driver.schematic.io().guard_ring_vss, io.schematic.vss);
             io.layout.din.merge(driver.layout.io().din);
-            io.layout.dout.merge(driver.layout.io().dout);
+            if self.0.esd.is_none() {
+                io.layout.dout.merge(driver.layout.io().dout);
+            }
             io.layout.vdd.merge(driver.layout.io().vdd);
             io.layout.vss.merge(driver.layout.io().vss);
             for j in 0..self.0.num_segments {
@@ -1308,29 +2258,48 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
                 io.layout.pd_ctlb[self.0.num_segments * i + j]
                     .merge(driver.layout.io().pd_ctlb[j].clone());
             }
+            for j in 0..self.0.spares_per_bank() {
+                let k = self.0.spares_per_bank() * i + j;
+                cell.connect(
+                    driver.schematic.io().spare_pu_ctl[j],
+                    io.schematic.spare_pu_ctl[k],
+                );
+                cell.connect(
+                    driver.schematic.io().spare_pd_ctlb[j],
+                    io.schematic.spare_pd_ctlb[k],
+                );
+                io.layout.spare_pu_ctl[k].merge(driver.layout.io().spare_pu_ctl[j].clone());
+                io.layout.spare_pd_ctlb[k].merge(driver.layout.io().spare_pd_ctlb[j].clone());
+            }
 
-            // Via up `dout` nets from each unit to layer 9 and draw a rectangle connecting them all.
+            // Via up `dout` nets from each unit to the bump layer and draw a rectangle connecting them all.
             let via_maker = T::via_maker();
-            let bump_rect = Rect::from_spans(
-                cell.layout.bbox_rect().hspan(),
-                Span::from_center_span(driver.layout.data().dout[0].center().y, T::BUMP_RECT_WIDTH),
-            );
-            cell.layout
-                .draw(Shape::new(cell.layer_stack.layers[9].id, bump_rect))?;
-            let mut via_stack = Vec::new();
-            for layer in 8..10 {
-                via_stack.extend(
-                    via_maker.draw_via(cell.ctx().clone(), TrackCoord { layer, x: 0, y: 0 }),
+            if self.0.esd.is_none() {
+                let bump_rect = Rect::from_spans(
+                    cell.layout.bbox_rect().hspan(),
+                    Span::from_center_span(
+                        driver.layout.data().dout[0].center().y,
+                        T::BUMP_RECT_WIDTH,
+                    ),
                 );
+                cell.layout.draw(Shape::new(
+                    cell.layer_stack.layers[layers.bump].id,
+                    bump_rect,
+                ))?;
+                dout_bumps.push(bump_rect);
             }
+            let via_stack = via::via_stack(
+                &via_maker,
+                cell.ctx(),
+                layers.strap + 1..via_top + 1,
+                NetClass::Dout,
+            );
             for (j, dout) in driver.layout.data().dout.into_iter().enumerate() {
-                for shape in &via_stack {
-                    let shape = shape
-                        .clone()
-                        .translate(dout.center() - shape.bbox_rect().center());
-                    // Track layer 8 vias to strap with other banks.
-                    if shape.layer() == cell.layer_stack.layers[8].id {
-                        layer8_vias[j].push(shape.bbox_rect());
+                for (_, shape) in &via_stack {
+                    let shape = shape.clone().translate(dout.center());
+                    // Track vias above the strap layer to strap with other banks.
+                    if shape.layer() == cell.layer_stack.layers[layers.strap + 1].id {
+                        bank_strap_vias[j].push(shape.bbox_rect());
                     }
                     cell.layout.draw(shape.clone())?;
                 }
@@ -1338,83 +2307,122 @@ impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK>
         }
 
         // Strap `dout` across banks.
-        for vias in layer8_vias {
-            cell.layout
-                .draw(Shape::new(cell.layer_stack.layers[8].id, vias.bbox_rect()))?;
+        let mut dout_straps = Vec::new();
+        for vias in bank_strap_vias {
+            let strap = vias.bbox_rect();
+            cell.layout.draw(Shape::new(
+                cell.layer_stack.layers[layers.strap + 1].id,
+                strap,
+            ))?;
+            dout_straps.push(strap);
         }
 
-        // Strap `din`, `vss`, and `vdd`.
-        cell.set_strapping(
-            io.schematic.din,
-            StrappingParams::new(
-                6,
-                vec![
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 5,
-                        period: 8,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 5,
-                        period: 8,
-                    },
-                ],
-            ),
-        );
-        cell.set_strapping(
-            io.schematic.vss,
-            StrappingParams::new(
-                6,
-                vec![
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 2,
-                        period: 8,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 2,
-                        period: 8,
-                    },
-                ],
-            ),
-        );
-        cell.set_strapping(
-            io.schematic.vdd,
-            StrappingParams::new(
-                6,
-                vec![
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 1,
-                        period: 8,
-                    },
-                    LayerStrappingParams::OffsetPeriod {
-                        offset: 1,
-                        period: 8,
-                    },
-                ],
-            ),
-        );
+        // Place the ESD protection beside the first bank and give it the bump.
+        if let (Some(params), Some(bounds)) = (self.0.esd, first_bounds) {
+            let mut esd = cell.generate_connected(
+                EsdSeries::<T>::new(params),
+                EsdSeriesIoSchematic {
+                    pad: io.schematic.dout,
+                    core: dout,
+                    vdd: io.schematic.vdd,
+                    vss: io.schematic.vss,
+                },
+            );
+            esd.align_rect_mut(bounds, AlignMode::Bottom, 0);
+            esd.align_rect_mut(bounds, AlignMode::ToTheRight, 1);
+            let esd = cell.draw(esd)?;
+            let pad = esd.layout.data().pad;
+
+            let via_maker = T::via_maker();
+            cell.layout.draw(Shape::new(
+                cell.layer_stack.layers[layers.bump].id,
+                Rect::from_spans(
+                    cell.layout.bbox_rect().hspan(),
+                    Span::from_center_span(pad.center().y, T::BUMP_RECT_WIDTH),
+                ),
+            ))?;
+            for (_, shape) in via::via_stack(
+                &via_maker,
+                cell.ctx(),
+                layers.pin + 1..layers.bump + 1,
+                NetClass::Dout,
+            ) {
+                cell.layout.draw(shape.translate(pad.center()))?;
+            }
+            io.layout.dout.merge(esd.layout.io().pad);
+            io.layout.vdd.merge(esd.layout.io().vdd);
+            io.layout.vss.merge(esd.layout.io().vss);
+        }
+
+        // Strap `din`, `vss`, and `vdd` across the banks.
+        let top_straps = layers.driver_strap_layers();
+        let bounds = cell.layout.bbox_rect();
+        for (net, budget, offset) in [
+            (io.schematic.din, DIN_STRAP_BUDGET, DIN_STRAP_OFFSET),
+            (io.schematic.vss, self.0.supply_budget(), VSS_STRAP_OFFSET),
+            (io.schematic.vdd, self.0.supply_budget(), VDD_STRAP_OFFSET),
+        ] {
+            let periods = plan_straps::<PDK, T>(cell, bounds, top_straps.clone(), &budget)?;
+            set_strapping(
+                cell,
+                net,
+                strapping_below(
+                    top_straps.start,
+                    offset_period_straps(&periods, offset),
+                    layers.top,
+                ),
+            );
+        }
 
-        cell.set_top_layer(9);
+        cell.set_top_layer(layers.top);
         cell.set_strapper(GreedyStrapper);
         cell.set_via_maker(T::via_maker());
 
         T::post_layout_hooks(cell)?;
 
-        Ok(((), ()))
+        Ok((
+            (),
+            HorizontalDriverLayoutData {
+                dout_straps,
+                dout_bumps,
+            },
+        ))
     }
 }
 
 /// A vertical driver unit.
+///
+/// Exports the same [probe points](crate::tb::probe) as a [`HorizontalDriverUnit`].
+/// Unlike the horizontal unit, it does not support inverted control polarities or
+/// unterminated outputs; see [`DriverUnitParams::check_vertical`].
+///
+/// Serializes as its [`DriverUnitParams`], which are checked when deserialized.
 #[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 #[derive(Serialize, Deserialize)]
-pub struct VerticalDriverUnit<T>(
-    DriverUnitParams,
-    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
-);
+#[serde(try_from = "DriverUnitParams", into = "DriverUnitParams", bound = "")]
+pub struct VerticalDriverUnit<T>(DriverUnitParams, PhantomData<fn() -> T>);
 
 impl<T> VerticalDriverUnit<T> {
     /// Creates a new [`VerticalDriverUnit`].
-    pub fn new(params: DriverUnitParams) -> Self {
-        Self(params, PhantomData)
+    ///
+    /// Returns an error if the parameters fail [`DriverUnitParams::check_vertical`].
+    pub fn new(params: DriverUnitParams) -> std::result::Result<Self, ParamsError> {
+        params.check_vertical()?;
+        Ok(Self(params, PhantomData))
+    }
+}
+
+impl<T> TryFrom<DriverUnitParams> for VerticalDriverUnit<T> {
+    type Error = ParamsError;
+
+    fn try_from(params: DriverUnitParams) -> std::result::Result<Self, Self::Error> {
+        Self::new(params)
+    }
+}
+
+impl<T> From<VerticalDriverUnit<T>> for DriverUnitParams {
+    fn from(unit: VerticalDriverUnit<T>) -> Self {
+        unit.0
     }
 }
 
@@ -1425,9 +2433,8 @@ impl<T: Any> Block for VerticalDriverUnit<T> {
         substrate::arcstr::literal!("vertical_driver_unit")
     }
 
-    // todo: include parameters in name
     fn name(&self) -> ArcStr {
-        substrate::arcstr::literal!("vertical_driver_unit")
+        crate::block_name("vertical_driver_unit", &self.0)
     }
 
     fn io(&self) -> Self::Io {
@@ -1436,16 +2443,28 @@ impl<T: Any> Block for VerticalDriverUnit<T> {
 }
 
 impl<T: Any> ExportsNestedData for VerticalDriverUnit<T> {
-    type NestedData = ();
+    type NestedData = Probes;
+}
+
+impl<T: Any> ProbePoints for VerticalDriverUnit<T> {
+    const PROBES: &'static [&'static str] = &["pd_en", "pu_en", "pd_x", "pu_x"];
+}
+
+/// Layout data returned by the [`VerticalDriverUnit`] layout generator.
+#[derive(LayoutData)]
+pub struct VerticalDriverUnitLayoutData {
+    /// The parameters with every MOS width replaced by the achieved width.
+    pub params: DriverUnitParams,
 }
 
 impl<T: Any> ExportsLayoutData for VerticalDriverUnit<T> {
-    type LayoutData = ();
+    type LayoutData = VerticalDriverUnitLayoutData;
 }
 
 impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK>
     for VerticalDriverUnit<T>
 {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
     fn tile<'a>(
         &self,
         io: IoBuilder<'a, Self>,
@@ -1454,22 +2473,24 @@ impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK>
         <Self as ExportsNestedData>::NestedData,
         <Self as ExportsLayoutData>::LayoutData,
     )> {
-        let nor_pu_en_params = MosTileParams::new(MosKind::Nom, TileKind::P, self.0.nor_pu_en_w);
-        let nor_pu_data_params =
-            MosTileParams::new(MosKind::Nom, TileKind::P, self.0.nor_pu_data_w);
-        let nor_pd_en_params = MosTileParams::new(MosKind::Nom, TileKind::N, self.0.nor_pd_en_w);
-        let nor_pd_data_params =
-            MosTileParams::new(MosKind::Nom, TileKind::N, self.0.nor_pd_data_w);
-        let driver_pd_params = MosTileParams::new(MosKind::Nom, TileKind::N, self.0.driver_pd_w);
-        let pd_res_params = ResistorTileParams::new(self.0.pd_res_l);
-        let pu_res_params = ResistorTileParams::new(self.0.pu_res_l);
-        let driver_pu_params = MosTileParams::new(MosKind::Nom, TileKind::P, self.0.driver_pu_w);
-        let nand_pu_en_params = MosTileParams::new(MosKind::Nom, TileKind::P, self.0.nand_pu_en_w);
-        let nand_pu_data_params =
-            MosTileParams::new(MosKind::Nom, TileKind::P, self.0.nand_pu_data_w);
-        let nand_pd_en_params = MosTileParams::new(MosKind::Nom, TileKind::N, self.0.nand_pd_en_w);
-        let nand_pd_data_params =
-            MosTileParams::new(MosKind::Nom, TileKind::N, self.0.nand_pd_data_w);
+        self.0
+            .check_lengths::<T>()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        // The remaining parameters were checked when the unit was created or deserialized.
+        let mos_params = |kind, w, l| MosTileParams::new(MosKind::Nom, kind, w).with_length(l);
+        let p = self.0.snapped(T::snap_width);
+        let nor_pu_en_params = mos_params(TileKind::P, p.nor_pu_en_w, p.nor_l);
+        let nor_pu_data_params = mos_params(TileKind::P, p.nor_pu_data_w, p.nor_l);
+        let nor_pd_en_params = mos_params(TileKind::N, p.nor_pd_en_w, p.nor_l);
+        let nor_pd_data_params = mos_params(TileKind::N, p.nor_pd_data_w, p.nor_l);
+        let driver_pd_params = mos_params(TileKind::N, p.driver_pd_w, p.driver_pd_l);
+        let pd_res_params = ResistorTileParams::new(p.pd_res_l);
+        let pu_res_params = ResistorTileParams::new(p.pu_res_l);
+        let driver_pu_params = mos_params(TileKind::P, p.driver_pu_w, p.driver_pu_l);
+        let nand_pu_en_params = mos_params(TileKind::P, p.nand_pu_en_w, p.nand_l);
+        let nand_pu_data_params = mos_params(TileKind::P, p.nand_pu_data_w, p.nand_l);
+        let nand_pd_en_params = mos_params(TileKind::N, p.nand_pd_en_w, p.nand_l);
+        let nand_pd_data_params = mos_params(TileKind::N, p.nand_pd_data_w, p.nand_l);
 
         let nor_x = cell.signal("nor_x", Signal::new());
         let nand_x = cell.signal("nand_x", Signal::new());
@@ -1611,8 +2632,28 @@ impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK>
         driver_pu.align_mut(&ntap, AlignMode::ToTheLeft, 0);
         driver_pu.align_mut(&ntap, AlignMode::Bottom, 0);
 
-        pu_res.align_mut(&driver_pu, AlignMode::ToTheLeft, 0);
-        pu_res.align_mut(&driver_pu, AlignMode::Bottom, 0);
+        // Insert taps on the far side of the driver transistors if needed.
+        let physical = |bounds| cell.layer_stack.slice(0..2).lcm_to_physical_rect(bounds);
+        let pu_needs_tap = needs_tap(
+            physical(driver_pu.lcm_bounds()),
+            &[physical(ntap.lcm_bounds())],
+            T::MAX_DIFF_TAP_DISTANCE,
+        );
+        let extra_ntap = pu_needs_tap.then(|| {
+            let mut tap = cell.generate(T::tap(TapTileParams::new(TileKind::N, 1)));
+            cell.connect(tap.io().x, io.schematic.vdd);
+            tap.align_mut(&driver_pu, AlignMode::ToTheLeft, 0);
+            tap.align_mut(&driver_pu, AlignMode::Bottom, 0);
+            tap
+        });
+
+        if let Some(extra_ntap) = &extra_ntap {
+            pu_res.align_mut(extra_ntap, AlignMode::ToTheLeft, 0);
+            pu_res.align_mut(extra_ntap, AlignMode::Bottom, 0);
+        } else {
+            pu_res.align_mut(&driver_pu, AlignMode::ToTheLeft, 0);
+            pu_res.align_mut(&driver_pu, AlignMode::Bottom, 0);
+        }
 
         pd_res.align_mut(&pu_res, AlignMode::ToTheLeft, 0);
         pd_res.align_mut(&pu_res, AlignMode::Bottom, 0);
@@ -1623,6 +2664,24 @@ impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK>
         ptap.align_mut(&driver_pd, AlignMode::ToTheLeft, 0);
         ptap.align_mut(&driver_pd, AlignMode::Bottom, 0);
 
+        let physical = |bounds| cell.layer_stack.slice(0..2).lcm_to_physical_rect(bounds);
+        let pd_needs_tap = needs_tap(
+            physical(driver_pd.lcm_bounds()),
+            &[physical(ptap.lcm_bounds())],
+            T::MAX_DIFF_TAP_DISTANCE,
+        );
+        let extra_ptap = pd_needs_tap.then(|| {
+            let mut tap = cell.generate(T::tap(TapTileParams::new(TileKind::P, 1)));
+            cell.connect(tap.io().x, io.schematic.vss);
+            tap.align_mut(&pd_res, AlignMode::ToTheLeft, 0);
+            tap.align_mut(&pd_res, AlignMode::Bottom, 0);
+            driver_pd.align_mut(&tap, AlignMode::ToTheLeft, 0);
+            driver_pd.align_mut(&tap, AlignMode::Bottom, 0);
+            ptap.align_mut(&driver_pd, AlignMode::ToTheLeft, 0);
+            ptap.align_mut(&driver_pd, AlignMode::Bottom, 0);
+            tap
+        });
+
         nor_pd_en.align_mut(&ptap, AlignMode::ToTheLeft, 0);
         nor_pd_en.align_mut(&ptap, AlignMode::Bottom, 0);
         nor_pd_data.align_mut(&nor_pd_en, AlignMode::ToTheLeft, 0);
@@ -1637,15 +2696,15 @@ impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK>
 
         let nor_pd_en = cell.draw(nor_pd_en)?;
         let _nor_pd_data = cell.draw(nor_pd_data)?;
-        let _nor_pu_en = cell.draw(nor_pu_en)?;
+        let nor_pu_en = cell.draw(nor_pu_en)?;
         let nor_pu_data = cell.draw(nor_pu_data)?;
         let _driver_pd = cell.draw(driver_pd)?;
         let pd_res = cell.draw(pd_res)?;
-        let _pu_res = cell.draw(pu_res)?;
-        let _driver_pu = cell.draw(driver_pu)?;
+        let pu_res = cell.draw(pu_res)?;
+        let driver_pu = cell.draw(driver_pu)?;
         let nand_pd_en = cell.draw(nand_pd_en)?;
         let _nand_pd_data = cell.draw(nand_pd_data)?;
-        let _nand_pu_en = cell.draw(nand_pu_en)?;
+        let nand_pu_en = cell.draw(nand_pu_en)?;
         let nand_pu_data = cell.draw(nand_pu_data)?;
 
         let ntap_bot = cell.draw(ntap_bot)?;
@@ -1653,7 +2712,14 @@ impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK>
         let ntap = cell.draw(ntap)?;
         let ptap_top = cell.draw(ptap_top)?;
 
-        for tap in [&ntap_bot, &ptap, &ntap, &ptap_top] {
+        let extra_ntap = extra_ntap.map(|tap| cell.draw(tap)).transpose()?;
+        let extra_ptap = extra_ptap.map(|tap| cell.draw(tap)).transpose()?;
+
+        for tap in [&ntap_bot, &ptap, &ntap, &ptap_top]
+            .into_iter()
+            .chain(extra_ntap.iter())
+            .chain(extra_ptap.iter())
+        {
             for shape in tap.layout.io().x.shapes() {
                 cell.layout.draw(Shape::new(
                     shape.layer().drawing(),
@@ -1662,51 +2728,44 @@ impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK>
             }
         }
 
-        let nwell = T::nwell_id(&cell.ctx().layers);
-
-        cell.layout.draw(Shape::new(
-            nwell,
-            T::nwell_transform(
-                ntap_bot
-                    .layout
-                    .layer_bbox(nwell)
-                    .unwrap()
-                    .union(nor_pu_data.layout.layer_bbox(nwell).unwrap()),
-            ),
-        ))?;
-
-        cell.layout.draw(Shape::new(
-            nwell,
-            T::nwell_transform(
-                pd_res
-                    .layout
-                    .layer_bbox(nwell)
-                    .unwrap()
-                    .union(nand_pu_data.layout.layer_bbox(nwell).unwrap()),
-            ),
-        ))?;
+        let mut nwell = WellMerger::new(T::nwell_id(&cell.ctx().layers), T::NWELL_MIN_SPACING);
+        nwell
+            .add(&ntap_bot.layout)
+            .add(&nor_pu_en.layout)
+            .add(&nor_pu_data.layout)
+            .add(&pd_res.layout)
+            .add(&pu_res.layout)
+            .add(&driver_pu.layout)
+            .add(&ntap.layout)
+            .add(&nand_pu_en.layout)
+            .add(&nand_pu_data.layout);
+        if let Some(extra_ntap) = &extra_ntap {
+            nwell.add(&extra_ntap.layout);
+        }
+        nwell.draw(cell, T::nwell_transform)?;
 
         let virtual_layers = cell.layout.ctx.install_layers::<atoll::VirtualLayers>();
         let bbox = cell.layout.layer_bbox(virtual_layers.outline.id()).unwrap();
 
-        let layer2 = cell.layer_stack.layers[2].clone();
+        let layers = T::LAYER_MAP;
+        let pin_layer = cell.layer_stack.layers[layers.pin].clone();
         // Route `din` along edges of driver.
-        let min_track = layer2
+        let min_track = pin_layer
             .inner
             .tracks()
-            .to_track_idx(bbox.left() + layer2.pitch() + 1, RoundingMode::Up);
-        let max_track = layer2
+            .to_track_idx(bbox.left() + pin_layer.pitch() + 1, RoundingMode::Up);
+        let max_track = pin_layer
             .inner
             .tracks()
-            .to_track_idx(bbox.right() - layer2.pitch() - 1, RoundingMode::Down);
+            .to_track_idx(bbox.right() - pin_layer.pitch() - 1, RoundingMode::Down);
         for track in [min_track, max_track] {
-            let track_rect = Rect::from_spans(layer2.inner.tracks().get(track), bbox.vspan());
-            cell.layout.draw(Shape::new(layer2.id, track_rect))?;
+            let track_rect = Rect::from_spans(pin_layer.inner.tracks().get(track), bbox.vspan());
+            cell.layout.draw(Shape::new(pin_layer.id, track_rect))?;
             cell.assign_grid_points(
                 Some(io.schematic.din),
-                2,
+                layers.pin,
                 cell.layer_stack
-                    .slice(0..3)
+                    .slice(0..layers.pin + 1)
                     .shrink_to_lcm_units(track_rect)
                     .unwrap(),
             );
@@ -1718,18 +2777,18 @@ impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK>
         // Route `dout` to center track.
         let virtual_layers = cell.layout.ctx.install_layers::<atoll::VirtualLayers>();
         let bbox = cell.layout.layer_bbox(virtual_layers.outline.id()).unwrap();
-        let center_track_x = layer2
+        let center_track_x = pin_layer
             .inner
             .tracks()
             .to_track_idx(bbox.center().x, RoundingMode::Nearest);
-        let center_track_y = cell.layer_stack.layers[1]
+        let center_track_y = cell.layer_stack.layers[layers.pin - 1]
             .inner
             .tracks()
             .to_track_idx(bbox.center().y, RoundingMode::Nearest);
 
         let track_rect = Rect::from_spans(
-            layer2.inner.tracks().get(center_track_x),
-            cell.layer_stack.layers[1]
+            pin_layer.inner.tracks().get(center_track_x),
+            cell.layer_stack.layers[layers.pin - 1]
                 .inner
                 .tracks()
                 .get(center_track_y),
@@ -1737,19 +2796,19 @@ impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK>
 
         cell.assign_grid_points(
             Some(io.schematic.dout),
-            2,
+            layers.pin,
             cell.layer_stack
-                .slice(0..3)
+                .slice(0..layers.pin + 1)
                 .shrink_to_lcm_units(track_rect)
                 .unwrap(),
         );
-        cell.layout.draw(Shape::new(layer2.id, track_rect))?;
+        cell.layout.draw(Shape::new(pin_layer.id, track_rect))?;
         io.layout
             .dout
             .push(IoShape::with_layers(T::pin(&cell.ctx().layers), track_rect));
 
-        cell.set_top_layer(2);
-        cell.set_router(GreedyRouter::new());
+        cell.set_top_layer(layers.pin);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
         cell.set_via_maker(T::via_maker());
 
         io.layout.pu_ctl.merge(nor_pd_en.layout.io().g);
@@ -1759,22 +2818,52 @@ impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK>
 
         T::post_layout_hooks(cell)?;
 
-        Ok(((), ()))
+        let probes = Probes::new::<Self>([
+            ("pd_en", pd_en),
+            ("pu_en", pu_en),
+            ("pd_x", pd_x),
+            ("pu_x", pu_x),
+        ]);
+
+        Ok((probes, VerticalDriverUnitLayoutData { params: p }))
     }
 }
 
 /// A vertical driver.
+///
+/// Serializes as its [`DriverParams`], which are checked with
+/// [`DriverParams::check_vertical`] when deserialized.
 #[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 #[derive(Serialize, Deserialize)]
-pub struct VerticalDriver<T>(
-    DriverParams,
-    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
-);
+#[serde(try_from = "DriverParams", into = "DriverParams", bound = "")]
+pub struct VerticalDriver<T>(DriverParams, PhantomData<fn() -> T>);
 
 impl<T> VerticalDriver<T> {
     /// Creates a new [`VerticalDriver`].
-    pub fn new(params: DriverParams) -> Self {
-        Self(params, PhantomData)
+    ///
+    /// Returns an error if the parameters fail [`DriverParams::check_vertical`].
+    pub fn new(params: DriverParams) -> std::result::Result<Self, ParamsError> {
+        params.check_vertical()?;
+        Ok(Self(params, PhantomData))
+    }
+
+    /// The driver parameters.
+    pub fn params(&self) -> DriverParams {
+        self.0
+    }
+}
+
+impl<T> TryFrom<DriverParams> for VerticalDriver<T> {
+    type Error = ParamsError;
+
+    fn try_from(params: DriverParams) -> std::result::Result<Self, Self::Error> {
+        Self::new(params)
+    }
+}
+
+impl<T> From<VerticalDriver<T>> for DriverParams {
+    fn from(driver: VerticalDriver<T>) -> Self {
+        driver.0
     }
 }
 
@@ -1785,9 +2874,8 @@ impl<T: Any> Block for VerticalDriver<T> {
         substrate::arcstr::literal!("vertical_driver")
     }
 
-    // todo: include parameters in name
     fn name(&self) -> ArcStr {
-        substrate::arcstr::literal!("vertical_driver")
+        crate::block_name("vertical_driver", &self.0)
     }
 
     fn io(&self) -> Self::Io {
@@ -1796,6 +2884,8 @@ impl<T: Any> Block for VerticalDriver<T> {
             dout: Default::default(),
             pu_ctl: Array::new(self.0.num_segments, Default::default()),
             pd_ctlb: Array::new(self.0.num_segments, Default::default()),
+            spare_pu_ctl: Array::new(self.0.spares_per_bank(), Default::default()),
+            spare_pd_ctlb: Array::new(self.0.spares_per_bank(), Default::default()),
             vdd: Default::default(),
             vss: Default::default(),
         }
@@ -1806,11 +2896,19 @@ impl<T: Any> ExportsNestedData for VerticalDriver<T> {
     type NestedData = ();
 }
 
+/// Layout data returned by the [`VerticalDriver`] layout generator.
+#[derive(LayoutData)]
+pub struct VerticalDriverLayoutData {
+    /// The bump straps, drawn on [`DriverLayerMap::bump`].
+    pub bump: Vec<Rect>,
+}
+
 impl<T: Any> ExportsLayoutData for VerticalDriver<T> {
-    type LayoutData = ();
+    type LayoutData = VerticalDriverLayoutData;
 }
 
 impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK> for VerticalDriver<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
     fn tile<'a>(
         &self,
         io: IoBuilder<'a, Self>,
@@ -1819,15 +2917,25 @@ impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK> for
         <Self as ExportsNestedData>::NestedData,
         <Self as ExportsLayoutData>::LayoutData,
     )> {
+        // The parameters were checked when the driver was created or deserialized.
         let mut units = Vec::new();
-        for i in 0..self.0.num_segments {
+        let n = self.0.num_segments;
+        let order = self.0.segment_order();
+        for i in 0..self.0.segments_per_bank() {
+            let (pu_ctl, pd_ctlb) = match i.checked_sub(n) {
+                None => (
+                    io.schematic.pu_ctl[order[i]],
+                    io.schematic.pd_ctlb[order[i]],
+                ),
+                Some(j) => (io.schematic.spare_pu_ctl[j], io.schematic.spare_pd_ctlb[j]),
+            };
             let mut unit = cell.generate_connected(
-                VerticalDriverUnit::<T>::new(self.0.unit),
+                VerticalDriverUnit::<T>(self.0.unit, PhantomData),
                 DriverUnitIoSchematic {
                     din: io.schematic.din,
                     dout: io.schematic.dout,
-                    pu_ctl: io.schematic.pu_ctl[i],
-                    pd_ctlb: io.schematic.pd_ctlb[i],
+                    pu_ctl,
+                    pd_ctlb,
                     vdd: io.schematic.vdd,
                     vss: io.schematic.vss,
                 },
@@ -1844,36 +2952,47 @@ impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK> for
             .enumerate()
             .map(|(i, unit)| {
                 let unit = cell.draw(unit)?;
+                let (pu_ctl_pin, pd_ctlb_pin) = match i.checked_sub(n) {
+                    None => (
+                        &mut io.layout.pu_ctl[order[i]],
+                        &mut io.layout.pd_ctlb[order[i]],
+                    ),
+                    Some(j) => (
+                        &mut io.layout.spare_pu_ctl[j],
+                        &mut io.layout.spare_pd_ctlb[j],
+                    ),
+                };
+                pu_ctl_pin.merge(unit.layout.io().pu_ctl);
+                pd_ctlb_pin.merge(unit.layout.io().pd_ctlb);
                 io.layout.din.merge(unit.layout.io().din);
                 io.layout.dout.merge(unit.layout.io().dout);
-                io.layout.pu_ctl[i].merge(unit.layout.io().pu_ctl);
-                io.layout.pd_ctlb[i].merge(unit.layout.io().pd_ctlb);
                 io.layout.vdd.merge(unit.layout.io().vdd);
                 io.layout.vss.merge(unit.layout.io().vss);
                 Ok(unit)
             })
             .collect::<Result<Vec<_>>>()?;
 
-        let layer3 = &cell.layer_stack.layers[3];
-        let din_connect_track = layer3.inner.tracks().to_track_idx(
+        let layers = T::LAYER_MAP;
+        let strap_layer = &cell.layer_stack.layers[layers.strap];
+        let din_connect_track = strap_layer.inner.tracks().to_track_idx(
             units[0].layout.io().din.bbox_rect().top(),
             RoundingMode::Nearest,
         );
         let din_pin = Rect::from_spans(
             units[0].layout.io().din.bbox_rect().hspan(),
-            layer3.inner.tracks().get(din_connect_track),
+            strap_layer.inner.tracks().get(din_connect_track),
         );
-        cell.layout.draw(Shape::new(layer3.id, din_pin))?;
+        cell.layout.draw(Shape::new(strap_layer.id, din_pin))?;
         let via_maker = T::via_maker();
         for shape in units[0].layout.io().din.shapes() {
-            let x_track = cell.layer_stack.layers[2]
+            let x_track = cell.layer_stack.layers[layers.pin]
                 .inner
                 .tracks()
                 .to_track_idx(shape.bbox_rect().center().x, RoundingMode::Nearest);
             for shape in via_maker.draw_via(
                 cell.ctx().clone(),
                 TrackCoord {
-                    layer: 3,
+                    layer: layers.strap,
                     x: x_track,
                     y: din_connect_track,
                 },
@@ -1882,30 +3001,265 @@ impl<PDK: Pdk + Schema + Sized, T: VerticalDriverImpl<PDK> + Any> Tile<PDK> for
             }
         }
 
-        let bump_rect = Rect::from_spans(
-            Span::from_center_span(units[0].layout.io().dout.bbox_rect().center().x, 1080),
-            cell.layout.bbox_rect().vspan(),
-        );
-        cell.layout
-            .draw(Shape::new(cell.layer_stack.layers[8].id, bump_rect))?;
-
-        let mut via_stack = Vec::new();
-        for layer in 3..9 {
-            via_stack
-                .extend(via_maker.draw_via(cell.ctx().clone(), TrackCoord { layer, x: 0, y: 0 }))
+        let bbox = cell.layout.bbox_rect();
+        let bump = match T::BUMP_RECT_DIR {
+            Dir::Vert => vec![Rect::from_spans(
+                Span::from_center_span(
+                    units[0].layout.io().dout.bbox_rect().center().x,
+                    T::BUMP_RECT_WIDTH,
+                ),
+                bbox.vspan(),
+            )],
+            Dir::Horiz => units
+                .iter()
+                .map(|unit| {
+                    Rect::from_spans(
+                        bbox.hspan(),
+                        Span::from_center_span(
+                            unit.layout.io().dout.bbox_rect().center().y,
+                            T::BUMP_RECT_WIDTH,
+                        ),
+                    )
+                })
+                .collect(),
+        };
+        for rect in bump.iter() {
+            cell.layout
+                .draw(Shape::new(cell.layer_stack.layers[layers.bump].id, *rect))?;
         }
+
+        let via_stack = via::via_stack(
+            &via_maker,
+            cell.ctx(),
+            layers.strap..layers.bump + 1,
+            NetClass::Dout,
+        );
         for unit in units.iter() {
-            for shape in &via_stack {
-                cell.layout.draw(shape.clone().translate(
-                    unit.layout.io().dout.bbox_rect().center() - shape.bbox_rect().center(),
-                ))?;
+            for (_, shape) in &via_stack {
+                cell.layout.draw(
+                    shape
+                        .clone()
+                        .translate(unit.layout.io().dout.bbox_rect().center()),
+                )?;
             }
         }
 
-        cell.set_top_layer(3);
+        cell.set_top_layer(layers.top);
 
         T::post_layout_hooks(cell)?;
 
-        Ok(((), ()))
+        Ok(((), VerticalDriverLayoutData { bump }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segment_placement_orders() {
+        assert_eq!(SegmentPlacement::Sequential.order(4), vec![0, 1, 2, 3]);
+        assert_eq!(SegmentPlacement::CenterOut.order(5), vec![3, 1, 0, 2, 4]);
+        assert_eq!(SegmentPlacement::CenterOut.order(4), vec![2, 0, 1, 3]);
+        assert_eq!(
+            SegmentPlacement::BinaryInterleaved.order(7),
+            vec![3, 1, 4, 0, 5, 2, 6]
+        );
+        for placement in [
+            SegmentPlacement::CenterOut,
+            SegmentPlacement::BinaryInterleaved,
+        ] {
+            for n in 0..20 {
+                let mut order = placement.order(n);
+                order.sort();
+                assert_eq!(order, (0..n).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn unterminated_units_resize_driver_transistors() {
+        let params = DriverUnitParams::builder().build().unwrap();
+        assert!(params.is_terminated());
+        assert_eq!(
+            params.driver_widths(),
+            (WidthSpec::Nm(2_000), WidthSpec::Nm(2_000))
+        );
+
+        let params = DriverUnitParams::builder()
+            .termination(DriverTermination::Unterminated {
+                driver_pd_w: WidthSpec::Nm(1_000),
+                driver_pu_w: WidthSpec::Nm(1_500),
+            })
+            .build()
+            .unwrap();
+        assert!(!params.is_terminated());
+        assert_eq!(
+            params.driver_widths(),
+            (WidthSpec::Nm(1_500), WidthSpec::Nm(1_000))
+        );
+        assert_eq!(
+            DriverUnitParams::builder()
+                .termination(DriverTermination::Unterminated {
+                    driver_pd_w: WidthSpec::Nm(0),
+                    driver_pu_w: WidthSpec::Nm(1_500),
+                })
+                .build(),
+            Err(ParamsError::NonPositive {
+                field: "termination.driver_pd_w",
+                value: 0
+            })
+        );
+    }
+
+    #[test]
+    fn vertical_units_reject_unsupported_params() {
+        let params = DriverUnitParams::builder().build().unwrap();
+        assert!(VerticalDriverUnit::<()>::new(params).is_ok());
+        let params = DriverUnitParams::builder()
+            .pu_ctl_polarity(CtlPolarity::ActiveLow)
+            .build()
+            .unwrap();
+        assert_eq!(
+            VerticalDriverUnit::<()>::new(params).unwrap_err(),
+            ParamsError::Unsupported {
+                field: "pu_ctl_polarity",
+                generator: "vertical driver unit",
+            }
+        );
+        let json = serde_json::to_value(params).unwrap();
+        assert_eq!(
+            serde_json::from_value::<VerticalDriverUnit<()>>(json)
+                .unwrap_err()
+                .to_string(),
+            "pu_ctl_polarity is not supported by the vertical driver unit"
+        );
+        let params = DriverUnitParams::builder()
+            .termination(DriverTermination::Unterminated {
+                driver_pd_w: WidthSpec::Nm(1_000),
+                driver_pu_w: WidthSpec::Nm(1_000),
+            })
+            .build()
+            .unwrap();
+        assert!(params.check_vertical().is_err());
+        let json = serde_json::to_value(params).unwrap();
+        assert!(serde_json::from_value::<VerticalDriverUnit<()>>(json).is_err());
+    }
+
+    #[test]
+    fn vertical_drivers_reject_esd() {
+        let params = DriverParams {
+            unit: DriverUnitParams::builder().build().unwrap(),
+            num_segments: 4,
+            banks: 1,
+            supply_budget: None,
+            spare_segment: false,
+            esd: None,
+            placement: SegmentPlacement::Sequential,
+        };
+        let driver = VerticalDriver::<()>::new(params).unwrap();
+        let json = serde_json::to_value(driver).unwrap();
+        assert_eq!(
+            serde_json::from_value::<VerticalDriver<()>>(json).unwrap(),
+            driver
+        );
+
+        let params = DriverParams {
+            esd: Some(EsdSeriesParams::builder().build().unwrap()),
+            ..params
+        };
+        let unsupported = ParamsError::Unsupported {
+            field: "esd",
+            generator: "vertical driver",
+        };
+        assert_eq!(params.check_vertical(), Err(unsupported.clone()));
+        assert_eq!(VerticalDriver::<()>::new(params).unwrap_err(), unsupported);
+        let json = serde_json::to_value(params).unwrap();
+        let err = serde_json::from_value::<VerticalDriver<()>>(json).unwrap_err();
+        assert_eq!(err.to_string(), unsupported.to_string());
+    }
+
+    #[test]
+    fn strap_layers_follow_the_layer_map() {
+        let horizontal = DriverLayerMap::HORIZONTAL;
+        assert_eq!(horizontal.bank_strap_layers(), 2..7);
+        assert_eq!(horizontal.driver_strap_layers(), 6..8);
+
+        // A 5-metal stack.
+        let small = DriverLayerMap {
+            pin: 2,
+            strap: 3,
+            bump: 5,
+            top: 5,
+        };
+        assert_eq!(small.bank_strap_layers(), 2..3);
+        assert_eq!(small.driver_strap_layers(), 2..4);
+
+        // A 12-metal stack.
+        let large = DriverLayerMap {
+            pin: 3,
+            strap: 10,
+            bump: 13,
+            top: 13,
+        };
+        assert_eq!(large.bank_strap_layers().len(), 8);
+        assert_eq!(large.driver_strap_layers(), 9..11);
+
+        // Nothing is strapped across the banks at or above the top layer.
+        let flat = DriverLayerMap { top: 3, ..small };
+        assert_eq!(flat.driver_strap_layers(), 2..3);
+    }
+
+    #[test]
+    fn default_strap_budgets_are_valid() {
+        for budget in [
+            DEFAULT_SUPPLY_BUDGET,
+            GUARD_RING_STRAP_BUDGET,
+            DIN_STRAP_BUDGET,
+        ] {
+            assert_eq!(budget.check(), Ok(()));
+        }
+
+        let params = DriverParams {
+            unit: DriverUnitParams::builder().build().unwrap(),
+            num_segments: 4,
+            banks: 2,
+            supply_budget: None,
+            spare_segment: false,
+            esd: None,
+            placement: SegmentPlacement::Sequential,
+        };
+        assert_eq!(params.supply_budget(), DEFAULT_SUPPLY_BUDGET);
+        let budget = DEFAULT_SUPPLY_BUDGET.split(4);
+        let params = DriverParams {
+            supply_budget: Some(budget),
+            ..params
+        };
+        assert_eq!(params.supply_budget(), budget);
+    }
+
+    #[test]
+    fn snapping_records_achieved_widths() {
+        // Snaps NMOS widths to 3 fins and PMOS widths up to a 1.5 um minimum.
+        let snap = |kind, width: WidthSpec| match kind {
+            TileKind::N => WidthSpec::Fins(3),
+            TileKind::P => WidthSpec::Nm(width.value().max(1_500)),
+        };
+        let params = DriverUnitParams::builder()
+            .nand_pu_en_w(2_000)
+            .termination(DriverTermination::Unterminated {
+                driver_pd_w: WidthSpec::Nm(1_000),
+                driver_pu_w: WidthSpec::Nm(1_000),
+            })
+            .build()
+            .unwrap()
+            .snapped(snap);
+        assert_eq!(params.nor_pd_en_w, WidthSpec::Fins(3));
+        assert_eq!(params.nor_pu_en_w, WidthSpec::Nm(1_500));
+        assert_eq!(params.nand_pu_en_w, WidthSpec::Nm(2_000));
+        assert_eq!(
+            params.driver_widths(),
+            (WidthSpec::Nm(1_500), WidthSpec::Fins(3))
+        );
     }
 }
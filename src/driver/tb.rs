@@ -1,7 +1,14 @@
 //! Driver verification testbenches.
 
-use crate::driver::DriverIo;
+use crate::analysis::dout_straps::DoutLumpedModel;
+use crate::code::ThermometerCode;
+use crate::driver::{DriverIo, DriverUnitParams};
+use crate::report::SimArtifact;
+use crate::sweep::McSample;
+use crate::tb::{StatisticalSimulator, UnsupportedAnalysisError};
+use crate::verification::pex::{ExtractedView, PexOutput};
 
+use ngspice::Ngspice;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use serde::de::DeserializeOwned;
@@ -9,6 +16,7 @@ use serde::{Deserialize, Serialize};
 use spectre::analysis::ac::{Ac, Sweep};
 use spectre::blocks::{AcSource, Isource, Vsource};
 use spectre::{ErrPreset, Spectre};
+use spice::Spice;
 use std::any::Any;
 use std::fmt::Debug;
 use std::hash::Hash;
@@ -20,23 +28,30 @@ use substrate::arcstr::ArcStr;
 use substrate::block::Block;
 use substrate::context::PdkContext;
 use substrate::io::schematic::{HardwareType, Node};
-use substrate::io::{Array, FlatLen, Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::io::{Array, FlatLen, Signal, TestbenchIo, TwoTerminalIo, TwoTerminalIoSchematic};
 use substrate::pdk::corner::Pvt;
 use substrate::pdk::Pdk;
-use substrate::schematic::primitives::Resistor;
+use substrate::schematic::primitives::{Capacitor, Resistor};
 use substrate::schematic::schema::Schema;
 use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
 use substrate::scir::schema::FromSchema;
 use substrate::simulation::data::{ac, FromSaved, Save, SaveTb};
 use substrate::simulation::options::SimOption;
 use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+#[cfg(feature = "xyce")]
+use xyce::Xyce;
 
 /// An AC testbench that sweeps frequency and measures output resistance.
+///
+/// The testbench runs on the simulator `S`, which defaults to [`Spectre`].
 #[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq; T, C)]
 #[derive(Serialize, Deserialize)]
-pub struct DriverAcTb<T, PDK, C> {
+pub struct DriverAcTb<T, PDK, C, S = Spectre> {
     /// The device-under-test.
     pub dut: T,
+    /// The parameters of the DUT's driver units, which set the polarity of its
+    /// segment control inputs.
+    pub unit: DriverUnitParams,
     /// The start frequency.
     pub fstart: Decimal,
     /// The stop frequency.
@@ -45,33 +60,113 @@ pub struct DriverAcTb<T, PDK, C> {
     pub vin: Decimal,
     /// The PVT corner.
     pub pvt: Pvt<C>,
-    /// Pull-up enable mask.
-    pub pu_mask: Vec<bool>,
-    /// Pull-down enable mask.
-    pub pd_mask: Vec<bool>,
+    /// The enabled pull-up segments.
+    pub pu_mask: ThermometerCode,
+    /// The enabled pull-down segments.
+    pub pd_mask: ThermometerCode,
+    /// The Monte Carlo sample to simulate, if any.
+    ///
+    /// Only supported by [statistical simulators](StatisticalSimulator).
+    mc: Option<McSample>,
+    /// A lumped model of the `dout` strap network to insert between the DUT and the
+    /// output, if any.
+    ///
+    /// The resistance is split by the capacitance into a pi network. The inductance is
+    /// not annotated, since there is no inductor primitive.
+    #[serde(default)]
+    pub dout_parasitics: Option<DoutLumpedModel>,
     #[serde(bound(deserialize = ""))]
-    phantom: PhantomData<fn() -> PDK>,
+    phantom: PhantomData<fn() -> (PDK, S)>,
 }
 
 impl<T, PDK, C> DriverAcTb<T, PDK, C> {
     /// Creates a new [`DriverAcTb`].
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         dut: T,
+        unit: DriverUnitParams,
         fstart: Decimal,
         fstop: Decimal,
         vin: Decimal,
-        pu_mask: Vec<bool>,
-        pd_mask: Vec<bool>,
+        pu_mask: ThermometerCode,
+        pd_mask: ThermometerCode,
         pvt: Pvt<C>,
     ) -> Self {
         Self {
             dut,
+            unit,
             fstart,
             fstop,
             vin,
             pvt,
             pu_mask,
             pd_mask,
+            mc: None,
+            dout_parasitics: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Back-annotates the `dout` strap network as the lumped `model`.
+    pub fn with_dout_parasitics(mut self, model: DoutLumpedModel) -> Self {
+        self.dout_parasitics = Some(model);
+        self
+    }
+}
+
+impl<T, PDK, C, S: StatisticalSimulator> DriverAcTb<T, PDK, C, S> {
+    /// Simulates the given Monte Carlo sample instead of the nominal design.
+    pub fn with_mc_sample(mut self, sample: McSample) -> Self {
+        self.mc = Some(sample);
+        self
+    }
+}
+
+impl<T, PDK, C, S> DriverAcTb<T, PDK, C, S> {
+    /// Runs this testbench on the simulator `S2` instead.
+    ///
+    /// Fails if the testbench simulates a Monte Carlo sample. Samples can only be added
+    /// on a [statistical simulator](StatisticalSimulator), and Spectre, the only such
+    /// simulator, is the default.
+    pub fn with_simulator<S2>(self) -> Result<DriverAcTb<T, PDK, C, S2>, UnsupportedAnalysisError> {
+        if self.mc.is_some() {
+            return Err(UnsupportedAnalysisError::MonteCarlo);
+        }
+        Ok(DriverAcTb {
+            dut: self.dut,
+            unit: self.unit,
+            fstart: self.fstart,
+            fstop: self.fstop,
+            vin: self.vin,
+            pvt: self.pvt,
+            pu_mask: self.pu_mask,
+            pd_mask: self.pd_mask,
+            mc: None,
+            dout_parasitics: self.dout_parasitics,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Simulates the [`ExtractedView`] of the DUT instead of its schematic.
+    ///
+    /// `pex` must be an extraction of the DUT whose subcircuit has the given `ports`, in
+    /// the order of the DUT's IO. See [`ExtractedView::new`].
+    pub fn with_extracted_dut(
+        self,
+        pex: PexOutput,
+        ports: Vec<ArcStr>,
+    ) -> DriverAcTb<ExtractedView<T>, Spice, C, S> {
+        DriverAcTb {
+            dut: ExtractedView::new(self.dut, pex, ports),
+            unit: self.unit,
+            fstart: self.fstart,
+            fstop: self.fstop,
+            vin: self.vin,
+            pvt: self.pvt,
+            pu_mask: self.pu_mask,
+            pd_mask: self.pd_mask,
+            mc: self.mc,
+            dout_parasitics: self.dout_parasitics,
             phantom: PhantomData,
         }
     }
@@ -91,7 +186,8 @@ impl<
             + Send
             + Sync
             + Any,
-    > Block for DriverAcTb<T, PDK, C>
+        S: Any,
+    > Block for DriverAcTb<T, PDK, C, S>
 {
     type Io = TestbenchIo;
 
@@ -114,38 +210,52 @@ pub struct DriverAcTbNodes {
     vout: Node,
 }
 
-impl<T, PDK, C> ExportsNestedData for DriverAcTb<T, PDK, C>
+impl<T, PDK, C, S> ExportsNestedData for DriverAcTb<T, PDK, C, S>
 where
-    DriverAcTb<T, PDK, C>: Block,
+    DriverAcTb<T, PDK, C, S>: Block,
 {
     type NestedData = DriverAcTbNodes;
 }
 
-impl<T: Block<Io = DriverIo> + Schematic<PDK> + Clone, PDK: Schema, C> Schematic<Spectre>
-    for DriverAcTb<T, PDK, C>
-where
-    DriverAcTb<T, PDK, C>: Block<Io = TestbenchIo>,
-    Spectre: FromSchema<PDK>,
-{
-    fn schematic(
+impl<T: Block<Io = DriverIo> + Schematic<PDK> + Clone, PDK: Schema, C, S> DriverAcTb<T, PDK, C, S> {
+    /// Instantiates the DUT with its control and bias sources.
+    ///
+    /// `vsrc` creates a DC voltage source, and `isrc` is the AC current source
+    /// driving the output.
+    fn schematic_with_sources<SC, V, I>(
         &self,
-        io: &<<Self as Block>::Io as HardwareType>::Bundle,
-        cell: &mut CellBuilder<Spectre>,
-    ) -> substrate::error::Result<Self::NestedData> {
+        vss: Node,
+        cell: &mut CellBuilder<SC>,
+        vsrc: impl Fn(Decimal) -> V,
+        isrc: I,
+    ) -> DriverAcTbNodes
+    where
+        SC: Schema + FromSchema<PDK>,
+        Resistor: Schematic<SC>,
+        Capacitor: Schematic<SC>,
+        V: Block<Io = TwoTerminalIo> + Schematic<SC>,
+        I: Block<Io = TwoTerminalIo> + Schematic<SC>,
+    {
         let vin = cell.signal("vin", Signal);
         let vout = cell.signal("vout", Signal);
         let vdd = cell.signal("vdd", Signal);
 
         let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
         let pu_ctl = cell.signal("pu_ctl", Array::new(dut.io().pu_ctl.len(), Signal));
-        let pd_ctlb = cell.signal("pd_ctlb", Array::new(dut.io().pu_ctl.len(), Signal));
+        let pd_ctlb = cell.signal("pd_ctlb", Array::new(dut.io().pd_ctlb.len(), Signal));
 
-        assert_eq!(pu_ctl.len(), self.pu_mask.len());
-        assert_eq!(pd_ctlb.len(), self.pd_mask.len());
+        self.pu_mask
+            .check_width(pu_ctl.len())
+            .expect("pull-up mask does not match the driver");
+        self.pd_mask
+            .check_width(pd_ctlb.len())
+            .expect("pull-down mask does not match the driver");
 
+        let rail = |high| if high { vdd } else { vss };
         for i in 0..pu_ctl.len() {
             cell.connect(&dut.io().pu_ctl[i], &pu_ctl[i]);
-            let supply = if self.pu_mask[i] { vdd } else { io.vss };
+            let (level, _) = self.unit.ctl_levels(self.pu_mask.is_enabled(i));
+            let supply = rail(level);
             cell.instantiate_connected(
                 Resistor::new(dec!(100)),
                 TwoTerminalIoSchematic {
@@ -156,7 +266,8 @@ where
         }
         for i in 0..pd_ctlb.len() {
             cell.connect(&dut.io().pd_ctlb[i], &pd_ctlb[i]);
-            let supply = if self.pd_mask[i] { io.vss } else { vdd };
+            let (_, level) = self.unit.ctl_levels(self.pd_mask.is_enabled(i));
+            let supply = rail(level);
             cell.instantiate_connected(
                 Resistor::new(dec!(100)),
                 TwoTerminalIoSchematic {
@@ -166,29 +277,115 @@ where
             );
         }
 
+        // Spare segments are always disabled.
+        let (spare_pu_ctl, spare_pd_ctlb) = self.unit.ctl_levels(false);
+        for i in 0..dut.io().spare_pu_ctl.len() {
+            cell.connect(&dut.io().spare_pu_ctl[i], rail(spare_pu_ctl));
+            cell.connect(&dut.io().spare_pd_ctlb[i], rail(spare_pd_ctlb));
+        }
+
         cell.connect(dut.io().vdd, vdd);
-        cell.connect(dut.io().vss, io.vss);
+        cell.connect(dut.io().vss, vss);
         cell.connect(dut.io().din, vin);
-        cell.connect(dut.io().dout, vout);
+        match self.dout_parasitics {
+            Some(model) => {
+                let dout = cell.signal("dout", Signal);
+                cell.connect(dut.io().dout, dout);
+                cell.instantiate_connected(
+                    Resistor::new(model.r),
+                    TwoTerminalIoSchematic { p: dout, n: vout },
+                );
+                for node in [dout, vout] {
+                    cell.instantiate_connected(
+                        Capacitor::new(model.c / dec!(2)),
+                        TwoTerminalIoSchematic { p: node, n: vss },
+                    );
+                }
+            }
+            None => cell.connect(dut.io().dout, vout),
+        }
 
+        cell.instantiate_connected(vsrc(self.vin), TwoTerminalIoSchematic { p: vin, n: vss });
         cell.instantiate_connected(
-            Vsource::dc(self.vin),
-            TwoTerminalIoSchematic { p: vin, n: io.vss },
-        );
-        cell.instantiate_connected(
-            Vsource::dc(self.pvt.voltage),
-            TwoTerminalIoSchematic { p: vdd, n: io.vss },
+            vsrc(self.pvt.voltage),
+            TwoTerminalIoSchematic { p: vdd, n: vss },
         );
-        cell.instantiate_connected(
+        cell.instantiate_connected(isrc, TwoTerminalIoSchematic { p: vss, n: vout });
+
+        DriverAcTbNodes { vout }
+    }
+}
+
+impl<T: Block<Io = DriverIo> + Schematic<PDK> + Clone, PDK: Schema, C> Schematic<Spectre>
+    for DriverAcTb<T, PDK, C>
+where
+    DriverAcTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        Ok(self.schematic_with_sources(
+            io.vss,
+            cell,
+            Vsource::dc,
             Isource::ac(AcSource {
                 dc: dec!(0),
                 mag: dec!(1),
                 phase: dec!(0),
             }),
-            TwoTerminalIoSchematic { p: io.vss, n: vout },
-        );
+        ))
+    }
+}
+
+impl<T: Block<Io = DriverIo> + Schematic<PDK> + Clone, PDK: Schema, C> Schematic<Ngspice>
+    for DriverAcTb<T, PDK, C, Ngspice>
+where
+    DriverAcTb<T, PDK, C, Ngspice>: Block<Io = TestbenchIo>,
+    Ngspice: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Ngspice>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        Ok(self.schematic_with_sources(
+            io.vss,
+            cell,
+            ngspice::blocks::Vsource::dc,
+            ngspice::blocks::Isource::ac(ngspice::blocks::AcSource {
+                dc: dec!(0),
+                mag: dec!(1),
+                phase: dec!(0),
+            }),
+        ))
+    }
+}
 
-        Ok(DriverAcTbNodes { vout })
+#[cfg(feature = "xyce")]
+impl<T: Block<Io = DriverIo> + Schematic<PDK> + Clone, PDK: Schema, C> Schematic<Xyce>
+    for DriverAcTb<T, PDK, C, Xyce>
+where
+    DriverAcTb<T, PDK, C, Xyce>: Block<Io = TestbenchIo>,
+    Xyce: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Xyce>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        Ok(self.schematic_with_sources(
+            io.vss,
+            cell,
+            xyce::blocks::Vsource::dc,
+            xyce::blocks::Isource::ac(xyce::blocks::AcSource {
+                dc: dec!(0),
+                mag: dec!(1),
+                phase: dec!(0),
+            }),
+        ))
     }
 }
 
@@ -217,6 +414,39 @@ where
     }
 }
 
+impl<T, PDK, C> SaveTb<Ngspice, ngspice::ac::Ac, DriverAcSim> for DriverAcTb<T, PDK, C, Ngspice>
+where
+    DriverAcTb<T, PDK, C, Ngspice>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Ngspice>,
+        cell: &Cell<Self>,
+        opts: &mut <Ngspice as Simulator>::Options,
+    ) -> <DriverAcSim as FromSaved<Ngspice, ngspice::ac::Ac>>::SavedKey {
+        DriverAcSimSavedKey {
+            freq: ac::Freq::save(ctx, (), opts),
+            vout: ac::Voltage::save(ctx, &cell.vout, opts),
+        }
+    }
+}
+
+#[cfg(feature = "xyce")]
+impl<T, PDK, C> SaveTb<Xyce, xyce::ac::Ac, DriverAcSim> for DriverAcTb<T, PDK, C, Xyce>
+where
+    DriverAcTb<T, PDK, C, Xyce>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Xyce>,
+        cell: &Cell<Self>,
+        opts: &mut <Xyce as Simulator>::Options,
+    ) -> <DriverAcSim as FromSaved<Xyce, xyce::ac::Ac>>::SavedKey {
+        DriverAcSimSavedKey {
+            freq: ac::Freq::save(ctx, (), opts),
+            vout: ac::Voltage::save(ctx, &cell.vout, opts),
+        }
+    }
+}
+
 impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for DriverAcTb<T, PDK, C>
 where
     DriverAcTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
@@ -226,18 +456,72 @@ where
     fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
         let mut opts = spectre::Options::default();
         sim.set_option(self.pvt.corner, &mut opts);
-        let wav: DriverAcSim = sim
-            .simulate(
-                opts,
-                Ac {
-                    start: dec!(1e3),
-                    stop: dec!(50e9),
-                    sweep: Sweep::Decade(40),
-                    errpreset: Some(ErrPreset::Conservative),
-                },
-            )
-            .expect("failed to run simulation");
-        wav
+        let ac = Ac {
+            start: self.fstart,
+            stop: self.fstop,
+            sweep: Sweep::Decade(40),
+            errpreset: Some(ErrPreset::Conservative),
+        };
+        match self.mc {
+            Some(mc) => sim
+                .simulate(opts, mc.analysis(ac))
+                .expect("failed to run simulation")
+                .into_iter()
+                .next()
+                .expect("Monte Carlo simulation produced no samples"),
+            None => sim.simulate(opts, ac).expect("failed to run simulation"),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Ngspice> + Copy> Testbench<Ngspice> for DriverAcTb<T, PDK, C, Ngspice>
+where
+    DriverAcTb<T, PDK, C, Ngspice>: Block<Io = TestbenchIo> + Schematic<Ngspice>,
+{
+    type Output = DriverAcSim;
+
+    fn run(&self, sim: SimController<Ngspice, Self>) -> Self::Output {
+        assert!(
+            self.mc.is_none(),
+            "Monte Carlo simulation is only supported by Spectre"
+        );
+        let mut opts = ngspice::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.simulate(
+            opts,
+            ngspice::ac::Ac {
+                start: self.fstart,
+                stop: self.fstop,
+                sweep: ngspice::ac::Sweep::Decade(40),
+            },
+        )
+        .expect("failed to run simulation")
+    }
+}
+
+#[cfg(feature = "xyce")]
+impl<T, PDK, C: SimOption<Xyce> + Copy> Testbench<Xyce> for DriverAcTb<T, PDK, C, Xyce>
+where
+    DriverAcTb<T, PDK, C, Xyce>: Block<Io = TestbenchIo> + Schematic<Xyce>,
+{
+    type Output = DriverAcSim;
+
+    fn run(&self, sim: SimController<Xyce, Self>) -> Self::Output {
+        assert!(
+            self.mc.is_none(),
+            "Monte Carlo simulation is only supported by Spectre"
+        );
+        let mut opts = xyce::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.simulate(
+            opts,
+            xyce::ac::Ac {
+                start: self.fstart,
+                stop: self.fstop,
+                sweep: xyce::ac::Sweep::Decade(40),
+            },
+        )
+        .expect("failed to run simulation")
     }
 }
 
@@ -245,6 +529,8 @@ where
 pub struct DriverSimParams<T, C> {
     /// The driver to simulate.
     pub driver: T,
+    /// The parameters of the driver's units.
+    pub unit: DriverUnitParams,
     /// The PVT corner.
     pub pvt: Pvt<C>,
     /// Start frequency.
@@ -253,6 +539,8 @@ pub struct DriverSimParams<T, C> {
     pub fstop: Decimal,
     /// Number of frequency sweep points.
     pub sweep_points: usize,
+    /// A lumped model of the `dout` strap network to back-annotate, if any.
+    pub dout_parasitics: Option<DoutLumpedModel>,
 }
 
 /// A set of driver simulation results.
@@ -276,7 +564,38 @@ pub struct DriverAcSims {
     pub pd_codes: Vec<usize>,
 }
 
-/// Run the given set of driver simulations.
+impl SimArtifact for DriverAcSims {
+    fn csv_header(&self) -> Vec<String> {
+        ["side", "code", "vin", "freq", "r"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        for (side, codes, r) in [
+            ("pu", &self.pu_codes, &self.r_pu),
+            ("pd", &self.pd_codes, &self.r_pd),
+        ] {
+            for (code, r) in codes.iter().zip(r) {
+                for (vin, r) in self.vin.iter().zip(r) {
+                    for (freq, r) in self.freq.iter().zip(r) {
+                        rows.push(vec![
+                            side.to_string(),
+                            code.to_string(),
+                            vin.to_string(),
+                            freq.to_string(),
+                            r.to_string(),
+                        ]);
+                    }
+                }
+            }
+        }
+        rows
+    }
+}
+
+/// Run the given set of driver simulations using Spectre.
 pub fn simulate_driver<T, PDK, C>(
     params: DriverSimParams<T, C>,
     ctx: PdkContext<PDK>,
@@ -288,6 +607,23 @@ where
     PDK: Schema + Pdk,
     T: Schematic<PDK> + Block<Io = DriverIo>,
     C: Clone + Send,
+{
+    simulate_driver_with::<T, PDK, C, Spectre>(params, ctx, work_dir)
+}
+
+/// Run the given set of driver simulations using the simulator `S`.
+pub fn simulate_driver_with<T, PDK, C, S>(
+    params: DriverSimParams<T, C>,
+    ctx: PdkContext<PDK>,
+    work_dir: impl AsRef<Path>,
+) -> DriverAcSims
+where
+    S: Simulator,
+    DriverAcTb<T, PDK, C, S>: Testbench<S, Output = DriverAcSim>,
+    T: Clone,
+    PDK: Schema + Pdk,
+    T: Schematic<PDK> + Block<Io = DriverIo>,
+    C: Clone + Send,
 {
     let x = ctx.generate_schematic(params.driver.clone());
     let n_pu = x.cell().io().pu_ctl.num_elems();
@@ -306,14 +642,13 @@ where
     for (mask_bits, is_pu) in [(n_pu, true), (n_pd, false)] {
         for code in 1..=mask_bits {
             for i in 0..params.sweep_points {
-                let var_mask = code_to_thermometer(code, mask_bits);
+                let var_mask = ThermometerCode::new(code, mask_bits).unwrap();
                 let (pu_mask, pd_mask, name) = if is_pu {
-                    (var_mask, vec![true; n_pd], "pu")
+                    (var_mask, ThermometerCode::all(n_pd), "pu")
                 } else {
-                    (vec![true; n_pu], var_mask, "pd")
+                    (ThermometerCode::all(n_pu), var_mask, "pd")
                 };
                 let vin = vin_swp_vec[i];
-                vin_swp_vec.push(vin);
                 let sim_dir = work_dir
                     .as_ref()
                     .join(format!("{name}_code{code}_vin{vin}"));
@@ -321,20 +656,21 @@ where
                 let pvt = params.pvt.clone();
                 let ctx = ctx.clone();
                 let handle = thread::spawn(move || {
-                    let sim = ctx
-                        .simulate(
-                            DriverAcTb::new(
-                                driver,
-                                params.fstart,
-                                params.fstop,
-                                vin,
-                                pu_mask,
-                                pd_mask,
-                                pvt,
-                            ),
-                            sim_dir,
-                        )
-                        .expect("failed to run sim");
+                    let mut tb = DriverAcTb::new(
+                        driver,
+                        params.unit,
+                        params.fstart,
+                        params.fstop,
+                        vin,
+                        pu_mask,
+                        pd_mask,
+                        pvt,
+                    );
+                    tb.dout_parasitics = params.dout_parasitics;
+                    let tb = tb
+                        .with_simulator::<S>()
+                        .expect("nominal testbenches run on every simulator");
+                    let sim = ctx.simulate(tb, sim_dir).expect("failed to run sim");
                     (
                         code,
                         i,
@@ -372,21 +708,3 @@ where
 
     out
 }
-
-/// Converts a code to thermometer coding.
-///
-/// Examples for bits=4:
-/// 0 becomes 0000
-/// 1 becomes 1000
-/// 2 becomes 1100
-/// 3 becomes 1110
-/// 4 becomes 1111
-fn code_to_thermometer(code: usize, bits: usize) -> Vec<bool> {
-    assert!(code <= bits);
-    let mut out = Vec::with_capacity(bits);
-    out.resize(code, true);
-    out.resize(bits, false);
-    assert_eq!(out.len(), bits);
-
-    out
-}
@@ -0,0 +1,246 @@
+//! Receive pad ESD protection.
+//!
+//! Transmit pads are protected by the [`EsdSeries`](crate::driver::esd::EsdSeries)
+//! network of the driver, whose series resistor and large clamp are hidden behind the
+//! low driver impedance. Receive pads have a much tighter capacitance budget, so
+//! [`RxEsd`] uses minimum-size dual diodes and a small local rail clamp instead.
+
+pub mod tb;
+
+use crate::buffer::InverterImpl;
+use crate::params::{check_positive, setters, ParamsError};
+use crate::tiles::{MosKind, MosTileParams, TapTileParams, TileKind};
+use atoll::{IoBuilder, Orientation, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::geometry::align::AlignMode;
+use substrate::io::{InOut, Io, MosIoSchematic, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+/// The interface to a receive pad ESD network.
+#[derive(Debug, Default, Clone, Io)]
+pub struct RxEsdIo {
+    /// The protected pad.
+    pub pad: InOut<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of the [`RxEsd`] layout generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct RxEsdParams {
+    /// The width of the PMOS diode from the pad to VDD.
+    pub up_diode_w: i64,
+    /// The width of the NMOS diode from VSS to the pad.
+    pub down_diode_w: i64,
+    /// The width of the grounded-gate NMOS clamp between the rails.
+    pub clamp_w: i64,
+}
+
+impl RxEsdParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> RxEsdParamsBuilder {
+        RxEsdParamsBuilder::default()
+    }
+}
+
+/// A builder for [`RxEsdParams`].
+///
+/// Defaults to 1 um diodes and a 2 um clamp, well below the 4 um clamp that the
+/// transmit network hangs on its internal node.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct RxEsdParamsBuilder {
+    params: RxEsdParams,
+}
+
+impl Default for RxEsdParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: RxEsdParams {
+                up_diode_w: 1_000,
+                down_diode_w: 1_000,
+                clamp_w: 2_000,
+            },
+        }
+    }
+}
+
+impl RxEsdParamsBuilder {
+    setters! {
+        /// Sets the width of the PMOS diode from the pad to VDD.
+        up_diode_w: i64,
+        /// Sets the width of the NMOS diode from VSS to the pad.
+        down_diode_w: i64,
+        /// Sets the width of the rail clamp.
+        clamp_w: i64,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<RxEsdParams, ParamsError> {
+        let p = &self.params;
+        for (field, value) in [
+            ("up_diode_w", p.up_diode_w),
+            ("down_diode_w", p.down_diode_w),
+            ("clamp_w", p.clamp_w),
+        ] {
+            check_positive(field, value)?;
+        }
+        Ok(self.params)
+    }
+}
+
+/// Dual diodes from a receive pad to each rail with a local rail clamp.
+///
+/// The diodes are the pad-side junctions of an off PMOS and an off NMOS. The clamp is a
+/// grounded-gate NMOS from VDD to VSS placed beside the NMOS diode, so that a pad
+/// stress to VSS through the up diode has a short return path. The PMOS diode sits
+/// above the NMOS row, between a well tap on top and a substrate tap on the bottom.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct RxEsd<T>(
+    RxEsdParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> RxEsd<T> {
+    /// Creates a new [`RxEsd`].
+    pub fn new(params: RxEsdParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The ESD network parameters.
+    pub fn params(&self) -> RxEsdParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for RxEsd<T> {
+    type Io = RxEsdIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("rx_esd")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("rx_esd", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for RxEsd<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for RxEsd<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for RxEsd<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let (pad, vdd, vss) = (io.schematic.pad, io.schematic.vdd, io.schematic.vss);
+        let mos =
+            |kind, w| T::mos(MosTileParams::new(MosKind::Nom, kind, w).snapped(T::snap_width));
+
+        let mut up = cell.generate_connected(
+            mos(TileKind::P, self.0.up_diode_w),
+            MosIoSchematic {
+                d: vdd,
+                g: vdd,
+                s: pad,
+                b: vdd,
+            },
+        );
+        let mut down = cell
+            .generate_connected(
+                mos(TileKind::N, self.0.down_diode_w),
+                MosIoSchematic {
+                    d: vss,
+                    g: vss,
+                    s: pad,
+                    b: vss,
+                },
+            )
+            .orient(Orientation::R180);
+        let mut clamp = cell
+            .generate_connected(
+                mos(TileKind::N, self.0.clamp_w),
+                MosIoSchematic {
+                    d: vss,
+                    g: vss,
+                    s: vdd,
+                    b: vss,
+                },
+            )
+            .orient(Orientation::R180);
+
+        let mut ptap = cell.generate(T::tap(TapTileParams::new(TileKind::P, 2)));
+        let ntap = cell.generate(T::tap(TapTileParams::new(TileKind::N, 2)));
+        cell.connect(ptap.io().x, vss);
+        cell.connect(ntap.io().x, vdd);
+
+        let prev = ntap.lcm_bounds();
+        up.align_rect_mut(prev, AlignMode::Left, 0);
+        up.align_rect_mut(prev, AlignMode::Beneath, 0);
+        let prev = up.lcm_bounds();
+        down.align_rect_mut(prev, AlignMode::Left, 0);
+        down.align_rect_mut(prev, AlignMode::Beneath, 0);
+        let prev = down.lcm_bounds();
+        clamp.align_rect_mut(prev, AlignMode::Bottom, 0);
+        clamp.align_rect_mut(prev, AlignMode::ToTheRight, 0);
+        ptap.align_rect_mut(prev, AlignMode::Left, 0);
+        ptap.align_rect_mut(prev, AlignMode::Beneath, 0);
+
+        let up = cell.draw(up)?;
+        let down = cell.draw(down)?;
+        cell.draw(clamp)?;
+        let ptap = cell.draw(ptap)?;
+        let ntap = cell.draw(ntap)?;
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        io.layout.pad.merge(up.layout.io().s);
+        io.layout.pad.merge(down.layout.io().s);
+        io.layout.vdd.merge(ntap.layout.io().x);
+        io.layout.vss.merge(ptap.layout.io().x);
+
+        T::post_layout_hooks(cell)?;
+
+        Ok(((), ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::driver::esd::EsdSeriesParams;
+
+    use super::*;
+
+    #[test]
+    fn clamp_is_smaller_than_transmit_clamp() {
+        let rx = RxEsdParams::builder().build().unwrap();
+        let tx = EsdSeriesParams::builder().build().unwrap();
+        assert!(rx.clamp_w < tx.clamp_w);
+        assert!(RxEsdParams::builder().clamp_w(0).build().is_err());
+    }
+}
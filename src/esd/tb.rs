@@ -0,0 +1,279 @@
+//! ESD network testbenches.
+
+use crate::esd::RxEsdIo;
+use crate::report::SimArtifact;
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::ac::{Ac, Sweep};
+use spectre::blocks::{AcSource, Isource, Vsource};
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::f64::consts::PI;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Node};
+use substrate::io::{Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::primitives::Resistor;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{ac, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+/// The resistance that biases the pad at its DC level, in ohms.
+///
+/// Large enough that its conductance does not show up in the measured capacitance.
+const BIAS_RESISTANCE: Decimal = dec!(1e9);
+
+/// An AC testbench that drives a unit current into the pad of an ESD network and
+/// measures the capacitance the network adds to the pad.
+#[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct PadCapTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: T,
+    /// The DC pad voltage.
+    pub vpad: Decimal,
+    /// The start frequency.
+    pub fstart: Decimal,
+    /// The stop frequency.
+    pub fstop: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> PadCapTb<T, PDK, C> {
+    /// Creates a new [`PadCapTb`].
+    pub fn new(dut: T, vpad: Decimal, fstart: Decimal, fstop: Decimal, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            vpad,
+            fstart,
+            fstop,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for PadCapTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("pad_cap_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("pad_cap_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`PadCapTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct PadCapTbNodes {
+    pad: Node,
+}
+
+impl<T, PDK, C> ExportsNestedData for PadCapTb<T, PDK, C>
+where
+    PadCapTb<T, PDK, C>: Block,
+{
+    type NestedData = PadCapTbNodes;
+}
+
+impl<T: Block<Io = RxEsdIo> + Schematic<PDK> + Clone, PDK: Schema, C> Schematic<Spectre>
+    for PadCapTb<T, PDK, C>
+where
+    PadCapTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vss = io.vss;
+        let vdd = cell.signal("vdd", Signal);
+        let pad = cell.signal("pad", Signal);
+        let bias = cell.signal("bias", Signal);
+
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
+        cell.connect(dut.io().pad, pad);
+        cell.connect(dut.io().vdd, vdd);
+        cell.connect(dut.io().vss, vss);
+
+        cell.instantiate_connected(
+            Vsource::dc(self.pvt.voltage),
+            TwoTerminalIoSchematic { p: vdd, n: vss },
+        );
+        cell.instantiate_connected(
+            Vsource::dc(self.vpad),
+            TwoTerminalIoSchematic { p: bias, n: vss },
+        );
+        cell.instantiate_connected(
+            Resistor::new(BIAS_RESISTANCE),
+            TwoTerminalIoSchematic { p: pad, n: bias },
+        );
+        cell.instantiate_connected(
+            Isource::ac(AcSource {
+                dc: dec!(0),
+                mag: dec!(1),
+                phase: dec!(0),
+            }),
+            TwoTerminalIoSchematic { p: vss, n: pad },
+        );
+
+        Ok(PadCapTbNodes { pad })
+    }
+}
+
+/// The resulting waveforms of a [`PadCapTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct PadCapSim {
+    /// The simulation frequency.
+    pub freq: ac::Freq,
+    /// The pad voltage, which equals the pad impedance for a unit current.
+    pub pad: ac::Voltage,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Ac, PadCapSim> for PadCapTb<T, PDK, C>
+where
+    PadCapTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <PadCapSim as FromSaved<Spectre, Ac>>::SavedKey {
+        PadCapSimSavedKey {
+            freq: ac::Freq::save(ctx, (), opts),
+            pad: ac::Voltage::save(ctx, &cell.pad, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for PadCapTb<T, PDK, C>
+where
+    PadCapTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = PadCapacitance;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let sim: PadCapSim = sim
+            .simulate(
+                opts,
+                Ac {
+                    start: self.fstart,
+                    stop: self.fstop,
+                    sweep: Sweep::Decade(10),
+                    errpreset: Some(ErrPreset::Conservative),
+                },
+            )
+            .expect("failed to run simulation");
+        PadCapacitance::new(
+            sim.freq.to_vec(),
+            sim.pad.iter().map(|z| (z.re, z.im)).collect(),
+        )
+    }
+}
+
+/// The capacitance added to a pad across frequency.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PadCapacitance {
+    /// The frequency vector, in hertz.
+    pub freq: Vec<f64>,
+    /// The pad capacitance at each frequency, in farads.
+    pub cap: Vec<f64>,
+}
+
+impl PadCapacitance {
+    /// Extracts the capacitance from the pad impedance `(re, im)` at each frequency.
+    ///
+    /// The capacitance is the susceptance of the pad admittance divided by the
+    /// angular frequency, so the junction and bias resistances drop out.
+    pub fn new(freq: Vec<f64>, impedance: Vec<(f64, f64)>) -> Self {
+        let cap = freq
+            .iter()
+            .zip(impedance)
+            .map(|(&f, (re, im))| -im / (re * re + im * im) / (2. * PI * f))
+            .collect();
+        Self { freq, cap }
+    }
+
+    /// The largest capacitance over the sweep, in farads.
+    pub fn max(&self) -> f64 {
+        self.cap.iter().copied().fold(0., f64::max)
+    }
+}
+
+impl SimArtifact for PadCapacitance {
+    fn csv_header(&self) -> Vec<String> {
+        ["freq", "cap"].map(String::from).to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.freq
+            .iter()
+            .zip(&self.cap)
+            .map(|(f, c)| vec![f.to_string(), c.to_string()])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pad_capacitance_from_impedance() {
+        // A 100 fF capacitor in parallel with 10 kohm.
+        let (c, g) = (100e-15, 1e-4);
+        let freq = vec![1e6, 1e9, 10e9];
+        let impedance = freq
+            .iter()
+            .map(|&f| {
+                let b = 2. * PI * f * c;
+                let mag = g * g + b * b;
+                (g / mag, -b / mag)
+            })
+            .collect();
+        let cap = PadCapacitance::new(freq, impedance);
+        for c_meas in cap.cap.iter() {
+            assert!((c_meas - c).abs() < 1e-20, "{c_meas}");
+        }
+        assert!((cap.max() - c).abs() < 1e-20);
+    }
+}
@@ -0,0 +1,378 @@
+//! GDS export with cell renaming and pin labels.
+//!
+//! Substrate names cells after their blocks, so two variants of a generator that share
+//! subcells produce colliding cell names when merged into one GDS. [`GdsExportOptions`]
+//! adds a prefix and suffix to every cell name and optionally renames the top cell.
+//!
+//! Extraction and LVS decks find top-level nets by their text labels, which Substrate
+//! does not write. With [`GdsExportOptions::pin_labels`] set, each shape of each top-level
+//! pin gets a label with the pin name on the label layer of its routing layer.
+//!
+//! With [`GdsExportOptions::quick_drc`] set, the layout is checked with the built-in
+//! [quick DRC](crate::verification::quick_drc) first, and nothing is written if it finds
+//! any violations.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use gds21::{GdsElement, GdsLibrary, GdsPoint, GdsTextElem};
+use serde::{Deserialize, Serialize};
+use substrate::block::Block;
+use substrate::context::PdkContext;
+use substrate::geometry::point::Point;
+use substrate::layout::element::RawCell;
+use substrate::layout::Layout;
+use substrate::pdk::{Pdk, PdkLayers};
+
+use crate::report::area::AreaLayers;
+use crate::verification::quick_drc::{quick_drc_of_cell, QuickDrcReport, QuickDrcRules};
+
+/// An error produced while exporting a GDS file.
+#[derive(Debug, thiserror::Error)]
+pub enum GdsExportError {
+    /// An I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The GDS file could not be read or written.
+    #[error("GDS error: {0}")]
+    Gds(#[from] gds21::GdsError),
+    /// Substrate failed to generate the layout.
+    #[error("failed to write layout: {0}")]
+    Layout(String),
+    /// A top-cell name was given, but the library does not have exactly one top cell.
+    #[error("expected a single top cell, found {0:?}")]
+    AmbiguousTop(Vec<String>),
+    /// Two cells were renamed to the same name.
+    #[error("renaming produced duplicate cell name {0:?}")]
+    Duplicate(String),
+    /// A pin label lies outside the range of GDS coordinates.
+    #[error("pin label {0:?} is out of range")]
+    LabelOutOfRange(String),
+    /// Layouts are being generated without routing or strapping.
+    #[error("cannot export layouts generated in schematic-only mode")]
+    SchematicOnly,
+    /// The built-in quick DRC found violations.
+    #[error("quick DRC found {} violation(s)", .0.violations.len())]
+    QuickDrc(QuickDrcReport),
+}
+
+/// Cell naming options for GDS export.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct GdsExportOptions {
+    /// A prefix added to every cell name.
+    pub prefix: String,
+    /// A suffix added to every cell name.
+    pub suffix: String,
+    /// The name of the top cell.
+    ///
+    /// Overrides the prefix and suffix for the top cell.
+    pub top_name: Option<String>,
+    /// Whether to label the shapes of the top-level pins with their names.
+    #[serde(default)]
+    pub pin_labels: bool,
+    /// Whether to run the built-in quick DRC before writing and fail on any violation.
+    #[serde(default)]
+    pub quick_drc: bool,
+}
+
+impl GdsExportOptions {
+    /// Creates options that add `prefix` to every cell name.
+    pub fn with_prefix(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if these options leave all cell names unchanged.
+    ///
+    /// Pin labels and checks do not affect cell names.
+    pub fn is_identity(&self) -> bool {
+        self.prefix.is_empty() && self.suffix.is_empty() && self.top_name.is_none()
+    }
+
+    fn cell_name(&self, name: &str) -> String {
+        format!("{}{name}{}", self.prefix, self.suffix)
+    }
+}
+
+/// The GDS label layers of a technology.
+pub trait PinLabelLayers<PDK: Pdk>: AreaLayers<PDK> {
+    /// The GDS layer and texttype of the labels on each routing layer,
+    /// keyed by the names given by [`AreaLayers::routing_layers`].
+    const PIN_LABEL_LAYERS: &'static [(&'static str, i16, i16)];
+}
+
+/// A text label on a pin.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct PinLabel {
+    /// The pin name.
+    pub text: String,
+    /// The GDS layer of the label.
+    pub layer: i16,
+    /// The GDS texttype of the label.
+    pub texttype: i16,
+    /// The location of the label.
+    pub at: Point,
+}
+
+/// Returns a label at the center of each shape of each pin of `cell`
+/// that lies on a routing layer of technology `T`.
+pub fn pin_labels<T: PinLabelLayers<PDK>, PDK: Pdk>(
+    layers: &PdkLayers<PDK>,
+    cell: &RawCell,
+) -> Vec<PinLabel> {
+    let label_layers = T::routing_layers(layers)
+        .into_iter()
+        .filter_map(|(name, id)| {
+            let (_, layer, texttype) = T::PIN_LABEL_LAYERS.iter().find(|(n, ..)| *n == name)?;
+            Some((id, (*layer, *texttype)))
+        })
+        .collect::<HashMap<_, _>>();
+    let mut labels = Vec::new();
+    for (name, port) in cell.ports() {
+        for shape in port.shapes() {
+            let (Some(&(layer, texttype)), Some(bbox)) =
+                (label_layers.get(&shape.layer()), shape.bbox())
+            else {
+                continue;
+            };
+            labels.push(PinLabel {
+                text: name.to_string(),
+                layer,
+                texttype,
+                at: bbox.center(),
+            });
+        }
+    }
+    labels
+}
+
+/// Adds `labels` to the top cell of `lib`.
+pub fn add_pin_labels(lib: &mut GdsLibrary, labels: &[PinLabel]) -> Result<(), GdsExportError> {
+    let tops = top_cells(lib);
+    let [top] = tops.as_slice() else {
+        return Err(GdsExportError::AmbiguousTop(tops));
+    };
+    let top = lib
+        .structs
+        .iter_mut()
+        .find(|s| &s.name == top)
+        .expect("top cell not found");
+    for label in labels {
+        let coord = |x: i64| {
+            i32::try_from(x).map_err(|_| GdsExportError::LabelOutOfRange(label.text.clone()))
+        };
+        top.elems.push(GdsElement::GdsTextElem(GdsTextElem {
+            string: label.text.clone().into(),
+            layer: label.layer,
+            texttype: label.texttype,
+            xy: GdsPoint::new(coord(label.at.x)?, coord(label.at.y)?),
+            ..Default::default()
+        }));
+    }
+    Ok(())
+}
+
+/// Adds `labels` to the top cell of the GDS file at `path` in place.
+pub fn add_pin_labels_in_file(
+    path: impl AsRef<Path>,
+    labels: &[PinLabel],
+) -> Result<(), GdsExportError> {
+    let path = path.as_ref();
+    let mut lib = GdsLibrary::load(path)?;
+    add_pin_labels(&mut lib, labels)?;
+    lib.save(path)?;
+    Ok(())
+}
+
+/// Returns the names of the cells in `lib` that are not instantiated by any other cell.
+pub fn top_cells(lib: &GdsLibrary) -> Vec<String> {
+    let referenced = lib
+        .structs
+        .iter()
+        .flat_map(|s| s.elems.iter())
+        .filter_map(|elem| match elem {
+            GdsElement::GdsStructRef(r) => Some(r.name.as_str()),
+            GdsElement::GdsArrayRef(r) => Some(r.name.as_str()),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+    lib.structs
+        .iter()
+        .map(|s| s.name.as_str())
+        .filter(|name| !referenced.contains(name))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Renames the cells of `lib` according to `options`, updating all references.
+///
+/// Returns a map from each original cell name to its new name.
+pub fn rename_cells(
+    lib: &mut GdsLibrary,
+    options: &GdsExportOptions,
+) -> Result<HashMap<String, String>, GdsExportError> {
+    let mut renames = lib
+        .structs
+        .iter()
+        .map(|s| (s.name.clone(), options.cell_name(&s.name)))
+        .collect::<HashMap<_, _>>();
+    if let Some(top_name) = &options.top_name {
+        let tops = top_cells(lib);
+        let [top] = tops.as_slice() else {
+            return Err(GdsExportError::AmbiguousTop(tops));
+        };
+        renames.insert(top.clone(), top_name.clone());
+    }
+
+    let mut seen = HashSet::new();
+    for name in renames.values() {
+        if !seen.insert(name) {
+            return Err(GdsExportError::Duplicate(name.clone()));
+        }
+    }
+
+    let rename = |name: &mut String| {
+        if let Some(new) = renames.get(name.as_str()) {
+            *name = new.clone();
+        }
+    };
+    for s in lib.structs.iter_mut() {
+        rename(&mut s.name);
+        for elem in s.elems.iter_mut() {
+            match elem {
+                GdsElement::GdsStructRef(r) => rename(&mut r.name),
+                GdsElement::GdsArrayRef(r) => rename(&mut r.name),
+                _ => {}
+            }
+        }
+    }
+    Ok(renames)
+}
+
+/// Renames the cells of the GDS file at `path` in place.
+pub fn rename_cells_in_file(
+    path: impl AsRef<Path>,
+    options: &GdsExportOptions,
+) -> Result<HashMap<String, String>, GdsExportError> {
+    let path = path.as_ref();
+    let mut lib = GdsLibrary::load(path)?;
+    let renames = rename_cells(&mut lib, options)?;
+    lib.save(path)?;
+    Ok(renames)
+}
+
+/// Writes the layout of `block` to `path`, naming cells according to `options`
+/// and labeling pins on the label layers of technology `T` if requested.
+///
+/// Returns the name of the top cell in the written file. Fails if the
+/// [`GenerationOptions`](crate::GenerationOptions) of `ctx` request schematics only, or if
+/// [`GdsExportOptions::quick_drc`] is set and the layout has quick DRC violations.
+pub fn write_gds<
+    T: PinLabelLayers<PDK> + QuickDrcRules<PDK>,
+    PDK: Pdk,
+    B: Block + Layout<PDK> + Clone,
+>(
+    ctx: &PdkContext<PDK>,
+    block: B,
+    path: impl Into<PathBuf>,
+    options: &GdsExportOptions,
+) -> Result<String, GdsExportError> {
+    if crate::generation_options(ctx).schematic_only {
+        return Err(GdsExportError::SchematicOnly);
+    }
+    let path = path.into();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if options.quick_drc {
+        let report =
+            quick_drc_of_cell::<T, PDK>(&ctx.layers, ctx.generate_layout(block.clone()).raw());
+        if !report.is_clean() {
+            return Err(GdsExportError::QuickDrc(report));
+        }
+    }
+    let name = block.name().to_string();
+    let labels = options
+        .pin_labels
+        .then(|| pin_labels::<T, PDK>(&ctx.layers, ctx.generate_layout(block.clone()).raw()));
+    ctx.write_layout(block, &path)
+        .map_err(|e| GdsExportError::Layout(format!("{e:?}")))?;
+    if let Some(labels) = labels {
+        add_pin_labels_in_file(&path, &labels)?;
+    }
+    if options.is_identity() {
+        return Ok(name);
+    }
+    let renames = rename_cells_in_file(&path, options)?;
+    Ok(renames.get(&name).cloned().unwrap_or(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use gds21::{GdsStruct, GdsStructRef};
+
+    use super::*;
+
+    #[test]
+    fn renames_cells_and_references() {
+        let mut lib = GdsLibrary::new("lib");
+        let mut top = GdsStruct::new("driver");
+        top.elems.push(GdsElement::GdsStructRef(GdsStructRef {
+            name: "unit".to_string(),
+            ..Default::default()
+        }));
+        lib.structs.push(top);
+        lib.structs.push(GdsStruct::new("unit"));
+
+        let options = GdsExportOptions {
+            prefix: "code3_".to_string(),
+            suffix: String::new(),
+            top_name: Some("tx_driver_code3".to_string()),
+            pin_labels: false,
+            quick_drc: false,
+        };
+        rename_cells(&mut lib, &options).unwrap();
+
+        assert_eq!(lib.structs[0].name, "tx_driver_code3");
+        assert_eq!(lib.structs[1].name, "code3_unit");
+        let GdsElement::GdsStructRef(r) = &lib.structs[0].elems[0] else {
+            panic!("expected a struct reference");
+        };
+        assert_eq!(r.name, "code3_unit");
+    }
+
+    #[test]
+    fn labels_the_top_cell() {
+        let mut lib = GdsLibrary::new("lib");
+        let mut top = GdsStruct::new("driver");
+        top.elems.push(GdsElement::GdsStructRef(GdsStructRef {
+            name: "unit".to_string(),
+            ..Default::default()
+        }));
+        lib.structs.push(GdsStruct::new("unit"));
+        lib.structs.push(top);
+
+        let label = PinLabel {
+            text: "dout".to_string(),
+            layer: 68,
+            texttype: 5,
+            at: Point::new(100, -200),
+        };
+        add_pin_labels(&mut lib, &[label.clone()]).unwrap();
+        assert!(lib.structs[0].elems.is_empty());
+        let GdsElement::GdsTextElem(text) = &lib.structs[1].elems[1] else {
+            panic!("expected a text element");
+        };
+        assert_eq!(text.string.to_string(), "dout");
+        assert_eq!((text.layer, text.texttype), (68, 5));
+        assert_eq!(text.xy, GdsPoint::new(100, -200));
+
+        lib.structs.push(GdsStruct::new("other"));
+        assert!(matches!(
+            add_pin_labels(&mut lib, &[label]),
+            Err(GdsExportError::AmbiguousTop(_))
+        ));
+    }
+}
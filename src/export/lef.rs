@@ -0,0 +1,241 @@
+//! LEF abstract generation.
+//!
+//! The generated abstracts contain the macro size, the shapes of every port on a
+//! routing layer, and an obstruction covering the full macro on every routing layer
+//! used inside the macro. Coordinates are shifted so that the lower left corner of
+//! the macro's bounding box lies at the origin.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use substrate::context::PdkContext;
+use substrate::geometry::bbox::Bbox;
+use substrate::geometry::point::Point;
+use substrate::geometry::rect::Rect;
+use substrate::geometry::transform::Translate;
+use substrate::layout::element::RawCell;
+use substrate::layout::Layout;
+use substrate::pdk::{Pdk, PdkLayers};
+
+use crate::report::area::{layer_shapes, AreaLayers};
+
+/// The use of a LEF pin.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum PinUse {
+    /// A signal pin.
+    Signal,
+    /// A power supply pin.
+    Power,
+    /// A ground pin.
+    Ground,
+}
+
+impl PinUse {
+    /// Infers the use of a pin from its name.
+    ///
+    /// Pins named `vdd*` are power pins and pins named `vss*` are ground pins.
+    pub fn from_name(name: &str) -> Self {
+        let name = name.to_lowercase();
+        if name.starts_with("vdd") {
+            PinUse::Power
+        } else if name.starts_with("vss") {
+            PinUse::Ground
+        } else {
+            PinUse::Signal
+        }
+    }
+
+    fn keyword(&self) -> &'static str {
+        match self {
+            PinUse::Signal => "SIGNAL",
+            PinUse::Power => "POWER",
+            PinUse::Ground => "GROUND",
+        }
+    }
+}
+
+/// A pin of a [`LefMacro`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LefPin {
+    /// The pin name.
+    pub name: String,
+    /// The pin use.
+    pub pin_use: PinUse,
+    /// The pin shapes, as pairs of layer name and rectangle.
+    pub shapes: Vec<(String, Rect)>,
+}
+
+/// A LEF macro abstract.
+///
+/// All dimensions are in nanometers.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LefMacro {
+    /// The macro name.
+    pub name: String,
+    /// The macro width.
+    pub width: i64,
+    /// The macro height.
+    pub height: i64,
+    /// The macro pins.
+    pub pins: Vec<LefPin>,
+    /// The obstructions, as pairs of layer name and rectangle.
+    pub obstructions: Vec<(String, Rect)>,
+}
+
+impl LefMacro {
+    /// Creates an abstract of `cell` using the routing layers of technology `T`.
+    pub fn from_cell<T: AreaLayers<PDK>, PDK: Pdk>(
+        layers: &PdkLayers<PDK>,
+        cell: &RawCell,
+    ) -> Self {
+        let bbox = cell.bbox().unwrap_or(Rect::from_sides(0, 0, 0, 0));
+        let offset = Point::new(-bbox.left(), -bbox.bot());
+        let routing_layers = T::routing_layers(layers);
+        let layer_name = |layer| {
+            routing_layers
+                .iter()
+                .find(|(_, id)| *id == layer)
+                .map(|(name, _)| name.to_string())
+        };
+
+        let mut pins: BTreeMap<String, LefPin> = BTreeMap::new();
+        for (name, port) in cell.ports() {
+            let name = name.to_string();
+            let shapes = port.shapes().filter_map(|shape| {
+                Some((layer_name(shape.layer())?, shape.bbox()?.translate(offset)))
+            });
+            pins.entry(name.clone())
+                .or_insert_with(|| LefPin {
+                    pin_use: PinUse::from_name(&name),
+                    name,
+                    shapes: Vec::new(),
+                })
+                .shapes
+                .extend(shapes);
+        }
+
+        let used = layer_shapes(cell);
+        let obstructions = routing_layers
+            .iter()
+            .filter(|(_, id)| used.contains_key(id))
+            .map(|(name, _)| (name.to_string(), bbox.translate(offset)))
+            .collect();
+
+        Self {
+            name: cell.name().to_string(),
+            width: bbox.width(),
+            height: bbox.height(),
+            pins: pins
+                .into_values()
+                .filter(|pin| !pin.shapes.is_empty())
+                .collect(),
+            obstructions,
+        }
+    }
+
+    /// Returns the LEF text of this macro.
+    pub fn to_lef(&self) -> String {
+        let mut out = String::new();
+        self.write_lef(&mut out).expect("failed to format LEF");
+        out
+    }
+
+    fn write_lef(&self, out: &mut String) -> std::fmt::Result {
+        writeln!(out, "VERSION 5.7 ;")?;
+        writeln!(out, "BUSBITCHARS \"[]\" ;")?;
+        writeln!(out, "DIVIDERCHAR \"/\" ;")?;
+        writeln!(out, "UNITS\n  DATABASE MICRONS 1000 ;\nEND UNITS\n")?;
+        writeln!(out, "MACRO {}", self.name)?;
+        writeln!(out, "  CLASS BLOCK ;")?;
+        writeln!(out, "  ORIGIN 0 0 ;")?;
+        writeln!(out, "  FOREIGN {} 0 0 ;", self.name)?;
+        writeln!(out, "  SIZE {} BY {} ;", um(self.width), um(self.height))?;
+        writeln!(out, "  SYMMETRY X Y ;")?;
+        for pin in self.pins.iter() {
+            writeln!(out, "  PIN {}", pin.name)?;
+            writeln!(out, "    DIRECTION INOUT ;")?;
+            writeln!(out, "    USE {} ;", pin.pin_use.keyword())?;
+            writeln!(out, "    PORT")?;
+            write_shapes(out, &pin.shapes, "      ")?;
+            writeln!(out, "    END")?;
+            writeln!(out, "  END {}", pin.name)?;
+        }
+        if !self.obstructions.is_empty() {
+            writeln!(out, "  OBS")?;
+            write_shapes(out, &self.obstructions, "    ")?;
+            writeln!(out, "  END")?;
+        }
+        writeln!(out, "END {}\n", self.name)?;
+        writeln!(out, "END LIBRARY")
+    }
+}
+
+fn write_shapes(out: &mut String, shapes: &[(String, Rect)], indent: &str) -> std::fmt::Result {
+    let mut prev_layer = None;
+    for (layer, rect) in shapes {
+        if prev_layer != Some(layer) {
+            writeln!(out, "{indent}LAYER {layer} ;")?;
+            prev_layer = Some(layer);
+        }
+        writeln!(
+            out,
+            "{indent}  RECT {} {} {} {} ;",
+            um(rect.left()),
+            um(rect.bot()),
+            um(rect.right()),
+            um(rect.top())
+        )?;
+    }
+    Ok(())
+}
+
+/// Formats a length in nanometers as microns.
+fn um(nm: i64) -> String {
+    let sign = if nm < 0 { "-" } else { "" };
+    let nm = nm.unsigned_abs();
+    format!("{sign}{}.{:03}", nm / 1000, nm % 1000)
+}
+
+/// Generates the layout of `block` and writes its LEF abstract to `path`,
+/// using the routing layers of technology `T`.
+pub fn write_lef<T: AreaLayers<PDK>, PDK: Pdk, B: Layout<PDK>>(
+    ctx: &PdkContext<PDK>,
+    block: B,
+    path: impl AsRef<Path>,
+) -> std::io::Result<LefMacro> {
+    let cell = ctx.generate_layout(block);
+    let lef = LefMacro::from_cell::<T, PDK>(&ctx.layers, cell.raw());
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, lef.to_lef())?;
+    Ok(lef)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_lef_macro() {
+        let lef = LefMacro {
+            name: "driver".to_string(),
+            width: 2_500,
+            height: 1_000,
+            pins: vec![LefPin {
+                name: "vdd".to_string(),
+                pin_use: PinUse::from_name("vdd"),
+                shapes: vec![("met2".to_string(), Rect::from_sides(0, 0, 2_500, 140))],
+            }],
+            obstructions: vec![("met1".to_string(), Rect::from_sides(0, 0, 2_500, 1_000))],
+        }
+        .to_lef();
+        assert!(lef.contains("  SIZE 2.500 BY 1.000 ;\n"));
+        assert!(lef.contains("    USE POWER ;\n"));
+        assert!(lef.contains("      LAYER met2 ;\n        RECT 0.000 0.000 2.500 0.140 ;\n"));
+        assert!(lef.contains("  OBS\n    LAYER met1 ;\n"));
+    }
+}
@@ -0,0 +1,281 @@
+//! Liberty timing and power model generation.
+//!
+//! Cells are characterized with [`BufferTimingTb`] over a grid of input slews and
+//! output loads. The resulting libraries use units of ns, pF, V, and pJ.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use spectre::Spectre;
+use substrate::context::PdkContext;
+use substrate::pdk::corner::Pvt;
+use substrate::pdk::Pdk;
+use substrate::simulation::Testbench;
+
+use crate::buffer::tb::{BufferTiming, BufferTimingTb};
+
+/// A two-dimensional lookup table indexed by input slew and output load.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LibertyTable {
+    /// The input slews, in seconds.
+    pub slews: Vec<f64>,
+    /// The output loads, in farads.
+    pub loads: Vec<f64>,
+    /// The table values, indexed first by slew and then by load.
+    pub values: Vec<Vec<f64>>,
+}
+
+impl LibertyTable {
+    fn write(&self, out: &mut String, group: &str, scale: f64) -> std::fmt::Result {
+        let join = |values: &[f64], scale: f64| {
+            values
+                .iter()
+                .map(|v| format!("{:.6}", v * scale))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        writeln!(out, "        {group} (delay_template) {{")?;
+        writeln!(out, "          index_1 (\"{}\");", join(&self.slews, 1e9))?;
+        writeln!(out, "          index_2 (\"{}\");", join(&self.loads, 1e12))?;
+        writeln!(out, "          values ( \\")?;
+        for (i, row) in self.values.iter().enumerate() {
+            let sep = if i + 1 == self.values.len() { "" } else { "," };
+            writeln!(out, "            \"{}\"{sep} \\", join(row, scale))?;
+        }
+        writeln!(out, "          );")?;
+        writeln!(out, "        }}")
+    }
+}
+
+/// A single-input, single-output cell.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LibertyCell {
+    /// The cell name.
+    pub name: String,
+    /// The cell area, in square microns.
+    pub area: f64,
+    /// The input pin name.
+    pub input: String,
+    /// The output pin name.
+    pub output: String,
+    /// The Boolean function of the output in terms of the input, such as `!din`.
+    pub function: String,
+    /// The input pin capacitance, in farads.
+    pub input_cap: f64,
+    /// The delay to a rising output, in seconds.
+    pub cell_rise: LibertyTable,
+    /// The delay to a falling output, in seconds.
+    pub cell_fall: LibertyTable,
+    /// The transition time of a rising output, in seconds.
+    pub rise_transition: LibertyTable,
+    /// The transition time of a falling output, in seconds.
+    pub fall_transition: LibertyTable,
+    /// The internal energy of a rising output transition, in joules.
+    pub rise_power: LibertyTable,
+    /// The internal energy of a falling output transition, in joules.
+    pub fall_power: LibertyTable,
+}
+
+impl LibertyCell {
+    /// Returns `true` if the output is the inverse of the input.
+    pub fn is_inverting(&self) -> bool {
+        self.function.trim_start().starts_with('!')
+    }
+
+    fn write(&self, out: &mut String) -> std::fmt::Result {
+        writeln!(out, "  cell ({}) {{", self.name)?;
+        writeln!(out, "    area : {:.4};", self.area)?;
+        writeln!(out, "    pin ({}) {{", self.input)?;
+        writeln!(out, "      direction : input;")?;
+        writeln!(out, "      capacitance : {:.6};", self.input_cap * 1e12)?;
+        writeln!(out, "    }}")?;
+        writeln!(out, "    pin ({}) {{", self.output)?;
+        writeln!(out, "      direction : output;")?;
+        writeln!(out, "      function : \"{}\";", self.function)?;
+        writeln!(out, "      timing () {{")?;
+        writeln!(out, "        related_pin : \"{}\";", self.input)?;
+        let sense = if self.is_inverting() {
+            "negative_unate"
+        } else {
+            "positive_unate"
+        };
+        writeln!(out, "        timing_sense : {sense};")?;
+        self.cell_rise.write(out, "cell_rise", 1e9)?;
+        self.cell_fall.write(out, "cell_fall", 1e9)?;
+        self.rise_transition.write(out, "rise_transition", 1e9)?;
+        self.fall_transition.write(out, "fall_transition", 1e9)?;
+        writeln!(out, "      }}")?;
+        writeln!(out, "      internal_power () {{")?;
+        writeln!(out, "        related_pin : \"{}\";", self.input)?;
+        self.rise_power.write(out, "rise_power", 1e12)?;
+        self.fall_power.write(out, "fall_power", 1e12)?;
+        writeln!(out, "      }}")?;
+        writeln!(out, "    }}")?;
+        writeln!(out, "  }}")
+    }
+}
+
+/// A Liberty library characterized at a single operating condition.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LibertyLibrary {
+    /// The library name.
+    pub name: String,
+    /// The supply voltage, in volts.
+    pub voltage: f64,
+    /// The temperature, in degrees Celsius.
+    pub temperature: f64,
+    /// The cells in the library.
+    pub cells: Vec<LibertyCell>,
+}
+
+impl LibertyLibrary {
+    /// Returns the Liberty text of this library.
+    pub fn to_liberty(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out).expect("failed to format Liberty");
+        out
+    }
+
+    fn write(&self, out: &mut String) -> std::fmt::Result {
+        writeln!(out, "library ({}) {{", self.name)?;
+        writeln!(out, "  delay_model : table_lookup;")?;
+        writeln!(out, "  time_unit : \"1ns\";")?;
+        writeln!(out, "  voltage_unit : \"1V\";")?;
+        writeln!(out, "  current_unit : \"1mA\";")?;
+        writeln!(out, "  leakage_power_unit : \"1nW\";")?;
+        writeln!(out, "  capacitive_load_unit (1, pf);")?;
+        writeln!(out, "  nom_voltage : {};", self.voltage)?;
+        writeln!(out, "  nom_temperature : {};", self.temperature)?;
+        writeln!(out, "  nom_process : 1;")?;
+        for (key, value) in [
+            ("input_threshold_pct_rise", 50),
+            ("input_threshold_pct_fall", 50),
+            ("output_threshold_pct_rise", 50),
+            ("output_threshold_pct_fall", 50),
+            ("slew_lower_threshold_pct_rise", 10),
+            ("slew_lower_threshold_pct_fall", 10),
+            ("slew_upper_threshold_pct_rise", 90),
+            ("slew_upper_threshold_pct_fall", 90),
+        ] {
+            writeln!(out, "  {key} : {value};")?;
+        }
+        writeln!(out, "  lu_table_template (delay_template) {{")?;
+        writeln!(out, "    variable_1 : input_net_transition;")?;
+        writeln!(out, "    variable_2 : total_output_net_capacitance;")?;
+        writeln!(out, "  }}")?;
+        for cell in self.cells.iter() {
+            cell.write(out)?;
+        }
+        writeln!(out, "}}")
+    }
+
+    /// Writes this library to `path`.
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.to_liberty())
+    }
+}
+
+/// Characterizes a buffer or inverter `dut` over a grid of input slews and output loads.
+///
+/// The pins are named after [`BufferIo`](crate::buffer::BufferIo), and `function` is the
+/// output's Boolean function of `din`, such as `!din` for an inverter.
+/// The input capacitance is averaged across all simulations.
+/// The area is left at zero; it can be filled in from [`crate::report::area`].
+#[allow(clippy::too_many_arguments)]
+pub fn characterize_buffer<PDK, T, C>(
+    ctx: &PdkContext<PDK>,
+    name: &str,
+    dut: T,
+    function: &str,
+    slews: &[Decimal],
+    loads: &[Decimal],
+    pvt: Pvt<C>,
+    work_dir: impl AsRef<Path>,
+) -> LibertyCell
+where
+    PDK: Pdk,
+    T: Clone,
+    C: Copy,
+    BufferTimingTb<T, PDK, C>: Testbench<Spectre, Output = BufferTiming>,
+{
+    let work_dir = work_dir.as_ref();
+    let timing = slews
+        .iter()
+        .enumerate()
+        .map(|(i, &slew)| {
+            loads
+                .iter()
+                .enumerate()
+                .map(|(j, &load)| {
+                    let _span = tracing::info_span!("liberty_point", cell = name, i, j).entered();
+                    ctx.simulate(
+                        BufferTimingTb::new(dut.clone(), slew, load, pvt),
+                        work_dir.join(format!("slew{i}_load{j}")),
+                    )
+                    .expect("failed to run simulation")
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let to_f64 = |values: &[Decimal]| {
+        values
+            .iter()
+            .map(|v| v.to_f64().unwrap())
+            .collect::<Vec<_>>()
+    };
+    let table = |metric: fn(&BufferTiming) -> f64| LibertyTable {
+        slews: to_f64(slews),
+        loads: to_f64(loads),
+        values: timing
+            .iter()
+            .map(|row| row.iter().map(metric).collect())
+            .collect(),
+    };
+    let caps = timing
+        .iter()
+        .flatten()
+        .map(|t| t.input_cap)
+        .collect::<Vec<_>>();
+
+    LibertyCell {
+        name: name.to_string(),
+        area: 0.,
+        input: "din".to_string(),
+        output: "dout".to_string(),
+        function: function.to_string(),
+        input_cap: caps.iter().sum::<f64>() / caps.len().max(1) as f64,
+        cell_rise: table(|t| t.rise_delay),
+        cell_fall: table(|t| t.fall_delay),
+        rise_transition: table(|t| t.rise_transition),
+        fall_transition: table(|t| t.fall_transition),
+        rise_power: table(|t| t.rise_energy),
+        fall_power: table(|t| t.fall_energy),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_tables_in_library_units() {
+        let table = LibertyTable {
+            slews: vec![10e-12, 100e-12],
+            loads: vec![1e-15],
+            values: vec![vec![20e-12], vec![35e-12]],
+        };
+        let mut out = String::new();
+        table.write(&mut out, "cell_rise", 1e9).unwrap();
+        assert!(out.contains("index_1 (\"0.010000, 0.100000\");"));
+        assert!(out.contains("index_2 (\"0.001000\");"));
+        assert!(out.contains("\"0.020000\", \\\n            \"0.035000\" \\\n"));
+    }
+}
@@ -0,0 +1,7 @@
+//! Exporters for views of generated blocks used by other design flows.
+
+pub mod gds;
+pub mod lef;
+pub mod liberty;
+pub mod netlist;
+pub mod verilog;
@@ -0,0 +1,456 @@
+//! Hierarchical SPICE netlist export.
+//!
+//! Substrate netlists keep one subckt per generated block, apart from device tiles,
+//! which flatten into their parents. Most blocks are named with a hash of their
+//! parameters, but testbenches are not, and SCIR resolves colliding names with
+//! numeric suffixes that depend on generation order. Such names
+//! cannot be matched across runs or against an extracted netlist.
+//!
+//! [`hash_subckt_names`] renames every subckt without a parameter hash after its
+//! contents, and [`NetlistHierarchy`] records which subckts instantiate which, so
+//! that mismatches can be traced to a cell and cells can be picked for partial
+//! extraction.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// An error produced while exporting a hierarchical netlist.
+#[derive(Debug, thiserror::Error)]
+pub enum NetlistExportError {
+    /// An I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// A subckt definition was malformed.
+    #[error("line {line}: {message}")]
+    Parse {
+        /// The line on which the error occurred, starting from 1.
+        line: usize,
+        /// A description of the error.
+        message: String,
+    },
+    /// Two subckts have the same name.
+    #[error("duplicate subckt {0:?}")]
+    Duplicate(String),
+    /// A subckt instantiates itself, directly or through its children.
+    #[error("subckt {0:?} instantiates itself")]
+    Recursive(String),
+}
+
+/// An instance of a subckt.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct SubcktInstance {
+    /// The instance name.
+    pub name: String,
+    /// The name of the instantiated subckt.
+    pub cell: String,
+}
+
+/// A subckt definition.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Subckt {
+    /// The subckt name.
+    pub name: String,
+    /// The port names, in order.
+    pub ports: Vec<String>,
+    /// The instances of other subckts defined in the same netlist.
+    ///
+    /// Instances of primitive devices and of subckts defined elsewhere are omitted.
+    pub instances: Vec<SubcktInstance>,
+}
+
+/// The subckts of a netlist and the instances between them.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct NetlistHierarchy {
+    /// The subckts, in the order in which they are defined.
+    pub subckts: Vec<Subckt>,
+}
+
+/// A line of a SPICE netlist with its continuation lines joined.
+struct LogicalLine {
+    /// The index of the first physical line.
+    start: usize,
+    /// The number of physical lines.
+    len: usize,
+    /// The whitespace-separated tokens of all physical lines.
+    tokens: Vec<String>,
+}
+
+fn logical_lines(netlist: &str) -> Vec<LogicalLine> {
+    let mut lines: Vec<LogicalLine> = Vec::new();
+    for (i, line) in netlist.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(rest) = trimmed.strip_prefix('+') {
+            if let Some(last) = lines.last_mut() {
+                if last.start + last.len == i {
+                    last.len += 1;
+                    last.tokens
+                        .extend(rest.split_whitespace().map(str::to_string));
+                    continue;
+                }
+            }
+        }
+        lines.push(LogicalLine {
+            start: i,
+            len: 1,
+            tokens: if trimmed.starts_with('*') {
+                Vec::new()
+            } else {
+                trimmed.split_whitespace().map(str::to_string).collect()
+            },
+        });
+    }
+    lines
+}
+
+fn is_subckt(tokens: &[String]) -> bool {
+    tokens
+        .first()
+        .is_some_and(|t| t.eq_ignore_ascii_case(".subckt"))
+}
+
+fn is_ends(tokens: &[String]) -> bool {
+    tokens
+        .first()
+        .is_some_and(|t| t.eq_ignore_ascii_case(".ends"))
+}
+
+/// Returns the index of the token naming the subckt of an instance line,
+/// or `None` if the line is not a subckt instance.
+///
+/// The subckt name is the last token that is not a `key=value` parameter.
+fn instance_cell(tokens: &[String]) -> Option<usize> {
+    let first = tokens.first()?;
+    if !first.starts_with(['x', 'X']) {
+        return None;
+    }
+    let end = tokens
+        .iter()
+        .position(|t| t.contains('=') || t.eq_ignore_ascii_case("params:"))
+        .unwrap_or(tokens.len());
+    (end > 1).then_some(end - 1)
+}
+
+/// Returns `true` if `name` ends in a parameter hash added by [`crate::block_name`].
+pub fn has_param_hash(name: &str) -> bool {
+    match name.rsplit_once('_') {
+        Some((base, hash)) => {
+            !base.is_empty()
+                && hash.len() == 8
+                && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        }
+        None => false,
+    }
+}
+
+impl NetlistHierarchy {
+    /// Parses the subckt definitions of a SPICE netlist.
+    pub fn parse(netlist: &str) -> Result<Self, NetlistExportError> {
+        let mut subckts = Vec::new();
+        let mut current: Option<Subckt> = None;
+        for line in logical_lines(netlist) {
+            let tokens = &line.tokens;
+            if is_subckt(tokens) {
+                if current.is_some() {
+                    return Err(NetlistExportError::Parse {
+                        line: line.start + 1,
+                        message: "nested subckt definition".to_string(),
+                    });
+                }
+                let Some(name) = tokens.get(1) else {
+                    return Err(NetlistExportError::Parse {
+                        line: line.start + 1,
+                        message: "subckt without a name".to_string(),
+                    });
+                };
+                let ports = tokens[2..]
+                    .iter()
+                    .take_while(|t| !t.contains('=') && !t.eq_ignore_ascii_case("params:"))
+                    .cloned()
+                    .collect();
+                let subckt = Subckt {
+                    name: name.clone(),
+                    ports,
+                    instances: Vec::new(),
+                };
+                current = Some(subckt);
+            } else if is_ends(tokens) {
+                let Some(subckt) = current.take() else {
+                    return Err(NetlistExportError::Parse {
+                        line: line.start + 1,
+                        message: ".ends outside of a subckt".to_string(),
+                    });
+                };
+                subckts.push(subckt);
+            } else if let (Some(subckt), Some(cell)) = (current.as_mut(), instance_cell(tokens)) {
+                subckt.instances.push(SubcktInstance {
+                    name: tokens[0].clone(),
+                    cell: tokens[cell].clone(),
+                });
+            }
+        }
+        if let Some(subckt) = current {
+            return Err(NetlistExportError::Parse {
+                line: netlist.lines().count(),
+                message: format!("subckt {:?} is not terminated", subckt.name),
+            });
+        }
+
+        let mut names = HashSet::new();
+        for subckt in &subckts {
+            if !names.insert(subckt.name.clone()) {
+                return Err(NetlistExportError::Duplicate(subckt.name.clone()));
+            }
+        }
+        for subckt in &mut subckts {
+            subckt
+                .instances
+                .retain(|inst| names.contains(inst.cell.as_str()));
+        }
+        Ok(Self { subckts })
+    }
+
+    /// Returns the subckt named `name`.
+    pub fn get(&self, name: &str) -> Option<&Subckt> {
+        self.subckts.iter().find(|s| s.name == name)
+    }
+
+    /// Returns the names of the subckts that are not instantiated by any other subckt.
+    pub fn top_cells(&self) -> Vec<&str> {
+        let referenced = self
+            .subckts
+            .iter()
+            .flat_map(|s| s.instances.iter().map(|inst| inst.cell.as_str()))
+            .collect::<HashSet<_>>();
+        self.subckts
+            .iter()
+            .map(|s| s.name.as_str())
+            .filter(|name| !referenced.contains(name))
+            .collect()
+    }
+
+    /// Returns the names of `cell` and every subckt beneath it, with each subckt
+    /// listed after all of the subckts it instantiates.
+    ///
+    /// Returns an empty list if `cell` is not defined.
+    pub fn subtree(&self, cell: &str) -> Vec<&str> {
+        fn visit<'a>(
+            hierarchy: &'a NetlistHierarchy,
+            cell: &str,
+            seen: &mut HashSet<&'a str>,
+            order: &mut Vec<&'a str>,
+        ) {
+            let Some(subckt) = hierarchy.get(cell) else {
+                return;
+            };
+            if !seen.insert(&subckt.name) {
+                return;
+            }
+            for inst in &subckt.instances {
+                visit(hierarchy, &inst.cell, seen, order);
+            }
+            order.push(&subckt.name);
+        }
+        let mut order = Vec::new();
+        visit(self, cell, &mut HashSet::new(), &mut order);
+        order
+    }
+
+    /// Formats the instance tree beneath `cell`, one instance per line,
+    /// indented by depth.
+    pub fn tree(&self, cell: &str) -> String {
+        fn visit(hierarchy: &NetlistHierarchy, cell: &str, depth: usize, out: &mut String) {
+            let Some(subckt) = hierarchy.get(cell) else {
+                return;
+            };
+            for inst in &subckt.instances {
+                writeln!(
+                    out,
+                    "{:indent$}{} ({})",
+                    "",
+                    inst.name,
+                    inst.cell,
+                    indent = 2 * depth
+                )
+                .unwrap();
+                visit(hierarchy, &inst.cell, depth + 1, out);
+            }
+        }
+        let mut out = format!("{cell}\n");
+        visit(self, cell, 1, &mut out);
+        out
+    }
+}
+
+/// Renames every subckt in `netlist` that is not a top cell and whose name
+/// does not end in a parameter hash.
+///
+/// Each such subckt is suffixed with a hash of its ports and body, after its own
+/// instances have been renamed, so that the new name depends only on the contents of
+/// the subckt and not on the order in which cells were generated. Top cells keep their
+/// names so that they still match the layout. Returns the renamed netlist and the map
+/// from old to new names.
+pub fn hash_subckt_names(
+    netlist: &str,
+) -> Result<(String, HashMap<String, String>), NetlistExportError> {
+    let hierarchy = NetlistHierarchy::parse(netlist)?;
+    let lines = logical_lines(netlist);
+
+    // The logical lines of the body of each subckt, keyed by name.
+    let mut bodies: HashMap<&str, &[LogicalLine]> = HashMap::new();
+    let mut start = None;
+    for (i, line) in lines.iter().enumerate() {
+        if is_subckt(&line.tokens) {
+            start = Some(i);
+        } else if is_ends(&line.tokens) {
+            let start = start.take().expect("subckts were already parsed");
+            bodies.insert(lines[start].tokens[1].as_str(), &lines[start + 1..i]);
+        }
+    }
+
+    let tops = hierarchy.top_cells();
+    let mut renames = HashMap::new();
+    for top in &tops {
+        let mut order = hierarchy.subtree(top);
+        order.pop();
+        for name in order {
+            if renames.contains_key(name) || has_param_hash(name) {
+                continue;
+            }
+            let subckt = hierarchy.get(name).expect("subtree cells are defined");
+            let mut contents = subckt.ports.join(" ");
+            for line in bodies[name] {
+                contents.push('\n');
+                contents.push_str(&rename_tokens(&line.tokens, &renames).join(" "));
+            }
+            let hash = crate::stable_hash(contents.as_bytes());
+            let new = format!("{name}_{:08x}", (hash >> 32) ^ (hash & 0xffffffff));
+            renames.insert(name.to_string(), new);
+        }
+    }
+    for subckt in &hierarchy.subckts {
+        if renames.contains_key(&subckt.name) {
+            continue;
+        }
+        if !tops.contains(&subckt.name.as_str()) && !has_param_hash(&subckt.name) {
+            return Err(NetlistExportError::Recursive(subckt.name.clone()));
+        }
+    }
+    let names = hierarchy
+        .subckts
+        .iter()
+        .map(|s| renames.get(&s.name).unwrap_or(&s.name))
+        .collect::<Vec<_>>();
+    let mut unique = HashSet::new();
+    for name in names {
+        if !unique.insert(name) {
+            return Err(NetlistExportError::Duplicate(name.clone()));
+        }
+    }
+
+    let physical = netlist.lines().collect::<Vec<_>>();
+    let mut out = String::with_capacity(netlist.len());
+    for line in &lines {
+        let renamed = rename_tokens(&line.tokens, &renames);
+        if renamed == line.tokens {
+            for physical in &physical[line.start..line.start + line.len] {
+                out.push_str(physical);
+                out.push('\n');
+            }
+        } else {
+            out.push_str(&renamed.join(" "));
+            out.push('\n');
+        }
+    }
+    Ok((out, renames))
+}
+
+fn rename_tokens(tokens: &[String], renames: &HashMap<String, String>) -> Vec<String> {
+    let index = if is_subckt(tokens) || is_ends(tokens) {
+        Some(1)
+    } else {
+        instance_cell(tokens)
+    };
+    let mut tokens = tokens.to_vec();
+    if let Some(token) = index.and_then(|i| tokens.get_mut(i)) {
+        if let Some(new) = renames.get(token.as_str()) {
+            *token = new.clone();
+        }
+    }
+    tokens
+}
+
+/// Returns the path of the hierarchy listing written next to `netlist`.
+pub fn hierarchy_path(netlist: impl AsRef<Path>) -> PathBuf {
+    let mut path = netlist.as_ref().as_os_str().to_owned();
+    path.push(".hier");
+    PathBuf::from(path)
+}
+
+/// Renames the subckts of the netlist at `path` with [`hash_subckt_names`] and writes
+/// the instance tree of each top cell to [`hierarchy_path`].
+pub fn export_hierarchy_in_file(
+    path: impl AsRef<Path>,
+) -> Result<NetlistHierarchy, NetlistExportError> {
+    let path = path.as_ref();
+    let netlist = std::fs::read_to_string(path)?;
+    let (netlist, _) = hash_subckt_names(&netlist)?;
+    let hierarchy = NetlistHierarchy::parse(&netlist)?;
+    let tree = hierarchy
+        .top_cells()
+        .into_iter()
+        .map(|top| hierarchy.tree(top))
+        .collect::<Vec<_>>()
+        .join("\n");
+    std::fs::write(path, netlist)?;
+    std::fs::write(hierarchy_path(path), tree)?;
+    Ok(hierarchy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETLIST: &str = "\
+* test netlist
+.subckt ptap_tile vss
+.ends ptap_tile
+.subckt ptap_tile_1 vss sub
+Xdummy vss sub
++ cap w=1
+.ends ptap_tile_1
+.subckt inverter_0123abcd din dout vdd vss
+Xtap vss ptap_tile
+Xn dout din vss vss sky130_fd_pr__nfet_01v8 w=1 l=0.15
+.ends inverter_0123abcd
+.subckt top_cell din dout vdd vss
+Xinv din dout vdd vss inverter_0123abcd
+Xtap vss vss ptap_tile_1
+.ends top_cell
+";
+
+    #[test]
+    fn renames_unhashed_subckts() {
+        let hierarchy = NetlistHierarchy::parse(NETLIST).unwrap();
+        assert_eq!(hierarchy.top_cells(), ["top_cell"]);
+        assert_eq!(
+            hierarchy.subtree("top_cell"),
+            ["ptap_tile", "inverter_0123abcd", "ptap_tile_1", "top_cell"]
+        );
+        assert_eq!(
+            hierarchy.get("inverter_0123abcd").unwrap().instances.len(),
+            1
+        );
+
+        let (renamed, map) = hash_subckt_names(NETLIST).unwrap();
+        assert_eq!(map.len(), 2);
+        assert!(map.values().all(|name| has_param_hash(name)));
+        let hierarchy = NetlistHierarchy::parse(&renamed).unwrap();
+        let tap = &hierarchy.get("top_cell").unwrap().instances[1];
+        assert_eq!(tap.cell, map["ptap_tile_1"]);
+        assert!(hierarchy.get(&map["ptap_tile"]).is_some());
+        assert_eq!(hash_subckt_names(&renamed).unwrap().0, renamed);
+    }
+}
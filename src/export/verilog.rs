@@ -0,0 +1,372 @@
+//! Behavioral Verilog model generation.
+//!
+//! Models use the same module and port names as the generated netlists, so they can
+//! stand in for the PHY in SoC-level simulations. Buses are declared as vectors, and
+//! differential pairs are split into `_p` and `_n` ports.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use substrate::block::Block;
+
+use crate::buffer::{Buffer, Inverter};
+use crate::driver::{HorizontalDriver, VerticalDriver};
+use crate::strongarm::{StrongArm, StrongArmParams, StrongArmWithOutputBuffers};
+
+/// The style of a behavioral model.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum ModelKind {
+    /// A purely digital model.
+    Digital,
+    /// A SystemVerilog real-number model, in which analog pins carry voltages.
+    RealNumber,
+}
+
+/// The direction of a port.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum PortDirection {
+    /// An input port.
+    Input,
+    /// An output port.
+    Output,
+    /// A bidirectional port.
+    InOut,
+}
+
+/// A port of a [`VerilogModule`].
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct VerilogPort {
+    /// The port name.
+    pub name: String,
+    /// The port direction.
+    pub direction: PortDirection,
+    /// The bus width, or `None` for a scalar port.
+    pub width: Option<usize>,
+    /// Whether the port carries a real-valued voltage.
+    pub real: bool,
+}
+
+impl VerilogPort {
+    fn new(name: &str, direction: PortDirection) -> Self {
+        Self {
+            name: name.to_string(),
+            direction,
+            width: None,
+            real: false,
+        }
+    }
+
+    fn bus(name: &str, direction: PortDirection, width: usize) -> Self {
+        Self {
+            width: Some(width),
+            ..Self::new(name, direction)
+        }
+    }
+
+    fn real_if(mut self, real: bool) -> Self {
+        self.real = real;
+        self
+    }
+}
+
+/// A behavioral Verilog module.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct VerilogModule {
+    /// The module name.
+    pub name: String,
+    /// Module parameters, as pairs of name and default value.
+    pub parameters: Vec<(String, String)>,
+    /// The module ports.
+    pub ports: Vec<VerilogPort>,
+    /// The module body.
+    pub body: String,
+}
+
+impl VerilogModule {
+    /// Returns the Verilog source of this module.
+    pub fn to_verilog(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out).expect("failed to format Verilog");
+        out
+    }
+
+    fn write(&self, out: &mut String) -> std::fmt::Result {
+        write!(out, "module {}", self.name)?;
+        if !self.parameters.is_empty() {
+            writeln!(out, " #(")?;
+            let params = self
+                .parameters
+                .iter()
+                .map(|(name, value)| format!("  parameter {name} = {value}"))
+                .collect::<Vec<_>>();
+            write!(out, "{}\n)", params.join(",\n"))?;
+        }
+        writeln!(out, " (")?;
+        let ports = self
+            .ports
+            .iter()
+            .map(|port| {
+                let direction = match port.direction {
+                    PortDirection::Input => "input",
+                    PortDirection::Output => "output",
+                    PortDirection::InOut => "inout",
+                };
+                let ty = match (port.real, port.direction) {
+                    (true, _) => "real",
+                    (false, PortDirection::Output) => "reg",
+                    (false, _) => "wire",
+                };
+                let range = port
+                    .width
+                    .map(|width| format!(" [{}:0]", width - 1))
+                    .unwrap_or_default();
+                format!("  {direction} {ty}{range} {}", port.name)
+            })
+            .collect::<Vec<_>>();
+        writeln!(out, "{}\n);", ports.join(",\n"))?;
+        writeln!(out, "{}", self.body.trim_end())?;
+        writeln!(out, "endmodule")
+    }
+
+    /// Writes this module to `path`.
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.to_verilog())
+    }
+}
+
+/// A block with a behavioral Verilog model.
+pub trait VerilogModel: Block {
+    /// Returns a behavioral model of this block named after the generated cell.
+    fn verilog_model(&self, kind: ModelKind) -> VerilogModule;
+}
+
+fn supply_ports() -> [VerilogPort; 2] {
+    [
+        VerilogPort::new("vdd", PortDirection::InOut),
+        VerilogPort::new("vss", PortDirection::InOut),
+    ]
+}
+
+fn buffer_model(name: &str, inverting: bool) -> VerilogModule {
+    let mut ports = vec![
+        VerilogPort::new("din", PortDirection::Input),
+        VerilogPort::new("dout", PortDirection::Output),
+    ];
+    ports.extend(supply_ports());
+    let op = if inverting { "~" } else { "" };
+    VerilogModule {
+        name: name.to_string(),
+        parameters: Vec::new(),
+        ports,
+        body: format!("  always @(*) dout = {op}din;\n"),
+    }
+}
+
+impl<T> VerilogModel for Inverter<T>
+where
+    Self: Block,
+{
+    fn verilog_model(&self, _kind: ModelKind) -> VerilogModule {
+        buffer_model(&self.name(), true)
+    }
+}
+
+impl<T> VerilogModel for Buffer<T>
+where
+    Self: Block,
+{
+    fn verilog_model(&self, _kind: ModelKind) -> VerilogModule {
+        buffer_model(&self.name(), false)
+    }
+}
+
+/// A clocked comparator model.
+///
+/// The comparator resolves when its evaluation phase begins and holds its outputs at
+/// `RESET` otherwise. An NMOS-input StrongARM evaluates while the clock is high
+/// and precharges its outputs high; a PMOS-input StrongARM is the complement. Inverting
+/// output buffers flip the reset value but not the decision.
+fn comparator_model(
+    name: &str,
+    params: StrongArmParams,
+    buffered: bool,
+    kind: ModelKind,
+) -> VerilogModule {
+    let real = kind == ModelKind::RealNumber;
+    let active_high = params.input_kind.is_n();
+    let reset = active_high != buffered;
+    let mut ports = vec![
+        VerilogPort::new("input_p", PortDirection::Input).real_if(real),
+        VerilogPort::new("input_n", PortDirection::Input).real_if(real),
+        VerilogPort::new("output_p", PortDirection::Output),
+        VerilogPort::new("output_n", PortDirection::Output),
+        VerilogPort::new("clock", PortDirection::Input),
+    ];
+    ports.extend(supply_ports());
+    let phase = if active_high { "clock" } else { "!clock" };
+    let decision = if real {
+        "input_p > input_n"
+    } else {
+        "input_p & ~input_n"
+    };
+    let body = format!(
+        "  always @(clock) begin\n\
+         \x20   if ({phase}) begin\n\
+         \x20     output_p = {decision};\n\
+         \x20     output_n = !output_p;\n\
+         \x20   end else begin\n\
+         \x20     output_p = RESET;\n\
+         \x20     output_n = RESET;\n\
+         \x20   end\n\
+         \x20 end\n"
+    );
+    VerilogModule {
+        name: name.to_string(),
+        parameters: vec![("RESET".to_string(), format!("1'b{}", reset as u8))],
+        ports,
+        body,
+    }
+}
+
+impl<T> VerilogModel for StrongArm<T>
+where
+    Self: Block,
+{
+    fn verilog_model(&self, kind: ModelKind) -> VerilogModule {
+        comparator_model(&self.name(), self.params(), false, kind)
+    }
+}
+
+impl<T> VerilogModel for StrongArmWithOutputBuffers<T>
+where
+    Self: Block,
+{
+    fn verilog_model(&self, kind: ModelKind) -> VerilogModule {
+        comparator_model(&self.name(), self.sa_params(), true, kind)
+    }
+}
+
+/// A segmented driver model.
+///
+/// Each enabled pull-up segment drives `dout` high while `din` is high, and each enabled
+/// pull-down segment drives it low while `din` is low. With no segments enabled, the
+/// digital model floats `dout`. The real-number model computes the voltage across a
+/// termination of `R_TERM` ohms to VSS, given a per-segment resistance of `R_UNIT` ohms.
+/// Enabled spare segments count the same as main segments.
+fn driver_model(name: &str, n: usize, spares: usize, kind: ModelKind) -> VerilogModule {
+    let real = kind == ModelKind::RealNumber;
+    let mut ports = vec![
+        VerilogPort::new("din", PortDirection::Input),
+        VerilogPort::new("dout", PortDirection::Output).real_if(real),
+        VerilogPort::bus("pu_ctl", PortDirection::Input, n),
+        VerilogPort::bus("pd_ctlb", PortDirection::Input, n),
+    ];
+    let (mut body, pu, pdb) = if spares > 0 {
+        ports.extend([
+            VerilogPort::bus("spare_pu_ctl", PortDirection::Input, spares),
+            VerilogPort::bus("spare_pd_ctlb", PortDirection::Input, spares),
+        ]);
+        let msb = n + spares - 1;
+        (
+            format!(
+                "  wire [{msb}:0] pu_all = {{spare_pu_ctl, pu_ctl}};\n  \
+                 wire [{msb}:0] pd_all = {{spare_pd_ctlb, pd_ctlb}};\n"
+            ),
+            "pu_all",
+            "pd_all",
+        )
+    } else {
+        (String::new(), "pu_ctl", "pd_ctlb")
+    };
+    ports.extend(supply_ports());
+    let mut parameters = Vec::new();
+    if real {
+        parameters.extend([
+            ("VDD".to_string(), "0.4".to_string()),
+            ("R_UNIT".to_string(), format!("{}.0", 50 * n)),
+            ("R_TERM".to_string(), "50.0".to_string()),
+        ]);
+        body.push_str(&format!(
+            "  integer i;\n\
+             \x20 real n_on;\n\
+             \x20 always @(*) begin\n\
+             \x20   n_on = 0;\n\
+             \x20   for (i = 0; i < $bits({pu}); i = i + 1)\n\
+             \x20     n_on = n_on + (din ? {pu}[i] : 0);\n\
+             \x20   dout = n_on > 0 ? VDD * R_TERM / (R_TERM + R_UNIT / n_on) : 0.0;\n\
+             \x20 end\n"
+        ));
+    } else {
+        body.push_str(&format!(
+            "  always @(*) begin\n\
+             \x20   if (din) dout = |{pu} ? 1'b1 : 1'bz;\n\
+             \x20   else dout = ~&{pdb} ? 1'b0 : 1'bz;\n\
+             \x20 end\n"
+        ));
+    }
+    VerilogModule {
+        name: name.to_string(),
+        parameters,
+        ports,
+        body,
+    }
+}
+
+impl<T> VerilogModel for HorizontalDriver<T>
+where
+    Self: Block,
+{
+    fn verilog_model(&self, kind: ModelKind) -> VerilogModule {
+        let params = self.params();
+        driver_model(
+            &self.name(),
+            params.num_segments * params.banks,
+            params.spares_per_bank() * params.banks,
+            kind,
+        )
+    }
+}
+
+impl<T> VerilogModel for VerticalDriver<T>
+where
+    Self: Block,
+{
+    fn verilog_model(&self, kind: ModelKind) -> VerilogModule {
+        let params = self.params();
+        driver_model(
+            &self.name(),
+            params.num_segments,
+            params.spares_per_bank(),
+            kind,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declares_buses_and_parameters() {
+        let module = VerilogModule {
+            name: "driver".to_string(),
+            parameters: vec![("R_TERM".to_string(), "50.0".to_string())],
+            ports: vec![
+                VerilogPort::bus("pu_ctl", PortDirection::Input, 16),
+                VerilogPort::new("dout", PortDirection::Output).real_if(true),
+                VerilogPort::new("vdd", PortDirection::InOut),
+            ],
+            body: "  assign dout = 0.0;\n".to_string(),
+        };
+        assert_eq!(
+            module.to_verilog(),
+            "module driver #(\n  parameter R_TERM = 50.0\n) (\n  input wire [15:0] pu_ctl,\n  \
+             output real dout,\n  inout wire vdd\n);\n  assign dout = 0.0;\nendmodule\n"
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! Helpers shared by the command-line interface and the Python bindings.
+//!
+//! Both front ends generate SKY130 blocks from serialized parameters, write their
+//! netlists, and characterize StrongARM latches across corners. Keeping the block
+//! dispatch, netlisting, and characterization corners here keeps the two in sync.
+
+use std::fmt::Debug;
+use std::path::Path;
+use std::str::FromStr;
+
+use atoll::TileWrapper;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sky130pdk::corner::Sky130Corner;
+use sky130pdk::{Sky130CommercialSchema, Sky130OpenSchema, Sky130Pdk};
+use spice::netlist::NetlistOptions;
+use spice::Spice;
+use substrate::block::Block;
+use substrate::context::PdkContext;
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::netlist::ConvertibleNetlister;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::Schematic;
+use substrate::scir::schema::FromSchema;
+
+use crate::buffer::InverterParams;
+use crate::config::SamplerConfig;
+use crate::params::ParamsError;
+use crate::strongarm::tb::StrongArmTranTb;
+use crate::strongarm::{StrongArm, StrongArmParams};
+use crate::sweep::corners::CornerLibrary;
+use crate::sweep::{pvt_grid, CornerSweep};
+use crate::tech::sky130::Sky130Ucie;
+
+/// An error produced by a front-end helper.
+#[derive(Debug, thiserror::Error)]
+pub enum FrontendError {
+    /// No block has the given name.
+    #[error("unknown block {0:?}")]
+    UnknownBlock(String),
+    /// The block parameters are invalid.
+    #[error("invalid parameters: {0}")]
+    Params(#[from] ParamsError),
+    /// The schematic could not be exported as a netlist.
+    #[error("{0}")]
+    Netlist(String),
+    /// An I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A block that the front ends can generate.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "snake_case")]
+pub enum BlockKind {
+    /// An [`Inverter`](crate::buffer::Inverter).
+    Inverter,
+    /// A [`Buffer`](crate::buffer::Buffer).
+    Buffer,
+    /// A [`StrongArm`].
+    Strongarm,
+    /// A [`StrongArmWithOutputBuffers`](crate::strongarm::StrongArmWithOutputBuffers),
+    /// whose parameters are a [`SamplerConfig`](crate::config::SamplerConfig).
+    StrongarmWithOutputBuffers,
+}
+
+impl BlockKind {
+    /// The name of the block kind, as accepted by [`BlockKind::from_str`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Inverter => "inverter",
+            Self::Buffer => "buffer",
+            Self::Strongarm => "strongarm",
+            Self::StrongarmWithOutputBuffers => "strongarm_with_output_buffers",
+        }
+    }
+}
+
+impl FromStr for BlockKind {
+    type Err = FrontendError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Self::Inverter,
+            Self::Buffer,
+            Self::Strongarm,
+            Self::StrongarmWithOutputBuffers,
+        ]
+        .into_iter()
+        .find(|kind| kind.name() == s)
+        .ok_or_else(|| FrontendError::UnknownBlock(s.to_string()))
+    }
+}
+
+/// Parameters that the front ends deserialize from user input.
+pub trait FrontendParams: DeserializeOwned {
+    /// Returns an error if the parameters are invalid or use channel lengths that the
+    /// SKY130 MOS tiles do not support.
+    ///
+    /// The front ends call this before generating anything, so that bad input is
+    /// reported as an error rather than failing deep inside layout generation.
+    fn validate(&self) -> Result<(), ParamsError>;
+}
+
+impl FrontendParams for InverterParams {
+    fn validate(&self) -> Result<(), ParamsError> {
+        InverterParams::validate(self)?;
+        self.check_lengths::<Sky130Ucie>()
+    }
+}
+
+impl FrontendParams for StrongArmParams {
+    fn validate(&self) -> Result<(), ParamsError> {
+        StrongArmParams::validate(self)?;
+        self.check_lengths::<Sky130Ucie>()
+    }
+}
+
+impl FrontendParams for SamplerConfig {
+    fn validate(&self) -> Result<(), ParamsError> {
+        FrontendParams::validate(&self.strongarm)?;
+        FrontendParams::validate(&self.buffer)
+    }
+}
+
+/// Deserializes the parameters of the [`BlockKind`] `$kind` by calling `$read($src)`,
+/// generates the block in SKY130, and evaluates `$f` with the block bound to `$block`.
+///
+/// `$read` must be generic over [`FrontendParams`] and should
+/// [validate](FrontendParams::validate) the parameters it returns. Its error must
+/// convert into the error type of the enclosing function.
+#[macro_export]
+macro_rules! with_sky130_block {
+    ($kind:expr, $read:expr, $src:expr, |$block:ident| $f:expr) => {
+        match $kind {
+            $crate::frontend::BlockKind::Inverter => {
+                let $block = ::atoll::TileWrapper::new($crate::buffer::Inverter::<
+                    $crate::tech::sky130::Sky130Ucie,
+                >::new($read($src)?));
+                $f
+            }
+            $crate::frontend::BlockKind::Buffer => {
+                let $block = ::atoll::TileWrapper::new($crate::buffer::Buffer::<
+                    $crate::tech::sky130::Sky130Ucie,
+                >::new($read($src)?));
+                $f
+            }
+            $crate::frontend::BlockKind::Strongarm => {
+                let $block = ::atoll::TileWrapper::new($crate::strongarm::StrongArm::<
+                    $crate::tech::sky130::Sky130Ucie,
+                >::new($read($src)?));
+                $f
+            }
+            $crate::frontend::BlockKind::StrongarmWithOutputBuffers => {
+                let sampler: $crate::config::SamplerConfig = $read($src)?;
+                let $block =
+                    ::atoll::TileWrapper::new($crate::strongarm::StrongArmWithOutputBuffers::<
+                        $crate::tech::sky130::Sky130Ucie,
+                    >::new(
+                        sampler.strongarm, sampler.buffer
+                    ));
+                $f
+            }
+        }
+    };
+}
+
+/// Writes the schematic of `block` to a SPICE netlist at `path` using the schema `S`.
+pub fn write_netlist_with_schema<S, B>(
+    ctx: &PdkContext<Sky130Pdk>,
+    block: B,
+    path: &Path,
+) -> Result<(), FrontendError>
+where
+    S: Schema + FromSchema<Sky130Pdk>,
+    Spice: FromSchema<S>,
+    <S as FromSchema<Sky130Pdk>>::Error: Debug,
+    <Spice as FromSchema<S>>::Error: Debug,
+    B: Block + Schematic<Sky130Pdk>,
+{
+    let scir = ctx
+        .export_scir(block)
+        .map_err(|e| FrontendError::Netlist(format!("failed to export schematic: {e:?}")))?
+        .scir
+        .convert_schema::<S>()
+        .map_err(|e| FrontendError::Netlist(format!("failed to convert schematic: {e:?}")))?
+        .convert_schema::<Spice>()
+        .map_err(|e| FrontendError::Netlist(format!("failed to convert schematic: {e:?}")))?
+        .build()
+        .map_err(|e| FrontendError::Netlist(format!("failed to build netlist: {e:?}")))?;
+    Spice
+        .write_scir_netlist_to_file(&scir, path, NetlistOptions::default())
+        .map_err(|e| FrontendError::Netlist(format!("failed to write netlist: {e:?}")))?;
+    Ok(())
+}
+
+/// Writes the schematic of `block` to a SPICE netlist at `path`, creating its parent
+/// directory if needed.
+///
+/// Uses the open-source SKY130A device names if `open` is set, and the commercial
+/// SKY130 names otherwise.
+pub fn write_netlist<B: Block + Schematic<Sky130Pdk>>(
+    ctx: &PdkContext<Sky130Pdk>,
+    open: bool,
+    block: B,
+    path: &Path,
+) -> Result<(), FrontendError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if open {
+        write_netlist_with_schema::<Sky130OpenSchema, _>(ctx, block, path)
+    } else {
+        write_netlist_with_schema::<Sky130CommercialSchema, _>(ctx, block, path)
+    }
+}
+
+/// The corners at which the front ends characterize blocks: the typical, slow, and fast
+/// corners at every SKY130 supply voltage and temperature.
+pub fn characterization_pvts() -> Vec<Pvt<Sky130Corner>> {
+    pvt_grid(
+        [Sky130Corner::Tt, Sky130Corner::Ss, Sky130Corner::Ff],
+        Sky130Ucie::voltages(),
+        Sky130Ucie::temperatures(),
+    )
+}
+
+/// Returns a sweep that simulates a StrongARM with the given inputs at each of the
+/// [`characterization_pvts`].
+///
+/// P-input latches are clocked with an inverted clock.
+pub fn strongarm_characterization(
+    params: StrongArmParams,
+    vinp: Decimal,
+    vinn: Decimal,
+) -> CornerSweep<
+    StrongArmTranTb<TileWrapper<StrongArm<Sky130Ucie>>, Sky130Pdk, Sky130Corner>,
+    Sky130Corner,
+> {
+    let inverted_clk = params.input_kind.is_p();
+    let dut = TileWrapper::new(StrongArm::<Sky130Ucie>::new(params));
+    CornerSweep::new(characterization_pvts(), move |pvt| {
+        StrongArmTranTb::new(dut, vinp, vinn, inverted_clk, pvt)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    #[test]
+    fn block_kinds_round_trip_through_names() {
+        for kind in [
+            BlockKind::Inverter,
+            BlockKind::Buffer,
+            BlockKind::Strongarm,
+            BlockKind::StrongarmWithOutputBuffers,
+        ] {
+            assert_eq!(kind.name().parse::<BlockKind>().unwrap(), kind);
+        }
+        assert!(matches!(
+            "driver".parse::<BlockKind>(),
+            Err(FrontendError::UnknownBlock(name)) if name == "driver"
+        ));
+    }
+
+    #[test]
+    fn rejects_unsupported_lengths() {
+        let toml = |l: i64| {
+            format!(
+                "nmos_kind = \"Nom\"\n\
+                 pmos_kind = \"Nom\"\n\
+                 half_tail_w = 1000\n\
+                 input_pair_w = 1000\n\
+                 inv_input_w = 1000\n\
+                 inv_precharge_w = 1000\n\
+                 precharge_w = 1000\n\
+                 input_kind = \"P\"\n\
+                 input_pair_l = {l}\n"
+            )
+        };
+        let params: StrongArmParams = toml::from_str(&toml(150)).unwrap();
+        assert!(FrontendParams::validate(&params).is_ok());
+        let params: StrongArmParams = toml::from_str(&toml(500)).unwrap();
+        assert!(matches!(
+            FrontendParams::validate(&params),
+            Err(ParamsError::Unsupported {
+                field: "input_pair_l",
+                ..
+            })
+        ));
+        let params: StrongArmParams = toml::from_str(&toml(-150)).unwrap();
+        assert!(matches!(
+            FrontendParams::validate(&params),
+            Err(ParamsError::NonPositive { .. })
+        ));
+    }
+
+    #[test]
+    fn characterization_covers_typical_and_skewed_corners() {
+        let pvts = characterization_pvts();
+        assert_eq!(pvts.len(), 27);
+        assert_eq!(
+            pvts.first().map(|pvt| (pvt.corner, pvt.voltage, pvt.temp)),
+            Some((Sky130Corner::Tt, dec!(1.62), dec!(-40)))
+        );
+        assert_eq!(
+            pvts.last().map(|pvt| (pvt.corner, pvt.voltage, pvt.temp)),
+            Some((Sky130Corner::Ff, dec!(1.98), dec!(125)))
+        );
+    }
+}
@@ -0,0 +1,497 @@
+//! Static CMOS logic gate layout generators.
+
+use crate::buffer::{BufferIoSchematic, Inverter, InverterImpl, InverterParams};
+use crate::params::{check_positive, setters, ParamsError};
+use crate::tiles::{MosKind, MosTileParams, TapTileParams, TileKind, WidthSpec};
+use atoll::{IoBuilder, Orientation, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::geometry::align::AlignMode;
+use substrate::io::{InOut, Input, Io, MosIoSchematic, Output, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+/// The interface to a two-input gate.
+#[derive(Debug, Default, Clone, Io)]
+pub struct Gate2Io {
+    /// The first input.
+    pub a: Input<Signal>,
+    /// The second input.
+    pub b: Input<Signal>,
+    /// The output.
+    pub y: Output<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The logic function of a [`Gate2`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Gate2Kind {
+    /// A NAND gate, with series NMOS and parallel PMOS devices.
+    Nand,
+    /// A NOR gate, with parallel NMOS and series PMOS devices.
+    Nor,
+}
+
+impl Gate2Kind {
+    /// The drain and source nets of the NMOS and PMOS devices driven by each input,
+    /// given the rails, the output `y`, and the internal node `x` of the series stack.
+    ///
+    /// The series stack runs from its rail through `x` to the output; the parallel
+    /// devices each connect their rail directly to the output.
+    fn devices<N: Copy>(&self, vdd: N, vss: N, x: N, y: N) -> ([(N, N); 2], [(N, N); 2]) {
+        match self {
+            Gate2Kind::Nand => ([(vss, x), (x, y)], [(vdd, y), (vdd, y)]),
+            Gate2Kind::Nor => ([(vss, y), (vss, y)], [(vdd, x), (x, y)]),
+        }
+    }
+}
+
+/// The parameters of the [`Gate2`] layout generator.
+///
+/// Device widths are given per transistor, so series devices should be sized up from
+/// the equivalent inverter to match its drive strength.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Gate2Params {
+    /// The logic function.
+    pub kind: Gate2Kind,
+    /// The device sizes.
+    pub devices: InverterParams,
+}
+
+impl Gate2Params {
+    /// Creates a NAND gate with the given device sizes.
+    pub fn nand(devices: InverterParams) -> Self {
+        Self {
+            kind: Gate2Kind::Nand,
+            devices,
+        }
+    }
+
+    /// Creates a NOR gate with the given device sizes.
+    pub fn nor(devices: InverterParams) -> Self {
+        Self {
+            kind: Gate2Kind::Nor,
+            devices,
+        }
+    }
+}
+
+/// A two-input NAND or NOR gate.
+///
+/// The two PMOS devices sit side by side above the two NMOS devices, with a well tap
+/// on top and a substrate tap on the bottom, matching the row order of an
+/// [`Inverter`](crate::buffer::Inverter).
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct Gate2<T>(
+    Gate2Params,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> Gate2<T> {
+    /// Creates a new [`Gate2`].
+    pub fn new(params: Gate2Params) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The gate parameters.
+    pub fn params(&self) -> Gate2Params {
+        self.0
+    }
+}
+
+impl<T: Any> Block for Gate2<T> {
+    type Io = Gate2Io;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("gate2")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("gate2", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for Gate2<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for Gate2<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for Gate2<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let devices = self.0.devices;
+        let nmos_params = MosTileParams::new(devices.nmos_kind, TileKind::N, devices.nmos_w)
+            .with_length(devices.nmos_l)
+            .snapped(T::snap_width);
+        let pmos_params = MosTileParams::new(devices.pmos_kind, TileKind::P, devices.pmos_w)
+            .with_length(devices.pmos_l)
+            .snapped(T::snap_width);
+
+        let x = cell.signal("x", Signal);
+        let (vdd, vss, y) = (io.schematic.vdd, io.schematic.vss, io.schematic.y);
+        let inputs = [io.schematic.a, io.schematic.b];
+        let (nmos_ds, pmos_ds) = self.0.kind.devices(vdd, vss, x, y);
+
+        let mut nmos = nmos_ds
+            .into_iter()
+            .zip(inputs)
+            .map(|((d, s), g)| {
+                cell.generate_connected(T::mos(nmos_params), MosIoSchematic { d, g, s, b: vss })
+                    .orient(Orientation::R180)
+            })
+            .collect::<Vec<_>>();
+        let mut pmos = pmos_ds
+            .into_iter()
+            .zip(inputs)
+            .map(|((d, s), g)| {
+                cell.generate_connected(T::mos(pmos_params), MosIoSchematic { d, g, s, b: vdd })
+            })
+            .collect::<Vec<_>>();
+
+        let mut ptap = cell.generate(T::tap(TapTileParams::new(TileKind::P, 2)));
+        let ntap = cell.generate(T::tap(TapTileParams::new(TileKind::N, 2)));
+        cell.connect(ptap.io().x, vss);
+        cell.connect(ntap.io().x, vdd);
+
+        let mut prev = ntap.lcm_bounds();
+        for row in [&mut pmos, &mut nmos] {
+            row[0].align_rect_mut(prev, AlignMode::Left, 0);
+            row[0].align_rect_mut(prev, AlignMode::Beneath, 0);
+            let left = row[0].lcm_bounds();
+            row[1].align_rect_mut(left, AlignMode::Bottom, 0);
+            row[1].align_rect_mut(left, AlignMode::ToTheRight, 0);
+            prev = left;
+        }
+        ptap.align_rect_mut(prev, AlignMode::Left, 0);
+        ptap.align_rect_mut(prev, AlignMode::Beneath, 0);
+
+        let nmos = nmos
+            .into_iter()
+            .map(|mos| cell.draw(mos))
+            .collect::<substrate::error::Result<Vec<_>>>()?;
+        let pmos = pmos
+            .into_iter()
+            .map(|mos| cell.draw(mos))
+            .collect::<substrate::error::Result<Vec<_>>>()?;
+        let ptap = cell.draw(ptap)?;
+        let ntap = cell.draw(ntap)?;
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        io.layout.a.merge(nmos[0].layout.io().g);
+        io.layout.a.merge(pmos[0].layout.io().g);
+        io.layout.b.merge(nmos[1].layout.io().g);
+        io.layout.b.merge(pmos[1].layout.io().g);
+        // Every device has at least one terminal on the output except the first device
+        // of each series stack.
+        let (nmos_y, pmos_y) = match self.0.kind {
+            Gate2Kind::Nand => (&nmos[1..], &pmos[..]),
+            Gate2Kind::Nor => (&nmos[..], &pmos[1..]),
+        };
+        for mos in nmos_y {
+            io.layout.y.merge(mos.layout.io().s);
+        }
+        for mos in pmos_y {
+            io.layout.y.merge(mos.layout.io().s);
+        }
+        io.layout.vdd.merge(ntap.layout.io().x);
+        io.layout.vss.merge(ptap.layout.io().x);
+
+        T::post_layout_hooks(cell)?;
+
+        Ok(((), ()))
+    }
+}
+
+/// The interface to a [`Dff`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct DffIo {
+    /// The data input.
+    pub d: Input<Signal>,
+    /// The clock.
+    pub clk: Input<Signal>,
+    /// The data output.
+    pub q: Output<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of the [`Dff`] layout generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct DffParams {
+    /// The device sizes of the NAND gates, per transistor.
+    pub gate: InverterParams,
+    /// The device sizes of the clock inverter.
+    pub clk_inv: InverterParams,
+}
+
+impl DffParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> DffParamsBuilder {
+        DffParamsBuilder::default()
+    }
+}
+
+/// A builder for [`DffParams`].
+///
+/// Defaults to 2 um NAND devices and a minimum-length clock inverter with a 2:1 PMOS.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct DffParamsBuilder {
+    params: DffParams,
+}
+
+impl Default for DffParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: DffParams {
+                gate: InverterParams {
+                    nmos_kind: MosKind::Nom,
+                    pmos_kind: MosKind::Nom,
+                    nmos_w: WidthSpec::Nm(2_000),
+                    pmos_w: WidthSpec::Nm(2_000),
+                    nmos_l: None,
+                    pmos_l: None,
+                },
+                clk_inv: InverterParams {
+                    nmos_kind: MosKind::Nom,
+                    pmos_kind: MosKind::Nom,
+                    nmos_w: WidthSpec::Nm(1_000),
+                    pmos_w: WidthSpec::Nm(2_000),
+                    nmos_l: None,
+                    pmos_l: None,
+                },
+            },
+        }
+    }
+}
+
+impl DffParamsBuilder {
+    setters! {
+        /// Sets the device sizes of the NAND gates.
+        gate: InverterParams,
+        /// Sets the device sizes of the clock inverter.
+        clk_inv: InverterParams,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<DffParams, ParamsError> {
+        let p = &self.params;
+        for (field, value) in [
+            ("gate.nmos_w", p.gate.nmos_w.value()),
+            ("gate.pmos_w", p.gate.pmos_w.value()),
+            ("clk_inv.nmos_w", p.clk_inv.nmos_w.value()),
+            ("clk_inv.pmos_w", p.clk_inv.pmos_w.value()),
+        ] {
+            check_positive(field, value)?;
+        }
+        Ok(self.params)
+    }
+}
+
+/// A static positive-edge-triggered D flip-flop.
+///
+/// A master latch, transparent while `clk` is low, feeds a slave latch that is
+/// transparent while `clk` is high. Each latch is four [`Gate2`] NAND gates: two steer
+/// the data onto a cross-coupled NAND pair while the latch is enabled. The clock
+/// inverter and the eight gates sit in a single row in signal order.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct Dff<T>(
+    DffParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> Dff<T> {
+    /// Creates a new [`Dff`].
+    pub fn new(params: DffParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The flip-flop parameters.
+    pub fn params(&self) -> DffParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for Dff<T> {
+    type Io = DffIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("dff")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("dff", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for Dff<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for Dff<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for Dff<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let (vdd, vss) = (io.schematic.vdd, io.schematic.vss);
+        let clkb = cell.signal("clkb", Signal);
+        let master_q = cell.signal("master_q", Signal);
+
+        let clk_inv = cell.generate_connected(
+            Inverter::<T>::new(self.0.clk_inv),
+            BufferIoSchematic {
+                din: io.schematic.clk,
+                dout: clkb,
+                vdd,
+                vss,
+            },
+        );
+
+        // Each latch steers `d` onto the cross-coupled pair through `s` and `r`.
+        let mut gates = Vec::new();
+        for (name, d, en, q) in [
+            ("master", io.schematic.d, clkb, master_q),
+            ("slave", master_q, io.schematic.clk, io.schematic.q),
+        ] {
+            let s = cell.signal(format!("{name}_s"), Signal);
+            let r = cell.signal(format!("{name}_r"), Signal);
+            let qb = cell.signal(format!("{name}_qb"), Signal);
+            for (a, b, y) in [(d, en, s), (s, en, r), (s, qb, q), (r, q, qb)] {
+                gates.push(cell.generate_connected(
+                    Gate2::<T>::new(Gate2Params::nand(self.0.gate)),
+                    Gate2IoSchematic { a, b, y, vdd, vss },
+                ));
+            }
+        }
+
+        let mut prev = clk_inv.lcm_bounds();
+        for gate in gates.iter_mut() {
+            gate.align_rect_mut(prev, AlignMode::ToTheRight, 0);
+            gate.align_rect_mut(prev, AlignMode::Bottom, 0);
+            prev = gate.lcm_bounds();
+        }
+
+        let clk_inv = cell.draw(clk_inv)?;
+        let gates = gates
+            .into_iter()
+            .map(|gate| cell.draw(gate))
+            .collect::<substrate::error::Result<Vec<_>>>()?;
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        io.layout.d.merge(gates[0].layout.io().a);
+        io.layout.clk.merge(clk_inv.layout.io().din);
+        io.layout.q.merge(gates[6].layout.io().y);
+        io.layout.vdd.merge(clk_inv.layout.io().vdd);
+        io.layout.vss.merge(clk_inv.layout.io().vss);
+
+        Ok(((), ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Node {
+        Vdd,
+        Vss,
+        X,
+        Y,
+    }
+
+    /// Returns whether the output of `kind` is driven high for inputs `a` and `b`, or
+    /// panics if it is not driven to exactly one rail.
+    fn eval(kind: Gate2Kind, a: bool, b: bool) -> bool {
+        let (nmos, pmos) = kind.devices(Node::Vdd, Node::Vss, Node::X, Node::Y);
+        // Each device conducts when its NMOS gate is high or its PMOS gate is low.
+        let on = nmos
+            .into_iter()
+            .zip([a, b])
+            .chain(pmos.into_iter().zip([!a, !b]))
+            .filter(|&(_, gate)| gate)
+            .map(|(ds, _)| ds)
+            .collect::<Vec<_>>();
+        let reaches = |rail| {
+            let mut reached = vec![rail];
+            while let Some(&(d, s)) = on
+                .iter()
+                .find(|&&(d, s)| reached.contains(&d) != reached.contains(&s))
+            {
+                reached.push(if reached.contains(&d) { s } else { d });
+            }
+            reached.contains(&Node::Y)
+        };
+        let (high, low) = (reaches(Node::Vdd), reaches(Node::Vss));
+        assert_ne!(
+            high, low,
+            "{kind:?} output contended or floating for {a}, {b}"
+        );
+        high
+    }
+
+    #[test]
+    fn gate2_truth_tables() {
+        for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+            assert_eq!(eval(Gate2Kind::Nand, a, b), !(a && b));
+            assert_eq!(eval(Gate2Kind::Nor, a, b), !(a || b));
+        }
+    }
+
+    #[test]
+    fn dff_rejects_empty_devices() {
+        let mut gate = DffParams::builder().build().unwrap().gate;
+        gate.pmos_w = WidthSpec::Nm(0);
+        assert!(matches!(
+            DffParams::builder().gate(gate).build(),
+            Err(ParamsError::NonPositive {
+                field: "gate.pmos_w",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,1288 @@
+//! Lane macro generators.
+
+use std::any::Any;
+use std::marker::PhantomData;
+
+use atoll::{Instance, IoBuilder, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::geometry::align::AlignMode;
+use substrate::geometry::bbox::Bbox;
+use substrate::geometry::dir::Dir;
+use substrate::geometry::rect::Rect;
+use substrate::geometry::span::Span;
+use substrate::io::layout::IoShape;
+use substrate::io::schematic::HardwareType;
+use substrate::io::{
+    Array, DiffPair, InOut, Input, Io, LayoutType, Output, Signal, TwoTerminalIo,
+    TwoTerminalIoSchematic,
+};
+use substrate::layout::element::Shape;
+use substrate::layout::{CellBuilder as LayoutCellBuilder, ExportsLayoutData, Layout};
+use substrate::pdk::layers::HasPin;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{CellBuilder as SchematicCellBuilder, ExportsNestedData, Schematic};
+
+use crate::abutment::{abut, check_abutment, tile_lanes, Abutment, Edge, EdgeKind};
+use crate::buffer::{Buffer, BufferIoSchematic, InverterImpl, InverterParams};
+use crate::config::DataRate;
+use crate::driver::{DriverParams, HorizontalDriver, HorizontalDriverImpl};
+use crate::esd::{RxEsd, RxEsdIoSchematic, RxEsdParams};
+use crate::pll::loop_filter::LoopFilterRules;
+use crate::route::ShieldNet;
+use crate::strongarm::{
+    ClockedDiffComparatorIoSchematic, StrongArmImpl, StrongArmParams, StrongArmWithOutputBuffers,
+    StrongArmWithOutputBuffersImpl,
+};
+use crate::tiles::{CapacitorIo, CapacitorIoSchematic, ResistorConn, ResistorIoSchematic};
+
+/// The interface to a transmit lane.
+#[derive(Debug, Default, Clone, Io)]
+pub struct TxLaneIo {
+    /// The signal to transmit, such as the divided clock from the PLL.
+    pub din: Input<Signal>,
+    /// The transmitted signal, connected to the bump.
+    pub dout: Output<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of the [`ClockLane`] generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ClockLaneParams {
+    /// The driver parameters.
+    ///
+    /// Use the parameters of the data lane drivers so that the clock lane is pitch-matched
+    /// to the data lanes.
+    pub driver: DriverParams,
+    /// The number of driver segments that are enabled.
+    ///
+    /// Segments are enabled in order of their control index, and the remaining
+    /// segments are disabled.
+    pub enabled_segments: usize,
+    /// The parameters of each inverter in the clock buffer.
+    pub clk_buf: InverterParams,
+}
+
+/// A lane implementation.
+pub trait LaneImpl<PDK: Pdk + Schema>:
+    HorizontalDriverImpl<PDK> + InverterImpl<PDK> + StrongArmWithOutputBuffersImpl<PDK>
+{
+    /// The schematic model of a [`MomCap`].
+    type MomCapModel: Schematic<PDK> + Block<Io = TwoTerminalIo>;
+    /// The spacing between a transmit lane's input buffer and its driver
+    /// in ATOLL grid coordinates.
+    const INPUT_BUFFER_SPACING: i64;
+    /// The fastest data rate at which lanes built in this technology can run.
+    const MAX_DATA_RATE: DataRate;
+
+    /// Creates the schematic model of a [`MomCap`] with the given parameters.
+    fn mom_cap_model(params: MomCapParams) -> Self::MomCapModel;
+}
+
+/// A forwarded clock lane.
+///
+/// The clock is buffered and drives a [`HorizontalDriver`] whose impedance control
+/// inputs are tied to a fixed code, since the forwarded clock is not recalibrated
+/// at runtime.
+// The clock buffer takes the place of a duty-cycle corrector, which does not exist yet.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct ClockLane<T>(
+    ClockLaneParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> ClockLane<T> {
+    /// Creates a new [`ClockLane`].
+    pub fn new(params: ClockLaneParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The clock lane parameters.
+    pub fn params(&self) -> ClockLaneParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for ClockLane<T> {
+    type Io = TxLaneIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("clock_lane")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("clock_lane", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for ClockLane<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for ClockLane<T> {
+    type LayoutData = ();
+}
+
+// The driver banks are enclosed in guard rings and fillers, so nothing is shared
+// across the lane outline.
+impl<T> Abutment for ClockLane<T> {
+    fn edge(&self, _edge: Edge) -> EdgeKind {
+        EdgeKind::Clear
+    }
+}
+
+impl<PDK: Pdk + Schema + Sized, T: LaneImpl<PDK> + Any> Tile<PDK> for ClockLane<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        tile_fixed_code_tx::<PDK, T, _>(
+            io,
+            cell,
+            self.0.driver,
+            self.0.enabled_segments,
+            self.0.clk_buf,
+        )?;
+        Ok(((), ()))
+    }
+}
+
+/// The role of a low-speed control lane.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum LaneRole {
+    /// The valid lane, which frames each 8 UI of data.
+    Valid,
+    /// The track lane, which carries a clock-like pattern used to track
+    /// the phase of the forwarded clock.
+    Track,
+}
+
+impl LaneRole {
+    /// The pattern transmitted on the lane in each 8 UI frame.
+    pub fn pattern(&self) -> [bool; 8] {
+        match self {
+            LaneRole::Valid => [true, true, true, true, false, false, false, false],
+            LaneRole::Track => [true, false, true, false, true, false, true, false],
+        }
+    }
+}
+
+/// The parameters of the [`ControlTxLane`] generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ControlTxLaneParams {
+    /// The role of the lane.
+    pub role: LaneRole,
+    /// The driver parameters, typically those of the data lane drivers.
+    pub driver: DriverParams,
+    /// The number of driver segments that are enabled.
+    pub enabled_segments: usize,
+    /// The parameters of each inverter in the input buffer.
+    pub buf: InverterParams,
+}
+
+/// A valid or track transmit lane.
+///
+/// Like the data lanes, the lane drives the bump with a [`HorizontalDriver`], but
+/// its low-speed patterns need no equalization, so the driver code is fixed
+/// as in the [`ClockLane`].
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct ControlTxLane<T>(
+    ControlTxLaneParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> ControlTxLane<T> {
+    /// Creates a new [`ControlTxLane`].
+    pub fn new(params: ControlTxLaneParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The lane parameters.
+    pub fn params(&self) -> ControlTxLaneParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for ControlTxLane<T> {
+    type Io = TxLaneIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("control_tx_lane")
+    }
+
+    fn name(&self) -> ArcStr {
+        let base = match self.0.role {
+            LaneRole::Valid => "valid_tx_lane",
+            LaneRole::Track => "track_tx_lane",
+        };
+        crate::block_name(base, &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for ControlTxLane<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for ControlTxLane<T> {
+    type LayoutData = ();
+}
+
+// Same layout as the clock lane.
+impl<T> Abutment for ControlTxLane<T> {
+    fn edge(&self, _edge: Edge) -> EdgeKind {
+        EdgeKind::Clear
+    }
+}
+
+impl<PDK: Pdk + Schema + Sized, T: LaneImpl<PDK> + Any> Tile<PDK> for ControlTxLane<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        tile_fixed_code_tx::<PDK, T, _>(
+            io,
+            cell,
+            self.0.driver,
+            self.0.enabled_segments,
+            self.0.buf,
+        )?;
+        Ok(((), ()))
+    }
+}
+
+/// The interface to a single-ended receive lane.
+#[derive(Debug, Default, Clone, Io)]
+pub struct RxLaneIo {
+    /// The received signal, connected to the bump.
+    pub din: Input<Signal>,
+    /// The reference voltage against which `din` is compared.
+    pub vref: Input<Signal>,
+    /// The sampling clock.
+    pub clk: Input<Signal>,
+    /// The sampled data.
+    pub dout: Output<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of the [`ControlRxLane`] generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ControlRxLaneParams {
+    /// The role of the lane.
+    pub role: LaneRole,
+    /// The sampler parameters.
+    pub sampler: StrongArmParams,
+    /// The parameters of the sampler output buffers.
+    pub buf: InverterParams,
+    /// The AC-coupling network on `din`, if any.
+    ///
+    /// Use when the link common mode is not compatible with the sampler input.
+    #[serde(default)]
+    pub ac_coupling: Option<AcCouplingParams>,
+    /// The ESD network on `din`, if any.
+    #[serde(default)]
+    pub esd: Option<RxEsdParams>,
+}
+
+/// The parameters of a [`MomCap`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct MomCapParams {
+    /// The number of fingers, alternating between the two terminals.
+    pub fingers: i64,
+    /// The length of each finger in nanometers.
+    pub finger_length: i64,
+}
+
+impl MomCapParams {
+    /// The capacitance between adjacent fingers in technology `T`, in farads.
+    pub fn capacitance<T: LoopFilterRules>(&self) -> f64 {
+        T::MOM_CAP_PER_LENGTH * ((self.fingers - 1) * self.finger_length) as f64
+    }
+}
+
+/// The parameters of an AC-coupling network.
+///
+/// A series [`MomCap`] blocks the DC level of the bump, and a bias resistor
+/// re-establishes the common mode at the reference voltage.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct AcCouplingParams {
+    /// The series capacitor.
+    pub cap: MomCapParams,
+    /// The number of legs in the bias resistor.
+    pub bias_res_legs: i64,
+    /// The width of each bias resistor leg.
+    pub bias_res_w: i64,
+    /// The length of each bias resistor leg.
+    pub bias_res_l: i64,
+    /// Whether to shield the high-impedance node between the capacitor and the sampler
+    /// with VSS tracks on the driver pin layer.
+    #[serde(default)]
+    pub shield: bool,
+}
+
+/// A lateral metal-oxide-metal capacitor.
+///
+/// Draws two interdigitated combs on the driver pin layer. The schematic contains the
+/// model given by [`LaneImpl::mom_cap_model`], which only accounts for the capacitance
+/// between adjacent fingers; extracted netlists also include the fringing to the
+/// buses and to nearby wires.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct MomCap<T>(
+    MomCapParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> MomCap<T> {
+    /// Creates a new [`MomCap`].
+    pub fn new(params: MomCapParams) -> Self {
+        Self(params, PhantomData)
+    }
+}
+
+impl<T: Any> Block for MomCap<T> {
+    type Io = CapacitorIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("mom_cap")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("mom_cap", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for MomCap<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for MomCap<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: LaneImpl<PDK> + Any> Tile<PDK> for MomCap<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let MomCapParams {
+            fingers,
+            finger_length,
+        } = self.0;
+        assert!(fingers >= 2, "a MOM capacitor needs at least two fingers");
+
+        let pin = <T as HorizontalDriverImpl<PDK>>::LAYER_MAP.pin;
+        let layer = cell.layer_stack.layers[pin].clone();
+        let dir = cell.layer_stack.layer(pin).dir().track_dir();
+        let tracks = layer.inner.tracks();
+        let width = tracks.get(0).length();
+        let space = layer.pitch() - width;
+
+        // Fingers on `p` reach down to the `p` bus and fingers on `n` reach up to the
+        // `n` bus, leaving one space between each finger and the opposite bus.
+        let p_bus = Span::new(-(space + width), -space);
+        let n_bus = Span::new(finger_length + space, finger_length + space + width);
+        let across = Span::new(tracks.get(0).start(), tracks.get(fingers - 1).stop());
+        let combs = cell.generate_primitive(MomCapCombs::<T>::new(
+            self.0,
+            (0..fingers)
+                .map(|i| {
+                    let along = if i % 2 == 0 {
+                        Span::new(p_bus.start(), finger_length)
+                    } else {
+                        Span::new(0, n_bus.stop())
+                    };
+                    Rect::from_dir_spans(dir, along, tracks.get(i))
+                })
+                .collect(),
+            Rect::from_dir_spans(dir, p_bus, across),
+            Rect::from_dir_spans(dir, n_bus, across),
+        ));
+        cell.connect(combs.io().p, io.schematic.p);
+        cell.connect(combs.io().n, io.schematic.n);
+        let combs = cell.draw(combs)?;
+        io.layout.p.merge(combs.layout.io().p);
+        io.layout.n.merge(combs.layout.io().n);
+
+        let slice = cell.layer_stack.slice(0..pin + 1);
+        let bbox = Rect::from_dir_spans(dir, Span::new(p_bus.start(), n_bus.stop()), across);
+        let outline = slice.lcm_to_physical_rect(slice.expand_to_lcm_units(bbox));
+        let virtual_layers = cell.layout.ctx.install_layers::<atoll::VirtualLayers>();
+        cell.layout
+            .draw(Shape::new(virtual_layers.outline.id(), outline))?;
+
+        cell.set_top_layer(pin);
+        cell.set_router(crate::route::router(
+            cell.ctx(),
+            <T as HorizontalDriverImpl<PDK>>::ROUTER,
+        ));
+        cell.set_via_maker(<T as HorizontalDriverImpl<PDK>>::via_maker());
+
+        Ok(((), ()))
+    }
+}
+
+/// The combs of a [`MomCap`], drawn as a single primitive so that they carry the
+/// schematic model of the capacitor.
+#[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+struct MomCapCombs<T> {
+    params: MomCapParams,
+    fingers: Vec<Rect>,
+    p_bus: Rect,
+    n_bus: Rect,
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> MomCapCombs<T> {
+    fn new(params: MomCapParams, fingers: Vec<Rect>, p_bus: Rect, n_bus: Rect) -> Self {
+        Self {
+            params,
+            fingers,
+            p_bus,
+            n_bus,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Any> Block for MomCapCombs<T> {
+    type Io = CapacitorIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("mom_cap_combs")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("mom_cap_combs", &self.params)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for MomCapCombs<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for MomCapCombs<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema, T: LaneImpl<PDK> + Any> Schematic<PDK> for MomCapCombs<T> {
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut SchematicCellBuilder<PDK>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        cell.instantiate_connected(
+            T::mom_cap_model(self.params),
+            TwoTerminalIoSchematic { p: io.p, n: io.n },
+        );
+        Ok(())
+    }
+}
+
+impl<PDK: Pdk + Schema, T: LaneImpl<PDK> + Any> Layout<PDK> for MomCapCombs<T> {
+    fn layout(
+        &self,
+        io: &mut <<Self as Block>::Io as LayoutType>::Builder,
+        cell: &mut LayoutCellBuilder<PDK>,
+    ) -> substrate::error::Result<Self::LayoutData> {
+        let pin = <T as HorizontalDriverImpl<PDK>>::pin(&cell.ctx.layers);
+        for &finger in &self.fingers {
+            cell.draw(Shape::new(pin.drawing(), finger))?;
+        }
+        for (bus, pins) in [(self.p_bus, &mut io.p), (self.n_bus, &mut io.n)] {
+            cell.draw(Shape::new(pin.drawing(), bus))?;
+            pins.push(IoShape::with_layers(
+                <T as HorizontalDriverImpl<PDK>>::pin(&cell.ctx.layers),
+                bus,
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A valid or track receive lane.
+///
+/// The lane samples the bump against a reference voltage without any equalization,
+/// optionally through an AC-coupling capacitor biased at the reference voltage.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct ControlRxLane<T>(
+    ControlRxLaneParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> ControlRxLane<T> {
+    /// Creates a new [`ControlRxLane`].
+    pub fn new(params: ControlRxLaneParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The lane parameters.
+    pub fn params(&self) -> ControlRxLaneParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for ControlRxLane<T> {
+    type Io = RxLaneIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("control_rx_lane")
+    }
+
+    fn name(&self) -> ArcStr {
+        let base = match self.0.role {
+            LaneRole::Valid => "valid_rx_lane",
+            LaneRole::Track => "track_rx_lane",
+        };
+        crate::block_name(base, &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for ControlRxLane<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for ControlRxLane<T> {
+    type LayoutData = ();
+}
+
+// The sampler and coupling network carry their own taps and stay inside the outline.
+impl<T> Abutment for ControlRxLane<T> {
+    fn edge(&self, _edge: Edge) -> EdgeKind {
+        EdgeKind::Clear
+    }
+}
+
+impl<PDK: Pdk + Schema + Sized, T: LaneImpl<PDK> + Any> Tile<PDK> for ControlRxLane<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let input = cell.signal("input", DiffPair::default());
+        let output = cell.signal("output", DiffPair::default());
+        cell.connect(input.n, io.schematic.vref);
+        cell.connect(output.p, io.schematic.dout);
+        let sampler = cell.generate_connected(
+            StrongArmWithOutputBuffers::<T>::new(self.0.sampler, self.0.buf),
+            ClockedDiffComparatorIoSchematic {
+                input,
+                output,
+                clock: io.schematic.clk,
+                vdd: io.schematic.vdd,
+                vss: io.schematic.vss,
+            },
+        );
+        let mut top_layer = 2;
+        // The leftmost cell on `din`, beside which the ESD network is placed.
+        let mut input_bounds = sampler.lcm_bounds();
+        // The right edge and `n` pin span of the coupling capacitor, if shielded.
+        let mut shield_from = None;
+        if let Some(ac) = self.0.ac_coupling {
+            // There is no termination or CTLE yet, so the coupling network sits
+            // directly between the bump and the sampler.
+            let cap = cell
+                .generate_connected(
+                    MomCap::<T>::new(ac.cap),
+                    CapacitorIoSchematic {
+                        p: io.schematic.din,
+                        n: input.p,
+                    },
+                )
+                .align(&sampler, AlignMode::CenterVertical, 0)
+                .align(&sampler, AlignMode::ToTheLeft, -T::INPUT_BUFFER_SPACING);
+            let bias = cell
+                .generate_connected(
+                    <T as HorizontalDriverImpl<PDK>>::resistor(
+                        ac.bias_res_legs,
+                        ac.bias_res_w,
+                        ac.bias_res_l,
+                        ResistorConn::Series,
+                    ),
+                    ResistorIoSchematic {
+                        p: input.p,
+                        n: io.schematic.vref,
+                        b: io.schematic.vdd,
+                    },
+                )
+                .align(&cap, AlignMode::CenterHorizontal, 0)
+                .align(&cap, AlignMode::Beneath, -T::INPUT_BUFFER_SPACING);
+            input_bounds = cap.lcm_bounds();
+            let cap = cell.draw(cap)?;
+            cell.draw(bias)?;
+            if ac.shield {
+                shield_from = Some((
+                    cap.layout.bbox_rect().right(),
+                    cap.layout.io().n.bbox_rect().vspan(),
+                ));
+            }
+            io.layout.din.merge(cap.layout.io().p);
+            top_layer = top_layer.max(<T as HorizontalDriverImpl<PDK>>::LAYER_MAP.pin);
+        } else {
+            cell.connect(input.p, io.schematic.din);
+        }
+        if let Some(esd) = self.0.esd {
+            let esd = cell
+                .generate_connected(
+                    RxEsd::<T>::new(esd),
+                    RxEsdIoSchematic {
+                        pad: io.schematic.din,
+                        vdd: io.schematic.vdd,
+                        vss: io.schematic.vss,
+                    },
+                )
+                .align_rect(input_bounds, AlignMode::CenterVertical, 0)
+                .align_rect(input_bounds, AlignMode::ToTheLeft, -T::INPUT_BUFFER_SPACING);
+            let esd = cell.draw(esd)?;
+            io.layout.din.merge(esd.layout.io().pad);
+        }
+        let sampler = cell.draw(sampler)?;
+        if let Some((left, vspan)) = shield_from {
+            let pin = <T as HorizontalDriverImpl<PDK>>::LAYER_MAP.pin;
+            let corridor =
+                Rect::from_spans(Span::new(left, sampler.layout.bbox_rect().left()), vspan);
+            cell.shield_net(input.p, io.schematic.vss, corridor, pin..pin + 1)?;
+        }
+
+        cell.set_top_layer(top_layer);
+        cell.set_router(crate::route::router(
+            cell.ctx(),
+            <T as StrongArmImpl<PDK>>::ROUTER,
+        ));
+        cell.set_via_maker(<T as StrongArmImpl<PDK>>::via_maker());
+
+        if self.0.ac_coupling.is_none() {
+            io.layout.din.merge(sampler.layout.io().input.p);
+        }
+        io.layout.vref.merge(sampler.layout.io().input.n);
+        io.layout.clk.merge(sampler.layout.io().clock);
+        io.layout.dout.merge(sampler.layout.io().output.p);
+        io.layout.vdd.merge(sampler.layout.io().vdd);
+        io.layout.vss.merge(sampler.layout.io().vss);
+
+        Ok(((), ()))
+    }
+}
+
+// The driver banks are enclosed in guard rings and fillers, as in the lanes built on them.
+impl<T> Abutment for HorizontalDriver<T> {
+    fn edge(&self, _edge: Edge) -> EdgeKind {
+        EdgeKind::Clear
+    }
+}
+
+// The sampler carries its own taps and stays inside its outline.
+impl<T> Abutment for StrongArmWithOutputBuffers<T> {
+    fn edge(&self, _edge: Edge) -> EdgeKind {
+        EdgeKind::Clear
+    }
+}
+
+/// The parameters of the [`TxModule`] generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct TxModuleParams {
+    /// The number of data lanes.
+    pub data_lanes: usize,
+    /// The driver parameters of each data lane.
+    pub driver: DriverParams,
+    /// The forwarded clock lane parameters.
+    pub clock: ClockLaneParams,
+    /// The valid lane parameters.
+    pub valid: ControlTxLaneParams,
+    /// The track lane parameters.
+    pub track: ControlTxLaneParams,
+}
+
+/// The interface to a [`TxModule`].
+#[derive(Debug, Clone, Io)]
+pub struct TxModuleIo {
+    /// The data transmitted on each data lane.
+    pub din: Array<Input<Signal>>,
+    /// The output of each data lane, connected to its bump.
+    pub dout: Array<Output<Signal>>,
+    /// The pull-up control shared by every data lane driver.
+    pub pu_ctl: Array<Input<Signal>>,
+    /// The pull-down control (inverted) shared by every data lane driver.
+    pub pd_ctlb: Array<Input<Signal>>,
+    /// The pull-up control of each spare segment, shared by every data lane driver.
+    pub spare_pu_ctl: Array<Input<Signal>>,
+    /// The pull-down control (inverted) of each spare segment, shared by every
+    /// data lane driver.
+    pub spare_pd_ctlb: Array<Input<Signal>>,
+    /// The clock forwarded on the clock lane.
+    pub clk_din: Input<Signal>,
+    /// The clock lane output, connected to its bump.
+    pub clk_dout: Output<Signal>,
+    /// The pattern transmitted on the valid lane.
+    pub valid_din: Input<Signal>,
+    /// The valid lane output, connected to its bump.
+    pub valid_dout: Output<Signal>,
+    /// The pattern transmitted on the track lane.
+    pub track_din: Input<Signal>,
+    /// The track lane output, connected to its bump.
+    pub track_dout: Output<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The transmit half of a module.
+///
+/// The data lanes are stacked bottom to top, followed by the clock, valid, and track
+/// lanes, with every lane abutting the next. The data lane drivers share one
+/// impedance control code.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct TxModule<T>(
+    TxModuleParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> TxModule<T> {
+    /// Creates a new [`TxModule`].
+    pub fn new(params: TxModuleParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The module parameters.
+    pub fn params(&self) -> TxModuleParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for TxModule<T> {
+    type Io = TxModuleIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("tx_module")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("tx_module", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        let driver = self.0.driver;
+        let segments = driver.num_segments * driver.banks;
+        let spares = driver.spares_per_bank() * driver.banks;
+        TxModuleIo {
+            din: Array::new(self.0.data_lanes, Default::default()),
+            dout: Array::new(self.0.data_lanes, Default::default()),
+            pu_ctl: Array::new(segments, Default::default()),
+            pd_ctlb: Array::new(segments, Default::default()),
+            spare_pu_ctl: Array::new(spares, Default::default()),
+            spare_pd_ctlb: Array::new(spares, Default::default()),
+            clk_din: Default::default(),
+            clk_dout: Default::default(),
+            valid_din: Default::default(),
+            valid_dout: Default::default(),
+            track_din: Default::default(),
+            track_dout: Default::default(),
+            vdd: Default::default(),
+            vss: Default::default(),
+        }
+    }
+}
+
+impl<T: Any> ExportsNestedData for TxModule<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for TxModule<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: LaneImpl<PDK> + Any> Tile<PDK> for TxModule<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let driver = self.0.driver;
+        let segments = driver.num_segments * driver.banks;
+        let spares = driver.spares_per_bank() * driver.banks;
+        let data = vec![HorizontalDriver::<T>::new(driver); self.0.data_lanes];
+        let clock = ClockLane::<T>::new(self.0.clock);
+        let valid = ControlTxLane::<T>::new(self.0.valid);
+        let track = ControlTxLane::<T>::new(self.0.track);
+        check_abutment(
+            data.iter().map(|lane| lane as &dyn Abutment).chain([
+                &clock as &dyn Abutment,
+                &valid,
+                &track,
+            ]),
+            Dir::Vert,
+        )
+        .expect("transmit lanes have clear edges");
+
+        let data = tile_lanes(cell, &data, Dir::Vert).expect("data lanes have clear edges");
+        let mut prev = data.last().map(|lane| lane.lcm_bounds());
+        let mut clock = cell.generate(clock);
+        abut_next(&mut clock, &mut prev);
+        let mut valid = cell.generate(valid);
+        abut_next(&mut valid, &mut prev);
+        let mut track = cell.generate(track);
+        abut_next(&mut track, &mut prev);
+
+        for (i, lane) in data.into_iter().enumerate() {
+            let lane = cell.draw(lane)?;
+            cell.connect(lane.schematic.io().din, io.schematic.din[i]);
+            cell.connect(lane.schematic.io().dout, io.schematic.dout[i]);
+            cell.connect(lane.schematic.io().vdd, io.schematic.vdd);
+            cell.connect(lane.schematic.io().vss, io.schematic.vss);
+            for j in 0..segments {
+                cell.connect(lane.schematic.io().pu_ctl[j], io.schematic.pu_ctl[j]);
+                cell.connect(lane.schematic.io().pd_ctlb[j], io.schematic.pd_ctlb[j]);
+                io.layout.pu_ctl[j].merge(lane.layout.io().pu_ctl[j].clone());
+                io.layout.pd_ctlb[j].merge(lane.layout.io().pd_ctlb[j].clone());
+            }
+            for j in 0..spares {
+                cell.connect(
+                    lane.schematic.io().spare_pu_ctl[j],
+                    io.schematic.spare_pu_ctl[j],
+                );
+                cell.connect(
+                    lane.schematic.io().spare_pd_ctlb[j],
+                    io.schematic.spare_pd_ctlb[j],
+                );
+                io.layout.spare_pu_ctl[j].merge(lane.layout.io().spare_pu_ctl[j].clone());
+                io.layout.spare_pd_ctlb[j].merge(lane.layout.io().spare_pd_ctlb[j].clone());
+            }
+            io.layout.din[i].merge(lane.layout.io().din);
+            io.layout.dout[i].merge(lane.layout.io().dout);
+            io.layout.vdd.merge(lane.layout.io().vdd);
+            io.layout.vss.merge(lane.layout.io().vss);
+        }
+
+        let clock = cell.draw(clock)?;
+        let valid = cell.draw(valid)?;
+        let track = cell.draw(track)?;
+        cell.connect(clock.schematic.io().din, io.schematic.clk_din);
+        cell.connect(clock.schematic.io().dout, io.schematic.clk_dout);
+        cell.connect(clock.schematic.io().vdd, io.schematic.vdd);
+        cell.connect(clock.schematic.io().vss, io.schematic.vss);
+        for (lane, din, dout) in [
+            (&valid, io.schematic.valid_din, io.schematic.valid_dout),
+            (&track, io.schematic.track_din, io.schematic.track_dout),
+        ] {
+            cell.connect(lane.schematic.io().din, din);
+            cell.connect(lane.schematic.io().dout, dout);
+            cell.connect(lane.schematic.io().vdd, io.schematic.vdd);
+            cell.connect(lane.schematic.io().vss, io.schematic.vss);
+        }
+        io.layout.clk_din.merge(clock.layout.io().din);
+        io.layout.clk_dout.merge(clock.layout.io().dout);
+        io.layout.valid_din.merge(valid.layout.io().din);
+        io.layout.valid_dout.merge(valid.layout.io().dout);
+        io.layout.track_din.merge(track.layout.io().din);
+        io.layout.track_dout.merge(track.layout.io().dout);
+        for rails in [clock.layout.io(), valid.layout.io(), track.layout.io()] {
+            io.layout.vdd.merge(rails.vdd);
+            io.layout.vss.merge(rails.vss);
+        }
+
+        cell.set_top_layer(<T as HorizontalDriverImpl<PDK>>::LAYER_MAP.top);
+        cell.set_router(crate::route::router(
+            cell.ctx(),
+            <T as HorizontalDriverImpl<PDK>>::ROUTER,
+        ));
+        cell.set_via_maker(<T as HorizontalDriverImpl<PDK>>::via_maker());
+
+        Ok(((), ()))
+    }
+}
+
+/// The parameters of the [`RxModule`] generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct RxModuleParams {
+    /// The number of data lanes.
+    pub data_lanes: usize,
+    /// The sampler parameters of each data lane.
+    pub sampler: StrongArmParams,
+    /// The parameters of the data lane sampler output buffers.
+    pub buf: InverterParams,
+    /// The valid lane parameters.
+    pub valid: ControlRxLaneParams,
+    /// The track lane parameters.
+    pub track: ControlRxLaneParams,
+}
+
+/// The interface to an [`RxModule`].
+#[derive(Debug, Clone, Io)]
+pub struct RxModuleIo {
+    /// The signal received on each data lane, connected to its bump.
+    pub din: Array<Input<Signal>>,
+    /// The reference voltage shared by every lane.
+    pub vref: Input<Signal>,
+    /// The sampling clock shared by every lane.
+    pub clk: Input<Signal>,
+    /// The data sampled on each data lane.
+    pub dout: Array<Output<Signal>>,
+    /// The valid lane input, connected to its bump.
+    pub valid_din: Input<Signal>,
+    /// The sampled valid pattern.
+    pub valid_dout: Output<Signal>,
+    /// The track lane input, connected to its bump.
+    pub track_din: Input<Signal>,
+    /// The sampled track pattern.
+    pub track_dout: Output<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The receive half of a module.
+///
+/// Each data lane samples its bump against `vref`, like the valid and track lanes
+/// but without any coupling or ESD network. The data lanes are stacked bottom to top,
+/// followed by the valid and track lanes, with every lane abutting the next.
+// There is no forwarded clock receiver yet, so the sampling clock is an input.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct RxModule<T>(
+    RxModuleParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> RxModule<T> {
+    /// Creates a new [`RxModule`].
+    pub fn new(params: RxModuleParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The module parameters.
+    pub fn params(&self) -> RxModuleParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for RxModule<T> {
+    type Io = RxModuleIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("rx_module")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("rx_module", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        RxModuleIo {
+            din: Array::new(self.0.data_lanes, Default::default()),
+            vref: Default::default(),
+            clk: Default::default(),
+            dout: Array::new(self.0.data_lanes, Default::default()),
+            valid_din: Default::default(),
+            valid_dout: Default::default(),
+            track_din: Default::default(),
+            track_dout: Default::default(),
+            vdd: Default::default(),
+            vss: Default::default(),
+        }
+    }
+}
+
+impl<T: Any> ExportsNestedData for RxModule<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for RxModule<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: LaneImpl<PDK> + Any> Tile<PDK> for RxModule<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let data = vec![
+            StrongArmWithOutputBuffers::<T>::new(self.0.sampler, self.0.buf);
+            self.0.data_lanes
+        ];
+        let valid = ControlRxLane::<T>::new(self.0.valid);
+        let track = ControlRxLane::<T>::new(self.0.track);
+        check_abutment(
+            data.iter()
+                .map(|lane| lane as &dyn Abutment)
+                .chain([&valid as &dyn Abutment, &track]),
+            Dir::Vert,
+        )
+        .expect("receive lanes have clear edges");
+
+        let data = tile_lanes(cell, &data, Dir::Vert).expect("data lanes have clear edges");
+        let mut prev = data.last().map(|lane| lane.lcm_bounds());
+        let mut valid = cell.generate(valid);
+        abut_next(&mut valid, &mut prev);
+        let mut track = cell.generate(track);
+        abut_next(&mut track, &mut prev);
+
+        for (i, lane) in data.into_iter().enumerate() {
+            let lane = cell.draw(lane)?;
+            cell.connect(lane.schematic.io().input.p, io.schematic.din[i]);
+            cell.connect(lane.schematic.io().input.n, io.schematic.vref);
+            cell.connect(lane.schematic.io().clock, io.schematic.clk);
+            cell.connect(lane.schematic.io().output.p, io.schematic.dout[i]);
+            cell.connect(lane.schematic.io().vdd, io.schematic.vdd);
+            cell.connect(lane.schematic.io().vss, io.schematic.vss);
+            io.layout.din[i].merge(lane.layout.io().input.p);
+            io.layout.vref.merge(lane.layout.io().input.n);
+            io.layout.clk.merge(lane.layout.io().clock);
+            io.layout.dout[i].merge(lane.layout.io().output.p);
+            io.layout.vdd.merge(lane.layout.io().vdd);
+            io.layout.vss.merge(lane.layout.io().vss);
+        }
+
+        let valid = cell.draw(valid)?;
+        let track = cell.draw(track)?;
+        for (lane, din, dout) in [
+            (&valid, io.schematic.valid_din, io.schematic.valid_dout),
+            (&track, io.schematic.track_din, io.schematic.track_dout),
+        ] {
+            cell.connect(lane.schematic.io().din, din);
+            cell.connect(lane.schematic.io().dout, dout);
+            cell.connect(lane.schematic.io().vref, io.schematic.vref);
+            cell.connect(lane.schematic.io().clk, io.schematic.clk);
+            cell.connect(lane.schematic.io().vdd, io.schematic.vdd);
+            cell.connect(lane.schematic.io().vss, io.schematic.vss);
+            io.layout.vref.merge(lane.layout.io().vref);
+            io.layout.clk.merge(lane.layout.io().clk);
+            io.layout.vdd.merge(lane.layout.io().vdd);
+            io.layout.vss.merge(lane.layout.io().vss);
+        }
+        io.layout.valid_din.merge(valid.layout.io().din);
+        io.layout.valid_dout.merge(valid.layout.io().dout);
+        io.layout.track_din.merge(track.layout.io().din);
+        io.layout.track_dout.merge(track.layout.io().dout);
+
+        // Coupling capacitors are drawn on the driver pin layer.
+        let coupled = self.0.valid.ac_coupling.is_some() || self.0.track.ac_coupling.is_some();
+        cell.set_top_layer(if coupled {
+            2.max(<T as HorizontalDriverImpl<PDK>>::LAYER_MAP.pin)
+        } else {
+            2
+        });
+        cell.set_router(crate::route::router(
+            cell.ctx(),
+            <T as StrongArmImpl<PDK>>::ROUTER,
+        ));
+        cell.set_via_maker(<T as StrongArmImpl<PDK>>::via_maker());
+
+        Ok(((), ()))
+    }
+}
+
+/// Abuts `inst` above the previously placed lane, if any, and records its bounds
+/// as those of the previous lane.
+fn abut_next<B: ExportsNestedData + ExportsLayoutData>(
+    inst: &mut Instance<B>,
+    prev: &mut Option<Rect>,
+) {
+    if let Some(bounds) = *prev {
+        abut(inst, bounds, Dir::Vert);
+    }
+    *prev = Some(inst.lcm_bounds());
+}
+
+/// Draws a buffer driving a [`HorizontalDriver`] whose first `enabled_segments`
+/// segments are permanently enabled and whose remaining segments and spares are disabled.
+///
+/// The control inputs are tied off according to the driver's control polarities.
+fn tile_fixed_code_tx<'a, PDK, T, B>(
+    io: IoBuilder<'a, B>,
+    cell: &mut TileBuilder<'a, PDK>,
+    driver: DriverParams,
+    enabled_segments: usize,
+    buf_params: InverterParams,
+) -> substrate::error::Result<()>
+where
+    PDK: Pdk + Schema + Sized,
+    T: LaneImpl<PDK> + Any,
+    B: Block<Io = TxLaneIo>,
+{
+    let segments = driver.num_segments * driver.banks;
+    assert!(
+        enabled_segments <= segments,
+        "cannot enable {enabled_segments} of {segments} driver segments"
+    );
+    let levels = fixed_code_levels(&driver, enabled_segments);
+
+    let din_buf = cell.signal("din_buf", Signal::new());
+    let driver = cell.generate(HorizontalDriver::<T>::new(driver));
+    let buf = cell
+        .generate_connected(
+            Buffer::<T>::new(buf_params),
+            BufferIoSchematic {
+                din: io.schematic.din,
+                dout: din_buf,
+                vdd: io.schematic.vdd,
+                vss: io.schematic.vss,
+            },
+        )
+        .align(&driver, AlignMode::CenterVertical, 0)
+        .align(&driver, AlignMode::ToTheLeft, -T::INPUT_BUFFER_SPACING);
+
+    let driver = cell.draw(driver)?;
+    let buf = cell.draw(buf)?;
+
+    cell.connect(driver.schematic.io().din, din_buf);
+    cell.connect(driver.schematic.io().dout, io.schematic.dout);
+    cell.connect(driver.schematic.io().vdd, io.schematic.vdd);
+    cell.connect(driver.schematic.io().vss, io.schematic.vss);
+    let rail = |high| {
+        if high {
+            io.schematic.vdd
+        } else {
+            io.schematic.vss
+        }
+    };
+    for (i, &(pu_ctl, pd_ctlb)) in levels.iter().enumerate() {
+        let (pu_ctl, pd_ctlb) = (rail(pu_ctl), rail(pd_ctlb));
+        match i.checked_sub(segments) {
+            None => {
+                cell.connect(driver.schematic.io().pu_ctl[i], pu_ctl);
+                cell.connect(driver.schematic.io().pd_ctlb[i], pd_ctlb);
+            }
+            Some(j) => {
+                cell.connect(driver.schematic.io().spare_pu_ctl[j], pu_ctl);
+                cell.connect(driver.schematic.io().spare_pd_ctlb[j], pd_ctlb);
+            }
+        }
+    }
+
+    cell.set_top_layer(<T as HorizontalDriverImpl<PDK>>::LAYER_MAP.top);
+    cell.set_router(crate::route::router(
+        cell.ctx(),
+        <T as HorizontalDriverImpl<PDK>>::ROUTER,
+    ));
+    cell.set_via_maker(<T as HorizontalDriverImpl<PDK>>::via_maker());
+
+    io.layout.din.merge(buf.layout.io().din);
+    io.layout.dout.merge(driver.layout.io().dout);
+    io.layout.vdd.merge(driver.layout.io().vdd);
+    io.layout.vss.merge(driver.layout.io().vss);
+
+    Ok(())
+}
+
+/// The levels of `pu_ctl` and `pd_ctlb` at which each segment of a fixed-code `driver`
+/// is tied, followed by those of each spare segment.
+///
+/// The first `enabled_segments` segments are enabled, and the remaining segments and
+/// the spares are disabled, according to the control polarities of the driver unit.
+fn fixed_code_levels(driver: &DriverParams, enabled_segments: usize) -> Vec<(bool, bool)> {
+    let segments = driver.num_segments * driver.banks;
+    let spares = driver.spares_per_bank() * driver.banks;
+    (0..segments + spares)
+        .map(|i| driver.unit.ctl_levels(i < enabled_segments.min(segments)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::driver::{CtlPolarity, DriverUnitParams, SegmentPlacement};
+
+    use super::*;
+
+    fn driver(spare_segment: bool) -> DriverParams {
+        DriverParams {
+            unit: DriverUnitParams::builder().build().unwrap(),
+            num_segments: 4,
+            banks: 2,
+            supply_budget: None,
+            spare_segment,
+            esd: None,
+            placement: SegmentPlacement::Sequential,
+        }
+    }
+
+    #[test]
+    fn fixed_code_enables_leading_segments() {
+        // Active-high pull-up and active-low pull-down controls.
+        let levels = fixed_code_levels(&driver(false), 3);
+        assert_eq!(levels.len(), 8);
+        assert!(levels[..3].iter().all(|&l| l == (true, false)));
+        assert!(levels[3..].iter().all(|&l| l == (false, true)));
+    }
+
+    #[test]
+    fn fixed_code_follows_control_polarity() {
+        let mut driver = driver(false);
+        driver.unit.pu_ctl_polarity = CtlPolarity::ActiveLow;
+        driver.unit.pd_ctl_polarity = CtlPolarity::ActiveHigh;
+        let levels = fixed_code_levels(&driver, 5);
+        assert!(levels[..5].iter().all(|&l| l == (false, true)));
+        assert!(levels[5..].iter().all(|&l| l == (true, false)));
+    }
+
+    #[test]
+    fn fixed_code_disables_spares() {
+        // Requesting more segments than exist never enables a spare.
+        let levels = fixed_code_levels(&driver(true), 100);
+        assert_eq!(levels.len(), 10);
+        assert!(levels[..8].iter().all(|&l| l == (true, false)));
+        assert!(levels[8..].iter().all(|&l| l == (false, true)));
+    }
+}
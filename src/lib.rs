@@ -3,23 +3,387 @@
 //! physical layer implementation.
 #![warn(missing_docs)]
 
+use ngspice::Ngspice;
+use serde::{Deserialize, Serialize};
 use sky130pdk::Sky130Pdk;
 use spectre::Spectre;
-use substrate::context::{Context, PdkContext};
+use std::path::{Path, PathBuf};
+use substrate::arcstr::ArcStr;
+use substrate::context::{Context, Installation, PdkContext};
 
+pub mod abutment;
+pub mod adc;
+pub mod analysis;
+pub mod bias;
 pub mod buffer;
+pub mod cache;
+pub mod code;
+pub mod config;
 pub mod driver;
+pub mod esd;
+pub mod export;
+pub mod frontend;
+pub mod gates;
+pub mod lane;
+pub mod loadbank;
+pub mod params;
+pub mod pll;
+pub mod plot;
+pub mod power;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod regression;
+pub mod report;
+pub mod route;
+pub mod rx;
+pub mod scan;
+pub mod sideband;
+pub mod spec;
 pub mod strongarm;
+pub mod sweep;
+pub mod tb;
 pub mod tech;
+pub mod testsuite;
 pub mod tiles;
+pub mod trim;
+pub mod verification;
+pub mod via;
+pub mod wells;
 
-/// Returns a configured SKY130 context.
-pub fn sky130_ctx() -> PdkContext<Sky130Pdk> {
-    let pdk_root = std::env::var("SKY130_COMMERCIAL_PDK_ROOT")
-        .expect("the SKY130_COMMERCIAL_PDK_ROOT environment variable must be set");
-    Context::builder()
+/// An error produced while configuring a context.
+#[derive(Debug, thiserror::Error)]
+pub enum ContextError {
+    /// A required environment variable is not set.
+    #[error("the {0} environment variable must be set")]
+    MissingEnvVar(&'static str),
+    /// The PDK root directory does not exist.
+    #[error("PDK root {0:?} does not exist")]
+    MissingPdkRoot(PathBuf),
+}
+
+fn pdk_root_from_env(var: &'static str) -> Result<PathBuf, ContextError> {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .ok_or(ContextError::MissingEnvVar(var))
+}
+
+fn check_pdk_root(pdk_root: &Path) -> Result<(), ContextError> {
+    if pdk_root.is_dir() {
+        Ok(())
+    } else {
+        Err(ContextError::MissingPdkRoot(pdk_root.to_path_buf()))
+    }
+}
+
+/// Returns a configured GF180MCU context, or an error if `GF180_PDK_ROOT` is not set
+/// to an existing directory.
+#[cfg(feature = "gf180")]
+pub fn try_gf180_ctx() -> Result<PdkContext<gf180pdk::Gf180Pdk>, ContextError> {
+    let pdk_root = pdk_root_from_env("GF180_PDK_ROOT")?;
+    check_pdk_root(&pdk_root)?;
+    Ok(Context::builder()
         .install(Spectre::default())
-        .install(Sky130Pdk::commercial(pdk_root))
+        .install(tech::gf180::Gf180Models {
+            pdk_root: pdk_root.clone(),
+        })
+        .install(gf180pdk::Gf180Pdk::new(pdk_root))
+        .install(PdkFlavor("gf180mcu"))
         .build()
-        .with_pdk()
+        .with_pdk())
+}
+
+/// Returns a configured GF180MCU context.
+///
+/// # Panics
+///
+/// Panics if `GF180_PDK_ROOT` is not set to an existing directory.
+/// See [`try_gf180_ctx`] for a fallible version.
+#[cfg(feature = "gf180")]
+pub fn gf180_ctx() -> PdkContext<gf180pdk::Gf180Pdk> {
+    try_gf180_ctx().unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// The PDK and simulator installed by a [`Sky130CtxBuilder`].
+enum Sky130Flavor {
+    Commercial(Spectre),
+    Open(Ngspice),
+}
+
+/// A builder for SKY130 contexts with an explicit PDK root.
+pub struct Sky130CtxBuilder {
+    pdk_root: PathBuf,
+    flavor: Sky130Flavor,
+    generation: GenerationOptions,
+}
+
+impl Sky130CtxBuilder {
+    /// Creates a builder for a context using the commercial SKY130 PDK at `pdk_root`,
+    /// simulated with a default-configured Spectre.
+    pub fn commercial(pdk_root: impl Into<PathBuf>) -> Self {
+        Self {
+            pdk_root: pdk_root.into(),
+            flavor: Sky130Flavor::Commercial(Spectre::default()),
+            generation: GenerationOptions::default(),
+        }
+    }
+
+    /// Creates a builder for a context using the open-source SKY130A PDK at `pdk_root`,
+    /// simulated with a default-configured ngspice.
+    pub fn open(pdk_root: impl Into<PathBuf>) -> Self {
+        Self {
+            pdk_root: pdk_root.into(),
+            flavor: Sky130Flavor::Open(Ngspice::default()),
+            generation: GenerationOptions::default(),
+        }
+    }
+
+    /// Uses the commercial PDK simulated with `spectre`.
+    pub fn spectre(mut self, spectre: Spectre) -> Self {
+        self.flavor = Sky130Flavor::Commercial(spectre);
+        self
+    }
+
+    /// Uses the open-source PDK simulated with `ngspice`.
+    pub fn ngspice(mut self, ngspice: Ngspice) -> Self {
+        self.flavor = Sky130Flavor::Open(ngspice);
+        self
+    }
+
+    /// Generates every cell in the context with `options`.
+    pub fn generation_options(mut self, options: GenerationOptions) -> Self {
+        self.generation = options;
+        self
+    }
+
+    /// Builds the context, or returns an error if the PDK root does not exist.
+    pub fn build(self) -> Result<PdkContext<Sky130Pdk>, ContextError> {
+        check_pdk_root(&self.pdk_root)?;
+        Ok(match self.flavor {
+            Sky130Flavor::Commercial(spectre) => Context::builder()
+                .install(spectre)
+                .install(Sky130Pdk::commercial(self.pdk_root))
+                .install(PdkFlavor("sky130-commercial"))
+                .install(self.generation)
+                .build(),
+            Sky130Flavor::Open(ngspice) => Context::builder()
+                .install(ngspice)
+                .install(Sky130Pdk::open(self.pdk_root))
+                .install(PdkFlavor("sky130-open"))
+                .install(self.generation)
+                .build(),
+        }
+        .with_pdk())
+    }
+}
+
+/// Returns a configured SKY130 context, or an error if `SKY130_COMMERCIAL_PDK_ROOT`
+/// is not set to an existing directory.
+pub fn try_sky130_ctx() -> Result<PdkContext<Sky130Pdk>, ContextError> {
+    Sky130CtxBuilder::commercial(pdk_root_from_env("SKY130_COMMERCIAL_PDK_ROOT")?).build()
+}
+
+/// Returns a configured SKY130 context.
+///
+/// # Panics
+///
+/// Panics if `SKY130_COMMERCIAL_PDK_ROOT` is not set to an existing directory.
+/// See [`try_sky130_ctx`] for a fallible version.
+pub fn sky130_ctx() -> PdkContext<Sky130Pdk> {
+    try_sky130_ctx().unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Returns a SKY130 context configured with the open-source SKY130A PDK, or an error
+/// if `SKY130_OPEN_PDK_ROOT` is not set to an existing directory.
+pub fn try_sky130_open_ctx() -> Result<PdkContext<Sky130Pdk>, ContextError> {
+    Sky130CtxBuilder::open(pdk_root_from_env("SKY130_OPEN_PDK_ROOT")?).build()
+}
+
+/// Returns a SKY130 context configured with the open-source SKY130A PDK.
+///
+/// Installs ngspice as the simulator, so no commercial tools are required.
+///
+/// # Panics
+///
+/// Panics if `SKY130_OPEN_PDK_ROOT` is not set to an existing directory.
+/// See [`try_sky130_open_ctx`] for a fallible version.
+pub fn sky130_open_ctx() -> PdkContext<Sky130Pdk> {
+    try_sky130_open_ctx().unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Installs a global subscriber that prints the time spent in each generator and
+/// simulation span to stderr.
+///
+/// The verbosity is controlled by the `RUST_LOG` environment variable and defaults to `info`,
+/// which includes per-tile generation and per-simulation wall time.
+///
+/// Does nothing if a global subscriber has already been installed.
+#[cfg(feature = "trace")]
+pub fn init_tracing() {
+    use tracing_subscriber::fmt::format::FmtSpan;
+    use tracing_subscriber::EnvFilter;
+
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .try_init();
+}
+
+/// Options that apply to every generator in the crate.
+///
+/// The options are installed in the context used for generation, for example with
+/// [`Sky130CtxBuilder::generation_options`], and read with [`generation_options`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct GenerationOptions {
+    /// The seed of the router used by every tile.
+    pub router_seed: [u8; 32],
+    /// Whether to skip routing and strapping, for fast sizing iterations in simulation.
+    ///
+    /// Routes and straps only add layout geometry, so every generator produces the same
+    /// schematic, with the same connectivity, in either mode. Placement still runs,
+    /// since ATOLL builds the schematic and layout of a tile in the same pass. Layouts
+    /// generated in this mode are incomplete and are rejected by
+    /// [`write_gds`](crate::export::gds::write_gds).
+    #[serde(default)]
+    pub schematic_only: bool,
+    /// The via redundancy of each net class.
+    #[serde(default)]
+    pub vias: via::ViaPolicy,
+    /// Whether tiles that use the [pathfinder router](route::RouterKind::Pathfinder)
+    /// fall back to the greedy router when it fails. Enabled by default.
+    ///
+    /// ATOLL routers cannot return errors, so if unset, a pathfinder failure panics
+    /// during routing instead of producing greedy-routed geometry that the technology
+    /// did not select. Every fallback is logged as a warning.
+    #[serde(default = "greedy_fallback_default")]
+    pub greedy_fallback: bool,
+}
+
+impl GenerationOptions {
+    const DEFAULT: Self = Self {
+        router_seed: [1; 32],
+        schematic_only: false,
+        vias: via::ViaPolicy::DEFAULT,
+        greedy_fallback: true,
+    };
+
+    /// The default options with routing and strapping skipped.
+    pub fn schematic_only() -> Self {
+        Self {
+            schematic_only: true,
+            ..Self::DEFAULT
+        }
+    }
+}
+
+fn greedy_fallback_default() -> bool {
+    GenerationOptions::DEFAULT.greedy_fallback
+}
+
+impl Default for GenerationOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Installation for GenerationOptions {}
+
+/// Returns the generation options installed in `ctx`, or the defaults if none were
+/// installed.
+///
+/// Options are fixed when a context is built, so every cell Substrate caches in the
+/// context was generated with the same options.
+pub fn generation_options(ctx: &Context) -> GenerationOptions {
+    ctx.get_installation::<GenerationOptions>()
+        .map(|options| *options)
+        .unwrap_or_default()
+}
+
+/// The flavor of the PDK installed in a context, such as `"sky130-open"`.
+///
+/// Contexts that install the same PDK type with different models and layer maps,
+/// such as the commercial and open-source SKY130 PDKs, generate different files
+/// from the same block. Every context built by this crate installs its flavor.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct PdkFlavor(pub &'static str);
+
+impl Installation for PdkFlavor {}
+
+/// Returns the PDK flavor installed in `ctx`, if any.
+pub fn pdk_flavor(ctx: &Context) -> Option<PdkFlavor> {
+    ctx.get_installation::<PdkFlavor>().map(|flavor| *flavor)
+}
+
+/// A fingerprint of the generator sources, `Cargo.toml`, and `Cargo.lock`, computed by
+/// the build script.
+///
+/// Cached files and simulation results are keyed by the fingerprint rather than the
+/// crate version, which does not change between commits.
+pub const GENERATOR_FINGERPRINT: &str = env!("UCIEANALOG_SOURCE_HASH");
+
+/// Returns a 64-bit FNV-1a hash of `bytes`, which is stable across builds and platforms.
+pub(crate) fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325u64, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Returns the name of a block with base name `base` generated from `params`.
+///
+/// The name is suffixed with a hash of the serialized parameters, so that differently
+/// parameterized instances of the same generator receive distinct cell names.
+/// The hash is stable across builds and platforms.
+pub(crate) fn block_name(base: &str, params: &impl Serialize) -> ArcStr {
+    let json = serde_json::to_vec(params).expect("failed to serialize block parameters");
+    let hash = stable_hash(&json);
+    substrate::arcstr::format!("{base}_{:08x}", (hash >> 32) ^ (hash & 0xffffffff))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_names_distinguish_params() {
+        assert_eq!(
+            block_name("inverter", &(1, 2)),
+            block_name("inverter", &(1, 2))
+        );
+        assert_ne!(
+            block_name("inverter", &(1, 2)),
+            block_name("inverter", &(2, 1))
+        );
+        assert!(block_name("inverter", &(1, 2)).starts_with("inverter_"));
+    }
+
+    #[test]
+    fn generator_fingerprint_is_a_source_hash() {
+        assert_eq!(GENERATOR_FINGERPRINT.len(), 16);
+        assert!(GENERATOR_FINGERPRINT.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn generation_options_are_per_context() {
+        let ctx = Context::builder()
+            .install(GenerationOptions::schematic_only())
+            .build();
+        assert_eq!(
+            generation_options(&ctx),
+            GenerationOptions::schematic_only()
+        );
+        assert_eq!(
+            generation_options(&Context::builder().build()),
+            GenerationOptions::default()
+        );
+    }
+
+    #[test]
+    fn missing_pdk_root_is_an_error() {
+        assert!(matches!(
+            Sky130CtxBuilder::open("/nonexistent/sky130A").build(),
+            Err(ContextError::MissingPdkRoot(_))
+        ));
+    }
 }
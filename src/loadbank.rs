@@ -0,0 +1,279 @@
+//! Switchable capacitive load banks.
+//!
+//! A [`LoadBank`] hangs binary-weighted MOS capacitors off an internal node, each
+//! grounded through an NMOS switch driven from a configuration chain. During silicon
+//! bring-up, switching in capacitance on nodes such as a VCO output or a predriver
+//! output emulates the loading conditions characterized in simulation.
+
+use crate::driver::HorizontalDriverImpl;
+use crate::params::{check_at_least, check_positive, setters, ParamsError};
+use crate::scan::ConfigField;
+use crate::tiles::{TapIoSchematic, TileKind};
+use atoll::{IoBuilder, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::geometry::align::AlignMode;
+use substrate::io::{Array, InOut, Input, Io, MosIoSchematic, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+/// The interface to a [`LoadBank`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct LoadBankIo {
+    /// The loaded node.
+    pub node: InOut<Signal>,
+    /// The load code, least significant bit first.
+    ///
+    /// Setting bit `i` switches in `2^i` unit capacitors.
+    pub en: Array<Input<Signal>>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of the [`LoadBank`] layout generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct LoadBankParams {
+    /// The number of load bits.
+    pub bits: usize,
+    /// The gate width of each unit capacitor.
+    pub cap_w: i64,
+    /// The gate length of each unit capacitor.
+    pub cap_l: i64,
+    /// The width of the switch in series with a single unit capacitor.
+    ///
+    /// The switch of bit `i` is `2^i` times as wide, so that every switched capacitor
+    /// sees the same series resistance.
+    pub switch_w: i64,
+}
+
+impl LoadBankParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> LoadBankParamsBuilder {
+        LoadBankParamsBuilder::default()
+    }
+
+    /// The number of load codes.
+    pub fn codes(&self) -> usize {
+        1 << self.bits
+    }
+
+    /// The number of unit capacitors switched in by `code`.
+    pub fn switched_units(&self, code: usize) -> usize {
+        code & (self.codes() - 1)
+    }
+
+    /// The load added to `node` at code `code`, given the capacitance `c_unit` of one
+    /// unit capacitor and the residual capacitance `c_off` of a unit capacitor whose
+    /// switch is off.
+    pub fn capacitance(&self, code: usize, c_unit: f64, c_off: f64) -> f64 {
+        let on = self.switched_units(code);
+        let off = self.codes() - 1 - on;
+        on as f64 * c_unit + off as f64 * c_off
+    }
+
+    /// The configuration chain field that loads the load code.
+    pub fn config_field(&self, name: impl Into<String>) -> ConfigField {
+        ConfigField::new(name, self.bits)
+    }
+}
+
+/// A builder for [`LoadBankParams`].
+///
+/// Defaults to 3 bits of 2 um by 1 um unit capacitors, roughly 30 fF each in SKY130,
+/// for up to about 200 fF of added load.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct LoadBankParamsBuilder {
+    params: LoadBankParams,
+}
+
+impl Default for LoadBankParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: LoadBankParams {
+                bits: 3,
+                cap_w: 2_000,
+                cap_l: 1_000,
+                switch_w: 1_000,
+            },
+        }
+    }
+}
+
+impl LoadBankParamsBuilder {
+    setters! {
+        /// Sets the number of load bits.
+        bits: usize,
+        /// Sets the gate width of each unit capacitor.
+        cap_w: i64,
+        /// Sets the gate length of each unit capacitor.
+        cap_l: i64,
+        /// Sets the width of the switch in series with a single unit capacitor.
+        switch_w: i64,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<LoadBankParams, ParamsError> {
+        let p = &self.params;
+        check_at_least("bits", p.bits as i64, 1)?;
+        for (field, value) in [
+            ("cap_w", p.cap_w),
+            ("cap_l", p.cap_l),
+            ("switch_w", p.switch_w),
+        ] {
+            check_positive(field, value)?;
+        }
+        Ok(self.params)
+    }
+}
+
+/// A bank of binary-weighted MOS capacitors switched onto a node.
+///
+/// The gate of each capacitor is the loaded node. Bit `i` of the load code grounds the
+/// source and drain of `2^i` unit capacitors through an NMOS switch, so a larger code
+/// loads the node more. With the switch off, the capacitor floats and only its junction
+/// capacitance loads the node. Each bit is a column with its capacitor on top and its
+/// switch and substrate tap beneath.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct LoadBank<T>(
+    LoadBankParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> LoadBank<T> {
+    /// Creates a new [`LoadBank`].
+    pub fn new(params: LoadBankParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The parameters of this load bank.
+    pub fn params(&self) -> LoadBankParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for LoadBank<T> {
+    type Io = LoadBankIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("load_bank")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("load_bank", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        LoadBankIo {
+            en: Array::new(self.0.bits, Default::default()),
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Any> ExportsNestedData for LoadBank<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for LoadBank<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK> for LoadBank<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let p = self.0;
+        let vss = io.schematic.vss;
+
+        let mut prev = None;
+        for i in 0..p.bits {
+            // One finger per unit capacitor.
+            let nf = 1 << i;
+            let x = cell.signal(format!("x{i}"), Signal);
+
+            let mut cap = cell.generate_connected(
+                T::mos(TileKind::N, nf, p.cap_w * nf, Some(p.cap_l)),
+                MosIoSchematic {
+                    d: x,
+                    g: io.schematic.node,
+                    s: x,
+                    b: vss,
+                },
+            );
+            let mut switch = cell.generate_connected(
+                T::mos(TileKind::N, nf, p.switch_w * nf, None),
+                MosIoSchematic {
+                    d: x,
+                    g: io.schematic.en[i],
+                    s: vss,
+                    b: vss,
+                },
+            );
+            let mut ptap =
+                cell.generate_connected(T::tap(TileKind::P, nf), TapIoSchematic { x: vss });
+
+            if let Some(prev) = &prev {
+                cap.align_mut(prev, AlignMode::ToTheRight, 0);
+                cap.align_mut(prev, AlignMode::Top, 0);
+            }
+            switch.align_mut(&cap, AlignMode::Left, 0);
+            switch.align_mut(&cap, AlignMode::Beneath, 0);
+            ptap.align_mut(&switch, AlignMode::Left, 0);
+            ptap.align_mut(&switch, AlignMode::Beneath, 0);
+
+            io.layout.node.merge(cap.layout.io().g);
+            io.layout.en[i].merge(switch.layout.io().g);
+            io.layout.vss.merge(ptap.layout.io().x);
+
+            cell.draw(switch)?;
+            cell.draw(ptap)?;
+            if let Some(prev) = prev.replace(cap) {
+                cell.draw(prev)?;
+            }
+        }
+        if let Some(prev) = prev {
+            cell.draw(prev)?;
+        }
+
+        cell.set_top_layer(T::LAYER_MAP.pin);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        T::post_layout_hooks(cell)?;
+
+        Ok(((), ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_grows_with_code() {
+        let params = LoadBankParams::builder().build().unwrap();
+        let (c_unit, c_off) = (30e-15, 5e-15);
+        let load = (0..params.codes())
+            .map(|code| params.capacitance(code, c_unit, c_off))
+            .collect::<Vec<_>>();
+        assert!((load[0] - 7. * c_off).abs() < 1e-24);
+        assert!((load[7] - 7. * c_unit).abs() < 1e-24);
+        assert!(load.windows(2).all(|c| c[1] > c[0]));
+        assert!(matches!(
+            LoadBankParams::builder().cap_l(0).build(),
+            Err(ParamsError::NonPositive { field: "cap_l", .. })
+        ));
+    }
+}
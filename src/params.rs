@@ -0,0 +1,159 @@
+//! Validation of generator parameters.
+
+/// An error produced when building generator parameters.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParamsError {
+    /// A dimension or count that must be positive was not.
+    #[error("{field} must be positive, got {value}")]
+    NonPositive {
+        /// The name of the parameter.
+        field: &'static str,
+        /// The provided value.
+        value: i64,
+    },
+    /// A count was smaller than its minimum.
+    #[error("{field} must be at least {min}, got {value}")]
+    TooSmall {
+        /// The name of the parameter.
+        field: &'static str,
+        /// The provided value.
+        value: i64,
+        /// The smallest allowed value.
+        min: i64,
+    },
+    /// A count was larger than its maximum.
+    #[error("{field} must be at most {max}, got {value}")]
+    TooLarge {
+        /// The name of the parameter.
+        field: &'static str,
+        /// The provided value.
+        value: i64,
+        /// The largest allowed value.
+        max: i64,
+    },
+    /// A parameter has a value that the generator does not support.
+    #[error("{field} is not supported by the {generator}")]
+    Unsupported {
+        /// The name of the parameter.
+        field: &'static str,
+        /// The generator that does not support it.
+        generator: &'static str,
+    },
+}
+
+/// Returns an error if `value` is not positive.
+pub(crate) fn check_positive(field: &'static str, value: i64) -> Result<(), ParamsError> {
+    if value > 0 {
+        Ok(())
+    } else {
+        Err(ParamsError::NonPositive { field, value })
+    }
+}
+
+/// Returns an error if `value` is smaller than `min`.
+pub(crate) fn check_at_least(field: &'static str, value: i64, min: i64) -> Result<(), ParamsError> {
+    if value >= min {
+        Ok(())
+    } else {
+        Err(ParamsError::TooSmall { field, value, min })
+    }
+}
+
+/// Returns an error if `value` is larger than `max`.
+pub(crate) fn check_at_most(field: &'static str, value: i64, max: i64) -> Result<(), ParamsError> {
+    if value <= max {
+        Ok(())
+    } else {
+        Err(ParamsError::TooLarge { field, value, max })
+    }
+}
+
+/// Generates fluent setters on a parameter builder for the given fields of its `params` field.
+///
+/// A field declared as `field: impl Into<Ty>` gets a setter that accepts anything
+/// convertible to `Ty`.
+macro_rules! setters {
+    () => {};
+    ($(#[$doc:meta])* $field:ident: impl Into<$ty:ty> $(, $($rest:tt)*)?) => {
+        $(#[$doc])*
+        pub fn $field(mut self, $field: impl Into<$ty>) -> Self {
+            self.params.$field = $field.into();
+            self
+        }
+        $($crate::params::setters! { $($rest)* })?
+    };
+    ($(#[$doc:meta])* $field:ident: $ty:ty $(, $($rest:tt)*)?) => {
+        $(#[$doc])*
+        pub fn $field(mut self, $field: $ty) -> Self {
+            self.params.$field = $field;
+            self
+        }
+        $($crate::params::setters! { $($rest)* })?
+    };
+}
+
+pub(crate) use setters;
+
+#[cfg(test)]
+mod tests {
+    use crate::bias::TempSensorParams;
+    use crate::buffer::InverterParams;
+    use crate::driver::DriverUnitParams;
+    use crate::strongarm::StrongArmParams;
+    use crate::tiles::WidthSpec;
+
+    use super::*;
+
+    #[test]
+    fn builders_validate_params() {
+        assert!(StrongArmParams::builder().build().is_ok());
+        assert!(DriverUnitParams::builder().build().is_ok());
+        assert_eq!(
+            InverterParams::builder().nmos_w(0).build(),
+            Err(ParamsError::NonPositive {
+                field: "nmos_w",
+                value: 0
+            })
+        );
+        assert!(StrongArmParams::builder()
+            .half_tail_l(Some(500))
+            .build()
+            .is_ok());
+        assert_eq!(
+            DriverUnitParams::builder().driver_pd_l(Some(0)).build(),
+            Err(ParamsError::NonPositive {
+                field: "driver_pd_l",
+                value: 0
+            })
+        );
+        assert!(TempSensorParams::builder().build().is_ok());
+        assert_eq!(
+            TempSensorParams::builder().pnp_ratio(1).build(),
+            Err(ParamsError::TooSmall {
+                field: "pnp_ratio",
+                value: 1,
+                min: 2
+            })
+        );
+    }
+
+    #[test]
+    fn width_setters_accept_fin_counts() {
+        let params = StrongArmParams::builder()
+            .input_pair_w(WidthSpec::Fins(4))
+            .half_tail_w(2_000)
+            .build()
+            .unwrap();
+        assert_eq!(params.input_pair_w, WidthSpec::Fins(4));
+        assert_eq!(params.half_tail_w, WidthSpec::Nm(2_000));
+
+        // Planar configurations keep their bare nanometer widths.
+        let json = serde_json::to_value(params).unwrap();
+        assert_eq!(json["half_tail_w"], 2_000);
+        assert_eq!(json["input_pair_w"]["fins"], 4);
+        assert_eq!(
+            serde_json::from_value::<StrongArmParams>(json).unwrap(),
+            params
+        );
+    }
+}
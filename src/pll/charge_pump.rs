@@ -0,0 +1,529 @@
+//! Charge pump generators.
+
+use crate::bias::idac::{IdacParams, IdacUnit, IdacUnitIoSchematic};
+use crate::buffer::InverterImpl;
+use crate::params::{check_positive, setters, ParamsError};
+use crate::tiles::{MosKind, MosLengthRules, MosTileParams, TapTileParams, TileKind};
+use atoll::{IoBuilder, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::error::Result;
+use substrate::geometry::align::AlignMode;
+use substrate::io::{InOut, Input, Io, MosIoSchematic, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+/// The interface to a [`ChargePumpBranch`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct ChargePumpBranchIo {
+    /// The mirror gate.
+    pub bias: Input<Signal>,
+    /// The cascode gate.
+    pub vcas: Input<Signal>,
+    /// The gate of the switch that steers the current to `out`.
+    pub steer: Input<Signal>,
+    /// The gate of the switch that steers the current to `dummy`.
+    pub steer_dummy: Input<Signal>,
+    /// The output.
+    pub out: InOut<Signal>,
+    /// The dummy output.
+    pub dummy: InOut<Signal>,
+    /// The rail to which the mirror device is connected.
+    pub rail: InOut<Signal>,
+}
+
+/// The interface to a [`ChargePump`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct ChargePumpIo {
+    /// The reference current, which flows into a diode-connected NMOS mirror.
+    pub iref: InOut<Signal>,
+    /// The NMOS cascode bias voltage.
+    pub vcas_n: Input<Signal>,
+    /// The PMOS cascode bias voltage.
+    pub vcas_p: Input<Signal>,
+    /// Sources current into `out` when high.
+    pub up: Input<Signal>,
+    /// The complement of `up`.
+    pub upb: Input<Signal>,
+    /// Sinks current from `out` when high.
+    pub dn: Input<Signal>,
+    /// The complement of `dn`.
+    pub dnb: Input<Signal>,
+    /// The output, which drives the loop filter.
+    pub out: InOut<Signal>,
+    /// The dummy output, which takes the current of each branch while it is steered away
+    /// from `out`.
+    ///
+    /// Should be held at the voltage of `out`, usually by a unity-gain buffer, so that the
+    /// cascode drains do not move when the current is steered.
+    pub dummy: InOut<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The device sizes of one [`ChargePump`] branch and its reference.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ChargePumpBranchParams {
+    /// The width of the mirror device.
+    pub mirror_w: i64,
+    /// The channel length of the mirror device, or the technology minimum if `None`.
+    #[serde(default)]
+    pub mirror_l: Option<i64>,
+    /// The width of the cascode device.
+    pub cascode_w: i64,
+    /// The width of each steering switch.
+    pub switch_w: i64,
+}
+
+impl ChargePumpBranchParams {
+    /// The parameters of an [`IdacUnit`] of kind `kind` with the same device sizes.
+    fn unit(&self, kind: TileKind) -> IdacParams {
+        IdacParams {
+            kind,
+            binary_bits: 0,
+            thermometer_bits: 0,
+            mirror_w: self.mirror_w,
+            mirror_l: self.mirror_l,
+            cascode_w: self.cascode_w,
+            switch_w: self.switch_w,
+        }
+    }
+}
+
+/// The parameters of the [`ChargePump`] generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ChargePumpParams {
+    /// The PMOS branch that sources the up current.
+    pub up: ChargePumpBranchParams,
+    /// The NMOS branch that sinks the down current.
+    pub down: ChargePumpBranchParams,
+}
+
+impl ChargePumpParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> ChargePumpParamsBuilder {
+        ChargePumpParamsBuilder::default()
+    }
+
+    /// Returns an error if the MOS tiles of technology `T` do not support one of the
+    /// mirror channel lengths.
+    pub fn check_lengths<T: MosLengthRules>(&self) -> std::result::Result<(), ParamsError> {
+        T::check_length("up.mirror_l", self.up.mirror_l)?;
+        T::check_length("down.mirror_l", self.down.mirror_l)
+    }
+}
+
+/// A builder for [`ChargePumpParams`].
+///
+/// Defaults to minimum-length mirrors, with PMOS devices twice as wide as NMOS devices.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ChargePumpParamsBuilder {
+    params: ChargePumpParams,
+}
+
+impl Default for ChargePumpParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: ChargePumpParams {
+                up: ChargePumpBranchParams {
+                    mirror_w: 2_000,
+                    mirror_l: None,
+                    cascode_w: 2_000,
+                    switch_w: 2_000,
+                },
+                down: ChargePumpBranchParams {
+                    mirror_w: 1_000,
+                    mirror_l: None,
+                    cascode_w: 1_000,
+                    switch_w: 1_000,
+                },
+            },
+        }
+    }
+}
+
+impl ChargePumpParamsBuilder {
+    setters! {
+        /// Sets the device sizes of the PMOS branch.
+        up: ChargePumpBranchParams,
+        /// Sets the device sizes of the NMOS branch.
+        down: ChargePumpBranchParams,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<ChargePumpParams, ParamsError> {
+        let (up, down) = (self.params.up, self.params.down);
+        for (field, value) in [
+            ("up.mirror_w", up.mirror_w),
+            ("up.cascode_w", up.cascode_w),
+            ("up.switch_w", up.switch_w),
+            ("down.mirror_w", down.mirror_w),
+            ("down.cascode_w", down.cascode_w),
+            ("down.switch_w", down.switch_w),
+        ] {
+            check_positive(field, value)?;
+        }
+        for (field, value) in [
+            ("up.mirror_l", up.mirror_l),
+            ("down.mirror_l", down.mirror_l),
+        ] {
+            value.map_or(Ok(()), |value| check_positive(field, value))?;
+        }
+        Ok(self.params)
+    }
+}
+
+/// A cascoded current source whose current is steered to either `out` or `dummy`.
+///
+/// The mirror and cascode are stacked from `rail` to the shared source of the two
+/// switches, which sit side by side in the row farthest from the tap.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct ChargePumpBranch<T>(
+    TileKind,
+    ChargePumpBranchParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> ChargePumpBranch<T> {
+    /// Creates a new [`ChargePumpBranch`] built from devices of kind `kind`.
+    pub fn new(kind: TileKind, params: ChargePumpBranchParams) -> Self {
+        Self(kind, params, PhantomData)
+    }
+}
+
+impl<T: Any> Block for ChargePumpBranch<T> {
+    type Io = ChargePumpBranchIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("charge_pump_branch")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("charge_pump_branch", &(self.0, self.1))
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for ChargePumpBranch<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for ChargePumpBranch<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for ChargePumpBranch<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let kind = self.0;
+        let rail = io.schematic.rail;
+        let x = cell.signal("x", Signal);
+        let y = cell.signal("y", Signal);
+
+        let params = |w, l| {
+            MosTileParams::new(MosKind::Nom, kind, w)
+                .with_length(l)
+                .snapped(T::snap_width)
+        };
+        let mirror = cell.generate_connected(
+            T::mos(params(self.1.mirror_w, self.1.mirror_l)),
+            MosIoSchematic {
+                d: x,
+                g: io.schematic.bias,
+                s: rail,
+                b: rail,
+            },
+        );
+        let cascode = cell.generate_connected(
+            T::mos(params(self.1.cascode_w, None)),
+            MosIoSchematic {
+                d: y,
+                g: io.schematic.vcas,
+                s: x,
+                b: rail,
+            },
+        );
+        let switches = [
+            (io.schematic.out, io.schematic.steer),
+            (io.schematic.dummy, io.schematic.steer_dummy),
+        ]
+        .into_iter()
+        .map(|(d, g)| {
+            cell.generate_connected(
+                T::mos(params(self.1.switch_w, None)),
+                MosIoSchematic {
+                    d,
+                    g,
+                    s: y,
+                    b: rail,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+        let mut tap = cell.generate(T::tap(TapTileParams::new(kind, 2)));
+        cell.connect(tap.io().x, rail);
+
+        // Rows run from the rail to the switches. NMOS branches hang down from the
+        // switches to a substrate tap at the bottom, and PMOS branches hang down from a
+        // well tap at the top to the switches.
+        let mut rows = vec![vec![mirror], vec![cascode], switches];
+        let mut prev = tap.lcm_bounds();
+        if kind == TileKind::N {
+            rows.reverse();
+            prev = rows[0][0].lcm_bounds();
+        }
+        for (i, row) in rows.iter_mut().enumerate() {
+            let mut left = prev;
+            for (j, mos) in row.iter_mut().enumerate() {
+                if j > 0 {
+                    mos.align_rect_mut(left, AlignMode::Bottom, 0);
+                    mos.align_rect_mut(left, AlignMode::ToTheRight, 0);
+                } else if kind == TileKind::P || i > 0 {
+                    mos.align_rect_mut(prev, AlignMode::Left, 0);
+                    mos.align_rect_mut(prev, AlignMode::Beneath, 0);
+                }
+                left = mos.lcm_bounds();
+            }
+            prev = row[0].lcm_bounds();
+        }
+        if kind == TileKind::N {
+            tap.align_rect_mut(prev, AlignMode::Left, 0);
+            tap.align_rect_mut(prev, AlignMode::Beneath, 0);
+        }
+
+        let mut rows = rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|mos| cell.draw(mos))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let tap = cell.draw(tap)?;
+        if kind == TileKind::N {
+            rows.reverse();
+        }
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        let (mirror, cascode, switches) = (&rows[0][0], &rows[1][0], &rows[2]);
+        io.layout.bias.merge(mirror.layout.io().g);
+        io.layout.vcas.merge(cascode.layout.io().g);
+        io.layout.steer.merge(switches[0].layout.io().g);
+        io.layout.steer_dummy.merge(switches[1].layout.io().g);
+        io.layout.out.merge(switches[0].layout.io().d);
+        io.layout.dummy.merge(switches[1].layout.io().d);
+        io.layout.rail.merge(tap.layout.io().x);
+
+        Ok(((), ()))
+    }
+}
+
+/// A cascoded current-steering charge pump.
+///
+/// `iref` flows into a diode-connected NMOS reference, which biases the down branch and,
+/// through a second NMOS mirror and a diode-connected PMOS reference, the up branch. Both
+/// branches conduct continuously and steer their current between `out` and `dummy`, so
+/// the cascode drains stay put when `up` and `dn` switch and little charge is injected
+/// into `out` on each edge.
+///
+/// The references are diode-connected through their cascodes, so `vcas_n` and `vcas_p`
+/// must leave the reference mirror devices in saturation.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct ChargePump<T>(
+    ChargePumpParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> ChargePump<T> {
+    /// Creates a new [`ChargePump`].
+    pub fn new(params: ChargePumpParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The charge pump parameters.
+    pub fn params(&self) -> ChargePumpParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for ChargePump<T> {
+    type Io = ChargePumpIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("charge_pump")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("charge_pump", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for ChargePump<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for ChargePump<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for ChargePump<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        self.0
+            .check_lengths::<T>()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let bias_p = cell.signal("bias_p", Signal);
+        let (vdd, vss) = (io.schematic.vdd, io.schematic.vss);
+
+        // The NMOS reference, the NMOS mirror that feeds the PMOS reference,
+        // and the PMOS reference, with their switches always on.
+        let mut references = [
+            (TileKind::N, io.schematic.iref, io.schematic.iref),
+            (TileKind::N, io.schematic.iref, bias_p),
+            (TileKind::P, bias_p, bias_p),
+        ]
+        .into_iter()
+        .map(|(kind, bias, out)| {
+            let (params, vcas, en, rail) = match kind {
+                TileKind::N => (self.0.down.unit(kind), io.schematic.vcas_n, vdd, vss),
+                TileKind::P => (self.0.up.unit(kind), io.schematic.vcas_p, vss, vdd),
+            };
+            cell.generate_connected(
+                IdacUnit::<T>::new(params),
+                IdacUnitIoSchematic {
+                    bias,
+                    vcas,
+                    en,
+                    out,
+                    rail,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+        let mut up = cell.generate_connected(
+            ChargePumpBranch::<T>::new(TileKind::P, self.0.up),
+            ChargePumpBranchIoSchematic {
+                bias: bias_p,
+                vcas: io.schematic.vcas_p,
+                steer: io.schematic.upb,
+                steer_dummy: io.schematic.up,
+                out: io.schematic.out,
+                dummy: io.schematic.dummy,
+                rail: vdd,
+            },
+        );
+        let mut down = cell.generate_connected(
+            ChargePumpBranch::<T>::new(TileKind::N, self.0.down),
+            ChargePumpBranchIoSchematic {
+                bias: io.schematic.iref,
+                vcas: io.schematic.vcas_n,
+                steer: io.schematic.dn,
+                steer_dummy: io.schematic.dnb,
+                out: io.schematic.out,
+                dummy: io.schematic.dummy,
+                rail: vss,
+            },
+        );
+
+        let mut prev = references[0].lcm_bounds();
+        for reference in references.iter_mut().skip(1) {
+            reference.align_rect_mut(prev, AlignMode::Bottom, 0);
+            reference.align_rect_mut(prev, AlignMode::ToTheRight, 0);
+            prev = reference.lcm_bounds();
+        }
+        up.align_rect_mut(prev, AlignMode::Bottom, 0);
+        up.align_rect_mut(prev, AlignMode::ToTheRight, 0);
+        down.align_rect_mut(up.lcm_bounds(), AlignMode::Bottom, 0);
+        down.align_rect_mut(up.lcm_bounds(), AlignMode::ToTheRight, 0);
+
+        let references = references
+            .into_iter()
+            .map(|reference| cell.draw(reference))
+            .collect::<Result<Vec<_>>>()?;
+        let up = cell.draw(up)?;
+        let down = cell.draw(down)?;
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        io.layout.iref.merge(references[0].layout.io().out);
+        io.layout.vcas_n.merge(references[0].layout.io().vcas);
+        io.layout.vcas_p.merge(references[2].layout.io().vcas);
+        io.layout.up.merge(up.layout.io().steer_dummy);
+        io.layout.upb.merge(up.layout.io().steer);
+        io.layout.dn.merge(down.layout.io().steer);
+        io.layout.dnb.merge(down.layout.io().steer_dummy);
+        io.layout.out.merge(up.layout.io().out);
+        io.layout.out.merge(down.layout.io().out);
+        io.layout.dummy.merge(up.layout.io().dummy);
+        io.layout.dummy.merge(down.layout.io().dummy);
+        io.layout.vdd.merge(references[2].layout.io().rail);
+        io.layout.vss.merge(references[0].layout.io().rail);
+
+        Ok(((), ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branches_are_single_idac_units() {
+        let params = ChargePumpParams::builder().build().unwrap();
+        let up = params.up.unit(TileKind::P);
+        assert_eq!(up.kind, TileKind::P);
+        assert_eq!(up.bits(), 0);
+        assert!(up.units_per_control().is_empty());
+        assert_eq!(
+            (up.mirror_w, up.mirror_l, up.cascode_w, up.switch_w),
+            (2_000, None, 2_000, 2_000)
+        );
+        assert_eq!(params.down.unit(TileKind::N).mirror_w, 1_000);
+
+        let mut down = params.down;
+        down.mirror_l = Some(0);
+        assert!(matches!(
+            ChargePumpParams::builder().down(down).build(),
+            Err(ParamsError::NonPositive {
+                field: "down.mirror_l",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,415 @@
+//! Lock detector generators.
+
+use crate::buffer::{Buffer, BufferIoSchematic, Inverter, InverterImpl, InverterParams};
+use crate::gates::{Gate2, Gate2IoSchematic, Gate2Params};
+use crate::params::{check_at_least, check_positive, setters, ParamsError};
+use crate::tiles::{MosKind, MosLengthRules, MosTileParams, TapTileParams, TileKind, WidthSpec};
+use atoll::{IoBuilder, Orientation, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::geometry::align::AlignMode;
+use substrate::io::{InOut, Input, Io, MosIoSchematic, Output, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+/// The interface to a [`LockDetector`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct LockDetectorIo {
+    /// The up output of the phase-frequency detector.
+    pub up: Input<Signal>,
+    /// The down output of the phase-frequency detector.
+    pub dn: Input<Signal>,
+    /// High while the loop is locked.
+    pub lock: Output<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of the [`LockDetector`] generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct LockDetectorParams {
+    /// The device sizes of the NAND and NOR gates, per transistor.
+    pub gate: InverterParams,
+    /// The device sizes of the inverters in the delay line and output buffer.
+    pub delay: InverterParams,
+    /// The number of inverter pairs in the delay line, which sets the widest phase error
+    /// pulse accepted while locked.
+    pub delay_pairs: i64,
+    /// The width of the NMOS that discharges the hold node on each wide pulse.
+    pub discharge_w: i64,
+    /// The width of the weak PMOS that recharges the hold node.
+    pub pullup_w: i64,
+    /// The channel length of the weak PMOS that recharges the hold node,
+    /// or the technology minimum if `None`.
+    #[serde(default)]
+    pub pullup_l: Option<i64>,
+    /// The width of the MOS capacitor on the hold node.
+    pub hold_w: i64,
+    /// The channel length of the MOS capacitor on the hold node,
+    /// or the technology minimum if `None`.
+    #[serde(default)]
+    pub hold_l: Option<i64>,
+}
+
+impl LockDetectorParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> LockDetectorParamsBuilder {
+        LockDetectorParamsBuilder::default()
+    }
+
+    /// Returns an error if the MOS tiles of technology `T` do not support one of the
+    /// channel lengths.
+    pub fn check_lengths<T: MosLengthRules>(&self) -> std::result::Result<(), ParamsError> {
+        self.gate.check_lengths::<T>()?;
+        self.delay.check_lengths::<T>()?;
+        T::check_length("pullup_l", self.pullup_l)?;
+        T::check_length("hold_l", self.hold_l)
+    }
+}
+
+/// A builder for [`LockDetectorParams`].
+///
+/// Defaults to a delay line of two inverter pairs and minimum-length devices
+/// throughout. Where the MOS tiles support longer channels, a longer pull-up
+/// lengthens the hold time and longer delay inverters widen the lock window.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct LockDetectorParamsBuilder {
+    params: LockDetectorParams,
+}
+
+impl Default for LockDetectorParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: LockDetectorParams {
+                gate: InverterParams {
+                    nmos_kind: MosKind::Nom,
+                    pmos_kind: MosKind::Nom,
+                    nmos_w: WidthSpec::Nm(2_000),
+                    pmos_w: WidthSpec::Nm(2_000),
+                    nmos_l: None,
+                    pmos_l: None,
+                },
+                delay: InverterParams {
+                    nmos_kind: MosKind::Nom,
+                    pmos_kind: MosKind::Nom,
+                    nmos_w: WidthSpec::Nm(1_000),
+                    pmos_w: WidthSpec::Nm(2_000),
+                    nmos_l: None,
+                    pmos_l: None,
+                },
+                delay_pairs: 2,
+                discharge_w: 2_000,
+                pullup_w: 420,
+                pullup_l: None,
+                hold_w: 4_000,
+                hold_l: None,
+            },
+        }
+    }
+}
+
+impl LockDetectorParamsBuilder {
+    setters! {
+        /// Sets the device sizes of the NAND and NOR gates.
+        gate: InverterParams,
+        /// Sets the device sizes of the delay line and output buffer inverters.
+        delay: InverterParams,
+        /// Sets the number of inverter pairs in the delay line.
+        delay_pairs: i64,
+        /// Sets the width of the discharge NMOS.
+        discharge_w: i64,
+        /// Sets the width of the weak pull-up PMOS.
+        pullup_w: i64,
+        /// Sets the channel length of the weak pull-up PMOS.
+        pullup_l: Option<i64>,
+        /// Sets the width of the hold capacitor.
+        hold_w: i64,
+        /// Sets the channel length of the hold capacitor.
+        hold_l: Option<i64>,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<LockDetectorParams, ParamsError> {
+        let p = &self.params;
+        check_at_least("delay_pairs", p.delay_pairs, 1)?;
+        for (field, value) in [
+            ("discharge_w", p.discharge_w),
+            ("pullup_w", p.pullup_w),
+            ("hold_w", p.hold_w),
+        ] {
+            check_positive(field, value)?;
+        }
+        for (field, value) in [("pullup_l", p.pullup_l), ("hold_l", p.hold_l)] {
+            value.map_or(Ok(()), |value| check_positive(field, value))?;
+        }
+        Ok(self.params)
+    }
+}
+
+/// A PLL or DLL lock detector.
+///
+/// A NOR gate combines `up` and `dn` into a phase error pulse, and a NAND gate compares
+/// that pulse against a copy of itself delayed by the inverter delay line. Only error
+/// pulses wider than the delay produce an output from the NAND, and each such pulse
+/// discharges a hold capacitor that a weak PMOS slowly recharges. `lock` is the buffered
+/// hold node, so it falls on the first wide pulse and rises once pulses have stayed
+/// within the window for the hold time.
+///
+/// The reset pulse of the phase-frequency detector must be shorter than the delay line,
+/// or the detector never reports lock.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct LockDetector<T>(
+    LockDetectorParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> LockDetector<T> {
+    /// Creates a new [`LockDetector`].
+    pub fn new(params: LockDetectorParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The lock detector parameters.
+    pub fn params(&self) -> LockDetectorParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for LockDetector<T> {
+    type Io = LockDetectorIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("lock_detector")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("lock_detector", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for LockDetector<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for LockDetector<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for LockDetector<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        self.0
+            .check_lengths::<T>()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let params = self.0;
+        let (vdd, vss) = (io.schematic.vdd, io.schematic.vss);
+        let errb = cell.signal("errb", Signal);
+        let err = cell.signal("err", Signal);
+        let wideb = cell.signal("wideb", Signal);
+        let wide = cell.signal("wide", Signal);
+        let hold = cell.signal("hold", Signal);
+
+        let nor = cell.generate_connected(
+            Gate2::<T>::new(Gate2Params::nor(params.gate)),
+            Gate2IoSchematic {
+                a: io.schematic.up,
+                b: io.schematic.dn,
+                y: errb,
+                vdd,
+                vss,
+            },
+        );
+        let inverter = |cell: &mut TileBuilder<'a, PDK>, din, dout| {
+            cell.generate_connected(
+                Inverter::<T>::new(params.delay),
+                BufferIoSchematic {
+                    din,
+                    dout,
+                    vdd,
+                    vss,
+                },
+            )
+        };
+        let err_inv = inverter(cell, errb, err);
+        let mut delay = Vec::new();
+        let mut din = err;
+        for i in 0..2 * params.delay_pairs {
+            let dout = cell.signal(format!("err_d{i}"), Signal);
+            delay.push(inverter(cell, din, dout));
+            din = dout;
+        }
+        let nand = cell.generate_connected(
+            Gate2::<T>::new(Gate2Params::nand(params.gate)),
+            Gate2IoSchematic {
+                a: err,
+                b: din,
+                y: wideb,
+                vdd,
+                vss,
+            },
+        );
+        let wide_inv = inverter(cell, wideb, wide);
+
+        // The hold node stack: a weak, always-on pull-up above the discharge device and
+        // a MOS capacitor, between a well tap and a substrate tap.
+        let mos = |kind, w, l| {
+            T::mos(
+                MosTileParams::new(MosKind::Nom, kind, w)
+                    .with_length(l)
+                    .snapped(T::snap_width),
+            )
+        };
+        let mut pullup = cell.generate_connected(
+            mos(TileKind::P, params.pullup_w, params.pullup_l),
+            MosIoSchematic {
+                d: vdd,
+                g: vss,
+                s: hold,
+                b: vdd,
+            },
+        );
+        let mut discharge = cell
+            .generate_connected(
+                mos(TileKind::N, params.discharge_w, None),
+                MosIoSchematic {
+                    d: vss,
+                    g: wide,
+                    s: hold,
+                    b: vss,
+                },
+            )
+            .orient(Orientation::R180);
+        let mut hold_cap = cell
+            .generate_connected(
+                mos(TileKind::N, params.hold_w, params.hold_l),
+                MosIoSchematic {
+                    d: vss,
+                    g: hold,
+                    s: vss,
+                    b: vss,
+                },
+            )
+            .orient(Orientation::R180);
+        let mut ptap = cell.generate(T::tap(TapTileParams::new(TileKind::P, 1)));
+        let mut ntap = cell.generate(T::tap(TapTileParams::new(TileKind::N, 1)));
+        cell.connect(ptap.io().x, vss);
+        cell.connect(ntap.io().x, vdd);
+
+        let buffer = cell.generate_connected(
+            Buffer::<T>::new(params.delay),
+            BufferIoSchematic {
+                din: hold,
+                dout: io.schematic.lock,
+                vdd,
+                vss,
+            },
+        );
+
+        // Logic runs left to right in signal order, followed by the hold stack and
+        // the output buffer, all sharing a bottom edge.
+        let (mut err_inv, mut nand, mut wide_inv, mut buffer) = (err_inv, nand, wide_inv, buffer);
+        let mut prev = nor.lcm_bounds();
+        for inst in std::iter::once(&mut err_inv).chain(delay.iter_mut()) {
+            inst.align_rect_mut(prev, AlignMode::ToTheRight, 0);
+            inst.align_rect_mut(prev, AlignMode::Bottom, 0);
+            prev = inst.lcm_bounds();
+        }
+        nand.align_rect_mut(prev, AlignMode::ToTheRight, 0);
+        nand.align_rect_mut(prev, AlignMode::Bottom, 0);
+        let prev = nand.lcm_bounds();
+        wide_inv.align_rect_mut(prev, AlignMode::ToTheRight, 0);
+        wide_inv.align_rect_mut(prev, AlignMode::Bottom, 0);
+        let prev = wide_inv.lcm_bounds();
+        ptap.align_rect_mut(prev, AlignMode::ToTheRight, 0);
+        ptap.align_rect_mut(prev, AlignMode::Bottom, 0);
+
+        let mut prev = ptap.lcm_bounds();
+        for mos in [&mut hold_cap, &mut discharge, &mut pullup] {
+            mos.align_rect_mut(prev, AlignMode::Left, 0);
+            mos.align_rect_mut(prev, AlignMode::Above, 0);
+            prev = mos.lcm_bounds();
+        }
+        ntap.align_rect_mut(prev, AlignMode::Left, 0);
+        ntap.align_rect_mut(prev, AlignMode::Above, 0);
+        let prev = ntap.lcm_bounds();
+        buffer.align_rect_mut(prev, AlignMode::ToTheRight, 0);
+        buffer.align_rect_mut(ptap.lcm_bounds(), AlignMode::Bottom, 0);
+
+        let nor = cell.draw(nor)?;
+        cell.draw(err_inv)?;
+        for inv in delay {
+            cell.draw(inv)?;
+        }
+        cell.draw(nand)?;
+        cell.draw(wide_inv)?;
+        let ptap = cell.draw(ptap)?;
+        let ntap = cell.draw(ntap)?;
+        cell.draw(pullup)?;
+        cell.draw(discharge)?;
+        cell.draw(hold_cap)?;
+        let buffer = cell.draw(buffer)?;
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        io.layout.up.merge(nor.layout.io().a);
+        io.layout.dn.merge(nor.layout.io().b);
+        io.layout.lock.merge(buffer.layout.io().dout);
+        io.layout.vdd.merge(ntap.layout.io().x);
+        io.layout.vdd.merge(buffer.layout.io().vdd);
+        io.layout.vss.merge(ptap.layout.io().x);
+        io.layout.vss.merge(buffer.layout.io().vss);
+
+        Ok(((), ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_a_delay_line_and_hold_node() {
+        let params = LockDetectorParams::builder().build().unwrap();
+        assert_eq!(params.delay_pairs, 2);
+        // Every technology can draw the default lengths.
+        assert_eq!((params.pullup_l, params.hold_l), (None, None));
+        assert_eq!((params.delay.nmos_l, params.delay.pmos_l), (None, None));
+
+        assert!(matches!(
+            LockDetectorParams::builder().delay_pairs(0).build(),
+            Err(ParamsError::TooSmall {
+                field: "delay_pairs",
+                min: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            LockDetectorParams::builder().hold_l(Some(0)).build(),
+            Err(ParamsError::NonPositive {
+                field: "hold_l",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,331 @@
+//! Loop filter synthesis.
+//!
+//! [`design_loop_filter`] sizes the second-order passive filter of a charge pump PLL:
+//! a series resistor and capacitor `C1` from the charge pump output to ground, in
+//! parallel with a ripple capacitor `C2`. The resistor and capacitors are snapped to
+//! realizable tile sizes, and the closed-loop response is recomputed from the snapped
+//! values so that the report reflects what will actually be built.
+
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+use crate::lane::MomCapParams;
+use crate::report::SimArtifact;
+
+/// The grid to which resistor and capacitor dimensions are snapped, in nanometers.
+const GRID: i64 = 10;
+
+/// The number of frequency points per decade in a [`ClosedLoopResponse`].
+const POINTS_PER_DECADE: usize = 20;
+
+/// Technology parameters of the resistor and capacitor tiles used by a loop filter.
+pub trait LoopFilterRules {
+    /// The sheet resistance of the resistor tile, in ohms per square.
+    const RES_SHEET_RESISTANCE: f64;
+    /// The width of each resistor leg, in nanometers.
+    const RES_W: i64;
+    /// The longest resistor leg, in nanometers.
+    const RES_MAX_L: i64;
+    /// The capacitance between adjacent [`MomCap`](crate::lane::MomCap) fingers,
+    /// in farads per nanometer of finger length.
+    const MOM_CAP_PER_LENGTH: f64;
+    /// The longest [`MomCap`](crate::lane::MomCap) finger, in nanometers.
+    const MOM_MAX_FINGER_LENGTH: i64;
+}
+
+/// An error produced while designing a loop filter.
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+pub enum LoopFilterError {
+    /// A specification that must be positive was not.
+    #[error("{field} must be positive, got {value}")]
+    NonPositive {
+        /// The name of the specification.
+        field: &'static str,
+        /// The provided value.
+        value: f64,
+    },
+    /// The phase margin is not strictly between 0 and 90 degrees.
+    #[error("phase margin must be between 0 and 90 degrees, got {0}")]
+    PhaseMargin(f64),
+    /// The loop bandwidth is too close to the reference frequency for the
+    /// continuous-time loop model to hold.
+    #[error("loop bandwidth {bw} Hz exceeds a tenth of the reference frequency {fref} Hz")]
+    Bandwidth {
+        /// The requested loop bandwidth, in hertz.
+        bw: f64,
+        /// The reference frequency, in hertz.
+        fref: f64,
+    },
+}
+
+/// The size of a series resistor built from identical legs.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ResistorSize {
+    /// The number of legs in series.
+    pub legs: i64,
+    /// The width of each leg, in nanometers.
+    pub w: i64,
+    /// The length of each leg, in nanometers.
+    pub l: i64,
+}
+
+/// Component values of a second-order passive loop filter.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct LoopFilterValues {
+    /// The zero resistor, in ohms.
+    pub r: f64,
+    /// The capacitor in series with the resistor, in farads.
+    pub c1: f64,
+    /// The ripple capacitor, in farads.
+    pub c2: f64,
+}
+
+/// A loop filter design, as returned by [`design_loop_filter`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LoopFilterDesign {
+    /// The ideal component values for the requested loop dynamics.
+    pub ideal: LoopFilterValues,
+    /// The component values of the snapped tiles.
+    pub snapped: LoopFilterValues,
+    /// The zero resistor tile.
+    pub r: ResistorSize,
+    /// The capacitor in series with the resistor.
+    pub c1: MomCapParams,
+    /// The ripple capacitor.
+    pub c2: MomCapParams,
+    /// The predicted closed-loop response with the snapped component values.
+    pub response: ClosedLoopResponse,
+}
+
+/// One point of a [`ClosedLoopResponse`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ResponsePoint {
+    /// The frequency offset, in hertz.
+    pub freq: f64,
+    /// The magnitude of the open-loop gain, in decibels.
+    pub open_loop_db: f64,
+    /// The phase of the open-loop gain, in degrees.
+    pub open_loop_phase: f64,
+    /// The magnitude of the closed-loop transfer function from reference phase to
+    /// divided output phase, in decibels.
+    pub closed_loop_db: f64,
+}
+
+/// The predicted loop response of a PLL.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ClosedLoopResponse {
+    /// The response at logarithmically-spaced frequencies.
+    pub points: Vec<ResponsePoint>,
+    /// The frequency at which the open-loop gain crosses unity, in hertz.
+    pub crossover: f64,
+    /// The phase margin at the crossover frequency, in degrees.
+    pub phase_margin: f64,
+    /// The largest closed-loop gain, in decibels.
+    pub peaking_db: f64,
+}
+
+/// The open-loop gain `K (1 + s T2) / (s^2 C (1 + s T1))` of a type-II PLL,
+/// evaluated at `freq` hertz as a magnitude and a phase in radians.
+fn open_loop(k: f64, values: &LoopFilterValues, freq: f64) -> (f64, f64) {
+    let LoopFilterValues { r, c1, c2 } = *values;
+    let (t1, t2, c) = (r * c1 * c2 / (c1 + c2), r * c1, c1 + c2);
+    let w = 2. * PI * freq;
+    let mag = k * (1. + (w * t2).powi(2)).sqrt() / (w * w * c * (1. + (w * t1).powi(2)).sqrt());
+    let phase = -PI + (w * t2).atan() - (w * t1).atan();
+    (mag, phase)
+}
+
+impl ClosedLoopResponse {
+    /// Evaluates the response of a loop with gain constant `k` and filter `values`
+    /// from `fstart` to `fstop` hertz.
+    ///
+    /// `k` is the product of the charge pump gain in amperes per radian and the VCO gain
+    /// in radians per second per volt, referred to the phase detector.
+    pub fn new(k: f64, values: &LoopFilterValues, fstart: f64, fstop: f64) -> Self {
+        let decades = (fstop / fstart).log10();
+        let n = (decades * POINTS_PER_DECADE as f64).ceil() as usize;
+        let points = (0..=n)
+            .map(|i| {
+                let freq = fstart * 10f64.powf(decades * i as f64 / n as f64);
+                let (mag, phase) = open_loop(k, values, freq);
+                // |G / (1 + G)| with G = mag * e^(j phase).
+                let (re, im) = (1. + mag * phase.cos(), mag * phase.sin());
+                let closed = mag / (re * re + im * im).sqrt();
+                ResponsePoint {
+                    freq,
+                    open_loop_db: 20. * mag.log10(),
+                    open_loop_phase: phase.to_degrees(),
+                    closed_loop_db: 20. * closed.log10(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // The open-loop magnitude falls monotonically, so bisect for unity gain.
+        let (mut lo, mut hi) = (fstart, fstop);
+        for _ in 0..100 {
+            let mid = (lo * hi).sqrt();
+            if open_loop(k, values, mid).0 > 1. {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let crossover = (lo * hi).sqrt();
+        let phase_margin = 180. + open_loop(k, values, crossover).1.to_degrees();
+        let peaking_db = points
+            .iter()
+            .map(|p| p.closed_loop_db)
+            .fold(f64::NEG_INFINITY, f64::max);
+        Self {
+            points,
+            crossover,
+            phase_margin,
+            peaking_db,
+        }
+    }
+}
+
+impl SimArtifact for ClosedLoopResponse {
+    fn csv_header(&self) -> Vec<String> {
+        ["freq", "open_loop_db", "open_loop_phase", "closed_loop_db"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.points
+            .iter()
+            .map(|p| {
+                [p.freq, p.open_loop_db, p.open_loop_phase, p.closed_loop_db]
+                    .map(|x| x.to_string())
+                    .to_vec()
+            })
+            .collect()
+    }
+}
+
+/// Rounds `x` nanometers to the nearest multiple of [`GRID`], clamped to `[GRID, max]`.
+fn snap(x: f64, max: i64) -> i64 {
+    (((x / GRID as f64).round() as i64) * GRID).clamp(GRID, max)
+}
+
+/// Splits a resistance of `r` ohms into the fewest series legs no longer than
+/// [`LoopFilterRules::RES_MAX_L`].
+pub(crate) fn snap_resistor<T: LoopFilterRules>(r: f64) -> ResistorSize {
+    let per_nm = T::RES_SHEET_RESISTANCE / T::RES_W as f64;
+    let legs = (r / (per_nm * T::RES_MAX_L as f64)).ceil().max(1.) as i64;
+    ResistorSize {
+        legs,
+        w: T::RES_W,
+        l: snap(r / (per_nm * legs as f64), T::RES_MAX_L),
+    }
+}
+
+/// Splits a capacitance of `c` farads into the fewest [`MomCap`](crate::lane::MomCap)
+/// fingers no longer than [`LoopFilterRules::MOM_MAX_FINGER_LENGTH`].
+fn snap_cap<T: LoopFilterRules>(c: f64) -> MomCapParams {
+    let gaps = (c / (T::MOM_CAP_PER_LENGTH * T::MOM_MAX_FINGER_LENGTH as f64))
+        .ceil()
+        .max(1.) as i64;
+    MomCapParams {
+        fingers: gaps + 1,
+        finger_length: snap(
+            c / (T::MOM_CAP_PER_LENGTH * gaps as f64),
+            T::MOM_MAX_FINGER_LENGTH,
+        ),
+    }
+}
+
+/// Designs the loop filter of a type-II charge pump PLL.
+///
+/// `kvco` is the VCO gain divided by the feedback division ratio, in hertz per volt,
+/// `icp` is the charge pump current in amperes, `fref` is the reference frequency in
+/// hertz, `bw` is the open-loop unity-gain bandwidth in hertz, and `pm` is the phase
+/// margin in degrees. The pole and zero are placed symmetrically about `bw` so that the
+/// phase margin peaks at the crossover frequency.
+pub fn design_loop_filter<T: LoopFilterRules>(
+    kvco: f64,
+    icp: f64,
+    fref: f64,
+    bw: f64,
+    pm: f64,
+) -> Result<LoopFilterDesign, LoopFilterError> {
+    for (field, value) in [("kvco", kvco), ("icp", icp), ("fref", fref), ("bw", bw)] {
+        if value.is_nan() || value <= 0. {
+            return Err(LoopFilterError::NonPositive { field, value });
+        }
+    }
+    if pm.is_nan() || pm <= 0. || pm >= 90. {
+        return Err(LoopFilterError::PhaseMargin(pm));
+    }
+    if bw > fref / 10. {
+        return Err(LoopFilterError::Bandwidth { bw, fref });
+    }
+
+    let wc = 2. * PI * bw;
+    let phi = pm.to_radians();
+    let (kpd, kv) = (icp / (2. * PI), 2. * PI * kvco);
+    let k = kpd * kv;
+    let t1 = (1. / phi.cos() - phi.tan()) / wc;
+    let t2 = 1. / (wc * wc * t1);
+    let c = k / (wc * wc) * ((1. + (wc * t2).powi(2)) / (1. + (wc * t1).powi(2))).sqrt();
+    let c2 = c * t1 / t2;
+    let c1 = c - c2;
+    let ideal = LoopFilterValues { r: t2 / c1, c1, c2 };
+
+    let r = snap_resistor::<T>(ideal.r);
+    let (c1, c2) = (snap_cap::<T>(ideal.c1), snap_cap::<T>(ideal.c2));
+    let snapped = LoopFilterValues {
+        r: T::RES_SHEET_RESISTANCE * (r.legs * r.l) as f64 / r.w as f64,
+        c1: c1.capacitance::<T>(),
+        c2: c2.capacitance::<T>(),
+    };
+    let response = ClosedLoopResponse::new(k, &snapped, bw / 100., fref / 2.);
+
+    Ok(LoopFilterDesign {
+        ideal,
+        snapped,
+        r,
+        c1,
+        c2,
+        response,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Rules;
+
+    impl LoopFilterRules for Rules {
+        const RES_SHEET_RESISTANCE: f64 = 1_000.;
+        const RES_W: i64 = 1_000;
+        const RES_MAX_L: i64 = 20_000;
+        const MOM_CAP_PER_LENGTH: f64 = 1e-19;
+        const MOM_MAX_FINGER_LENGTH: i64 = 50_000;
+    }
+
+    #[test]
+    fn loop_filter_meets_specs() {
+        let design = design_loop_filter::<Rules>(50e6, 100e-6, 100e6, 1e6, 60.).unwrap();
+        let response = &design.response;
+        assert!((response.crossover / 1e6 - 1.).abs() < 0.05);
+        assert!((response.phase_margin - 60.).abs() < 2.);
+        assert!(design.r.l <= Rules::RES_MAX_L);
+        assert!(design.c1.finger_length <= Rules::MOM_MAX_FINGER_LENGTH);
+        assert!((design.snapped.c1 / design.ideal.c1 - 1.).abs() < 0.01);
+
+        assert_eq!(
+            design_loop_filter::<Rules>(50e6, 100e-6, 100e6, 20e6, 60.),
+            Err(LoopFilterError::Bandwidth {
+                bw: 20e6,
+                fref: 100e6
+            })
+        );
+        assert_eq!(
+            design_loop_filter::<Rules>(50e6, 100e-6, 100e6, 1e6, 90.),
+            Err(LoopFilterError::PhaseMargin(90.))
+        );
+    }
+}
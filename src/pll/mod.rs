@@ -0,0 +1,8 @@
+//! Phase-locked loop building blocks.
+
+pub mod charge_pump;
+pub mod lock_detector;
+pub mod loop_filter;
+pub mod tb;
+
+pub use loop_filter::design_loop_filter;
@@ -0,0 +1,749 @@
+//! Phase-locked loop characterization.
+//!
+//! [`ChargePumpTb`] holds the output of a [`ChargePump`] at a fixed voltage and measures
+//! its up and down currents and the net charge it injects when `up` and `dn` pulse
+//! together, as they do every reference cycle once the loop is locked. Run it at several
+//! output voltages and collect the results in a [`ChargePumpCharacteristic`].
+//!
+//! [`LockDetectorTb`] checks the handoff signal of a [`LockDetector`] across a frequency
+//! step.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::{Isource, Pulse, Vsource};
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Node, Terminal};
+use substrate::io::{Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::buffer::tb::integrate;
+use crate::pll::charge_pump::ChargePump;
+use crate::pll::lock_detector::LockDetector;
+use crate::report::SimArtifact;
+use crate::tb::pi::Samples;
+
+/// The rise and fall time of the charge pump control inputs.
+const EDGE: Decimal = dec!(50e-12);
+
+/// A transient testbench that measures the up current, the down current, and the net
+/// charge injected by simultaneous `up` and `dn` pulses of a [`ChargePump`].
+///
+/// The simulation has four phases of length `period`: both branches steered to the dummy
+/// output, `up` alone, `dn` alone, and a single pulse of length `width` on both `up` and
+/// `dn` at the start of the last phase. The output and dummy output are held at `vout` by
+/// one voltage source, through which the output current is measured.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq; C)]
+#[derive(Serialize, Deserialize)]
+pub struct ChargePumpTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: ChargePump<T>,
+    /// The reference current, in amperes.
+    pub iref: Decimal,
+    /// The NMOS cascode bias voltage, in volts.
+    pub vcas_n: Decimal,
+    /// The PMOS cascode bias voltage, in volts.
+    pub vcas_p: Decimal,
+    /// The output voltage, in volts.
+    pub vout: Decimal,
+    /// The length of each phase, in seconds.
+    pub period: Decimal,
+    /// The length of the simultaneous `up` and `dn` pulse, in seconds.
+    pub width: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> ChargePumpTb<T, PDK, C> {
+    /// Creates a new [`ChargePumpTb`] with 20 ns phases and a 200 ps overlap pulse.
+    pub fn new(
+        dut: ChargePump<T>,
+        iref: Decimal,
+        vcas_n: Decimal,
+        vcas_p: Decimal,
+        vout: Decimal,
+        pvt: Pvt<C>,
+    ) -> Self {
+        Self {
+            dut,
+            iref,
+            vcas_n,
+            vcas_p,
+            vout,
+            period: dec!(20e-9),
+            width: dec!(200e-12),
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        T: Any,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for ChargePumpTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("charge_pump_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("charge_pump_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`ChargePumpTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct ChargePumpTbNodes {
+    vout_src: Terminal,
+}
+
+impl<T, PDK, C> ExportsNestedData for ChargePumpTb<T, PDK, C>
+where
+    ChargePumpTb<T, PDK, C>: Block,
+{
+    type NestedData = ChargePumpTbNodes;
+}
+
+/// Drives `node` to `on` during each of `windows`, given as a delay and a width,
+/// and to `off` otherwise.
+///
+/// Each window gets its own pulse source, and the sources are stacked in series.
+fn drive_windows(
+    cell: &mut CellBuilder<Spectre>,
+    name: &str,
+    node: Node,
+    vss: Node,
+    off: Decimal,
+    on: Decimal,
+    windows: &[(Decimal, Decimal)],
+) {
+    let mut n = vss;
+    for (i, &(delay, width)) in windows.iter().enumerate() {
+        let p = if i + 1 == windows.len() {
+            node
+        } else {
+            cell.signal(format!("{name}_{i}"), Signal)
+        };
+        let val0 = if i == 0 { off } else { dec!(0) };
+        cell.instantiate_connected(
+            Vsource::pulse(Pulse {
+                val0,
+                val1: val0 + on - off,
+                period: None,
+                width: Some(width - EDGE),
+                delay: Some(delay),
+                rise: Some(EDGE),
+                fall: Some(EDGE),
+            }),
+            TwoTerminalIoSchematic { p, n },
+        );
+        n = p;
+    }
+}
+
+impl<T, PDK: Schema, C: Copy> Schematic<Spectre> for ChargePumpTb<T, PDK, C>
+where
+    ChargePumpTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    ChargePump<T>: Schematic<PDK>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vdd = cell.signal("vdd", Signal);
+        let vout = cell.signal("vout", Signal);
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut);
+        cell.connect(dut.io().vdd, vdd);
+        cell.connect(dut.io().vss, io.vss);
+        cell.connect(dut.io().out, vout);
+        cell.connect(dut.io().dummy, vout);
+
+        for (value, node) in [
+            (self.pvt.voltage, vdd),
+            (self.vcas_n, dut.io().vcas_n),
+            (self.vcas_p, dut.io().vcas_p),
+        ] {
+            cell.instantiate_connected(
+                Vsource::dc(value),
+                TwoTerminalIoSchematic { p: node, n: io.vss },
+            );
+        }
+        cell.instantiate_connected(
+            Isource::dc(self.iref),
+            TwoTerminalIoSchematic {
+                p: vdd,
+                n: dut.io().iref,
+            },
+        );
+        let vout_src = cell.instantiate(Vsource::dc(self.vout));
+        cell.connect(vout_src.io().p, vout);
+        cell.connect(vout_src.io().n, io.vss);
+
+        let (period, width) = (self.period, self.width);
+        let overlap = (dec!(3) * period, width);
+        let controls = [
+            ("up", dut.io().up, true, [(period, period), overlap]),
+            ("upb", dut.io().upb, false, [(period, period), overlap]),
+            (
+                "dn",
+                dut.io().dn,
+                true,
+                [(dec!(2) * period, period), overlap],
+            ),
+            (
+                "dnb",
+                dut.io().dnb,
+                false,
+                [(dec!(2) * period, period), overlap],
+            ),
+        ];
+        for (name, node, active_high, windows) in controls {
+            let (off, on) = if active_high {
+                (dec!(0), self.pvt.voltage)
+            } else {
+                (self.pvt.voltage, dec!(0))
+            };
+            drive_windows(cell, name, node, io.vss, off, on, &windows);
+        }
+
+        Ok(ChargePumpTbNodes {
+            vout_src: vout_src.io().p,
+        })
+    }
+}
+
+/// The resulting waveforms of a [`ChargePumpTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct ChargePumpSim {
+    /// The simulation time.
+    pub t: tran::Time,
+    /// The current into the output voltage source.
+    pub iout: tran::Current,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, ChargePumpSim> for ChargePumpTb<T, PDK, C>
+where
+    ChargePumpTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <ChargePumpSim as FromSaved<Spectre, Tran>>::SavedKey {
+        ChargePumpSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            iout: tran::Current::save(ctx, &cell.vout_src, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for ChargePumpTb<T, PDK, C>
+where
+    ChargePumpTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = ChargePumpMeasurement;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: ChargePumpSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: dec!(4) * self.period,
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+        let period = self.period.to_f64().unwrap();
+        let samples = Samples {
+            t: &wav.t,
+            v: &wav.iout,
+        };
+        // The output source sinks the up current and sources the down current.
+        let i_up = samples.value_at(1.9 * period);
+        let i_dn = -samples.value_at(2.9 * period);
+        let leakage = samples.value_at(0.9 * period);
+        let net_charge = integrate(&wav.t, &wav.iout, 3. * period, 4. * period) - leakage * period;
+        ChargePumpMeasurement::new(
+            self.vout.to_f64().unwrap(),
+            i_up,
+            i_dn,
+            net_charge,
+            self.width.to_f64().unwrap(),
+        )
+    }
+}
+
+/// The currents and injected charge of a charge pump at one output voltage.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ChargePumpMeasurement {
+    /// The output voltage, in volts.
+    pub vout: f64,
+    /// The current sourced into the output while `up` is high, in amperes.
+    pub i_up: f64,
+    /// The current sunk from the output while `dn` is high, in amperes.
+    pub i_dn: f64,
+    /// The up/down current mismatch, relative to their average.
+    pub mismatch: f64,
+    /// The net charge injected into the output by one simultaneous `up` and `dn` pulse,
+    /// in coulombs.
+    pub net_charge: f64,
+    /// The part of the net charge not explained by current mismatch over the pulse,
+    /// from charge sharing, clock feedthrough, and switch timing skew, in coulombs.
+    pub glitch_charge: f64,
+}
+
+impl ChargePumpMeasurement {
+    /// Computes the mismatch and glitch charge from the measured currents and the net
+    /// charge injected by a simultaneous pulse of length `width`.
+    pub fn new(vout: f64, i_up: f64, i_dn: f64, net_charge: f64, width: f64) -> Self {
+        Self {
+            vout,
+            i_up,
+            i_dn,
+            mismatch: (i_up - i_dn) / ((i_up + i_dn) / 2.),
+            net_charge,
+            glitch_charge: net_charge - (i_up - i_dn) * width,
+        }
+    }
+}
+
+/// Charge pump measurements across output voltages.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChargePumpCharacteristic {
+    /// The measurements, in increasing order of output voltage.
+    pub points: Vec<ChargePumpMeasurement>,
+}
+
+impl ChargePumpCharacteristic {
+    /// Collects `points` in increasing order of output voltage.
+    pub fn new(points: impl IntoIterator<Item = ChargePumpMeasurement>) -> Self {
+        let mut points = points.into_iter().collect::<Vec<_>>();
+        points.sort_by(|a, b| a.vout.total_cmp(&b.vout));
+        Self { points }
+    }
+
+    /// The contiguous output voltage range around `vnom` over which the magnitude of the
+    /// current mismatch stays within `max_mismatch`.
+    ///
+    /// Returns `None` if `vnom` is above the measured range or the mismatch at `vnom`
+    /// already exceeds `max_mismatch`.
+    pub fn range(&self, vnom: f64, max_mismatch: f64) -> Option<(f64, f64)> {
+        let k = self.points.iter().position(|p| p.vout >= vnom)?;
+        let within = |j: &usize| self.points[*j].mismatch.abs() <= max_mismatch;
+        let lo = (0..=k).rev().take_while(within).last()?;
+        let hi = (k..self.points.len()).take_while(within).last()?;
+        Some((self.points[lo].vout, self.points[hi].vout))
+    }
+
+    /// The largest magnitude of glitch charge within the output voltage range `range`.
+    pub fn max_glitch_charge(&self, range: (f64, f64)) -> f64 {
+        self.points
+            .iter()
+            .filter(|p| p.vout >= range.0 && p.vout <= range.1)
+            .map(|p| p.glitch_charge.abs())
+            .fold(0., f64::max)
+    }
+}
+
+impl SimArtifact for ChargePumpCharacteristic {
+    fn csv_header(&self) -> Vec<String> {
+        [
+            "vout",
+            "i_up",
+            "i_dn",
+            "mismatch",
+            "net_charge",
+            "glitch_charge",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.points
+            .iter()
+            .map(|p| {
+                [
+                    p.vout,
+                    p.i_up,
+                    p.i_dn,
+                    p.mismatch,
+                    p.net_charge,
+                    p.glitch_charge,
+                ]
+                .map(|x| x.to_string())
+                .to_vec()
+            })
+            .collect()
+    }
+}
+
+/// A transient testbench that checks that a [`LockDetector`] deasserts `lock` when the
+/// loop loses lock after a frequency step and reasserts it once the loop settles.
+///
+/// `up` and `dn` are driven with the pulses of a phase-frequency detector at a reference
+/// period of `period`. Both pulse for `reset_width` every cycle while the loop is locked.
+/// The frequency step starts after `locked_cycles` cycles, widening the `up` pulse by
+/// `step_error` and then by linearly less each cycle for `step_cycles` cycles as the loop
+/// reacquires, after which the loop stays locked for another `settle_cycles` cycles.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq; C)]
+#[derive(Serialize, Deserialize)]
+pub struct LockDetectorTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: LockDetector<T>,
+    /// The reference period, in seconds.
+    pub period: Decimal,
+    /// The width of the `up` and `dn` pulses while locked, in seconds.
+    pub reset_width: Decimal,
+    /// The phase error in the first cycle after the frequency step, in seconds.
+    pub step_error: Decimal,
+    /// The number of locked cycles before the frequency step.
+    pub locked_cycles: i64,
+    /// The number of cycles taken to reacquire lock after the frequency step.
+    pub step_cycles: i64,
+    /// The number of locked cycles after reacquisition.
+    pub settle_cycles: i64,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> LockDetectorTb<T, PDK, C> {
+    /// Creates a new [`LockDetectorTb`] with a 100 MHz reference, 100 ps reset pulses,
+    /// and a 2 ns phase error step that decays over 10 cycles.
+    pub fn new(dut: LockDetector<T>, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            period: dec!(10e-9),
+            reset_width: dec!(100e-12),
+            step_error: dec!(2e-9),
+            locked_cycles: 20,
+            step_cycles: 10,
+            settle_cycles: 20,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The time at which the frequency step occurs, in seconds.
+    pub fn step_start(&self) -> Decimal {
+        self.period * Decimal::from(self.locked_cycles)
+    }
+
+    /// The time at which the loop has reacquired lock, in seconds.
+    pub fn step_stop(&self) -> Decimal {
+        self.period * Decimal::from(self.locked_cycles + self.step_cycles)
+    }
+
+    /// The simulation stop time, in seconds.
+    pub fn stop(&self) -> Decimal {
+        self.period * Decimal::from(self.locked_cycles + self.step_cycles + self.settle_cycles)
+    }
+
+    /// The width of the `up` pulse in each cycle, in seconds.
+    pub fn up_widths(&self) -> Vec<Decimal> {
+        let step_cycles = Decimal::from(self.step_cycles);
+        (0..self.locked_cycles + self.step_cycles + self.settle_cycles)
+            .map(|i| {
+                let j = i - self.locked_cycles;
+                if (0..self.step_cycles).contains(&j) {
+                    self.reset_width
+                        + self.step_error * (step_cycles - Decimal::from(j)) / step_cycles
+                } else {
+                    self.reset_width
+                }
+            })
+            .collect()
+    }
+}
+
+/// Returns the `(time, voltage)` points of a train of pulses to `high` that start at the
+/// beginning of each `period` and last for the corresponding entry of `widths`.
+fn pulse_train(period: Decimal, widths: &[Decimal], high: Decimal) -> Vec<(Decimal, Decimal)> {
+    let mut points = vec![(dec!(0), dec!(0))];
+    for (i, &width) in widths.iter().enumerate() {
+        let start = period * Decimal::from(i) + EDGE;
+        points.extend([
+            (start, dec!(0)),
+            (start + EDGE, high),
+            (start + EDGE + width, high),
+            (start + dec!(2) * EDGE + width, dec!(0)),
+        ]);
+    }
+    points
+}
+
+impl<
+        T: Any,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for LockDetectorTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("lock_detector_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("lock_detector_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`LockDetectorTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct LockDetectorTbNodes {
+    lock: Node,
+}
+
+impl<T, PDK, C> ExportsNestedData for LockDetectorTb<T, PDK, C>
+where
+    LockDetectorTb<T, PDK, C>: Block,
+{
+    type NestedData = LockDetectorTbNodes;
+}
+
+impl<T, PDK: Schema, C: Copy> Schematic<Spectre> for LockDetectorTb<T, PDK, C>
+where
+    LockDetectorTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    LockDetector<T>: Schematic<PDK>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vdd = cell.signal("vdd", Signal);
+        let lock = cell.signal("lock", Signal);
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut);
+        cell.connect(dut.io().vdd, vdd);
+        cell.connect(dut.io().vss, io.vss);
+        cell.connect(dut.io().lock, lock);
+
+        let cycles = self.locked_cycles + self.step_cycles + self.settle_cycles;
+        let dn_widths = vec![self.reset_width; cycles as usize];
+        for (source, node) in [
+            (Vsource::dc(self.pvt.voltage), vdd),
+            (
+                Vsource::pwl(pulse_train(
+                    self.period,
+                    &self.up_widths(),
+                    self.pvt.voltage,
+                )),
+                dut.io().up,
+            ),
+            (
+                Vsource::pwl(pulse_train(self.period, &dn_widths, self.pvt.voltage)),
+                dut.io().dn,
+            ),
+        ] {
+            cell.instantiate_connected(source, TwoTerminalIoSchematic { p: node, n: io.vss });
+        }
+
+        Ok(LockDetectorTbNodes { lock })
+    }
+}
+
+/// The resulting waveforms of a [`LockDetectorTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct LockDetectorSim {
+    /// The simulation time.
+    pub t: tran::Time,
+    /// The lock detector output.
+    pub lock: tran::Voltage,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, LockDetectorSim> for LockDetectorTb<T, PDK, C>
+where
+    LockDetectorTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <LockDetectorSim as FromSaved<Spectre, Tran>>::SavedKey {
+        LockDetectorSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            lock: tran::Voltage::save(ctx, &cell.lock, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for LockDetectorTb<T, PDK, C>
+where
+    LockDetectorTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = LockDetectorResponse;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: LockDetectorSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: self.stop(),
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+        LockDetectorResponse::new(
+            Samples {
+                t: &wav.t,
+                v: &wav.lock,
+            },
+            self.pvt.voltage.to_f64().unwrap() / 2.,
+            self.step_start().to_f64().unwrap(),
+        )
+    }
+}
+
+/// The behavior of a lock detector across a frequency step.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LockDetectorResponse {
+    /// Whether `lock` was asserted when the frequency step occurred.
+    pub locked_before: bool,
+    /// The time from the frequency step until `lock` deasserted, in seconds,
+    /// or `None` if it never deasserted.
+    pub unlock_delay: Option<f64>,
+    /// The time from the frequency step until `lock` reasserted, in seconds,
+    /// or `None` if it never reasserted.
+    pub relock_delay: Option<f64>,
+    /// Whether `lock` was asserted at the end of the simulation.
+    pub locked_after: bool,
+}
+
+impl LockDetectorResponse {
+    /// Extracts the response from the `lock` waveform, given the logic `threshold` and
+    /// the time of the frequency step.
+    pub fn new(lock: Samples<'_>, threshold: f64, step: f64) -> Self {
+        let crossings = lock
+            .crossings(threshold)
+            .into_iter()
+            .filter(|&(t, _)| t > step)
+            .collect::<Vec<_>>();
+        let unlock = crossings.iter().position(|&(_, rising)| !rising);
+        let relock = unlock.and_then(|i| crossings[i..].iter().find(|&&(_, rising)| rising));
+        Self {
+            locked_before: lock.value_at(step) > threshold,
+            unlock_delay: unlock.map(|i| crossings[i].0 - step),
+            relock_delay: relock.map(|&(t, _)| t - step),
+            locked_after: lock.v.last().is_some_and(|&v| v > threshold),
+        }
+    }
+
+    /// Whether `lock` was asserted before the step, deasserted after it, and ended
+    /// asserted.
+    pub fn is_correct(&self) -> bool {
+        self.locked_before
+            && self.unlock_delay.is_some()
+            && self.relock_delay.is_some()
+            && self.locked_after
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charge_pump_mismatch_range() {
+        // Up current collapses near VDD and down current collapses near VSS.
+        let points = [
+            (0.1, 100e-6, 60e-6),
+            (0.3, 100e-6, 97e-6),
+            (0.9, 100e-6, 100e-6),
+            (1.5, 98e-6, 100e-6),
+            (1.7, 70e-6, 100e-6),
+        ]
+        .map(|(vout, i_up, i_dn)| ChargePumpMeasurement::new(vout, i_up, i_dn, 0., 200e-12));
+        let characteristic = ChargePumpCharacteristic::new(points.into_iter().rev());
+        assert_eq!(characteristic.points[0].vout, 0.1);
+        assert_eq!(characteristic.range(0.9, 0.05), Some((0.3, 1.5)));
+        assert_eq!(characteristic.range(2.0, 0.05), None);
+
+        // With no net charge, the glitch charge cancels the mismatch over the pulse.
+        let p = characteristic.points[1];
+        assert!((p.glitch_charge + 3e-6 * 200e-12).abs() < 1e-24);
+        assert!(characteristic.max_glitch_charge((0.3, 1.5)) > 0.);
+    }
+
+    #[test]
+    fn lock_detector_response() {
+        let t = [0., 1., 2., 3., 4., 5.];
+        let v = [0., 1.8, 1.8, 0., 0., 1.8];
+        let response = LockDetectorResponse::new(Samples { t: &t, v: &v }, 0.9, 1.5);
+        assert!(response.locked_before && response.locked_after);
+        assert_eq!(response.unlock_delay, Some(1.));
+        assert_eq!(response.relock_delay, Some(3.));
+        assert!(response.is_correct());
+
+        let points = pulse_train(dec!(10), &[dec!(1), dec!(2)], dec!(1.8));
+        assert_eq!(points.len(), 9);
+        assert!(points.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+}
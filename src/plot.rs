@@ -0,0 +1,376 @@
+//! SVG plots of characterization results.
+
+use crate::driver::tb::DriverAcSims;
+use std::fmt::Write;
+use std::path::Path;
+
+const WIDTH: f64 = 640.;
+const HEIGHT: f64 = 480.;
+const MARGIN_LEFT: f64 = 80.;
+const MARGIN_RIGHT: f64 = 160.;
+const MARGIN_TOP: f64 = 40.;
+const MARGIN_BOT: f64 = 60.;
+const NUM_TICKS: usize = 5;
+const COLORS: [&str; 8] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+];
+
+/// The scale of a plot axis.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scale {
+    /// A linear scale.
+    #[default]
+    Linear,
+    /// A base-10 logarithmic scale.
+    ///
+    /// Non-positive values are not drawn.
+    Log,
+}
+
+impl Scale {
+    fn apply(&self, x: f64) -> Option<f64> {
+        match self {
+            Scale::Linear => Some(x),
+            Scale::Log => (x > 0.).then(|| x.log10()),
+        }
+    }
+
+    fn invert(&self, x: f64) -> f64 {
+        match self {
+            Scale::Linear => x,
+            Scale::Log => 10f64.powf(x),
+        }
+    }
+}
+
+/// A set of points drawn as a connected line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Series {
+    /// The label shown in the legend.
+    ///
+    /// Unlabeled series are not shown in the legend.
+    pub label: Option<String>,
+    /// The points of the series.
+    pub points: Vec<(f64, f64)>,
+}
+
+impl Series {
+    /// Creates a new labeled [`Series`].
+    pub fn new(label: impl Into<String>, points: Vec<(f64, f64)>) -> Self {
+        Self {
+            label: Some(label.into()),
+            points,
+        }
+    }
+
+    /// Creates a new [`Series`] that is not shown in the legend.
+    pub fn unlabeled(points: Vec<(f64, f64)>) -> Self {
+        Self {
+            label: None,
+            points,
+        }
+    }
+}
+
+/// A two-dimensional line plot.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Plot {
+    /// The plot title.
+    pub title: String,
+    /// The x-axis label.
+    pub x_label: String,
+    /// The y-axis label.
+    pub y_label: String,
+    /// The x-axis scale.
+    pub x_scale: Scale,
+    /// The y-axis scale.
+    pub y_scale: Scale,
+    /// The series to draw.
+    pub series: Vec<Series>,
+}
+
+impl Plot {
+    /// Creates a new, empty [`Plot`] with linear axes.
+    pub fn new(
+        title: impl Into<String>,
+        x_label: impl Into<String>,
+        y_label: impl Into<String>,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            x_label: x_label.into(),
+            y_label: y_label.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the x-axis scale.
+    pub fn with_x_scale(mut self, scale: Scale) -> Self {
+        self.x_scale = scale;
+        self
+    }
+
+    /// Sets the y-axis scale.
+    pub fn with_y_scale(mut self, scale: Scale) -> Self {
+        self.y_scale = scale;
+        self
+    }
+
+    /// Adds a series to the plot.
+    pub fn with_series(mut self, series: Series) -> Self {
+        self.series.push(series);
+        self
+    }
+
+    /// Returns the range of the scaled data along each axis.
+    fn bounds(&self) -> ((f64, f64), (f64, f64)) {
+        let mut x = (f64::INFINITY, f64::NEG_INFINITY);
+        let mut y = (f64::INFINITY, f64::NEG_INFINITY);
+        for &(px, py) in self.series.iter().flat_map(|s| s.points.iter()) {
+            if let (Some(px), Some(py)) = (self.x_scale.apply(px), self.y_scale.apply(py)) {
+                x = (x.0.min(px), x.1.max(px));
+                y = (y.0.min(py), y.1.max(py));
+            }
+        }
+        let pad = |(lo, hi): (f64, f64)| {
+            if !lo.is_finite() {
+                (0., 1.)
+            } else if lo == hi {
+                (lo - 0.5, hi + 0.5)
+            } else {
+                (lo, hi)
+            }
+        };
+        (pad(x), pad(y))
+    }
+
+    /// Renders the plot as an SVG document.
+    pub fn to_svg(&self) -> String {
+        let ((x0, x1), (y0, y1)) = self.bounds();
+        let plot_w = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+        let plot_h = HEIGHT - MARGIN_TOP - MARGIN_BOT;
+        let to_px = |x: f64| MARGIN_LEFT + (x - x0) / (x1 - x0) * plot_w;
+        let to_py = |y: f64| MARGIN_TOP + (y1 - y) / (y1 - y0) * plot_h;
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" font-family="sans-serif" font-size="12">"#
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<rect width="{WIDTH}" height="{HEIGHT}" fill="white"/>"#
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<text x="{}" y="{}" text-anchor="middle" font-size="16">{}</text>"#,
+            MARGIN_LEFT + plot_w / 2.,
+            MARGIN_TOP / 2. + 6.,
+            escape(&self.title)
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<rect x="{MARGIN_LEFT}" y="{MARGIN_TOP}" width="{plot_w}" height="{plot_h}" fill="none" stroke="black"/>"#
+        )
+        .unwrap();
+
+        for i in 0..NUM_TICKS {
+            let frac = i as f64 / (NUM_TICKS - 1) as f64;
+            let x = x0 + frac * (x1 - x0);
+            let y = y0 + frac * (y1 - y0);
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+                to_px(x),
+                MARGIN_TOP + plot_h + 18.,
+                format_tick(self.x_scale.invert(x))
+            )
+            .unwrap();
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#,
+                MARGIN_LEFT - 6.,
+                to_py(y) + 4.,
+                format_tick(self.y_scale.invert(y))
+            )
+            .unwrap();
+        }
+        writeln!(
+            svg,
+            r#"<text x="{}" y="{}" text-anchor="middle">{}</text>"#,
+            MARGIN_LEFT + plot_w / 2.,
+            HEIGHT - 16.,
+            escape(&self.x_label)
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<text x="16" y="{0}" text-anchor="middle" transform="rotate(-90 16 {0})">{1}</text>"#,
+            MARGIN_TOP + plot_h / 2.,
+            escape(&self.y_label)
+        )
+        .unwrap();
+
+        let mut legend_y = MARGIN_TOP + 10.;
+        for (i, series) in self.series.iter().enumerate() {
+            let color = COLORS[i % COLORS.len()];
+            let points = series
+                .points
+                .iter()
+                .filter_map(|&(x, y)| Some((self.x_scale.apply(x)?, self.y_scale.apply(y)?)))
+                .map(|(x, y)| format!("{:.2},{:.2}", to_px(x), to_py(y)))
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(
+                svg,
+                r#"<polyline points="{points}" fill="none" stroke="{color}" stroke-width="1.5"/>"#
+            )
+            .unwrap();
+            if let Some(label) = &series.label {
+                let lx = WIDTH - MARGIN_RIGHT + 10.;
+                writeln!(
+                    svg,
+                    r#"<line x1="{lx}" y1="{legend_y}" x2="{}" y2="{legend_y}" stroke="{color}" stroke-width="2"/>"#,
+                    lx + 20.
+                )
+                .unwrap();
+                writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}">{}</text>"#,
+                    lx + 26.,
+                    legend_y + 4.,
+                    escape(label)
+                )
+                .unwrap();
+                legend_y += 16.;
+            }
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Writes the plot to an SVG file at `path`.
+    pub fn write_svg(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.to_svg())
+    }
+}
+
+/// Plots driver pull-up and pull-down resistance against code at the given frequency index.
+///
+/// Draws one series per input voltage for each of the pull-up and pull-down networks.
+pub fn impedance_vs_code(sims: &DriverAcSims, freq_idx: usize) -> Plot {
+    let mut plot = Plot::new(
+        format!(
+            "Driver impedance at {} Hz",
+            format_tick(sims.freq[freq_idx])
+        ),
+        "Code",
+        "Resistance (ohm)",
+    );
+    for (side, codes, r) in [
+        ("pu", &sims.pu_codes, &sims.r_pu),
+        ("pd", &sims.pd_codes, &sims.r_pd),
+    ] {
+        for (vin_idx, vin) in sims.vin.iter().enumerate() {
+            let points = codes
+                .iter()
+                .zip(r)
+                .map(|(&code, r)| (code as f64, r[vin_idx][freq_idx]))
+                .collect();
+            plot = plot.with_series(Series::new(format!("{side}, vin={vin}"), points));
+        }
+    }
+    plot
+}
+
+/// Plots the magnitude of a frequency response, in dB, against frequency on a log axis.
+pub fn bode_magnitude(title: impl Into<String>, freq: &[f64], magnitude: &[f64]) -> Plot {
+    let points = freq
+        .iter()
+        .zip(magnitude)
+        .map(|(&f, &m)| (f, 20. * m.abs().log10()))
+        .collect();
+    Plot::new(title, "Frequency (Hz)", "Magnitude (dB)")
+        .with_x_scale(Scale::Log)
+        .with_series(Series::unlabeled(points))
+}
+
+/// Plots an eye diagram by folding a transient waveform into windows of `period` seconds.
+///
+/// Time is measured relative to `offset`; samples before `offset` are skipped.
+/// Each window is drawn as a separate, unlabeled series.
+pub fn eye_diagram(
+    title: impl Into<String>,
+    t: &[f64],
+    v: &[f64],
+    period: f64,
+    offset: f64,
+) -> Plot {
+    let mut plot = Plot::new(title, "Time (s)", "Voltage (V)");
+    let mut window = None;
+    let mut points = Vec::new();
+    for (&t, &v) in t.iter().zip(v) {
+        if t < offset {
+            continue;
+        }
+        let n = ((t - offset) / period).floor() as i64;
+        if window != Some(n) {
+            if points.len() > 1 {
+                plot = plot.with_series(Series::unlabeled(std::mem::take(&mut points)));
+            }
+            points.clear();
+            window = Some(n);
+        }
+        points.push((t - offset - n as f64 * period, v));
+    }
+    if points.len() > 1 {
+        plot = plot.with_series(Series::unlabeled(points));
+    }
+    plot
+}
+
+/// Formats an axis tick value compactly.
+fn format_tick(x: f64) -> String {
+    if x != 0. && (x.abs() >= 1e4 || x.abs() < 1e-2) {
+        format!("{x:.2e}")
+    } else {
+        format!("{x:.3}")
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    }
+}
+
+/// Escapes text for inclusion in SVG.
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_eye_diagram() {
+        let t = (0..40).map(|i| i as f64 * 0.1).collect::<Vec<_>>();
+        let v = t.iter().map(|t| t.sin()).collect::<Vec<_>>();
+        let plot = eye_diagram("eye", &t, &v, 1., 0.);
+        assert_eq!(plot.series.len(), 4);
+        assert!(plot
+            .series
+            .iter()
+            .flat_map(|s| s.points.iter())
+            .all(|&(t, _)| (0. ..1.).contains(&t)));
+        assert_eq!(plot.to_svg().matches("<polyline").count(), 4);
+    }
+}
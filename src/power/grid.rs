@@ -0,0 +1,558 @@
+//! Module-level power grids.
+//!
+//! Each macro straps its own supplies up to its top routing layer. When macros are
+//! assembled into a full module, [`PowerGrid`] straps VDD, VSS, and VDDIO over the
+//! whole floorplan on the top two metals and drops via stacks onto the rail pins of
+//! every macro underneath.
+//!
+//! The straps of each net are periodic on each layer, so the grid of a single net can
+//! be handed directly to [`ir_drop::analyze`](crate::analysis::ir_drop::analyze) with
+//! [`PowerGrid::ir_drop_params`].
+
+use atoll::abs::TrackCoord;
+use atoll::route::ViaMaker;
+use atoll::TileBuilder;
+use serde::{Deserialize, Serialize};
+use substrate::geometry::bbox::Bbox;
+use substrate::geometry::dir::Dir;
+use substrate::geometry::point::Point;
+use substrate::geometry::rect::Rect;
+use substrate::geometry::span::Span;
+use substrate::geometry::transform::Translate;
+use substrate::layout::element::Shape;
+use substrate::pdk::Pdk;
+
+use crate::analysis::ir_drop::{IrDropParams, Load, MeshLayer, MetalStack};
+use crate::params::{check_at_least, check_positive, ParamsError};
+use crate::via::{self, NetClass};
+
+/// A supply net carried by a [`PowerGrid`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GridNet {
+    /// The core supply.
+    Vdd,
+    /// Ground.
+    Vss,
+    /// The I/O supply of the drivers.
+    Vddio,
+}
+
+impl GridNet {
+    /// The name of the net.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GridNet::Vdd => "vdd",
+            GridNet::Vss => "vss",
+            GridNet::Vddio => "vddio",
+        }
+    }
+}
+
+/// The straps of a [`PowerGrid`] on one layer.
+///
+/// Straps are drawn in groups of one strap per net, in the order of
+/// [`PowerGridParams::nets`]. Groups repeat every `pitch`, starting `offset` from the
+/// lower or left edge of the floorplan.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct GridLayerParams {
+    /// The width of each strap.
+    pub width: i64,
+    /// The spacing between adjacent straps in a group.
+    pub spacing: i64,
+    /// The distance between adjacent groups.
+    pub pitch: i64,
+    /// The position of the lower or left edge of the first group.
+    pub offset: i64,
+}
+
+/// The parameters of a [`PowerGrid`].
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct PowerGridParams {
+    /// The upper of the two ATOLL layers used by the grid.
+    ///
+    /// The grid is drawn on `top_layer - 1` and `top_layer`.
+    pub top_layer: usize,
+    /// The nets in each group of straps.
+    pub nets: Vec<GridNet>,
+    /// The straps on `top_layer - 1`.
+    pub lower: GridLayerParams,
+    /// The straps on `top_layer`.
+    pub upper: GridLayerParams,
+}
+
+impl PowerGridParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> PowerGridParamsBuilder {
+        PowerGridParamsBuilder::default()
+    }
+
+    fn layer(&self, i: usize) -> GridLayerParams {
+        [self.lower, self.upper][i]
+    }
+}
+
+/// A builder for [`PowerGridParams`].
+///
+/// Defaults to VDD, VSS, and VDDIO on SKY130 met4 and met5 with 3.2 um straps and a
+/// 40 um group pitch.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct PowerGridParamsBuilder {
+    params: PowerGridParams,
+}
+
+impl Default for PowerGridParamsBuilder {
+    fn default() -> Self {
+        let layer = GridLayerParams {
+            width: 3_200,
+            spacing: 1_600,
+            pitch: 40_000,
+            offset: 0,
+        };
+        Self {
+            params: PowerGridParams {
+                top_layer: 5,
+                nets: vec![GridNet::Vdd, GridNet::Vss, GridNet::Vddio],
+                lower: layer,
+                upper: layer,
+            },
+        }
+    }
+}
+
+impl PowerGridParamsBuilder {
+    /// Sets the upper of the two grid layers.
+    pub fn top_layer(mut self, top_layer: usize) -> Self {
+        self.params.top_layer = top_layer;
+        self
+    }
+
+    /// Sets the nets in each group of straps.
+    pub fn nets(mut self, nets: Vec<GridNet>) -> Self {
+        self.params.nets = nets;
+        self
+    }
+
+    /// Sets the straps on the lower grid layer.
+    pub fn lower(mut self, lower: GridLayerParams) -> Self {
+        self.params.lower = lower;
+        self
+    }
+
+    /// Sets the straps on the upper grid layer.
+    pub fn upper(mut self, upper: GridLayerParams) -> Self {
+        self.params.upper = upper;
+        self
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<PowerGridParams, ParamsError> {
+        let p = &self.params;
+        check_at_least("top_layer", p.top_layer as i64, 2)?;
+        check_at_least("nets", p.nets.len() as i64, 1)?;
+        for (fields, layer) in [
+            (["lower.width", "lower.spacing", "lower.pitch"], p.lower),
+            (["upper.width", "upper.spacing", "upper.pitch"], p.upper),
+        ] {
+            check_positive(fields[0], layer.width)?;
+            check_positive(fields[1], layer.spacing)?;
+            // Adjacent groups keep at least the spacing of the straps within a group.
+            check_at_least(
+                fields[2],
+                layer.pitch,
+                p.nets.len() as i64 * (layer.width + layer.spacing),
+            )?;
+        }
+        Ok(self.params)
+    }
+}
+
+/// A supply rail pin of a placed macro, in module coordinates.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RailPin {
+    /// The net of the rail.
+    pub net: GridNet,
+    /// The ATOLL layer of the pin.
+    pub layer: usize,
+    /// The pin shape.
+    pub rect: Rect,
+}
+
+/// A macro placed in a module floorplan.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GridMacro {
+    /// The instance name, used in error messages.
+    pub name: String,
+    /// The supply rail pins of the macro.
+    pub rails: Vec<RailPin>,
+}
+
+/// An error produced while building a [`PowerGrid`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PowerGridError {
+    /// A rail pin lies on or above the lower grid layer.
+    #[error("{inst}: {net} rail on layer {layer} is not below the grid layer {grid_layer}")]
+    RailAboveGrid {
+        /// The macro instance name.
+        inst: String,
+        /// The rail net name.
+        net: &'static str,
+        /// The ATOLL layer of the rail pin.
+        layer: usize,
+        /// The lower grid layer.
+        grid_layer: usize,
+    },
+    /// No grid strap of the right net crosses a rail pin.
+    #[error("{inst}: no {net} strap crosses the rail at {rect:?}")]
+    Unconnected {
+        /// The macro instance name.
+        inst: String,
+        /// The rail net name.
+        net: &'static str,
+        /// The rail pin shape.
+        rect: Rect,
+    },
+}
+
+/// A strap of a [`PowerGrid`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Strap {
+    /// The net of the strap.
+    pub net: GridNet,
+    /// The ATOLL layer of the strap.
+    pub layer: usize,
+    /// The strap shape.
+    pub rect: Rect,
+}
+
+/// A via stack connecting a [`PowerGrid`] strap to the shape below it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GridVia {
+    /// The net of the via stack.
+    pub net: GridNet,
+    /// The bottom ATOLL layer of the stack.
+    pub bot: usize,
+    /// The top ATOLL layer of the stack.
+    pub top: usize,
+    /// The overlap of the connected shapes, over which vias are arrayed.
+    pub rect: Rect,
+}
+
+/// The geometry of a module-level power grid.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PowerGrid {
+    /// The grid parameters.
+    pub params: PowerGridParams,
+    /// The extent of the module floorplan.
+    pub bounds: Rect,
+    /// The straps, lower layer first.
+    pub straps: Vec<Strap>,
+    /// The via stacks between the two grid layers and down to the macro rails.
+    pub vias: Vec<GridVia>,
+}
+
+/// Returns the direction of the straps on ATOLL layer `layer`.
+///
+/// Odd ATOLL layers run horizontally and even layers run vertically.
+fn layer_dir(layer: usize) -> Dir {
+    if layer % 2 == 1 {
+        Dir::Horiz
+    } else {
+        Dir::Vert
+    }
+}
+
+/// Returns the overlap of `a` and `b`, if they share any area.
+fn overlap(a: Rect, b: Rect) -> Option<Rect> {
+    let (left, bot) = (a.left().max(b.left()), a.bot().max(b.bot()));
+    let (right, top) = (a.right().min(b.right()), a.top().min(b.top()));
+    (left < right && bot < top).then(|| Rect::from_sides(left, bot, right, top))
+}
+
+/// Returns the centers of an array of vias of size `footprint` over `rect`, spaced by
+/// one footprint in each direction and centered in `rect`.
+///
+/// Always returns at least the center of `rect`.
+fn via_array(rect: Rect, footprint: Rect) -> Vec<Point> {
+    let centers = |span: Span, size: i64| {
+        let n = ((span.length() + size) / (2 * size)).max(1);
+        let start = span.center() - (n - 1) * size;
+        (0..n).map(move |i| start + 2 * i * size)
+    };
+    centers(rect.hspan(), footprint.width())
+        .flat_map(|x| centers(rect.vspan(), footprint.height()).map(move |y| Point::new(x, y)))
+        .collect()
+}
+
+impl PowerGrid {
+    /// Straps the module floorplan `bounds` and connects the grid to the rails of `macros`.
+    ///
+    /// Every rail pin must be crossed by at least one strap of its net on the lower grid
+    /// layer.
+    pub fn new(
+        params: PowerGridParams,
+        bounds: Rect,
+        macros: &[GridMacro],
+    ) -> Result<Self, PowerGridError> {
+        let lower = params.top_layer - 1;
+        let mut grid = Self {
+            params,
+            bounds,
+            straps: Vec::new(),
+            vias: Vec::new(),
+        };
+        for i in 0..2 {
+            for &net in grid.params.nets.iter() {
+                let mesh = grid.mesh_layer(i, net, 0.);
+                let layer = lower + i;
+                for pos in mesh.positions(bounds) {
+                    grid.straps.push(Strap {
+                        net,
+                        layer,
+                        rect: Rect::from_dir_spans(
+                            mesh.dir,
+                            bounds.span(mesh.dir),
+                            Span::from_center_span(pos, mesh.width),
+                        ),
+                    });
+                }
+            }
+        }
+
+        let (bottom, top): (Vec<_>, Vec<_>) =
+            grid.straps.iter().copied().partition(|s| s.layer == lower);
+        for a in bottom.iter() {
+            for b in top.iter().filter(|b| b.net == a.net) {
+                if let Some(rect) = overlap(a.rect, b.rect) {
+                    grid.vias.push(GridVia {
+                        net: a.net,
+                        bot: lower,
+                        top: lower + 1,
+                        rect,
+                    });
+                }
+            }
+        }
+
+        for inst in macros.iter() {
+            for rail in inst.rails.iter() {
+                if rail.layer >= lower {
+                    return Err(PowerGridError::RailAboveGrid {
+                        inst: inst.name.clone(),
+                        net: rail.net.name(),
+                        layer: rail.layer,
+                        grid_layer: lower,
+                    });
+                }
+                let vias = bottom
+                    .iter()
+                    .filter(|s| s.net == rail.net)
+                    .filter_map(|s| overlap(s.rect, rail.rect))
+                    .map(|rect| GridVia {
+                        net: rail.net,
+                        bot: rail.layer,
+                        top: lower,
+                        rect,
+                    })
+                    .collect::<Vec<_>>();
+                if vias.is_empty() {
+                    return Err(PowerGridError::Unconnected {
+                        inst: inst.name.clone(),
+                        net: rail.net.name(),
+                        rect: rail.rect,
+                    });
+                }
+                grid.vias.extend(vias);
+            }
+        }
+
+        Ok(grid)
+    }
+
+    /// The straps of `net` on grid layer `i`, with the given sheet resistance.
+    fn mesh_layer(&self, i: usize, net: GridNet, sheet_resistance: f64) -> MeshLayer {
+        let p = self.params.layer(i);
+        let slot = self.params.nets.iter().position(|&n| n == net).unwrap() as i64;
+        MeshLayer {
+            dir: layer_dir(self.params.top_layer - 1 + i),
+            pitch: p.pitch,
+            offset: p.offset + slot * (p.width + p.spacing) + p.width / 2,
+            width: p.width,
+            sheet_resistance,
+        }
+    }
+
+    /// The straps of `net`.
+    pub fn net_straps(&self, net: GridNet) -> impl Iterator<Item = &Strap> {
+        self.straps.iter().filter(move |s| s.net == net)
+    }
+
+    /// The mesh of `net` and the via resistance between its two layers, as used by the
+    /// IR-drop analysis.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid does not carry `net`.
+    pub fn mesh<T: MetalStack>(&self, net: GridNet) -> (Vec<MeshLayer>, Vec<f64>) {
+        let lower = self.params.top_layer - 1;
+        assert!(
+            self.params.nets.contains(&net),
+            "the grid does not carry {}",
+            net.name()
+        );
+        let layers = (0..2)
+            .map(|i| self.mesh_layer(i, net, T::sheet_resistance(lower + i)))
+            .collect();
+        (layers, vec![T::via_resistance(lower)])
+    }
+
+    /// The IR-drop analysis of `net` with the given loads on the lower grid layer.
+    ///
+    /// The upper grid layer is treated as the ideal supply, so the result is the drop
+    /// across the grid alone, not including the bumps or package.
+    pub fn ir_drop_params<T: MetalStack>(&self, net: GridNet, loads: Vec<Load>) -> IrDropParams {
+        let (layers, via_resistance) = self.mesh::<T>(net);
+        IrDropParams {
+            bounds: self.bounds,
+            layers,
+            via_resistance,
+            loads,
+        }
+    }
+
+    /// Draws the straps and via arrays into `cell`.
+    ///
+    /// The straps are not routed by ATOLL, so this should be called after all macros
+    /// have been placed. Each crossing gets at least as many vias as the supply
+    /// [`ViaPolicy`](crate::via::ViaPolicy) requests.
+    pub fn draw<PDK: Pdk, V: ViaMaker<PDK>>(
+        &self,
+        cell: &mut TileBuilder<'_, PDK>,
+        via_maker: &V,
+    ) -> substrate::error::Result<()> {
+        for strap in self.straps.iter() {
+            cell.layout.draw(Shape::new(
+                cell.layer_stack.layers[strap.layer].id,
+                strap.rect,
+            ))?;
+        }
+        for via in self.vias.iter() {
+            for layer in via.bot + 1..via.top + 1 {
+                let shapes =
+                    via_maker.draw_via(cell.ctx().clone(), TrackCoord { layer, x: 0, y: 0 });
+                let footprint = shapes
+                    .iter()
+                    .map(|shape| shape.bbox_rect())
+                    .reduce(|a, b| a.union(b))
+                    .unwrap();
+                let mut centers = via_array(via.rect, footprint);
+                let redundancy = via::redundancy(cell.ctx(), NetClass::Supply);
+                if centers.len() < redundancy.cuts() {
+                    centers = redundancy
+                        .offsets(footprint)
+                        .into_iter()
+                        .map(|offset| via.rect.center() + offset)
+                        .collect();
+                }
+                for center in centers {
+                    for shape in shapes.iter() {
+                        cell.layout
+                            .draw(shape.clone().translate(center - footprint.center()))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::ir_drop::analyze;
+
+    struct Stack;
+
+    impl MetalStack for Stack {
+        fn sheet_resistance(_layer: usize) -> f64 {
+            0.03
+        }
+        fn via_resistance(_below: usize) -> f64 {
+            0.5
+        }
+    }
+
+    fn rail(net: GridNet, rect: Rect) -> RailPin {
+        RailPin {
+            net,
+            layer: 3,
+            rect,
+        }
+    }
+
+    #[test]
+    fn power_grid_connects_macro_rails() {
+        let params = PowerGridParams::builder().build().unwrap();
+        let bounds = Rect::from_sides(0, 0, 100_000, 100_000);
+        // The first group of vertical met4 straps sits at x = 1.6, 6.4, and 11.2 um.
+        let inst = GridMacro {
+            name: "lane0".to_string(),
+            rails: vec![
+                rail(GridNet::Vdd, Rect::from_sides(0, 10_000, 20_000, 11_000)),
+                rail(GridNet::Vss, Rect::from_sides(0, 12_000, 20_000, 13_000)),
+            ],
+        };
+        let grid = PowerGrid::new(params.clone(), bounds, &[inst]).unwrap();
+
+        // Three groups fit on each layer for each of the three nets.
+        assert_eq!(grid.straps.len(), 2 * 3 * 3);
+        assert_eq!(grid.net_straps(GridNet::Vddio).count(), 6);
+        // Each net crosses itself at every group intersection, and each rail is
+        // crossed by one strap of its net.
+        assert_eq!(grid.vias.len(), 3 * 9 + 2);
+        let rail_via = grid.vias.iter().find(|v| v.bot == 3).unwrap();
+        assert_eq!(rail_via.net, GridNet::Vdd);
+        assert_eq!(rail_via.rect, Rect::from_sides(0, 10_000, 3_200, 11_000));
+
+        let (layers, vias) = grid.mesh::<Stack>(GridNet::Vss);
+        assert_eq!(layers[0].offset, 6_400);
+        assert_eq!(vias, vec![0.5]);
+        let report = analyze(&grid.ir_drop_params::<Stack>(
+            GridNet::Vdd,
+            vec![Load {
+                rect: Rect::from_sides(0, 0, 100_000, 100_000),
+                current: 1e-2,
+            }],
+        ));
+        assert!(report.worst_droop > 0.);
+
+        let missed = GridMacro {
+            name: "lane1".to_string(),
+            rails: vec![rail(
+                GridNet::Vdd,
+                Rect::from_sides(20_000, 0, 30_000, 1_000),
+            )],
+        };
+        assert!(matches!(
+            PowerGrid::new(params, bounds, &[missed]),
+            Err(PowerGridError::Unconnected { .. })
+        ));
+    }
+
+    #[test]
+    fn via_array_fills_overlap() {
+        let footprint = Rect::from_sides(-100, -100, 100, 100);
+        let centers = via_array(Rect::from_sides(0, 0, 1_000, 200), footprint);
+        assert_eq!(
+            centers,
+            vec![
+                Point::new(100, 100),
+                Point::new(500, 100),
+                Point::new(900, 100)
+            ]
+        );
+        assert_eq!(
+            via_array(Rect::from_sides(0, 0, 50, 50), footprint),
+            vec![Point::new(25, 25)]
+        );
+    }
+}
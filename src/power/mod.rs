@@ -0,0 +1,402 @@
+//! Supply protection.
+//!
+//! [`RcClampTile`] is a rail clamp meant to be arrayed along the module supply rails.
+//! An RC timer watches VDD through a chain of inverters. A fast ESD ramp leaves the
+//! timer node behind, so the chain turns on a large NMOS across the rails. A normal
+//! power-up is slow enough for the timer to follow VDD, so the clamp stays off.
+//!
+//! [`grid::PowerGrid`] straps the supplies over a full module.
+
+pub mod grid;
+pub mod tb;
+
+use crate::driver::HorizontalDriverImpl;
+use crate::params::{check_at_least, check_positive, setters, ParamsError};
+use crate::pll::loop_filter::{snap_resistor, LoopFilterRules, ResistorSize};
+use crate::tiles::{ResistorConn, ResistorIoSchematic, TapIoSchematic, TileKind};
+use atoll::{IoBuilder, Orientation, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::geometry::align::AlignMode;
+use substrate::io::{InOut, Io, MosIoSchematic, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+/// The most series legs used by the timer resistor of a designed [`RcTimerParams`].
+const MAX_TIMER_LEGS: i64 = 8;
+
+/// Technology parameters of the MOS capacitor used by an RC clamp timer.
+///
+/// The timer resistor is sized with the same rules as the loop filter resistor.
+pub trait RcClampRules: LoopFilterRules {
+    /// The gate capacitance of the MOS capacitor, in farads per square nanometer.
+    const GATE_CAP_PER_AREA: f64;
+    /// The finger width of the MOS capacitor, in nanometers.
+    const MOSCAP_W: i64;
+    /// The channel length of the MOS capacitor, in nanometers.
+    const MOSCAP_L: i64;
+}
+
+/// An error produced while designing an RC clamp.
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+pub enum RcClampError {
+    /// The requested time constant was not positive.
+    #[error("time constant must be positive, got {0}")]
+    TimeConstant(f64),
+}
+
+/// The interface to an [`RcClampTile`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct RcClampIo {
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The RC timer of an [`RcClampTile`].
+///
+/// A series resistor from VDD charges an NMOS gate capacitor to VSS.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct RcTimerParams {
+    /// The timer resistor.
+    pub res: ResistorSize,
+    /// The number of fingers of the MOS capacitor.
+    pub cap_nf: i64,
+    /// The finger width of the MOS capacitor.
+    pub cap_w: i64,
+    /// The channel length of the MOS capacitor.
+    pub cap_l: i64,
+}
+
+impl RcTimerParams {
+    /// Sizes a timer with time constant `tau`, in seconds.
+    ///
+    /// The resistor is cheaper per unit of time constant than the gate capacitor, so
+    /// the capacitor gets the fewest fingers that keep the resistor within eight legs of
+    /// the longest allowed length.
+    pub fn design<T: RcClampRules>(tau: f64) -> Result<Self, RcClampError> {
+        if tau.is_nan() || tau <= 0. {
+            return Err(RcClampError::TimeConstant(tau));
+        }
+        let unit_cap = T::GATE_CAP_PER_AREA * (T::MOSCAP_W * T::MOSCAP_L) as f64;
+        let max_res =
+            T::RES_SHEET_RESISTANCE * (MAX_TIMER_LEGS * T::RES_MAX_L) as f64 / T::RES_W as f64;
+        // Keep an even finger count so that the capacitor matches the width of its tap.
+        let cap_nf = 2 * (tau / (max_res * unit_cap) / 2.).ceil().max(1.) as i64;
+        Ok(Self {
+            res: snap_resistor::<T>(tau / (cap_nf as f64 * unit_cap)),
+            cap_nf,
+            cap_w: T::MOSCAP_W,
+            cap_l: T::MOSCAP_L,
+        })
+    }
+
+    /// The time constant of the timer, in seconds.
+    pub fn time_constant<T: RcClampRules>(&self) -> f64 {
+        let r = T::RES_SHEET_RESISTANCE * (self.res.legs * self.res.l) as f64 / self.res.w as f64;
+        let c = T::GATE_CAP_PER_AREA * (self.cap_nf * self.cap_w * self.cap_l) as f64;
+        r * c
+    }
+}
+
+/// The parameters of the [`RcClampTile`] layout generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct RcClampParams {
+    /// The RC timer.
+    pub timer: RcTimerParams,
+    /// The NMOS finger width of the inverter chain; the PMOS fingers are twice as wide.
+    pub inv_w: i64,
+    /// The number of fingers of each inverter, from the timer to the clamp.
+    pub inv_nf: [i64; 3],
+    /// The finger width of the clamp NMOS.
+    pub clamp_w: i64,
+    /// The number of fingers of the clamp NMOS.
+    pub clamp_nf: i64,
+}
+
+impl RcClampParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> RcClampParamsBuilder {
+        RcClampParamsBuilder::default()
+    }
+}
+
+/// A builder for [`RcClampParams`].
+///
+/// Defaults to a timer of about 1 us in SKY130 and a 500 um clamp. Use
+/// [`RcTimerParams::design`] to size the timer for another time constant.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct RcClampParamsBuilder {
+    params: RcClampParams,
+}
+
+impl Default for RcClampParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: RcClampParams {
+                timer: RcTimerParams {
+                    res: ResistorSize {
+                        legs: 8,
+                        w: 1_000,
+                        l: 20_000,
+                    },
+                    cap_nf: 24,
+                    cap_w: 4_000,
+                    cap_l: 4_000,
+                },
+                inv_w: 1_000,
+                inv_nf: [2, 4, 16],
+                clamp_w: 5_000,
+                clamp_nf: 100,
+            },
+        }
+    }
+}
+
+impl RcClampParamsBuilder {
+    setters! {
+        /// Sets the RC timer.
+        timer: RcTimerParams,
+        /// Sets the NMOS finger width of the inverter chain.
+        inv_w: i64,
+        /// Sets the number of fingers of each inverter.
+        inv_nf: [i64; 3],
+        /// Sets the finger width of the clamp NMOS.
+        clamp_w: i64,
+        /// Sets the number of fingers of the clamp NMOS.
+        clamp_nf: i64,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<RcClampParams, ParamsError> {
+        let p = &self.params;
+        for (field, value) in [
+            ("timer.res.legs", p.timer.res.legs),
+            ("timer.res.w", p.timer.res.w),
+            ("timer.res.l", p.timer.res.l),
+            ("timer.cap_w", p.timer.cap_w),
+            ("timer.cap_l", p.timer.cap_l),
+            ("inv_w", p.inv_w),
+            ("clamp_w", p.clamp_w),
+        ] {
+            check_positive(field, value)?;
+        }
+        for (field, value) in [
+            ("timer.cap_nf", p.timer.cap_nf),
+            ("inv_nf", p.inv_nf.into_iter().min().unwrap()),
+            ("clamp_nf", p.clamp_nf),
+        ] {
+            check_at_least(field, value, 2)?;
+        }
+        Ok(self.params)
+    }
+}
+
+/// An RC-triggered rail clamp.
+///
+/// From left to right, the layout has a column with the timer resistor above the MOS
+/// capacitor, one column per inverter with the PMOS above the NMOS, and the clamp NMOS.
+/// Each column has its own taps. The three inverters make the clamp gate high while
+/// the timer node lags VDD.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct RcClampTile<T>(
+    RcClampParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> RcClampTile<T> {
+    /// Creates a new [`RcClampTile`].
+    pub fn new(params: RcClampParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The clamp parameters.
+    pub fn params(&self) -> RcClampParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for RcClampTile<T> {
+    type Io = RcClampIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("rc_clamp")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("rc_clamp", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for RcClampTile<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for RcClampTile<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK> for RcClampTile<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let p = self.0;
+        let (vdd, vss) = (io.schematic.vdd, io.schematic.vss);
+        let rc = cell.signal("rc", Signal);
+        let trigger = cell.signal("trigger", Signal);
+
+        let res = cell.generate_connected(
+            T::resistor(
+                p.timer.res.legs,
+                p.timer.res.w,
+                p.timer.res.l,
+                ResistorConn::Series,
+            ),
+            ResistorIoSchematic {
+                p: vdd,
+                n: rc,
+                b: vdd,
+            },
+        );
+        let mut cap = cell.generate_connected(
+            T::mos(
+                TileKind::N,
+                p.timer.cap_nf,
+                p.timer.cap_w,
+                Some(p.timer.cap_l),
+            ),
+            MosIoSchematic {
+                d: vss,
+                g: rc,
+                s: vss,
+                b: vss,
+            },
+        );
+        let mut cap_tap = cell.generate_connected(
+            T::tap(TileKind::P, p.timer.cap_nf),
+            TapIoSchematic { x: vss },
+        );
+        cap.align_mut(&res, AlignMode::Left, 0);
+        cap.align_mut(&res, AlignMode::Beneath, 0);
+        cap_tap.align_mut(&cap, AlignMode::Left, 0);
+        cap_tap.align_mut(&cap, AlignMode::Beneath, 0);
+        let mut prev = res.lcm_bounds().union(cap_tap.lcm_bounds());
+        cell.draw(res)?;
+        cell.draw(cap)?;
+        let cap_tap = cell.draw(cap_tap)?;
+
+        let mut input = rc;
+        let mut ntaps = Vec::new();
+        for (i, nf) in p.inv_nf.into_iter().enumerate() {
+            let output = if i + 1 == p.inv_nf.len() {
+                trigger
+            } else {
+                cell.signal(format!("stage{i}"), Signal)
+            };
+            let mut ntap =
+                cell.generate_connected(T::tap(TileKind::N, nf), TapIoSchematic { x: vdd });
+            let mut pmos = cell.generate_connected(
+                T::mos(TileKind::P, nf, 2 * p.inv_w, None),
+                MosIoSchematic {
+                    d: vdd,
+                    g: input,
+                    s: output,
+                    b: vdd,
+                },
+            );
+            let mut nmos = cell
+                .generate_connected(
+                    T::mos(TileKind::N, nf, p.inv_w, None),
+                    MosIoSchematic {
+                        d: vss,
+                        g: input,
+                        s: output,
+                        b: vss,
+                    },
+                )
+                .orient(Orientation::R180);
+            let mut ptap =
+                cell.generate_connected(T::tap(TileKind::P, nf), TapIoSchematic { x: vss });
+
+            ntap.align_rect_mut(prev, AlignMode::Top, 0);
+            ntap.align_rect_mut(prev, AlignMode::ToTheRight, 0);
+            pmos.align_mut(&ntap, AlignMode::Left, 0);
+            pmos.align_mut(&ntap, AlignMode::Beneath, 0);
+            nmos.align_mut(&pmos, AlignMode::Left, 0);
+            nmos.align_mut(&pmos, AlignMode::Beneath, 0);
+            ptap.align_mut(&nmos, AlignMode::Left, 0);
+            ptap.align_mut(&nmos, AlignMode::Beneath, 0);
+            prev = ntap.lcm_bounds().union(ptap.lcm_bounds());
+
+            ntaps.push(cell.draw(ntap)?);
+            cell.draw(pmos)?;
+            cell.draw(nmos)?;
+            cell.draw(ptap)?;
+            input = output;
+        }
+
+        let mut clamp = cell.generate_connected(
+            T::mos(TileKind::N, p.clamp_nf, p.clamp_w, None),
+            MosIoSchematic {
+                d: vdd,
+                g: trigger,
+                s: vss,
+                b: vss,
+            },
+        );
+        let mut clamp_tap =
+            cell.generate_connected(T::tap(TileKind::P, p.clamp_nf), TapIoSchematic { x: vss });
+        clamp.align_rect_mut(prev, AlignMode::Top, 0);
+        clamp.align_rect_mut(prev, AlignMode::ToTheRight, 0);
+        clamp_tap.align_mut(&clamp, AlignMode::Left, 0);
+        clamp_tap.align_mut(&clamp, AlignMode::Beneath, 0);
+        cell.draw(clamp)?;
+        cell.draw(clamp_tap)?;
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        io.layout.vdd.merge(ntaps[0].layout.io().x);
+        io.layout.vss.merge(cap_tap.layout.io().x);
+
+        T::post_layout_hooks(cell)?;
+
+        Ok(((), ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tech::sky130::Sky130Ucie;
+
+    #[test]
+    fn rc_timer_meets_time_constant() {
+        for tau in [50e-9, 1e-6, 5e-6] {
+            let timer = RcTimerParams::design::<Sky130Ucie>(tau).unwrap();
+            assert_eq!(timer.cap_nf % 2, 0);
+            assert!(timer.res.legs <= MAX_TIMER_LEGS);
+            let achieved = timer.time_constant::<Sky130Ucie>();
+            assert!((achieved / tau - 1.).abs() < 0.01, "{tau}: {achieved}");
+        }
+        assert!(RcTimerParams::design::<Sky130Ucie>(0.).is_err());
+    }
+}
@@ -0,0 +1,312 @@
+//! Rail clamp testbenches.
+//!
+//! [`RcClampTb`] ramps the supply of an [`RcClampTile`](crate::power::RcClampTile) and
+//! records the current it draws. Run it once with [`RcClampTb::esd_event`] and once with
+//! [`RcClampTb::power_up`], then combine the results in an [`RcClampVerification`] to
+//! check that the clamp fires on the fast ramp and stays off on the slow one.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::{Pulse, Vsource};
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Terminal};
+use substrate::io::{Signal, TestbenchIo};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::power::RcClampIo;
+use crate::report::SimArtifact;
+
+/// A transient testbench that ramps the supply of a rail clamp from zero to the PVT
+/// voltage, holds it, and measures the supply current.
+#[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct RcClampTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: T,
+    /// The supply rise time, in seconds.
+    pub rise: Decimal,
+    /// The time for which the supply is held after the ramp, in seconds.
+    pub hold: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> RcClampTb<T, PDK, C> {
+    /// Creates a new [`RcClampTb`].
+    pub fn new(dut: T, rise: Decimal, hold: Decimal, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            rise,
+            hold,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+
+    /// A 10 ns supply ramp, representative of the rising edge of an HBM event,
+    /// held for 5 us so that the clamp times out.
+    pub fn esd_event(dut: T, pvt: Pvt<C>) -> Self {
+        Self::new(dut, dec!(10e-9), dec!(5e-6), pvt)
+    }
+
+    /// A 100 us supply ramp, faster than any supply the PHY is specified to power up
+    /// from, held for 5 us.
+    pub fn power_up(dut: T, pvt: Pvt<C>) -> Self {
+        Self::new(dut, dec!(100e-6), dec!(5e-6), pvt)
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for RcClampTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("rc_clamp_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("rc_clamp_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`RcClampTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct RcClampTbNodes {
+    vdd_src: Terminal,
+}
+
+impl<T, PDK, C> ExportsNestedData for RcClampTb<T, PDK, C>
+where
+    RcClampTb<T, PDK, C>: Block,
+{
+    type NestedData = RcClampTbNodes;
+}
+
+impl<T: Block<Io = RcClampIo> + Schematic<PDK> + Clone, PDK: Schema, C> Schematic<Spectre>
+    for RcClampTb<T, PDK, C>
+where
+    RcClampTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vdd = cell.signal("vdd", Signal);
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
+        cell.connect(dut.io().vdd, vdd);
+        cell.connect(dut.io().vss, io.vss);
+
+        let vdd_src = cell.instantiate(Vsource::pulse(Pulse {
+            val0: dec!(0),
+            val1: self.pvt.voltage,
+            period: None,
+            width: Some(self.hold),
+            delay: Some(dec!(0)),
+            rise: Some(self.rise),
+            fall: Some(self.rise),
+        }));
+        cell.connect(vdd_src.io().p, vdd);
+        cell.connect(vdd_src.io().n, io.vss);
+
+        Ok(RcClampTbNodes {
+            vdd_src: vdd_src.io().p,
+        })
+    }
+}
+
+/// The resulting waveforms of a [`RcClampTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct RcClampSim {
+    /// The simulation time.
+    pub t: tran::Time,
+    /// The current into the supply source.
+    pub idd: tran::Current,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, RcClampSim> for RcClampTb<T, PDK, C>
+where
+    RcClampTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <RcClampSim as FromSaved<Spectre, Tran>>::SavedKey {
+        RcClampSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            idd: tran::Current::save(ctx, &cell.vdd_src, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for RcClampTb<T, PDK, C>
+where
+    RcClampTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = RcClampResponse;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: RcClampSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: self.rise + self.hold,
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+        // The supply source delivers current out of its positive terminal.
+        let idd: Vec<f64> = wav.idd.iter().map(|i| -i).collect();
+        RcClampResponse::new(self.rise.to_f64().unwrap(), &idd)
+    }
+}
+
+/// The supply current drawn by a rail clamp during one supply ramp.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RcClampResponse {
+    /// The supply rise time, in seconds.
+    pub rise: f64,
+    /// The largest supply current, in amperes.
+    pub peak_current: f64,
+    /// The supply current at the end of the hold time, in amperes.
+    pub final_current: f64,
+}
+
+impl RcClampResponse {
+    /// Summarizes the supply current waveform `idd` of a ramp with rise time `rise`.
+    pub fn new(rise: f64, idd: &[f64]) -> Self {
+        Self {
+            rise,
+            peak_current: idd.iter().copied().fold(0., f64::max),
+            final_current: idd.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// The responses of a rail clamp to an ESD event and to a normal power-up.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RcClampVerification {
+    /// The response to the fast ramp.
+    pub esd_event: RcClampResponse,
+    /// The response to the slow ramp.
+    pub power_up: RcClampResponse,
+    /// The supply current above which the clamp is considered on, in amperes.
+    pub threshold: f64,
+}
+
+impl RcClampVerification {
+    /// Creates a new [`RcClampVerification`].
+    pub fn new(esd_event: RcClampResponse, power_up: RcClampResponse, threshold: f64) -> Self {
+        Self {
+            esd_event,
+            power_up,
+            threshold,
+        }
+    }
+
+    /// Whether the clamp fires during the ESD event and releases before it ends.
+    pub fn triggers(&self) -> bool {
+        self.esd_event.peak_current > self.threshold
+            && self.esd_event.final_current < self.threshold
+    }
+
+    /// Whether the clamp stays off during power-up.
+    pub fn rejects_power_up(&self) -> bool {
+        self.power_up.peak_current < self.threshold
+    }
+
+    /// Whether the clamp both triggers and rejects power-up.
+    pub fn passed(&self) -> bool {
+        self.triggers() && self.rejects_power_up()
+    }
+}
+
+impl SimArtifact for RcClampVerification {
+    fn csv_header(&self) -> Vec<String> {
+        ["event", "rise", "peak_current", "final_current"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        [("esd_event", self.esd_event), ("power_up", self.power_up)]
+            .into_iter()
+            .map(|(event, r)| {
+                vec![
+                    event.to_string(),
+                    r.rise.to_string(),
+                    r.peak_current.to_string(),
+                    r.final_current.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rc_clamp_verification() {
+        let esd_event = RcClampResponse::new(10e-9, &[0., 0.2, 0.5, 0.1, 1e-6]);
+        let power_up = RcClampResponse::new(100e-6, &[0., 1e-6, 2e-6, 1e-6]);
+        assert_eq!(esd_event.peak_current, 0.5);
+        assert_eq!(esd_event.final_current, 1e-6);
+
+        let check = RcClampVerification::new(esd_event, power_up, 1e-3);
+        assert!(check.triggers());
+        assert!(check.rejects_power_up());
+        assert!(check.passed());
+
+        // A clamp that latches on fails even though it fires.
+        let latched = RcClampResponse::new(10e-9, &[0., 0.5, 0.4]);
+        assert!(!RcClampVerification::new(latched, power_up, 1e-3).triggers());
+        // A clamp that fires on power-up fails.
+        assert!(!RcClampVerification::new(esd_event, esd_event, 1e-3).rejects_power_up());
+    }
+}
@@ -0,0 +1,144 @@
+//! Python bindings.
+//!
+//! Built with the `python` feature as an extension module named `ucieanalog`.
+//! Block parameters are passed as dictionaries with the same fields as the
+//! corresponding Rust parameter structs:
+//!
+//! ```python
+//! import ucieanalog
+//!
+//! params = ucieanalog.default_params("strongarm")
+//! params["input_pair_w"] = 2000
+//! ucieanalog.write_gds("strongarm", params, "build/strongarm.gds")
+//! ```
+
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pythonize::{depythonize_bound, pythonize};
+use rust_decimal::Decimal;
+use sky130pdk::Sky130Pdk;
+use spectre::Spectre;
+use substrate::block::Block;
+use substrate::context::PdkContext;
+use substrate::layout::Layout;
+
+use crate::buffer::InverterParams;
+use crate::config::SamplerConfig;
+use crate::frontend::{self, BlockKind, FrontendError, FrontendParams};
+use crate::strongarm::StrongArmParams;
+use crate::{try_sky130_ctx, try_sky130_open_ctx, with_sky130_block};
+
+fn ctx(open: bool) -> PyResult<PdkContext<Sky130Pdk>> {
+    if open {
+        try_sky130_open_ctx()
+    } else {
+        try_sky130_ctx()
+    }
+    .map_err(|e| PyRuntimeError::new_err(e.to_string()))
+}
+
+fn params<P: FrontendParams>(params: &Bound<'_, PyAny>) -> PyResult<P> {
+    let params: P = depythonize_bound(params.clone())
+        .map_err(|e| PyValueError::new_err(format!("invalid parameters: {e}")))?;
+    params
+        .validate()
+        .map_err(|e| PyValueError::new_err(format!("invalid parameters: {e}")))?;
+    Ok(params)
+}
+
+fn decimal(value: f64) -> PyResult<Decimal> {
+    Decimal::try_from(value).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+fn runtime_err(e: impl Debug) -> PyErr {
+    PyRuntimeError::new_err(format!("{e:?}"))
+}
+
+fn block_kind(block: &str) -> PyResult<BlockKind> {
+    block
+        .parse()
+        .map_err(|e: FrontendError| PyValueError::new_err(e.to_string()))
+}
+
+/// Returns the default SKY130 parameters of a block as a dictionary.
+#[pyfunction]
+fn default_params(py: Python<'_>, block: &str) -> PyResult<PyObject> {
+    let inverter = InverterParams::builder().build().map_err(runtime_err)?;
+    let strongarm = StrongArmParams::builder().build().map_err(runtime_err)?;
+    let value = match block_kind(block)? {
+        BlockKind::Inverter | BlockKind::Buffer => pythonize(py, &inverter),
+        BlockKind::Strongarm => pythonize(py, &strongarm),
+        BlockKind::StrongarmWithOutputBuffers => pythonize(
+            py,
+            &SamplerConfig {
+                strongarm,
+                buffer: inverter,
+            },
+        ),
+    };
+    value.map_err(runtime_err)
+}
+
+fn gds<B: Block + Layout<Sky130Pdk>>(open: bool, block: B, path: &Path) -> PyResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    ctx(open)?.write_layout(block, path).map_err(runtime_err)
+}
+
+/// Writes the layout of a block to a GDS file.
+#[pyfunction]
+#[pyo3(signature = (block, params, path, open = false))]
+fn write_gds(block: &str, params: &Bound<'_, PyAny>, path: PathBuf, open: bool) -> PyResult<()> {
+    with_sky130_block!(block_kind(block)?, self::params, params, |block| gds(
+        open, block, &path
+    ))
+}
+
+/// Writes the schematic of a block to a SPICE netlist.
+#[pyfunction]
+#[pyo3(signature = (block, params, path, open = false))]
+fn write_netlist(
+    block: &str,
+    params: &Bound<'_, PyAny>,
+    path: PathBuf,
+    open: bool,
+) -> PyResult<()> {
+    let ctx = ctx(open)?;
+    with_sky130_block!(block_kind(block)?, self::params, params, |block| {
+        frontend::write_netlist(&ctx, open, block, &path).map_err(runtime_err)
+    })
+}
+
+/// Simulates a StrongARM across process, voltage, and temperature corners using Spectre.
+///
+/// Returns a list of `(pvt, output)` pairs. The output is `None` if the latch
+/// failed to resolve at that corner.
+#[pyfunction]
+#[pyo3(signature = (params, work_dir, vinp = 0.65, vinn = 0.55))]
+fn characterize_strongarm(
+    py: Python<'_>,
+    params: &Bound<'_, PyAny>,
+    work_dir: PathBuf,
+    vinp: f64,
+    vinn: f64,
+) -> PyResult<PyObject> {
+    let sweep =
+        frontend::strongarm_characterization(self::params(params)?, decimal(vinp)?, decimal(vinn)?);
+    let ctx = ctx(false)?;
+    let results = py.allow_threads(|| sweep.run::<Spectre, _>(&ctx, &work_dir));
+    pythonize(py, &results).map_err(runtime_err)
+}
+
+/// The `ucieanalog` Python module.
+#[pymodule]
+fn ucieanalog(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(default_params, m)?)?;
+    m.add_function(wrap_pyfunction!(write_gds, m)?)?;
+    m.add_function(wrap_pyfunction!(write_netlist, m)?)?;
+    m.add_function(wrap_pyfunction!(characterize_strongarm, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,247 @@
+//! Waveform regression against stored golden references.
+//!
+//! Golden waveforms are stored as JSON files. If a golden file does not exist,
+//! or the `UCIE_BLESS` environment variable is set, the current waveforms are
+//! written as the new golden reference instead of being compared.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// The environment variable that causes golden references to be overwritten.
+pub const BLESS_ENV_VAR: &str = "UCIE_BLESS";
+
+/// A sampled waveform.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Waveform {
+    /// Sample times, in increasing order.
+    pub t: Vec<f64>,
+    /// Sample values.
+    pub v: Vec<f64>,
+}
+
+impl Waveform {
+    /// Creates a new [`Waveform`] from sample times and values.
+    pub fn new(t: impl Into<Vec<f64>>, v: impl Into<Vec<f64>>) -> Self {
+        let (t, v) = (t.into(), v.into());
+        assert_eq!(t.len(), v.len(), "times and values must have equal length");
+        Self { t, v }
+    }
+
+    /// Returns the value at time `t`, linearly interpolating between samples.
+    ///
+    /// Returns [`None`] if `t` is outside the sampled range.
+    pub fn sample(&self, t: f64) -> Option<f64> {
+        let i = self.t.partition_point(|&x| x < t);
+        if i == self.t.len() {
+            return None;
+        }
+        if self.t[i] == t {
+            return Some(self.v[i]);
+        }
+        if i == 0 {
+            return None;
+        }
+        let (t0, t1) = (self.t[i - 1], self.t[i]);
+        let (v0, v1) = (self.v[i - 1], self.v[i]);
+        Some(v0 + (v1 - v0) * (t - t0) / (t1 - t0))
+    }
+}
+
+/// A named collection of waveforms from a single testbench run.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct WaveformSet {
+    /// The waveforms, keyed by name.
+    pub waveforms: BTreeMap<String, Waveform>,
+}
+
+impl WaveformSet {
+    /// Creates an empty [`WaveformSet`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a named waveform.
+    pub fn with(mut self, name: impl Into<String>, waveform: Waveform) -> Self {
+        self.waveforms.insert(name.into(), waveform);
+        self
+    }
+}
+
+/// The allowed deviation from a golden waveform.
+///
+/// A sample passes if `|v - golden| <= abs + rel * |golden|`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    /// The absolute tolerance.
+    pub abs: f64,
+    /// The tolerance relative to the golden value.
+    pub rel: f64,
+}
+
+impl Tolerance {
+    fn allows(&self, golden: f64, value: f64) -> bool {
+        (value - golden).abs() <= self.abs + self.rel * golden.abs()
+    }
+}
+
+/// A difference between a waveform and its golden reference.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Drift {
+    /// The waveform exceeds its tolerance band.
+    OutOfTolerance {
+        /// The waveform name.
+        name: String,
+        /// The time at which the largest error occurred.
+        t: f64,
+        /// The largest absolute error.
+        error: f64,
+    },
+    /// The golden reference has a waveform that the current run does not.
+    Missing(String),
+    /// The current run has a waveform that the golden reference does not.
+    Unexpected(String),
+}
+
+impl Display for Drift {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Drift::OutOfTolerance { name, t, error } => {
+                write!(f, "{name}: error of {error:e} at t = {t:e}")
+            }
+            Drift::Missing(name) => write!(f, "{name}: missing from current run"),
+            Drift::Unexpected(name) => write!(f, "{name}: not in golden reference"),
+        }
+    }
+}
+
+/// The result of comparing a [`WaveformSet`] against its golden reference.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RegressionReport {
+    /// The golden reference file.
+    pub golden: PathBuf,
+    /// Whether the golden reference was written by this run rather than compared.
+    pub blessed: bool,
+    /// The differences found.
+    pub drifts: Vec<Drift>,
+}
+
+impl RegressionReport {
+    /// Returns `true` if no differences were found.
+    pub fn passed(&self) -> bool {
+        self.drifts.is_empty()
+    }
+}
+
+/// Compares `current` against `golden`.
+///
+/// Golden samples outside the time range of the current waveform are ignored.
+pub fn compare(current: &WaveformSet, golden: &WaveformSet, tol: Tolerance) -> Vec<Drift> {
+    let mut drifts = Vec::new();
+    for (name, golden) in golden.waveforms.iter() {
+        let Some(current) = current.waveforms.get(name) else {
+            drifts.push(Drift::Missing(name.clone()));
+            continue;
+        };
+        let worst = golden
+            .t
+            .iter()
+            .zip(golden.v.iter())
+            .filter_map(|(&t, &g)| {
+                let v = current.sample(t)?;
+                (!tol.allows(g, v)).then_some((t, (v - g).abs()))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((t, error)) = worst {
+            drifts.push(Drift::OutOfTolerance {
+                name: name.clone(),
+                t,
+                error,
+            });
+        }
+    }
+    for name in current.waveforms.keys() {
+        if !golden.waveforms.contains_key(name) {
+            drifts.push(Drift::Unexpected(name.clone()));
+        }
+    }
+    drifts
+}
+
+/// Compares `current` against the golden reference stored at `golden`.
+///
+/// Writes `current` as the golden reference if the file does not exist
+/// or the [`BLESS_ENV_VAR`] environment variable is set.
+pub fn check_golden(
+    current: &WaveformSet,
+    golden: impl AsRef<Path>,
+    tol: Tolerance,
+) -> std::io::Result<RegressionReport> {
+    let golden = golden.as_ref();
+    if !golden.exists() || std::env::var_os(BLESS_ENV_VAR).is_some() {
+        if let Some(parent) = golden.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(golden, serde_json::to_string(current)?)?;
+        return Ok(RegressionReport {
+            golden: golden.to_path_buf(),
+            blessed: true,
+            drifts: Vec::new(),
+        });
+    }
+
+    let reference: WaveformSet = serde_json::from_str(&std::fs::read_to_string(golden)?)?;
+    Ok(RegressionReport {
+        golden: golden.to_path_buf(),
+        blessed: false,
+        drifts: compare(current, &reference, tol),
+    })
+}
+
+/// Compares `current` against its golden reference, panicking if they differ.
+pub fn assert_matches_golden(current: &WaveformSet, golden: impl AsRef<Path>, tol: Tolerance) {
+    let report = check_golden(current, golden, tol).expect("failed to check golden waveforms");
+    if !report.passed() {
+        let drifts = report
+            .drifts
+            .iter()
+            .map(|d| format!("  {d}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        panic!(
+            "waveforms differ from golden reference {:?}; set {BLESS_ENV_VAR} to update it\n{drifts}",
+            report.golden
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_drift() {
+        let golden = WaveformSet::new()
+            .with("vout", Waveform::new([0., 1., 2.], [0., 1., 0.]))
+            .with("vdd", Waveform::new([0., 2.], [1.8, 1.8]));
+        let current = WaveformSet::new()
+            .with("vout", Waveform::new([0., 0.5, 2.], [0., 0.5, 0.2]))
+            .with("vss", Waveform::new([0., 2.], [0., 0.]));
+        let tol = Tolerance { abs: 0.05, rel: 0. };
+
+        let drifts = compare(&current, &golden, tol);
+        assert_eq!(drifts.len(), 3);
+        assert!(matches!(
+            &drifts[0],
+            Drift::Missing(name) if name == "vdd"
+        ));
+        let Drift::OutOfTolerance { name, t, error } = &drifts[1] else {
+            panic!("expected out of tolerance drift");
+        };
+        assert_eq!(name, "vout");
+        assert_eq!(*t, 1.);
+        assert!((error - 0.6).abs() < 1e-12);
+        assert!(matches!(&drifts[2], Drift::Unexpected(name) if name == "vss"));
+    }
+}
@@ -0,0 +1,280 @@
+//! Layout area and utilization reports.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use substrate::context::PdkContext;
+use substrate::geometry::bbox::Bbox;
+use substrate::geometry::rect::Rect;
+use substrate::geometry::transform::{TransformRef, Transformation};
+use substrate::layout::element::{Element, RawCell};
+use substrate::layout::Layout;
+use substrate::pdk::layers::LayerId;
+use substrate::pdk::{Pdk, PdkLayers};
+
+use crate::report::SimArtifact;
+
+/// The layers of a technology relevant to area reporting.
+pub trait AreaLayers<PDK: Pdk> {
+    /// The layers whose union makes up the device active area.
+    fn active_layers(layers: &PdkLayers<PDK>) -> Vec<LayerId>;
+    /// The routing layers, from bottom to top, named as in the technology LEF.
+    fn routing_layers(layers: &PdkLayers<PDK>) -> Vec<(&'static str, LayerId)>;
+}
+
+/// The area occupied by the instances of a sub-tile.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SubtileArea {
+    /// The name of the sub-tile cell.
+    pub cell: String,
+    /// The number of instances of the sub-tile in the top cell.
+    pub count: usize,
+    /// The summed bounding box area of all instances, in square nanometers.
+    pub area: i64,
+}
+
+/// The fraction of a cell covered by a routing layer.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LayerUtilization {
+    /// The name of the layer.
+    pub layer: String,
+    /// The area covered by shapes on the layer, in square nanometers.
+    pub area: i64,
+    /// The covered area as a fraction of the bounding box area.
+    pub utilization: f64,
+}
+
+/// A summary of the area of a layout cell.
+///
+/// Areas are in square nanometers. Overlapping shapes are only counted once.
+/// Shape coverage is computed from shape bounding boxes, so non-rectangular
+/// shapes are slightly overestimated.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AreaReport {
+    /// The bounding box of the cell.
+    pub bbox: Option<Rect>,
+    /// The area of the bounding box.
+    pub total_area: i64,
+    /// The area of each sub-tile instantiated directly by the cell.
+    pub subtiles: Vec<SubtileArea>,
+    /// The area covered by device active layers.
+    pub active_area: i64,
+    /// The utilization of each routing layer.
+    pub routing: Vec<LayerUtilization>,
+}
+
+impl AreaReport {
+    /// The device active area as a fraction of the bounding box area.
+    pub fn active_utilization(&self) -> f64 {
+        fraction(self.active_area, self.total_area)
+    }
+}
+
+impl SimArtifact for AreaReport {
+    fn csv_header(&self) -> Vec<String> {
+        ["category", "name", "count", "area_nm2", "utilization"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        let mut rows = vec![
+            vec![
+                "total".to_string(),
+                String::new(),
+                "1".to_string(),
+                self.total_area.to_string(),
+                "1".to_string(),
+            ],
+            vec![
+                "active".to_string(),
+                String::new(),
+                String::new(),
+                self.active_area.to_string(),
+                self.active_utilization().to_string(),
+            ],
+        ];
+        rows.extend(self.subtiles.iter().map(|subtile| {
+            vec![
+                "subtile".to_string(),
+                subtile.cell.clone(),
+                subtile.count.to_string(),
+                subtile.area.to_string(),
+                fraction(subtile.area, self.total_area).to_string(),
+            ]
+        }));
+        rows.extend(self.routing.iter().map(|layer| {
+            vec![
+                "routing".to_string(),
+                layer.layer.clone(),
+                String::new(),
+                layer.area.to_string(),
+                layer.utilization.to_string(),
+            ]
+        }));
+        rows
+    }
+}
+
+/// Generates the layout of `block` and reports its area using the layers of technology `T`.
+pub fn area<T: AreaLayers<PDK>, PDK: Pdk, B: Layout<PDK>>(
+    ctx: &PdkContext<PDK>,
+    block: B,
+) -> AreaReport {
+    let cell = ctx.generate_layout(block);
+    area_of_cell::<T, PDK>(&ctx.layers, cell.raw())
+}
+
+/// Reports the area of an already generated layout cell using the layers of technology `T`.
+pub fn area_of_cell<T: AreaLayers<PDK>, PDK: Pdk>(
+    layers: &PdkLayers<PDK>,
+    cell: &RawCell,
+) -> AreaReport {
+    let bbox = cell.bbox();
+    let total_area = bbox.map(|bbox| bbox.area()).unwrap_or_default();
+
+    let mut subtiles: HashMap<String, SubtileArea> = HashMap::new();
+    for element in cell.elements() {
+        if let Element::Instance(inst) = element {
+            let name = inst.raw_cell().name().to_string();
+            let entry = subtiles.entry(name.clone()).or_insert(SubtileArea {
+                cell: name,
+                count: 0,
+                area: 0,
+            });
+            entry.count += 1;
+            entry.area += inst.bbox().map(|bbox| bbox.area()).unwrap_or_default();
+        }
+    }
+    let mut subtiles = subtiles.into_values().collect::<Vec<_>>();
+    subtiles.sort_by(|a, b| b.area.cmp(&a.area).then_with(|| a.cell.cmp(&b.cell)));
+
+    let shapes = layer_shapes(cell);
+    let covered = |layers: &[LayerId]| {
+        union_area(
+            layers
+                .iter()
+                .filter_map(|layer| shapes.get(layer))
+                .flatten()
+                .copied(),
+        )
+    };
+
+    let active_area = covered(&T::active_layers(layers));
+    let routing = T::routing_layers(layers)
+        .into_iter()
+        .map(|(name, layer)| {
+            let area = covered(&[layer]);
+            LayerUtilization {
+                layer: name.to_string(),
+                area,
+                utilization: fraction(area, total_area),
+            }
+        })
+        .collect();
+
+    AreaReport {
+        bbox,
+        total_area,
+        subtiles,
+        active_area,
+        routing,
+    }
+}
+
+/// Returns the bounding box of every shape in `cell` and its descendants, grouped by layer.
+pub(crate) fn layer_shapes(cell: &RawCell) -> HashMap<LayerId, Vec<Rect>> {
+    let mut shapes = HashMap::new();
+    flatten(cell, Transformation::identity(), &mut shapes);
+    shapes
+}
+
+fn flatten(cell: &RawCell, trans: Transformation, shapes: &mut HashMap<LayerId, Vec<Rect>>) {
+    for element in cell.elements() {
+        match element {
+            Element::Instance(inst) => flatten(
+                inst.raw_cell(),
+                Transformation::cascade(trans, inst.transformation()),
+                shapes,
+            ),
+            Element::Shape(shape) => {
+                if let Some(bbox) = shape.bbox() {
+                    shapes
+                        .entry(shape.layer())
+                        .or_default()
+                        .push(bbox.transform_ref(trans));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the area covered by the union of `rects`.
+pub(crate) fn union_area(rects: impl IntoIterator<Item = Rect>) -> i64 {
+    // Sweep from left to right, tracking the vertical spans of the rectangles
+    // that overlap each vertical slab.
+    let mut events = Vec::new();
+    for rect in rects {
+        if rect.area() > 0 {
+            events.push((rect.left(), true, rect.bot(), rect.top()));
+            events.push((rect.right(), false, rect.bot(), rect.top()));
+        }
+    }
+    events.sort_unstable();
+
+    let mut active: Vec<(i64, i64)> = Vec::new();
+    let mut area = 0;
+    let mut prev_x = None;
+    for (x, start, bot, top) in events {
+        if let Some(prev_x) = prev_x {
+            area += (x - prev_x) * covered_length(&mut active);
+        }
+        prev_x = Some(x);
+        if start {
+            active.push((bot, top));
+        } else if let Some(i) = active.iter().position(|&span| span == (bot, top)) {
+            active.swap_remove(i);
+        }
+    }
+    area
+}
+
+/// Returns the total length covered by the union of `spans`, sorting them in place.
+fn covered_length(spans: &mut [(i64, i64)]) -> i64 {
+    spans.sort_unstable();
+    let mut length = 0;
+    let mut end = i64::MIN;
+    for &(start, stop) in spans.iter() {
+        let start = start.max(end);
+        if stop > start {
+            length += stop - start;
+            end = stop;
+        }
+    }
+    length
+}
+
+fn fraction(area: i64, total: i64) -> f64 {
+    if total == 0 {
+        0.
+    } else {
+        area as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_shapes_are_counted_once() {
+        let rects = [
+            Rect::from_sides(0, 0, 100, 100),
+            Rect::from_sides(50, 50, 150, 150),
+            Rect::from_sides(0, 0, 100, 100),
+            Rect::from_sides(200, 0, 300, 10),
+        ];
+        assert_eq!(union_area(rects), 10_000 + 7_500 + 1_000);
+    }
+}
@@ -0,0 +1,256 @@
+//! Export of characterization results and layout reports to JSON and CSV.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use substrate::pdk::corner::Pvt;
+
+use crate::sweep::{CornerSweepOutput, MonteCarloOutput, Summary};
+
+pub mod area;
+pub mod snapshot;
+
+pub use area::area;
+
+/// Metadata recorded alongside an exported artifact.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArtifactMetadata {
+    /// The name of the artifact, used as the base name of the exported files.
+    pub name: String,
+    /// The git commit of this repository when the artifact was produced, if known.
+    pub git_hash: Option<String>,
+    /// A hash of the parameters that produced the artifact.
+    pub params_hash: Option<String>,
+    /// The PVT corner at which the artifact was produced.
+    pub corner: Option<String>,
+}
+
+impl ArtifactMetadata {
+    /// Creates metadata for an artifact named `name`, recording the current git commit.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            git_hash: git_hash(),
+            params_hash: None,
+            corner: None,
+        }
+    }
+
+    /// Records a hash of the given parameters.
+    ///
+    /// The hash is taken over the serialized parameters, so it is stable across builds,
+    /// platforms, and toolchains.
+    pub fn with_params(mut self, params: &impl Serialize) -> serde_json::Result<Self> {
+        let hash = crate::stable_hash(&serde_json::to_vec(params)?);
+        self.params_hash = Some(format!("{hash:016x}"));
+        Ok(self)
+    }
+
+    /// Records the given PVT corner.
+    pub fn with_corner<C: Debug>(mut self, pvt: &Pvt<C>) -> Self {
+        self.corner = Some(format_pvt(pvt));
+        self
+    }
+}
+
+/// The files written by [`SimArtifact::write_artifact`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArtifactPaths {
+    /// The JSON file, containing the metadata and the full serialized artifact.
+    pub json: PathBuf,
+    /// The CSV file, containing the metadata as comments followed by the tabular data.
+    pub csv: PathBuf,
+}
+
+/// A simulation result that can be exported as JSON and CSV.
+pub trait SimArtifact: Serialize {
+    /// The CSV column names.
+    fn csv_header(&self) -> Vec<String>;
+
+    /// The CSV rows, each with one entry per column.
+    fn csv_rows(&self) -> Vec<Vec<String>>;
+
+    /// Writes this artifact to `<dir>/<name>.json` and `<dir>/<name>.csv`.
+    fn write_artifact(
+        &self,
+        dir: impl AsRef<Path>,
+        metadata: &ArtifactMetadata,
+    ) -> std::io::Result<ArtifactPaths> {
+        #[derive(Serialize)]
+        struct JsonArtifact<'a, T: ?Sized> {
+            metadata: &'a ArtifactMetadata,
+            data: &'a T,
+        }
+
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let json = dir.join(format!("{}.json", metadata.name));
+        let csv = dir.join(format!("{}.csv", metadata.name));
+
+        let contents = serde_json::to_string_pretty(&JsonArtifact {
+            metadata,
+            data: self,
+        })?;
+        std::fs::write(&json, contents)?;
+
+        let mut contents = String::new();
+        for (key, value) in [
+            ("git_hash", &metadata.git_hash),
+            ("params_hash", &metadata.params_hash),
+            ("corner", &metadata.corner),
+        ] {
+            if let Some(value) = value {
+                contents.push_str(&format!("# {key}: {value}\n"));
+            }
+        }
+        for row in std::iter::once(self.csv_header()).chain(self.csv_rows()) {
+            let row = row.iter().map(|v| csv_field(v)).collect::<Vec<_>>();
+            contents.push_str(&row.join(","));
+            contents.push('\n');
+        }
+        std::fs::write(&csv, contents)?;
+
+        Ok(ArtifactPaths { json, csv })
+    }
+}
+
+impl SimArtifact for Summary {
+    fn csv_header(&self) -> Vec<String> {
+        vec!["statistic".to_string(), "value".to_string()]
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        [
+            ("mean", self.mean),
+            ("std_dev", self.std_dev),
+            ("min", self.min),
+            ("max", self.max),
+            ("p1", self.percentile(1.)),
+            ("p50", self.percentile(50.)),
+            ("p99", self.percentile(99.)),
+        ]
+        .into_iter()
+        .map(|(name, value)| vec![name.to_string(), value.to_string()])
+        .collect()
+    }
+}
+
+impl<O: SimArtifact> SimArtifact for MonteCarloOutput<O> {
+    fn csv_header(&self) -> Vec<String> {
+        let mut header = vec!["sample".to_string()];
+        header.extend(
+            self.samples
+                .first()
+                .map(|s| s.csv_header())
+                .unwrap_or_default(),
+        );
+        header
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        prefixed_rows(
+            self.samples
+                .iter()
+                .enumerate()
+                .map(|(i, sample)| (vec![i.to_string()], sample)),
+        )
+    }
+}
+
+impl<C: Debug + Serialize, O: SimArtifact> SimArtifact for CornerSweepOutput<C, O> {
+    fn csv_header(&self) -> Vec<String> {
+        let mut header = vec![
+            "corner".to_string(),
+            "voltage".to_string(),
+            "temp".to_string(),
+        ];
+        header.extend(
+            self.outputs
+                .values()
+                .next()
+                .map(|o| o.csv_header())
+                .unwrap_or_default(),
+        );
+        header
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        let mut outputs = self
+            .outputs
+            .iter()
+            .map(|(pvt, output)| {
+                (
+                    vec![
+                        format!("{:?}", pvt.corner),
+                        pvt.voltage.to_string(),
+                        pvt.temp.to_string(),
+                    ],
+                    output,
+                )
+            })
+            .collect::<Vec<_>>();
+        outputs.sort_by(|a, b| a.0.cmp(&b.0));
+        prefixed_rows(outputs)
+    }
+}
+
+/// Returns the rows of each artifact, with each row prefixed by the artifact's key columns.
+fn prefixed_rows<'a, O: SimArtifact + 'a>(
+    artifacts: impl IntoIterator<Item = (Vec<String>, &'a O)>,
+) -> Vec<Vec<String>> {
+    artifacts
+        .into_iter()
+        .flat_map(|(prefix, artifact)| {
+            artifact.csv_rows().into_iter().map(move |row| {
+                let mut prefix = prefix.clone();
+                prefix.extend(row);
+                prefix
+            })
+        })
+        .collect()
+}
+
+fn format_pvt<C: Debug>(pvt: &Pvt<C>) -> String {
+    format!("{:?} {}V {}C", pvt.corner, pvt.voltage, pvt.temp)
+}
+
+/// Quotes a CSV field if necessary.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Returns the current git commit of this repository, if it can be determined.
+fn git_hash() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_csv_fields() {
+        assert_eq!(csv_field("1.5"), "1.5");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn params_hash_is_stable() {
+        let metadata = ArtifactMetadata::default().with_params(&(1, 2)).unwrap();
+        assert_eq!(metadata.params_hash.as_deref(), Some("6a12f12d4705a9b6"));
+    }
+}
@@ -0,0 +1,409 @@
+//! Structural layout snapshots.
+//!
+//! A [`BlockSnapshot`] records what a generated layout is made of: its bounding box,
+//! the sub-cells it instantiates and where, its ports, and how many shapes it draws
+//! itself on each routing layer, which for the drivers and lanes are mostly straps.
+//! Snapshots of a set of blocks are saved to a JSON [`Snapshot`] file, and
+//! [`diff_snapshots`] reports what changed between two of them, so a refactor can be
+//! reviewed without diffing GDS files.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use substrate::context::PdkContext;
+use substrate::geometry::bbox::Bbox;
+use substrate::geometry::rect::Rect;
+use substrate::layout::element::{Element, RawCell};
+use substrate::layout::Layout;
+use substrate::pdk::{Pdk, PdkLayers};
+
+use crate::report::area::AreaLayers;
+
+/// An error produced while reading or writing a snapshot file.
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    /// An I/O error.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The snapshot file could not be parsed or serialized.
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// The structure of one generated layout cell.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockSnapshot {
+    /// The name of the cell.
+    pub cell: String,
+    /// The bounding box of the cell.
+    pub bbox: Option<Rect>,
+    /// The bounding boxes of the instances placed directly in the cell, by sub-cell name.
+    ///
+    /// The boxes of each sub-cell are sorted, so reordering instances is not a change.
+    pub instances: BTreeMap<String, Vec<Option<Rect>>>,
+    /// The number of shapes on each port, by port name.
+    pub ports: BTreeMap<String, usize>,
+    /// The number of shapes drawn directly in the cell on each routing layer,
+    /// by layer name.
+    pub straps: BTreeMap<String, usize>,
+}
+
+impl BlockSnapshot {
+    /// Records the structure of `cell` using the routing layers of technology `T`.
+    pub fn from_cell<T: AreaLayers<PDK>, PDK: Pdk>(
+        layers: &PdkLayers<PDK>,
+        cell: &RawCell,
+    ) -> Self {
+        let routing_layers = T::routing_layers(layers);
+        let mut instances: BTreeMap<String, Vec<Option<Rect>>> = BTreeMap::new();
+        let mut straps = BTreeMap::new();
+        for element in cell.elements() {
+            match element {
+                Element::Instance(inst) => instances
+                    .entry(inst.raw_cell().name().to_string())
+                    .or_default()
+                    .push(inst.bbox()),
+                Element::Shape(shape) => {
+                    if let Some((name, _)) =
+                        routing_layers.iter().find(|(_, id)| *id == shape.layer())
+                    {
+                        *straps.entry(name.to_string()).or_default() += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        for bboxes in instances.values_mut() {
+            bboxes.sort_by_key(|bbox| bbox.map(|r| (r.left(), r.bot(), r.right(), r.top())));
+        }
+
+        let mut ports = BTreeMap::new();
+        for (name, port) in cell.ports() {
+            *ports.entry(name.to_string()).or_default() += port.shapes().count();
+        }
+
+        Self {
+            cell: cell.name().to_string(),
+            bbox: cell.bbox(),
+            instances,
+            ports,
+            straps,
+        }
+    }
+}
+
+/// Generates the layout of `block` and records its structure using the routing layers
+/// of technology `T`.
+pub fn snapshot_block<T: AreaLayers<PDK>, PDK: Pdk, B: Layout<PDK>>(
+    ctx: &PdkContext<PDK>,
+    block: B,
+) -> BlockSnapshot {
+    let cell = ctx.generate_layout(block);
+    BlockSnapshot::from_cell::<T, PDK>(&ctx.layers, cell.raw())
+}
+
+/// Snapshots of a set of blocks, keyed by a label that stays the same across versions.
+///
+/// Cell names include a hash of the block parameters, so they are not suitable keys.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// The snapshot of each block, by label.
+    pub blocks: BTreeMap<String, BlockSnapshot>,
+}
+
+impl Snapshot {
+    /// Adds or replaces the snapshot of the block labeled `label`.
+    pub fn insert(&mut self, label: impl Into<String>, block: BlockSnapshot) {
+        self.blocks.insert(label.into(), block);
+    }
+
+    /// Reads a snapshot file.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Reads a snapshot file, or returns an empty snapshot if the file does not exist.
+    pub fn read_or_default(path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        let path = path.as_ref();
+        if path.exists() {
+            Self::read(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Writes this snapshot to a file, creating its parent directories.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// A difference between two snapshots of the same block, or a block present in only one.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotChange {
+    /// The block only appears in the new snapshot.
+    Added(String),
+    /// The block only appears in the old snapshot.
+    Removed(String),
+    /// The block generated a cell with a different name, so its parameters changed.
+    Renamed {
+        /// The block label.
+        block: String,
+        /// The old cell name.
+        old: String,
+        /// The new cell name.
+        new: String,
+    },
+    /// The bounding box of the block changed.
+    Bbox {
+        /// The block label.
+        block: String,
+        /// The old bounding box.
+        old: Option<Rect>,
+        /// The new bounding box.
+        new: Option<Rect>,
+    },
+    /// The number of instances of a sub-cell changed.
+    InstanceCount {
+        /// The block label.
+        block: String,
+        /// The sub-cell name.
+        cell: String,
+        /// The old number of instances.
+        old: usize,
+        /// The new number of instances.
+        new: usize,
+    },
+    /// The instances of a sub-cell moved without changing in number.
+    InstancesMoved {
+        /// The block label.
+        block: String,
+        /// The sub-cell name.
+        cell: String,
+    },
+    /// The number of shapes on a port changed, or a port was added or removed.
+    Port {
+        /// The block label.
+        block: String,
+        /// The port name.
+        port: String,
+        /// The old number of shapes, or `None` if the port is new.
+        old: Option<usize>,
+        /// The new number of shapes, or `None` if the port was removed.
+        new: Option<usize>,
+    },
+    /// The number of shapes drawn on a routing layer changed.
+    Straps {
+        /// The block label.
+        block: String,
+        /// The layer name.
+        layer: String,
+        /// The old number of shapes.
+        old: usize,
+        /// The new number of shapes.
+        new: usize,
+    },
+}
+
+impl Display for SnapshotChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let rect = |r: &Option<Rect>| match r {
+            Some(r) => format!(
+                "({}, {}) to ({}, {})",
+                r.left(),
+                r.bot(),
+                r.right(),
+                r.top()
+            ),
+            None => "empty".to_string(),
+        };
+        let count = |c: &Option<usize>| c.map_or("none".to_string(), |c| c.to_string());
+        match self {
+            Self::Added(block) => write!(f, "{block}: added"),
+            Self::Removed(block) => write!(f, "{block}: removed"),
+            Self::Renamed { block, old, new } => write!(f, "{block}: cell {old} is now {new}"),
+            Self::Bbox { block, old, new } => {
+                write!(f, "{block}: bbox {} -> {}", rect(old), rect(new))
+            }
+            Self::InstanceCount {
+                block,
+                cell,
+                old,
+                new,
+            } => write!(f, "{block}: {old} -> {new} instances of {cell}"),
+            Self::InstancesMoved { block, cell } => {
+                write!(f, "{block}: instances of {cell} moved")
+            }
+            Self::Port {
+                block,
+                port,
+                old,
+                new,
+            } => write!(
+                f,
+                "{block}: port {port} shapes {} -> {}",
+                count(old),
+                count(new)
+            ),
+            Self::Straps {
+                block,
+                layer,
+                old,
+                new,
+            } => write!(f, "{block}: {old} -> {new} shapes on {layer}"),
+        }
+    }
+}
+
+/// Returns the keys of `old` and `new` in order, paired with their values in each.
+fn zip_keys<'a, V>(
+    old: &'a BTreeMap<String, V>,
+    new: &'a BTreeMap<String, V>,
+) -> impl Iterator<Item = (&'a String, Option<&'a V>, Option<&'a V>)> {
+    let mut keys = old.keys().chain(new.keys()).collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .map(move |key| (key, old.get(key), new.get(key)))
+}
+
+/// Reports every structural difference between two snapshots.
+pub fn diff_snapshots(old: &Snapshot, new: &Snapshot) -> Vec<SnapshotChange> {
+    let mut changes = Vec::new();
+    for (label, old, new) in zip_keys(&old.blocks, &new.blocks) {
+        let block = label.clone();
+        let (old, new) = match (old, new) {
+            (Some(old), Some(new)) => (old, new),
+            (Some(_), None) => {
+                changes.push(SnapshotChange::Removed(block));
+                continue;
+            }
+            _ => {
+                changes.push(SnapshotChange::Added(block));
+                continue;
+            }
+        };
+
+        if old.cell != new.cell {
+            changes.push(SnapshotChange::Renamed {
+                block: block.clone(),
+                old: old.cell.clone(),
+                new: new.cell.clone(),
+            });
+        }
+        if old.bbox != new.bbox {
+            changes.push(SnapshotChange::Bbox {
+                block: block.clone(),
+                old: old.bbox,
+                new: new.bbox,
+            });
+        }
+        for (cell, old, new) in zip_keys(&old.instances, &new.instances) {
+            let (old, new) = (old.map_or(&[][..], |v| v), new.map_or(&[][..], |v| v));
+            if old.len() != new.len() {
+                changes.push(SnapshotChange::InstanceCount {
+                    block: block.clone(),
+                    cell: cell.clone(),
+                    old: old.len(),
+                    new: new.len(),
+                });
+            } else if old != new {
+                changes.push(SnapshotChange::InstancesMoved {
+                    block: block.clone(),
+                    cell: cell.clone(),
+                });
+            }
+        }
+        for (port, old, new) in zip_keys(&old.ports, &new.ports) {
+            if old != new {
+                changes.push(SnapshotChange::Port {
+                    block: block.clone(),
+                    port: port.clone(),
+                    old: old.copied(),
+                    new: new.copied(),
+                });
+            }
+        }
+        for (layer, old, new) in zip_keys(&old.straps, &new.straps) {
+            let (old, new) = (old.copied().unwrap_or(0), new.copied().unwrap_or(0));
+            if old != new {
+                changes.push(SnapshotChange::Straps {
+                    block: block.clone(),
+                    layer: layer.clone(),
+                    old,
+                    new,
+                });
+            }
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_reports_structural_changes() {
+        let block = BlockSnapshot {
+            cell: "driver_0123".to_string(),
+            bbox: Some(Rect::from_sides(0, 0, 1_000, 2_000)),
+            instances: BTreeMap::from([(
+                "unit".to_string(),
+                vec![
+                    Some(Rect::from_sides(0, 0, 500, 2_000)),
+                    Some(Rect::from_sides(500, 0, 1_000, 2_000)),
+                ],
+            )]),
+            ports: BTreeMap::from([("din".to_string(), 1), ("vdd".to_string(), 4)]),
+            straps: BTreeMap::from([("met3".to_string(), 8)]),
+        };
+        let mut old = Snapshot::default();
+        old.insert("driver", block.clone());
+        old.insert("buffer", BlockSnapshot::default());
+        assert!(diff_snapshots(&old, &old).is_empty());
+
+        let mut moved = block.clone();
+        moved.instances.get_mut("unit").unwrap()[1] = Some(Rect::from_sides(600, 0, 1_100, 2_000));
+        moved.bbox = Some(Rect::from_sides(0, 0, 1_100, 2_000));
+        moved.ports.remove("din");
+        moved.straps.insert("met3".to_string(), 6);
+        let mut new = Snapshot::default();
+        new.insert("driver", moved);
+
+        assert_eq!(
+            diff_snapshots(&old, &new),
+            vec![
+                SnapshotChange::Removed("buffer".to_string()),
+                SnapshotChange::Bbox {
+                    block: "driver".to_string(),
+                    old: Some(Rect::from_sides(0, 0, 1_000, 2_000)),
+                    new: Some(Rect::from_sides(0, 0, 1_100, 2_000)),
+                },
+                SnapshotChange::InstancesMoved {
+                    block: "driver".to_string(),
+                    cell: "unit".to_string(),
+                },
+                SnapshotChange::Port {
+                    block: "driver".to_string(),
+                    port: "din".to_string(),
+                    old: Some(1),
+                    new: None,
+                },
+                SnapshotChange::Straps {
+                    block: "driver".to_string(),
+                    layer: "met3".to_string(),
+                    old: 8,
+                    new: 6,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,700 @@
+//! Routers used by the tile generators.
+//!
+//! Every tile uses ATOLL's [`GreedyRouter`] by default. Technologies can opt congested
+//! tiles into [`PathfinderRouter`], a negotiated-congestion router that routes all nets,
+//! lets them temporarily share grid points, and then iteratively raises the cost of
+//! shared points until every net has a legal route.
+//!
+//! Tiles can reserve grounded shield tracks around sensitive nets with [`ShieldNet`].
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+use std::ops::Range;
+
+use atoll::grid::{AtollLayer, PdkLayer, RoutingState};
+use atoll::route::{GreedyRouter, Path, Router};
+use atoll::{NodeKey, TileBuilder};
+use serde::{Deserialize, Serialize};
+use substrate::context::Context;
+use substrate::geometry::dir::Dir;
+use substrate::geometry::point::Point;
+use substrate::geometry::rect::Rect;
+use substrate::geometry::span::Span;
+use substrate::geometry::transform::{TransformMut, Transformation, TranslateMut};
+use substrate::io::schematic::Node;
+use substrate::layout::element::Shape;
+use substrate::layout::tracks::RoundingMode;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+
+/// The kind of router used by a tile.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum RouterKind {
+    /// ATOLL's greedy router, which routes nets one at a time in a seeded random order.
+    #[default]
+    Greedy,
+    /// A slower negotiated-congestion router that does not depend on net order.
+    Pathfinder,
+}
+
+/// Returns a router of the given kind, configured by the
+/// [`GenerationOptions`](crate::GenerationOptions) of `ctx`.
+///
+/// Returns a router that routes nothing if the options request schematics only.
+pub(crate) fn router(ctx: &Context, kind: RouterKind) -> UcieRouter {
+    let options = crate::generation_options(ctx);
+    if options.schematic_only {
+        return UcieRouter::Skip;
+    }
+    match kind {
+        RouterKind::Greedy => UcieRouter::Greedy(GreedyRouter::with_seed(options.router_seed)),
+        RouterKind::Pathfinder => UcieRouter::Pathfinder {
+            router: PathfinderRouter::default(),
+            fallback: options
+                .greedy_fallback
+                .then(|| GreedyRouter::with_seed(options.router_seed)),
+        },
+    }
+}
+
+/// One of the routers supported by the generators.
+#[derive(Debug, Clone)]
+pub enum UcieRouter {
+    /// See [`RouterKind::Greedy`].
+    Greedy(GreedyRouter),
+    /// See [`RouterKind::Pathfinder`].
+    ///
+    /// If the pathfinder router fails, the nets are routed by `fallback` instead. If the
+    /// fallback was disabled, routing panics with the [`RouteError`], since
+    /// [`Router::route`] cannot return errors.
+    Pathfinder {
+        /// The negotiated-congestion router.
+        router: PathfinderRouter,
+        /// The router used if `router` returns an error, if enabled by
+        /// [`GenerationOptions::greedy_fallback`](crate::GenerationOptions::greedy_fallback).
+        fallback: Option<GreedyRouter>,
+    },
+    /// Leaves every net unrouted, for [`GenerationOptions::schematic_only`](crate::GenerationOptions::schematic_only).
+    Skip,
+}
+
+impl Router for UcieRouter {
+    fn route(
+        &self,
+        routing_state: &mut RoutingState<PdkLayer>,
+        to_connect: Vec<Vec<NodeKey>>,
+    ) -> Vec<Path> {
+        match self {
+            UcieRouter::Greedy(router) => router.route(routing_state, to_connect),
+            UcieRouter::Pathfinder { router, fallback } => {
+                match (router.try_route(routing_state, &to_connect), fallback) {
+                    (Ok(paths), _) => paths,
+                    (Err(e), Some(fallback)) => {
+                        tracing::warn!("pathfinder routing failed, falling back to greedy: {e}");
+                        fallback.route(routing_state, to_connect)
+                    }
+                    (Err(e), None) => panic!("pathfinder routing failed: {e}"),
+                }
+            }
+            UcieRouter::Skip => Vec::new(),
+        }
+    }
+}
+
+/// An error produced by [`negotiate`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RouteError {
+    /// A terminal of a net could not be reached at any cost.
+    #[error("net {net} has an unreachable terminal")]
+    Unreachable {
+        /// The index of the net.
+        net: usize,
+    },
+    /// Nets still shared grid points after the maximum number of iterations.
+    #[error("{overused} grid points remain congested after {iterations} iterations")]
+    Congested {
+        /// The number of grid points used by more than one net.
+        overused: usize,
+        /// The number of iterations run.
+        iterations: usize,
+    },
+}
+
+/// A graph that can be routed by [`negotiate`].
+pub trait RoutingGraph {
+    /// A node of the graph.
+    type Node: Copy + Eq + Ord + Hash;
+
+    /// Pushes the neighbors of `node` that may be used by net `net` onto `out`,
+    /// along with the base cost of the edge to each neighbor.
+    fn neighbors(&self, node: Self::Node, net: usize, out: &mut Vec<(Self::Node, f64)>);
+}
+
+/// A negotiated-congestion router based on the PathFinder algorithm.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PathfinderRouter {
+    /// The maximum number of rip-up-and-reroute iterations.
+    pub max_iterations: usize,
+    /// The initial cost factor of sharing a grid point with another net.
+    pub present_factor: f64,
+    /// The factor by which the sharing cost grows each iteration.
+    pub present_growth: f64,
+    /// The cost added to a grid point each iteration it is congested.
+    pub history_factor: f64,
+}
+
+impl Default for PathfinderRouter {
+    fn default() -> Self {
+        Self {
+            max_iterations: 50,
+            present_factor: 0.5,
+            present_growth: 1.5,
+            history_factor: 1.0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+struct Candidate<N> {
+    cost: f64,
+    node: N,
+}
+
+impl<N: PartialEq> Eq for Candidate<N> {}
+
+impl<N: Ord> Ord for Candidate<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so that `BinaryHeap` pops the cheapest candidate first.
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl<N: Ord> PartialOrd for Candidate<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Routes each group of terminals in `nets` as a tree, returning the edges of each tree.
+///
+/// Each node may be used by at most one net in the final solution.
+pub fn negotiate<G: RoutingGraph>(
+    graph: &G,
+    nets: &[Vec<G::Node>],
+    params: &PathfinderRouter,
+) -> Result<Vec<Vec<(G::Node, G::Node)>>, RouteError> {
+    let mut history: HashMap<G::Node, f64> = HashMap::new();
+    let mut usage: HashMap<G::Node, usize> = HashMap::new();
+    for terminals in nets {
+        for node in tree_nodes(&[], terminals) {
+            *usage.entry(node).or_default() += 1;
+        }
+    }
+    let mut trees: Vec<Vec<(G::Node, G::Node)>> = vec![Vec::new(); nets.len()];
+    let mut present = params.present_factor;
+    let mut overused = usage.values().filter(|&&count| count > 1).count();
+
+    for _ in 0..params.max_iterations {
+        for (net, terminals) in nets.iter().enumerate() {
+            for node in tree_nodes(&trees[net], terminals) {
+                *usage.get_mut(&node).unwrap() -= 1;
+            }
+            trees[net] = route_net(graph, net, terminals, &usage, &history, present)?;
+            for node in tree_nodes(&trees[net], terminals) {
+                *usage.entry(node).or_default() += 1;
+            }
+        }
+
+        overused = 0;
+        for (&node, &count) in usage.iter().filter(|(_, &count)| count > 1) {
+            *history.entry(node).or_default() += params.history_factor * (count - 1) as f64;
+            overused += 1;
+        }
+        if overused == 0 {
+            return Ok(trees);
+        }
+        present *= params.present_growth;
+    }
+    Err(RouteError::Congested {
+        overused,
+        iterations: params.max_iterations,
+    })
+}
+
+fn tree_nodes<N: Copy + Eq + Hash>(tree: &[(N, N)], terminals: &[N]) -> HashSet<N> {
+    tree.iter()
+        .flat_map(|&(a, b)| [a, b])
+        .chain(terminals.iter().copied())
+        .collect()
+}
+
+/// Routes a single net with Dijkstra's algorithm, growing a tree from the first terminal.
+fn route_net<G: RoutingGraph>(
+    graph: &G,
+    net: usize,
+    terminals: &[G::Node],
+    usage: &HashMap<G::Node, usize>,
+    history: &HashMap<G::Node, f64>,
+    present: f64,
+) -> Result<Vec<(G::Node, G::Node)>, RouteError> {
+    let Some((&first, rest)) = terminals.split_first() else {
+        return Ok(Vec::new());
+    };
+    let cost_of = |node: &G::Node, base: f64| {
+        let shared = usage.get(node).copied().unwrap_or_default() as f64;
+        base * (1. + history.get(node).copied().unwrap_or_default()) * (1. + present * shared)
+    };
+
+    let mut tree = HashSet::from([first]);
+    let mut edges = Vec::new();
+    let mut neighbors = Vec::new();
+    for &target in rest {
+        if tree.contains(&target) {
+            continue;
+        }
+        let mut best: HashMap<G::Node, f64> = tree.iter().map(|&n| (n, 0.)).collect();
+        let mut prev: HashMap<G::Node, G::Node> = HashMap::new();
+        let mut heap = tree
+            .iter()
+            .map(|&node| Candidate { cost: 0., node })
+            .collect::<BinaryHeap<_>>();
+        while let Some(Candidate { cost, node }) = heap.pop() {
+            if node == target {
+                break;
+            }
+            if cost > best[&node] {
+                continue;
+            }
+            neighbors.clear();
+            graph.neighbors(node, net, &mut neighbors);
+            for &(next, base) in neighbors.iter() {
+                let next_cost = cost + cost_of(&next, base);
+                if best.get(&next).map_or(true, |&c| next_cost < c) {
+                    best.insert(next, next_cost);
+                    prev.insert(next, node);
+                    heap.push(Candidate {
+                        cost: next_cost,
+                        node: next,
+                    });
+                }
+            }
+        }
+
+        let mut node = target;
+        while !tree.contains(&node) {
+            let &from = prev.get(&node).ok_or(RouteError::Unreachable { net })?;
+            edges.push((from, node));
+            tree.insert(node);
+            node = from;
+        }
+    }
+    Ok(edges)
+}
+
+/// Adapts an ATOLL routing grid to [`RoutingGraph`].
+///
+/// Vias cost twice as much as a single track step.
+struct AtollGraph<'a> {
+    state: &'a RoutingState<PdkLayer>,
+    roots: Vec<NodeKey>,
+}
+
+impl RoutingGraph for AtollGraph<'_> {
+    type Node = NodeKey;
+
+    fn neighbors(&self, node: NodeKey, net: usize, out: &mut Vec<(NodeKey, f64)>) {
+        out.extend(
+            self.state
+                .successors(node, self.roots[net])
+                .into_iter()
+                .map(|next| (next, if next.layer == node.layer { 1. } else { 2. })),
+        );
+    }
+}
+
+impl PathfinderRouter {
+    /// Routes `to_connect` on `routing_state`, or returns an error if the nets cannot
+    /// be routed without congestion.
+    ///
+    /// `routing_state` is only modified if routing succeeds.
+    pub fn try_route(
+        &self,
+        routing_state: &mut RoutingState<PdkLayer>,
+        to_connect: &[Vec<NodeKey>],
+    ) -> Result<Vec<Path>, RouteError> {
+        let roots = to_connect
+            .iter()
+            .map(|group| routing_state.find(group[0]))
+            .collect();
+        let graph = AtollGraph {
+            state: routing_state,
+            roots,
+        };
+        let trees = negotiate(&graph, to_connect, self)?;
+        let AtollGraph { roots, .. } = graph;
+        Ok(trees
+            .into_iter()
+            .zip(roots)
+            .map(|(tree, root)| {
+                for &(a, b) in tree.iter() {
+                    routing_state.occupy(a, b, root);
+                }
+                tree
+            })
+            .collect())
+    }
+}
+
+/// A meander inserted into a straight route to lengthen it.
+///
+/// Each fold leaves the route's track, runs `amplitude` tracks away, and comes back one
+/// track pitch later, adding `2 * amplitude` track pitches of wire.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Serpentine {
+    /// The number of folds.
+    pub folds: i64,
+    /// The height of each fold, in tracks.
+    pub amplitude: i64,
+}
+
+impl Serpentine {
+    /// The wire length added by the serpentine on tracks with the given pitch.
+    pub fn added_length(&self, pitch: i64) -> i64 {
+        2 * self.folds * self.amplitude * pitch
+    }
+}
+
+/// Chooses the serpentine that brings a route of length `short` closest to `long`.
+///
+/// At most `max_folds` folds of at most `max_amplitude` tracks are considered. Ties go
+/// to the serpentine that adds the least wire, so no serpentine is added unless it
+/// reduces the mismatch.
+pub fn match_length(
+    short: i64,
+    long: i64,
+    pitch: i64,
+    max_folds: i64,
+    max_amplitude: i64,
+) -> Serpentine {
+    let delta = long - short;
+    let mut best = Serpentine::default();
+    let mut best_residual = delta.abs();
+    for folds in 1..=max_folds {
+        for amplitude in 1..=max_amplitude {
+            let candidate = Serpentine { folds, amplitude };
+            let added = candidate.added_length(pitch);
+            let residual = (delta - added).abs();
+            if residual < best_residual
+                || (residual == best_residual && added < best.added_length(pitch))
+            {
+                best = candidate;
+                best_residual = residual;
+            }
+        }
+    }
+    best
+}
+
+/// Routing annotations that reserve grounded shield tracks around a net.
+///
+/// Implemented for [`TileBuilder`] as an extension, since ATOLL owns the tile builder.
+pub trait ShieldNet {
+    /// Reserves the tracks of `corridor` on each layer in `layers` for `net`, and ties
+    /// the track on either side of them to `vss` across the length of the corridor.
+    ///
+    /// The shields are drawn and assigned to `vss` in the routing grid, so the router
+    /// keeps other nets out of them and strapping `vss` connects them to the rail. The
+    /// corridor should cover the path the route of `net` is expected to take, such as
+    /// the gap between its driver and its load.
+    fn shield_net(
+        &mut self,
+        net: Node,
+        vss: Node,
+        corridor: Rect,
+        layers: Range<usize>,
+    ) -> substrate::error::Result<()>;
+}
+
+impl<PDK: Pdk + Schema> ShieldNet for TileBuilder<'_, PDK> {
+    fn shield_net(
+        &mut self,
+        net: Node,
+        vss: Node,
+        corridor: Rect,
+        layers: Range<usize>,
+    ) -> substrate::error::Result<()> {
+        let layer_stack = self.layer_stack.clone();
+        for layer in layers {
+            let id = layer_stack.layers[layer].id;
+            let tracks = layer_stack.layers[layer].inner.tracks();
+            let dir = layer_stack.layer(layer).dir().track_dir();
+            let along = corridor.span(dir);
+            let across = corridor.span(dir.other());
+
+            // A corridor narrower than a track still reserves the nearest track.
+            let first = tracks.to_track_idx(across.start(), RoundingMode::Up);
+            let last = tracks.to_track_idx(across.stop(), RoundingMode::Down);
+            let (first, last) = if first > last {
+                let center = tracks.to_track_idx(across.center(), RoundingMode::Nearest);
+                (center, center)
+            } else {
+                (first, last)
+            };
+
+            let slice = layer_stack.slice(0..layer + 1);
+            let reserved = Rect::from_dir_spans(
+                dir,
+                along,
+                Span::new(tracks.get(first).start(), tracks.get(last).stop()),
+            );
+            if let Some(reserved) = slice.shrink_to_lcm_units(reserved) {
+                self.assign_grid_points(Some(net), layer, reserved);
+            }
+            for track in [first - 1, last + 1] {
+                let shield = Rect::from_dir_spans(dir, along, tracks.get(track));
+                self.layout.draw(Shape::new(id, shield))?;
+                if let Some(shield) = slice.shrink_to_lcm_units(shield) {
+                    self.assign_grid_points(Some(vss), layer, shield);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The lengths of the two routes of a differential pair, in layout units.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct DiffRouteLengths {
+    /// The length of the route of the positive net.
+    pub p: i64,
+    /// The length of the route of the negative net.
+    pub n: i64,
+}
+
+impl DiffRouteLengths {
+    /// The residual P/N route length mismatch.
+    pub fn mismatch(&self) -> i64 {
+        (self.p - self.n).abs()
+    }
+}
+
+// Route lengths do not change when the layout is moved or rotated.
+impl TranslateMut for DiffRouteLengths {
+    fn translate_mut(&mut self, _p: Point) {}
+}
+
+impl TransformMut for DiffRouteLengths {
+    fn transform_mut(&mut self, _trans: Transformation) {}
+}
+
+/// Estimates the lengths of a differential pair routed on tracks centered at `p_track`
+/// and `n_track`, spanning `along`, with the pins of each net given as
+/// `(along, across)` center coordinates.
+///
+/// Each pin adds a stub across to its track and along to the nearer end of the span if
+/// it lies outside it.
+fn diff_route_lengths(
+    along: Span,
+    p_track: i64,
+    n_track: i64,
+    p_pins: &[(i64, i64)],
+    n_pins: &[(i64, i64)],
+) -> DiffRouteLengths {
+    let length = |track: i64, pins: &[(i64, i64)]| {
+        along.length()
+            + pins
+                .iter()
+                .map(|&(x, y)| (y - track).abs() + (along.start() - x).max(x - along.stop()).max(0))
+                .sum::<i64>()
+    };
+    DiffRouteLengths {
+        p: length(p_track, p_pins),
+        n: length(n_track, n_pins),
+    }
+}
+
+/// A routing constraint that routes a differential pair on adjacent tracks.
+///
+/// Implemented for [`TileBuilder`] as an extension, since ATOLL owns the tile builder.
+pub trait RouteDifferential {
+    /// Draws `p` and `n` on tracks `track` and `track + 1` of `layer`, spanning all of
+    /// their pins, and returns the estimated length of each route.
+    ///
+    /// The net whose pins are nearer lower track indices gets `track`, so the routes do
+    /// not cross. Both drawn routes have the same length, so the residual mismatch
+    /// comes from the stubs the router adds between each pin and its track.
+    fn route_differential(
+        &mut self,
+        p: (Node, &[Rect]),
+        n: (Node, &[Rect]),
+        layer: usize,
+        track: i64,
+    ) -> substrate::error::Result<DiffRouteLengths>;
+}
+
+impl<PDK: Pdk + Schema> RouteDifferential for TileBuilder<'_, PDK> {
+    fn route_differential(
+        &mut self,
+        p: (Node, &[Rect]),
+        n: (Node, &[Rect]),
+        layer: usize,
+        track: i64,
+    ) -> substrate::error::Result<DiffRouteLengths> {
+        let layer_stack = self.layer_stack.clone();
+        let id = layer_stack.layers[layer].id;
+        let tracks = layer_stack.layers[layer].inner.tracks();
+        let width = tracks.get(0).length();
+        let dir = layer_stack.layer(layer).dir().track_dir();
+        let centers = |pins: &[Rect]| {
+            pins.iter()
+                .map(|pin| {
+                    let center = pin.center();
+                    match dir {
+                        Dir::Horiz => (center.x, center.y),
+                        Dir::Vert => (center.y, center.x),
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        let (p_pins, n_pins) = (centers(p.1), centers(n.1));
+        assert!(
+            !p_pins.is_empty() && !n_pins.is_empty(),
+            "both nets of a differential pair need pins"
+        );
+        let mean =
+            |pins: &[(i64, i64)]| pins.iter().map(|&(_, y)| y).sum::<i64>() / pins.len() as i64;
+        let (p_track, n_track) = if mean(&p_pins) <= mean(&n_pins) {
+            (track, track + 1)
+        } else {
+            (track + 1, track)
+        };
+        let xs = || p_pins.iter().chain(n_pins.iter()).map(|&(x, _)| x);
+        let along = Span::new(xs().min().unwrap(), xs().max().unwrap());
+
+        let slice = layer_stack.slice(0..layer + 1);
+        for (net, track) in [(p.0, p_track), (n.0, n_track)] {
+            let drawn = Span::new(along.start() - width / 2, along.stop() + width / 2);
+            let rect = Rect::from_dir_spans(dir, drawn, tracks.get(track));
+            self.layout.draw(Shape::new(id, rect))?;
+            if let Some(bounds) = slice.shrink_to_lcm_units(rect) {
+                self.assign_grid_points(Some(net), layer, bounds);
+            }
+        }
+
+        Ok(diff_route_lengths(
+            along,
+            tracks.get(p_track).center(),
+            tracks.get(n_track).center(),
+            &p_pins,
+            &n_pins,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A three-column grid whose middle column is blocked except at the given rows.
+    struct Grid {
+        height: i64,
+        openings: Vec<i64>,
+    }
+
+    impl RoutingGraph for Grid {
+        type Node = (i64, i64);
+
+        fn neighbors(&self, (x, y): (i64, i64), _net: usize, out: &mut Vec<((i64, i64), f64)>) {
+            for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                let (nx, ny) = (x + dx, y + dy);
+                let in_bounds = (0..3).contains(&nx) && (0..self.height).contains(&ny);
+                let blocked = nx == 1 && !self.openings.contains(&ny);
+                if in_bounds && !blocked {
+                    out.push(((nx, ny), 1.));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pathfinder_falls_back_to_greedy_by_default() {
+        let ctx = Context::builder().build();
+        assert!(matches!(
+            router(&ctx, RouterKind::Pathfinder),
+            UcieRouter::Pathfinder {
+                fallback: Some(_),
+                ..
+            }
+        ));
+
+        let ctx = Context::builder()
+            .install(crate::GenerationOptions {
+                greedy_fallback: false,
+                ..Default::default()
+            })
+            .build();
+        assert!(matches!(
+            router(&ctx, RouterKind::Pathfinder),
+            UcieRouter::Pathfinder { fallback: None, .. }
+        ));
+    }
+
+    #[test]
+    fn diff_route_lengths_count_stubs() {
+        let along = Span::new(0, 1_000);
+        let lengths = diff_route_lengths(
+            along,
+            100,
+            200,
+            &[(0, 0), (1_000, 0)],
+            &[(500, 400), (1_200, 300)],
+        );
+        assert_eq!(lengths.p, 1_000 + 100 + 100);
+        assert_eq!(lengths.n, 1_000 + 200 + (100 + 200));
+        assert_eq!(lengths.mismatch(), 300);
+    }
+
+    #[test]
+    fn negotiates_shared_channel() {
+        // Net 1 is equally close to both openings, but net 0 needs the one at row 1.
+        let grid = Grid {
+            height: 5,
+            openings: vec![1, 3],
+        };
+        let nets = vec![vec![(0, 1), (2, 1)], vec![(0, 2), (2, 2)]];
+        let trees = negotiate(&grid, &nets, &PathfinderRouter::default()).unwrap();
+        let nodes = trees
+            .iter()
+            .zip(&nets)
+            .map(|(tree, terminals)| tree_nodes(tree, terminals))
+            .collect::<Vec<_>>();
+        assert!(nodes[0].is_disjoint(&nodes[1]));
+        assert!(nodes[1].contains(&(1, 3)));
+
+        let grid = Grid {
+            height: 5,
+            openings: vec![1],
+        };
+        assert!(matches!(
+            negotiate(&grid, &nets, &PathfinderRouter::default()),
+            Err(RouteError::Congested { iterations: 50, .. })
+        ));
+    }
+
+    #[test]
+    fn serpentine_matches_length() {
+        let serpentine = match_length(1000, 1600, 100, 4, 3);
+        assert_eq!(serpentine.added_length(100), 600);
+        assert_eq!(serpentine.folds, 1);
+
+        // Folding would overshoot by as much as it gains, so nothing is added.
+        assert_eq!(match_length(1000, 1100, 100, 4, 3), Serpentine::default());
+        // The serpentine saturates when the gap is too narrow for more folds.
+        assert_eq!(match_length(0, 10_000, 100, 2, 3).added_length(100), 1200);
+        assert_eq!(match_length(1000, 1000, 100, 4, 3), Serpentine::default());
+    }
+}
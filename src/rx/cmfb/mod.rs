@@ -0,0 +1,348 @@
+//! Common-mode bias generation for the receive front end.
+//!
+//! The CTLE and VGA stages have PMOS loads whose gates share a control voltage. [`Cmfb`]
+//! generates that voltage from a replica of one half of a stage: an error amplifier
+//! adjusts the replica load until the replica output equals `vref`, which is usually
+//! set by a reference DAC. Every stage whose loads and tail currents are scaled copies
+//! of the replica then settles to the same output common mode.
+//!
+//! [`tb::CmfbTb`] steps `vref` through a set of levels and measures the accuracy and
+//! settling of the replica common mode.
+
+pub mod tb;
+
+use crate::buffer::InverterImpl;
+use crate::params::{check_positive, setters, ParamsError};
+use crate::tiles::{MosKind, MosTileParams, TapTileParams, TileKind};
+use atoll::{IoBuilder, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::error::Result;
+use substrate::geometry::align::AlignMode;
+use substrate::io::{InOut, Input, Io, MosIoSchematic, Output, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+/// The interface to a [`Cmfb`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct CmfbIo {
+    /// The target output common mode.
+    pub vref: Input<Signal>,
+    /// The reference current, which flows into a diode-connected NMOS.
+    pub iref: InOut<Signal>,
+    /// The load gate voltage shared by the front-end stages.
+    pub vctl: Output<Signal>,
+    /// The replica output, which tracks `vref` once the loop settles.
+    pub vcm: Output<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of the [`Cmfb`] layout generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct CmfbParams {
+    /// The width of each error amplifier input device.
+    pub input_w: i64,
+    /// The width of each PMOS mirror load of the error amplifier.
+    pub load_w: i64,
+    /// The width of the error amplifier tail device.
+    pub tail_w: i64,
+    /// The width of the diode-connected device that carries the reference current.
+    pub bias_w: i64,
+    /// The width of the replica PMOS load.
+    pub replica_load_w: i64,
+    /// The width of the replica NMOS current sink.
+    pub replica_sink_w: i64,
+    /// The channel length of every device, or the technology minimum if `None`.
+    #[serde(default)]
+    pub length: Option<i64>,
+    /// The gate width of the PMOS compensation capacitor on `vctl`.
+    pub comp_w: i64,
+}
+
+impl CmfbParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> CmfbParamsBuilder {
+        CmfbParamsBuilder::default()
+    }
+
+    /// The tail current of the error amplifier for a reference current `iref`.
+    pub fn error_amp_current(&self, iref: f64) -> f64 {
+        iref * self.tail_w as f64 / self.bias_w as f64
+    }
+
+    /// The current through the replica for a reference current `iref`.
+    pub fn replica_current(&self, iref: f64) -> f64 {
+        iref * self.replica_sink_w as f64 / self.bias_w as f64
+    }
+
+    /// The current that a front-end load of width `afe_load_w` must carry for its output
+    /// to sit at `vref`, given a reference current `iref`.
+    ///
+    /// A stage whose tail current is twice this value settles to the replica common mode.
+    pub fn matched_load_current(&self, iref: f64, afe_load_w: i64) -> f64 {
+        self.replica_current(iref) * afe_load_w as f64 / self.replica_load_w as f64
+    }
+}
+
+/// A builder for [`CmfbParams`].
+///
+/// Defaults to minimum-length devices, an error amplifier biased at twice the reference
+/// current, a replica carrying the reference current, and an 8 um compensation capacitor.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct CmfbParamsBuilder {
+    params: CmfbParams,
+}
+
+impl Default for CmfbParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: CmfbParams {
+                input_w: 2_000,
+                load_w: 2_000,
+                tail_w: 2_000,
+                bias_w: 1_000,
+                replica_load_w: 1_000,
+                replica_sink_w: 1_000,
+                length: None,
+                comp_w: 8_000,
+            },
+        }
+    }
+}
+
+impl CmfbParamsBuilder {
+    setters! {
+        /// Sets the width of the error amplifier input devices.
+        input_w: i64,
+        /// Sets the width of the error amplifier mirror loads.
+        load_w: i64,
+        /// Sets the width of the error amplifier tail device.
+        tail_w: i64,
+        /// Sets the width of the reference current diode.
+        bias_w: i64,
+        /// Sets the width of the replica load.
+        replica_load_w: i64,
+        /// Sets the width of the replica current sink.
+        replica_sink_w: i64,
+        /// Sets the channel length of every device.
+        length: Option<i64>,
+        /// Sets the gate width of the compensation capacitor.
+        comp_w: i64,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<CmfbParams, ParamsError> {
+        let p = &self.params;
+        for (field, value) in [
+            ("input_w", p.input_w),
+            ("load_w", p.load_w),
+            ("tail_w", p.tail_w),
+            ("bias_w", p.bias_w),
+            ("replica_load_w", p.replica_load_w),
+            ("replica_sink_w", p.replica_sink_w),
+            ("comp_w", p.comp_w),
+        ] {
+            check_positive(field, value)?;
+        }
+        p.length
+            .map_or(Ok(()), |value| check_positive("length", value))?;
+        Ok(self.params)
+    }
+}
+
+/// A replica-biased common-mode control generator.
+///
+/// The replica is a PMOS load, driven by `vctl`, over an NMOS sink that mirrors the
+/// reference current. A five-transistor error amplifier compares the replica output
+/// with `vref` and drives `vctl`: a replica output above `vref` raises `vctl`, which
+/// starves the replica load and pulls its output back down. The compensation capacitor
+/// sets the dominant pole at `vctl`, together with the gate capacitance of the
+/// front-end loads connected there.
+///
+/// The PMOS row holds the error amplifier loads, the replica load, and the compensation
+/// capacitor. The NMOS row beneath holds the input pair, the tail, the reference diode,
+/// and the replica sink.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct Cmfb<T>(
+    CmfbParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> Cmfb<T> {
+    /// Creates a new [`Cmfb`].
+    pub fn new(params: CmfbParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The common-mode generator parameters.
+    pub fn params(&self) -> CmfbParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for Cmfb<T> {
+    type Io = CmfbIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("cmfb")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("cmfb", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for Cmfb<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for Cmfb<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for Cmfb<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let p = self.0;
+        let (vdd, vss) = (io.schematic.vdd, io.schematic.vss);
+        let (vref, iref) = (io.schematic.vref, io.schematic.iref);
+        let (vctl, vcm) = (io.schematic.vctl, io.schematic.vcm);
+        let mirror = cell.signal("mirror", Signal);
+        let tail = cell.signal("tail", Signal);
+
+        let mos = |kind, w| {
+            T::mos(
+                MosTileParams::new(MosKind::Nom, kind, w)
+                    .with_length(p.length)
+                    .snapped(T::snap_width),
+            )
+        };
+
+        // PMOS row: the diode-connected and output mirror loads of the error amplifier,
+        // the replica load, and the compensation capacitor from `vctl` to VDD.
+        let mut pmos = [
+            (p.load_w, mirror, mirror, vdd),
+            (p.load_w, vctl, mirror, vdd),
+            (p.replica_load_w, vcm, vctl, vdd),
+            (p.comp_w, vdd, vctl, vdd),
+        ]
+        .into_iter()
+        .map(|(w, d, g, s)| {
+            cell.generate_connected(mos(TileKind::P, w), MosIoSchematic { d, g, s, b: vdd })
+        })
+        .collect::<Vec<_>>();
+
+        // NMOS row: the input pair, with the replica on the diode side so that `vctl`
+        // follows the replica output, the tail, the reference diode, and the replica sink.
+        let mut nmos = [
+            (p.input_w, mirror, vcm, tail),
+            (p.input_w, vctl, vref, tail),
+            (p.tail_w, tail, iref, vss),
+            (p.bias_w, iref, iref, vss),
+            (p.replica_sink_w, vcm, iref, vss),
+        ]
+        .into_iter()
+        .map(|(w, d, g, s)| {
+            cell.generate_connected(mos(TileKind::N, w), MosIoSchematic { d, g, s, b: vss })
+        })
+        .collect::<Vec<_>>();
+
+        let ntap = cell.generate(T::tap(TapTileParams::new(TileKind::N, pmos.len() as i64)));
+        let mut ptap = cell.generate(T::tap(TapTileParams::new(TileKind::P, nmos.len() as i64)));
+        cell.connect(ntap.io().x, vdd);
+        cell.connect(ptap.io().x, vss);
+
+        let mut prev = ntap.lcm_bounds();
+        for row in [&mut pmos, &mut nmos] {
+            let mut left_rect = None;
+            for inst in row.iter_mut() {
+                match left_rect {
+                    None => {
+                        inst.align_rect_mut(prev, AlignMode::Left, 0);
+                        inst.align_rect_mut(prev, AlignMode::Beneath, 0);
+                        prev = inst.lcm_bounds();
+                    }
+                    Some(left_rect) => {
+                        inst.align_rect_mut(left_rect, AlignMode::Bottom, 0);
+                        inst.align_rect_mut(left_rect, AlignMode::ToTheRight, 0);
+                    }
+                }
+                left_rect = Some(inst.lcm_bounds());
+            }
+        }
+        ptap.align_rect_mut(prev, AlignMode::Left, 0);
+        ptap.align_rect_mut(prev, AlignMode::Beneath, 0);
+
+        let pmos = pmos
+            .into_iter()
+            .map(|inst| cell.draw(inst))
+            .collect::<Result<Vec<_>>>()?;
+        let nmos = nmos
+            .into_iter()
+            .map(|inst| cell.draw(inst))
+            .collect::<Result<Vec<_>>>()?;
+        let ntap = cell.draw(ntap)?;
+        let ptap = cell.draw(ptap)?;
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        io.layout.vref.merge(nmos[1].layout.io().g);
+        io.layout.iref.merge(nmos[3].layout.io().d);
+        io.layout.vctl.merge(pmos[1].layout.io().d);
+        io.layout.vcm.merge(pmos[2].layout.io().d);
+        io.layout.vdd.merge(ntap.layout.io().x);
+        io.layout.vss.merge(ptap.layout.io().x);
+
+        T::post_layout_hooks(cell)?;
+
+        Ok(((), ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn currents_scale_with_device_ratios() {
+        let params = CmfbParams::builder().build().unwrap();
+        let iref = 10e-6;
+        assert!((params.error_amp_current(iref) - 20e-6).abs() < 1e-15);
+        assert!((params.replica_current(iref) - 10e-6).abs() < 1e-15);
+        // A front-end load four times the replica load carries four times its current.
+        assert!((params.matched_load_current(iref, 4_000) - 40e-6).abs() < 1e-15);
+
+        let params = CmfbParams::builder().replica_sink_w(500).build().unwrap();
+        assert!((params.matched_load_current(iref, 4_000) - 20e-6).abs() < 1e-15);
+
+        assert!(matches!(
+            CmfbParams::builder().length(Some(0)).build(),
+            Err(ParamsError::NonPositive {
+                field: "length",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,440 @@
+//! Common-mode generator characterization.
+//!
+//! [`CmfbTb`] steps the reference of a [`Cmfb`] through a set of levels, as a reference
+//! DAC would when its code changes, and reduces each step of the replica common mode to
+//! its settled error, overshoot, and settling time. A loop that does not settle within
+//! the hold time of a level, such as one that oscillates, reports no settling time.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::{Isource, Vsource};
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Node};
+use substrate::io::{Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::primitives::Capacitor;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::report::SimArtifact;
+use crate::rx::cmfb::Cmfb;
+use crate::tb::pi::Samples;
+
+/// The rise and fall time of each reference step.
+const EDGE: Decimal = dec!(100e-12);
+/// The fraction of each hold time at the end of which the common mode must stay
+/// within the settling tolerance.
+const SETTLED_FRACTION: f64 = 0.25;
+
+/// A transient testbench that steps the reference of a [`Cmfb`] through `levels`,
+/// holding each level for `step`.
+///
+/// The testbench starts from the operating point at the first level. `c_load` models
+/// the gate capacitance of the front-end loads driven by `vctl`.
+#[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq; C)]
+#[derive(Serialize, Deserialize)]
+pub struct CmfbTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: Cmfb<T>,
+    /// The reference current, in amperes.
+    pub iref: Decimal,
+    /// The reference levels, in volts.
+    pub levels: Vec<Decimal>,
+    /// The time each level is held, in seconds.
+    pub step: Decimal,
+    /// The capacitance on `vctl`, in farads.
+    pub c_load: Decimal,
+    /// The band around its final value within which the common mode is settled, in volts.
+    pub tolerance: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> CmfbTb<T, PDK, C> {
+    /// Creates a new [`CmfbTb`].
+    pub fn new(
+        dut: Cmfb<T>,
+        iref: Decimal,
+        levels: Vec<Decimal>,
+        step: Decimal,
+        c_load: Decimal,
+        tolerance: Decimal,
+        pvt: Pvt<C>,
+    ) -> Self {
+        assert!(
+            !levels.is_empty(),
+            "at least one reference level is required"
+        );
+        Self {
+            dut,
+            iref,
+            levels,
+            step,
+            c_load,
+            tolerance,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The `(time, voltage)` points of the reference waveform.
+    fn vref_pwl(&self) -> Vec<(Decimal, Decimal)> {
+        let mut points = vec![(dec!(0), self.levels[0])];
+        for (k, pair) in self.levels.windows(2).enumerate() {
+            let t = self.step * Decimal::from(k + 1);
+            points.extend([(t, pair[0]), (t + EDGE, pair[1])]);
+        }
+        points
+    }
+}
+
+impl<
+        T: Any,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for CmfbTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("cmfb_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("cmfb_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`CmfbTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct CmfbTbNodes {
+    vcm: Node,
+    vctl: Node,
+}
+
+impl<T, PDK, C> ExportsNestedData for CmfbTb<T, PDK, C>
+where
+    CmfbTb<T, PDK, C>: Block,
+{
+    type NestedData = CmfbTbNodes;
+}
+
+impl<T, PDK: Schema, C: Copy> Schematic<Spectre> for CmfbTb<T, PDK, C>
+where
+    CmfbTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Cmfb<T>: Schematic<PDK>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vss = io.vss;
+        let vdd = cell.signal("vdd", Signal);
+        let vref = cell.signal("vref", Signal);
+        let iref = cell.signal("iref", Signal);
+        let vctl = cell.signal("vctl", Signal);
+        let vcm = cell.signal("vcm", Signal);
+
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut);
+        cell.connect(dut.io().vdd, vdd);
+        cell.connect(dut.io().vss, vss);
+        cell.connect(dut.io().vref, vref);
+        cell.connect(dut.io().iref, iref);
+        cell.connect(dut.io().vctl, vctl);
+        cell.connect(dut.io().vcm, vcm);
+
+        cell.instantiate_connected(
+            Vsource::dc(self.pvt.voltage),
+            TwoTerminalIoSchematic { p: vdd, n: vss },
+        );
+        cell.instantiate_connected(
+            Vsource::pwl(self.vref_pwl()),
+            TwoTerminalIoSchematic { p: vref, n: vss },
+        );
+        // The reference diode sinks the reference current from VDD.
+        cell.instantiate_connected(
+            Isource::dc(self.iref),
+            TwoTerminalIoSchematic { p: vdd, n: iref },
+        );
+        cell.instantiate_connected(
+            Capacitor::new(self.c_load),
+            TwoTerminalIoSchematic { p: vctl, n: vss },
+        );
+
+        Ok(CmfbTbNodes { vcm, vctl })
+    }
+}
+
+/// The resulting waveforms of a [`CmfbTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct CmfbSim {
+    /// The simulation time.
+    pub t: tran::Time,
+    /// The replica common mode.
+    pub vcm: tran::Voltage,
+    /// The load gate control voltage.
+    pub vctl: tran::Voltage,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, CmfbSim> for CmfbTb<T, PDK, C>
+where
+    CmfbTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <CmfbSim as FromSaved<Spectre, Tran>>::SavedKey {
+        CmfbSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            vcm: tran::Voltage::save(ctx, &cell.vcm, opts),
+            vctl: tran::Voltage::save(ctx, &cell.vctl, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for CmfbTb<T, PDK, C>
+where
+    CmfbTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = CmfbResponse;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: CmfbSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: self.step * Decimal::from(self.levels.len()),
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+        CmfbResponse::new(
+            Samples {
+                t: &wav.t,
+                v: &wav.vcm,
+            },
+            &self
+                .levels
+                .iter()
+                .map(|level| level.to_f64().unwrap())
+                .collect::<Vec<_>>(),
+            self.step.to_f64().unwrap(),
+            self.tolerance.to_f64().unwrap(),
+        )
+    }
+}
+
+/// The response of the replica common mode to one reference level.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CmfbStep {
+    /// The reference level, in volts.
+    pub vref: f64,
+    /// The common mode at the end of the hold time, in volts.
+    pub vcm: f64,
+    /// The largest excursion past the final value in the direction of the step,
+    /// relative to the step size.
+    ///
+    /// Zero for the first level and for steps smaller than the settling tolerance.
+    pub overshoot: f64,
+    /// The time from the start of the step after which the common mode stays within
+    /// the settling tolerance, in seconds.
+    ///
+    /// `None` if the common mode is still outside the tolerance near the end of the
+    /// hold time.
+    pub settling: Option<f64>,
+}
+
+impl CmfbStep {
+    /// The settled common-mode error, in volts.
+    pub fn error(&self) -> f64 {
+        self.vcm - self.vref
+    }
+}
+
+/// The results of a [`CmfbTb`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CmfbResponse {
+    /// The response to each reference level, in order.
+    pub steps: Vec<CmfbStep>,
+}
+
+impl CmfbResponse {
+    /// Reduces a common-mode waveform to one [`CmfbStep`] per level, where level `k` is
+    /// applied from `k * step` to `(k + 1) * step`.
+    pub fn new(vcm: Samples<'_>, levels: &[f64], step: f64, tolerance: f64) -> Self {
+        let mut steps: Vec<CmfbStep> = Vec::with_capacity(levels.len());
+        for (k, &vref) in levels.iter().enumerate() {
+            let (start, stop) = (k as f64 * step, (k + 1) as f64 * step);
+            let last = vcm.value_at(stop);
+            let window = vcm
+                .t
+                .iter()
+                .zip(vcm.v)
+                .filter(|(&t, _)| t >= start && t <= stop)
+                .collect::<Vec<_>>();
+
+            let delta = steps.last().map_or(0., |prev| last - prev.vcm);
+            let overshoot = if delta.abs() < tolerance {
+                0.
+            } else {
+                window
+                    .iter()
+                    .map(|(_, &v)| (v - last) * delta.signum() / delta.abs())
+                    .fold(0., f64::max)
+            };
+
+            let unsettled = window
+                .iter()
+                .rev()
+                .find(|(_, &v)| (v - last).abs() > tolerance)
+                .map(|(&t, _)| t);
+            let settling = match unsettled {
+                Some(t) if t > stop - SETTLED_FRACTION * step => None,
+                Some(t) => Some(t - start),
+                None => Some(0.),
+            };
+
+            steps.push(CmfbStep {
+                vref,
+                vcm: last,
+                overshoot,
+                settling,
+            });
+        }
+        Self { steps }
+    }
+
+    /// The largest settled error magnitude of any level, in volts.
+    pub fn worst_error(&self) -> f64 {
+        self.steps
+            .iter()
+            .map(|step| step.error().abs())
+            .fold(0., f64::max)
+    }
+
+    /// The largest overshoot of any step, relative to the step size.
+    pub fn worst_overshoot(&self) -> f64 {
+        self.steps
+            .iter()
+            .map(|step| step.overshoot)
+            .fold(0., f64::max)
+    }
+
+    /// The longest settling time of any step, or `None` if any step did not settle.
+    pub fn worst_settling(&self) -> Option<f64> {
+        self.steps
+            .iter()
+            .map(|step| step.settling)
+            .try_fold(0., |worst, settling| settling.map(|t| f64::max(worst, t)))
+    }
+}
+
+impl SimArtifact for CmfbResponse {
+    fn csv_header(&self) -> Vec<String> {
+        ["vref", "vcm", "error", "overshoot", "settling"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.steps
+            .iter()
+            .map(|step| {
+                vec![
+                    step.vref.to_string(),
+                    step.vcm.to_string(),
+                    step.error().to_string(),
+                    step.overshoot.to_string(),
+                    step.settling.map(|t| t.to_string()).unwrap_or_default(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_error_overshoot_and_settling() {
+        let (step, tau) = (1e-6, 50e-9);
+        let t = (0..=2000).map(|i| i as f64 * 1e-9).collect::<Vec<_>>();
+        // A 2 mV offset and an underdamped 200 mV step at 1 us.
+        let ringing = t
+            .iter()
+            .map(|&t| {
+                if t < step {
+                    0.602
+                } else {
+                    let dt = t - step;
+                    0.802 - 0.2 * (-dt / tau).exp() * (2e7 * std::f64::consts::PI * dt).cos()
+                }
+            })
+            .collect::<Vec<_>>();
+        let response = CmfbResponse::new(Samples { t: &t, v: &ringing }, &[0.6, 0.8], step, 1e-3);
+        assert!((response.worst_error() - 2e-3).abs() < 1e-9);
+        assert_eq!(response.steps[0].overshoot, 0.);
+        assert_eq!(response.steps[0].settling, Some(0.));
+        let overshoot = response.steps[1].overshoot;
+        assert!(overshoot > 0.3 && overshoot < 0.5, "{overshoot}");
+        let settling = response.worst_settling().unwrap();
+        assert!(settling > 3. * tau && settling < 6. * tau, "{settling}");
+
+        // A loop that rings for the whole hold time never settles.
+        let oscillating = t
+            .iter()
+            .map(|&t| 0.6 + 0.01 * (2e7 * std::f64::consts::PI * t).sin())
+            .collect::<Vec<_>>();
+        let response = CmfbResponse::new(
+            Samples {
+                t: &t,
+                v: &oscillating,
+            },
+            &[0.6, 0.6],
+            step,
+            1e-3,
+        );
+        assert_eq!(response.worst_settling(), None);
+    }
+}
@@ -0,0 +1,538 @@
+//! Receive front-end characterization.
+//!
+//! [`characterize_afe`] is the receive-side counterpart of
+//! [`simulate_driver`](crate::driver::tb::simulate_driver). It sweeps a continuous-time
+//! front-end stage, such as a CTLE or VGA, across its control codes and a set of corners,
+//! and reduces each frequency response to gain, peaking, and bandwidth.
+//!
+//! Stages are characterized independently. [`AfeResponse::cascade`] combines the
+//! responses of a CTLE and the VGA that follows it at the same corner.
+//!
+//! The [`cmfb`] module generates the load bias that sets the output common mode of the
+//! front-end stages.
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::ac::{Ac, Sweep};
+use spectre::blocks::{AcSource, Vsource};
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::path::Path;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::context::PdkContext;
+use substrate::io::schematic::{HardwareType, Node};
+use substrate::io::{
+    Array, DiffPair, InOut, Input, Io, Output, Signal, TestbenchIo, TwoTerminalIoSchematic,
+};
+use substrate::pdk::corner::Pvt;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{ac, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::plot::{Plot, Scale, Series};
+use crate::report::SimArtifact;
+use crate::sweep::CornerSweep;
+
+pub mod cmfb;
+
+/// The interface to a receive front-end stage with a digital control code.
+#[derive(Debug, Default, Clone, Io)]
+pub struct AfeIo {
+    /// The differential input.
+    pub input: Input<DiffPair>,
+    /// The differential output.
+    pub output: Output<DiffPair>,
+    /// The gain or peaking control code, least significant bit first.
+    pub code: Array<Input<Signal>>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// A small-signal testbench that applies a 1 V differential AC input to a front-end stage
+/// and measures its differential output.
+#[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct AfeAcTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: T,
+    /// The control code.
+    pub code: usize,
+    /// The input common-mode voltage.
+    pub vcm: Decimal,
+    /// The start frequency.
+    pub fstart: Decimal,
+    /// The stop frequency.
+    pub fstop: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> AfeAcTb<T, PDK, C> {
+    /// Creates a new [`AfeAcTb`].
+    pub fn new(
+        dut: T,
+        code: usize,
+        vcm: Decimal,
+        fstart: Decimal,
+        fstop: Decimal,
+        pvt: Pvt<C>,
+    ) -> Self {
+        Self {
+            dut,
+            code,
+            vcm,
+            fstart,
+            fstop,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for AfeAcTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("afe_ac_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("afe_ac_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`AfeAcTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct AfeAcTbNodes {
+    out_p: Node,
+    out_n: Node,
+}
+
+impl<T, PDK, C> ExportsNestedData for AfeAcTb<T, PDK, C>
+where
+    AfeAcTb<T, PDK, C>: Block,
+{
+    type NestedData = AfeAcTbNodes;
+}
+
+impl<T: Block<Io = AfeIo> + Schematic<PDK> + Clone, PDK: Schema, C> Schematic<Spectre>
+    for AfeAcTb<T, PDK, C>
+where
+    AfeAcTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vss = io.vss;
+        let vdd = cell.signal("vdd", Signal);
+        let input = cell.signal("input", DiffPair::default());
+        let output = cell.signal("output", DiffPair::default());
+
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
+        assert!(
+            self.code < 1 << dut.io().code.len(),
+            "code {} does not fit in {} bits",
+            self.code,
+            dut.io().code.len()
+        );
+        for i in 0..dut.io().code.len() {
+            let level = if (self.code >> i) & 1 == 1 { vdd } else { vss };
+            cell.connect(&dut.io().code[i], level);
+        }
+        cell.connect(dut.io().input, &input);
+        cell.connect(dut.io().output, &output);
+        cell.connect(dut.io().vdd, vdd);
+        cell.connect(dut.io().vss, vss);
+
+        cell.instantiate_connected(
+            Vsource::dc(self.pvt.voltage),
+            TwoTerminalIoSchematic { p: vdd, n: vss },
+        );
+        for (node, phase) in [(input.p, dec!(0)), (input.n, dec!(180))] {
+            cell.instantiate_connected(
+                Vsource::ac(AcSource {
+                    dc: self.vcm,
+                    mag: dec!(0.5),
+                    phase,
+                }),
+                TwoTerminalIoSchematic { p: node, n: vss },
+            );
+        }
+
+        Ok(AfeAcTbNodes {
+            out_p: output.p,
+            out_n: output.n,
+        })
+    }
+}
+
+/// The resulting waveforms of an [`AfeAcTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct AfeAcSim {
+    /// The simulation frequency.
+    pub freq: ac::Freq,
+    /// The positive output voltage.
+    pub out_p: ac::Voltage,
+    /// The negative output voltage.
+    pub out_n: ac::Voltage,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Ac, AfeAcSim> for AfeAcTb<T, PDK, C>
+where
+    AfeAcTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <AfeAcSim as FromSaved<Spectre, Ac>>::SavedKey {
+        AfeAcSimSavedKey {
+            freq: ac::Freq::save(ctx, (), opts),
+            out_p: ac::Voltage::save(ctx, &cell.out_p, opts),
+            out_n: ac::Voltage::save(ctx, &cell.out_n, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for AfeAcTb<T, PDK, C>
+where
+    AfeAcTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = AfeResponse;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let sim: AfeAcSim = sim
+            .simulate(
+                opts,
+                Ac {
+                    start: self.fstart,
+                    stop: self.fstop,
+                    sweep: Sweep::Decade(40),
+                    errpreset: Some(ErrPreset::Conservative),
+                },
+            )
+            .expect("failed to run simulation");
+        AfeResponse {
+            freq: sim.freq.to_vec(),
+            gain: sim
+                .out_p
+                .iter()
+                .zip(sim.out_n.iter())
+                .map(|(p, n)| (p - n).norm())
+                .collect(),
+        }
+    }
+}
+
+/// The differential gain of a front-end stage across frequency.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AfeResponse {
+    /// The frequency vector, in Hz.
+    pub freq: Vec<f64>,
+    /// The gain magnitude at each frequency, in V/V.
+    pub gain: Vec<f64>,
+}
+
+impl AfeResponse {
+    /// Returns the response of this stage followed by `next`.
+    ///
+    /// Both responses must be sampled at the same frequencies. Loading of this stage
+    /// by the next is not captured.
+    pub fn cascade(&self, next: &AfeResponse) -> AfeResponse {
+        assert_eq!(
+            self.freq, next.freq,
+            "cascaded responses must share a frequency vector"
+        );
+        AfeResponse {
+            freq: self.freq.clone(),
+            gain: self
+                .gain
+                .iter()
+                .zip(next.gain.iter())
+                .map(|(a, b)| a * b)
+                .collect(),
+        }
+    }
+
+    /// Summarizes the response.
+    ///
+    /// The DC gain is the gain at the lowest simulated frequency.
+    pub fn metrics(&self) -> AfeMetrics {
+        let db = |g: f64| 20. * g.log10();
+        let dc_gain = self.gain[0];
+        let (peak_idx, &peak_gain) = self
+            .gain
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .expect("response must not be empty");
+        // The -3 dB point is referenced to the DC gain and searched for above the peak,
+        // interpolating linearly in log-frequency between samples.
+        let threshold = dc_gain / 2f64.sqrt();
+        let bandwidth = (peak_idx + 1..self.gain.len())
+            .find(|&i| self.gain[i] < threshold)
+            .map(|i| {
+                let (f0, f1) = (self.freq[i - 1].log10(), self.freq[i].log10());
+                let (g0, g1) = (self.gain[i - 1], self.gain[i]);
+                10f64.powf(f0 + (f1 - f0) * (g0 - threshold) / (g0 - g1))
+            });
+        AfeMetrics {
+            dc_gain_db: db(dc_gain),
+            peak_gain_db: db(peak_gain),
+            peaking_db: db(peak_gain) - db(dc_gain),
+            peak_freq: self.freq[peak_idx],
+            bandwidth,
+        }
+    }
+}
+
+/// Gain, peaking, and bandwidth of a front-end stage.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AfeMetrics {
+    /// The DC gain, in dB.
+    pub dc_gain_db: f64,
+    /// The maximum gain, in dB.
+    pub peak_gain_db: f64,
+    /// The maximum gain relative to the DC gain, in dB.
+    pub peaking_db: f64,
+    /// The frequency of maximum gain, in Hz.
+    pub peak_freq: f64,
+    /// The frequency at which the gain falls 3 dB below the DC gain, in Hz.
+    ///
+    /// `None` if the gain stays within 3 dB up to the stop frequency.
+    pub bandwidth: Option<f64>,
+}
+
+/// The parameters of [`characterize_afe`].
+#[derive(Clone, Debug)]
+pub struct AfeCharParams<T, C> {
+    /// The front-end stage.
+    pub dut: T,
+    /// The control codes to sweep.
+    pub codes: Vec<usize>,
+    /// The corners to simulate at each code.
+    pub pvts: Vec<Pvt<C>>,
+    /// The input common-mode voltage.
+    pub vcm: Decimal,
+    /// The start frequency.
+    pub fstart: Decimal,
+    /// The stop frequency.
+    pub fstop: Decimal,
+}
+
+/// The response of a front-end stage at one code and corner.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AfePoint<C> {
+    /// The control code.
+    pub code: usize,
+    /// The corner.
+    pub pvt: Pvt<C>,
+    /// The frequency response.
+    pub response: AfeResponse,
+    /// The summarized response.
+    pub metrics: AfeMetrics,
+}
+
+/// The results of [`characterize_afe`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AfeCharacterization<C> {
+    /// The simulated points, ordered by code and then by corner.
+    pub points: Vec<AfePoint<C>>,
+}
+
+impl<C: Debug> AfeCharacterization<C> {
+    /// Plots the gain at `code` with one series per corner.
+    pub fn plot(&self, code: usize) -> Plot {
+        self.points.iter().filter(|point| point.code == code).fold(
+            Plot::new(
+                format!("Front-end response, code {code}"),
+                "Frequency (Hz)",
+                "Gain (dB)",
+            )
+            .with_x_scale(Scale::Log),
+            |plot, point| {
+                let points = point
+                    .response
+                    .freq
+                    .iter()
+                    .zip(point.response.gain.iter())
+                    .map(|(&f, &g)| (f, 20. * g.log10()))
+                    .collect();
+                plot.with_series(Series::new(
+                    format!(
+                        "{:?}, {} V, {} C",
+                        point.pvt.corner, point.pvt.voltage, point.pvt.temp
+                    ),
+                    points,
+                ))
+            },
+        )
+    }
+
+    /// Writes a frequency-response plot for each code to `<dir>/code<code>.svg`.
+    pub fn write_plots(&self, dir: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut codes = self
+            .points
+            .iter()
+            .map(|point| point.code)
+            .collect::<Vec<_>>();
+        codes.dedup();
+        for code in codes {
+            self.plot(code)
+                .write_svg(dir.as_ref().join(format!("code{code}.svg")))?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: Debug + Serialize> SimArtifact for AfeCharacterization<C> {
+    fn csv_header(&self) -> Vec<String> {
+        [
+            "code",
+            "corner",
+            "voltage",
+            "temp",
+            "dc_gain_db",
+            "peak_gain_db",
+            "peaking_db",
+            "peak_freq",
+            "bandwidth",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.points
+            .iter()
+            .map(|point| {
+                let m = &point.metrics;
+                vec![
+                    point.code.to_string(),
+                    format!("{:?}", point.pvt.corner),
+                    point.pvt.voltage.to_string(),
+                    point.pvt.temp.to_string(),
+                    m.dc_gain_db.to_string(),
+                    m.peak_gain_db.to_string(),
+                    m.peaking_db.to_string(),
+                    m.peak_freq.to_string(),
+                    m.bandwidth.map(|bw| bw.to_string()).unwrap_or_default(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Simulates a front-end stage at each code and corner.
+///
+/// Each code is swept across corners in its own subdirectory of `work_dir`.
+pub fn characterize_afe<T, PDK, C>(
+    params: AfeCharParams<T, C>,
+    ctx: &PdkContext<PDK>,
+    work_dir: impl AsRef<Path>,
+) -> AfeCharacterization<C>
+where
+    T: Block<Io = AfeIo> + Schematic<PDK> + Clone + Serialize + Send + Sync + 'static,
+    PDK: Pdk + Schema,
+    Spectre: FromSchema<PDK>,
+    C: SimOption<Spectre>
+        + Serialize
+        + DeserializeOwned
+        + Copy
+        + Debug
+        + Hash
+        + Eq
+        + Send
+        + Sync
+        + Any,
+    AfeAcTb<T, PDK, C>: Testbench<Spectre, Output = AfeResponse>,
+{
+    let mut points = Vec::new();
+    for &code in params.codes.iter() {
+        let dut = params.dut.clone();
+        let (vcm, fstart, fstop) = (params.vcm, params.fstart, params.fstop);
+        let sweep = CornerSweep::new(params.pvts.clone(), move |pvt| {
+            AfeAcTb::<T, PDK, C>::new(dut.clone(), code, vcm, fstart, fstop, pvt)
+        });
+        let output = sweep.run::<Spectre, PDK>(ctx, work_dir.as_ref().join(format!("code{code}")));
+        for pvt in params.pvts.iter() {
+            let response = output.get(pvt).expect("missing corner").clone();
+            points.push(AfePoint {
+                code,
+                pvt: pvt.clone(),
+                metrics: response.metrics(),
+                response,
+            });
+        }
+    }
+    AfeCharacterization { points }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn afe_metrics_capture_peaking_and_bandwidth() {
+        let ctle = AfeResponse {
+            freq: vec![1e6, 1e8, 1e9, 1e10],
+            gain: vec![0.5, 0.5, 1.0, 0.25],
+        };
+        let metrics = ctle.metrics();
+        assert!((metrics.dc_gain_db + 6.0206).abs() < 1e-3);
+        assert!((metrics.peaking_db - 6.0206).abs() < 1e-3);
+        assert_eq!(metrics.peak_freq, 1e9);
+        let bw = metrics.bandwidth.unwrap();
+        assert!(bw > 1e9 && bw < 1e10);
+
+        let vga = AfeResponse {
+            freq: ctle.freq.clone(),
+            gain: vec![2.0; 4],
+        };
+        let cascade = ctle.cascade(&vga);
+        assert_eq!(cascade.gain, vec![1.0, 1.0, 2.0, 0.5]);
+        assert!(cascade.metrics().dc_gain_db.abs() < 1e-9);
+        assert_eq!(vga.metrics().bandwidth, None);
+    }
+}
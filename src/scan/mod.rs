@@ -0,0 +1,452 @@
+//! Serial configuration chains.
+//!
+//! A lane exposes many static control buses: driver impedance codes, calibration DAC
+//! codes, and phase interpolator codes. Rather than routing each bus to the edge of the
+//! PHY, a [`ConfigChain`] loads them serially. Bits are shifted in on `sin` with `sclk`,
+//! then copied to the parallel outputs `q` on a rising edge of `update`, so that the
+//! controlled circuits never see the intermediate shift states.
+//!
+//! A [`ConfigMap`] assigns each control bus to a range of chain bits and converts
+//! between field values and the bit sequence to shift in.
+
+use std::any::Any;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use atoll::{IoBuilder, Orientation, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::geometry::align::AlignMode;
+use substrate::io::{Array, InOut, Input, Io, Output, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+use crate::abutment::{Abutment, Edge, EdgeKind, Rail};
+use crate::buffer::InverterImpl;
+use crate::gates::{Dff, DffIoSchematic, DffParams};
+use crate::params::{check_at_least, setters, ParamsError};
+use crate::tiles::TileKind;
+
+pub mod tb;
+
+/// A control bus loaded through a configuration chain.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct ConfigField {
+    /// The name of the bus.
+    pub name: String,
+    /// The number of bits in the bus.
+    pub width: usize,
+}
+
+impl ConfigField {
+    /// Creates a new [`ConfigField`].
+    pub fn new(name: impl Into<String>, width: usize) -> Self {
+        Self {
+            name: name.into(),
+            width,
+        }
+    }
+}
+
+/// An error produced while building or encoding a [`ConfigMap`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConfigMapError {
+    /// Two fields have the same name.
+    #[error("field {0:?} appears more than once")]
+    DuplicateField(String),
+    /// A field is empty or wider than a `u64`.
+    #[error("field {name:?} has unsupported width {width}")]
+    InvalidWidth {
+        /// The name of the field.
+        name: String,
+        /// The width of the field.
+        width: usize,
+    },
+    /// A value was given for a field that is not in the map.
+    #[error("no field named {0:?}")]
+    UnknownField(String),
+    /// A value does not fit in its field.
+    #[error("value {value} does not fit in the {width}-bit field {name:?}")]
+    Overflow {
+        /// The name of the field.
+        name: String,
+        /// The value.
+        value: u64,
+        /// The width of the field.
+        width: usize,
+    },
+}
+
+/// An assignment of control buses to configuration chain bits.
+///
+/// Fields occupy consecutive bits in the order given, LSB first, starting from chain
+/// bit 0 (the bit nearest `sin`).
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct ConfigMap {
+    fields: Vec<ConfigField>,
+}
+
+impl ConfigMap {
+    /// Creates a new [`ConfigMap`] from the given fields.
+    pub fn new(fields: Vec<ConfigField>) -> Result<Self, ConfigMapError> {
+        for (i, field) in fields.iter().enumerate() {
+            if field.width == 0 || field.width > u64::BITS as usize {
+                return Err(ConfigMapError::InvalidWidth {
+                    name: field.name.clone(),
+                    width: field.width,
+                });
+            }
+            if fields[..i].iter().any(|other| other.name == field.name) {
+                return Err(ConfigMapError::DuplicateField(field.name.clone()));
+            }
+        }
+        Ok(Self { fields })
+    }
+
+    /// The fields, in chain order.
+    pub fn fields(&self) -> &[ConfigField] {
+        &self.fields
+    }
+
+    /// The total number of chain bits.
+    pub fn bits(&self) -> usize {
+        self.fields.iter().map(|field| field.width).sum()
+    }
+
+    /// The chain bits driving the field `name`, LSB first.
+    pub fn range(&self, name: &str) -> Option<Range<usize>> {
+        let mut start = 0;
+        for field in &self.fields {
+            if field.name == name {
+                return Some(start..start + field.width);
+            }
+            start += field.width;
+        }
+        None
+    }
+
+    /// Encodes field values into the contents of the chain, indexed by chain bit.
+    ///
+    /// Fields without a value are cleared.
+    pub fn encode(&self, values: &[(&str, u64)]) -> Result<Vec<bool>, ConfigMapError> {
+        let mut bits = vec![false; self.bits()];
+        for &(name, value) in values {
+            let range = self
+                .range(name)
+                .ok_or_else(|| ConfigMapError::UnknownField(name.to_string()))?;
+            let width = range.len();
+            if width < u64::BITS as usize && value >> width != 0 {
+                return Err(ConfigMapError::Overflow {
+                    name: name.to_string(),
+                    value,
+                    width,
+                });
+            }
+            for (i, bit) in bits[range].iter_mut().enumerate() {
+                *bit = (value >> i) & 1 == 1;
+            }
+        }
+        Ok(bits)
+    }
+
+    /// Decodes the contents of the chain, indexed by chain bit, into field values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` does not have exactly [`ConfigMap::bits`] entries.
+    pub fn decode(&self, bits: &[bool]) -> Vec<(String, u64)> {
+        assert_eq!(bits.len(), self.bits(), "chain length mismatch");
+        let mut start = 0;
+        self.fields
+            .iter()
+            .map(|field| {
+                let value = bits[start..start + field.width]
+                    .iter()
+                    .rev()
+                    .fold(0, |acc, &bit| (acc << 1) | bit as u64);
+                start += field.width;
+                (field.name.clone(), value)
+            })
+            .collect()
+    }
+}
+
+/// The order in which to shift `bits`, indexed by chain bit, into a chain.
+///
+/// The last chain bit is shifted in first, since every earlier bit pushes it one
+/// stage further from `sin`.
+pub fn shift_order(bits: &[bool]) -> Vec<bool> {
+    bits.iter().rev().copied().collect()
+}
+
+/// The interface to a [`ConfigChain`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct ConfigChainIo {
+    /// The serial input.
+    pub sin: Input<Signal>,
+    /// The shift clock.
+    pub sclk: Input<Signal>,
+    /// The update strobe; the parallel outputs load on its rising edge.
+    pub update: Input<Signal>,
+    /// The serial output, for daisy-chaining.
+    pub sout: Output<Signal>,
+    /// The parallel outputs, indexed by chain bit.
+    pub q: Array<Output<Signal>>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of the [`ConfigChain`] layout generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ConfigChainParams {
+    /// The number of bits.
+    pub bits: usize,
+    /// The flip-flop used for both the shift and update registers.
+    pub dff: DffParams,
+}
+
+impl ConfigChainParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> ConfigChainParamsBuilder {
+        ConfigChainParamsBuilder::default()
+    }
+}
+
+/// A builder for [`ConfigChainParams`].
+///
+/// Defaults to a 32-bit chain of default [`DffParams`] flip-flops.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ConfigChainParamsBuilder {
+    params: ConfigChainParams,
+}
+
+impl Default for ConfigChainParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: ConfigChainParams {
+                bits: 32,
+                dff: DffParams::builder().build().unwrap(),
+            },
+        }
+    }
+}
+
+impl ConfigChainParamsBuilder {
+    setters! {
+        /// Sets the number of bits.
+        bits: usize,
+        /// Sets the flip-flop parameters.
+        dff: DffParams,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<ConfigChainParams, ParamsError> {
+        check_at_least("bits", self.params.bits as i64, 1)?;
+        Ok(self.params)
+    }
+}
+
+/// A serial configuration chain: a shift register with an update register beneath it.
+///
+/// Shift flip-flop `i` feeds shift flip-flop `i + 1` and update flip-flop `i`, which
+/// drives `q[i]`. The shift register forms the top row of the layout and the update
+/// register, rotated so that the two rows share their VSS taps, forms the bottom row.
+/// The parallel outputs therefore leave through the bottom edge; place the chain with
+/// [`place_along_edge`](crate::abutment::place_along_edge) against the digital edge of
+/// the lane it configures.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct ConfigChain<T>(
+    ConfigChainParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> ConfigChain<T> {
+    /// Creates a new [`ConfigChain`].
+    pub fn new(params: ConfigChainParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The chain parameters.
+    pub fn params(&self) -> ConfigChainParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for ConfigChain<T> {
+    type Io = ConfigChainIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("config_chain")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("config_chain", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        ConfigChainIo {
+            q: Array::new(self.0.bits, Default::default()),
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Any> ExportsNestedData for ConfigChain<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for ConfigChain<T> {
+    type LayoutData = ();
+}
+
+// Both rows put their N-well taps on the outer edges of the chain.
+impl<T> Abutment for ConfigChain<T> {
+    fn edge(&self, edge: Edge) -> EdgeKind {
+        match edge {
+            Edge::Top | Edge::Bottom => EdgeKind::Rail {
+                rail: Rail::Vdd,
+                well: TileKind::N,
+            },
+            Edge::Left | Edge::Right => EdgeKind::Clear,
+        }
+    }
+}
+
+impl<PDK: Pdk + Schema + Sized, T: InverterImpl<PDK> + Any> Tile<PDK> for ConfigChain<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let bits = self.0.bits;
+        let (vdd, vss) = (io.schematic.vdd, io.schematic.vss);
+        let dff = Dff::<T>::new(self.0.dff);
+
+        let mut shift = Vec::with_capacity(bits);
+        let mut update = Vec::with_capacity(bits);
+        let mut d = io.schematic.sin;
+        for i in 0..bits {
+            let q = if i + 1 == bits {
+                io.schematic.sout
+            } else {
+                cell.signal(format!("shift{i}"), Signal)
+            };
+            shift.push(cell.generate_connected(
+                dff,
+                DffIoSchematic {
+                    d,
+                    clk: io.schematic.sclk,
+                    q,
+                    vdd,
+                    vss,
+                },
+            ));
+            update.push(
+                cell.generate_connected(
+                    dff,
+                    DffIoSchematic {
+                        d: q,
+                        clk: io.schematic.update,
+                        q: io.schematic.q[i],
+                        vdd,
+                        vss,
+                    },
+                )
+                .orient(Orientation::R180),
+            );
+            d = q;
+        }
+
+        for i in 1..bits {
+            let prev = shift[i - 1].lcm_bounds();
+            shift[i].align_rect_mut(prev, AlignMode::ToTheRight, 0);
+            shift[i].align_rect_mut(prev, AlignMode::Bottom, 0);
+        }
+        for (upd, sh) in update.iter_mut().zip(&shift) {
+            let above = sh.lcm_bounds();
+            upd.align_rect_mut(above, AlignMode::Beneath, 0);
+            upd.align_rect_mut(above, AlignMode::Left, 0);
+        }
+
+        let shift = shift
+            .into_iter()
+            .map(|dff| cell.draw(dff))
+            .collect::<substrate::error::Result<Vec<_>>>()?;
+        let update = update
+            .into_iter()
+            .map(|dff| cell.draw(dff))
+            .collect::<substrate::error::Result<Vec<_>>>()?;
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        io.layout.sin.merge(shift[0].layout.io().d);
+        io.layout.sclk.merge(shift[0].layout.io().clk);
+        io.layout.update.merge(update[0].layout.io().clk);
+        io.layout.sout.merge(shift[bits - 1].layout.io().q);
+        for (i, upd) in update.iter().enumerate() {
+            io.layout.q[i].merge(upd.layout.io().q);
+        }
+        io.layout.vdd.merge(shift[0].layout.io().vdd);
+        io.layout.vss.merge(shift[0].layout.io().vss);
+
+        Ok(((), ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_map_round_trips() {
+        let map = ConfigMap::new(vec![
+            ConfigField::new("pu_code", 4),
+            ConfigField::new("cal_dac", 6),
+            ConfigField::new("pi_code", 5),
+        ])
+        .unwrap();
+        assert_eq!(map.bits(), 15);
+        assert_eq!(map.range("cal_dac"), Some(4..10));
+        assert_eq!(map.range("missing"), None);
+
+        let bits = map
+            .encode(&[("pu_code", 0b1010), ("pi_code", 0b10011)])
+            .unwrap();
+        assert_eq!(&bits[..4], &[false, true, false, true]);
+        assert!(bits[4..10].iter().all(|&bit| !bit));
+        assert_eq!(
+            map.decode(&bits),
+            vec![
+                ("pu_code".to_string(), 0b1010),
+                ("cal_dac".to_string(), 0),
+                ("pi_code".to_string(), 0b10011),
+            ]
+        );
+        assert_eq!(shift_order(&bits)[0], bits[14]);
+
+        assert_eq!(
+            map.encode(&[("cal_dac", 64)]),
+            Err(ConfigMapError::Overflow {
+                name: "cal_dac".to_string(),
+                value: 64,
+                width: 6,
+            })
+        );
+        assert_eq!(
+            ConfigMap::new(vec![ConfigField::new("a", 1), ConfigField::new("a", 2)]),
+            Err(ConfigMapError::DuplicateField("a".to_string()))
+        );
+    }
+}
@@ -0,0 +1,457 @@
+//! Configuration chain testbenches.
+//!
+//! [`ConfigChainTb`] loads two patterns into a [`ConfigChain`](crate::scan::ConfigChain)
+//! back to back. Sampling the parallel outputs after each update, and once more just
+//! before the second update, checks that the update register captures the shift
+//! register and holds its value while new bits are shifted in. Sampling `sout` while the
+//! second pattern is shifted in checks that the first pattern shifts out intact.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::Vsource;
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Node};
+use substrate::io::{Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::primitives::Capacitor;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::report::SimArtifact;
+use crate::scan::{shift_order, ConfigChainIo};
+use crate::tb::pi::Samples;
+
+/// The load on each parallel output, in farads.
+const Q_LOAD: Decimal = dec!(5e-15);
+
+/// A transient testbench that shifts two patterns into a configuration chain,
+/// updating after each.
+///
+/// Each bit is shifted in over one clock period: `sin` changes at the start of the
+/// period and `sclk` rises halfway through it. Each update likewise takes one period.
+#[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct ConfigChainTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: T,
+    /// The first pattern, indexed by chain bit.
+    pub first: Vec<bool>,
+    /// The second pattern, indexed by chain bit.
+    pub second: Vec<bool>,
+    /// The shift clock period, in seconds.
+    pub period: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> ConfigChainTb<T, PDK, C> {
+    /// Creates a new [`ConfigChainTb`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the patterns have different lengths.
+    pub fn new(dut: T, first: Vec<bool>, second: Vec<bool>, period: Decimal, pvt: Pvt<C>) -> Self {
+        assert_eq!(first.len(), second.len(), "pattern length mismatch");
+        Self {
+            dut,
+            first,
+            second,
+            period,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Loads an alternating pattern into a `bits`-bit chain, then its complement,
+    /// so that every output toggles on the second update.
+    pub fn alternating(dut: T, bits: usize, period: Decimal, pvt: Pvt<C>) -> Self {
+        let first = (0..bits).map(|i| i % 2 == 0).collect::<Vec<_>>();
+        let second = first.iter().map(|bit| !bit).collect();
+        Self::new(dut, first, second, period, pvt)
+    }
+
+    fn bits(&self) -> usize {
+        self.first.len()
+    }
+
+    /// The start of the period in which the first pattern is updated.
+    fn first_update(&self) -> Decimal {
+        self.period * Decimal::from(self.bits() + 1)
+    }
+
+    /// The start of the period in which the second pattern is updated.
+    fn second_update(&self) -> Decimal {
+        self.period * Decimal::from(2 * self.bits() + 2)
+    }
+
+    fn stop(&self) -> Decimal {
+        self.second_update() + self.period
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for ConfigChainTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("config_chain_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::format!("config_chain_tb_{}", self.bits())
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`ConfigChainTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct ConfigChainTbNodes {
+    sout: Node,
+    q: Vec<Node>,
+}
+
+impl<T, PDK, C> ExportsNestedData for ConfigChainTb<T, PDK, C>
+where
+    ConfigChainTb<T, PDK, C>: Block,
+{
+    type NestedData = ConfigChainTbNodes;
+}
+
+/// A piecewise-linear waveform that holds `levels[i]` from `start + i * period` until
+/// the next level, switching in `edge`.
+fn levels_pwl(
+    start: Decimal,
+    period: Decimal,
+    edge: Decimal,
+    levels: &[bool],
+    high: Decimal,
+) -> Vec<(Decimal, Decimal)> {
+    let volts = |bit: bool| if bit { high } else { dec!(0) };
+    let mut points = vec![(dec!(0), dec!(0))];
+    let mut prev = false;
+    for (i, &bit) in levels.iter().enumerate() {
+        if bit != prev {
+            let t = start + period * Decimal::from(i);
+            points.extend([(t, volts(prev)), (t + edge, volts(bit))]);
+            prev = bit;
+        }
+    }
+    points
+}
+
+/// A piecewise-linear waveform with one pulse in the second quarter of each period
+/// starting at each of `starts`.
+fn strobe_pwl(
+    starts: impl IntoIterator<Item = Decimal>,
+    period: Decimal,
+    edge: Decimal,
+    high: Decimal,
+) -> Vec<(Decimal, Decimal)> {
+    let mut points = vec![(dec!(0), dec!(0))];
+    for t in starts {
+        let rise = t + period / dec!(2);
+        let fall = t + period * dec!(0.75);
+        points.extend([
+            (rise, dec!(0)),
+            (rise + edge, high),
+            (fall, high),
+            (fall + edge, dec!(0)),
+        ]);
+    }
+    points
+}
+
+impl<T: Block<Io = ConfigChainIo> + Schematic<PDK> + Clone, PDK: Schema, C: Copy> Schematic<Spectre>
+    for ConfigChainTb<T, PDK, C>
+where
+    ConfigChainTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vss = io.vss;
+        let vdd = cell.signal("vdd", Signal);
+        let sout = cell.signal("sout", Signal);
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
+        cell.connect(dut.io().vdd, vdd);
+        cell.connect(dut.io().vss, vss);
+        cell.connect(dut.io().sout, sout);
+
+        let bits = self.bits();
+        let (period, high) = (self.period, self.pvt.voltage);
+        let edge = period / dec!(20);
+
+        // Shift periods start one period in, and again just after the first update.
+        let first_shift = period;
+        let second_shift = self.first_update() + period;
+        let shifts = (0..bits)
+            .map(|i| first_shift + period * Decimal::from(i))
+            .chain((0..bits).map(|i| second_shift + period * Decimal::from(i)))
+            .collect::<Vec<_>>();
+        let mut levels = shift_order(&self.first);
+        levels.push(false);
+        levels.extend(shift_order(&self.second));
+
+        for (source, node) in [
+            (Vsource::dc(high), vdd),
+            (
+                Vsource::pwl(levels_pwl(first_shift, period, edge, &levels, high)),
+                dut.io().sin,
+            ),
+            (
+                Vsource::pwl(strobe_pwl(shifts, period, edge, high)),
+                dut.io().sclk,
+            ),
+            (
+                Vsource::pwl(strobe_pwl(
+                    [self.first_update(), self.second_update()],
+                    period,
+                    edge,
+                    high,
+                )),
+                dut.io().update,
+            ),
+        ] {
+            cell.instantiate_connected(source, TwoTerminalIoSchematic { p: node, n: vss });
+        }
+
+        let q = (0..bits)
+            .map(|i| {
+                let q = cell.signal(format!("q{i}"), Signal);
+                cell.connect(dut.io().q[i], q);
+                cell.instantiate_connected(
+                    Capacitor::new(Q_LOAD),
+                    TwoTerminalIoSchematic { p: q, n: vss },
+                );
+                q
+            })
+            .collect();
+
+        Ok(ConfigChainTbNodes { sout, q })
+    }
+}
+
+/// The resulting waveforms of a [`ConfigChainTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct ConfigChainSim {
+    t: tran::Time,
+    sout: tran::Voltage,
+    q: Vec<tran::Voltage>,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, ConfigChainSim> for ConfigChainTb<T, PDK, C>
+where
+    ConfigChainTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <ConfigChainSim as FromSaved<Spectre, Tran>>::SavedKey {
+        ConfigChainSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            sout: tran::Voltage::save(ctx, cell.data().sout, opts),
+            q: cell
+                .data()
+                .q
+                .iter()
+                .map(|q| tran::Voltage::save(ctx, q, opts))
+                .collect(),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for ConfigChainTb<T, PDK, C>
+where
+    ConfigChainTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = ConfigChainResult;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: ConfigChainSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: self.stop(),
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+
+        let period = self.period.to_f64().unwrap();
+        let threshold = self.pvt.voltage.to_f64().unwrap() / 2.;
+        let read = |v: &[f64], t: f64| Samples { t: &wav.t, v }.value_at(t) > threshold;
+        let read_q = |t: f64| wav.q.iter().map(|q| read(q, t)).collect::<Vec<_>>();
+
+        let first_update = self.first_update().to_f64().unwrap();
+        let second_update = self.second_update().to_f64().unwrap();
+        // `sout` presents one bit of the first pattern before each shift edge of the
+        // second pattern, starting from the last chain bit.
+        let mut shifted_out = (0..self.bits())
+            .map(|i| read(&wav.sout, first_update + period * (i as f64 + 1.25)))
+            .collect::<Vec<_>>();
+        shifted_out.reverse();
+
+        ConfigChainResult {
+            first: self.first.clone(),
+            second: self.second.clone(),
+            loaded: read_q(first_update + period * 0.95),
+            held: read_q(second_update + period * 0.25),
+            updated: read_q(second_update + period * 0.95),
+            shifted_out,
+        }
+    }
+}
+
+/// The chain contents observed by a [`ConfigChainTb`], each indexed by chain bit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigChainResult {
+    /// The first pattern shifted in.
+    pub first: Vec<bool>,
+    /// The second pattern shifted in.
+    pub second: Vec<bool>,
+    /// The parallel outputs after the first update.
+    pub loaded: Vec<bool>,
+    /// The parallel outputs after the second pattern is shifted in, before it is updated.
+    pub held: Vec<bool>,
+    /// The parallel outputs after the second update.
+    pub updated: Vec<bool>,
+    /// The bits seen on `sout` while the second pattern is shifted in.
+    pub shifted_out: Vec<bool>,
+}
+
+impl ConfigChainResult {
+    /// Whether each update loads the pattern that was shifted in before it.
+    pub fn updates(&self) -> bool {
+        self.loaded == self.first && self.updated == self.second
+    }
+
+    /// Whether the parallel outputs hold their value while the chain shifts.
+    pub fn holds(&self) -> bool {
+        self.held == self.first
+    }
+
+    /// Whether the first pattern shifts out of `sout` intact.
+    pub fn shifts_out(&self) -> bool {
+        self.shifted_out == self.first
+    }
+
+    /// Whether the chain shifts, holds, and updates correctly.
+    pub fn passed(&self) -> bool {
+        self.updates() && self.holds() && self.shifts_out()
+    }
+}
+
+impl SimArtifact for ConfigChainResult {
+    fn csv_header(&self) -> Vec<String> {
+        [
+            "bit",
+            "first",
+            "second",
+            "loaded",
+            "held",
+            "updated",
+            "shifted_out",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        (0..self.first.len())
+            .map(|i| {
+                let mut row = vec![i.to_string()];
+                row.extend(
+                    [
+                        &self.first,
+                        &self.second,
+                        &self.loaded,
+                        &self.held,
+                        &self.updated,
+                        &self.shifted_out,
+                    ]
+                    .map(|bits| (bits[i] as u8).to_string()),
+                );
+                row
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_chain_result_checks() {
+        let first = vec![true, false, true];
+        let second = vec![false, true, false];
+        let good = ConfigChainResult {
+            first: first.clone(),
+            second: second.clone(),
+            loaded: first.clone(),
+            held: first.clone(),
+            updated: second.clone(),
+            shifted_out: first.clone(),
+        };
+        assert!(good.passed());
+
+        // An update register that is transparent follows the shift register.
+        let transparent = ConfigChainResult {
+            held: vec![false, true, true],
+            ..good.clone()
+        };
+        assert!(transparent.updates());
+        assert!(!transparent.holds());
+        assert!(!transparent.passed());
+
+        // A dropped stage shifts out a delayed pattern.
+        let short = ConfigChainResult {
+            shifted_out: vec![false, true, false],
+            ..good
+        };
+        assert!(!short.shifts_out());
+    }
+}
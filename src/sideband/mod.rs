@@ -0,0 +1,363 @@
+//! Sideband receivers.
+//!
+//! The sideband stays active through power-state transitions, when the supply is at its
+//! noisiest. [`SidebandRx`] squares up the sideband input with a Schmitt trigger whose
+//! hysteresis keeps supply and input noise near the threshold from toggling the output.
+//! An optional RC glitch filter ahead of the trigger also rejects pulses that are short
+//! compared to its time constant.
+//!
+//! [`tb::SidebandGlitchTb`] checks that glitches of a given width are rejected while the
+//! supply is disturbed.
+
+pub mod tb;
+
+use crate::driver::HorizontalDriverImpl;
+use crate::params::{check_at_least, check_positive, setters, ParamsError};
+use crate::power::{RcClampError, RcClampRules, RcTimerParams};
+use crate::tiles::{ResistorConn, ResistorIoSchematic, TapIoSchematic, TileKind};
+use atoll::{IoBuilder, Orientation, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::geometry::align::AlignMode;
+use substrate::io::{InOut, Input, Io, MosIoSchematic, Output, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+/// The interface to a [`SidebandRx`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct SidebandRxIo {
+    /// The sideband input.
+    pub din: Input<Signal>,
+    /// The restored output, in phase with `din`.
+    pub dout: Output<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of the [`SidebandRx`] layout generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct SidebandRxParams {
+    /// The RC glitch filter between the input and the Schmitt trigger,
+    /// or `None` to drive the trigger directly.
+    ///
+    /// The filter resistor runs from the input to the gate of the MOS capacitor.
+    pub filter: Option<RcTimerParams>,
+    /// The NMOS finger width of every inverting device; the PMOS fingers are twice as wide.
+    pub w: i64,
+    /// The number of fingers of each stacked device of the Schmitt trigger.
+    pub stack_nf: i64,
+    /// The number of fingers of each feedback device of the Schmitt trigger,
+    /// or `None` for a stacked inverter without hysteresis.
+    ///
+    /// Stronger feedback devices widen the hysteresis.
+    pub feedback_nf: Option<i64>,
+    /// The number of fingers of the output inverter.
+    pub buf_nf: i64,
+}
+
+impl SidebandRxParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> SidebandRxParamsBuilder {
+        SidebandRxParamsBuilder::default()
+    }
+
+    /// The time constant of the glitch filter, in seconds, or `None` without a filter.
+    pub fn filter_time_constant<T: RcClampRules>(&self) -> Option<f64> {
+        self.filter.map(|filter| filter.time_constant::<T>())
+    }
+}
+
+/// A builder for [`SidebandRxParams`].
+///
+/// Defaults to a Schmitt trigger with feedback devices as strong as its stacked devices
+/// and no glitch filter. Use [`SidebandRxParamsBuilder::design_filter`] to add a filter
+/// with a given time constant.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct SidebandRxParamsBuilder {
+    params: SidebandRxParams,
+}
+
+impl Default for SidebandRxParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: SidebandRxParams {
+                filter: None,
+                w: 1_000,
+                stack_nf: 2,
+                feedback_nf: Some(2),
+                buf_nf: 4,
+            },
+        }
+    }
+}
+
+impl SidebandRxParamsBuilder {
+    setters! {
+        /// Sets the RC glitch filter.
+        filter: Option<RcTimerParams>,
+        /// Sets the NMOS finger width of the inverting devices.
+        w: i64,
+        /// Sets the number of fingers of each stacked device.
+        stack_nf: i64,
+        /// Sets the number of fingers of each feedback device.
+        feedback_nf: Option<i64>,
+        /// Sets the number of fingers of the output inverter.
+        buf_nf: i64,
+    }
+
+    /// Adds a glitch filter with time constant `tau`, in seconds.
+    pub fn design_filter<T: RcClampRules>(self, tau: f64) -> Result<Self, RcClampError> {
+        Ok(self.filter(Some(RcTimerParams::design::<T>(tau)?)))
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<SidebandRxParams, ParamsError> {
+        let p = &self.params;
+        check_positive("w", p.w)?;
+        if let Some(filter) = p.filter {
+            for (field, value) in [
+                ("filter.res.legs", filter.res.legs),
+                ("filter.res.w", filter.res.w),
+                ("filter.res.l", filter.res.l),
+                ("filter.cap_w", filter.cap_w),
+                ("filter.cap_l", filter.cap_l),
+            ] {
+                check_positive(field, value)?;
+            }
+            check_at_least("filter.cap_nf", filter.cap_nf, 2)?;
+        }
+        for (field, value) in [("stack_nf", p.stack_nf), ("buf_nf", p.buf_nf)] {
+            check_at_least(field, value, 2)?;
+        }
+        p.feedback_nf
+            .map_or(Ok(()), |value| check_at_least("feedback_nf", value, 2))?;
+        Ok(self.params)
+    }
+}
+
+/// A sideband receiver with hysteresis and an optional RC glitch filter.
+///
+/// The Schmitt trigger stacks two PMOS and two NMOS devices between the rails. Each
+/// feedback device connects the middle of a stack to the opposite rail and is gated by
+/// the trigger output, so the output holds its state until the input pulls well past
+/// the midpoint. An output inverter restores the polarity and drives the load.
+///
+/// From left to right, the layout has the filter column, with the resistor above the
+/// MOS capacitor, then one column per stacked pair and feedback pair of the trigger,
+/// with the PMOS above the NMOS, and the output inverter. Each column has its own taps.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct SidebandRx<T>(
+    SidebandRxParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> SidebandRx<T> {
+    /// Creates a new [`SidebandRx`].
+    pub fn new(params: SidebandRxParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The receiver parameters.
+    pub fn params(&self) -> SidebandRxParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for SidebandRx<T> {
+    type Io = SidebandRxIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("sideband_rx")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("sideband_rx", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for SidebandRx<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for SidebandRx<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK> for SidebandRx<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let p = self.0;
+        let (vdd, vss) = (io.schematic.vdd, io.schematic.vss);
+        let (din, dout) = (io.schematic.din, io.schematic.dout);
+        let trigger = cell.signal("trigger", Signal);
+        let pmid = cell.signal("pmid", Signal);
+        let nmid = cell.signal("nmid", Signal);
+
+        let mut prev = None;
+        let input = if let Some(filter) = p.filter {
+            let filtered = cell.signal("filtered", Signal);
+            let res = cell.generate_connected(
+                T::resistor(
+                    filter.res.legs,
+                    filter.res.w,
+                    filter.res.l,
+                    ResistorConn::Series,
+                ),
+                ResistorIoSchematic {
+                    p: din,
+                    n: filtered,
+                    b: vdd,
+                },
+            );
+            let mut cap = cell.generate_connected(
+                T::mos(TileKind::N, filter.cap_nf, filter.cap_w, Some(filter.cap_l)),
+                MosIoSchematic {
+                    d: vss,
+                    g: filtered,
+                    s: vss,
+                    b: vss,
+                },
+            );
+            let mut cap_tap = cell.generate_connected(
+                T::tap(TileKind::P, filter.cap_nf),
+                TapIoSchematic { x: vss },
+            );
+            cap.align_mut(&res, AlignMode::Left, 0);
+            cap.align_mut(&res, AlignMode::Beneath, 0);
+            cap_tap.align_mut(&cap, AlignMode::Left, 0);
+            cap_tap.align_mut(&cap, AlignMode::Beneath, 0);
+            prev = Some(res.lcm_bounds().union(cap_tap.lcm_bounds()));
+            let res = cell.draw(res)?;
+            cell.draw(cap)?;
+            cell.draw(cap_tap)?;
+            io.layout.din.merge(res.layout.io().p);
+            filtered
+        } else {
+            din
+        };
+
+        // Each column is a PMOS over an NMOS, given as the number of fingers and the
+        // (drain, gate, source) connections of each device.
+        let mut columns = vec![
+            (p.stack_nf, (vdd, input, pmid), (vss, input, nmid)),
+            (p.stack_nf, (pmid, input, trigger), (nmid, input, trigger)),
+        ];
+        if let Some(nf) = p.feedback_nf {
+            columns.push((nf, (pmid, trigger, vss), (nmid, trigger, vdd)));
+        }
+        columns.push((p.buf_nf, (vdd, trigger, dout), (vss, trigger, dout)));
+
+        let mut ntaps = Vec::new();
+        let mut ptaps = Vec::new();
+        let mut devices = Vec::new();
+        for (nf, (pd, pg, ps), (nd, ng, ns)) in columns {
+            let mut ntap =
+                cell.generate_connected(T::tap(TileKind::N, nf), TapIoSchematic { x: vdd });
+            let mut pmos = cell.generate_connected(
+                T::mos(TileKind::P, nf, 2 * p.w * nf, None),
+                MosIoSchematic {
+                    d: pd,
+                    g: pg,
+                    s: ps,
+                    b: vdd,
+                },
+            );
+            let mut nmos = cell
+                .generate_connected(
+                    T::mos(TileKind::N, nf, p.w * nf, None),
+                    MosIoSchematic {
+                        d: nd,
+                        g: ng,
+                        s: ns,
+                        b: vss,
+                    },
+                )
+                .orient(Orientation::R180);
+            let mut ptap =
+                cell.generate_connected(T::tap(TileKind::P, nf), TapIoSchematic { x: vss });
+
+            if let Some(prev) = prev {
+                ntap.align_rect_mut(prev, AlignMode::Top, 0);
+                ntap.align_rect_mut(prev, AlignMode::ToTheRight, 0);
+            }
+            pmos.align_mut(&ntap, AlignMode::Left, 0);
+            pmos.align_mut(&ntap, AlignMode::Beneath, 0);
+            nmos.align_mut(&pmos, AlignMode::Left, 0);
+            nmos.align_mut(&pmos, AlignMode::Beneath, 0);
+            ptap.align_mut(&nmos, AlignMode::Left, 0);
+            ptap.align_mut(&nmos, AlignMode::Beneath, 0);
+            prev = Some(ntap.lcm_bounds().union(ptap.lcm_bounds()));
+
+            ntaps.push(cell.draw(ntap)?);
+            devices.push((cell.draw(pmos)?, cell.draw(nmos)?));
+            ptaps.push(cell.draw(ptap)?);
+        }
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        if p.filter.is_none() {
+            io.layout.din.merge(devices[0].0.layout.io().g);
+        }
+        let (buf_p, buf_n) = devices.last().expect("the output inverter is always drawn");
+        io.layout.dout.merge(buf_p.layout.io().s);
+        io.layout.dout.merge(buf_n.layout.io().s);
+        io.layout.vdd.merge(ntaps[0].layout.io().x);
+        io.layout.vss.merge(ptaps[0].layout.io().x);
+
+        T::post_layout_hooks(cell)?;
+
+        Ok(((), ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tech::sky130::Sky130Ucie;
+
+    #[test]
+    fn designs_filter_for_time_constant() {
+        let params = SidebandRxParams::builder()
+            .design_filter::<Sky130Ucie>(5e-9)
+            .unwrap()
+            .build()
+            .unwrap();
+        let tau = params.filter_time_constant::<Sky130Ucie>().unwrap();
+        assert!((tau - 5e-9).abs() < 0.05 * 5e-9, "{tau}");
+        assert_eq!(
+            SidebandRxParams::builder()
+                .build()
+                .unwrap()
+                .filter_time_constant::<Sky130Ucie>(),
+            None
+        );
+        assert!(matches!(
+            SidebandRxParams::builder().feedback_nf(Some(1)).build(),
+            Err(ParamsError::TooSmall {
+                field: "feedback_nf",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,430 @@
+//! Sideband receiver glitch rejection.
+//!
+//! [`SidebandGlitchTb`] drives a sideband receiver with a high-going glitch, a real
+//! rising edge, and a low-going glitch, each a `hold` apart, while the supply is
+//! disturbed by [`SupplyNoise`]. The receiver rejects the glitch if its output makes
+//! exactly one transition, in response to the real edge. [`glitch_rejection`] repeats the
+//! test over a set of glitch widths to find the widest glitch that is rejected.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::Vsource;
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::path::Path;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::context::PdkContext;
+use substrate::io::schematic::{HardwareType, Node};
+use substrate::io::{Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::pdk::corner::Pvt;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::report::SimArtifact;
+use crate::sideband::SidebandRxIo;
+use crate::tb::pi::Samples;
+use crate::tb::SupplyNoise;
+
+/// The rise and fall time of the input edges and glitches.
+const EDGE: Decimal = dec!(50e-12);
+
+/// A transient testbench that checks that a sideband receiver rejects glitches of width
+/// `width` on a disturbed supply.
+///
+/// The input is low for `hold`, pulses high for `width`, rises for good at `2 * hold`,
+/// and pulses low for `width` at `3 * hold`. The simulation stops at `4 * hold`, so
+/// `hold` should be several glitch filter time constants.
+#[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct SidebandGlitchTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: T,
+    /// The glitch width, in seconds.
+    pub width: Decimal,
+    /// The time between input events, in seconds.
+    pub hold: Decimal,
+    /// The disturbance on the supply.
+    pub supply: SupplyNoise,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> SidebandGlitchTb<T, PDK, C> {
+    /// Creates a new [`SidebandGlitchTb`].
+    pub fn new(dut: T, width: Decimal, hold: Decimal, supply: SupplyNoise, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            width,
+            hold,
+            supply,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+
+    /// The `(time, voltage)` points of the input waveform.
+    fn din_pwl(&self) -> Vec<(Decimal, Decimal)> {
+        let (lo, hi) = (dec!(0), self.pvt.voltage);
+        let pulse = |start: Decimal, from: Decimal, to: Decimal| {
+            [
+                (start, from),
+                (start + EDGE, to),
+                (start + EDGE + self.width, to),
+                (start + dec!(2) * EDGE + self.width, from),
+            ]
+        };
+        let mut points = vec![(dec!(0), lo)];
+        points.extend(pulse(self.hold, lo, hi));
+        points.extend([(dec!(2) * self.hold, lo), (dec!(2) * self.hold + EDGE, hi)]);
+        points.extend(pulse(dec!(3) * self.hold, hi, lo));
+        points
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for SidebandGlitchTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("sideband_glitch_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("sideband_glitch_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`SidebandGlitchTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct SidebandGlitchTbNodes {
+    din: Node,
+    dout: Node,
+}
+
+impl<T, PDK, C> ExportsNestedData for SidebandGlitchTb<T, PDK, C>
+where
+    SidebandGlitchTb<T, PDK, C>: Block,
+{
+    type NestedData = SidebandGlitchTbNodes;
+}
+
+impl<T: Block<Io = SidebandRxIo> + Schematic<PDK> + Clone, PDK: Schema, C: Copy> Schematic<Spectre>
+    for SidebandGlitchTb<T, PDK, C>
+where
+    SidebandGlitchTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vss = io.vss;
+        let vdd = cell.signal("vdd", Signal);
+        let din = cell.signal("din", Signal);
+        let dout = cell.signal("dout", Signal);
+
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
+        cell.connect(dut.io().vdd, vdd);
+        cell.connect(dut.io().vss, vss);
+        cell.connect(dut.io().din, din);
+        cell.connect(dut.io().dout, dout);
+
+        cell.instantiate_connected(
+            Vsource::pwl(self.supply.pwl(self.pvt.voltage, dec!(4) * self.hold)),
+            TwoTerminalIoSchematic { p: vdd, n: vss },
+        );
+        cell.instantiate_connected(
+            Vsource::pwl(self.din_pwl()),
+            TwoTerminalIoSchematic { p: din, n: vss },
+        );
+
+        Ok(SidebandGlitchTbNodes { din, dout })
+    }
+}
+
+/// The resulting waveforms of a [`SidebandGlitchTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct SidebandGlitchSim {
+    /// The simulation time.
+    pub t: tran::Time,
+    /// The receiver input.
+    pub din: tran::Voltage,
+    /// The receiver output.
+    pub dout: tran::Voltage,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, SidebandGlitchSim> for SidebandGlitchTb<T, PDK, C>
+where
+    SidebandGlitchTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <SidebandGlitchSim as FromSaved<Spectre, Tran>>::SavedKey {
+        SidebandGlitchSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            din: tran::Voltage::save(ctx, &cell.din, opts),
+            dout: tran::Voltage::save(ctx, &cell.dout, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for SidebandGlitchTb<T, PDK, C>
+where
+    SidebandGlitchTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = GlitchResponse;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: SidebandGlitchSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: dec!(4) * self.hold,
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+        GlitchResponse::new(
+            Samples {
+                t: &wav.t,
+                v: &wav.dout,
+            },
+            self.pvt.voltage.to_f64().unwrap() / 2.,
+            self.width.to_f64().unwrap(),
+            self.hold.to_f64().unwrap(),
+        )
+    }
+}
+
+/// The response of a sideband receiver to a pair of glitches and a real edge.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GlitchResponse {
+    /// The glitch width, in seconds.
+    pub width: f64,
+    /// Whether the output stayed low through the high-going glitch.
+    pub rejects_high: bool,
+    /// Whether the output stayed high through the low-going glitch.
+    pub rejects_low: bool,
+    /// The delay from the real input edge to the output edge, in seconds,
+    /// or `None` if the output did not follow it.
+    pub delay: Option<f64>,
+}
+
+impl GlitchResponse {
+    /// Reduces the output waveform of a [`SidebandGlitchTb`] with the given glitch width
+    /// and hold time, using `threshold` as the output logic threshold.
+    pub fn new(dout: Samples<'_>, threshold: f64, width: f64, hold: f64) -> Self {
+        let edge = EDGE.to_f64().unwrap();
+        let crossings = dout.crossings(threshold);
+        let quiet = |start: f64, stop: f64| !crossings.iter().any(|&(t, _)| t >= start && t < stop);
+        let edges = crossings
+            .iter()
+            .copied()
+            .filter(|&(t, _)| t >= 2. * hold && t < 3. * hold)
+            .collect::<Vec<_>>();
+        let delay = match edges[..] {
+            [(t, true)] => Some(t - 2. * hold - edge / 2.),
+            _ => None,
+        };
+        Self {
+            width,
+            rejects_high: dout.v[0] < threshold && quiet(0., 2. * hold),
+            rejects_low: quiet(3. * hold, f64::INFINITY),
+            delay,
+        }
+    }
+
+    /// Whether both glitches were rejected and the real edge passed.
+    pub fn rejected(&self) -> bool {
+        self.rejects_high && self.rejects_low && self.delay.is_some()
+    }
+}
+
+/// The results of [`glitch_rejection`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlitchRejection {
+    /// The response to each glitch width, in the order simulated.
+    pub points: Vec<GlitchResponse>,
+}
+
+impl GlitchRejection {
+    /// The widest glitch below which every simulated glitch was rejected, in seconds.
+    ///
+    /// Returns `None` if the narrowest simulated glitch was not rejected.
+    pub fn widest_rejected(&self) -> Option<f64> {
+        let mut points = self.points.clone();
+        points.sort_by(|a, b| a.width.total_cmp(&b.width));
+        points
+            .iter()
+            .take_while(|point| point.rejected())
+            .last()
+            .map(|point| point.width)
+    }
+}
+
+impl SimArtifact for GlitchRejection {
+    fn csv_header(&self) -> Vec<String> {
+        ["width", "rejects_high", "rejects_low", "delay"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.points
+            .iter()
+            .map(|point| {
+                vec![
+                    point.width.to_string(),
+                    point.rejects_high.to_string(),
+                    point.rejects_low.to_string(),
+                    point.delay.map(|t| t.to_string()).unwrap_or_default(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Simulates a [`SidebandGlitchTb`] for each glitch width in `widths`.
+///
+/// Each width is simulated in its own subdirectory of `work_dir`.
+pub fn glitch_rejection<T, PDK, C>(
+    ctx: &PdkContext<PDK>,
+    dut: T,
+    widths: &[Decimal],
+    hold: Decimal,
+    supply: SupplyNoise,
+    pvt: Pvt<C>,
+    work_dir: impl AsRef<Path>,
+) -> GlitchRejection
+where
+    T: Clone,
+    SidebandGlitchTb<T, PDK, C>: Testbench<Spectre, Output = GlitchResponse>,
+    PDK: Pdk,
+    C: Copy,
+{
+    let points = widths
+        .iter()
+        .map(|&width| {
+            ctx.simulate(
+                SidebandGlitchTb::new(dut.clone(), width, hold, supply.clone(), pvt),
+                work_dir.as_ref().join(format!("width_{width}")),
+            )
+            .expect("failed to run simulation")
+        })
+        .collect();
+    GlitchRejection { points }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_glitch_responses() {
+        let hold = 10e-9;
+        let t = (0..=400).map(|i| i as f64 * 0.1e-9).collect::<Vec<_>>();
+        // The output follows the real edge 1 ns late.
+        let clean = t
+            .iter()
+            .map(|&t| if t < 2. * hold + 1e-9 { 0. } else { 1.8 })
+            .collect::<Vec<_>>();
+        let response = GlitchResponse::new(Samples { t: &t, v: &clean }, 0.9, 1e-9, hold);
+        assert!(response.rejected());
+        let delay = response.delay.unwrap();
+        assert!((delay - 1e-9).abs() < 0.1e-9, "{delay}");
+
+        // The high-going glitch passes through to the output.
+        let glitched = t
+            .iter()
+            .zip(&clean)
+            .map(|(&t, &v)| {
+                if (hold..hold + 2e-9).contains(&t) {
+                    1.8
+                } else {
+                    v
+                }
+            })
+            .collect::<Vec<_>>();
+        let response = GlitchResponse::new(
+            Samples {
+                t: &t,
+                v: &glitched,
+            },
+            0.9,
+            1e-9,
+            hold,
+        );
+        assert!(!response.rejects_high && response.rejects_low);
+        assert!(!response.rejected());
+
+        let rejection = GlitchRejection {
+            points: vec![
+                GlitchResponse {
+                    width: 3e-9,
+                    ..response
+                },
+                GlitchResponse {
+                    width: 1e-9,
+                    rejects_high: true,
+                    ..response
+                },
+                GlitchResponse {
+                    width: 2e-9,
+                    rejects_high: true,
+                    ..response
+                },
+            ],
+        };
+        assert_eq!(rejection.widest_rejected(), Some(2e-9));
+        assert_eq!(
+            GlitchRejection {
+                points: rejection.points[..1].to_vec()
+            }
+            .widest_rejected(),
+            None
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Measurement specifications and compliance reports.
+
+use crate::report::SimArtifact;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// The allowed values of a measurement.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Limit {
+    /// The measurement must be at least the given value.
+    Min(f64),
+    /// The measurement must be at most the given value.
+    Max(f64),
+    /// The measurement must lie within the given inclusive range.
+    Range {
+        /// The minimum allowed value.
+        min: f64,
+        /// The maximum allowed value.
+        max: f64,
+    },
+}
+
+impl Limit {
+    /// Returns `true` if `value` is within this limit.
+    pub fn contains(&self, value: f64) -> bool {
+        match *self {
+            Limit::Min(min) => value >= min,
+            Limit::Max(max) => value <= max,
+            Limit::Range { min, max } => (min..=max).contains(&value),
+        }
+    }
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::Min(min) => write!(f, ">= {min}"),
+            Limit::Max(max) => write!(f, "<= {max}"),
+            Limit::Range { min, max } => write!(f, "{min} to {max}"),
+        }
+    }
+}
+
+/// A named limit on a measurement.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Spec {
+    /// The name of the measurement the spec applies to.
+    pub name: String,
+    /// The allowed values of the measurement.
+    pub limit: Limit,
+    /// The unit of the measurement, used only for display.
+    pub unit: String,
+}
+
+impl Spec {
+    /// Creates a new [`Spec`].
+    pub fn new(name: impl Into<String>, limit: Limit, unit: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            limit,
+            unit: unit.into(),
+        }
+    }
+}
+
+/// Whether a measurement meets its spec.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Status {
+    /// The measurement is within its limit.
+    Pass,
+    /// The measurement is outside its limit.
+    Fail,
+    /// No measurement was provided for the spec.
+    Missing,
+}
+
+/// The result of checking a single spec.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ComplianceRow {
+    /// The spec that was checked.
+    pub spec: Spec,
+    /// The measured value, if one was provided.
+    pub value: Option<f64>,
+    /// Whether the measurement meets the spec.
+    pub status: Status,
+}
+
+/// The results of checking a set of measurements against a set of specs.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ComplianceReport {
+    /// One row per spec, in the order the specs were given.
+    pub rows: Vec<ComplianceRow>,
+}
+
+impl ComplianceReport {
+    /// Checks `measurements` against `specs`.
+    ///
+    /// Measurements are matched to specs by name. Specs without a matching
+    /// measurement are reported as [`Status::Missing`].
+    pub fn evaluate<S: Into<String>>(
+        specs: &[Spec],
+        measurements: impl IntoIterator<Item = (S, f64)>,
+    ) -> Self {
+        let measurements = measurements
+            .into_iter()
+            .map(|(name, value)| (name.into(), value))
+            .collect::<HashMap<String, f64>>();
+        let rows = specs
+            .iter()
+            .map(|spec| {
+                let value = measurements.get(&spec.name).copied();
+                let status = match value {
+                    Some(value) if spec.limit.contains(value) => Status::Pass,
+                    Some(_) => Status::Fail,
+                    None => Status::Missing,
+                };
+                ComplianceRow {
+                    spec: spec.clone(),
+                    value,
+                    status,
+                }
+            })
+            .collect();
+        Self { rows }
+    }
+
+    /// Returns `true` if every spec passed.
+    pub fn passed(&self) -> bool {
+        self.rows.iter().all(|row| row.status == Status::Pass)
+    }
+
+    /// Returns the rows that did not pass.
+    pub fn failures(&self) -> impl Iterator<Item = &ComplianceRow> {
+        self.rows.iter().filter(|row| row.status != Status::Pass)
+    }
+
+    /// Serializes the report as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("failed to serialize compliance report")
+    }
+}
+
+impl SimArtifact for ComplianceReport {
+    fn csv_header(&self) -> Vec<String> {
+        ["spec", "value", "limit", "unit", "status"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                vec![
+                    row.spec.name.clone(),
+                    row.value.map(|v| v.to_string()).unwrap_or_default(),
+                    row.spec.limit.to_string(),
+                    row.spec.unit.clone(),
+                    format!("{:?}", row.status),
+                ]
+            })
+            .collect()
+    }
+}
+
+impl Display for ComplianceReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows.iter() {
+            let status = match row.status {
+                Status::Pass => "PASS",
+                Status::Fail => "FAIL",
+                Status::Missing => "MISSING",
+            };
+            let value = row
+                .value
+                .map(|value| format!("{value} {}", row.spec.unit))
+                .unwrap_or_else(|| "-".to_string());
+            writeln!(
+                f,
+                "{status:<8}{}: {value} (limit {} {})",
+                row.spec.name, row.spec.limit, row.spec.unit
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_specs() {
+        let specs = [
+            Spec::new("tx_impedance", Limit::Range { min: 40., max: 60. }, "ohm"),
+            Spec::new("sampler_offset_3sigma", Limit::Max(15e-3), "V"),
+            Spec::new("rx_bandwidth", Limit::Min(16e9), "Hz"),
+        ];
+        let report = ComplianceReport::evaluate(
+            &specs,
+            [("tx_impedance", 48.), ("sampler_offset_3sigma", 18e-3)],
+        );
+        let statuses = report.rows.iter().map(|row| row.status).collect::<Vec<_>>();
+        assert_eq!(statuses, [Status::Pass, Status::Fail, Status::Missing]);
+        assert!(!report.passed());
+        assert_eq!(report.failures().count(), 2);
+    }
+}
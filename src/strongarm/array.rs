@@ -0,0 +1,566 @@
+//! Arrays of StrongARM samplers sharing a balanced clock spine.
+//!
+//! A [`SamplerArray`] places identical sampler slices in a row and distributes their
+//! clock on a dedicated horizontal layer beneath them. The spine is fed at its center
+//! and tapped with equal-length vertical stubs at the center of each slice, so slices
+//! at mirrored positions see identical clock RC. The remaining skew grows with the
+//! distance from the feed point and is estimated by [`ClockSpine::slice_rc`].
+
+use std::any::Any;
+use std::marker::PhantomData;
+
+use atoll::abs::TrackCoord;
+use atoll::route::ViaMaker;
+use atoll::{IoBuilder, Tile, TileBuilder};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::error::Result;
+use substrate::geometry::align::AlignMode;
+use substrate::geometry::bbox::Bbox;
+use substrate::geometry::point::Point;
+use substrate::geometry::rect::Rect;
+use substrate::geometry::span::Span;
+use substrate::geometry::transform::{TransformMut, Transformation, TranslateMut};
+use substrate::io::layout::IoShape;
+use substrate::io::{Array, DiffPair, InOut, Input, Io, Output, Signal};
+use substrate::layout::element::Shape;
+use substrate::layout::tracks::RoundingMode;
+use substrate::layout::{ExportsLayoutData, LayoutData};
+use substrate::pdk::layers::HasPin;
+use substrate::pdk::{Pdk, PdkLayers};
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+use crate::analysis::ir_drop::MetalStack;
+use crate::buffer::InverterParams;
+use crate::params::{check_at_least, setters, ParamsError};
+use crate::report::SimArtifact;
+use crate::route::{DiffRouteLengths, RouteDifferential};
+use crate::strongarm::{
+    ClockedDiffComparatorIoSchematic, StrongArmImpl, StrongArmParams, StrongArmWithOutputBuffers,
+    StrongArmWithOutputBuffersImpl,
+};
+use crate::tb::skew::ClockBranch;
+
+/// The interface to a [`SamplerArray`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct SamplerArrayIo {
+    /// The input differential pair, shared by all slices.
+    pub input: Input<DiffPair>,
+    /// The output differential pair of each slice.
+    pub output: Array<Output<DiffPair>>,
+    /// The clock, fed at the center of the clock spine.
+    pub clock: Input<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of a [`SamplerArray`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct SamplerArrayParams {
+    /// The number of slices.
+    pub slices: usize,
+    /// The StrongARM latch parameters of each slice.
+    pub strongarm: StrongArmParams,
+    /// The output buffer parameters of each slice.
+    pub buffer: InverterParams,
+}
+
+impl SamplerArrayParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> SamplerArrayParamsBuilder {
+        SamplerArrayParamsBuilder::default()
+    }
+}
+
+/// A builder for [`SamplerArrayParams`].
+///
+/// Defaults to four slices of default StrongARM latches and output buffers.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct SamplerArrayParamsBuilder {
+    params: SamplerArrayParams,
+}
+
+impl Default for SamplerArrayParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: SamplerArrayParams {
+                slices: 4,
+                strongarm: StrongArmParams::builder().build().unwrap(),
+                buffer: InverterParams::builder().build().unwrap(),
+            },
+        }
+    }
+}
+
+impl SamplerArrayParamsBuilder {
+    setters! {
+        /// Sets the number of slices.
+        slices: usize,
+        /// Sets the StrongARM latch parameters.
+        strongarm: StrongArmParams,
+        /// Sets the output buffer parameters.
+        buffer: InverterParams,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<SamplerArrayParams, ParamsError> {
+        check_at_least("slices", self.params.slices as i64, 1)?;
+        Ok(self.params)
+    }
+}
+
+/// A sampler array implementation.
+pub trait SamplerArrayImpl<PDK: Pdk + Schema>:
+    StrongArmWithOutputBuffersImpl<PDK> + MetalStack
+{
+    /// The clock pin layer.
+    type ClockPin: HasPin;
+    /// The horizontal ATOLL layer of the clock spine.
+    ///
+    /// Taps run on the vertical layer beneath.
+    const CLOCK_SPINE_LAYER: usize = 3;
+    /// The number of spine layer tracks between the slices and the spine.
+    const CLOCK_SPINE_SPACING: i64 = 2;
+    /// The capacitance of clock spine and tap wires per unit length, in farads per
+    /// nanometer.
+    const CLOCK_WIRE_CAP_PER_LENGTH: f64;
+    /// The spacing between adjacent slices in ATOLL grid coordinates.
+    const SLICE_SPACING: i64;
+
+    /// Returns the clock pin layer.
+    fn clock_pin(layers: &PdkLayers<PDK>) -> Self::ClockPin;
+
+    /// Additional layout hooks to run after the layout is complete.
+    fn post_layout_hooks(_cell: &mut TileBuilder<'_, PDK>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The estimated clock parasitics from the spine feed point to one slice.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SliceClockRc {
+    /// The series resistance from the feed point to the slice, in ohms.
+    pub r: f64,
+    /// The capacitance that, behind `r`, gives the same Elmore delay, in farads.
+    pub c: f64,
+    /// The Elmore delay from the feed point to the slice, in seconds.
+    pub delay: f64,
+}
+
+/// The per-slice clock parasitics of a [`SamplerArray`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ClockSpineRc {
+    /// One entry per slice, in slice order.
+    pub slices: Vec<SliceClockRc>,
+}
+
+impl ClockSpineRc {
+    /// The largest difference in Elmore delay between any two slices.
+    pub fn skew(&self) -> f64 {
+        let max = self
+            .slices
+            .iter()
+            .map(|s| s.delay)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let min = self
+            .slices
+            .iter()
+            .map(|s| s.delay)
+            .fold(f64::INFINITY, f64::min);
+        max - min
+    }
+
+    /// The clock branch of each slice, for use in a
+    /// [`LaneSkewTb`](crate::tb::skew::LaneSkewTb).
+    pub fn branches(&self) -> Vec<ClockBranch> {
+        self.slices
+            .iter()
+            .map(|slice| ClockBranch {
+                r: Decimal::from_f64(slice.r).unwrap(),
+                c: Decimal::from_f64(slice.c).unwrap(),
+            })
+            .collect()
+    }
+}
+
+impl SimArtifact for ClockSpineRc {
+    fn csv_header(&self) -> Vec<String> {
+        ["slice", "r", "c", "delay"].map(String::from).to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.slices
+            .iter()
+            .enumerate()
+            .map(|(i, slice)| {
+                vec![
+                    i.to_string(),
+                    slice.r.to_string(),
+                    slice.c.to_string(),
+                    slice.delay.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// The geometry and wire parasitics of a clock spine.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ClockSpine {
+    /// The signed distance along the spine from the feed point to each tap, in
+    /// layout units, in slice order.
+    pub taps: Vec<i64>,
+    /// The resistance of the spine per unit length, in ohms per layout unit.
+    pub spine_r: f64,
+    /// The capacitance of the spine per unit length, in farads per layout unit.
+    pub spine_c: f64,
+    /// The resistance of each tap from the spine to its slice, including the via,
+    /// in ohms.
+    pub tap_r: f64,
+    /// The capacitance of each tap, in farads.
+    pub tap_c: f64,
+}
+
+impl ClockSpine {
+    /// Estimates the clock RC from the feed point to each slice when each slice loads
+    /// its tap with `load` farads.
+    ///
+    /// Spine segments between taps are modeled as pi sections and taps as a
+    /// resistance with half of their capacitance at each end.
+    pub fn slice_rc(&self, load: f64) -> ClockSpineRc {
+        let mut nodes = self.taps.clone();
+        nodes.push(0);
+        nodes.sort_unstable();
+        nodes.dedup();
+        let index = |x: i64| nodes.binary_search(&x).unwrap();
+
+        // The capacitance lumped at each spine node, including everything hanging off
+        // its taps.
+        let end = self.tap_c / 2. + load;
+        let mut caps = vec![0.; nodes.len()];
+        for (i, w) in nodes.windows(2).enumerate() {
+            let half = self.spine_c * (w[1] - w[0]) as f64 / 2.;
+            caps[i] += half;
+            caps[i + 1] += half;
+        }
+        for &tap in self.taps.iter() {
+            caps[index(tap)] += self.tap_c / 2. + end;
+        }
+
+        let root = index(0);
+        let slices = self
+            .taps
+            .iter()
+            .map(|&tap| {
+                let i = index(tap);
+                // Walk outward from the feed point, charging everything beyond each
+                // segment through it.
+                let spine_delay: f64 = if i >= root {
+                    (root + 1..=i)
+                        .map(|j| {
+                            let len = (nodes[j] - nodes[j - 1]) as f64;
+                            self.spine_r * len * caps[j..].iter().sum::<f64>()
+                        })
+                        .sum()
+                } else {
+                    (i..root)
+                        .map(|j| {
+                            let len = (nodes[j + 1] - nodes[j]) as f64;
+                            self.spine_r * len * caps[..=j].iter().sum::<f64>()
+                        })
+                        .sum()
+                };
+                let delay = spine_delay + self.tap_r * end;
+                let r = self.spine_r * tap.abs() as f64 + self.tap_r;
+                SliceClockRc {
+                    r,
+                    c: if r > 0. { delay / r } else { 0. },
+                    delay,
+                }
+            })
+            .collect();
+        ClockSpineRc { slices }
+    }
+}
+
+// The spine is described relative to its feed point and is symmetric about it.
+impl TranslateMut for ClockSpine {
+    fn translate_mut(&mut self, _p: Point) {}
+}
+
+impl TransformMut for ClockSpine {
+    fn transform_mut(&mut self, _trans: Transformation) {}
+}
+
+/// Layout data returned by the [`SamplerArray`] layout generator.
+#[derive(LayoutData)]
+pub struct SamplerArrayLayoutData {
+    /// The drawn clock spine.
+    pub spine: Rect,
+    /// The drawn taps from the spine to each slice.
+    pub taps: Vec<Rect>,
+    /// The clock spine parasitics.
+    pub clock: ClockSpine,
+    /// The lengths of the input pair routes, from the pins of every slice.
+    pub input: DiffRouteLengths,
+}
+
+/// A row of [`StrongArmWithOutputBuffers`] slices sampling the same input, clocked by
+/// a center-fed clock spine.
+///
+/// The spine runs on [`SamplerArrayImpl::CLOCK_SPINE_LAYER`] beneath the slices, so the
+/// general-purpose router only connects the top of each tap to its slice clock pin.
+/// The input pair runs as a differential pair on the same layer above the slices.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct SamplerArray<T>(
+    SamplerArrayParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> SamplerArray<T> {
+    /// Creates a new [`SamplerArray`].
+    pub fn new(params: SamplerArrayParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The array parameters.
+    pub fn params(&self) -> SamplerArrayParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for SamplerArray<T> {
+    type Io = SamplerArrayIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("sampler_array")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("sampler_array", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        SamplerArrayIo {
+            output: Array::new(self.0.slices, Default::default()),
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Any> ExportsNestedData for SamplerArray<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for SamplerArray<T> {
+    type LayoutData = SamplerArrayLayoutData;
+}
+
+impl<PDK: Pdk + Schema + Sized, T: SamplerArrayImpl<PDK> + Any> Tile<PDK> for SamplerArray<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let slice = StrongArmWithOutputBuffers::<T>::new(self.0.strongarm, self.0.buffer);
+        let mut slices = Vec::with_capacity(self.0.slices);
+        for i in 0..self.0.slices {
+            let mut inst = cell.generate_connected(
+                slice,
+                ClockedDiffComparatorIoSchematic {
+                    input: io.schematic.input.clone(),
+                    output: io.schematic.output[i].clone(),
+                    clock: io.schematic.clock,
+                    vdd: io.schematic.vdd,
+                    vss: io.schematic.vss,
+                },
+            );
+            if let Some(prev) = slices.last() {
+                inst.align_mut(prev, AlignMode::ToTheRight, T::SLICE_SPACING);
+                inst.align_mut(prev, AlignMode::Bottom, 0);
+            }
+            slices.push(inst);
+        }
+        let slices = slices
+            .into_iter()
+            .map(|inst| cell.draw(inst))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Draw the spine on a track beneath the slices and a tap up from it at the
+        // center of each slice. Slices are identical, so every tap has the same length
+        // and meets its slice at the same point relative to the clock pin.
+        let spine_layer = cell.layer_stack.layers[T::CLOCK_SPINE_LAYER].clone();
+        let tap_layer = cell.layer_stack.layers[T::CLOCK_SPINE_LAYER - 1].clone();
+        let spine_tracks = spine_layer.inner.tracks();
+        let tap_tracks = tap_layer.inner.tracks();
+        let bottom = slices
+            .iter()
+            .map(|inst| inst.layout.bbox_rect().bot())
+            .min()
+            .unwrap();
+        let spine_track =
+            spine_tracks.to_track_idx(bottom, RoundingMode::Down) - T::CLOCK_SPINE_SPACING;
+        let spine_span = spine_tracks.get(spine_track);
+        let tap_idxs = slices
+            .iter()
+            .map(|inst| {
+                tap_tracks.to_track_idx(inst.layout.bbox_rect().center().x, RoundingMode::Nearest)
+            })
+            .collect::<Vec<_>>();
+        let taps = tap_idxs
+            .iter()
+            .map(|&x| Rect::from_spans(tap_tracks.get(x), Span::new(spine_span.start(), bottom)))
+            .collect::<Vec<_>>();
+        let spine = Rect::from_spans(
+            Span::new(taps[0].left(), taps[taps.len() - 1].right()),
+            spine_span,
+        );
+
+        let via_maker = T::via_maker();
+        cell.layout.draw(Shape::new(spine_layer.id, spine))?;
+        for (&x, tap) in tap_idxs.iter().zip(taps.iter()) {
+            cell.layout.draw(Shape::new(tap_layer.id, *tap))?;
+            for shape in via_maker.draw_via(
+                cell.ctx().clone(),
+                TrackCoord {
+                    layer: T::CLOCK_SPINE_LAYER,
+                    x,
+                    y: spine_track,
+                },
+            ) {
+                cell.layout.draw(shape)?;
+            }
+        }
+        for (layer, rect) in taps
+            .iter()
+            .map(|tap| (T::CLOCK_SPINE_LAYER - 1, *tap))
+            .chain([(T::CLOCK_SPINE_LAYER, spine)])
+        {
+            if let Some(bounds) = cell
+                .layer_stack
+                .slice(0..layer + 1)
+                .shrink_to_lcm_units(rect)
+            {
+                cell.assign_grid_points(Some(io.schematic.clock), layer, bounds);
+            }
+        }
+
+        // Feed the spine at its center.
+        let feed = spine.center().x;
+        let feed_pin = Rect::from_spans(Span::from_center_span(feed, spine.height()), spine_span);
+        io.layout.clock.push(IoShape::with_layers(
+            T::clock_pin(&cell.ctx().layers),
+            feed_pin,
+        ));
+
+        let spine_width = spine.height();
+        let tap_width = taps[0].width();
+        let clock = ClockSpine {
+            taps: taps.iter().map(|tap| tap.center().x - feed).collect(),
+            spine_r: T::sheet_resistance(T::CLOCK_SPINE_LAYER) / spine_width as f64,
+            spine_c: T::CLOCK_WIRE_CAP_PER_LENGTH,
+            tap_r: T::sheet_resistance(T::CLOCK_SPINE_LAYER - 1) * taps[0].height() as f64
+                / tap_width as f64
+                + T::via_resistance(T::CLOCK_SPINE_LAYER - 1),
+            tap_c: T::CLOCK_WIRE_CAP_PER_LENGTH * taps[0].height() as f64,
+        };
+
+        // Run the input pair above the slices on adjacent spine layer tracks, so that
+        // both inputs see the same coupling and length to every slice.
+        let top = slices
+            .iter()
+            .map(|inst| inst.layout.bbox_rect().top())
+            .max()
+            .unwrap();
+        let input_track = spine_tracks.to_track_idx(top, RoundingMode::Up) + T::CLOCK_SPINE_SPACING;
+        let input_pins = |p: bool| {
+            slices
+                .iter()
+                .flat_map(|inst| {
+                    let input = inst.layout.io().input;
+                    let port = if p { input.p } else { input.n };
+                    port.shapes()
+                        .map(|shape| shape.bbox_rect())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        let (input_p, input_n) = (input_pins(true), input_pins(false));
+        let input = cell.route_differential(
+            (io.schematic.input.p, &input_p),
+            (io.schematic.input.n, &input_n),
+            T::CLOCK_SPINE_LAYER,
+            input_track,
+        )?;
+
+        cell.set_top_layer(T::CLOCK_SPINE_LAYER);
+        cell.set_router(crate::route::router(
+            cell.ctx(),
+            <T as StrongArmImpl<PDK>>::ROUTER,
+        ));
+        cell.set_via_maker(<T as StrongArmImpl<PDK>>::via_maker());
+
+        for (i, inst) in slices.iter().enumerate() {
+            io.layout.input.p.merge(inst.layout.io().input.p);
+            io.layout.input.n.merge(inst.layout.io().input.n);
+            io.layout.output[i].p.merge(inst.layout.io().output.p);
+            io.layout.output[i].n.merge(inst.layout.io().output.n);
+            io.layout.vdd.merge(inst.layout.io().vdd);
+            io.layout.vss.merge(inst.layout.io().vss);
+        }
+
+        <T as SamplerArrayImpl<PDK>>::post_layout_hooks(cell)?;
+
+        Ok((
+            (),
+            SamplerArrayLayoutData {
+                spine,
+                taps,
+                clock,
+                input,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn center_fed_spine_is_symmetric() {
+        let spine = ClockSpine {
+            taps: vec![-3_000, -1_000, 1_000, 3_000],
+            spine_r: 0.1,
+            spine_c: 2e-19,
+            tap_r: 50.,
+            tap_c: 1e-16,
+        };
+        let rc = spine.slice_rc(5e-15);
+        let delays = rc.slices.iter().map(|s| s.delay).collect::<Vec<_>>();
+        assert!((delays[0] - delays[3]).abs() < 1e-18);
+        assert!((delays[1] - delays[2]).abs() < 1e-18);
+        assert!(delays[0] > delays[1]);
+        assert!((rc.skew() - (delays[0] - delays[1])).abs() < 1e-18);
+
+        // Only the tap separates the feed point from a slice at the center.
+        let centered = ClockSpine {
+            taps: vec![0],
+            ..spine
+        };
+        let slice = centered.slice_rc(5e-15).slices[0];
+        assert_eq!(slice.r, 50.);
+        assert!((slice.delay - 50. * (5e-17 + 5e-15)).abs() < 1e-20);
+    }
+}
@@ -0,0 +1,202 @@
+//! Closed-loop simulation of sampler offset calibration.
+//!
+//! [`calibrate`] models the calibration FSM: with both comparator inputs shorted, it
+//! sets the offset-cancellation code one bit at a time by successive approximation,
+//! then measures the residual input-referred offset at the final code.
+//! [`simulate_calibration`] runs the loop against transient simulations of a
+//! comparator, which validates the range and step size of its calibration DAC.
+
+use std::path::Path;
+
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use spectre::Spectre;
+use substrate::context::PdkContext;
+use substrate::pdk::corner::Pvt;
+use substrate::pdk::Pdk;
+use substrate::simulation::Testbench;
+
+use crate::strongarm::tb::{ComparatorDecision, StrongArmTranTb};
+use crate::strongarm::InputKind;
+
+/// A signed offset-calibration DAC.
+///
+/// Codes range from `-2^(bits - 1)` to `2^(bits - 1) - 1`. Increasing the code must
+/// shift the comparator decision towards [`ComparatorDecision::Pos`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct CalDacSpec {
+    /// The number of bits in the calibration code.
+    pub bits: u32,
+    /// The nominal input-referred offset correction of one code step, in volts.
+    pub lsb: f64,
+}
+
+impl CalDacSpec {
+    /// The smallest calibration code.
+    pub fn min_code(&self) -> i64 {
+        -(1 << (self.bits - 1))
+    }
+
+    /// The largest calibration code.
+    pub fn max_code(&self) -> i64 {
+        (1 << (self.bits - 1)) - 1
+    }
+}
+
+/// The search used to measure the residual offset after calibration.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct OffsetSearch {
+    /// The largest differential input magnitude searched, in volts.
+    pub range: f64,
+    /// The resolution of the measured offset, in volts.
+    pub resolution: f64,
+}
+
+/// A comparator decision made during calibration.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CalStep {
+    /// The calibration code.
+    pub code: i64,
+    /// The decision with shorted inputs, or [`None`] if the comparator did not resolve.
+    pub decision: Option<ComparatorDecision>,
+}
+
+/// The outcome of a calibration run.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CalReport {
+    /// The final calibration code.
+    pub code: i64,
+    /// The input-referred offset at the final code, in volts.
+    pub residual_offset: f64,
+    /// The code and decision at each step of the successive approximation.
+    pub trajectory: Vec<CalStep>,
+    /// Whether the final code is at either end of the DAC range,
+    /// in which case the DAC may not be able to cancel the offset.
+    pub saturated: bool,
+}
+
+impl CalReport {
+    /// Returns `true` if calibration did not saturate and the residual offset is within
+    /// one step of `dac`.
+    pub fn converged(&self, dac: &CalDacSpec) -> bool {
+        !self.saturated && self.residual_offset.abs() <= dac.lsb
+    }
+}
+
+/// Runs the calibration loop.
+///
+/// `decide(code, vdiff)` returns the comparator decision with calibration code `code`
+/// and differential input `vdiff`. An unresolved decision is treated as
+/// [`ComparatorDecision::Pos`], since it only occurs when the input is within the
+/// comparator noise of its offset.
+pub fn calibrate(
+    dac: &CalDacSpec,
+    search: &OffsetSearch,
+    mut decide: impl FnMut(i64, f64) -> Option<ComparatorDecision>,
+) -> CalReport {
+    let is_pos = |decision: Option<ComparatorDecision>| {
+        decision.map_or(true, |decision| decision == ComparatorDecision::Pos)
+    };
+
+    // Successive approximation in offset binary: keep each bit that still leaves the
+    // decision negative, ending at the largest code with a negative decision.
+    let mut trajectory = Vec::with_capacity(dac.bits as usize);
+    let mut unsigned = 0;
+    for bit in (0..dac.bits).rev() {
+        let trial = unsigned | (1 << bit);
+        let code = trial + dac.min_code();
+        let decision = decide(code, 0.);
+        trajectory.push(CalStep { code, decision });
+        if !is_pos(decision) {
+            unsigned = trial;
+        }
+    }
+    let code = unsigned + dac.min_code();
+
+    // The decision is positive for inputs above the offset.
+    let (mut lo, mut hi) = (-search.range, search.range);
+    while hi - lo > search.resolution {
+        let mid = (lo + hi) / 2.;
+        if is_pos(decide(code, mid)) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+
+    CalReport {
+        code,
+        residual_offset: (lo + hi) / 2.,
+        trajectory,
+        saturated: code == dac.min_code() || code == dac.max_code(),
+    }
+}
+
+/// Runs the calibration loop against transient simulations of the comparators
+/// returned by `dut`, which maps a calibration code to a comparator.
+///
+/// Both inputs are centered around the common-mode voltage `vcm`. Comparators with a
+/// PMOS input pair, as given by `input_kind`, are clocked with an inverted clock.
+#[allow(clippy::too_many_arguments)]
+pub fn simulate_calibration<T, PDK, C>(
+    ctx: &PdkContext<PDK>,
+    dac: &CalDacSpec,
+    search: &OffsetSearch,
+    dut: impl Fn(i64) -> T,
+    input_kind: InputKind,
+    vcm: Decimal,
+    pvt: Pvt<C>,
+    work_dir: impl AsRef<Path>,
+) -> CalReport
+where
+    StrongArmTranTb<T, PDK, C>: Testbench<Spectre, Output = Option<ComparatorDecision>>,
+    PDK: Pdk,
+    C: Copy,
+{
+    let work_dir = work_dir.as_ref();
+    let inverted_clk = input_kind.is_p();
+    let mut sim = 0;
+    calibrate(dac, search, |code, vdiff| {
+        let half = Decimal::from_f64(vdiff / 2.).unwrap();
+        sim += 1;
+        ctx.simulate(
+            StrongArmTranTb::new(dut(code), vcm + half, vcm - half, inverted_clk, pvt),
+            work_dir.join(format!("sim{sim}")),
+        )
+        .expect("failed to run simulation")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancels_offset_within_one_step() {
+        let dac = CalDacSpec { bits: 5, lsb: 2e-3 };
+        let search = OffsetSearch {
+            range: 50e-3,
+            resolution: 1e-5,
+        };
+        let comparator = |offset: f64| {
+            move |code: i64, vdiff: f64| {
+                Some(if vdiff + code as f64 * dac.lsb - offset > 0. {
+                    ComparatorDecision::Pos
+                } else {
+                    ComparatorDecision::Neg
+                })
+            }
+        };
+
+        let report = calibrate(&dac, &search, comparator(13.3e-3));
+        assert_eq!(report.code, 6);
+        assert_eq!(report.trajectory.len(), 5);
+        assert!((report.residual_offset - 1.3e-3).abs() < 2. * search.resolution);
+        assert!(report.converged(&dac));
+
+        let report = calibrate(&dac, &search, comparator(100e-3));
+        assert_eq!(report.code, dac.max_code());
+        assert!(!report.converged(&dac));
+    }
+}
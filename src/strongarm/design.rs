@@ -0,0 +1,429 @@
+//! StrongARM sizing from target specifications.
+//!
+//! [`design_strongarm`] searches a grid of [`StrongArmParams`] from smallest to largest
+//! total gate width and returns the first design that meets a [`StrongArmSpec`] at every
+//! PVT corner and at both ends of the input common-mode range. [`simulate_strongarm`]
+//! measures a candidate in Spectre:
+//!
+//! * The input-referred offset is estimated from a Monte Carlo mismatch run of the
+//!   [`StrongArmTranTb`] with a differential input of one target sigma. If the offset is
+//!   normally distributed with standard deviation `sigma`, the comparator decides
+//!   incorrectly with probability `Q(v / sigma)`, which is inverted to estimate `sigma`.
+//! * The clock-to-output delay is measured by the [`StrongArmSupplyNoiseTb`] with an
+//!   undisturbed supply, alternating the input between plus and minus the minimum
+//!   overdrive.
+//!
+//! Every simulation goes through the [`CornerSweep`] and [`MonteCarlo`] caches, so
+//! re-running a search after changing the specification only simulates new candidates.
+
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::path::Path;
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use spectre::Spectre;
+use substrate::context::PdkContext;
+use substrate::pdk::corner::Pvt;
+use substrate::pdk::Pdk;
+use substrate::simulation::Testbench;
+
+use crate::strongarm::tb::{
+    ComparatorDecision, StrongArmHighSpeedTbParams, StrongArmSupplyNoiseTb,
+    StrongArmSupplyNoiseTbOutput, StrongArmTranTb,
+};
+use crate::strongarm::{InputKind, StrongArm, StrongArmParams};
+use crate::sweep::{CornerSweep, MonteCarlo, Variations};
+use crate::tb::probe::ProbeSet;
+use crate::tb::SupplyNoise;
+
+/// The number of clock cycles in each clock-to-output delay measurement.
+const DELAY_CYCLES: usize = 4;
+
+/// The target performance of a StrongARM comparator.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StrongArmSpec<C> {
+    /// The largest allowed standard deviation of the input-referred offset, in volts.
+    pub offset_sigma: f64,
+    /// The largest allowed delay from the evaluating clock edge until the outputs
+    /// separate by half the supply, in seconds.
+    pub clk_to_q: f64,
+    /// The differential input at which `clk_to_q` must be met, in volts.
+    pub min_overdrive: Decimal,
+    /// The lowest and highest input common-mode voltages.
+    pub vcm: (Decimal, Decimal),
+    /// The corners at which the specification must be met.
+    pub pvts: Vec<Pvt<C>>,
+}
+
+/// An error produced while sizing a StrongARM.
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+pub enum StrongArmDesignError {
+    /// A specification that must be positive was not.
+    #[error("{field} must be positive, got {value}")]
+    NonPositive {
+        /// The name of the specification.
+        field: &'static str,
+        /// The provided value.
+        value: f64,
+    },
+    /// The specification has no corners.
+    #[error("at least one PVT corner is required")]
+    NoCorners,
+    /// The offset cannot be estimated without Monte Carlo samples.
+    #[error("at least one Monte Carlo sample is required")]
+    NoSamples,
+    /// No candidate in the search space met the specification.
+    #[error("none of the {candidates} candidate designs met the specification")]
+    NoPassingDesign {
+        /// The number of candidates evaluated.
+        candidates: usize,
+    },
+}
+
+/// The worst-case performance of a StrongARM across corners and input common modes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct StrongArmMetrics {
+    /// The estimated standard deviation of the input-referred offset, in volts.
+    pub offset_sigma: f64,
+    /// The clock-to-output delay at the minimum overdrive, in seconds,
+    /// or [`None`] if the comparator failed to resolve correctly.
+    pub clk_to_q: Option<f64>,
+}
+
+impl StrongArmMetrics {
+    /// Returns true if these metrics meet `spec`.
+    pub fn meets<C>(&self, spec: &StrongArmSpec<C>) -> bool {
+        self.offset_sigma <= spec.offset_sigma && self.clk_to_q.is_some_and(|t| t <= spec.clk_to_q)
+    }
+}
+
+/// The candidate designs searched by [`design_strongarm`].
+///
+/// Each candidate scales the input pair and the remaining latch devices of `base`
+/// independently by each of `scales`, for each input kind in `input_kinds`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StrongArmSearch {
+    /// The design from which candidates are scaled.
+    pub base: StrongArmParams,
+    /// The input pair device kinds to try.
+    pub input_kinds: Vec<InputKind>,
+    /// The width scale factors to try, as percentages of the widths of `base`.
+    pub scales: Vec<i64>,
+}
+
+impl StrongArmSearch {
+    /// Creates a search over NMOS and PMOS input pairs, scaling `base` by 1x to 8x.
+    pub fn new(base: StrongArmParams) -> Self {
+        Self {
+            base,
+            input_kinds: vec![InputKind::N, InputKind::P],
+            scales: vec![100, 200, 400, 800],
+        }
+    }
+
+    /// The candidate designs, ordered by increasing [gate width](StrongArmParams::gate_width).
+    pub fn candidates(&self) -> Vec<StrongArmParams> {
+        let mut candidates = Vec::new();
+        for &input_kind in &self.input_kinds {
+            for &input in &self.scales {
+                for &latch in &self.scales {
+                    let b = self.base;
+                    candidates.push(StrongArmParams {
+                        input_kind,
+                        input_pair_w: b.input_pair_w.scale(input, 100),
+                        half_tail_w: b.half_tail_w.scale(latch, 100),
+                        inv_input_w: b.inv_input_w.scale(latch, 100),
+                        inv_precharge_w: b.inv_precharge_w.scale(latch, 100),
+                        precharge_w: b.precharge_w.scale(latch, 100),
+                        ..b
+                    });
+                }
+            }
+        }
+        candidates.sort_by_key(StrongArmParams::gate_width);
+        candidates.dedup();
+        candidates
+    }
+}
+
+/// A StrongARM design returned by [`design_strongarm`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct StrongArmDesign {
+    /// The parameters of the smallest passing design.
+    pub params: StrongArmParams,
+    /// The worst-case performance of the design.
+    pub metrics: StrongArmMetrics,
+    /// The number of candidates evaluated, including the passing design.
+    pub evaluated: usize,
+}
+
+/// Returns the smallest design in `search` that meets `spec`.
+///
+/// Candidates are evaluated in order of increasing gate width by `evaluate`, which
+/// returns the worst-case metrics of a candidate across the corners and common-mode
+/// range of `spec`. Use [`simulate_strongarm`] to evaluate candidates in Spectre.
+pub fn design_strongarm<C>(
+    spec: &StrongArmSpec<C>,
+    search: &StrongArmSearch,
+    mut evaluate: impl FnMut(StrongArmParams) -> StrongArmMetrics,
+) -> Result<StrongArmDesign, StrongArmDesignError> {
+    for (field, value) in [
+        ("offset_sigma", spec.offset_sigma),
+        ("clk_to_q", spec.clk_to_q),
+        ("min_overdrive", spec.min_overdrive.to_f64().unwrap()),
+    ] {
+        if value.is_nan() || value <= 0. {
+            return Err(StrongArmDesignError::NonPositive { field, value });
+        }
+    }
+    if spec.pvts.is_empty() {
+        return Err(StrongArmDesignError::NoCorners);
+    }
+
+    let candidates = search.candidates();
+    for (i, params) in candidates.iter().enumerate() {
+        let metrics = evaluate(*params);
+        tracing::debug!(?params, ?metrics, "evaluated StrongARM candidate");
+        if metrics.meets(spec) {
+            return Ok(StrongArmDesign {
+                params: *params,
+                metrics,
+                evaluated: i + 1,
+            });
+        }
+    }
+    Err(StrongArmDesignError::NoPassingDesign {
+        candidates: candidates.len(),
+    })
+}
+
+/// Measures the worst-case metrics of the StrongARM with parameters `params` across the
+/// corners and common-mode range of `spec` using Spectre.
+///
+/// The offset is estimated from `mc_samples` mismatch samples at each corner and common
+/// mode. Simulations are run in subdirectories of `work_dir` named after the candidate,
+/// so one working directory can be shared by all candidates of a search.
+///
+/// Returns an error without simulating if `mc_samples` is zero or the target offset is
+/// not positive.
+pub fn simulate_strongarm<T, PDK, C>(
+    ctx: &PdkContext<PDK>,
+    spec: &StrongArmSpec<C>,
+    params: StrongArmParams,
+    mc_samples: usize,
+    work_dir: impl AsRef<Path>,
+) -> Result<StrongArmMetrics, StrongArmDesignError>
+where
+    T: 'static,
+    PDK: Pdk + 'static,
+    C: Copy + Debug + Hash + Eq + Send + Sync + 'static,
+    StrongArmTranTb<StrongArm<T>, PDK, C>:
+        Testbench<Spectre, Output = Option<ComparatorDecision>> + Serialize,
+    StrongArmSupplyNoiseTb<StrongArm<T>, PDK, C>:
+        Testbench<Spectre, Output = StrongArmSupplyNoiseTbOutput> + Serialize,
+{
+    if mc_samples == 0 {
+        return Err(StrongArmDesignError::NoSamples);
+    }
+    let probe = offset_probe(spec.offset_sigma)?;
+    let work_dir = work_dir
+        .as_ref()
+        .join(crate::block_name("strongarm", &params).as_str());
+    let dut = StrongArm::<T>::new(params);
+    let inverted_clk = params.input_kind.is_p();
+    let od = spec.min_overdrive / dec!(2);
+    // Leave half of each evaluation phase for the outputs to separate.
+    let period = Decimal::from_f64(4. * spec.clk_to_q).unwrap().round_dp(15);
+
+    let mut metrics = StrongArmMetrics {
+        offset_sigma: 0.,
+        clk_to_q: Some(0.),
+    };
+    for vcm in [spec.vcm.0, spec.vcm.1] {
+        for (i, &pvt) in spec.pvts.iter().enumerate() {
+            let (vinp, vinn) = (vcm + probe / dec!(2), vcm - probe / dec!(2));
+            let mc = MonteCarlo::new(mc_samples, Variations::Mismatch, move |sample| {
+                StrongArmTranTb::new(dut, vinp, vinn, inverted_clk, pvt).with_mc_sample(sample)
+            })
+            .run::<Spectre, _>(ctx, work_dir.join(format!("offset_vcm{vcm}_pvt{i}")));
+            let errors = mc
+                .samples
+                .iter()
+                .filter(|&&decision| decision != Some(ComparatorDecision::Pos))
+                .count();
+            metrics.offset_sigma =
+                metrics
+                    .offset_sigma
+                    .max(offset_sigma(probe.to_f64().unwrap(), errors, mc_samples));
+        }
+
+        let delays = CornerSweep::new(spec.pvts.clone(), move |pvt| {
+            StrongArmSupplyNoiseTb::new(
+                StrongArmHighSpeedTbParams {
+                    dut,
+                    v0: (vcm - od, vcm + od),
+                    v1: (vcm + od, vcm - od),
+                    period,
+                    cycles: DELAY_CYCLES,
+                    thresh: dec!(0.75),
+                    tr: period / dec!(50),
+                    tf: period / dec!(50),
+                    inverted_clk,
+                    pvt,
+                    probes: ProbeSet::default(),
+                },
+                SupplyNoise::none(),
+            )
+        })
+        .run::<Spectre, _>(ctx, work_dir.join(format!("delay_vcm{vcm}")));
+        for output in delays.outputs.values() {
+            let worst = if output.decisions.is_correct() {
+                output
+                    .delays
+                    .iter()
+                    .copied()
+                    .reduce(|a, b| Some(a?.max(b?)))
+                    .flatten()
+            } else {
+                None
+            };
+            metrics.clk_to_q = metrics.clk_to_q.zip(worst).map(|(a, b)| a.max(b));
+        }
+    }
+    Ok(metrics)
+}
+
+/// The differential input with which the offset of a StrongARM with target offset
+/// `offset_sigma` is probed.
+///
+/// The input is rounded to 6 significant digits rather than decimal places, so that
+/// small targets do not round to a zero input.
+fn offset_probe(offset_sigma: f64) -> Result<Decimal, StrongArmDesignError> {
+    Decimal::from_f64(offset_sigma)
+        .and_then(|v| v.round_sf(6))
+        .filter(|v| v.is_sign_positive() && !v.is_zero())
+        .ok_or(StrongArmDesignError::NonPositive {
+            field: "offset_sigma",
+            value: offset_sigma,
+        })
+}
+
+/// Estimates the standard deviation of a zero-mean, normally distributed offset from the
+/// number of `errors` in `samples` decisions made with a differential input of `v`.
+///
+/// With no errors, the estimate assumes half an error, giving an upper bound that
+/// tightens with more samples.
+fn offset_sigma(v: f64, errors: usize, samples: usize) -> f64 {
+    let n = samples as f64;
+    let p = (errors as f64).clamp(0.5, n - 0.5) / n;
+    if p >= 0.5 {
+        return f64::INFINITY;
+    }
+    v / inverse_normal_cdf(1. - p)
+}
+
+/// The inverse of the standard normal cumulative distribution function.
+///
+/// Uses the rational approximation of P. J. Acklam, with a relative error below 1.2e-9.
+fn inverse_normal_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.383577518672690e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.)
+    };
+    if p < P_LOW {
+        tail((-2. * p.ln()).sqrt())
+    } else if p > 1. - P_LOW {
+        -tail((-2. * (1. - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tiles::WidthSpec;
+
+    #[test]
+    fn returns_smallest_passing_design() {
+        assert!((inverse_normal_cdf(0.841344746) - 1.).abs() < 1e-6);
+        assert!((inverse_normal_cdf(0.001) + 3.090232).abs() < 1e-5);
+        assert!((offset_sigma(1e-3, 159, 1000) - 1e-3).abs() < 2e-5);
+        assert_eq!(offset_probe(1.234567e-7), Ok(dec!(0.000000123457)));
+        assert!(offset_probe(0.).is_err());
+
+        let spec = StrongArmSpec {
+            offset_sigma: 5e-3,
+            clk_to_q: 100e-12,
+            min_overdrive: dec!(0.01),
+            vcm: (dec!(0.5), dec!(0.7)),
+            pvts: vec![Pvt {
+                corner: (),
+                voltage: dec!(1.8),
+                temp: dec!(25),
+            }],
+        };
+        let search = StrongArmSearch::new(StrongArmParams::builder().build().unwrap());
+        // Offset shrinks with input pair area, and delay with latch strength.
+        let model = |p: StrongArmParams| StrongArmMetrics {
+            offset_sigma: 10e-3 / (p.input_pair_w.value() as f64 / 1_000.).sqrt(),
+            clk_to_q: Some(300e-12 / (p.precharge_w.value() as f64 / 1_000.)),
+        };
+        let design = design_strongarm(&spec, &search, model).unwrap();
+        assert!(design.metrics.meets(&spec));
+        assert_eq!(design.params.precharge_w, WidthSpec::Nm(4_000));
+        let passing = search
+            .candidates()
+            .into_iter()
+            .filter(|&p| model(p).meets(&spec))
+            .map(|p| p.gate_width())
+            .min();
+        assert_eq!(Some(design.params.gate_width()), passing);
+
+        let spec = StrongArmSpec {
+            clk_to_q: 10e-12,
+            ..spec
+        };
+        assert_eq!(
+            design_strongarm(&spec, &search, model),
+            Err(StrongArmDesignError::NoPassingDesign { candidates: 32 })
+        );
+    }
+}
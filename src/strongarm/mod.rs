@@ -1,8 +1,13 @@
 //! StrongARM latch layout generators.
 
-use crate::buffer::{BufferIoSchematic, Inverter, InverterImpl, InverterParams};
-use crate::tiles::{MosKind, MosTileParams, TapIo, TapTileParams, TileKind};
-use atoll::route::{GreedyRouter, ViaMaker};
+use crate::buffer::{Buffer, BufferIoSchematic, Inverter, InverterImpl, InverterParams};
+use crate::params::{check_positive, setters, ParamsError};
+use crate::route::{match_length, DiffRouteLengths, RouterKind, Serpentine};
+use crate::tb::probe::{ProbePoints, Probes};
+use crate::tiles::{
+    MosKind, MosLengthRules, MosTileParams, TapIo, TapTileParams, TileKind, WidthSpec,
+};
+use atoll::route::ViaMaker;
 use atoll::{IoBuilder, Orientation, Tile, TileBuilder};
 use serde::{Deserialize, Serialize};
 use std::any::Any;
@@ -11,12 +16,22 @@ use substrate::arcstr::ArcStr;
 use substrate::block::Block;
 use substrate::error::Result;
 use substrate::geometry::align::AlignMode;
+use substrate::geometry::bbox::Bbox;
+use substrate::geometry::point::Point;
+use substrate::geometry::rect::Rect;
+use substrate::geometry::span::Span;
+use substrate::geometry::transform::{TransformMut, Transformation, TranslateMut};
 use substrate::io::{DiffPair, InOut, Input, Io, MosIo, MosIoSchematic, Output, Signal};
-use substrate::layout::ExportsLayoutData;
+use substrate::layout::element::Shape;
+use substrate::layout::tracks::RoundingMode;
+use substrate::layout::{ExportsLayoutData, LayoutData};
 use substrate::pdk::Pdk;
 use substrate::schematic::schema::Schema;
 use substrate::schematic::ExportsNestedData;
 
+pub mod array;
+pub mod cal;
+pub mod design;
 pub mod tb;
 
 /// The interface to a clocked differential comparator.
@@ -53,6 +68,94 @@ impl InputKind {
     pub fn is_p(&self) -> bool {
         matches!(self, InputKind::P)
     }
+
+    /// The tile kinds of the input-side and precharge-side devices.
+    pub fn tile_kinds(&self) -> (TileKind, TileKind) {
+        match self {
+            InputKind::N => (TileKind::N, TileKind::P),
+            InputKind::P => (TileKind::P, TileKind::N),
+        }
+    }
+}
+
+/// The edge dummies placed in each row of a [`StrongArm`] half.
+///
+/// Dummies are tied off to the rail of their row.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum DummyPolicy {
+    /// No dummies.
+    None,
+    /// `count` dummies on the outer edge of each row.
+    SingleSided {
+        /// The number of dummies per row.
+        count: usize,
+    },
+    /// `count` dummies on each edge of each row.
+    DoubleSided {
+        /// The number of dummies on each edge of a row.
+        count: usize,
+    },
+}
+
+impl Default for DummyPolicy {
+    /// A single dummy on the outer edge of each row.
+    fn default() -> Self {
+        DummyPolicy::SingleSided { count: 1 }
+    }
+}
+
+impl DummyPolicy {
+    /// The number of dummies on the outer and inner edges of each row.
+    pub fn counts(&self) -> (usize, usize) {
+        match *self {
+            DummyPolicy::None => (0, 0),
+            DummyPolicy::SingleSided { count } => (count, 0),
+            DummyPolicy::DoubleSided { count } => (count, count),
+        }
+    }
+}
+
+/// A keeper split from the output precharge devices of a [`StrongArm`] half.
+///
+/// The main output precharge devices keep [`StrongArmParams::precharge_w`], and a
+/// second pair of width `w` is added in parallel in its own row.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct PrechargeKeeper {
+    /// The width of the keeper MOS devices.
+    pub w: i64,
+    /// The channel length of the keeper MOS devices.
+    #[serde(default)]
+    pub l: Option<i64>,
+    /// Whether the keeper is driven by a delayed copy of the clock.
+    ///
+    /// The clock is delayed by two inverters of keeper-sized devices in each half, so the
+    /// keeper turns on after the main precharge devices have pulled the outputs most of the way.
+    #[serde(default)]
+    pub delayed_clock: bool,
+}
+
+/// Clock feedthrough neutralization capacitors added to each [`StrongArm`] half.
+///
+/// The clock couples onto the outputs through the gate-drain overlap of the output
+/// precharge devices. Each output gets a MOS capacitor of the precharge device kind, with
+/// its gate on the output and its source and drain on a locally inverted clock, so that
+/// the capacitor couples an opposing step onto the output. The inverter's devices form one
+/// extra row of each kind, with two devices per row.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Neutralization {
+    /// The width of each capacitor and inverter device, as a percentage of
+    /// [`StrongArmParams::precharge_w`].
+    pub percent: i64,
+    /// The channel length of the capacitor and inverter devices.
+    #[serde(default)]
+    pub l: Option<i64>,
+}
+
+impl Neutralization {
+    /// The width of the capacitor and inverter devices given the precharge device width.
+    pub fn width(&self, precharge_w: WidthSpec) -> WidthSpec {
+        precharge_w.scale(self.percent, 100)
+    }
 }
 
 /// The parameters of the [`StrongArm`] layout generator.
@@ -63,27 +166,250 @@ pub struct StrongArmParams {
     /// The PMOS device flavor.
     pub pmos_kind: MosKind,
     /// The width of one half of the tail MOS device.
-    pub half_tail_w: i64,
+    pub half_tail_w: WidthSpec,
     /// The width of an input pair MOS device.
-    pub input_pair_w: i64,
+    pub input_pair_w: WidthSpec,
     /// The width of the inverter MOS devices connected to the input pair.
-    pub inv_input_w: i64,
+    pub inv_input_w: WidthSpec,
     /// The width of the inverter MOS devices connected to the precharge devices.
-    pub inv_precharge_w: i64,
+    pub inv_precharge_w: WidthSpec,
     /// The width of the precharge MOS devices.
-    pub precharge_w: i64,
+    pub precharge_w: WidthSpec,
     /// The kind of the input pair MOS devices.
     pub input_kind: InputKind,
+    /// The channel length of the tail MOS devices.
+    ///
+    /// Like the other lengths, defaults to the technology minimum if `None`.
+    #[serde(default)]
+    pub half_tail_l: Option<i64>,
+    /// The channel length of the input pair MOS devices.
+    #[serde(default)]
+    pub input_pair_l: Option<i64>,
+    /// The channel length of the inverter MOS devices connected to the input pair.
+    #[serde(default)]
+    pub inv_input_l: Option<i64>,
+    /// The channel length of the inverter MOS devices connected to the precharge devices.
+    #[serde(default)]
+    pub inv_precharge_l: Option<i64>,
+    /// The channel length of the precharge MOS devices.
+    #[serde(default)]
+    pub precharge_l: Option<i64>,
+    /// The edge dummies in each row.
+    #[serde(default)]
+    pub dummies: DummyPolicy,
+    /// An optional keeper split from the output precharge devices.
+    #[serde(default)]
+    pub precharge_keeper: Option<PrechargeKeeper>,
+    /// Optional clock feedthrough neutralization capacitors.
+    #[serde(default)]
+    pub neutralization: Option<Neutralization>,
+}
+
+impl StrongArmParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> StrongArmParamsBuilder {
+        StrongArmParamsBuilder::default()
+    }
+
+    /// Returns an error if the MOS tiles of technology `T` do not support one of the
+    /// channel lengths.
+    pub fn check_lengths<T: MosLengthRules>(&self) -> std::result::Result<(), ParamsError> {
+        T::check_length("half_tail_l", self.half_tail_l)?;
+        T::check_length("input_pair_l", self.input_pair_l)?;
+        T::check_length("inv_input_l", self.inv_input_l)?;
+        T::check_length("inv_precharge_l", self.inv_precharge_l)?;
+        T::check_length("precharge_l", self.precharge_l)?;
+        T::check_length(
+            "precharge_keeper.l",
+            self.precharge_keeper.and_then(|k| k.l),
+        )?;
+        T::check_length("neutralization.l", self.neutralization.and_then(|n| n.l))
+    }
+
+    /// Returns an error if the parameters would be rejected by
+    /// [`StrongArmParamsBuilder::build`].
+    pub fn validate(&self) -> std::result::Result<(), ParamsError> {
+        StrongArmParamsBuilder { params: *self }.build().map(|_| ())
+    }
+
+    /// The total width of the gates driven by the clock.
+    ///
+    /// Each half of the latch has two tail devices and four precharge devices.
+    /// With a [`PrechargeKeeper`], the clock also drives either the two keeper devices
+    /// or the first delay inverter of each half, both of which have twice the keeper width.
+    /// With [`Neutralization`], it also drives the four inverter devices of each half.
+    ///
+    /// Widths are summed in their own unit, so all devices should use the same kind of
+    /// [`WidthSpec`].
+    pub fn clock_gate_width(&self) -> i64 {
+        let keeper_w = self.precharge_keeper.map_or(0, |keeper| 2 * keeper.w);
+        let neutralization_w = self
+            .neutralization
+            .map_or(0, |n| 4 * n.width(self.precharge_w).value());
+        2 * (2 * self.half_tail_w.value()
+            + 4 * self.precharge_w.value()
+            + keeper_w
+            + neutralization_w)
+    }
+
+    /// The total gate width of the latch, a proxy for its area.
+    ///
+    /// Adds the input pair and cross-coupled inverter devices of each half to the
+    /// [clock gate width](Self::clock_gate_width).
+    pub fn gate_width(&self) -> i64 {
+        self.clock_gate_width()
+            + 2 * (self.input_pair_w.value()
+                + self.inv_input_w.value()
+                + self.inv_precharge_w.value())
+    }
+
+    /// Snaps every MOS width with `snap`, returning parameters that record the
+    /// achieved widths.
+    pub fn snapped(self, snap: impl Fn(TileKind, WidthSpec) -> WidthSpec) -> Self {
+        let (input_kind, precharge_kind) = self.input_kind.tile_kinds();
+        Self {
+            half_tail_w: snap(input_kind, self.half_tail_w),
+            input_pair_w: snap(input_kind, self.input_pair_w),
+            inv_input_w: snap(input_kind, self.inv_input_w),
+            inv_precharge_w: snap(precharge_kind, self.inv_precharge_w),
+            precharge_w: snap(precharge_kind, self.precharge_w),
+            ..self
+        }
+    }
+}
+
+impl TranslateMut for StrongArmParams {
+    fn translate_mut(&mut self, _p: Point) {}
+}
+
+impl TransformMut for StrongArmParams {
+    fn transform_mut(&mut self, _trans: Transformation) {}
+}
+
+/// A builder for [`StrongArmParams`].
+///
+/// Defaults to a PMOS-input comparator built from 1 um nominal-Vt devices,
+/// which is known to be LVS and DRC clean in SKY130.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct StrongArmParamsBuilder {
+    params: StrongArmParams,
+}
+
+impl Default for StrongArmParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: StrongArmParams {
+                nmos_kind: MosKind::Nom,
+                pmos_kind: MosKind::Nom,
+                half_tail_w: WidthSpec::Nm(1_000),
+                input_pair_w: WidthSpec::Nm(1_000),
+                inv_input_w: WidthSpec::Nm(1_000),
+                inv_precharge_w: WidthSpec::Nm(1_000),
+                precharge_w: WidthSpec::Nm(1_000),
+                input_kind: InputKind::P,
+                half_tail_l: None,
+                input_pair_l: None,
+                inv_input_l: None,
+                inv_precharge_l: None,
+                precharge_l: None,
+                dummies: DummyPolicy::default(),
+                precharge_keeper: None,
+                neutralization: None,
+            },
+        }
+    }
+}
+
+impl StrongArmParamsBuilder {
+    setters! {
+        /// Sets the NMOS device flavor.
+        nmos_kind: MosKind,
+        /// Sets the PMOS device flavor.
+        pmos_kind: MosKind,
+        /// Sets the width of one half of the tail MOS device.
+        half_tail_w: impl Into<WidthSpec>,
+        /// Sets the width of an input pair MOS device.
+        input_pair_w: impl Into<WidthSpec>,
+        /// Sets the width of the inverter MOS devices connected to the input pair.
+        inv_input_w: impl Into<WidthSpec>,
+        /// Sets the width of the inverter MOS devices connected to the precharge devices.
+        inv_precharge_w: impl Into<WidthSpec>,
+        /// Sets the width of the precharge MOS devices.
+        precharge_w: impl Into<WidthSpec>,
+        /// Sets the kind of the input pair MOS devices.
+        input_kind: InputKind,
+        /// Sets the channel length of the tail MOS devices.
+        half_tail_l: Option<i64>,
+        /// Sets the channel length of the input pair MOS devices.
+        input_pair_l: Option<i64>,
+        /// Sets the channel length of the inverter MOS devices connected to the input pair.
+        inv_input_l: Option<i64>,
+        /// Sets the channel length of the inverter MOS devices connected to the precharge devices.
+        inv_precharge_l: Option<i64>,
+        /// Sets the channel length of the precharge MOS devices.
+        precharge_l: Option<i64>,
+        /// Sets the edge dummies in each row.
+        dummies: DummyPolicy,
+        /// Sets the keeper split from the output precharge devices.
+        precharge_keeper: Option<PrechargeKeeper>,
+        /// Sets the clock feedthrough neutralization capacitors.
+        neutralization: Option<Neutralization>,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<StrongArmParams, ParamsError> {
+        let p = &self.params;
+        for (field, value) in [
+            ("half_tail_w", p.half_tail_w.value()),
+            ("input_pair_w", p.input_pair_w.value()),
+            ("inv_input_w", p.inv_input_w.value()),
+            ("inv_precharge_w", p.inv_precharge_w.value()),
+            ("precharge_w", p.precharge_w.value()),
+        ] {
+            check_positive(field, value)?;
+        }
+        for (field, value) in [
+            ("half_tail_l", p.half_tail_l),
+            ("input_pair_l", p.input_pair_l),
+            ("inv_input_l", p.inv_input_l),
+            ("inv_precharge_l", p.inv_precharge_l),
+            ("precharge_l", p.precharge_l),
+        ] {
+            value.map_or(Ok(()), |value| check_positive(field, value))?;
+        }
+        if let Some(keeper) = p.precharge_keeper {
+            check_positive("precharge_keeper.w", keeper.w)?;
+            keeper
+                .l
+                .map_or(Ok(()), |l| check_positive("precharge_keeper.l", l))?;
+        }
+        if let Some(neutralization) = p.neutralization {
+            check_positive("neutralization.percent", neutralization.percent)?;
+            check_positive(
+                "neutralization width",
+                neutralization.width(p.precharge_w).value(),
+            )?;
+            neutralization
+                .l
+                .map_or(Ok(()), |l| check_positive("neutralization.l", l))?;
+        }
+        Ok(self.params)
+    }
 }
 
 /// A StrongARM latch implementation.
-pub trait StrongArmImpl<PDK: Pdk + Schema> {
+///
+/// StrongARM latches reject channel lengths that the [`MosLengthRules`] of the
+/// implementation do not support before generating any tiles.
+pub trait StrongArmImpl<PDK: Pdk + Schema>: MosLengthRules {
     /// The MOS tile.
     type MosTile: Tile<PDK> + Block<Io = MosIo> + Clone;
     /// The tap tile.
     type TapTile: Tile<PDK> + Block<Io = TapIo> + Clone;
     /// A PDK-specific via maker.
     type ViaMaker: ViaMaker<PDK>;
+    /// The router used by the StrongARM and its halves.
+    const ROUTER: RouterKind = RouterKind::Greedy;
 
     /// Creates an instance of the MOS tile.
     fn mos(params: MosTileParams) -> Self::MosTile;
@@ -91,6 +417,10 @@ pub trait StrongArmImpl<PDK: Pdk + Schema> {
     fn tap(params: TapTileParams) -> Self::TapTile;
     /// Creates a PDK-specific via maker.
     fn via_maker() -> Self::ViaMaker;
+    /// Snaps a requested MOS width to a legal value, returning the achieved width.
+    fn snap_width(_kind: TileKind, width: WidthSpec) -> WidthSpec {
+        width
+    }
     /// Additional layout hooks to run after the strongARM layout is complete.
     fn post_layout_hooks(_cell: &mut TileBuilder<'_, PDK>) -> Result<()> {
         Ok(())
@@ -127,9 +457,8 @@ impl<T: Any> Block for StrongArmHalf<T> {
         substrate::arcstr::literal!("strong_arm_half")
     }
 
-    // todo: include parameters in name
     fn name(&self) -> ArcStr {
-        substrate::arcstr::literal!("strong_arm_half")
+        crate::block_name("strong_arm_half", &self.0)
     }
 
     fn io(&self) -> Self::Io {
@@ -141,11 +470,19 @@ impl<T: Any> ExportsNestedData for StrongArmHalf<T> {
     type NestedData = ();
 }
 
+/// Layout data returned by the [`StrongArmHalf`] and [`StrongArm`] layout generators.
+#[derive(LayoutData)]
+pub struct StrongArmLayoutData {
+    /// The parameters with every MOS width replaced by the achieved width.
+    pub params: StrongArmParams,
+}
+
 impl<T: Any> ExportsLayoutData for StrongArmHalf<T> {
-    type LayoutData = ();
+    type LayoutData = StrongArmLayoutData;
 }
 
 impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for StrongArmHalf<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
     fn tile<'a>(
         &self,
         io: IoBuilder<'a, Self>,
@@ -154,6 +491,9 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for Stron
         <Self as ExportsNestedData>::NestedData,
         <Self as ExportsLayoutData>::LayoutData,
     )> {
+        self.0
+            .check_lengths::<T>()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
         let (
             input_kind,
             precharge_kind,
@@ -179,27 +519,47 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for Stron
                 io.schematic.top_io.vss,
             ),
         };
-        let half_tail_params = MosTileParams::new(input_flavor, input_kind, self.0.half_tail_w);
-        let input_pair_params = MosTileParams::new(input_flavor, input_kind, self.0.input_pair_w);
-        let inv_input_params = MosTileParams::new(input_flavor, input_kind, self.0.inv_input_w);
+        let snapped = self.0.snapped(T::snap_width);
+        let half_tail_params = MosTileParams::new(input_flavor, input_kind, snapped.half_tail_w)
+            .with_length(self.0.half_tail_l);
+        let input_pair_params = MosTileParams::new(input_flavor, input_kind, snapped.input_pair_w)
+            .with_length(self.0.input_pair_l);
+        let inv_input_params = MosTileParams::new(input_flavor, input_kind, snapped.inv_input_w)
+            .with_length(self.0.inv_input_l);
         let inv_precharge_params =
-            MosTileParams::new(precharge_flavor, precharge_kind, self.0.inv_precharge_w);
+            MosTileParams::new(precharge_flavor, precharge_kind, snapped.inv_precharge_w)
+                .with_length(self.0.inv_precharge_l);
         let precharge_params =
-            MosTileParams::new(precharge_flavor, precharge_kind, self.0.precharge_w);
+            MosTileParams::new(precharge_flavor, precharge_kind, snapped.precharge_w)
+                .with_length(self.0.precharge_l);
 
         let tail = io.schematic.tail_d;
         let intn = io.schematic.input_d.n;
-        let intp = cell.signal("intp", Signal);
-
-        let mut tail_dummy = cell.generate_connected(
-            T::mos(half_tail_params),
-            MosIoSchematic {
-                d: input_rail,
-                g: input_rail,
-                s: input_rail,
-                b: input_rail,
-            },
-        );
+        let intp = io.schematic.input_d.p;
+
+        // Dummies on the outer and inner edges of a row, tied off to `rail`.
+        let (outer, inner) = self.0.dummies.counts();
+        let dummies = |cell: &mut TileBuilder<'a, PDK>, params: MosTileParams, rail| {
+            let mut make = |n| {
+                (0..n)
+                    .map(|_| {
+                        cell.generate_connected(
+                            T::mos(params),
+                            MosIoSchematic {
+                                d: rail,
+                                g: rail,
+                                s: rail,
+                                b: rail,
+                            },
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let outer = make(outer);
+            (outer, make(inner))
+        };
+
+        let mut tail_dummies = dummies(cell, half_tail_params, input_rail);
         let mut tail_pair = (0..2)
             .map(|_| {
                 cell.generate_connected(
@@ -214,8 +574,9 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for Stron
             })
             .collect::<Vec<_>>();
 
-        let mut ptap = cell.generate(T::tap(TapTileParams::new(TileKind::P, 3)));
-        let ntap = cell.generate(T::tap(TapTileParams::new(TileKind::N, 3)));
+        let row_span = (2 + outer + inner) as i64;
+        let mut ptap = cell.generate(T::tap(TapTileParams::new(TileKind::P, row_span)));
+        let ntap = cell.generate(T::tap(TapTileParams::new(TileKind::N, row_span)));
         cell.connect(ptap.io().x, io.schematic.top_io.vss);
         cell.connect(ntap.io().x, io.schematic.top_io.vdd);
 
@@ -236,15 +597,7 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for Stron
                 )
             })
             .collect::<Vec<_>>();
-        let mut input_dummy = cell.generate_connected(
-            T::mos(input_pair_params),
-            MosIoSchematic {
-                d: input_rail,
-                g: input_rail,
-                s: input_rail,
-                b: input_rail,
-            },
-        );
+        let mut input_dummies = dummies(cell, input_pair_params, input_rail);
         let mut inv_input_pair = (0..2)
             .map(|i| {
                 cell.generate_connected(
@@ -267,15 +620,7 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for Stron
                 )
             })
             .collect::<Vec<_>>();
-        let mut inv_input_dummy = cell.generate_connected(
-            T::mos(inv_input_params),
-            MosIoSchematic {
-                d: input_rail,
-                g: input_rail,
-                s: input_rail,
-                b: input_rail,
-            },
-        );
+        let mut inv_input_dummies = dummies(cell, inv_input_params, input_rail);
         let mut inv_precharge_pair = (0..2)
             .map(|i| {
                 cell.generate_connected(
@@ -297,15 +642,7 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for Stron
                 )
             })
             .collect::<Vec<_>>();
-        let mut inv_precharge_dummy = cell.generate_connected(
-            T::mos(inv_precharge_params),
-            MosIoSchematic {
-                d: precharge_rail,
-                g: precharge_rail,
-                s: precharge_rail,
-                b: precharge_rail,
-            },
-        );
+        let mut inv_precharge_dummies = dummies(cell, inv_precharge_params, precharge_rail);
         let mut precharge_pair_a = (0..2)
             .map(|i| {
                 cell.generate_connected(
@@ -323,15 +660,7 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for Stron
                 )
             })
             .collect::<Vec<_>>();
-        let mut precharge_pair_a_dummy = cell.generate_connected(
-            T::mos(precharge_params),
-            MosIoSchematic {
-                d: precharge_rail,
-                g: precharge_rail,
-                s: precharge_rail,
-                b: precharge_rail,
-            },
-        );
+        let mut precharge_pair_a_dummies = dummies(cell, precharge_params, precharge_rail);
         let mut precharge_pair_b = (0..2)
             .map(|i| {
                 cell.generate_connected(
@@ -345,40 +674,180 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for Stron
                 )
             })
             .collect::<Vec<_>>();
-        let mut precharge_pair_b_dummy = cell.generate_connected(
-            T::mos(precharge_params),
-            MosIoSchematic {
-                d: precharge_rail,
-                g: precharge_rail,
-                s: precharge_rail,
-                b: precharge_rail,
-            },
-        );
+        let mut precharge_pair_b_dummies = dummies(cell, precharge_params, precharge_rail);
+
+        // The keeper sits in its own row next to the main output precharge devices.
+        // A delayed keeper clock is produced by two inverters whose devices form one extra
+        // row of each kind, placed next to the tap of the same kind.
+        let mut keeper_rows = self.0.precharge_keeper.map(|keeper| {
+            let keeper_params = MosTileParams::new(precharge_flavor, precharge_kind, keeper.w)
+                .with_length(keeper.l)
+                .snapped(T::snap_width);
+            let keeper_clock = if keeper.delayed_clock {
+                cell.signal("keeper_clock", Signal)
+            } else {
+                io.schematic.top_io.clock
+            };
+            let keeper_pair = (0..2)
+                .map(|i| {
+                    cell.generate_connected(
+                        T::mos(keeper_params),
+                        MosIoSchematic {
+                            d: if i == 0 {
+                                io.schematic.top_io.output.n
+                            } else {
+                                io.schematic.top_io.output.p
+                            },
+                            g: keeper_clock,
+                            s: precharge_rail,
+                            b: precharge_rail,
+                        },
+                    )
+                })
+                .collect::<Vec<_>>();
+            let keeper_dummies = dummies(cell, keeper_params, precharge_rail);
+            let delay_rows = keeper.delayed_clock.then(|| {
+                let clock_b = cell.signal("keeper_clock_b", Signal);
+                let stages = [
+                    (io.schematic.top_io.clock, clock_b),
+                    (clock_b, keeper_clock),
+                ];
+                let mut delay_row = |flavor, kind, rail| {
+                    let params = MosTileParams::new(flavor, kind, keeper.w)
+                        .with_length(keeper.l)
+                        .snapped(T::snap_width);
+                    let devices = stages
+                        .iter()
+                        .map(|&(input, output)| {
+                            cell.generate_connected(
+                                T::mos(params),
+                                MosIoSchematic {
+                                    d: output,
+                                    g: input,
+                                    s: rail,
+                                    b: rail,
+                                },
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    (dummies(cell, params, rail), devices)
+                };
+                (
+                    delay_row(precharge_flavor, precharge_kind, precharge_rail),
+                    delay_row(input_flavor, input_kind, input_rail),
+                )
+            });
+            ((keeper_dummies, keeper_pair), delay_rows)
+        });
+
+        // The neutralization capacitors sit in their own row next to the main output
+        // precharge devices, and their inverted clock comes from one extra row of each kind,
+        // placed outside any keeper delay rows.
+        let mut neutralization_rows = self.0.neutralization.map(|neutralization| {
+            let w = neutralization.width(self.0.precharge_w);
+            let cap_params = MosTileParams::new(precharge_flavor, precharge_kind, w)
+                .with_length(neutralization.l)
+                .snapped(T::snap_width);
+            let clock_b = cell.signal("neutralization_clock_b", Signal);
+            let caps = (0..2)
+                .map(|i| {
+                    cell.generate_connected(
+                        T::mos(cap_params),
+                        MosIoSchematic {
+                            d: clock_b,
+                            g: if i == 0 {
+                                io.schematic.top_io.output.n
+                            } else {
+                                io.schematic.top_io.output.p
+                            },
+                            s: clock_b,
+                            b: precharge_rail,
+                        },
+                    )
+                })
+                .collect::<Vec<_>>();
+            let cap_dummies = dummies(cell, cap_params, precharge_rail);
+            let mut inverter_row = |flavor, kind, rail| {
+                let params = MosTileParams::new(flavor, kind, w)
+                    .with_length(neutralization.l)
+                    .snapped(T::snap_width);
+                let devices = (0..2)
+                    .map(|_| {
+                        cell.generate_connected(
+                            T::mos(params),
+                            MosIoSchematic {
+                                d: clock_b,
+                                g: io.schematic.top_io.clock,
+                                s: rail,
+                                b: rail,
+                            },
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                (dummies(cell, params, rail), devices)
+            };
+            (
+                (cap_dummies, caps),
+                inverter_row(precharge_flavor, precharge_kind, precharge_rail),
+                inverter_row(input_flavor, input_kind, input_rail),
+            )
+        });
 
         let mut prev = ntap.lcm_bounds();
 
-        let mut rows = [
-            (&mut precharge_pair_a_dummy, &mut precharge_pair_a),
-            (&mut precharge_pair_b_dummy, &mut precharge_pair_b),
-            (&mut inv_precharge_dummy, &mut inv_precharge_pair),
-            (&mut inv_input_dummy, &mut inv_input_pair),
-            (&mut input_dummy, &mut input_pair),
-            (&mut tail_dummy, &mut tail_pair),
-        ];
+        let (keeper_row, delay_rows) = match &mut keeper_rows {
+            Some((keeper_row, delay_rows)) => (Some(keeper_row), delay_rows.as_mut()),
+            None => (None, None),
+        };
+        let (precharge_delay_row, input_delay_row) = match delay_rows {
+            Some((precharge_delay_row, input_delay_row)) => {
+                (Some(precharge_delay_row), Some(input_delay_row))
+            }
+            None => (None, None),
+        };
+
+        let (cap_row, precharge_inv_row, input_inv_row) = match &mut neutralization_rows {
+            Some((cap_row, precharge_inv_row, input_inv_row)) => {
+                (Some(cap_row), Some(precharge_inv_row), Some(input_inv_row))
+            }
+            None => (None, None, None),
+        };
+
+        let mut rows = Vec::new();
+        rows.extend(precharge_inv_row.map(|(row_dummies, devices)| (row_dummies, devices)));
+        rows.extend(precharge_delay_row.map(|(row_dummies, devices)| (row_dummies, devices)));
+        rows.push((&mut precharge_pair_a_dummies, &mut precharge_pair_a));
+        rows.extend(cap_row.map(|(row_dummies, devices)| (row_dummies, devices)));
+        rows.extend(keeper_row.map(|(row_dummies, devices)| (row_dummies, devices)));
+        rows.extend([
+            (&mut precharge_pair_b_dummies, &mut precharge_pair_b),
+            (&mut inv_precharge_dummies, &mut inv_precharge_pair),
+            (&mut inv_input_dummies, &mut inv_input_pair),
+            (&mut input_dummies, &mut input_pair),
+            (&mut tail_dummies, &mut tail_pair),
+        ]);
+        rows.extend(input_delay_row.map(|(row_dummies, devices)| (row_dummies, devices)));
+        rows.extend(input_inv_row.map(|(row_dummies, devices)| (row_dummies, devices)));
 
         if self.0.input_kind == InputKind::P {
             rows.reverse();
         }
 
-        for (dummy, mos_pair) in rows {
-            dummy.align_rect_mut(prev, AlignMode::Left, 0);
-            dummy.align_rect_mut(prev, AlignMode::Beneath, 0);
-            prev = dummy.lcm_bounds();
-            mos_pair[0].align_rect_mut(prev, AlignMode::Bottom, 0);
-            mos_pair[0].align_rect_mut(prev, AlignMode::ToTheRight, 0);
-            let left_rect = mos_pair[0].lcm_bounds();
-            mos_pair[1].align_rect_mut(left_rect, AlignMode::Bottom, 0);
-            mos_pair[1].align_rect_mut(left_rect, AlignMode::ToTheRight, 0);
+        for ((outer, inner), mos_pair) in rows {
+            let mut row = outer
+                .iter_mut()
+                .chain(mos_pair.iter_mut())
+                .chain(inner.iter_mut());
+            let first = row.next().unwrap();
+            first.align_rect_mut(prev, AlignMode::Left, 0);
+            first.align_rect_mut(prev, AlignMode::Beneath, 0);
+            prev = first.lcm_bounds();
+            let mut left_rect = prev;
+            for inst in row {
+                inst.align_rect_mut(left_rect, AlignMode::Bottom, 0);
+                inst.align_rect_mut(left_rect, AlignMode::ToTheRight, 0);
+                left_rect = inst.lcm_bounds();
+            }
         }
 
         ptap.align_rect_mut(prev, AlignMode::Left, 0);
@@ -390,35 +859,58 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for Stron
             .into_iter()
             .map(|inst| cell.draw(inst))
             .collect::<Result<Vec<_>>>()?;
-        let _tail_dummy = cell.draw(tail_dummy)?;
         let input_pair = input_pair
             .into_iter()
             .map(|inst| cell.draw(inst))
             .collect::<Result<Vec<_>>>()?;
-        let _input_dummy = cell.draw(input_dummy)?;
         let inv_nmos_pair = inv_input_pair
             .into_iter()
             .map(|inst| cell.draw(inst))
             .collect::<Result<Vec<_>>>()?;
-        let _inv_nmos_dummy = cell.draw(inv_input_dummy)?;
         let _inv_pmos_pair = inv_precharge_pair
             .into_iter()
             .map(|inst| cell.draw(inst))
             .collect::<Result<Vec<_>>>()?;
-        let _inv_pmos_dummy = cell.draw(inv_precharge_dummy)?;
         let _precharge_pair_a = precharge_pair_a
             .into_iter()
             .map(|inst| cell.draw(inst))
             .collect::<Result<Vec<_>>>()?;
-        let _precharge_pair_a_dummy = cell.draw(precharge_pair_a_dummy)?;
         let _precharge_pair_b = precharge_pair_b
             .into_iter()
             .map(|inst| cell.draw(inst))
             .collect::<Result<Vec<_>>>()?;
-        let _precharge_pair_b_dummy = cell.draw(precharge_pair_b_dummy)?;
+
+        let mut extra_rows = Vec::new();
+        if let Some((keeper_row, delay_rows)) = keeper_rows {
+            extra_rows.push(keeper_row);
+            if let Some((precharge_delay_row, input_delay_row)) = delay_rows {
+                extra_rows.extend([precharge_delay_row, input_delay_row]);
+            }
+        }
+        if let Some((cap_row, precharge_inv_row, input_inv_row)) = neutralization_rows {
+            extra_rows.extend([cap_row, precharge_inv_row, input_inv_row]);
+        }
+        for ((outer, inner), devices) in extra_rows {
+            for inst in outer.into_iter().chain(devices).chain(inner) {
+                cell.draw(inst)?;
+            }
+        }
+
+        for (outer, inner) in [
+            tail_dummies,
+            input_dummies,
+            inv_input_dummies,
+            inv_precharge_dummies,
+            precharge_pair_a_dummies,
+            precharge_pair_b_dummies,
+        ] {
+            for inst in outer.into_iter().chain(inner) {
+                cell.draw(inst)?;
+            }
+        }
 
         cell.set_top_layer(2);
-        cell.set_router(GreedyRouter::new());
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
         cell.set_via_maker(T::via_maker());
 
         io.layout.top_io.vdd.set_primary(ntap.layout.io().x.primary);
@@ -440,11 +932,14 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for Stron
             .n
             .merge(inv_nmos_pair[0].layout.io().d);
 
-        Ok(((), ()))
+        Ok(((), StrongArmLayoutData { params: snapped }))
     }
 }
 
 /// A StrongARM latch.
+///
+/// Exports the drains of the input pair (`intn` and `intp`) and of the tail (`tail`)
+/// as [probe points](crate::tb::probe).
 // Layout assumes that PDK layer stack has a vertical layer 0.
 #[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 #[derive(Serialize, Deserialize)]
@@ -458,6 +953,11 @@ impl<T> StrongArm<T> {
     pub const fn new(params: StrongArmParams) -> Self {
         Self(params, PhantomData)
     }
+
+    /// The StrongARM parameters.
+    pub const fn params(&self) -> StrongArmParams {
+        self.0
+    }
 }
 
 impl<T: Any> Block for StrongArm<T> {
@@ -467,9 +967,8 @@ impl<T: Any> Block for StrongArm<T> {
         substrate::arcstr::literal!("strong_arm")
     }
 
-    // todo: include parameters in name
     fn name(&self) -> ArcStr {
-        substrate::arcstr::literal!("strong_arm")
+        crate::block_name("strong_arm", &self.0)
     }
 
     fn io(&self) -> Self::Io {
@@ -478,14 +977,19 @@ impl<T: Any> Block for StrongArm<T> {
 }
 
 impl<T: Any> ExportsNestedData for StrongArm<T> {
-    type NestedData = ();
+    type NestedData = Probes;
+}
+
+impl<T: Any> ProbePoints for StrongArm<T> {
+    const PROBES: &'static [&'static str] = &["intn", "intp", "tail"];
 }
 
 impl<T: Any> ExportsLayoutData for StrongArm<T> {
-    type LayoutData = ();
+    type LayoutData = StrongArmLayoutData;
 }
 
 impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for StrongArm<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
     fn tile<'a>(
         &self,
         io: IoBuilder<'a, Self>,
@@ -494,8 +998,13 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for Stron
         <Self as ExportsNestedData>::NestedData,
         <Self as ExportsLayoutData>::LayoutData,
     )> {
+        self.0
+            .check_lengths::<T>()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
         let tail_d = cell.signal("tail_d", Signal::new());
         let input_d = cell.signal("input_d", DiffPair::default());
+        let probes =
+            Probes::new::<Self>([("intn", input_d.n), ("intp", input_d.p), ("tail", tail_d)]);
 
         let conn = StrongArmHalfIoSchematic {
             top_io: io.schematic.clone(),
@@ -513,7 +1022,7 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for Stron
         let right_half = cell.draw(right_half)?;
 
         cell.set_top_layer(2);
-        cell.set_router(GreedyRouter::new());
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
         cell.set_via_maker(T::via_maker());
 
         io.layout.vdd.merge(left_half.layout.io().top_io.vdd);
@@ -557,6 +1066,201 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for Stron
 
         T::post_layout_hooks(cell)?;
 
+        Ok((
+            probes,
+            StrongArmLayoutData {
+                params: left_half.layout.data().params,
+            },
+        ))
+    }
+}
+
+/// The interface to a clocked differential comparator with an asynchronous reset.
+#[derive(Debug, Default, Clone, Io)]
+pub struct ResettableComparatorIo {
+    /// The input differential pair.
+    pub input: Input<DiffPair>,
+    /// The output differential pair.
+    pub output: Output<DiffPair>,
+    /// The clock signal.
+    pub clock: Input<Signal>,
+    /// The reset signal.
+    ///
+    /// Active at the same level as the precharge phase of `clock`: low for an NMOS-input
+    /// comparator and high for a PMOS-input comparator.
+    pub reset: Input<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// The pull devices of a [`StrongArmWithReset`].
+///
+/// One device of the precharge kind sits on each output, in parallel with the output
+/// precharge device but gated by `reset` rather than the clock. While reset is held,
+/// the outputs are forced to the precharge level whatever the phase of the clock, so
+/// the devices must be strong enough to overpower the latch during evaluation.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ResetParams {
+    /// The width of each pull device.
+    pub w: i64,
+    /// The channel length of each pull device.
+    #[serde(default)]
+    pub l: Option<i64>,
+}
+
+impl Default for ResetParams {
+    fn default() -> Self {
+        Self { w: 1_000, l: None }
+    }
+}
+
+/// A StrongARM latch with an asynchronous reset that forces its outputs to the
+/// precharge level.
+///
+/// The pull devices form one extra row, placed next to the tap of the precharge device
+/// kind: above the latch for an NMOS-input latch and beneath it for a PMOS-input latch.
+// Layout assumes that PDK layer stack has a vertical layer 0.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct StrongArmWithReset<T>(
+    StrongArmParams,
+    ResetParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> StrongArmWithReset<T> {
+    /// Creates a new [`StrongArmWithReset`].
+    pub const fn new(params: StrongArmParams, reset: ResetParams) -> Self {
+        Self(params, reset, PhantomData)
+    }
+
+    /// The StrongARM parameters.
+    pub const fn params(&self) -> StrongArmParams {
+        self.0
+    }
+
+    /// The reset device parameters.
+    pub const fn reset(&self) -> ResetParams {
+        self.1
+    }
+}
+
+impl<T: Any> Block for StrongArmWithReset<T> {
+    type Io = ResettableComparatorIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("strong_arm_with_reset")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("strong_arm_with_reset", &(&self.0, &self.1))
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for StrongArmWithReset<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for StrongArmWithReset<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: StrongArmImpl<PDK> + Any> Tile<PDK> for StrongArmWithReset<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let (vdd, vss) = (io.schematic.vdd, io.schematic.vss);
+        let (flavor, kind, rail, tap_kind, outward) = match self.0.input_kind {
+            InputKind::N => (
+                self.0.pmos_kind,
+                TileKind::P,
+                vdd,
+                TileKind::N,
+                AlignMode::Above,
+            ),
+            InputKind::P => (
+                self.0.nmos_kind,
+                TileKind::N,
+                vss,
+                TileKind::P,
+                AlignMode::Beneath,
+            ),
+        };
+
+        let strongarm = cell.generate_connected(
+            StrongArm::<T>::new(self.0),
+            ClockedDiffComparatorIoSchematic {
+                input: io.schematic.input.clone(),
+                output: io.schematic.output.clone(),
+                clock: io.schematic.clock,
+                vdd,
+                vss,
+            },
+        );
+
+        let pull_params = MosTileParams::new(flavor, kind, self.1.w)
+            .with_length(self.1.l)
+            .snapped(T::snap_width);
+        let mut pulls = [io.schematic.output.n, io.schematic.output.p]
+            .into_iter()
+            .map(|d| {
+                cell.generate_connected(
+                    T::mos(pull_params),
+                    MosIoSchematic {
+                        d,
+                        g: io.schematic.reset,
+                        s: rail,
+                        b: rail,
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut tap = cell.generate(T::tap(TapTileParams::new(tap_kind, 2)));
+        cell.connect(tap.io().x, rail);
+
+        let bounds = strongarm.lcm_bounds();
+        pulls[0].align_rect_mut(bounds, outward, 0);
+        pulls[0].align_rect_mut(bounds, AlignMode::Left, 0);
+        let left = pulls[0].lcm_bounds();
+        pulls[1].align_rect_mut(left, AlignMode::Bottom, 0);
+        pulls[1].align_rect_mut(left, AlignMode::ToTheRight, 0);
+        tap.align_rect_mut(left, outward, 0);
+        tap.align_rect_mut(left, AlignMode::Left, 0);
+
+        let strongarm = cell.draw(strongarm)?;
+        let pulls = pulls
+            .into_iter()
+            .map(|inst| cell.draw(inst))
+            .collect::<Result<Vec<_>>>()?;
+        cell.draw(tap)?;
+
+        cell.set_top_layer(2);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        io.layout.vdd.merge(strongarm.layout.io().vdd);
+        io.layout.vss.merge(strongarm.layout.io().vss);
+        io.layout.clock.merge(strongarm.layout.io().clock);
+        io.layout.input.p.merge(strongarm.layout.io().input.p);
+        io.layout.input.n.merge(strongarm.layout.io().input.n);
+        io.layout.output.p.merge(strongarm.layout.io().output.p);
+        io.layout.output.n.merge(strongarm.layout.io().output.n);
+        for pull in pulls {
+            io.layout.reset.merge(pull.layout.io().g);
+        }
+
         Ok(((), ()))
     }
 }
@@ -567,6 +1271,9 @@ pub trait StrongArmWithOutputBuffersImpl<PDK: Pdk + Schema>:
 {
     /// The spacing between the StrongARM and the buffers in ATOLL grid coordinates.
     const BUFFER_SPACING: i64;
+    /// The maximum height of a serpentine fold used to match the output routes, in
+    /// layer 1 tracks.
+    const MEANDER_MAX_AMPLITUDE: i64 = 4;
 
     /// Additional layout hooks to run after the layout is complete.
     fn post_layout_hooks(_cell: &mut TileBuilder<'_, PDK>) -> Result<()> {
@@ -589,6 +1296,16 @@ impl<T> StrongArmWithOutputBuffers<T> {
     pub const fn new(sa_params: StrongArmParams, buf_params: InverterParams) -> Self {
         Self(sa_params, buf_params, PhantomData)
     }
+
+    /// The StrongARM parameters.
+    pub const fn sa_params(&self) -> StrongArmParams {
+        self.0
+    }
+
+    /// The output buffer parameters.
+    pub const fn buf_params(&self) -> InverterParams {
+        self.1
+    }
 }
 
 impl<T: Any> Block for StrongArmWithOutputBuffers<T> {
@@ -598,9 +1315,8 @@ impl<T: Any> Block for StrongArmWithOutputBuffers<T> {
         substrate::arcstr::literal!("strong_arm_with_output_buffers")
     }
 
-    // todo: include parameters in name
     fn name(&self) -> ArcStr {
-        substrate::arcstr::literal!("strong_arm_with_output_buffers")
+        crate::block_name("strong_arm_with_output_buffers", &(&self.0, &self.1))
     }
 
     fn io(&self) -> Self::Io {
@@ -612,13 +1328,62 @@ impl<T: Any> ExportsNestedData for StrongArmWithOutputBuffers<T> {
     type NestedData = ();
 }
 
+/// The lengths of the routes from the latch outputs to the output buffers, in layout
+/// units.
+///
+/// Lengths include the estimated stubs the router adds between the pins and the drawn
+/// routes.
+pub type OutputRouteLengths = DiffRouteLengths;
+
+/// Layout data returned by the [`StrongArmWithOutputBuffers`] layout generator.
+#[derive(LayoutData)]
+pub struct StrongArmWithOutputBuffersLayoutData {
+    /// The drawn layer 1 routes between the latch outputs and the buffer inputs.
+    pub routes: Vec<Rect>,
+    /// The route lengths after serpentine equalization.
+    pub lengths: OutputRouteLengths,
+}
+
 impl<T: Any> ExportsLayoutData for StrongArmWithOutputBuffers<T> {
-    type LayoutData = ();
+    type LayoutData = StrongArmWithOutputBuffersLayoutData;
+}
+
+/// The length of a route given as a polyline of `(x, track)` points.
+fn polyline_length(points: &[(i64, i64)], pitch: i64) -> i64 {
+    points
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0).abs() + (w[1].1 - w[0].1).abs() * pitch)
+        .sum()
+}
+
+/// The polyline of a route from `near` to `far` on `track`, folded by `serpentine`.
+///
+/// The folds are centered in the route and extend toward higher track indices.
+fn serpentine_polyline(
+    near: i64,
+    far: i64,
+    track: i64,
+    pitch: i64,
+    serpentine: Serpentine,
+) -> Vec<(i64, i64)> {
+    let dir = (far - near).signum();
+    let footprint = (2 * serpentine.folds - 1).max(0) * pitch;
+    let start = near + dir * ((far - near).abs() - footprint) / 2;
+    let mut points = vec![(near, track)];
+    for i in 0..serpentine.folds {
+        let xa = start + dir * 2 * i * pitch;
+        let xb = xa + dir * pitch;
+        let top = track + serpentine.amplitude;
+        points.extend([(xa, track), (xa, top), (xb, top), (xb, track)]);
+    }
+    points.push((far, track));
+    points
 }
 
 impl<PDK: Pdk + Schema + Sized, T: StrongArmWithOutputBuffersImpl<PDK> + Any> Tile<PDK>
     for StrongArmWithOutputBuffers<T>
 {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
     fn tile<'a>(
         &self,
         io: IoBuilder<'a, Self>,
@@ -671,8 +1436,118 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmWithOutputBuffersImpl<PDK> + Any> Ti
         let right_buf = cell.draw(right_buf)?;
         let left_buf = cell.draw(left_buf)?;
 
+        // Draw each latch output route across the gap to its buffer on a layer 1 track
+        // so that the shorter route can be lengthened with a serpentine. The ends of the
+        // drawn routes are the points of each net closest to its pins, so the router
+        // only adds the stubs between them.
+        let layer = cell.layer_stack.layers[1].clone();
+        let tracks = layer.inner.tracks();
+        let pitch = layer.pitch();
+        let width = tracks.get(0).length();
+        let sa_bbox = strongarm.layout.bbox_rect();
+        let sides = [
+            (
+                out.p,
+                strongarm.layout.io().output.p,
+                sa_bbox.right(),
+                right_buf.layout.bbox_rect().left(),
+                right_buf.layout.io().din.bbox_rect(),
+            ),
+            (
+                out.n,
+                strongarm.layout.io().output.n,
+                sa_bbox.left(),
+                left_buf.layout.bbox_rect().right(),
+                left_buf.layout.io().din.bbox_rect(),
+            ),
+        ]
+        .map(|(net, pin, near, far, din)| {
+            // The latch output is split between the two halves; route from the half
+            // facing the buffer.
+            let pin = pin
+                .shapes()
+                .map(|shape| shape.bbox_rect())
+                .min_by_key(|rect| (rect.center().x - near).abs())
+                .expect("latch output has no pin shapes");
+            let track = tracks.to_track_idx(pin.center().y, RoundingMode::Nearest);
+            let y = tracks.get(track).center();
+            let stubs = (pin.center().x - near).abs()
+                + (pin.center().y - y).abs()
+                + (din.center().x - far).abs()
+                + (din.center().y - y).abs();
+            (net, near, far, track, stubs)
+        });
+
+        let lengths = sides.map(|(_, near, far, _, stubs)| stubs + (far - near).abs());
+        let gap = sides
+            .iter()
+            .map(|&(_, near, far, _, _)| (far - near).abs())
+            .min()
+            .unwrap();
+        let max_folds = ((gap - pitch) / (2 * pitch)).max(0);
+        let short = if lengths[0] <= lengths[1] { 0 } else { 1 };
+        let serpentine = match_length(
+            lengths[short],
+            lengths[1 - short],
+            pitch,
+            max_folds,
+            T::MEANDER_MAX_AMPLITUDE,
+        );
+
+        let mut routes = Vec::new();
+        let mut matched = [0; 2];
+        for (i, &(net, near, far, track, stubs)) in sides.iter().enumerate() {
+            let points = serpentine_polyline(
+                near,
+                far,
+                track,
+                pitch,
+                if i == short {
+                    serpentine
+                } else {
+                    Serpentine::default()
+                },
+            );
+            matched[i] = stubs + polyline_length(&points, pitch);
+            let rects = points
+                .windows(2)
+                .map(|w| {
+                    let ((x0, t0), (x1, t1)) = (w[0], w[1]);
+                    if t0 == t1 {
+                        Rect::from_spans(
+                            Span::new(x0.min(x1) - width / 2, x0.max(x1) + width / 2),
+                            tracks.get(t0),
+                        )
+                    } else {
+                        Rect::from_spans(
+                            Span::from_center_span(x0, width),
+                            Span::new(
+                                tracks.get(t0.min(t1)).start(),
+                                tracks.get(t0.max(t1)).stop(),
+                            ),
+                        )
+                    }
+                })
+                .collect::<Vec<_>>();
+            let bbox = rects
+                .iter()
+                .copied()
+                .reduce(|a, b| a.union(b))
+                .expect("route has at least one segment");
+            if let Some(bounds) = cell.layer_stack.slice(0..2).shrink_to_lcm_units(bbox) {
+                cell.assign_grid_points(Some(net), 1, bounds);
+            }
+            for rect in rects.iter() {
+                cell.layout.draw(Shape::new(layer.id, *rect))?;
+            }
+            routes.extend(rects);
+        }
+
         cell.set_top_layer(2);
-        cell.set_router(GreedyRouter::new());
+        cell.set_router(crate::route::router(
+            cell.ctx(),
+            <T as StrongArmImpl<PDK>>::ROUTER,
+        ));
         cell.set_via_maker(<T as StrongArmImpl<PDK>>::via_maker());
 
         io.layout.vdd.merge(strongarm.layout.io().vdd);
@@ -685,6 +1560,152 @@ impl<PDK: Pdk + Schema + Sized, T: StrongArmWithOutputBuffersImpl<PDK> + Any> Ti
 
         <T as StrongArmWithOutputBuffersImpl<PDK>>::post_layout_hooks(cell)?;
 
+        Ok((
+            (),
+            StrongArmWithOutputBuffersLayoutData {
+                routes,
+                lengths: OutputRouteLengths {
+                    p: matched[0],
+                    n: matched[1],
+                },
+            },
+        ))
+    }
+}
+
+/// A StrongARM latch with a local clock buffer implementation.
+pub trait StrongArmWithClockBufferImpl<PDK: Pdk + Schema>:
+    StrongArmImpl<PDK> + InverterImpl<PDK>
+{
+    /// The spacing between the StrongARM and the clock buffer in ATOLL grid coordinates.
+    const CLOCK_BUFFER_SPACING: i64;
+
+    /// Additional layout hooks to run after the layout is complete.
+    fn post_layout_hooks(_cell: &mut TileBuilder<'_, PDK>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A StrongARM latch whose clock is driven by a local [`Buffer`].
+///
+/// The buffer isolates the tail and precharge gates from the slew of the external clock,
+/// which would otherwise modulate the decision threshold. It is centered beneath the
+/// latch so that the buffered clock reaches both mirrored halves with equal wire length.
+// Layout assumes that PDK layer stack has a vertical layer 0.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct StrongArmWithClockBuffer<T>(
+    StrongArmParams,
+    InverterParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> StrongArmWithClockBuffer<T> {
+    /// Creates a new [`StrongArmWithClockBuffer`].
+    pub const fn new(sa_params: StrongArmParams, clk_buf_params: InverterParams) -> Self {
+        Self(sa_params, clk_buf_params, PhantomData)
+    }
+
+    /// The StrongARM parameters.
+    pub const fn sa_params(&self) -> StrongArmParams {
+        self.0
+    }
+
+    /// The parameters of each inverter in the clock buffer.
+    pub const fn clk_buf_params(&self) -> InverterParams {
+        self.1
+    }
+
+    /// The gate width presented to the clock tree at the clock input.
+    ///
+    /// Only the first inverter of the clock buffer loads the external clock.
+    pub const fn clock_load_width(&self) -> i64 {
+        self.1.nmos_w.value() + self.1.pmos_w.value()
+    }
+}
+
+impl<T: Any> Block for StrongArmWithClockBuffer<T> {
+    type Io = ClockedDiffComparatorIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("strong_arm_with_clock_buffer")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("strong_arm_with_clock_buffer", &(&self.0, &self.1))
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for StrongArmWithClockBuffer<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for StrongArmWithClockBuffer<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: StrongArmWithClockBufferImpl<PDK> + Any> Tile<PDK>
+    for StrongArmWithClockBuffer<T>
+{
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let clock = cell.signal("clock_buf", Signal::new());
+
+        let strongarm = cell.generate_connected(
+            StrongArm::<T>::new(self.0),
+            ClockedDiffComparatorIoSchematic {
+                input: io.schematic.input.clone(),
+                output: io.schematic.output.clone(),
+                clock,
+                vdd: io.schematic.vdd,
+                vss: io.schematic.vss,
+            },
+        );
+
+        let clk_buf = cell
+            .generate_connected(
+                Buffer::<T>::new(self.1),
+                BufferIoSchematic {
+                    din: io.schematic.clock,
+                    dout: clock,
+                    vdd: io.schematic.vdd,
+                    vss: io.schematic.vss,
+                },
+            )
+            .align(&strongarm, AlignMode::CenterHorizontal, 0)
+            .align(&strongarm, AlignMode::Beneath, -T::CLOCK_BUFFER_SPACING);
+
+        let strongarm = cell.draw(strongarm)?;
+        let clk_buf = cell.draw(clk_buf)?;
+
+        cell.set_top_layer(2);
+        cell.set_router(crate::route::router(
+            cell.ctx(),
+            <T as StrongArmImpl<PDK>>::ROUTER,
+        ));
+        cell.set_via_maker(<T as StrongArmImpl<PDK>>::via_maker());
+
+        io.layout.vdd.merge(strongarm.layout.io().vdd);
+        io.layout.vss.merge(strongarm.layout.io().vss);
+        io.layout.clock.merge(clk_buf.layout.io().din);
+        io.layout.input.p.merge(strongarm.layout.io().input.p);
+        io.layout.input.n.merge(strongarm.layout.io().input.n);
+        io.layout.output.p.merge(strongarm.layout.io().output.p);
+        io.layout.output.n.merge(strongarm.layout.io().output.n);
+
+        <T as StrongArmWithClockBufferImpl<PDK>>::post_layout_hooks(cell)?;
+
         Ok(((), ()))
     }
 }
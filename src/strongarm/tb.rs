@@ -1,6 +1,7 @@
 //! StrongARM testbenches.
 
 use approx::abs_diff_eq;
+use ngspice::Ngspice;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
@@ -9,31 +10,48 @@ use serde::{Deserialize, Serialize};
 use spectre::analysis::tran::Tran;
 use spectre::blocks::{Pulse, Vsource};
 use spectre::{ErrPreset, Spectre};
+use spice::Spice;
 use std::any::Any;
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::Hash;
 use std::marker::PhantomData;
+use std::path::Path;
 use substrate::arcstr;
 use substrate::arcstr::ArcStr;
 use substrate::block::Block;
-use substrate::io::schematic::{Bundle, HardwareType, Node};
-use substrate::io::{DiffPair, Signal, TestbenchIo};
+use substrate::context::PdkContext;
+use substrate::io::schematic::{Bundle, HardwareType, NestedNode, Node};
+use substrate::io::{DiffPair, Signal, TestbenchIo, TwoTerminalIo, TwoTerminalIoSchematic};
 use substrate::pdk::corner::Pvt;
+use substrate::pdk::Pdk;
 use substrate::schematic::schema::Schema;
 use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
 use substrate::scir::schema::FromSchema;
 use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
 use substrate::simulation::options::{SimOption, Temperature};
 use substrate::simulation::waveform::{EdgeDir, TimeWaveform, WaveformRef};
-use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+use substrate::simulation::{Analysis, SimController, SimulationContext, Simulator, Testbench};
+#[cfg(feature = "xyce")]
+use xyce::Xyce;
 
-use crate::strongarm::ClockedDiffComparatorIo;
+use crate::report::SimArtifact;
+use crate::strongarm::{ClockedDiffComparatorIo, ResettableComparatorIo};
+use crate::sweep::McSample;
+use crate::tb::probe::{ProbeError, ProbePoints, ProbeRequest, ProbeSet};
+use crate::tb::{
+    SimNoiseOptions, StatisticalSimulator, SupplyNoise, SupplySensitivity, SupplySensitivityPoint,
+    UnsupportedAnalysisError,
+};
+use crate::verification::pex::{ExtractedView, PexOutput};
 
 /// A transient testbench that provides a differential input voltage and
 /// measures the output waveform.
+///
+/// The testbench runs on the simulator `S`, which defaults to [`Spectre`] and may be any
+/// [`StrongArmTranSimulator`].
 #[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq; T, C)]
 #[derive(Serialize, Deserialize)]
-pub struct StrongArmTranTb<T, PDK, C> {
+pub struct StrongArmTranTb<T, PDK, C, S = Spectre> {
     /// The device-under-test.
     pub dut: T,
 
@@ -53,8 +71,24 @@ pub struct StrongArmTranTb<T, PDK, C> {
     /// The PVT corner.
     pub pvt: Pvt<C>,
 
+    /// The Monte Carlo sample to simulate, if any.
+    ///
+    /// Only supported by [statistical simulators](StatisticalSimulator).
+    mc: Option<McSample>,
+
+    /// Transient noise options.
+    ///
+    /// Only supported by [statistical simulators](StatisticalSimulator).
+    noise: SimNoiseOptions,
+
+    /// The probe points of the DUT to save, in addition to the testbench nodes.
+    ///
+    /// The waveforms are written to the simulator output in the simulation directory.
+    #[serde(default)]
+    pub probes: ProbeSet,
+
     #[serde(bound(deserialize = ""))]
-    phantom: PhantomData<fn() -> PDK>,
+    phantom: PhantomData<fn() -> (PDK, S)>,
 }
 
 impl<T, PDK, C> StrongArmTranTb<T, PDK, C> {
@@ -66,6 +100,86 @@ impl<T, PDK, C> StrongArmTranTb<T, PDK, C> {
             vinn,
             pvt,
             inverted_clk,
+            mc: None,
+            noise: SimNoiseOptions::default(),
+            probes: ProbeSet::default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, PDK, C, S: StatisticalSimulator> StrongArmTranTb<T, PDK, C, S> {
+    /// Simulates the given Monte Carlo sample instead of the nominal design.
+    pub fn with_mc_sample(mut self, sample: McSample) -> Self {
+        self.mc = Some(sample);
+        self
+    }
+
+    /// Sets the transient noise options.
+    pub fn with_noise(mut self, noise: SimNoiseOptions) -> Self {
+        self.noise = noise;
+        self
+    }
+}
+
+impl<T: ProbePoints, PDK, C, S> StrongArmTranTb<T, PDK, C, S> {
+    /// Saves the probe points of the DUT named in `probes`.
+    ///
+    /// Fails if the DUT has no probe point with one of the requested names.
+    pub fn with_probes(mut self, probes: &ProbeRequest) -> Result<Self, ProbeError> {
+        self.probes = probes.resolve::<T>()?;
+        Ok(self)
+    }
+}
+
+impl<T, PDK, C, S> StrongArmTranTb<T, PDK, C, S> {
+    /// Runs this testbench on the simulator `S2` instead.
+    ///
+    /// Fails if the testbench simulates a Monte Carlo sample or enables transient noise
+    /// and `S2` does not support them.
+    pub fn with_simulator<S2: StrongArmTranSimulator>(
+        self,
+    ) -> Result<StrongArmTranTb<T, PDK, C, S2>, UnsupportedAnalysisError> {
+        if !S2::SUPPORTS_MC_AND_NOISE {
+            if self.mc.is_some() {
+                return Err(UnsupportedAnalysisError::MonteCarlo);
+            }
+            if self.noise.enable {
+                return Err(UnsupportedAnalysisError::TransientNoise);
+            }
+        }
+        Ok(StrongArmTranTb {
+            dut: self.dut,
+            vinp: self.vinp,
+            vinn: self.vinn,
+            inverted_clk: self.inverted_clk,
+            pvt: self.pvt,
+            mc: self.mc,
+            noise: self.noise,
+            probes: self.probes,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Simulates the [`ExtractedView`] of the DUT instead of its schematic.
+    ///
+    /// `pex` must be an extraction of the DUT whose subcircuit has the given `ports`, in
+    /// the order of the DUT's IO. See [`ExtractedView::new`]. The extracted view has no
+    /// probe points, so any saved probes are dropped.
+    pub fn with_extracted_dut(
+        self,
+        pex: PexOutput,
+        ports: Vec<ArcStr>,
+    ) -> StrongArmTranTb<ExtractedView<T>, Spice, C, S> {
+        StrongArmTranTb {
+            dut: ExtractedView::new(self.dut, pex, ports),
+            vinp: self.vinp,
+            vinn: self.vinn,
+            inverted_clk: self.inverted_clk,
+            pvt: self.pvt,
+            mc: self.mc,
+            noise: self.noise,
+            probes: ProbeSet::default(),
             phantom: PhantomData,
         }
     }
@@ -85,7 +199,8 @@ impl<
             + Send
             + Sync
             + Any,
-    > Block for StrongArmTranTb<T, PDK, C>
+        S: Any,
+    > Block for StrongArmTranTb<T, PDK, C, S>
 {
     type Io = TestbenchIo;
 
@@ -110,80 +225,194 @@ pub struct StrongArmTranTbNodes {
     vinn: Node,
     vinp: Node,
     clk: Node,
+    probes: Vec<NestedNode>,
 }
 
-impl<T, PDK, C> ExportsNestedData for StrongArmTranTb<T, PDK, C>
+impl<T, PDK, C, S> ExportsNestedData for StrongArmTranTb<T, PDK, C, S>
 where
-    StrongArmTranTb<T, PDK, C>: Block,
+    StrongArmTranTb<T, PDK, C, S>: Block,
 {
     type NestedData = StrongArmTranTbNodes;
 }
 
-impl<T: Block<Io = ClockedDiffComparatorIo> + Schematic<PDK> + Clone, PDK: Schema, C>
-    Schematic<Spectre> for StrongArmTranTb<T, PDK, C>
+/// Simulator-specific sources driving a StrongARM testbench.
+struct TbSources<V> {
+    vinp: V,
+    vinn: V,
+    vdd: V,
+    clk: V,
+}
+
+/// Instantiates `dut` and connects it to the given testbench sources.
+fn connect_dut<T, PDK, S, V>(
+    cell: &mut CellBuilder<S>,
+    vss: Node,
+    dut: T,
+    probes: ProbeSet,
+    sources: TbSources<V>,
+) -> StrongArmTranTbNodes
 where
-    StrongArmTranTb<T, PDK, C>: Block<Io = TestbenchIo>,
-    Spectre: FromSchema<PDK>,
+    T: Block<Io = ClockedDiffComparatorIo> + Schematic<PDK> + ProbePoints,
+    PDK: Schema,
+    S: Schema + FromSchema<PDK>,
+    V: Block<Io = TwoTerminalIo> + Schematic<S>,
+{
+    let dut = cell.sub_builder::<PDK>().instantiate(dut);
+    let probes = probes
+        .indices()
+        .map(|i| dut.data().nodes[i].clone())
+        .collect();
+
+    let vinp = cell.signal("vinp", Signal);
+    let vinn = cell.signal("vinn", Signal);
+    let vdd = cell.signal("vdd", Signal);
+    let clk = cell.signal("clk", Signal);
+
+    for (source, p) in [
+        (sources.vinp, vinp),
+        (sources.vinn, vinn),
+        (sources.vdd, vdd),
+        (sources.clk, clk),
+    ] {
+        cell.instantiate_connected(source, TwoTerminalIoSchematic { p, n: vss });
+    }
+
+    let output = cell.signal("output", DiffPair::default());
+
+    cell.connect(
+        Bundle::<ClockedDiffComparatorIo> {
+            input: Bundle::<DiffPair> { p: vinp, n: vinn },
+            output: output.clone(),
+            clock: clk,
+            vdd,
+            vss,
+        },
+        dut.io(),
+    );
+
+    StrongArmTranTbNodes {
+        vop: output.p,
+        von: output.n,
+        vinn,
+        vinp,
+        clk,
+        probes,
+    }
+}
+
+/// A simulator that can run a [`StrongArmTranTb`].
+///
+/// The testbench schematic and saved waveforms are shared by all simulators, so
+/// implementations only provide the simulator's voltage sources and transient analysis.
+pub trait StrongArmTranSimulator: Simulator + Schema + Sized {
+    /// A voltage source.
+    type Vsource: Block<Io = TwoTerminalIo> + Schematic<Self>;
+    /// The transient analysis.
+    type Tran: Analysis;
+    /// Whether the simulator supports Monte Carlo samples and transient noise.
+    const SUPPORTS_MC_AND_NOISE: bool = false;
+
+    /// Returns a DC voltage source.
+    fn dc(value: Decimal) -> Self::Vsource;
+    /// Returns a pulse voltage source.
+    fn pulse(pulse: Pulse) -> Self::Vsource;
+}
+
+impl StrongArmTranSimulator for Spectre {
+    type Vsource = Vsource;
+    type Tran = Tran;
+    const SUPPORTS_MC_AND_NOISE: bool = true;
+
+    fn dc(value: Decimal) -> Self::Vsource {
+        Vsource::dc(value)
+    }
+    fn pulse(pulse: Pulse) -> Self::Vsource {
+        Vsource::pulse(pulse)
+    }
+}
+
+impl StrongArmTranSimulator for Ngspice {
+    type Vsource = ngspice::blocks::Vsource;
+    type Tran = ngspice::tran::Tran;
+
+    fn dc(value: Decimal) -> Self::Vsource {
+        ngspice::blocks::Vsource::dc(value)
+    }
+    fn pulse(pulse: Pulse) -> Self::Vsource {
+        ngspice::blocks::Vsource::pulse(ngspice::blocks::Pulse {
+            val0: pulse.val0,
+            val1: pulse.val1,
+            period: pulse.period,
+            width: pulse.width,
+            delay: pulse.delay,
+            rise: pulse.rise,
+            fall: pulse.fall,
+            num_pulses: None,
+        })
+    }
+}
+
+#[cfg(feature = "xyce")]
+impl StrongArmTranSimulator for Xyce {
+    type Vsource = xyce::blocks::Vsource;
+    type Tran = xyce::tran::Tran;
+
+    fn dc(value: Decimal) -> Self::Vsource {
+        xyce::blocks::Vsource::dc(value)
+    }
+    fn pulse(pulse: Pulse) -> Self::Vsource {
+        xyce::blocks::Vsource::pulse(xyce::blocks::Pulse {
+            val0: pulse.val0,
+            val1: pulse.val1,
+            period: pulse.period,
+            width: pulse.width,
+            delay: pulse.delay,
+            rise: pulse.rise,
+            fall: pulse.fall,
+            num_pulses: None,
+        })
+    }
+}
+
+impl<T, PDK, C, S> Schematic<S> for StrongArmTranTb<T, PDK, C, S>
+where
+    T: Block<Io = ClockedDiffComparatorIo> + Schematic<PDK> + ProbePoints + Clone,
+    PDK: Schema,
+    S: StrongArmTranSimulator + FromSchema<PDK>,
+    StrongArmTranTb<T, PDK, C, S>: Block<Io = TestbenchIo>,
 {
     fn schematic(
         &self,
         io: &<<Self as Block>::Io as HardwareType>::Bundle,
-        cell: &mut CellBuilder<Spectre>,
+        cell: &mut CellBuilder<S>,
     ) -> substrate::error::Result<Self::NestedData> {
-        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
-
-        let vinp = cell.signal("vinp", Signal);
-        let vinn = cell.signal("vinn", Signal);
-        let vdd = cell.signal("vdd", Signal);
-        let clk = cell.signal("clk", Signal);
-
-        let vvinp = cell.instantiate(Vsource::dc(self.vinp));
-        let vvinn = cell.instantiate(Vsource::dc(self.vinn));
-        let vvdd = cell.instantiate(Vsource::dc(self.pvt.voltage));
         let (val0, val1) = if self.inverted_clk {
             (self.pvt.voltage, dec!(0))
         } else {
             (dec!(0), self.pvt.voltage)
         };
-        let vclk = cell.instantiate(Vsource::pulse(Pulse {
-            val0,
-            val1,
-            period: Some(dec!(1000)),
-            width: Some(dec!(100)),
-            delay: Some(dec!(10e-9)),
-            rise: Some(dec!(100e-12)),
-            fall: Some(dec!(100e-12)),
-        }));
-
-        cell.connect(io.vss, vvinp.io().n);
-        cell.connect(io.vss, vvinn.io().n);
-        cell.connect(io.vss, vvdd.io().n);
-        cell.connect(io.vss, vclk.io().n);
-        cell.connect(vinp, vvinp.io().p);
-        cell.connect(vinn, vvinn.io().p);
-        cell.connect(vdd, vvdd.io().p);
-        cell.connect(clk, vclk.io().p);
-
-        let output = cell.signal("output", DiffPair::default());
-
-        cell.connect(
-            Bundle::<ClockedDiffComparatorIo> {
-                input: Bundle::<DiffPair> { p: vinp, n: vinn },
-                output: output.clone(),
-                clock: clk,
-                vdd,
-                vss: io.vss,
-            },
-            dut.io(),
-        );
+        let sources = TbSources {
+            vinp: S::dc(self.vinp),
+            vinn: S::dc(self.vinn),
+            vdd: S::dc(self.pvt.voltage),
+            clk: S::pulse(Pulse {
+                val0,
+                val1,
+                period: Some(dec!(1000)),
+                width: Some(dec!(100)),
+                delay: Some(dec!(10e-9)),
+                rise: Some(dec!(100e-12)),
+                fall: Some(dec!(100e-12)),
+            }),
+        };
 
-        Ok(StrongArmTranTbNodes {
-            vop: output.p,
-            von: output.n,
-            vinn,
-            vinp,
-            clk,
-        })
+        Ok(connect_dut(
+            cell,
+            io.vss,
+            self.dut.clone(),
+            self.probes,
+            sources,
+        ))
     }
 }
 
@@ -196,6 +425,24 @@ pub struct ComparatorSim {
     vinn: tran::Voltage,
     vinp: tran::Voltage,
     clk: tran::Voltage,
+    probes: Vec<tran::Voltage>,
+}
+
+impl ComparatorSim {
+    /// Returns the final decision of the comparator,
+    /// or [`None`] if the outputs did not rail to `vdd` and ground.
+    fn final_decision(&self, vdd: f64) -> Option<ComparatorDecision> {
+        let von = *self.von.last().unwrap();
+        let vop = *self.vop.last().unwrap();
+
+        if abs_diff_eq!(von, 0.0, epsilon = 1e-4) && abs_diff_eq!(vop, vdd, epsilon = 1e-4) {
+            Some(ComparatorDecision::Pos)
+        } else if abs_diff_eq!(von, vdd, epsilon = 1e-4) && abs_diff_eq!(vop, 0.0, epsilon = 1e-4) {
+            Some(ComparatorDecision::Neg)
+        } else {
+            None
+        }
+    }
 }
 
 /// The decision made by a comparator.
@@ -211,15 +458,18 @@ pub enum ComparatorDecision {
     Pos,
 }
 
-impl<T, PDK, C> SaveTb<Spectre, Tran, ComparatorSim> for StrongArmTranTb<T, PDK, C>
+impl<T, PDK, C, S> SaveTb<S, S::Tran, ComparatorSim> for StrongArmTranTb<T, PDK, C, S>
 where
-    StrongArmTranTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    S: StrongArmTranSimulator,
+    StrongArmTranTb<T, PDK, C, S>: Block<Io = TestbenchIo>,
+    tran::Time: Save<S, S::Tran, ()>,
+    tran::Voltage: Save<S, S::Tran, NestedNode> + for<'a> Save<S, S::Tran, &'a NestedNode>,
 {
     fn save_tb(
-        ctx: &SimulationContext<Spectre>,
+        ctx: &SimulationContext<S>,
         cell: &Cell<Self>,
-        opts: &mut <Spectre as Simulator>::Options,
-    ) -> <ComparatorSim as FromSaved<Spectre, Tran>>::SavedKey {
+        opts: &mut <S as Simulator>::Options,
+    ) -> <ComparatorSim as FromSaved<S, S::Tran>>::SavedKey {
         ComparatorSimSavedKey {
             t: tran::Time::save(ctx, (), opts),
             vop: tran::Voltage::save(ctx, cell.data().vop, opts),
@@ -227,6 +477,12 @@ where
             vinn: tran::Voltage::save(ctx, cell.data().vinn, opts),
             vinp: tran::Voltage::save(ctx, cell.data().vinp, opts),
             clk: tran::Voltage::save(ctx, cell.data().clk, opts),
+            probes: cell
+                .data()
+                .probes
+                .iter()
+                .map(|probe| tran::Voltage::save(ctx, probe, opts))
+                .collect(),
         }
     }
 }
@@ -241,29 +497,75 @@ where
         let mut opts = spectre::Options::default();
         sim.set_option(self.pvt.corner, &mut opts);
         sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let tran = self.noise.apply(Tran {
+            stop: dec!(30e-9),
+            start: None,
+            errpreset: Some(ErrPreset::Conservative),
+            ..Default::default()
+        });
+        let wav: ComparatorSim = match self.mc {
+            Some(mc) => sim
+                .simulate(opts, mc.analysis(tran))
+                .expect("failed to run simulation")
+                .into_iter()
+                .next()
+                .expect("Monte Carlo simulation produced no samples"),
+            None => sim.simulate(opts, tran).expect("failed to run simulation"),
+        };
+
+        wav.final_decision(self.pvt.voltage.to_f64().unwrap())
+    }
+}
+
+impl<T, PDK, C: SimOption<Ngspice> + Copy> Testbench<Ngspice>
+    for StrongArmTranTb<T, PDK, C, Ngspice>
+where
+    StrongArmTranTb<T, PDK, C, Ngspice>: Block<Io = TestbenchIo> + Schematic<Ngspice>,
+{
+    type Output = Option<ComparatorDecision>;
+
+    fn run(&self, sim: SimController<Ngspice, Self>) -> Self::Output {
+        let mut opts = ngspice::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
         let wav: ComparatorSim = sim
             .simulate(
                 opts,
-                Tran {
+                ngspice::tran::Tran {
+                    step: dec!(10e-12),
                     stop: dec!(30e-9),
                     start: None,
-                    errpreset: Some(ErrPreset::Conservative),
-                    ..Default::default()
                 },
             )
             .expect("failed to run simulation");
 
-        let von = *wav.von.last().unwrap();
-        let vop = *wav.vop.last().unwrap();
+        wav.final_decision(self.pvt.voltage.to_f64().unwrap())
+    }
+}
 
-        let vdd = self.pvt.voltage.to_f64().unwrap();
-        if abs_diff_eq!(von, 0.0, epsilon = 1e-4) && abs_diff_eq!(vop, vdd, epsilon = 1e-4) {
-            Some(ComparatorDecision::Pos)
-        } else if abs_diff_eq!(von, vdd, epsilon = 1e-4) && abs_diff_eq!(vop, 0.0, epsilon = 1e-4) {
-            Some(ComparatorDecision::Neg)
-        } else {
-            None
-        }
+#[cfg(feature = "xyce")]
+impl<T, PDK, C: SimOption<Xyce> + Copy> Testbench<Xyce> for StrongArmTranTb<T, PDK, C, Xyce>
+where
+    StrongArmTranTb<T, PDK, C, Xyce>: Block<Io = TestbenchIo> + Schematic<Xyce>,
+{
+    type Output = Option<ComparatorDecision>;
+
+    fn run(&self, sim: SimController<Xyce, Self>) -> Self::Output {
+        let mut opts = xyce::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: ComparatorSim = sim
+            .simulate(
+                opts,
+                xyce::tran::Tran {
+                    step: dec!(10e-12),
+                    stop: dec!(30e-9),
+                    start: None,
+                },
+            )
+            .expect("failed to run simulation");
+
+        wav.final_decision(self.pvt.voltage.to_f64().unwrap())
     }
 }
 
@@ -306,19 +608,28 @@ pub struct StrongArmHighSpeedTbParams<T, C> {
 
     /// The PVT corner.
     pub pvt: Pvt<C>,
+
+    /// The probe points of the DUT to save, in addition to the testbench nodes.
+    ///
+    /// The waveforms are written to the simulator output in the simulation directory.
+    #[serde(default)]
+    pub probes: ProbeSet,
 }
 
 /// A high speed StrongARM testbench.
 ///
 /// Applies an alternating sequence of 0s and 1s,
 /// and checks that the output rails correctly.
+///
+/// The testbench runs on the simulator `S`, which defaults to [`Spectre`].
 #[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq; T, C)]
 #[derive(Serialize, Deserialize)]
-pub struct StrongArmHighSpeedTb<T, PDK, C> {
+pub struct StrongArmHighSpeedTb<T, PDK, C, S = Spectre> {
     params: StrongArmHighSpeedTbParams<T, C>,
+    noise: SimNoiseOptions,
 
     #[serde(bound(deserialize = ""))]
-    phantom: PhantomData<fn() -> PDK>,
+    phantom: PhantomData<fn() -> (PDK, S)>,
 }
 
 impl<T, PDK, C> StrongArmHighSpeedTb<T, PDK, C> {
@@ -326,11 +637,38 @@ impl<T, PDK, C> StrongArmHighSpeedTb<T, PDK, C> {
     pub fn new(params: StrongArmHighSpeedTbParams<T, C>) -> Self {
         Self {
             params,
+            noise: SimNoiseOptions::default(),
             phantom: PhantomData,
         }
     }
 }
 
+impl<T, PDK, C, S: StatisticalSimulator> StrongArmHighSpeedTb<T, PDK, C, S> {
+    /// Sets the transient noise options.
+    pub fn with_noise(mut self, noise: SimNoiseOptions) -> Self {
+        self.noise = noise;
+        self
+    }
+}
+
+impl<T, PDK, C, S> StrongArmHighSpeedTb<T, PDK, C, S> {
+    /// Runs this testbench on the simulator `S2` instead.
+    ///
+    /// Fails if the testbench enables transient noise and `S2` does not support it.
+    pub fn with_simulator<S2: StrongArmTranSimulator>(
+        self,
+    ) -> Result<StrongArmHighSpeedTb<T, PDK, C, S2>, UnsupportedAnalysisError> {
+        if self.noise.enable && !S2::SUPPORTS_MC_AND_NOISE {
+            return Err(UnsupportedAnalysisError::TransientNoise);
+        }
+        Ok(StrongArmHighSpeedTb {
+            params: self.params,
+            noise: self.noise,
+            phantom: PhantomData,
+        })
+    }
+}
+
 impl<
         T: Block,
         PDK: Any,
@@ -345,7 +683,8 @@ impl<
             + Send
             + Sync
             + Any,
-    > Block for StrongArmHighSpeedTb<T, PDK, C>
+        S: Any,
+    > Block for StrongArmHighSpeedTb<T, PDK, C, S>
 {
     type Io = TestbenchIo;
 
@@ -362,15 +701,60 @@ impl<
     }
 }
 
-impl<T, PDK, C> ExportsNestedData for StrongArmHighSpeedTb<T, PDK, C>
+impl<T, PDK, C, S> ExportsNestedData for StrongArmHighSpeedTb<T, PDK, C, S>
 where
-    StrongArmHighSpeedTb<T, PDK, C>: Block,
+    StrongArmHighSpeedTb<T, PDK, C, S>: Block,
 {
     type NestedData = StrongArmTranTbNodes;
 }
 
-impl<T: Block<Io = ClockedDiffComparatorIo> + Schematic<PDK> + Clone, PDK: Schema, C>
-    Schematic<Spectre> for StrongArmHighSpeedTb<T, PDK, C>
+/// Creates the Spectre sources of a [`StrongArmHighSpeedTb`], powering the DUT from `vdd`.
+fn spectre_high_speed_sources<T, C>(
+    params: &StrongArmHighSpeedTbParams<T, C>,
+    vdd: Vsource,
+) -> TbSources<Vsource> {
+    let (val0, val1) = if params.inverted_clk {
+        (params.pvt.voltage, dec!(0))
+    } else {
+        (dec!(0), params.pvt.voltage)
+    };
+    TbSources {
+        vinp: Vsource::pulse(Pulse {
+            val0: params.v0.0,
+            val1: params.v1.0,
+            period: Some(params.period * dec!(2)),
+            rise: Some(params.tr),
+            fall: Some(params.tf),
+            width: None,
+            delay: None,
+        }),
+        vinn: Vsource::pulse(Pulse {
+            val0: params.v0.1,
+            val1: params.v1.1,
+            period: Some(params.period * dec!(2)),
+            rise: Some(params.tr),
+            fall: Some(params.tf),
+            width: None,
+            delay: None,
+        }),
+        vdd,
+        clk: Vsource::pulse(Pulse {
+            val0,
+            val1,
+            period: Some(params.period),
+            width: None,
+            delay: Some(params.period / dec!(2)),
+            rise: Some(params.tr),
+            fall: Some(params.tf),
+        }),
+    }
+}
+
+impl<
+        T: Block<Io = ClockedDiffComparatorIo> + Schematic<PDK> + ProbePoints + Clone,
+        PDK: Schema,
+        C,
+    > Schematic<Spectre> for StrongArmHighSpeedTb<T, PDK, C>
 where
     StrongArmHighSpeedTb<T, PDK, C>: Block<Io = TestbenchIo>,
     Spectre: FromSchema<PDK>,
@@ -380,79 +764,133 @@ where
         io: &<<Self as Block>::Io as HardwareType>::Bundle,
         cell: &mut CellBuilder<Spectre>,
     ) -> substrate::error::Result<Self::NestedData> {
-        let dut = cell
-            .sub_builder::<PDK>()
-            .instantiate(self.params.dut.clone());
-
-        let vinp = cell.signal("vinp", Signal);
-        let vinn = cell.signal("vinn", Signal);
-        let vdd = cell.signal("vdd", Signal);
-        let clk = cell.signal("clk", Signal);
+        let sources =
+            spectre_high_speed_sources(&self.params, Vsource::dc(self.params.pvt.voltage));
 
-        let vvinp = cell.instantiate(Vsource::pulse(Pulse {
-            val0: self.params.v0.0,
-            val1: self.params.v1.0,
-            period: Some(self.params.period * dec!(2)),
-            rise: Some(self.params.tr),
-            fall: Some(self.params.tf),
-            width: None,
-            delay: None,
-        }));
-        let vvinn = cell.instantiate(Vsource::pulse(Pulse {
-            val0: self.params.v0.1,
-            val1: self.params.v1.1,
-            period: Some(self.params.period * dec!(2)),
-            rise: Some(self.params.tr),
-            fall: Some(self.params.tf),
-            width: None,
-            delay: None,
-        }));
+        Ok(connect_dut(
+            cell,
+            io.vss,
+            self.params.dut.clone(),
+            self.params.probes,
+            sources,
+        ))
+    }
+}
 
-        let vvdd = cell.instantiate(Vsource::dc(self.params.pvt.voltage));
+impl<
+        T: Block<Io = ClockedDiffComparatorIo> + Schematic<PDK> + ProbePoints + Clone,
+        PDK: Schema,
+        C,
+    > Schematic<Ngspice> for StrongArmHighSpeedTb<T, PDK, C, Ngspice>
+where
+    StrongArmHighSpeedTb<T, PDK, C, Ngspice>: Block<Io = TestbenchIo>,
+    Ngspice: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Ngspice>,
+    ) -> substrate::error::Result<Self::NestedData> {
         let (val0, val1) = if self.params.inverted_clk {
             (self.params.pvt.voltage, dec!(0))
         } else {
             (dec!(0), self.params.pvt.voltage)
         };
-        let vclk = cell.instantiate(Vsource::pulse(Pulse {
-            val0,
-            val1,
-            period: Some(self.params.period),
-            width: None,
-            delay: Some(self.params.period / dec!(2)),
-            rise: Some(self.params.tr),
-            fall: Some(self.params.tf),
-        }));
-
-        cell.connect(io.vss, vvinp.io().n);
-        cell.connect(io.vss, vvinn.io().n);
-        cell.connect(io.vss, vvdd.io().n);
-        cell.connect(io.vss, vclk.io().n);
-        cell.connect(vinp, vvinp.io().p);
-        cell.connect(vinn, vvinn.io().p);
-        cell.connect(vdd, vvdd.io().p);
-        cell.connect(clk, vclk.io().p);
+        // Unlike Spectre, ngspice does not default the pulse width to half the period.
+        let input_pulse = |val0, val1| {
+            ngspice::blocks::Vsource::pulse(ngspice::blocks::Pulse {
+                val0,
+                val1,
+                period: Some(self.params.period * dec!(2)),
+                rise: Some(self.params.tr),
+                fall: Some(self.params.tf),
+                width: Some(self.params.period - self.params.tr),
+                delay: None,
+                num_pulses: None,
+            })
+        };
+        let sources = TbSources {
+            vinp: input_pulse(self.params.v0.0, self.params.v1.0),
+            vinn: input_pulse(self.params.v0.1, self.params.v1.1),
+            vdd: ngspice::blocks::Vsource::dc(self.params.pvt.voltage),
+            clk: ngspice::blocks::Vsource::pulse(ngspice::blocks::Pulse {
+                val0,
+                val1,
+                period: Some(self.params.period),
+                width: Some(self.params.period / dec!(2) - self.params.tr),
+                delay: Some(self.params.period / dec!(2)),
+                rise: Some(self.params.tr),
+                fall: Some(self.params.tf),
+                num_pulses: None,
+            }),
+        };
 
-        let output = cell.signal("output", DiffPair::default());
+        Ok(connect_dut(
+            cell,
+            io.vss,
+            self.params.dut.clone(),
+            self.params.probes,
+            sources,
+        ))
+    }
+}
 
-        cell.connect(
-            Bundle::<ClockedDiffComparatorIo> {
-                input: Bundle::<DiffPair> { p: vinp, n: vinn },
-                output: output.clone(),
-                clock: clk,
-                vdd,
-                vss: io.vss,
-            },
-            dut.io(),
-        );
+#[cfg(feature = "xyce")]
+impl<
+        T: Block<Io = ClockedDiffComparatorIo> + Schematic<PDK> + ProbePoints + Clone,
+        PDK: Schema,
+        C,
+    > Schematic<Xyce> for StrongArmHighSpeedTb<T, PDK, C, Xyce>
+where
+    StrongArmHighSpeedTb<T, PDK, C, Xyce>: Block<Io = TestbenchIo>,
+    Xyce: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Xyce>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let (val0, val1) = if self.params.inverted_clk {
+            (self.params.pvt.voltage, dec!(0))
+        } else {
+            (dec!(0), self.params.pvt.voltage)
+        };
+        // Like ngspice, Xyce does not default the pulse width to half the period.
+        let input_pulse = |val0, val1| {
+            xyce::blocks::Vsource::pulse(xyce::blocks::Pulse {
+                val0,
+                val1,
+                period: Some(self.params.period * dec!(2)),
+                rise: Some(self.params.tr),
+                fall: Some(self.params.tf),
+                width: Some(self.params.period - self.params.tr),
+                delay: None,
+                num_pulses: None,
+            })
+        };
+        let sources = TbSources {
+            vinp: input_pulse(self.params.v0.0, self.params.v1.0),
+            vinn: input_pulse(self.params.v0.1, self.params.v1.1),
+            vdd: xyce::blocks::Vsource::dc(self.params.pvt.voltage),
+            clk: xyce::blocks::Vsource::pulse(xyce::blocks::Pulse {
+                val0,
+                val1,
+                period: Some(self.params.period),
+                width: Some(self.params.period / dec!(2) - self.params.tr),
+                delay: Some(self.params.period / dec!(2)),
+                rise: Some(self.params.tr),
+                fall: Some(self.params.tf),
+                num_pulses: None,
+            }),
+        };
 
-        Ok(StrongArmTranTbNodes {
-            vop: output.p,
-            von: output.n,
-            vinn,
-            vinp,
-            clk,
-        })
+        Ok(connect_dut(
+            cell,
+            io.vss,
+            self.params.dut.clone(),
+            self.params.probes,
+            sources,
+        ))
     }
 }
 
@@ -472,75 +910,230 @@ where
             vinn: tran::Voltage::save(ctx, cell.data().vinn, opts),
             vinp: tran::Voltage::save(ctx, cell.data().vinp, opts),
             clk: tran::Voltage::save(ctx, cell.data().clk, opts),
+            probes: cell
+                .data()
+                .probes
+                .iter()
+                .map(|probe| tran::Voltage::save(ctx, probe, opts))
+                .collect(),
         }
     }
 }
 
-/// The output of the [`StrongArmHighSpeedTb`].
-#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
-pub struct StrongArmHighSpeedTbOutput {
-    /// Whether the testbench used an inverted clock.
-    inverted_clk: bool,
-    /// The sequence of decisions made by the comparator.
-    pub decisions: Vec<Option<ComparatorDecision>>,
+impl<T, PDK, C> SaveTb<Ngspice, ngspice::tran::Tran, ComparatorSim>
+    for StrongArmHighSpeedTb<T, PDK, C, Ngspice>
+where
+    StrongArmHighSpeedTb<T, PDK, C, Ngspice>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Ngspice>,
+        cell: &Cell<Self>,
+        opts: &mut <Ngspice as Simulator>::Options,
+    ) -> <ComparatorSim as FromSaved<Ngspice, ngspice::tran::Tran>>::SavedKey {
+        ComparatorSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            vop: tran::Voltage::save(ctx, cell.data().vop, opts),
+            von: tran::Voltage::save(ctx, cell.data().von, opts),
+            vinn: tran::Voltage::save(ctx, cell.data().vinn, opts),
+            vinp: tran::Voltage::save(ctx, cell.data().vinp, opts),
+            clk: tran::Voltage::save(ctx, cell.data().clk, opts),
+            probes: cell
+                .data()
+                .probes
+                .iter()
+                .map(|probe| tran::Voltage::save(ctx, probe, opts))
+                .collect(),
+        }
+    }
 }
 
-impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for StrongArmHighSpeedTb<T, PDK, C>
+#[cfg(feature = "xyce")]
+impl<T, PDK, C> SaveTb<Xyce, xyce::tran::Tran, ComparatorSim>
+    for StrongArmHighSpeedTb<T, PDK, C, Xyce>
 where
-    StrongArmHighSpeedTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+    StrongArmHighSpeedTb<T, PDK, C, Xyce>: Block<Io = TestbenchIo>,
 {
-    type Output = StrongArmHighSpeedTbOutput;
-
+    fn save_tb(
+        ctx: &SimulationContext<Xyce>,
+        cell: &Cell<Self>,
+        opts: &mut <Xyce as Simulator>::Options,
+    ) -> <ComparatorSim as FromSaved<Xyce, xyce::tran::Tran>>::SavedKey {
+        ComparatorSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            vop: tran::Voltage::save(ctx, cell.data().vop, opts),
+            von: tran::Voltage::save(ctx, cell.data().von, opts),
+            vinn: tran::Voltage::save(ctx, cell.data().vinn, opts),
+            vinp: tran::Voltage::save(ctx, cell.data().vinp, opts),
+            clk: tran::Voltage::save(ctx, cell.data().clk, opts),
+            probes: cell
+                .data()
+                .probes
+                .iter()
+                .map(|probe| tran::Voltage::save(ctx, probe, opts))
+                .collect(),
+        }
+    }
+}
+
+/// The output of the [`StrongArmHighSpeedTb`].
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub struct StrongArmHighSpeedTbOutput {
+    /// Whether the testbench used an inverted clock.
+    inverted_clk: bool,
+    /// The sequence of decisions made by the comparator.
+    pub decisions: Vec<Option<ComparatorDecision>>,
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for StrongArmHighSpeedTb<T, PDK, C>
+where
+    StrongArmHighSpeedTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = StrongArmHighSpeedTbOutput;
+
     fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
         let mut opts = spectre::Options::default();
         sim.set_option(self.params.pvt.corner, &mut opts);
         let wav: ComparatorSim = sim
             .simulate(
                 opts,
-                Tran {
+                self.noise.apply(Tran {
                     stop: self.params.period * Decimal::from(self.params.cycles + 2),
                     start: None,
                     errpreset: Some(ErrPreset::Conservative),
                     ..Default::default()
+                }),
+            )
+            .expect("failed to run simulation");
+
+        StrongArmHighSpeedTbOutput::from_sim(&self.params, &wav)
+    }
+}
+
+impl<T, PDK, C: SimOption<Ngspice> + Copy> Testbench<Ngspice>
+    for StrongArmHighSpeedTb<T, PDK, C, Ngspice>
+where
+    StrongArmHighSpeedTb<T, PDK, C, Ngspice>: Block<Io = TestbenchIo> + Schematic<Ngspice>,
+{
+    type Output = StrongArmHighSpeedTbOutput;
+
+    fn run(&self, sim: SimController<Ngspice, Self>) -> Self::Output {
+        let mut opts = ngspice::Options::default();
+        sim.set_option(self.params.pvt.corner, &mut opts);
+        let wav: ComparatorSim = sim
+            .simulate(
+                opts,
+                ngspice::tran::Tran {
+                    step: self.params.tr / dec!(10),
+                    stop: self.params.period * Decimal::from(self.params.cycles + 2),
+                    start: None,
+                },
+            )
+            .expect("failed to run simulation");
+
+        StrongArmHighSpeedTbOutput::from_sim(&self.params, &wav)
+    }
+}
+
+#[cfg(feature = "xyce")]
+impl<T, PDK, C: SimOption<Xyce> + Copy> Testbench<Xyce> for StrongArmHighSpeedTb<T, PDK, C, Xyce>
+where
+    StrongArmHighSpeedTb<T, PDK, C, Xyce>: Block<Io = TestbenchIo> + Schematic<Xyce>,
+{
+    type Output = StrongArmHighSpeedTbOutput;
+
+    fn run(&self, sim: SimController<Xyce, Self>) -> Self::Output {
+        let mut opts = xyce::Options::default();
+        sim.set_option(self.params.pvt.corner, &mut opts);
+        let wav: ComparatorSim = sim
+            .simulate(
+                opts,
+                xyce::tran::Tran {
+                    step: self.params.tr / dec!(10),
+                    stop: self.params.period * Decimal::from(self.params.cycles + 2),
+                    start: None,
                 },
             )
             .expect("failed to run simulation");
 
+        StrongArmHighSpeedTbOutput::from_sim(&self.params, &wav)
+    }
+}
+
+/// Returns the times at which the clock of a [`StrongArmHighSpeedTb`] crosses
+/// `thresh * vdd` in direction `dir`.
+fn clock_edges<T, C>(
+    params: &StrongArmHighSpeedTbParams<T, C>,
+    wav: &ComparatorSim,
+    thresh: f64,
+    dir: EdgeDir,
+) -> Vec<f64> {
+    let vdd = params.pvt.voltage.to_f64().unwrap();
+    clock_edges_at(wav, thresh * vdd, dir)
+}
+
+/// Returns the times at which the clock of `wav` crosses `level` in direction `dir`.
+fn clock_edges_at(wav: &ComparatorSim, level: f64, dir: EdgeDir) -> Vec<f64> {
+    let clk = WaveformRef::new(&wav.t, &wav.clk);
+    clk.edges(level)
+        .filter(|e| e.dir() == dir)
+        .map(|e| e.t())
+        .collect()
+}
+
+/// Returns the times at which the outputs of a [`StrongArmHighSpeedTb`] are sampled.
+fn sampling_edges<T, C>(
+    params: &StrongArmHighSpeedTbParams<T, C>,
+    wav: &ComparatorSim,
+) -> Vec<f64> {
+    let vdd = params.pvt.voltage.to_f64().unwrap();
+    end_of_evaluation(wav, vdd, params.inverted_clk)
+}
+
+/// Returns the clock edges of `wav` that end each evaluation phase.
+fn end_of_evaluation(wav: &ComparatorSim, vdd: f64, inverted_clk: bool) -> Vec<f64> {
+    if inverted_clk {
+        clock_edges_at(wav, 0.2 * vdd, EdgeDir::Rising)
+    } else {
+        clock_edges_at(wav, 0.8 * vdd, EdgeDir::Falling)
+    }
+}
+
+/// Classifies comparator outputs `vop` and `von` as a decision if each is within
+/// `1 - thresh` of a different rail.
+fn classify(vop: f64, von: f64, vdd: f64, thresh: f64) -> Option<ComparatorDecision> {
+    if von >= thresh * vdd && vop <= (1. - thresh) * vdd {
+        Some(ComparatorDecision::Neg)
+    } else if von <= (1. - thresh) * vdd && vop >= thresh * vdd {
+        Some(ComparatorDecision::Pos)
+    } else {
+        None
+    }
+}
+
+impl StrongArmHighSpeedTbOutput {
+    /// Samples the comparator outputs of `wav` at each sampling clock edge.
+    fn from_sim<T, C>(params: &StrongArmHighSpeedTbParams<T, C>, wav: &ComparatorSim) -> Self {
         let von = WaveformRef::new(&wav.t, &wav.von);
         let vop = WaveformRef::new(&wav.t, &wav.vop);
-        let clk = WaveformRef::new(&wav.t, &wav.clk);
-        let vdd = self.params.pvt.voltage.to_f64().unwrap();
-        let (clk_thresh, edge_dir) = if self.params.inverted_clk {
-            (0.2, EdgeDir::Rising)
-        } else {
-            (0.8, EdgeDir::Falling)
-        };
-        let decisions = clk
-            .edges(clk_thresh * vdd)
-            .filter(|e| e.dir() == edge_dir)
-            .map(|edge| {
-                let t = edge.t();
-                let von = von.sample_at(t);
-                let vop = vop.sample_at(t);
-                let thresh = self.params.thresh.to_f64().unwrap();
-                if von >= thresh * vdd && vop <= (1. - thresh) * vdd {
-                    Some(ComparatorDecision::Neg)
-                } else if von <= (1. - thresh) * vdd && vop >= thresh * vdd {
-                    Some(ComparatorDecision::Pos)
-                } else {
-                    None
-                }
+        let vdd = params.pvt.voltage.to_f64().unwrap();
+        let decisions = sampling_edges(params, wav)
+            .into_iter()
+            .map(|t| {
+                classify(
+                    vop.sample_at(t),
+                    von.sample_at(t),
+                    vdd,
+                    params.thresh.to_f64().unwrap(),
+                )
             })
             .collect::<Vec<_>>();
 
-        StrongArmHighSpeedTbOutput {
-            inverted_clk: self.params.inverted_clk,
+        Self {
+            inverted_clk: params.inverted_clk,
             decisions,
         }
     }
-}
 
-impl StrongArmHighSpeedTbOutput {
     /// Returns true if the testbench output was correct.
     pub fn is_correct(&self) -> bool {
         for (i, item) in self.decisions.iter().enumerate() {
@@ -558,6 +1151,27 @@ impl StrongArmHighSpeedTbOutput {
     }
 }
 
+impl SimArtifact for StrongArmHighSpeedTbOutput {
+    fn csv_header(&self) -> Vec<String> {
+        vec!["cycle".to_string(), "decision".to_string()]
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.decisions
+            .iter()
+            .enumerate()
+            .map(|(i, decision)| {
+                let decision = match decision {
+                    Some(ComparatorDecision::Pos) => "1",
+                    Some(ComparatorDecision::Neg) => "0",
+                    None => "x",
+                };
+                vec![i.to_string(), decision.to_string()]
+            })
+            .collect()
+    }
+}
+
 impl Display for StrongArmHighSpeedTbOutput {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
@@ -573,3 +1187,646 @@ impl Display for StrongArmHighSpeedTbOutput {
         Ok(())
     }
 }
+
+/// A [`StrongArmHighSpeedTb`] whose supply is disturbed by [`SupplyNoise`].
+///
+/// Measures the delay from each evaluating clock edge until the outputs separate,
+/// giving the output jitter caused by the disturbance.
+///
+/// Only supported by Spectre.
+#[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct StrongArmSupplyNoiseTb<T, PDK, C> {
+    params: StrongArmHighSpeedTbParams<T, C>,
+    supply: SupplyNoise,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> StrongArmSupplyNoiseTb<T, PDK, C> {
+    /// Creates a new [`StrongArmSupplyNoiseTb`].
+    pub fn new(params: StrongArmHighSpeedTbParams<T, C>, supply: SupplyNoise) -> Self {
+        Self {
+            params,
+            supply,
+            phantom: PhantomData,
+        }
+    }
+
+    fn stop(&self) -> Decimal {
+        self.params.period * Decimal::from(self.params.cycles + 2)
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for StrongArmSupplyNoiseTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("strong_arm_supply_noise_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("strong_arm_supply_noise_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T, PDK, C> ExportsNestedData for StrongArmSupplyNoiseTb<T, PDK, C>
+where
+    StrongArmSupplyNoiseTb<T, PDK, C>: Block,
+{
+    type NestedData = StrongArmTranTbNodes;
+}
+
+impl<
+        T: Block<Io = ClockedDiffComparatorIo> + Schematic<PDK> + ProbePoints + Clone,
+        PDK: Schema,
+        C,
+    > Schematic<Spectre> for StrongArmSupplyNoiseTb<T, PDK, C>
+where
+    StrongArmSupplyNoiseTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vdd = Vsource::pwl(self.supply.pwl(self.params.pvt.voltage, self.stop()));
+        let sources = spectre_high_speed_sources(&self.params, vdd);
+
+        Ok(connect_dut(
+            cell,
+            io.vss,
+            self.params.dut.clone(),
+            self.params.probes,
+            sources,
+        ))
+    }
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, ComparatorSim> for StrongArmSupplyNoiseTb<T, PDK, C>
+where
+    StrongArmSupplyNoiseTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <ComparatorSim as FromSaved<Spectre, Tran>>::SavedKey {
+        ComparatorSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            vop: tran::Voltage::save(ctx, cell.data().vop, opts),
+            von: tran::Voltage::save(ctx, cell.data().von, opts),
+            vinn: tran::Voltage::save(ctx, cell.data().vinn, opts),
+            vinp: tran::Voltage::save(ctx, cell.data().vinp, opts),
+            clk: tran::Voltage::save(ctx, cell.data().clk, opts),
+            probes: cell
+                .data()
+                .probes
+                .iter()
+                .map(|probe| tran::Voltage::save(ctx, probe, opts))
+                .collect(),
+        }
+    }
+}
+
+/// The output of the [`StrongArmSupplyNoiseTb`].
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct StrongArmSupplyNoiseTbOutput {
+    /// The decisions made by the comparator.
+    pub decisions: StrongArmHighSpeedTbOutput,
+    /// The delay from each evaluating clock edge until the outputs separate by
+    /// the testbench threshold, or [`None`] if they never did.
+    pub delays: Vec<Option<f64>>,
+}
+
+impl StrongArmSupplyNoiseTbOutput {
+    /// The peak-to-peak variation in delay across all cycles that resolved.
+    pub fn jitter(&self) -> Option<f64> {
+        let delays = self.delays.iter().flatten();
+        let max = delays.clone().copied().reduce(f64::max)?;
+        let min = delays.copied().reduce(f64::min)?;
+        Some(max - min)
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for StrongArmSupplyNoiseTb<T, PDK, C>
+where
+    StrongArmSupplyNoiseTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = StrongArmSupplyNoiseTbOutput;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.params.pvt.corner, &mut opts);
+        let wav: ComparatorSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: self.stop(),
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+
+        let vdd = self.params.pvt.voltage.to_f64().unwrap();
+        let diff = (2. * self.params.thresh.to_f64().unwrap() - 1.) * vdd;
+        let evaluation_edges = if self.params.inverted_clk {
+            clock_edges(&self.params, &wav, 0.5, EdgeDir::Falling)
+        } else {
+            clock_edges(&self.params, &wav, 0.5, EdgeDir::Rising)
+        };
+        let delays = evaluation_edges
+            .into_iter()
+            .map(|t0| {
+                let i = wav.t.iter().position(|&t| t > t0)?;
+                let j =
+                    i + (i..wav.t.len()).position(|j| (wav.vop[j] - wav.von[j]).abs() >= diff)?;
+                if j == i {
+                    return Some(wav.t[j] - t0);
+                }
+                // Interpolate the time at which the outputs separate by `diff`.
+                let (d0, d1) = (
+                    (wav.vop[j - 1] - wav.von[j - 1]).abs(),
+                    (wav.vop[j] - wav.von[j]).abs(),
+                );
+                let t = wav.t[j - 1] + (wav.t[j] - wav.t[j - 1]) * (diff - d0) / (d1 - d0);
+                Some(t - t0)
+            })
+            .collect();
+
+        StrongArmSupplyNoiseTbOutput {
+            decisions: StrongArmHighSpeedTbOutput::from_sim(&self.params, &wav),
+            delays,
+        }
+    }
+}
+
+/// Measures the jitter of a StrongARM under sinusoidal supply disturbances of
+/// peak deviation `amplitude` at each of the frequencies `freqs`.
+pub fn supply_sensitivity<T, PDK, C>(
+    ctx: &PdkContext<PDK>,
+    params: StrongArmHighSpeedTbParams<T, C>,
+    amplitude: Decimal,
+    freqs: &[Decimal],
+    work_dir: impl AsRef<Path>,
+) -> SupplySensitivity
+where
+    StrongArmSupplyNoiseTb<T, PDK, C>: Testbench<Spectre, Output = StrongArmSupplyNoiseTbOutput>,
+    PDK: Pdk,
+    T: Clone,
+    C: Clone,
+{
+    let points = freqs
+        .iter()
+        .map(|&freq| {
+            let output = ctx
+                .simulate(
+                    StrongArmSupplyNoiseTb::new(params.clone(), SupplyNoise::sine(amplitude, freq)),
+                    work_dir.as_ref().join(format!("freq{freq}")),
+                )
+                .expect("failed to run simulation");
+            SupplySensitivityPoint {
+                freq: freq.to_f64().unwrap(),
+                amplitude: amplitude.to_f64().unwrap(),
+                jitter: output
+                    .jitter()
+                    .expect("comparator did not resolve in any cycle"),
+            }
+        })
+        .collect();
+    SupplySensitivity { points }
+}
+
+/// A transient testbench that holds a StrongARM in reset while its clock runs, then
+/// releases the reset and measures how long the comparator takes to make a correct
+/// decision.
+///
+/// The reset is asserted at the idle level of the clock, which is the precharge level
+/// of the comparator. Release it during an evaluation phase to measure the worst case.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct StrongArmResetTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: T,
+    /// The positive input voltage.
+    pub vinp: Decimal,
+    /// The negative input voltage.
+    pub vinn: Decimal,
+    /// The clock period.
+    pub period: Decimal,
+    /// The rise and fall time of the clock and reset.
+    pub edge: Decimal,
+    /// The time at which the reset starts to release.
+    pub release: Decimal,
+    /// The number of clock cycles simulated after the release.
+    pub cycles: usize,
+    /// Threshold for valid voltage levels at comparator outputs, as a percent of VDD.
+    pub thresh: Decimal,
+    /// Whether to pass an inverted clock to the DUT.
+    ///
+    /// Also inverts the reset, which is then active high.
+    pub inverted_clk: bool,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> StrongArmResetTb<T, PDK, C> {
+    /// Creates a new [`StrongArmResetTb`] with a 1 ns clock whose reset releases midway
+    /// through the fifth evaluation phase.
+    pub fn new(dut: T, vinp: Decimal, vinn: Decimal, inverted_clk: bool, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            vinp,
+            vinn,
+            period: dec!(1e-9),
+            edge: dec!(50e-12),
+            release: dec!(4.75e-9),
+            cycles: 4,
+            thresh: dec!(0.8),
+            inverted_clk,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+
+    fn stop(&self) -> Decimal {
+        self.release + self.period * Decimal::from(self.cycles + 1)
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for StrongArmResetTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("strong_arm_reset_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("strong_arm_reset_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T, PDK, C> ExportsNestedData for StrongArmResetTb<T, PDK, C>
+where
+    StrongArmResetTb<T, PDK, C>: Block,
+{
+    type NestedData = StrongArmTranTbNodes;
+}
+
+impl<T: Block<Io = ResettableComparatorIo> + Schematic<PDK> + Clone, PDK: Schema, C>
+    Schematic<Spectre> for StrongArmResetTb<T, PDK, C>
+where
+    StrongArmResetTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
+
+        let vinp = cell.signal("vinp", Signal);
+        let vinn = cell.signal("vinn", Signal);
+        let vdd = cell.signal("vdd", Signal);
+        let clk = cell.signal("clk", Signal);
+        let reset = cell.signal("reset", Signal);
+        let output = cell.signal("output", DiffPair::default());
+
+        // The clock idles at the precharge level, which is also the active reset level.
+        let (idle, active) = if self.inverted_clk {
+            (self.pvt.voltage, dec!(0))
+        } else {
+            (dec!(0), self.pvt.voltage)
+        };
+        for (source, p) in [
+            (Vsource::dc(self.vinp), vinp),
+            (Vsource::dc(self.vinn), vinn),
+            (Vsource::dc(self.pvt.voltage), vdd),
+            (
+                Vsource::pulse(Pulse {
+                    val0: idle,
+                    val1: active,
+                    period: Some(self.period),
+                    width: Some(self.period / dec!(2) - self.edge),
+                    delay: Some(self.period / dec!(2)),
+                    rise: Some(self.edge),
+                    fall: Some(self.edge),
+                }),
+                clk,
+            ),
+            (
+                Vsource::pwl(vec![
+                    (dec!(0), idle),
+                    (self.release, idle),
+                    (self.release + self.edge, active),
+                ]),
+                reset,
+            ),
+        ] {
+            cell.instantiate_connected(source, TwoTerminalIoSchematic { p, n: io.vss });
+        }
+
+        cell.connect(
+            Bundle::<ResettableComparatorIo> {
+                input: Bundle::<DiffPair> { p: vinp, n: vinn },
+                output: output.clone(),
+                clock: clk,
+                reset,
+                vdd,
+                vss: io.vss,
+            },
+            dut.io(),
+        );
+
+        Ok(StrongArmTranTbNodes {
+            vop: output.p,
+            von: output.n,
+            vinn,
+            vinp,
+            clk,
+            probes: Vec::new(),
+        })
+    }
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, ComparatorSim> for StrongArmResetTb<T, PDK, C>
+where
+    StrongArmResetTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <ComparatorSim as FromSaved<Spectre, Tran>>::SavedKey {
+        ComparatorSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            vop: tran::Voltage::save(ctx, cell.data().vop, opts),
+            von: tran::Voltage::save(ctx, cell.data().von, opts),
+            vinn: tran::Voltage::save(ctx, cell.data().vinn, opts),
+            vinp: tran::Voltage::save(ctx, cell.data().vinp, opts),
+            clk: tran::Voltage::save(ctx, cell.data().clk, opts),
+            probes: cell
+                .data()
+                .probes
+                .iter()
+                .map(|probe| tran::Voltage::save(ctx, probe, opts))
+                .collect(),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for StrongArmResetTb<T, PDK, C>
+where
+    StrongArmResetTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = StrongArmResetTbOutput;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: ComparatorSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: self.stop(),
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+
+        let vdd = self.pvt.voltage.to_f64().unwrap();
+        let thresh = self.thresh.to_f64().unwrap();
+        let release = self.release.to_f64().unwrap();
+        let period = self.period.to_f64().unwrap();
+
+        // Both outputs must sit at the precharge level for the full clock period before
+        // the release, covering an evaluation phase.
+        let precharge = if self.inverted_clk { 0. } else { vdd };
+        let held = wav
+            .t
+            .iter()
+            .zip(wav.vop.iter().zip(wav.von.iter()))
+            .filter(|&(&t, _)| t >= release - period && t <= release)
+            .all(|(_, (&vop, &von))| {
+                (vop - precharge).abs() <= (1. - thresh) * vdd
+                    && (von - precharge).abs() <= (1. - thresh) * vdd
+            });
+
+        let vop = WaveformRef::new(&wav.t, &wav.vop);
+        let von = WaveformRef::new(&wav.t, &wav.von);
+        let (sample_times, decisions) = end_of_evaluation(&wav, vdd, self.inverted_clk)
+            .into_iter()
+            .filter(|&t| t > release)
+            .map(|t| {
+                (
+                    t - release,
+                    classify(vop.sample_at(t), von.sample_at(t), vdd, thresh),
+                )
+            })
+            .unzip();
+
+        StrongArmResetTbOutput {
+            held,
+            expected: if self.vinp > self.vinn {
+                ComparatorDecision::Pos
+            } else {
+                ComparatorDecision::Neg
+            },
+            sample_times,
+            decisions,
+        }
+    }
+}
+
+/// The output of the [`StrongArmResetTb`].
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct StrongArmResetTbOutput {
+    /// Whether both outputs stayed at the precharge level for the clock period
+    /// before the release.
+    pub held: bool,
+    /// The decision the inputs call for.
+    pub expected: ComparatorDecision,
+    /// The time of each sampling edge after the release, relative to the release.
+    pub sample_times: Vec<f64>,
+    /// The decision made at each sampling edge after the release.
+    pub decisions: Vec<Option<ComparatorDecision>>,
+}
+
+impl StrongArmResetTbOutput {
+    /// The time from the release to the first sampling edge from which every decision
+    /// is correct, or [`None`] if the last decision is wrong.
+    pub fn recovery_time(&self) -> Option<f64> {
+        let wrong = self
+            .decisions
+            .iter()
+            .rposition(|&decision| decision != Some(self.expected));
+        let first = wrong.map_or(0, |i| i + 1);
+        self.sample_times.get(first).copied()
+    }
+
+    /// Whether the reset held the outputs and the comparator recovered.
+    pub fn passed(&self) -> bool {
+        self.held && self.recovery_time().is_some()
+    }
+}
+
+impl SimArtifact for StrongArmResetTbOutput {
+    fn csv_header(&self) -> Vec<String> {
+        ["time_after_release", "decision"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.sample_times
+            .iter()
+            .zip(&self.decisions)
+            .map(|(t, decision)| {
+                let decision = match decision {
+                    Some(ComparatorDecision::Pos) => "1",
+                    Some(ComparatorDecision::Neg) => "0",
+                    None => "x",
+                };
+                vec![t.to_string(), decision.to_string()]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sweep::Variations;
+
+    #[test]
+    fn rejects_spectre_only_analyses() {
+        let pvt = Pvt {
+            corner: (),
+            voltage: dec!(1.8),
+            temp: dec!(25),
+        };
+        let tb = StrongArmTranTb::<(), (), ()>::new((), dec!(0.65), dec!(0.55), false, pvt);
+        assert!(tb.with_simulator::<Ngspice>().is_ok());
+        assert!(tb.with_simulator::<Spectre>().is_ok());
+
+        let mc = tb.with_mc_sample(McSample {
+            variations: Variations::Process,
+            seed: 0,
+            index: 0,
+        });
+        assert!(matches!(
+            mc.with_simulator::<Ngspice>(),
+            Err(UnsupportedAnalysisError::MonteCarlo)
+        ));
+        assert!(mc.with_simulator::<Spectre>().is_ok());
+
+        let noise = tb.with_noise(SimNoiseOptions::enabled(dec!(100e9)));
+        assert!(matches!(
+            noise.with_simulator::<Ngspice>(),
+            Err(UnsupportedAnalysisError::TransientNoise)
+        ));
+    }
+
+    #[test]
+    fn extracted_dut_keeps_the_analyses() {
+        let pvt = Pvt {
+            corner: (),
+            voltage: dec!(1.8),
+            temp: dec!(25),
+        };
+        let pex = PexOutput {
+            netlist: "strongarm.pex.spice".into(),
+            cell: "strongarm".to_string(),
+        };
+        let ports = vec![arcstr::literal!("clk"), arcstr::literal!("vdd")];
+        let tb = StrongArmTranTb::<(), (), ()>::new((), dec!(0.65), dec!(0.55), false, pvt)
+            .with_noise(SimNoiseOptions::enabled(dec!(100e9)))
+            .with_extracted_dut(pex.clone(), ports.clone());
+        assert_eq!(tb.dut, ExtractedView::new((), pex, ports));
+        assert!(matches!(
+            tb.with_simulator::<Ngspice>(),
+            Err(UnsupportedAnalysisError::TransientNoise)
+        ));
+    }
+
+    #[test]
+    fn reset_recovery_time() {
+        let output = StrongArmResetTbOutput {
+            held: true,
+            expected: ComparatorDecision::Pos,
+            sample_times: vec![0.25e-9, 1.25e-9, 2.25e-9],
+            decisions: vec![
+                None,
+                Some(ComparatorDecision::Pos),
+                Some(ComparatorDecision::Pos),
+            ],
+        };
+        assert_eq!(output.recovery_time(), Some(1.25e-9));
+        assert!(output.passed());
+
+        let stuck = StrongArmResetTbOutput {
+            decisions: vec![Some(ComparatorDecision::Pos), None, None],
+            ..output.clone()
+        };
+        assert_eq!(stuck.recovery_time(), None);
+
+        let glitch = StrongArmResetTbOutput {
+            decisions: vec![
+                Some(ComparatorDecision::Pos),
+                Some(ComparatorDecision::Neg),
+                Some(ComparatorDecision::Pos),
+            ],
+            ..output
+        };
+        assert_eq!(glitch.recovery_time(), Some(2.25e-9));
+    }
+}
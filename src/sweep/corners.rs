@@ -0,0 +1,154 @@
+//! Per-technology process corner libraries.
+//!
+//! Testbenches take any corner that sets its own simulator options, but the sweep
+//! helpers need to know which corners exist and at which supply voltages and
+//! temperatures to run them. Each technology implementation provides this through
+//! [`CornerLibrary`], so the same sweeps and Monte Carlo runs can be used with any PDK.
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use substrate::pdk::corner::Pvt;
+
+use crate::sweep::pvt_grid;
+
+/// A model file, or a section of one, included by a corner.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct ModelInclude {
+    /// The path of the model file.
+    pub path: PathBuf,
+    /// The library section to include, or `None` to include the whole file.
+    pub section: Option<String>,
+}
+
+impl ModelInclude {
+    /// Includes section `section` of the model file at `path`.
+    pub fn section(path: impl Into<PathBuf>, section: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            section: Some(section.into()),
+        }
+    }
+
+    /// Adds this include to a set of Spectre options.
+    pub fn apply(&self, opts: &mut spectre::Options) {
+        match &self.section {
+            Some(section) => opts.include_section(self.path.clone(), section.clone()),
+            None => opts.include(self.path.clone()),
+        }
+    }
+}
+
+/// An error produced while looking up corners by name.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CornerError {
+    /// The library has no corner with the given name.
+    #[error("unknown corner {0:?}")]
+    Unknown(String),
+}
+
+/// The process corners of a technology and the conditions at which to run them.
+pub trait CornerLibrary {
+    /// The corner type passed to testbenches.
+    type Corner: Copy + Debug + Hash + Eq + Send + Sync;
+
+    /// The named corners, starting with the typical corner.
+    fn corners() -> Vec<(&'static str, Self::Corner)>;
+    /// The nominal supply voltage.
+    fn nominal_voltage() -> Decimal;
+    /// The supply tolerance, as a fraction of the nominal voltage.
+    fn voltage_tolerance() -> Decimal {
+        dec!(0.1)
+    }
+    /// The temperatures at which to run each corner, in degrees Celsius.
+    fn temperatures() -> Vec<Decimal> {
+        vec![dec!(-40), dec!(25), dec!(125)]
+    }
+    /// The model files included by `corner` for a PDK installed at `pdk_root`.
+    ///
+    /// Corners whose simulator options already include their models return no files.
+    fn model_includes(_pdk_root: &Path, _corner: Self::Corner) -> Vec<ModelInclude> {
+        Vec::new()
+    }
+
+    /// Returns the corner named `name`, ignoring case.
+    fn corner(name: &str) -> Result<Self::Corner, CornerError> {
+        Self::corners()
+            .into_iter()
+            .find(|(corner, _)| corner.eq_ignore_ascii_case(name))
+            .map(|(_, corner)| corner)
+            .ok_or_else(|| CornerError::Unknown(name.to_string()))
+    }
+
+    /// The minimum, nominal, and maximum supply voltages.
+    fn voltages() -> [Decimal; 3] {
+        let nominal = Self::nominal_voltage();
+        let delta = nominal * Self::voltage_tolerance();
+        [nominal - delta, nominal, nominal + delta]
+    }
+
+    /// The typical corner at the nominal voltage and 25 degrees Celsius.
+    fn typical() -> Pvt<Self::Corner> {
+        Pvt {
+            corner: Self::corners()[0].1,
+            voltage: Self::nominal_voltage(),
+            temp: dec!(25),
+        }
+    }
+
+    /// Every combination of corner, voltage, and temperature.
+    fn pvts() -> Vec<Pvt<Self::Corner>> {
+        pvt_grid(
+            Self::corners().into_iter().map(|(_, corner)| corner),
+            Self::voltages(),
+            Self::temperatures(),
+        )
+    }
+
+    /// Every combination of voltage and temperature at each of the named corners.
+    fn named_pvts(names: &[&str]) -> Result<Vec<Pvt<Self::Corner>>, CornerError> {
+        let corners = names
+            .iter()
+            .map(|name| Self::corner(name))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(pvt_grid(corners, Self::voltages(), Self::temperatures()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Library;
+
+    impl CornerLibrary for Library {
+        type Corner = u8;
+
+        fn corners() -> Vec<(&'static str, u8)> {
+            vec![("tt", 0), ("ss", 1), ("ff", 2)]
+        }
+
+        fn nominal_voltage() -> Decimal {
+            dec!(1.8)
+        }
+    }
+
+    #[test]
+    fn corner_library_grid() {
+        assert_eq!(Library::corner("SS"), Ok(1));
+        assert_eq!(
+            Library::corner("sf"),
+            Err(CornerError::Unknown("sf".to_string()))
+        );
+        assert_eq!(Library::voltages(), [dec!(1.62), dec!(1.8), dec!(1.98)]);
+        assert_eq!(Library::typical().corner, 0);
+        assert_eq!(Library::pvts().len(), 27);
+
+        let pvts = Library::named_pvts(&["ff"]).unwrap();
+        assert_eq!(pvts.len(), 9);
+        assert!(pvts.iter().all(|pvt| pvt.corner == 2));
+    }
+}
@@ -0,0 +1,516 @@
+//! Utilities for running testbenches across many operating conditions.
+
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::montecarlo;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use substrate::context::PdkContext;
+use substrate::pdk::corner::Pvt;
+use substrate::pdk::Pdk;
+use substrate::simulation::{Simulator, Testbench};
+
+pub mod corners;
+
+use crate::tb::StatisticalSimulator;
+use corners::CornerLibrary;
+
+/// Returns every combination of the given corners, voltages, and temperatures.
+pub fn pvt_grid<C: Copy>(
+    corners: impl IntoIterator<Item = C>,
+    voltages: impl IntoIterator<Item = Decimal> + Clone,
+    temps: impl IntoIterator<Item = Decimal> + Clone,
+) -> Vec<Pvt<C>> {
+    let mut pvts = Vec::new();
+    for corner in corners {
+        for voltage in voltages.clone() {
+            for temp in temps.clone() {
+                pvts.push(Pvt {
+                    corner,
+                    voltage,
+                    temp,
+                });
+            }
+        }
+    }
+    pvts
+}
+
+/// Runs a testbench at each of a list of PVT corners.
+///
+/// The testbench for each corner is produced by a user-supplied function,
+/// so any testbench that is parameterized by a [`Pvt`] can be swept.
+pub struct CornerSweep<TB, C> {
+    pvts: Vec<Pvt<C>>,
+    max_concurrency: usize,
+    cache: bool,
+    tb: Arc<dyn Fn(Pvt<C>) -> TB + Send + Sync>,
+}
+
+/// A cached corner simulation result.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<O> {
+    /// The [`cache_key`] of the simulation that produced the output.
+    key: String,
+    output: O,
+}
+
+const CACHE_FILE: &str = "sweep_cache.json";
+
+impl<TB, C> CornerSweep<TB, C> {
+    /// Creates a new [`CornerSweep`] that runs the testbench returned by `tb` at each of `pvts`.
+    ///
+    /// By default, at most 8 simulations run concurrently and results are cached.
+    pub fn new(
+        pvts: impl IntoIterator<Item = Pvt<C>>,
+        tb: impl Fn(Pvt<C>) -> TB + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            pvts: pvts.into_iter().collect(),
+            max_concurrency: 8,
+            cache: true,
+            tb: Arc::new(tb),
+        }
+    }
+
+    /// Creates a new [`CornerSweep`] that runs the testbench returned by `tb` at every
+    /// corner, voltage, and temperature of the corner library `L`.
+    pub fn from_library<L: CornerLibrary<Corner = C>>(
+        tb: impl Fn(Pvt<C>) -> TB + Send + Sync + 'static,
+    ) -> Self {
+        Self::new(L::pvts(), tb)
+    }
+
+    /// Sets the maximum number of simulations that may run at the same time.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        assert!(max_concurrency > 0, "max concurrency must be positive");
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
+    /// Sets whether simulation results are cached.
+    ///
+    /// If enabled, a corner whose testbench is unchanged since the last run
+    /// in the same working directory is not simulated again.
+    pub fn with_cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// The corners swept.
+    pub fn pvts(&self) -> &[Pvt<C>] {
+        &self.pvts
+    }
+
+    /// Runs the sweep using simulator `S`, returning the output at each corner.
+    ///
+    /// Each corner is simulated in its own subdirectory of `work_dir`.
+    pub fn run<S, PDK>(
+        &self,
+        ctx: &PdkContext<PDK>,
+        work_dir: impl AsRef<Path>,
+    ) -> CornerSweepOutput<C, TB::Output>
+    where
+        S: Simulator,
+        PDK: Pdk,
+        TB: Testbench<S> + Serialize,
+        TB::Output: Serialize + DeserializeOwned + Send,
+        C: Copy + Debug + Hash + Eq + Send + Sync,
+    {
+        let _span = tracing::info_span!("corner_sweep", corners = self.pvts.len()).entered();
+        let work_dir = work_dir.as_ref();
+        let outputs = run_concurrently(&self.pvts, self.max_concurrency, |pvt| {
+            let sim_dir = work_dir.join(corner_dir_name(&pvt));
+            simulate_cached::<S, _, _>(ctx, (self.tb)(pvt), sim_dir, self.cache)
+        });
+        CornerSweepOutput {
+            outputs: outputs.into_iter().collect(),
+        }
+    }
+}
+
+/// The outputs of a [`CornerSweep`].
+#[derive(Clone, Debug)]
+pub struct CornerSweepOutput<C, O> {
+    /// The output at each corner.
+    pub outputs: HashMap<Pvt<C>, O>,
+}
+
+impl<C: Hash + Eq, O> CornerSweepOutput<C, O> {
+    /// Returns the output at the given corner.
+    pub fn get(&self, pvt: &Pvt<C>) -> Option<&O> {
+        self.outputs.get(pvt)
+    }
+}
+
+/// Corners are not valid map keys in most serialization formats,
+/// so outputs are serialized as a list of corner-output pairs.
+impl<C: Serialize, O: Serialize> Serialize for CornerSweepOutput<C, O> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Entry<'a, C, O> {
+            pvt: &'a Pvt<C>,
+            output: &'a O,
+        }
+
+        serializer.collect_seq(
+            self.outputs
+                .iter()
+                .map(|(pvt, output)| Entry { pvt, output }),
+        )
+    }
+}
+
+/// The sources of variation enabled in a Monte Carlo simulation.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Variations {
+    /// Global process variation.
+    Process,
+    /// Local device mismatch.
+    Mismatch,
+    /// Both process variation and mismatch.
+    All,
+}
+
+impl From<Variations> for montecarlo::Variations {
+    fn from(value: Variations) -> Self {
+        match value {
+            Variations::Process => montecarlo::Variations::Process,
+            Variations::Mismatch => montecarlo::Variations::Mismatch,
+            Variations::All => montecarlo::Variations::All,
+        }
+    }
+}
+
+/// A single Monte Carlo sample.
+///
+/// Testbenches that support Monte Carlo take an optional [`McSample`] and
+/// wrap their analysis with [`McSample::analysis`].
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McSample {
+    /// The sources of variation.
+    pub variations: Variations,
+    /// The random seed shared by all samples of a run.
+    pub seed: u64,
+    /// The index of this sample.
+    pub index: usize,
+}
+
+impl McSample {
+    /// Wraps `analysis` in a Spectre Monte Carlo analysis that runs only this sample.
+    ///
+    /// Since every sample of a run shares a seed, the sample drawn depends only on its index.
+    pub fn analysis<A>(&self, analysis: A) -> montecarlo::MonteCarlo<A> {
+        montecarlo::MonteCarlo {
+            variations: self.variations.into(),
+            seed: Some(self.seed),
+            numruns: 1,
+            firstrun: Some(self.index + 1),
+            analysis,
+        }
+    }
+}
+
+/// Runs Monte Carlo samples of a testbench.
+///
+/// Samples are simulated independently, so they can run in parallel and be cached
+/// in the same way as a [`CornerSweep`]. The testbench applies each [`McSample`]
+/// itself, so runs are limited to simulators that support Monte Carlo, which are
+/// those implementing [`StatisticalSimulator`].
+pub struct MonteCarlo<TB> {
+    samples: usize,
+    variations: Variations,
+    seed: u64,
+    max_concurrency: usize,
+    cache: bool,
+    tb: Arc<dyn Fn(McSample) -> TB + Send + Sync>,
+}
+
+impl<TB> MonteCarlo<TB> {
+    /// Creates a new [`MonteCarlo`] that runs `samples` samples of the testbench returned by `tb`.
+    ///
+    /// By default, the seed is 0, at most 8 simulations run concurrently, and results are cached.
+    pub fn new(
+        samples: usize,
+        variations: Variations,
+        tb: impl Fn(McSample) -> TB + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            samples,
+            variations,
+            seed: 0,
+            max_concurrency: 8,
+            cache: true,
+            tb: Arc::new(tb),
+        }
+    }
+
+    /// Sets the random seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the maximum number of simulations that may run at the same time.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        assert!(max_concurrency > 0, "max concurrency must be positive");
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
+    /// Sets whether simulation results are cached.
+    pub fn with_cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Runs all samples using simulator `S`, with each sample simulated in its own
+    /// subdirectory of `work_dir`.
+    pub fn run<S, PDK>(
+        &self,
+        ctx: &PdkContext<PDK>,
+        work_dir: impl AsRef<Path>,
+    ) -> MonteCarloOutput<TB::Output>
+    where
+        S: StatisticalSimulator,
+        PDK: Pdk,
+        TB: Testbench<S> + Serialize,
+        TB::Output: Serialize + DeserializeOwned + Send,
+    {
+        let _span = tracing::info_span!("monte_carlo", samples = self.samples).entered();
+        let work_dir = work_dir.as_ref();
+        let samples = (0..self.samples)
+            .map(|index| McSample {
+                variations: self.variations,
+                seed: self.seed,
+                index,
+            })
+            .collect::<Vec<_>>();
+        let samples = run_concurrently(&samples, self.max_concurrency, |sample| {
+            let sim_dir = work_dir.join(format!("mc{}", sample.index));
+            simulate_cached::<S, _, _>(ctx, (self.tb)(sample), sim_dir, self.cache)
+        })
+        .into_iter()
+        .map(|(_, output)| output)
+        .collect();
+        MonteCarloOutput { samples }
+    }
+}
+
+/// The outputs of a Monte Carlo run.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MonteCarloOutput<O> {
+    /// The output of each sample, in sample order.
+    pub samples: Vec<O>,
+}
+
+impl<O> MonteCarloOutput<O> {
+    /// Summarizes the value of `metric` across all samples.
+    pub fn summarize(&self, metric: impl Fn(&O) -> f64) -> Summary {
+        Summary::new(self.samples.iter().map(metric))
+    }
+}
+
+/// Summary statistics of a set of values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+    /// The mean.
+    pub mean: f64,
+    /// The sample standard deviation.
+    pub std_dev: f64,
+    /// The minimum value.
+    pub min: f64,
+    /// The maximum value.
+    pub max: f64,
+    /// The values, sorted in ascending order.
+    pub values: Vec<f64>,
+}
+
+impl Summary {
+    /// Computes summary statistics of `values`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty or contains NaN.
+    pub fn new(values: impl IntoIterator<Item = f64>) -> Self {
+        let mut values = values.into_iter().collect::<Vec<_>>();
+        assert!(
+            !values.is_empty(),
+            "cannot summarize an empty set of values"
+        );
+        values.sort_by(|a, b| a.partial_cmp(b).expect("cannot summarize NaN values"));
+
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let std_dev = if values.len() > 1 {
+            (values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.)).sqrt()
+        } else {
+            0.
+        };
+        Self {
+            mean,
+            std_dev,
+            min: values[0],
+            max: values[values.len() - 1],
+            values,
+        }
+    }
+
+    /// Returns the `p`th percentile, linearly interpolating between values.
+    ///
+    /// `p` must be between 0 and 100.
+    pub fn percentile(&self, p: f64) -> f64 {
+        assert!(
+            (0. ..=100.).contains(&p),
+            "percentile must be between 0 and 100"
+        );
+        let pos = p / 100. * (self.values.len() - 1) as f64;
+        let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+        self.values[lo] + (self.values[hi] - self.values[lo]) * (pos - lo as f64)
+    }
+}
+
+/// Calls `f` on each item, with at most `max_concurrency` calls running at a time.
+///
+/// Returns each item paired with its output, in the order the items were given.
+fn run_concurrently<K, O>(
+    items: &[K],
+    max_concurrency: usize,
+    f: impl Fn(K) -> O + Sync,
+) -> Vec<(K, O)>
+where
+    K: Copy + Send + Sync,
+    O: Send,
+{
+    // Spans are per-thread, so each worker re-enters the caller's span.
+    let span = tracing::Span::current();
+    let mut outputs = Vec::with_capacity(items.len());
+    for chunk in items.chunks(max_concurrency) {
+        thread::scope(|s| {
+            let handles = chunk
+                .iter()
+                .map(|&item| {
+                    let (f, span) = (&f, &span);
+                    s.spawn(move || (item, span.in_scope(|| f(item))))
+                })
+                .collect::<Vec<_>>();
+            for handle in handles {
+                outputs.push(handle.join().expect("thread failed"));
+            }
+        });
+    }
+    outputs
+}
+
+/// Simulates `tb` in `sim_dir`, reusing a cached result if `cache` is set.
+fn simulate_cached<S, PDK, TB>(
+    ctx: &PdkContext<PDK>,
+    tb: TB,
+    sim_dir: PathBuf,
+    cache: bool,
+) -> TB::Output
+where
+    S: Simulator,
+    PDK: Pdk,
+    TB: Testbench<S> + Serialize,
+    TB::Output: Serialize + DeserializeOwned,
+{
+    let _span = tracing::info_span!("simulate", dir = %sim_dir.display()).entered();
+    let cache_path = sim_dir.join(CACHE_FILE);
+    let key = cache_key::<S, PDK, _>(&tb);
+    if cache {
+        if let Some(output) = read_cache(&cache_path, &key) {
+            tracing::debug!("reusing cached simulation output");
+            return output;
+        }
+    }
+
+    let output = ctx
+        .simulate(tb, &sim_dir)
+        .expect("failed to run simulation");
+
+    if cache {
+        let entry = CacheEntry { key, output };
+        let contents =
+            serde_json::to_string(&entry).expect("failed to serialize simulation output");
+        std::fs::write(&cache_path, contents).expect("failed to write sweep cache");
+        entry.output
+    } else {
+        output
+    }
+}
+
+/// Returns the key of a cached simulation of `tb` with simulator `S` in a `PDK` context.
+///
+/// The key includes the [generator fingerprint](crate::GENERATOR_FINGERPRINT) and the
+/// type names of the simulator, PDK, and testbench, so that identical testbenches run
+/// with different simulators or PDKs, or by a different build of the testbench code,
+/// do not share results.
+fn cache_key<S, PDK, TB: Serialize>(tb: &TB) -> String {
+    serde_json::to_string(&(
+        crate::GENERATOR_FINGERPRINT,
+        std::any::type_name::<S>(),
+        std::any::type_name::<PDK>(),
+        std::any::type_name::<TB>(),
+        tb,
+    ))
+    .expect("failed to serialize testbench")
+}
+
+/// Returns the cached output at `path` if it was produced by the simulation with key `key`.
+fn read_cache<O: DeserializeOwned>(path: &Path, key: &str) -> Option<O> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let entry: CacheEntry<O> = serde_json::from_str(&contents).ok()?;
+    (entry.key == key).then_some(entry.output)
+}
+
+fn corner_dir_name<C: Debug>(pvt: &Pvt<C>) -> String {
+    format!("{:?}_{}V_{}C", pvt.corner, pvt.voltage, pvt.temp).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use ngspice::Ngspice;
+    use sky130pdk::Sky130Pdk;
+    use spectre::Spectre;
+
+    #[test]
+    fn summary_statistics() {
+        let summary = Summary::new([4., 1., 3., 2., 5.]);
+        assert_relative_eq!(summary.mean, 3.);
+        assert_relative_eq!(summary.std_dev, 2.5f64.sqrt());
+        assert_relative_eq!(summary.min, 1.);
+        assert_relative_eq!(summary.max, 5.);
+        assert_relative_eq!(summary.percentile(50.), 3.);
+        assert_relative_eq!(summary.percentile(90.), 4.6);
+    }
+
+    #[test]
+    fn cache_is_keyed_by_simulator() {
+        let path = std::env::temp_dir().join(format!(
+            "ucieanalog_sweep_cache_{}.json",
+            std::process::id()
+        ));
+        let tb = ("tran", 1.8);
+        let entry = CacheEntry {
+            key: cache_key::<Spectre, Sky130Pdk, _>(&tb),
+            output: 1.,
+        };
+        std::fs::write(&path, serde_json::to_string(&entry).unwrap()).unwrap();
+
+        let spectre_key = cache_key::<Spectre, Sky130Pdk, _>(&tb);
+        let ngspice_key = cache_key::<Ngspice, Sky130Pdk, _>(&tb);
+        assert_ne!(spectre_key, ngspice_key);
+        assert_eq!(read_cache::<f64>(&path, &spectre_key), Some(1.));
+        assert_eq!(read_cache::<f64>(&path, &ngspice_key), None);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
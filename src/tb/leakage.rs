@@ -0,0 +1,484 @@
+//! Leakage and standby power characterization.
+//!
+//! [`LeakageTb`] holds a block in a static control state, such as a driver in Hi-Z or a
+//! sampler with its clock idle, and measures the current it draws from its supply. Run
+//! it across temperatures and process corners with a [`CornerSweep`](crate::sweep::CornerSweep),
+//! then reduce the results with [`LeakageSummary`] to the worst-case standby power
+//! reported for each [`PowerState`] of the link.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::Vsource;
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{Bundle, HardwareType, Node, Terminal};
+use substrate::io::{FlatLen, Io, Signal, TestbenchIo};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::driver::{DriverIo, DriverWithGuardRingRailsIo};
+use crate::report::SimArtifact;
+use crate::spec::{ComplianceReport, Limit, Spec};
+use crate::strongarm::{ClockedDiffComparatorIo, InputKind};
+use crate::sweep::CornerSweepOutput;
+
+/// A static logic level.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Level {
+    /// Tied to VSS.
+    Low,
+    /// Tied to VDD.
+    High,
+}
+
+impl Level {
+    /// The node at this level.
+    pub fn node(&self, vdd: Node, vss: Node) -> Node {
+        match self {
+            Level::Low => vss,
+            Level::High => vdd,
+        }
+    }
+}
+
+/// An interface that can be held in a static control state of type `S`.
+pub trait StandbyIo<S>: Io {
+    /// Ties every port of `io` for `state`, connecting the supply rails of the block
+    /// to `vdd` and `vss`.
+    fn hold<SC: Schema>(
+        state: S,
+        cell: &mut CellBuilder<SC>,
+        io: &Bundle<Self>,
+        vdd: Node,
+        vss: Node,
+    );
+}
+
+/// A static state of a driver.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum DriverStandby {
+    /// Every segment is disabled, leaving the output floating.
+    HiZ,
+    /// Every main segment is enabled and the input is held at the given level.
+    Parked(Level),
+}
+
+impl DriverStandby {
+    /// The levels of the `pu_ctl` and `pd_ctlb` lines of main segments, and of `din`.
+    fn levels(&self) -> (Level, Level, Level) {
+        match *self {
+            DriverStandby::HiZ => (Level::Low, Level::High, Level::Low),
+            DriverStandby::Parked(din) => (Level::High, Level::Low, din),
+        }
+    }
+}
+
+impl StandbyIo<DriverStandby> for DriverIo {
+    fn hold<SC: Schema>(
+        state: DriverStandby,
+        cell: &mut CellBuilder<SC>,
+        io: &Bundle<Self>,
+        vdd: Node,
+        vss: Node,
+    ) {
+        let (pu_ctl, pd_ctlb, din) = state.levels();
+        cell.connect(io.din, din.node(vdd, vss));
+        for i in 0..io.pu_ctl.len() {
+            cell.connect(io.pu_ctl[i], pu_ctl.node(vdd, vss));
+            cell.connect(io.pd_ctlb[i], pd_ctlb.node(vdd, vss));
+        }
+        // Spare segments stay disabled.
+        for i in 0..io.spare_pu_ctl.len() {
+            cell.connect(io.spare_pu_ctl[i], vss);
+            cell.connect(io.spare_pd_ctlb[i], vdd);
+        }
+        cell.connect(io.vdd, vdd);
+        cell.connect(io.vss, vss);
+    }
+}
+
+impl StandbyIo<DriverStandby> for DriverWithGuardRingRailsIo {
+    fn hold<SC: Schema>(
+        state: DriverStandby,
+        cell: &mut CellBuilder<SC>,
+        io: &Bundle<Self>,
+        vdd: Node,
+        vss: Node,
+    ) {
+        let (pu_ctl, pd_ctlb, din) = state.levels();
+        cell.connect(io.din, din.node(vdd, vss));
+        for i in 0..io.pu_ctl.len() {
+            cell.connect(io.pu_ctl[i], pu_ctl.node(vdd, vss));
+            cell.connect(io.pd_ctlb[i], pd_ctlb.node(vdd, vss));
+        }
+        for i in 0..io.spare_pu_ctl.len() {
+            cell.connect(io.spare_pu_ctl[i], vss);
+            cell.connect(io.spare_pd_ctlb[i], vdd);
+        }
+        cell.connect(io.vdd, vdd);
+        cell.connect(io.vss, vss);
+        cell.connect(io.guard_ring_vdd, vdd);
+        cell.connect(io.guard_ring_vss, vss);
+    }
+}
+
+/// A static state of a clocked comparator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ComparatorStandby {
+    /// The level of the clock.
+    pub clock: Level,
+    /// The level of both inputs.
+    pub input: Level,
+}
+
+impl ComparatorStandby {
+    /// The clock held in its reset phase with the inputs at the rail that turns the
+    /// input pair on, which gives the largest leakage through the tail device.
+    pub fn clock_idle(kind: InputKind) -> Self {
+        match kind {
+            InputKind::N => Self {
+                clock: Level::Low,
+                input: Level::High,
+            },
+            InputKind::P => Self {
+                clock: Level::High,
+                input: Level::Low,
+            },
+        }
+    }
+}
+
+impl StandbyIo<ComparatorStandby> for ClockedDiffComparatorIo {
+    fn hold<SC: Schema>(
+        state: ComparatorStandby,
+        cell: &mut CellBuilder<SC>,
+        io: &Bundle<Self>,
+        vdd: Node,
+        vss: Node,
+    ) {
+        let input = state.input.node(vdd, vss);
+        cell.connect(io.input.p, input);
+        cell.connect(io.input.n, input);
+        cell.connect(io.clock, state.clock.node(vdd, vss));
+        cell.connect(io.vdd, vdd);
+        cell.connect(io.vss, vss);
+    }
+}
+
+/// A transient testbench that holds a block in a static control state and measures
+/// the current drawn from its supply once it has settled.
+///
+/// Controls are tied directly to the supply, so gate leakage of the control inputs is
+/// included in the measurement.
+#[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq; T, S, C)]
+#[derive(Serialize, Deserialize)]
+pub struct LeakageTb<T, S, PDK, C> {
+    /// The device-under-test.
+    pub dut: T,
+    /// The static control state.
+    pub state: S,
+    /// The time allowed for internal nodes to settle, in seconds.
+    pub settle: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, S, PDK, C> LeakageTb<T, S, PDK, C> {
+    /// Creates a new [`LeakageTb`] that settles for 1 us.
+    pub fn new(dut: T, state: S, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            state,
+            settle: dec!(1e-6),
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the time allowed for internal nodes to settle.
+    pub fn with_settle(mut self, settle: Decimal) -> Self {
+        self.settle = settle;
+        self
+    }
+}
+
+impl<
+        T: Block,
+        S: Serialize + DeserializeOwned + Clone + Debug + Hash + PartialEq + Eq + Send + Sync + Any,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for LeakageTb<T, S, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("leakage_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("leakage_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`LeakageTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct LeakageTbNodes {
+    vdd_src: Terminal,
+}
+
+impl<T, S, PDK, C> ExportsNestedData for LeakageTb<T, S, PDK, C>
+where
+    LeakageTb<T, S, PDK, C>: Block,
+{
+    type NestedData = LeakageTbNodes;
+}
+
+impl<T: Block + Schematic<PDK> + Clone, S: Copy, PDK: Schema, C> Schematic<Spectre>
+    for LeakageTb<T, S, PDK, C>
+where
+    LeakageTb<T, S, PDK, C>: Block<Io = TestbenchIo>,
+    T::Io: StandbyIo<S>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vdd = cell.signal("vdd", Signal);
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
+        <T::Io as StandbyIo<S>>::hold(self.state, cell, dut.io(), vdd, io.vss);
+
+        let vdd_src = cell.instantiate(Vsource::dc(self.pvt.voltage));
+        cell.connect(vdd_src.io().p, vdd);
+        cell.connect(vdd_src.io().n, io.vss);
+
+        Ok(LeakageTbNodes {
+            vdd_src: vdd_src.io().p,
+        })
+    }
+}
+
+/// The resulting waveforms of a [`LeakageTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct LeakageSim {
+    /// The simulation time.
+    pub t: tran::Time,
+    /// The current into the supply source.
+    pub idd: tran::Current,
+}
+
+impl<T, S, PDK, C> SaveTb<Spectre, Tran, LeakageSim> for LeakageTb<T, S, PDK, C>
+where
+    LeakageTb<T, S, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <LeakageSim as FromSaved<Spectre, Tran>>::SavedKey {
+        LeakageSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            idd: tran::Current::save(ctx, &cell.vdd_src, opts),
+        }
+    }
+}
+
+impl<T, S, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for LeakageTb<T, S, PDK, C>
+where
+    LeakageTb<T, S, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = Leakage;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: LeakageSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: self.settle,
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+        // The supply source delivers current out of its positive terminal.
+        let idd = -wav.idd.last().copied().unwrap_or_default();
+        Leakage::new(idd, self.pvt.voltage.to_f64().unwrap())
+    }
+}
+
+/// The settled supply current of a block in a static state.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Leakage {
+    /// The supply current, in amperes.
+    pub idd: f64,
+    /// The supply power, in watts.
+    pub power: f64,
+}
+
+impl Leakage {
+    /// The leakage of a block drawing `idd` from a supply at `vdd`.
+    pub fn new(idd: f64, vdd: f64) -> Self {
+        Self {
+            idd,
+            power: idd * vdd,
+        }
+    }
+}
+
+impl SimArtifact for Leakage {
+    fn csv_header(&self) -> Vec<String> {
+        ["idd", "power"].map(String::from).to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        vec![vec![self.idd.to_string(), self.power.to_string()]]
+    }
+}
+
+/// A low-power state of the link.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum PowerState {
+    /// The L1 standby state, exited without retraining.
+    L1,
+    /// The L2 sleep state.
+    L2,
+}
+
+impl PowerState {
+    /// The name of the standby power spec of this state in a [`ComplianceReport`].
+    pub fn spec_name(&self) -> &'static str {
+        match self {
+            PowerState::L1 => "l1_standby_power",
+            PowerState::L2 => "l2_standby_power",
+        }
+    }
+}
+
+/// The worst-case leakage of a block across PVT corners.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeakageSummary {
+    /// The corner with the largest leakage.
+    pub worst_corner: String,
+    /// The supply voltage at the worst corner, in volts.
+    pub worst_voltage: f64,
+    /// The temperature at the worst corner, in degrees Celsius.
+    pub worst_temp: f64,
+    /// The largest leakage.
+    pub worst: Leakage,
+}
+
+impl LeakageSummary {
+    /// Summarizes leakage measured at a set of PVT corners, or returns `None` if there
+    /// are no measurements.
+    pub fn new<C: Debug>(points: impl IntoIterator<Item = (Pvt<C>, Leakage)>) -> Option<Self> {
+        points
+            .into_iter()
+            .max_by(|(_, a), (_, b)| a.power.total_cmp(&b.power))
+            .map(|(pvt, worst)| Self {
+                worst_corner: format!("{:?}", pvt.corner),
+                worst_voltage: pvt.voltage.to_f64().unwrap(),
+                worst_temp: pvt.temp.to_f64().unwrap(),
+                worst,
+            })
+    }
+
+    /// Summarizes the output of a corner sweep of [`LeakageTb`].
+    pub fn from_sweep<C: Copy + Debug + Hash + Eq>(
+        sweep: &CornerSweepOutput<C, Leakage>,
+    ) -> Option<Self> {
+        Self::new(sweep.outputs.iter().map(|(pvt, leakage)| (*pvt, *leakage)))
+    }
+
+    /// The worst-case standby power, as a measurement for the spec of `state`.
+    pub fn measurement(&self, state: PowerState) -> (&'static str, f64) {
+        (state.spec_name(), self.worst.power)
+    }
+
+    /// Checks the worst-case standby power against `max_power`, in watts, for `state`.
+    pub fn compliance(&self, state: PowerState, max_power: f64) -> ComplianceReport {
+        ComplianceReport::evaluate(
+            &[Spec::new(state.spec_name(), Limit::Max(max_power), "W")],
+            [self.measurement(state)],
+        )
+    }
+}
+
+impl SimArtifact for LeakageSummary {
+    fn csv_header(&self) -> Vec<String> {
+        ["corner", "voltage", "temp", "idd", "power"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.worst_corner.clone(),
+            self.worst_voltage.to_string(),
+            self.worst_temp.to_string(),
+            self.worst.idd.to_string(),
+            self.worst.power.to_string(),
+        ]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leakage_summary_finds_worst_corner() {
+        let pvt = |temp| Pvt {
+            corner: "tt",
+            voltage: dec!(1.8),
+            temp,
+        };
+        let summary = LeakageSummary::new([
+            (pvt(dec!(25)), Leakage::new(1e-9, 1.8)),
+            (pvt(dec!(125)), Leakage::new(40e-9, 1.8)),
+            (pvt(dec!(-40)), Leakage::new(0.1e-9, 1.8)),
+        ])
+        .unwrap();
+        assert_eq!(summary.worst_temp, 125.);
+        assert!((summary.worst.power - 72e-9).abs() < 1e-18);
+
+        assert!(summary.compliance(PowerState::L1, 100e-9).passed());
+        assert!(!summary.compliance(PowerState::L2, 50e-9).passed());
+        assert_eq!(LeakageSummary::new::<&str>([]), None);
+    }
+}
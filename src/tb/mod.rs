@@ -0,0 +1,216 @@
+//! Options and testbenches shared by multiple blocks.
+
+pub mod leakage;
+pub mod mpi;
+pub mod pi;
+pub mod probe;
+pub mod psrr;
+pub mod pulse;
+pub mod resistor;
+pub mod sequencing;
+pub mod skew;
+pub mod ssc;
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::Spectre;
+use std::f64::consts::PI;
+use substrate::simulation::Simulator;
+
+use crate::report::SimArtifact;
+
+/// The number of points per period used to approximate a sinusoidal supply disturbance.
+const SINE_POINTS_PER_PERIOD: u32 = 32;
+
+/// Transient noise options.
+///
+/// Only supported by Spectre.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimNoiseOptions {
+    /// Whether to enable transient noise.
+    pub enable: bool,
+    /// The maximum noise frequency, in Hz.
+    ///
+    /// Noise above this frequency is not modeled.
+    /// The simulator time step is limited to roughly `1 / fmax`.
+    pub fmax: Decimal,
+    /// The noise seed.
+    ///
+    /// If [`None`], the simulator's default seed is used.
+    pub seed: Option<u64>,
+}
+
+impl Default for SimNoiseOptions {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            fmax: dec!(100e9),
+            seed: None,
+        }
+    }
+}
+
+impl SimNoiseOptions {
+    /// Creates a new [`SimNoiseOptions`] with transient noise enabled up to `fmax`.
+    pub fn enabled(fmax: Decimal) -> Self {
+        Self {
+            enable: true,
+            fmax,
+            seed: None,
+        }
+    }
+
+    /// Sets the noise seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the transient noise parameters of `tran`.
+    ///
+    /// Returns `tran` unchanged if noise is disabled.
+    pub fn apply(&self, tran: Tran) -> Tran {
+        if !self.enable {
+            return tran;
+        }
+        Tran {
+            noisefmax: Some(self.fmax),
+            noiseseed: self.seed,
+            ..tran
+        }
+    }
+}
+
+/// A simulator that supports Monte Carlo samples and transient noise.
+///
+/// Only Spectre implements this trait. [`MonteCarlo::run`](crate::sweep::MonteCarlo::run)
+/// and the testbench methods that add Monte Carlo samples or transient noise require it,
+/// so requesting either analysis from another simulator fails to compile.
+pub trait StatisticalSimulator: Simulator {}
+
+impl StatisticalSimulator for Spectre {}
+
+/// An error produced when a testbench is moved to a simulator that does not support
+/// one of its analyses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum UnsupportedAnalysisError {
+    /// The testbench simulates a Monte Carlo sample.
+    #[error("Monte Carlo simulation is only supported by Spectre")]
+    MonteCarlo,
+    /// The testbench enables transient noise.
+    #[error("transient noise is only supported by Spectre")]
+    TransientNoise,
+}
+
+/// A disturbance superimposed on a DC supply.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SupplyNoise {
+    /// A sinusoid starting at time zero.
+    Sine {
+        /// The peak deviation from the DC supply, in volts.
+        amplitude: Decimal,
+        /// The frequency, in Hz.
+        freq: Decimal,
+    },
+    /// A piecewise-linear waveform, given as `(time, voltage)` points added to the DC supply.
+    ///
+    /// The waveform holds its last value after the final point.
+    Pwl(Vec<(Decimal, Decimal)>),
+}
+
+impl SupplyNoise {
+    /// Creates a sinusoidal [`SupplyNoise`].
+    pub fn sine(amplitude: Decimal, freq: Decimal) -> Self {
+        Self::Sine { amplitude, freq }
+    }
+
+    /// An undisturbed supply.
+    pub fn none() -> Self {
+        Self::Pwl(vec![(Decimal::ZERO, Decimal::ZERO)])
+    }
+
+    /// The largest deviation from the DC supply, in volts.
+    pub fn amplitude(&self) -> f64 {
+        match self {
+            Self::Sine { amplitude, .. } => amplitude.to_f64().unwrap(),
+            Self::Pwl(points) => points
+                .iter()
+                .map(|(_, v)| v.to_f64().unwrap().abs())
+                .fold(0., f64::max),
+        }
+    }
+
+    /// Returns the `(time, voltage)` points of the disturbed supply from time zero to `stop`.
+    ///
+    /// Sinusoids are sampled at 32 points per period.
+    pub fn pwl(&self, dc: Decimal, stop: Decimal) -> Vec<(Decimal, Decimal)> {
+        match self {
+            Self::Sine { amplitude, freq } => {
+                let n = (stop * freq * Decimal::from(SINE_POINTS_PER_PERIOD))
+                    .ceil()
+                    .to_u32()
+                    .unwrap()
+                    .max(1);
+                let (amplitude, freq) = (amplitude.to_f64().unwrap(), freq.to_f64().unwrap());
+                (0..=n)
+                    .map(|i| {
+                        let t = stop * Decimal::from(i) / Decimal::from(n);
+                        let v = amplitude * (2. * PI * freq * t.to_f64().unwrap()).sin();
+                        (t, dc + Decimal::from_f64(v).unwrap().round_dp(9))
+                    })
+                    .collect()
+            }
+            Self::Pwl(points) => points.iter().map(|&(t, v)| (t, dc + v)).collect(),
+        }
+    }
+}
+
+/// The timing sensitivity of a block to a sinusoidal supply disturbance at one frequency.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SupplySensitivityPoint {
+    /// The disturbance frequency, in Hz.
+    pub freq: f64,
+    /// The peak deviation of the disturbance, in volts.
+    pub amplitude: f64,
+    /// The peak-to-peak output jitter, in seconds.
+    pub jitter: f64,
+}
+
+impl SupplySensitivityPoint {
+    /// The peak-to-peak jitter per volt of peak-to-peak supply disturbance, in seconds per volt.
+    pub fn sensitivity(&self) -> f64 {
+        self.jitter / (2. * self.amplitude)
+    }
+}
+
+/// A supply-sensitivity transfer curve.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SupplySensitivity {
+    /// The measured points, in order of increasing frequency.
+    pub points: Vec<SupplySensitivityPoint>,
+}
+
+impl SimArtifact for SupplySensitivity {
+    fn csv_header(&self) -> Vec<String> {
+        ["freq", "amplitude", "jitter", "sensitivity"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.points
+            .iter()
+            .map(|p| {
+                vec![
+                    p.freq.to_string(),
+                    p.amplitude.to_string(),
+                    p.jitter.to_string(),
+                    p.sensitivity().to_string(),
+                ]
+            })
+            .collect()
+    }
+}
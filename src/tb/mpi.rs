@@ -0,0 +1,144 @@
+//! Running Xyce under MPI.
+//!
+//! The Xyce backend launches the `Xyce` executable found on the `PATH` once per
+//! simulation. [`XyceMpi`] writes a launcher of that name that runs a parallel build of
+//! Xyce under `mpirun`, so that each simulation is split across several processes.
+//! Sweeps still run their simulations concurrently, so a sweep with a concurrency of
+//! `n` uses up to `n * processes` MPI processes.
+
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The name of the executable run by the Xyce backend.
+const XYCE: &str = "Xyce";
+
+/// Options for running a parallel build of Xyce under MPI.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XyceMpi {
+    /// The number of MPI processes per simulation.
+    pub processes: usize,
+    /// The parallel Xyce executable.
+    pub xyce: PathBuf,
+    /// The MPI launcher.
+    pub mpirun: PathBuf,
+}
+
+impl XyceMpi {
+    /// Creates a new [`XyceMpi`] that runs `xyce` on `processes` processes using the
+    /// `mpirun` on the `PATH`.
+    pub fn new(processes: usize, xyce: impl Into<PathBuf>) -> Self {
+        Self {
+            processes,
+            xyce: xyce.into(),
+            mpirun: PathBuf::from("mpirun"),
+        }
+    }
+
+    /// Sets the MPI launcher.
+    pub fn with_mpirun(mut self, mpirun: impl Into<PathBuf>) -> Self {
+        self.mpirun = mpirun.into();
+        self
+    }
+
+    /// The command that runs Xyce under MPI, to which the launcher appends the
+    /// arguments passed by the Xyce backend.
+    pub fn command(&self) -> Vec<String> {
+        vec![
+            self.mpirun.display().to_string(),
+            "-np".to_string(),
+            self.processes.to_string(),
+            self.xyce.display().to_string(),
+        ]
+    }
+
+    /// Writes an executable launcher named `Xyce` into `dir`, returning its path.
+    ///
+    /// Fails if the process count is zero or if `xyce` is itself a launcher in `dir`,
+    /// which would run the launcher recursively.
+    pub fn write_launcher(&self, dir: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let dir = dir.as_ref();
+        let path = dir.join(XYCE);
+        if self.processes == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the MPI process count must be positive",
+            ));
+        }
+        if self.xyce == path || self.xyce == Path::new(XYCE) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the parallel Xyce executable must not resolve to the launcher",
+            ));
+        }
+        let command = self
+            .command()
+            .iter()
+            .map(|arg| shell_quote(arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(&path, format!("#!/bin/sh\nexec {command} \"$@\"\n"))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(path)
+    }
+
+    /// Writes the launcher into `dir` and puts `dir` first on the `PATH` of this
+    /// process, so that every later Xyce simulation runs under MPI.
+    ///
+    /// Should be called before simulations start, since it modifies the environment of
+    /// the whole process.
+    pub fn install(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        self.write_launcher(dir)?;
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let path = std::env::join_paths(
+            std::iter::once(dir.to_path_buf()).chain(std::env::split_paths(&path)),
+        )
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        std::env::set_var("PATH", path);
+        Ok(())
+    }
+}
+
+/// Quotes `arg` for a POSIX shell.
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_mpi_launcher() {
+        let dir = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/writes_mpi_launcher"
+        ));
+        let mpi = XyceMpi::new(4, "/opt/xyce parallel/bin/Xyce").with_mpirun("/usr/bin/mpirun");
+        let path = mpi.write_launcher(&dir).unwrap();
+        assert_eq!(path, dir.join("Xyce"));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "#!/bin/sh\nexec '/usr/bin/mpirun' '-np' '4' '/opt/xyce parallel/bin/Xyce' \"$@\"\n"
+        );
+
+        assert!(XyceMpi::new(0, "/opt/xyce/bin/Xyce")
+            .write_launcher(&dir)
+            .is_err());
+        assert!(XyceMpi::new(4, "Xyce").write_launcher(&dir).is_err());
+        assert!(XyceMpi::new(4, dir.join("Xyce"))
+            .write_launcher(&dir)
+            .is_err());
+    }
+
+    #[test]
+    fn quotes_shell_arguments() {
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+}
@@ -0,0 +1,374 @@
+//! Phase interpolator characterization.
+//!
+//! These functions analyze sampled waveforms from any phase interpolator testbench:
+//! the output clock at each static code, and the output across each code transition.
+//! Phases and linearity errors are expressed in unit intervals (UI).
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::report::{ArtifactMetadata, ArtifactPaths, SimArtifact};
+use crate::spec::{ComplianceReport, Limit, Spec};
+
+/// A sampled waveform.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Samples<'a> {
+    /// The sample times, in seconds.
+    pub t: &'a [f64],
+    /// The sampled voltages.
+    pub v: &'a [f64],
+}
+
+impl Samples<'_> {
+    /// Returns the times at which the waveform crosses `threshold`,
+    /// each paired with `true` for a rising crossing.
+    pub fn crossings(&self, threshold: f64) -> Vec<(f64, bool)> {
+        self.t
+            .windows(2)
+            .zip(self.v.windows(2))
+            .filter(|(_, v)| (v[0] < threshold) != (v[1] < threshold))
+            .map(|(t, v)| {
+                let t = t[0] + (t[1] - t[0]) * (threshold - v[0]) / (v[1] - v[0]);
+                (t, v[1] > v[0])
+            })
+            .collect()
+    }
+
+    /// Returns the voltage at time `t`, interpolating linearly between samples.
+    ///
+    /// Times outside the simulated range return the first or last sample.
+    pub fn value_at(&self, t: f64) -> f64 {
+        let i = self.t.partition_point(|&ti| ti < t);
+        if i == 0 {
+            return self.v[0];
+        }
+        if i == self.t.len() {
+            return self.v[self.v.len() - 1];
+        }
+        let (t0, t1) = (self.t[i - 1], self.t[i]);
+        let (v0, v1) = (self.v[i - 1], self.v[i]);
+        v0 + (v1 - v0) * (t - t0) / (t1 - t0)
+    }
+}
+
+/// The output of the phase interpolator at a single code.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PiCodeMeasurement {
+    /// The interpolator code.
+    pub code: i64,
+    /// The delay of the output rising edge after the reference rising edge, in UI.
+    ///
+    /// Wrapped to lie within one clock period.
+    pub phase: f64,
+    /// The fraction of each period the output is high.
+    pub duty_cycle: f64,
+}
+
+impl PiCodeMeasurement {
+    /// Measures the output clock `out` against the reference clock `reference`.
+    ///
+    /// `period` is the clock period in seconds, and `ui` is the unit interval in seconds.
+    /// Returns [`None`] if the output does not toggle.
+    pub fn measure(
+        code: i64,
+        reference: Samples<'_>,
+        out: Samples<'_>,
+        threshold: f64,
+        period: f64,
+        ui: f64,
+    ) -> Option<Self> {
+        let ref_rising = reference
+            .crossings(threshold)
+            .into_iter()
+            .filter_map(|(t, rising)| rising.then_some(t))
+            .collect::<Vec<_>>();
+        let out_edges = out.crossings(threshold);
+        let first_ref = *ref_rising.first()?;
+
+        let delays = out_edges
+            .iter()
+            .filter(|&&(t, rising)| rising && t >= first_ref)
+            .map(|&(t, _)| {
+                let prev = ref_rising.iter().rev().find(|&&r| r <= t).unwrap();
+                (t - prev).rem_euclid(period)
+            })
+            .collect::<Vec<_>>();
+        if delays.is_empty() {
+            return None;
+        }
+        // Average around the first delay so that delays near a period boundary do not
+        // average to the middle of the period.
+        let base = delays[0];
+        let delay = base
+            + delays
+                .iter()
+                .map(|d| (d - base + period / 2.).rem_euclid(period) - period / 2.)
+                .sum::<f64>()
+                / delays.len() as f64;
+
+        let high = out_edges
+            .windows(2)
+            .filter(|edges| edges[0].1 && !edges[1].1)
+            .map(|edges| edges[1].0 - edges[0].0)
+            .collect::<Vec<_>>();
+        let duty_cycle = high.iter().sum::<f64>() / high.len().max(1) as f64 / period;
+
+        Some(Self {
+            code,
+            phase: delay.rem_euclid(period) / ui,
+            duty_cycle,
+        })
+    }
+}
+
+/// Counts the pulses in `out` narrower than `min_width` seconds.
+///
+/// Apply this to the output across a code transition to detect glitches.
+pub fn count_glitches(out: Samples<'_>, threshold: f64, min_width: f64) -> usize {
+    out.crossings(threshold)
+        .windows(2)
+        .filter(|edges| edges[1].0 - edges[0].0 < min_width)
+        .count()
+}
+
+/// Limits on phase interpolator linearity.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PiLimits {
+    /// The maximum magnitude of the integral nonlinearity, in UI.
+    pub max_inl: f64,
+    /// The maximum magnitude of the differential nonlinearity, in UI.
+    pub max_dnl: f64,
+    /// The maximum deviation of the duty cycle from 50%.
+    pub max_duty_cycle_error: f64,
+    /// The maximum number of glitches across all code transitions.
+    pub max_glitches: usize,
+}
+
+/// The characterization of a phase interpolator across all codes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PiCharacterization {
+    /// The measurement at each code, in increasing code order.
+    pub codes: Vec<PiCodeMeasurement>,
+    /// The phase of each code, unwrapped to increase monotonically with code, in UI.
+    pub unwrapped_phase: Vec<f64>,
+    /// The integral nonlinearity of each code against an endpoint fit, in UI.
+    pub inl: Vec<f64>,
+    /// The differential nonlinearity of each code step, in UI.
+    ///
+    /// Entry `i` is the error of the step from code `i` to code `i + 1`.
+    pub dnl: Vec<f64>,
+    /// The number of glitches at each measured code transition, keyed by `(from, to)` code.
+    pub glitches: Vec<((i64, i64), usize)>,
+}
+
+impl PiCharacterization {
+    /// Computes the linearity of `codes`, which must span less than one clock period.
+    ///
+    /// `period_ui` is the clock period in UI, used to unwrap phases.
+    pub fn new(
+        mut codes: Vec<PiCodeMeasurement>,
+        period_ui: f64,
+        glitches: Vec<((i64, i64), usize)>,
+    ) -> Self {
+        codes.sort_by_key(|m| m.code);
+        let mut unwrapped_phase: Vec<f64> = Vec::with_capacity(codes.len());
+        for m in codes.iter() {
+            let phase = match unwrapped_phase.last() {
+                Some(&prev) => {
+                    prev + (m.phase - prev + period_ui / 2.).rem_euclid(period_ui) - period_ui / 2.
+                }
+                None => m.phase,
+            };
+            unwrapped_phase.push(phase);
+        }
+
+        let n = unwrapped_phase.len();
+        let (inl, dnl) = if n < 2 {
+            (vec![0.; n], Vec::new())
+        } else {
+            let lsb = (unwrapped_phase[n - 1] - unwrapped_phase[0]) / (n - 1) as f64;
+            let inl = unwrapped_phase
+                .iter()
+                .enumerate()
+                .map(|(i, p)| p - (unwrapped_phase[0] + i as f64 * lsb))
+                .collect();
+            let dnl = unwrapped_phase
+                .windows(2)
+                .map(|w| w[1] - w[0] - lsb)
+                .collect();
+            (inl, dnl)
+        };
+
+        Self {
+            codes,
+            unwrapped_phase,
+            inl,
+            dnl,
+            glitches,
+        }
+    }
+
+    /// The largest INL magnitude, in UI.
+    pub fn max_inl(&self) -> f64 {
+        self.inl.iter().fold(0., |max, x| f64::max(max, x.abs()))
+    }
+
+    /// The largest DNL magnitude, in UI.
+    pub fn max_dnl(&self) -> f64 {
+        self.dnl.iter().fold(0., |max, x| f64::max(max, x.abs()))
+    }
+
+    /// The largest deviation of any code's duty cycle from 50%.
+    pub fn max_duty_cycle_error(&self) -> f64 {
+        self.codes
+            .iter()
+            .fold(0., |max, m| f64::max(max, (m.duty_cycle - 0.5).abs()))
+    }
+
+    /// The total number of glitches across all measured code transitions.
+    pub fn total_glitches(&self) -> usize {
+        self.glitches.iter().map(|(_, n)| n).sum()
+    }
+
+    /// Checks the characterization against `limits`.
+    pub fn compliance(&self, limits: &PiLimits) -> ComplianceReport {
+        ComplianceReport::evaluate(
+            &[
+                Spec::new("pi_inl", Limit::Max(limits.max_inl), "UI"),
+                Spec::new("pi_dnl", Limit::Max(limits.max_dnl), "UI"),
+                Spec::new(
+                    "pi_duty_cycle_error",
+                    Limit::Max(limits.max_duty_cycle_error),
+                    "",
+                ),
+                Spec::new(
+                    "pi_transition_glitches",
+                    Limit::Max(limits.max_glitches as f64),
+                    "",
+                ),
+            ],
+            [
+                ("pi_inl", self.max_inl()),
+                ("pi_dnl", self.max_dnl()),
+                ("pi_duty_cycle_error", self.max_duty_cycle_error()),
+                ("pi_transition_glitches", self.total_glitches() as f64),
+            ],
+        )
+    }
+
+    /// Writes the per-code characterization and its compliance report against `limits`
+    /// to `dir`, with the report named `<name>_compliance`.
+    pub fn write_report(
+        &self,
+        dir: impl AsRef<Path>,
+        metadata: &ArtifactMetadata,
+        limits: &PiLimits,
+    ) -> std::io::Result<(ArtifactPaths, ArtifactPaths)> {
+        let dir = dir.as_ref();
+        let characterization = self.write_artifact(dir, metadata)?;
+        let compliance = self.compliance(limits).write_artifact(
+            dir,
+            &ArtifactMetadata {
+                name: format!("{}_compliance", metadata.name),
+                ..metadata.clone()
+            },
+        )?;
+        Ok((characterization, compliance))
+    }
+}
+
+impl SimArtifact for PiCharacterization {
+    fn csv_header(&self) -> Vec<String> {
+        [
+            "code",
+            "phase_ui",
+            "unwrapped_phase_ui",
+            "inl_ui",
+            "dnl_ui",
+            "duty_cycle",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.codes
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                vec![
+                    m.code.to_string(),
+                    m.phase.to_string(),
+                    self.unwrapped_phase[i].to_string(),
+                    self.inl[i].to_string(),
+                    self.dnl.get(i).map(|d| d.to_string()).unwrap_or_default(),
+                    m.duty_cycle.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A square wave with the given period, delay, and duty cycle, sampled every picosecond.
+    fn clock(period: f64, delay: f64, duty: f64) -> (Vec<f64>, Vec<f64>) {
+        (0..4_000)
+            .map(|i| {
+                let t = i as f64 * 1e-12;
+                let phase = (t - delay).rem_euclid(period) / period;
+                (t, if phase < duty { 1. } else { 0. })
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn measures_pi_linearity() {
+        // 8 codes across a 1 ns period (2 UI) with a 5 ps error at code 3.
+        let (period, ui) = (1e-9, 0.5e-9);
+        let (rt, rv) = clock(period, 0., 0.5);
+        let reference = Samples { t: &rt, v: &rv };
+        let codes = (0..8)
+            .map(|code| {
+                let delay = code as f64 * 125e-12 + if code == 3 { 5e-12 } else { 0. };
+                let (t, v) = clock(period, delay + 600e-12, 0.5);
+                PiCodeMeasurement::measure(
+                    code,
+                    reference,
+                    Samples { t: &t, v: &v },
+                    0.5,
+                    period,
+                    ui,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let pi = PiCharacterization::new(codes, 2., vec![((3, 4), 1)]);
+
+        // The phases wrap past the period but unwrap to increase by 0.25 UI per code.
+        assert!(pi.codes[4].phase < pi.codes[3].phase);
+        assert!((pi.unwrapped_phase[7] - pi.unwrapped_phase[0] - 1.75).abs() < 0.01);
+        assert!((pi.max_inl() - 0.01).abs() < 0.003);
+        assert!((pi.max_dnl() - 0.01).abs() < 0.003);
+        assert!(pi.max_duty_cycle_error() < 0.01);
+
+        let limits = PiLimits {
+            max_inl: 0.02,
+            max_dnl: 0.02,
+            max_duty_cycle_error: 0.05,
+            max_glitches: 0,
+        };
+        let report = pi.compliance(&limits);
+        assert_eq!(report.failures().count(), 1);
+    }
+
+    #[test]
+    fn counts_narrow_pulses() {
+        let t = (0..10).map(|i| i as f64).collect::<Vec<_>>();
+        let v = [0., 0., 1., 0., 0., 1., 1., 1., 1., 0.];
+        assert_eq!(count_glitches(Samples { t: &t, v: &v }, 0.5, 2.), 1);
+    }
+}
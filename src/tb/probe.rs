@@ -0,0 +1,172 @@
+//! Named internal probe points.
+//!
+//! A generator makes internal nodes observable by exporting [`Probes`] as its nested
+//! data and listing their names in [`ProbePoints::PROBES`]. A [`ProbeRequest`] names
+//! the probe points to save, and resolves against the probe points of a DUT to a
+//! [`ProbeSet`] that testbenches take. Testbenches save the selected nodes alongside
+//! their own outputs, so that new internal nodes can be inspected without changing the
+//! testbench.
+
+use atoll::TileWrapper;
+use serde::{Deserialize, Serialize};
+use substrate::io::schematic::Node;
+use substrate::schematic::{ExportsNestedData, NestedData};
+
+/// A block with named internal probe points.
+pub trait ProbePoints: ExportsNestedData<NestedData = Probes> {
+    /// The names of the probe points, in the order of [`Probes::nodes`].
+    ///
+    /// A block has at most [`ProbeSet::CAPACITY`] probe points.
+    const PROBES: &'static [&'static str];
+}
+
+impl<T: ProbePoints> ProbePoints for TileWrapper<T>
+where
+    TileWrapper<T>: ExportsNestedData<NestedData = Probes>,
+{
+    const PROBES: &'static [&'static str] = T::PROBES;
+}
+
+/// The internal nodes exported by a block implementing [`ProbePoints`].
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, NestedData)]
+pub struct Probes {
+    /// The probed nodes, in the order of [`ProbePoints::PROBES`].
+    pub nodes: Vec<Node>,
+}
+
+impl Probes {
+    /// Creates the probe points of block `B` from `(name, node)` pairs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the names do not match [`ProbePoints::PROBES`] of `B`, in order,
+    /// or if there are more than [`ProbeSet::CAPACITY`] probe points.
+    pub fn new<B: ProbePoints>(probes: impl IntoIterator<Item = (&'static str, Node)>) -> Self {
+        let (names, nodes): (Vec<_>, Vec<_>) = probes.into_iter().unzip();
+        assert_eq!(
+            names,
+            B::PROBES,
+            "probe points do not match their declaration"
+        );
+        assert!(nodes.len() <= ProbeSet::CAPACITY, "too many probe points");
+        Self { nodes }
+    }
+}
+
+/// A set of probe points of a block, identified by their index in
+/// [`ProbePoints::PROBES`].
+///
+/// Testbenches store probe points in this form since it is [`Copy`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct ProbeSet(u64);
+
+impl ProbeSet {
+    /// The maximum number of probe points of a block.
+    pub const CAPACITY: usize = u64::BITS as usize;
+
+    /// Returns the set of every probe point of block `B`.
+    pub fn all<B: ProbePoints>() -> Self {
+        Self::from_indices(0..B::PROBES.len())
+    }
+
+    fn from_indices(indices: impl IntoIterator<Item = usize>) -> Self {
+        Self(indices.into_iter().fold(0, |bits, i| bits | (1 << i)))
+    }
+
+    /// Returns `true` if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the indices of the probe points in the set, in increasing order.
+    pub fn indices(&self) -> impl Iterator<Item = usize> {
+        let bits = self.0;
+        (0..Self::CAPACITY).filter(move |i| bits & (1 << i) != 0)
+    }
+
+    /// Returns the names of the probe points of block `B` in the set.
+    pub fn names<B: ProbePoints>(&self) -> Vec<&'static str> {
+        self.indices().map(|i| B::PROBES[i]).collect()
+    }
+}
+
+/// An error produced when resolving a [`ProbeRequest`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ProbeError {
+    /// The DUT has no probe point with the requested name.
+    #[error("unknown probe {name:?}; available probes are {available:?}")]
+    Unknown {
+        /// The requested name.
+        name: String,
+        /// The probe points of the DUT.
+        available: Vec<&'static str>,
+    },
+}
+
+/// The names of the probe points a testbench should save.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct ProbeRequest {
+    /// The requested probe point names.
+    pub names: Vec<String>,
+}
+
+impl ProbeRequest {
+    /// Requests the probe points named `names`.
+    pub fn new(names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            names: names.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Returns `true` if no probe points are requested.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Returns the set of requested probe points of block `B`.
+    pub fn resolve<B: ProbePoints>(&self) -> Result<ProbeSet, ProbeError> {
+        self.resolve_in(B::PROBES)
+    }
+
+    /// Returns the set of requested probe points, given the names of all probe points.
+    pub fn resolve_in(&self, available: &[&'static str]) -> Result<ProbeSet, ProbeError> {
+        let indices = self
+            .names
+            .iter()
+            .map(|name| {
+                available
+                    .iter()
+                    .position(|probe| probe == name)
+                    .ok_or_else(|| ProbeError::Unknown {
+                        name: name.clone(),
+                        available: available.to_vec(),
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ProbeSet::from_indices(indices))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_probe_names() {
+        let available = ["intn", "intp", "tail"];
+        let request = ProbeRequest::new(["tail", "intn"]);
+        let probes = request.resolve_in(&available).unwrap();
+        assert_eq!(probes.indices().collect::<Vec<_>>(), [0, 2]);
+        assert!(ProbeRequest::default()
+            .resolve_in(&available)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            ProbeRequest::new(["intx"]).resolve_in(&available),
+            Err(ProbeError::Unknown {
+                name: "intx".to_string(),
+                available: available.to_vec(),
+            })
+        );
+    }
+}
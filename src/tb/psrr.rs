@@ -0,0 +1,336 @@
+//! Power supply rejection testbenches.
+
+use ngspice::Ngspice;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::ac::{Ac, Sweep};
+use spectre::blocks::{AcSource, Vsource};
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Node};
+use substrate::io::{
+    InOut, Io, Output, Signal, TestbenchIo, TwoTerminalIo, TwoTerminalIoSchematic,
+};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{ac, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::report::SimArtifact;
+
+/// The interface to a block that produces an output voltage from its supply,
+/// such as an LDO, bandgap, or reference DAC.
+///
+/// Blocks with additional inputs can be wrapped so that those inputs are tied
+/// to fixed values.
+#[derive(Debug, Default, Clone, Io)]
+pub struct ReferenceIo {
+    /// The output voltage.
+    pub vout: Output<Signal>,
+    /// The VDD rail.
+    pub vdd: InOut<Signal>,
+    /// The VSS rail.
+    pub vss: InOut<Signal>,
+}
+
+/// A small-signal testbench that applies an AC source on the supply of a DUT
+/// and measures its output from 1 kHz to 10 GHz.
+///
+/// The testbench runs on the simulator `S`, which defaults to [`Spectre`].
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct PsrrTb<T, PDK, C, S = Spectre> {
+    /// The device-under-test.
+    pub dut: T,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> (PDK, S)>,
+}
+
+impl<T, PDK, C> PsrrTb<T, PDK, C> {
+    /// Creates a new [`PsrrTb`].
+    pub fn new(dut: T, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, PDK, C, S> PsrrTb<T, PDK, C, S> {
+    /// Runs this testbench on the simulator `S2` instead.
+    pub fn with_simulator<S2>(self) -> PsrrTb<T, PDK, C, S2> {
+        PsrrTb {
+            dut: self.dut,
+            pvt: self.pvt,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+        S: Any,
+    > Block for PsrrTb<T, PDK, C, S>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("psrr_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("psrr_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`PsrrTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct PsrrTbNodes {
+    vout: Node,
+}
+
+impl<T, PDK, C, S> ExportsNestedData for PsrrTb<T, PDK, C, S>
+where
+    PsrrTb<T, PDK, C, S>: Block,
+{
+    type NestedData = PsrrTbNodes;
+}
+
+impl<T: Block<Io = ReferenceIo> + Schematic<PDK> + Clone, PDK: Schema, C: Copy, S>
+    PsrrTb<T, PDK, C, S>
+{
+    /// Instantiates the DUT, powered by `vsupply`.
+    fn schematic_with_supply<SC, V>(
+        &self,
+        vss: Node,
+        cell: &mut CellBuilder<SC>,
+        vsupply: V,
+    ) -> PsrrTbNodes
+    where
+        SC: Schema + FromSchema<PDK>,
+        V: Block<Io = TwoTerminalIo> + Schematic<SC>,
+    {
+        let vdd = cell.signal("vdd", Signal);
+        let vout = cell.signal("vout", Signal);
+
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
+        cell.connect(dut.io().vdd, vdd);
+        cell.connect(dut.io().vss, vss);
+        cell.connect(dut.io().vout, vout);
+
+        cell.instantiate_connected(vsupply, TwoTerminalIoSchematic { p: vdd, n: vss });
+
+        PsrrTbNodes { vout }
+    }
+}
+
+impl<T: Block<Io = ReferenceIo> + Schematic<PDK> + Clone, PDK: Schema, C: Copy> Schematic<Spectre>
+    for PsrrTb<T, PDK, C>
+where
+    PsrrTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        Ok(self.schematic_with_supply(
+            io.vss,
+            cell,
+            Vsource::ac(AcSource {
+                dc: self.pvt.voltage,
+                mag: dec!(1),
+                phase: dec!(0),
+            }),
+        ))
+    }
+}
+
+impl<T: Block<Io = ReferenceIo> + Schematic<PDK> + Clone, PDK: Schema, C: Copy> Schematic<Ngspice>
+    for PsrrTb<T, PDK, C, Ngspice>
+where
+    PsrrTb<T, PDK, C, Ngspice>: Block<Io = TestbenchIo>,
+    Ngspice: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Ngspice>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        Ok(self.schematic_with_supply(
+            io.vss,
+            cell,
+            ngspice::blocks::Vsource::ac(ngspice::blocks::AcSource {
+                dc: self.pvt.voltage,
+                mag: dec!(1),
+                phase: dec!(0),
+            }),
+        ))
+    }
+}
+
+/// The resulting waveforms of a [`PsrrTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct PsrrSim {
+    /// The simulation frequency.
+    pub freq: ac::Freq,
+    /// The output voltage.
+    pub vout: ac::Voltage,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Ac, PsrrSim> for PsrrTb<T, PDK, C>
+where
+    PsrrTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <PsrrSim as FromSaved<Spectre, Ac>>::SavedKey {
+        PsrrSimSavedKey {
+            freq: ac::Freq::save(ctx, (), opts),
+            vout: ac::Voltage::save(ctx, &cell.vout, opts),
+        }
+    }
+}
+
+impl<T, PDK, C> SaveTb<Ngspice, ngspice::ac::Ac, PsrrSim> for PsrrTb<T, PDK, C, Ngspice>
+where
+    PsrrTb<T, PDK, C, Ngspice>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Ngspice>,
+        cell: &Cell<Self>,
+        opts: &mut <Ngspice as Simulator>::Options,
+    ) -> <PsrrSim as FromSaved<Ngspice, ngspice::ac::Ac>>::SavedKey {
+        PsrrSimSavedKey {
+            freq: ac::Freq::save(ctx, (), opts),
+            vout: ac::Voltage::save(ctx, &cell.vout, opts),
+        }
+    }
+}
+
+/// The power supply rejection of a block across frequency.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Psrr {
+    /// The frequency vector, in Hz.
+    pub freq: Vec<f64>,
+    /// The supply rejection at each frequency, in dB.
+    ///
+    /// Positive values indicate that supply disturbances are attenuated at the output.
+    pub psrr: Vec<f64>,
+}
+
+impl Psrr {
+    fn from_sim(sim: &PsrrSim) -> Self {
+        Self {
+            freq: sim.freq.to_vec(),
+            psrr: sim.vout.iter().map(|v| -20. * v.norm().log10()).collect(),
+        }
+    }
+
+    /// Returns the lowest supply rejection at or below `fmax`, in dB, and the frequency at
+    /// which it occurs.
+    pub fn worst(&self, fmax: f64) -> Option<(f64, f64)> {
+        self.freq
+            .iter()
+            .zip(self.psrr.iter())
+            .filter(|(&f, _)| f <= fmax)
+            .map(|(&f, &psrr)| (f, psrr))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+}
+
+impl SimArtifact for Psrr {
+    fn csv_header(&self) -> Vec<String> {
+        vec!["freq".to_string(), "psrr_db".to_string()]
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.freq
+            .iter()
+            .zip(self.psrr.iter())
+            .map(|(f, psrr)| vec![f.to_string(), psrr.to_string()])
+            .collect()
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for PsrrTb<T, PDK, C>
+where
+    PsrrTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = Psrr;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let sim: PsrrSim = sim
+            .simulate(
+                opts,
+                Ac {
+                    start: dec!(1e3),
+                    stop: dec!(10e9),
+                    sweep: Sweep::Decade(20),
+                    errpreset: Some(ErrPreset::Conservative),
+                },
+            )
+            .expect("failed to run simulation");
+        Psrr::from_sim(&sim)
+    }
+}
+
+impl<T, PDK, C: SimOption<Ngspice> + Copy> Testbench<Ngspice> for PsrrTb<T, PDK, C, Ngspice>
+where
+    PsrrTb<T, PDK, C, Ngspice>: Block<Io = TestbenchIo> + Schematic<Ngspice>,
+{
+    type Output = Psrr;
+
+    fn run(&self, sim: SimController<Ngspice, Self>) -> Self::Output {
+        let mut opts = ngspice::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let sim: PsrrSim = sim
+            .simulate(
+                opts,
+                ngspice::ac::Ac {
+                    start: dec!(1e3),
+                    stop: dec!(10e9),
+                    sweep: ngspice::ac::Sweep::Decade(20),
+                },
+            )
+            .expect("failed to run simulation");
+        Psrr::from_sim(&sim)
+    }
+}
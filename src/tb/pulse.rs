@@ -0,0 +1,434 @@
+//! Pulse response testbenches.
+//!
+//! [`PulseResponseTb`] sends a single one-UI pulse through a transmit lane, a lumped
+//! channel, and an optional passive CTLE, and samples the received waveform once per UI
+//! around its peak. The resulting cursors quantify the inter-symbol interference that
+//! FFE and DFE taps must cancel.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::{Pulse, Vsource};
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Node};
+use substrate::io::{Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::primitives::{Capacitor, Resistor};
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::lane::TxLaneIo;
+use crate::report::SimArtifact;
+use crate::tb::pi::Samples;
+use crate::tb::SimNoiseOptions;
+
+/// The number of UIs simulated before the pulse is launched.
+const LEAD_UI: i64 = 4;
+/// The number of UIs simulated after the last post-cursor.
+const TAIL_UI: i64 = 4;
+
+/// A lumped channel, modeled as an RC ladder terminated to VSS at the receiver.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ChannelModel {
+    /// The number of RC sections.
+    pub sections: usize,
+    /// The total series resistance, in ohms.
+    pub r: Decimal,
+    /// The total shunt capacitance, in farads.
+    pub c: Decimal,
+    /// The receiver termination resistance, in ohms.
+    pub r_term: Decimal,
+}
+
+/// A passive CTLE: a series resistor bypassed by a capacitor, followed by a shunt resistor.
+///
+/// The DC gain is `r_shunt / (r_series + r_shunt)`, and the zero sits at
+/// `1 / (2 pi r_series c_series)`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct PassiveCtle {
+    /// The series resistance, in ohms.
+    pub r_series: Decimal,
+    /// The capacitance across the series resistor, in farads.
+    pub c_series: Decimal,
+    /// The shunt resistance to VSS, in ohms.
+    pub r_shunt: Decimal,
+}
+
+/// A transient testbench that drives a single pulse through a transmit lane and a channel.
+#[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct PulseResponseTb<T, PDK, C> {
+    /// The transmit lane.
+    pub tx: T,
+    /// The channel.
+    pub channel: ChannelModel,
+    /// The CTLE at the receiver, if any.
+    pub ctle: Option<PassiveCtle>,
+    /// The unit interval, in seconds.
+    pub ui: Decimal,
+    /// The number of pre-cursors to sample.
+    pub pre_cursors: usize,
+    /// The number of post-cursors to sample.
+    pub post_cursors: usize,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+    /// Transient noise options.
+    pub noise: SimNoiseOptions,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> PulseResponseTb<T, PDK, C> {
+    /// Creates a new [`PulseResponseTb`] without a CTLE that samples one pre-cursor
+    /// and four post-cursors.
+    pub fn new(tx: T, channel: ChannelModel, ui: Decimal, pvt: Pvt<C>) -> Self {
+        Self {
+            tx,
+            channel,
+            ctle: None,
+            ui,
+            pre_cursors: 1,
+            post_cursors: 4,
+            pvt,
+            noise: SimNoiseOptions::default(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Adds a passive CTLE at the receiver.
+    pub fn with_ctle(mut self, ctle: PassiveCtle) -> Self {
+        self.ctle = Some(ctle);
+        self
+    }
+
+    /// Sets the number of pre- and post-cursors to sample.
+    pub fn with_cursors(mut self, pre_cursors: usize, post_cursors: usize) -> Self {
+        self.pre_cursors = pre_cursors;
+        self.post_cursors = post_cursors;
+        self
+    }
+
+    /// Sets the transient noise options.
+    pub fn with_noise(mut self, noise: SimNoiseOptions) -> Self {
+        self.noise = noise;
+        self
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for PulseResponseTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("pulse_response_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("pulse_response_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`PulseResponseTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct PulseResponseTbNodes {
+    rx: Node,
+}
+
+impl<T, PDK, C> ExportsNestedData for PulseResponseTb<T, PDK, C>
+where
+    PulseResponseTb<T, PDK, C>: Block,
+{
+    type NestedData = PulseResponseTbNodes;
+}
+
+impl<T: Block<Io = TxLaneIo> + Schematic<PDK> + Clone, PDK: Schema, C: Copy> Schematic<Spectre>
+    for PulseResponseTb<T, PDK, C>
+where
+    PulseResponseTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        assert!(self.channel.sections > 0, "channel must have a section");
+        let vss = io.vss;
+        let vdd = cell.signal("vdd", Signal);
+        let din = cell.signal("din", Signal);
+        let pad = cell.signal("pad", Signal);
+
+        cell.instantiate_connected(
+            Vsource::dc(self.pvt.voltage),
+            TwoTerminalIoSchematic { p: vdd, n: vss },
+        );
+        let edge = self.ui / dec!(10);
+        cell.instantiate_connected(
+            Vsource::pulse(Pulse {
+                val0: dec!(0),
+                val1: self.pvt.voltage,
+                period: None,
+                width: Some(self.ui - edge),
+                delay: Some(self.ui * Decimal::from(LEAD_UI)),
+                rise: Some(edge),
+                fall: Some(edge),
+            }),
+            TwoTerminalIoSchematic { p: din, n: vss },
+        );
+
+        let tx = cell.sub_builder::<PDK>().instantiate(self.tx.clone());
+        cell.connect(tx.io().din, din);
+        cell.connect(tx.io().dout, pad);
+        cell.connect(tx.io().vdd, vdd);
+        cell.connect(tx.io().vss, vss);
+
+        let sections = Decimal::from(self.channel.sections);
+        let mut prev = pad;
+        for i in 0..self.channel.sections {
+            let next = cell.signal(format!("ch{i}"), Signal);
+            cell.instantiate_connected(
+                Resistor::new(self.channel.r / sections),
+                TwoTerminalIoSchematic { p: prev, n: next },
+            );
+            cell.instantiate_connected(
+                Capacitor::new(self.channel.c / sections),
+                TwoTerminalIoSchematic { p: next, n: vss },
+            );
+            prev = next;
+        }
+        cell.instantiate_connected(
+            Resistor::new(self.channel.r_term),
+            TwoTerminalIoSchematic { p: prev, n: vss },
+        );
+
+        let rx = if let Some(ctle) = self.ctle {
+            let rx = cell.signal("rx", Signal);
+            cell.instantiate_connected(
+                Resistor::new(ctle.r_series),
+                TwoTerminalIoSchematic { p: prev, n: rx },
+            );
+            cell.instantiate_connected(
+                Capacitor::new(ctle.c_series),
+                TwoTerminalIoSchematic { p: prev, n: rx },
+            );
+            cell.instantiate_connected(
+                Resistor::new(ctle.r_shunt),
+                TwoTerminalIoSchematic { p: rx, n: vss },
+            );
+            rx
+        } else {
+            prev
+        };
+
+        Ok(PulseResponseTbNodes { rx })
+    }
+}
+
+/// The resulting waveforms of a [`PulseResponseTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct PulseResponseSim {
+    t: tran::Time,
+    rx: tran::Voltage,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, PulseResponseSim> for PulseResponseTb<T, PDK, C>
+where
+    PulseResponseTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <PulseResponseSim as FromSaved<Spectre, Tran>>::SavedKey {
+        PulseResponseSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            rx: tran::Voltage::save(ctx, cell.data().rx, opts),
+        }
+    }
+}
+
+/// A sampled single-bit response.
+///
+/// Cursor values are measured relative to the received voltage before the pulse arrives.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PulseResponse {
+    /// The unit interval, in seconds.
+    pub ui: f64,
+    /// The time of the main cursor, in seconds.
+    pub cursor_time: f64,
+    /// The main cursor.
+    pub cursor: f64,
+    /// The pre-cursors, nearest to the main cursor first.
+    pub pre: Vec<f64>,
+    /// The post-cursors, nearest to the main cursor first.
+    pub post: Vec<f64>,
+}
+
+impl PulseResponse {
+    /// Samples `wav` at its peak and at whole UIs before and after it.
+    pub fn measure(wav: Samples<'_>, ui: f64, pre_cursors: usize, post_cursors: usize) -> Self {
+        let baseline = wav.v[0];
+        let (peak, _) = wav
+            .v
+            .iter()
+            .enumerate()
+            .max_by(|a, b| (a.1 - baseline).abs().total_cmp(&(b.1 - baseline).abs()))
+            .expect("waveform must not be empty");
+        let cursor_time = wav.t[peak];
+        let sample = |k: f64| wav.value_at(cursor_time + k * ui) - baseline;
+        Self {
+            ui,
+            cursor_time,
+            cursor: sample(0.),
+            pre: (1..=pre_cursors).map(|k| sample(-(k as f64))).collect(),
+            post: (1..=post_cursors).map(|k| sample(k as f64)).collect(),
+        }
+    }
+
+    /// The taps from the earliest pre-cursor to the last post-cursor.
+    pub fn taps(&self) -> Vec<f64> {
+        self.pre
+            .iter()
+            .rev()
+            .copied()
+            .chain(std::iter::once(self.cursor))
+            .chain(self.post.iter().copied())
+            .collect()
+    }
+
+    /// The total magnitude of the pre- and post-cursors relative to the main cursor.
+    ///
+    /// This is the worst-case eye closure from inter-symbol interference, as a fraction
+    /// of the main cursor.
+    pub fn isi_ratio(&self) -> f64 {
+        let isi = self
+            .pre
+            .iter()
+            .chain(self.post.iter())
+            .map(|h| h.abs())
+            .sum::<f64>();
+        isi / self.cursor.abs()
+    }
+}
+
+impl SimArtifact for PulseResponse {
+    fn csv_header(&self) -> Vec<String> {
+        ["cursor", "time", "value"].map(String::from).to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        let offset = self.pre.len() as i64;
+        self.taps()
+            .into_iter()
+            .enumerate()
+            .map(|(i, h)| {
+                let k = i as i64 - offset;
+                vec![
+                    k.to_string(),
+                    (self.cursor_time + k as f64 * self.ui).to_string(),
+                    h.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for PulseResponseTb<T, PDK, C>
+where
+    PulseResponseTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = PulseResponse;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let uis = LEAD_UI + 1 + self.post_cursors as i64 + TAIL_UI;
+        let wav: PulseResponseSim = sim
+            .simulate(
+                opts,
+                self.noise.apply(Tran {
+                    stop: self.ui * Decimal::from(uis),
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                }),
+            )
+            .expect("failed to run simulation");
+
+        PulseResponse::measure(
+            Samples {
+                t: &wav.t,
+                v: &wav.rx,
+            },
+            self.ui.to_f64().unwrap(),
+            self.pre_cursors,
+            self.post_cursors,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_cursors_around_peak() {
+        // A triangular pulse peaking at 40 ps with a long linear tail.
+        let t = (0..=200).map(|i| i as f64 * 1e-12).collect::<Vec<_>>();
+        let v = t
+            .iter()
+            .map(|&t| {
+                if t < 20e-12 {
+                    0.1
+                } else if t < 40e-12 {
+                    0.1 + (t - 20e-12) / 20e-12
+                } else {
+                    0.1 + (1. - (t - 40e-12) / 100e-12).max(0.)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let pulse = PulseResponse::measure(Samples { t: &t, v: &v }, 20e-12, 1, 2);
+        assert!((pulse.cursor_time - 40e-12).abs() < 1e-15);
+        assert!((pulse.cursor - 1.).abs() < 1e-9);
+        assert!(pulse.pre[0].abs() < 1e-9);
+        assert!((pulse.post[0] - 0.8).abs() < 1e-9);
+        assert!((pulse.post[1] - 0.6).abs() < 1e-9);
+        assert_eq!(pulse.taps().len(), 4);
+        assert!((pulse.isi_ratio() - 1.4).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,378 @@
+//! Resistor tile characterization.
+//!
+//! [`ResistorTb`] measures the resistance of a resistor tile at one PVT corner.
+//! Run it across corners and temperatures with a [`CornerSweep`](crate::sweep::CornerSweep),
+//! then summarize the results with [`ResistorSpread`] to size the impedance code
+//! range of a driver built from that resistor.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::ac::{Ac, Sweep};
+use spectre::blocks::{AcSource, Isource, Vsource};
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Node};
+use substrate::io::{Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{ac, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::report::SimArtifact;
+use crate::sweep::CornerSweepOutput;
+use crate::tiles::ResistorIo;
+
+/// A small-signal testbench that measures the resistance between the terminals
+/// of a resistor tile.
+///
+/// A 1 A AC current is driven into `p` with `n` grounded, so the voltage at `p` is the
+/// resistance. The body is tied to VDD, matching its connection in the drivers.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct ResistorTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: T,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> ResistorTb<T, PDK, C> {
+    /// Creates a new [`ResistorTb`].
+    pub fn new(dut: T, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for ResistorTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("resistor_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("resistor_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`ResistorTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct ResistorTbNodes {
+    p: Node,
+}
+
+impl<T, PDK, C> ExportsNestedData for ResistorTb<T, PDK, C>
+where
+    ResistorTb<T, PDK, C>: Block,
+{
+    type NestedData = ResistorTbNodes;
+}
+
+impl<T: Block<Io = ResistorIo> + Schematic<PDK> + Clone, PDK: Schema, C: Copy> Schematic<Spectre>
+    for ResistorTb<T, PDK, C>
+where
+    ResistorTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let p = cell.signal("p", Signal);
+        let vdd = cell.signal("vdd", Signal);
+
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
+        cell.connect(dut.io().p, p);
+        cell.connect(dut.io().n, io.vss);
+        cell.connect(dut.io().b, vdd);
+
+        cell.instantiate_connected(
+            Vsource::dc(self.pvt.voltage),
+            TwoTerminalIoSchematic { p: vdd, n: io.vss },
+        );
+        cell.instantiate_connected(
+            Isource::ac(AcSource {
+                dc: dec!(0),
+                mag: dec!(1),
+                phase: dec!(0),
+            }),
+            TwoTerminalIoSchematic { p: io.vss, n: p },
+        );
+
+        Ok(ResistorTbNodes { p })
+    }
+}
+
+/// The resulting waveforms of a [`ResistorTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct ResistorSim {
+    /// The simulation frequency.
+    pub freq: ac::Freq,
+    /// The voltage across the resistor.
+    pub vp: ac::Voltage,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Ac, ResistorSim> for ResistorTb<T, PDK, C>
+where
+    ResistorTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <ResistorSim as FromSaved<Spectre, Ac>>::SavedKey {
+        ResistorSimSavedKey {
+            freq: ac::Freq::save(ctx, (), opts),
+            vp: ac::Voltage::save(ctx, &cell.p, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for ResistorTb<T, PDK, C>
+where
+    ResistorTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    /// The resistance in ohms.
+    type Output = f64;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: ResistorSim = sim
+            .simulate(
+                opts,
+                Ac {
+                    start: dec!(1e3),
+                    stop: dec!(1e4),
+                    sweep: Sweep::Decade(1),
+                    errpreset: Some(ErrPreset::Conservative),
+                },
+            )
+            .expect("failed to run simulation");
+        wav.vp[0].re
+    }
+}
+
+/// The temperature coefficient of a resistor at one process corner and supply voltage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResistorTc {
+    /// The process corner.
+    pub corner: String,
+    /// The supply voltage, in volts.
+    pub voltage: f64,
+    /// The resistance at the temperature nearest 25 C, in ohms.
+    pub r25: f64,
+    /// The first-order temperature coefficient, in ppm/K, from a least-squares fit.
+    pub tc_ppm: f64,
+}
+
+/// The number of driver segments needed to hit a target impedance across resistor variation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeRange {
+    /// The number of enabled segments at the lowest resistance.
+    pub min_enabled: usize,
+    /// The number of enabled segments at the highest resistance,
+    /// which is the number of segments the driver needs.
+    pub max_enabled: usize,
+}
+
+/// The spread of a resistor's value across PVT corners.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResistorSpread {
+    /// The smallest resistance, in ohms.
+    pub min: f64,
+    /// The largest resistance, in ohms.
+    pub max: f64,
+    /// The mean resistance, in ohms.
+    pub mean: f64,
+    /// The temperature coefficient at each process corner and supply voltage
+    /// with at least two temperatures.
+    pub tc: Vec<ResistorTc>,
+}
+
+impl ResistorSpread {
+    /// Summarizes resistances measured at a set of PVT corners.
+    pub fn new<C: Copy + Debug + Hash + Eq>(
+        points: impl IntoIterator<Item = (Pvt<C>, f64)>,
+    ) -> Self {
+        let points = points.into_iter().collect::<Vec<_>>();
+        assert!(!points.is_empty(), "no resistances to summarize");
+        let values = points.iter().map(|(_, r)| *r);
+        let min = values.clone().fold(f64::INFINITY, f64::min);
+        let max = values.clone().fold(f64::NEG_INFINITY, f64::max);
+        let mean = values.sum::<f64>() / points.len() as f64;
+
+        let mut groups: HashMap<(C, _), Vec<(f64, f64)>> = HashMap::new();
+        for (pvt, r) in &points {
+            groups
+                .entry((pvt.corner, pvt.voltage))
+                .or_default()
+                .push((pvt.temp.to_f64().unwrap(), *r));
+        }
+        let mut tc = groups
+            .into_iter()
+            .filter(|(_, samples)| samples.len() >= 2)
+            .map(|((corner, voltage), samples)| {
+                let n = samples.len() as f64;
+                let t_mean = samples.iter().map(|(t, _)| t).sum::<f64>() / n;
+                let r_mean = samples.iter().map(|(_, r)| r).sum::<f64>() / n;
+                let slope = samples
+                    .iter()
+                    .map(|(t, r)| (t - t_mean) * (r - r_mean))
+                    .sum::<f64>()
+                    / samples
+                        .iter()
+                        .map(|(t, _)| (t - t_mean).powi(2))
+                        .sum::<f64>();
+                let r25 = samples
+                    .iter()
+                    .min_by(|a, b| (a.0 - 25.).abs().total_cmp(&(b.0 - 25.).abs()))
+                    .unwrap()
+                    .1;
+                ResistorTc {
+                    corner: format!("{corner:?}"),
+                    voltage: voltage.to_f64().unwrap(),
+                    r25,
+                    tc_ppm: slope / r25 * 1e6,
+                }
+            })
+            .collect::<Vec<_>>();
+        tc.sort_by(|a, b| {
+            a.corner
+                .cmp(&b.corner)
+                .then(a.voltage.total_cmp(&b.voltage))
+        });
+
+        Self { min, max, mean, tc }
+    }
+
+    /// Summarizes the output of a corner sweep of [`ResistorTb`].
+    pub fn from_sweep<C: Copy + Debug + Hash + Eq>(sweep: &CornerSweepOutput<C, f64>) -> Self {
+        Self::new(sweep.outputs.iter().map(|(pvt, r)| (*pvt, *r)))
+    }
+
+    /// The total spread relative to the mean resistance.
+    pub fn spread(&self) -> f64 {
+        (self.max - self.min) / self.mean
+    }
+
+    /// The range of enabled segments needed to keep the impedance of a driver made of
+    /// parallel segments of this resistor within `tolerance` of its target at every corner.
+    ///
+    /// `tolerance` is relative, so 0.1 allows the impedance to be within 10% of its target.
+    pub fn codes_needed(&self, tolerance: f64) -> CodeRange {
+        assert!(tolerance > 0., "tolerance must be positive");
+        // Enabling one more of `n` segments changes the impedance by about `1 / n`, so
+        // rounding to the nearest code is within tolerance once `1 / (2n) <= tolerance`.
+        let min_enabled = (1. / (2. * tolerance)).ceil() as usize;
+        let max_enabled = (min_enabled as f64 * self.max / self.min).ceil() as usize;
+        CodeRange {
+            min_enabled,
+            max_enabled,
+        }
+    }
+}
+
+impl SimArtifact for ResistorSpread {
+    fn csv_header(&self) -> Vec<String> {
+        ["corner", "voltage", "r25", "tc_ppm"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.tc
+            .iter()
+            .map(|tc| {
+                vec![
+                    tc.corner.clone(),
+                    tc.voltage.to_string(),
+                    tc.r25.to_string(),
+                    tc.tc_ppm.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_tc_and_sizes_code_range() {
+        #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+        enum Corner {
+            Slow,
+            Fast,
+        }
+
+        let points = [(Corner::Slow, 1_100.), (Corner::Fast, 900.)]
+            .into_iter()
+            .flat_map(|(corner, r0)| {
+                [dec!(-40), dec!(25), dec!(125)].map(move |temp| {
+                    let pvt = Pvt {
+                        corner,
+                        voltage: dec!(1.8),
+                        temp,
+                    };
+                    // 1000 ppm/K relative to the 25 C value.
+                    let t = temp.to_f64().unwrap();
+                    (pvt, r0 * (1. + 1e-3 * (t - 25.)))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let spread = ResistorSpread::new(points);
+        assert_eq!(spread.tc.len(), 2);
+        for tc in &spread.tc {
+            assert!((tc.tc_ppm - 1_000.).abs() < 1e-6);
+        }
+        assert!((spread.min - 900. * 0.935).abs() < 1e-9);
+        assert!((spread.max - 1_100. * 1.1).abs() < 1e-9);
+
+        let codes = spread.codes_needed(0.05);
+        assert_eq!(codes.min_enabled, 10);
+        assert_eq!(codes.max_enabled, 15);
+    }
+}
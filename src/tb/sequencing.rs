@@ -0,0 +1,576 @@
+//! Supply sequencing checks for multi-rail blocks.
+//!
+//! [`SupplySequencingTb`] ramps the supply rails of a block one after another in a given
+//! order, with every control input held low as it would be before its drivers power up,
+//! and records the current through each rail. Current flowing back into a rail that is
+//! not yet up indicates a junction forward-biased from a rail that is, and current that
+//! stays high after every rail is up indicates latch-up. [`check_sequencing`] runs every
+//! ordering of a block's rails and reduces the results to the [`OrderingConstraint`]s
+//! that avoid all violations.
+
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::Vsource;
+use spectre::{ErrPreset, Spectre};
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::context::PdkContext;
+use substrate::io::schematic::{Bundle, HardwareType, Node, Terminal};
+use substrate::io::{FlatLen, Io, Signal, TestbenchIo};
+use substrate::pdk::corner::Pvt;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::driver::DriverWithGuardRingRailsIo;
+use crate::report::SimArtifact;
+
+/// A supply rail that can be sequenced independently of the others.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SupplyRail {
+    /// The core supply.
+    Vdd,
+    /// The I/O supply.
+    Vddio,
+    /// The supply of an n-well guard ring.
+    GuardRingVdd,
+}
+
+impl SupplyRail {
+    /// Every rail, in declaration order.
+    pub const ALL: [SupplyRail; 3] = [SupplyRail::Vdd, SupplyRail::Vddio, SupplyRail::GuardRingVdd];
+
+    /// The name of this rail.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SupplyRail::Vdd => "vdd",
+            SupplyRail::Vddio => "vddio",
+            SupplyRail::GuardRingVdd => "guard_ring_vdd",
+        }
+    }
+}
+
+/// An interface with several supply rails.
+pub trait MultiRailIo: Io {
+    /// The rails of the interface.
+    const RAILS: &'static [SupplyRail];
+
+    /// Connects each rail of `io` to the node returned by `rail`, ground rails to `vss`,
+    /// and every input to `vss`.
+    fn connect_rails<SC: Schema>(
+        cell: &mut CellBuilder<SC>,
+        io: &Bundle<Self>,
+        rail: impl Fn(SupplyRail) -> Node,
+        vss: Node,
+    );
+}
+
+impl MultiRailIo for DriverWithGuardRingRailsIo {
+    const RAILS: &'static [SupplyRail] = &[SupplyRail::Vdd, SupplyRail::GuardRingVdd];
+
+    fn connect_rails<SC: Schema>(
+        cell: &mut CellBuilder<SC>,
+        io: &Bundle<Self>,
+        rail: impl Fn(SupplyRail) -> Node,
+        vss: Node,
+    ) {
+        cell.connect(io.din, vss);
+        for ctl in [&io.pu_ctl, &io.pd_ctlb, &io.spare_pu_ctl, &io.spare_pd_ctlb] {
+            for i in 0..ctl.len() {
+                cell.connect(ctl[i], vss);
+            }
+        }
+        cell.connect(io.vdd, rail(SupplyRail::Vdd));
+        cell.connect(io.guard_ring_vdd, rail(SupplyRail::GuardRingVdd));
+        cell.connect(io.vss, vss);
+        cell.connect(io.guard_ring_vss, vss);
+    }
+}
+
+/// A transient testbench that ramps the supply rails of a block in `order` and measures
+/// the current through each rail.
+///
+/// Each rail ramps linearly from zero to the corner voltage in `ramp`, starting `gap`
+/// after the previous rail finished ramping. The simulation continues for `settle` after
+/// the last rail is up.
+#[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct SupplySequencingTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: T,
+    /// The order in which the rails ramp up.
+    pub order: Vec<SupplyRail>,
+    /// The duration of each ramp, in seconds.
+    pub ramp: Decimal,
+    /// The delay between the end of one ramp and the start of the next, in seconds.
+    pub gap: Decimal,
+    /// The time simulated after the last rail is up, in seconds.
+    pub settle: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> SupplySequencingTb<T, PDK, C> {
+    /// Creates a new [`SupplySequencingTb`] with 1 us ramps, gaps, and settling time.
+    pub fn new(dut: T, order: Vec<SupplyRail>, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            order,
+            ramp: dec!(1e-6),
+            gap: dec!(1e-6),
+            settle: dec!(1e-6),
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the duration of each ramp.
+    pub fn with_ramp(mut self, ramp: Decimal) -> Self {
+        self.ramp = ramp;
+        self
+    }
+
+    /// Sets the delay between ramps.
+    pub fn with_gap(mut self, gap: Decimal) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// The time at which `rail` starts ramping, or [`None`] if it is not in the order.
+    fn start(&self, rail: SupplyRail) -> Option<Decimal> {
+        let i = self.order.iter().position(|&r| r == rail)?;
+        Some(self.gap + Decimal::from(i) * (self.ramp + self.gap))
+    }
+
+    fn stop(&self) -> Decimal {
+        Decimal::from(self.order.len()) * (self.ramp + self.gap) + self.settle
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for SupplySequencingTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("supply_sequencing_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("supply_sequencing_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`SupplySequencingTb`].
+///
+/// Every rail has a source, even if the device-under-test does not use it.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct SupplySequencingTbNodes {
+    vdd_src: Terminal,
+    vddio_src: Terminal,
+    guard_ring_vdd_src: Terminal,
+}
+
+impl<T, PDK, C> ExportsNestedData for SupplySequencingTb<T, PDK, C>
+where
+    SupplySequencingTb<T, PDK, C>: Block,
+{
+    type NestedData = SupplySequencingTbNodes;
+}
+
+impl<T: Block + Schematic<PDK> + Clone, PDK: Schema, C> Schematic<Spectre>
+    for SupplySequencingTb<T, PDK, C>
+where
+    SupplySequencingTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    T::Io: MultiRailIo,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let sources = SupplyRail::ALL.map(|rail| {
+            let node = cell.signal(rail.name(), Signal);
+            let pwl = match self.start(rail) {
+                Some(start) => vec![
+                    (dec!(0), dec!(0)),
+                    (start, dec!(0)),
+                    (start + self.ramp, self.pvt.voltage),
+                ],
+                None => vec![(dec!(0), dec!(0))],
+            };
+            let src = cell.instantiate(Vsource::pwl(pwl));
+            cell.connect(src.io().p, node);
+            cell.connect(src.io().n, io.vss);
+            (node, src.io().p)
+        });
+        let nodes = SupplyRail::ALL.map(|rail| sources[rail as usize].0);
+
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut.clone());
+        <T::Io as MultiRailIo>::connect_rails(cell, dut.io(), |rail| nodes[rail as usize], io.vss);
+
+        let [(_, vdd_src), (_, vddio_src), (_, guard_ring_vdd_src)] = sources;
+        Ok(SupplySequencingTbNodes {
+            vdd_src,
+            vddio_src,
+            guard_ring_vdd_src,
+        })
+    }
+}
+
+/// The resulting waveforms of a [`SupplySequencingTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct SupplySequencingSim {
+    /// The simulation time.
+    pub t: tran::Time,
+    /// The current into the `vdd` source.
+    pub vdd: tran::Current,
+    /// The current into the `vddio` source.
+    pub vddio: tran::Current,
+    /// The current into the `guard_ring_vdd` source.
+    pub guard_ring_vdd: tran::Current,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, SupplySequencingSim> for SupplySequencingTb<T, PDK, C>
+where
+    SupplySequencingTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <SupplySequencingSim as FromSaved<Spectre, Tran>>::SavedKey {
+        SupplySequencingSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            vdd: tran::Current::save(ctx, &cell.vdd_src, opts),
+            vddio: tran::Current::save(ctx, &cell.vddio_src, opts),
+            guard_ring_vdd: tran::Current::save(ctx, &cell.guard_ring_vdd_src, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for SupplySequencingTb<T, PDK, C>
+where
+    SupplySequencingTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = SequencingResult;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: SupplySequencingSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: self.stop(),
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+
+        let rails = self
+            .order
+            .iter()
+            .map(|&rail| {
+                let current = match rail {
+                    SupplyRail::Vdd => &wav.vdd,
+                    SupplyRail::Vddio => &wav.vddio,
+                    SupplyRail::GuardRingVdd => &wav.guard_ring_vdd,
+                };
+                // The supply source delivers current out of its positive terminal.
+                let delivered = current.iter().map(|i| -i);
+                RailCurrents {
+                    rail,
+                    peak: delivered.clone().fold(0., f64::max),
+                    reverse: delivered.fold(0., |max, i| max.max(-i)),
+                    settled: -current.last().copied().unwrap_or_default(),
+                }
+            })
+            .collect();
+        SequencingResult {
+            order: self.order.clone(),
+            rails,
+        }
+    }
+}
+
+/// The currents through one rail of a [`SupplySequencingTb`], in amperes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct RailCurrents {
+    /// The rail.
+    pub rail: SupplyRail,
+    /// The largest current delivered by the rail.
+    pub peak: f64,
+    /// The largest current flowing back into the rail.
+    pub reverse: f64,
+    /// The current delivered by the rail once every rail is up.
+    pub settled: f64,
+}
+
+/// The rail currents measured for one ordering of the rails.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SequencingResult {
+    /// The order in which the rails ramped up.
+    pub order: Vec<SupplyRail>,
+    /// The currents through each rail, in ramp order.
+    pub rails: Vec<RailCurrents>,
+}
+
+impl SequencingResult {
+    /// The rails whose currents exceed `limits`.
+    pub fn violations(&self, limits: &SequencingLimits) -> Vec<SupplyRail> {
+        self.rails
+            .iter()
+            .filter(|c| {
+                c.peak > limits.peak || c.reverse > limits.reverse || c.settled > limits.settled
+            })
+            .map(|c| c.rail)
+            .collect()
+    }
+
+    /// Returns true if `first` ramped up before `then`.
+    fn precedes(&self, first: SupplyRail, then: SupplyRail) -> bool {
+        let pos = |rail| self.order.iter().position(|&r| r == rail);
+        matches!((pos(first), pos(then)), (Some(a), Some(b)) if a < b)
+    }
+}
+
+/// The largest acceptable rail currents, in amperes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SequencingLimits {
+    /// The largest current a rail may deliver at any time, including the current that
+    /// charges decoupling capacitance during its ramp.
+    pub peak: f64,
+    /// The largest current that may flow back into a rail.
+    pub reverse: f64,
+    /// The largest current a rail may deliver once every rail is up.
+    pub settled: f64,
+}
+
+/// A requirement that one rail ramps up before another.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct OrderingConstraint {
+    /// The rail that must ramp up first.
+    pub first: SupplyRail,
+    /// The rail that must ramp up after `first`.
+    pub then: SupplyRail,
+}
+
+/// The results of every ordering of a block's rails.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SequencingReport {
+    /// The limits against which the orderings were checked.
+    pub limits: SequencingLimits,
+    /// The result of each ordering.
+    pub results: Vec<SequencingResult>,
+}
+
+impl SequencingReport {
+    /// Returns true if no ordering violates the limits.
+    pub fn is_order_independent(&self) -> bool {
+        self.results
+            .iter()
+            .all(|r| r.violations(&self.limits).is_empty())
+    }
+
+    /// The orderings that violate the limits.
+    pub fn failing_orders(&self) -> Vec<&[SupplyRail]> {
+        self.results
+            .iter()
+            .filter(|r| !r.violations(&self.limits).is_empty())
+            .map(|r| r.order.as_slice())
+            .collect()
+    }
+
+    /// The pairwise constraints under which every ordering passes.
+    ///
+    /// Rail `first` must precede rail `then` if some ordering with `then` first fails and
+    /// every ordering with `first` first passes. Violations that no pairwise constraint
+    /// avoids are not reported here; check [`failing_orders`](Self::failing_orders).
+    pub fn constraints(&self) -> Vec<OrderingConstraint> {
+        let passes = |r: &SequencingResult| r.violations(&self.limits).is_empty();
+        let mut constraints = Vec::new();
+        for first in SupplyRail::ALL {
+            for then in SupplyRail::ALL {
+                let ordered = |a, b| self.results.iter().filter(move |r| r.precedes(a, b));
+                if first != then
+                    && ordered(first, then).all(passes)
+                    && ordered(then, first).any(|r| !passes(r))
+                {
+                    constraints.push(OrderingConstraint { first, then });
+                }
+            }
+        }
+        constraints
+    }
+}
+
+impl SimArtifact for SequencingReport {
+    fn csv_header(&self) -> Vec<String> {
+        ["order", "rail", "peak", "reverse", "settled", "pass"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.results
+            .iter()
+            .flat_map(|r| {
+                let order = r
+                    .order
+                    .iter()
+                    .map(SupplyRail::name)
+                    .collect::<Vec<_>>()
+                    .join(">");
+                let violations = r.violations(&self.limits);
+                r.rails.iter().map(move |c| {
+                    vec![
+                        order.clone(),
+                        c.rail.name().to_string(),
+                        c.peak.to_string(),
+                        c.reverse.to_string(),
+                        c.settled.to_string(),
+                        (!violations.contains(&c.rail)).to_string(),
+                    ]
+                })
+            })
+            .collect()
+    }
+}
+
+/// Every ordering of `rails`.
+fn orderings(rails: &[SupplyRail]) -> Vec<Vec<SupplyRail>> {
+    if rails.len() <= 1 {
+        return vec![rails.to_vec()];
+    }
+    (0..rails.len())
+        .flat_map(|i| {
+            let mut rest = rails.to_vec();
+            let first = rest.remove(i);
+            orderings(&rest).into_iter().map(move |mut order| {
+                order.insert(0, first);
+                order
+            })
+        })
+        .collect()
+}
+
+/// Ramps the rails of `dut` in every order at `pvt` and checks the rail currents
+/// against `limits`.
+///
+/// Each ordering is simulated in its own subdirectory of `work_dir`.
+pub fn check_sequencing<T, PDK, C>(
+    ctx: &PdkContext<PDK>,
+    dut: T,
+    pvt: Pvt<C>,
+    limits: SequencingLimits,
+    work_dir: impl AsRef<Path>,
+) -> SequencingReport
+where
+    T: Block + Clone,
+    T::Io: MultiRailIo,
+    SupplySequencingTb<T, PDK, C>: Testbench<Spectre, Output = SequencingResult>,
+    PDK: Pdk,
+    C: Copy,
+{
+    let results = orderings(<T::Io as MultiRailIo>::RAILS)
+        .into_iter()
+        .map(|order| {
+            let dir = order
+                .iter()
+                .map(SupplyRail::name)
+                .collect::<Vec<_>>()
+                .join("_then_");
+            ctx.simulate(
+                SupplySequencingTb::new(dut.clone(), order, pvt),
+                work_dir.as_ref().join(dir),
+            )
+            .expect("failed to run simulation")
+        })
+        .collect();
+    SequencingReport { limits, results }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_ordering_constraints() {
+        use SupplyRail::*;
+        assert_eq!(orderings(&SupplyRail::ALL).len(), 6);
+
+        let limits = SequencingLimits {
+            peak: 10e-3,
+            reverse: 1e-6,
+            settled: 1e-3,
+        };
+        let result = |order: Vec<SupplyRail>| {
+            // The guard ring forward-biases into the core supply if it ramps first.
+            let reverse = if order[0] == GuardRingVdd { 1e-3 } else { 0. };
+            let rails = order
+                .iter()
+                .map(|&rail| RailCurrents {
+                    rail,
+                    peak: 1e-3,
+                    reverse: if rail == Vdd { reverse } else { 0. },
+                    settled: 1e-6,
+                })
+                .collect();
+            SequencingResult { order, rails }
+        };
+        let report = SequencingReport {
+            limits,
+            results: orderings(&[Vdd, GuardRingVdd])
+                .into_iter()
+                .map(result)
+                .collect(),
+        };
+        assert!(!report.is_order_independent());
+        assert_eq!(report.failing_orders(), vec![&[GuardRingVdd, Vdd][..]]);
+        assert_eq!(
+            report.constraints(),
+            vec![OrderingConstraint {
+                first: Vdd,
+                then: GuardRingVdd
+            }]
+        );
+    }
+}
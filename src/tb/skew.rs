@@ -0,0 +1,404 @@
+//! Cross-lane skew testbenches.
+//!
+//! [`LaneSkewTb`] drives several transmit lanes from one clock source and measures
+//! when each lane's pad output switches. The spread of those times is the lane-to-lane
+//! skew contributed by the clock distribution and the lanes themselves.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::{Pulse, Vsource};
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::path::Path;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Node};
+use substrate::io::{Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::primitives::{Capacitor, Resistor};
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::lane::TxLaneIo;
+use crate::report::{ArtifactMetadata, ArtifactPaths, SimArtifact};
+use crate::spec::{ComplianceReport, Limit, Spec};
+use crate::tb::pi::Samples;
+use crate::tb::SimNoiseOptions;
+
+/// The number of clock periods simulated.
+const PERIODS: i64 = 4;
+
+/// The clock distribution from the shared source to one lane, modeled as a series
+/// resistance followed by a shunt capacitance.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ClockBranch {
+    /// The series resistance, in ohms.
+    pub r: Decimal,
+    /// The capacitance at the lane input, in farads.
+    pub c: Decimal,
+}
+
+/// A transient testbench that drives the `din` of each lane from one clock source
+/// and records the output of every lane into a capacitive pad load.
+///
+/// Lanes are typically the same generator placed at different positions, with
+/// each branch extracted from the clock distribution that feeds it.
+#[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct LaneSkewTb<T, PDK, C> {
+    /// The lanes, each with the clock branch that feeds it.
+    pub lanes: Vec<(T, ClockBranch)>,
+    /// The clock period, in seconds.
+    pub period: Decimal,
+    /// The pad load capacitance of each lane, in farads.
+    pub load: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+    /// Transient noise options.
+    pub noise: SimNoiseOptions,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> LaneSkewTb<T, PDK, C> {
+    /// Creates a new [`LaneSkewTb`].
+    pub fn new(lanes: Vec<(T, ClockBranch)>, period: Decimal, load: Decimal, pvt: Pvt<C>) -> Self {
+        Self {
+            lanes,
+            period,
+            load,
+            pvt,
+            noise: SimNoiseOptions::default(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the transient noise options.
+    pub fn with_noise(mut self, noise: SimNoiseOptions) -> Self {
+        self.noise = noise;
+        self
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for LaneSkewTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("lane_skew_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::format!("lane_skew_tb_{}", self.lanes.len())
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`LaneSkewTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct LaneSkewTbNodes {
+    clk: Node,
+    dout: Vec<Node>,
+}
+
+impl<T, PDK, C> ExportsNestedData for LaneSkewTb<T, PDK, C>
+where
+    LaneSkewTb<T, PDK, C>: Block,
+{
+    type NestedData = LaneSkewTbNodes;
+}
+
+impl<T: Block<Io = TxLaneIo> + Schematic<PDK> + Clone, PDK: Schema, C: Copy> Schematic<Spectre>
+    for LaneSkewTb<T, PDK, C>
+where
+    LaneSkewTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let clk = cell.signal("clk", Signal);
+        let vdd = cell.signal("vdd", Signal);
+
+        let vdd_src = cell.instantiate(Vsource::dc(self.pvt.voltage));
+        cell.connect(vdd_src.io().p, vdd);
+        cell.connect(vdd_src.io().n, io.vss);
+
+        let edge = self.period / dec!(20);
+        let clk_src = cell.instantiate(Vsource::pulse(Pulse {
+            val0: dec!(0),
+            val1: self.pvt.voltage,
+            period: Some(self.period),
+            width: Some(self.period / dec!(2) - edge),
+            delay: Some(self.period / dec!(2)),
+            rise: Some(edge),
+            fall: Some(edge),
+        }));
+        cell.connect(clk_src.io().p, clk);
+        cell.connect(clk_src.io().n, io.vss);
+
+        let mut dout = Vec::with_capacity(self.lanes.len());
+        for (i, (lane, branch)) in self.lanes.iter().enumerate() {
+            let din = cell.signal(format!("din{i}"), Signal);
+            let out = cell.signal(format!("dout{i}"), Signal);
+            cell.instantiate_connected(
+                Resistor::new(branch.r),
+                TwoTerminalIoSchematic { p: clk, n: din },
+            );
+            cell.instantiate_connected(
+                Capacitor::new(branch.c),
+                TwoTerminalIoSchematic { p: din, n: io.vss },
+            );
+
+            let dut = cell.sub_builder::<PDK>().instantiate(lane.clone());
+            cell.connect(dut.io().din, din);
+            cell.connect(dut.io().dout, out);
+            cell.connect(dut.io().vdd, vdd);
+            cell.connect(dut.io().vss, io.vss);
+
+            cell.instantiate_connected(
+                Capacitor::new(self.load),
+                TwoTerminalIoSchematic { p: out, n: io.vss },
+            );
+            dout.push(out);
+        }
+
+        Ok(LaneSkewTbNodes { clk, dout })
+    }
+}
+
+/// The resulting waveforms of a [`LaneSkewTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct LaneSkewSim {
+    t: tran::Time,
+    clk: tran::Voltage,
+    dout: Vec<tran::Voltage>,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, LaneSkewSim> for LaneSkewTb<T, PDK, C>
+where
+    LaneSkewTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <LaneSkewSim as FromSaved<Spectre, Tran>>::SavedKey {
+        LaneSkewSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            clk: tran::Voltage::save(ctx, cell.data().clk, opts),
+            dout: cell
+                .data()
+                .dout
+                .iter()
+                .map(|dout| tran::Voltage::save(ctx, dout, opts))
+                .collect(),
+        }
+    }
+}
+
+/// The output timing of each lane relative to the shared clock source.
+///
+/// Delays are in seconds, measured between 50% crossings and averaged over all
+/// simulated clock edges.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LaneSkew {
+    /// The delay of each lane's output after a rising clock edge.
+    pub rise_delay: Vec<f64>,
+    /// The delay of each lane's output after a falling clock edge.
+    pub fall_delay: Vec<f64>,
+}
+
+impl LaneSkew {
+    /// Measures the delay from each `clk` crossing of `threshold` to the next crossing
+    /// of each lane output.
+    ///
+    /// Lanes that never switch have a delay of NaN.
+    pub fn measure(clk: Samples<'_>, lanes: &[Samples<'_>], threshold: f64) -> Self {
+        let clk_edges = clk.crossings(threshold);
+        let mut rise_delay = Vec::with_capacity(lanes.len());
+        let mut fall_delay = Vec::with_capacity(lanes.len());
+        for lane in lanes {
+            let out_edges = lane.crossings(threshold);
+            let mean_delay = |rising: bool| {
+                let delays = clk_edges
+                    .iter()
+                    .filter(|(_, r)| *r == rising)
+                    .filter_map(|(t, _)| {
+                        out_edges
+                            .iter()
+                            .find(|(t_out, _)| t_out > t)
+                            .map(|(t_out, _)| t_out - t)
+                    })
+                    .collect::<Vec<_>>();
+                delays.iter().sum::<f64>() / delays.len() as f64
+            };
+            rise_delay.push(mean_delay(true));
+            fall_delay.push(mean_delay(false));
+        }
+        Self {
+            rise_delay,
+            fall_delay,
+        }
+    }
+
+    /// The largest difference in delay between any two lanes for the same clock edge.
+    pub fn skew(&self) -> f64 {
+        let spread = |delays: &[f64]| {
+            let max = delays.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let min = delays.iter().copied().fold(f64::INFINITY, f64::min);
+            max - min
+        };
+        f64::max(spread(&self.rise_delay), spread(&self.fall_delay))
+    }
+
+    /// Checks the lane-to-lane skew against `max_skew`, in seconds.
+    ///
+    /// The UCIe skew budget is given in UI, so convert it using the link data rate.
+    pub fn compliance(&self, max_skew: f64) -> ComplianceReport {
+        ComplianceReport::evaluate(
+            &[Spec::new("lane_to_lane_skew", Limit::Max(max_skew), "s")],
+            [("lane_to_lane_skew", self.skew())],
+        )
+    }
+
+    /// Writes the per-lane delays and their compliance report against `max_skew`
+    /// to `dir`, with the report named `<name>_compliance`.
+    pub fn write_report(
+        &self,
+        dir: impl AsRef<Path>,
+        metadata: &ArtifactMetadata,
+        max_skew: f64,
+    ) -> std::io::Result<(ArtifactPaths, ArtifactPaths)> {
+        let dir = dir.as_ref();
+        let delays = self.write_artifact(dir, metadata)?;
+        let compliance = self.compliance(max_skew).write_artifact(
+            dir,
+            &ArtifactMetadata {
+                name: format!("{}_compliance", metadata.name),
+                ..metadata.clone()
+            },
+        )?;
+        Ok((delays, compliance))
+    }
+}
+
+impl SimArtifact for LaneSkew {
+    fn csv_header(&self) -> Vec<String> {
+        ["lane", "rise_delay", "fall_delay"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.rise_delay
+            .iter()
+            .zip(&self.fall_delay)
+            .enumerate()
+            .map(|(i, (rise, fall))| vec![i.to_string(), rise.to_string(), fall.to_string()])
+            .collect()
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for LaneSkewTb<T, PDK, C>
+where
+    LaneSkewTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = LaneSkew;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: LaneSkewSim = sim
+            .simulate(
+                opts,
+                self.noise.apply(Tran {
+                    stop: self.period * Decimal::from(PERIODS),
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                }),
+            )
+            .expect("failed to run simulation");
+
+        let lanes = wav
+            .dout
+            .iter()
+            .map(|v| Samples { t: &wav.t, v })
+            .collect::<Vec<_>>();
+        LaneSkew::measure(
+            Samples {
+                t: &wav.t,
+                v: &wav.clk,
+            },
+            &lanes,
+            self.pvt.voltage.to_f64().unwrap() / 2.,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_lane_to_lane_skew() {
+        let t = (0..400).map(|i| i as f64 * 1e-12).collect::<Vec<_>>();
+        let square = |delay: f64| {
+            t.iter()
+                .map(|&t| {
+                    if (t - delay).rem_euclid(100e-12) < 50e-12 {
+                        1.
+                    } else {
+                        0.
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        let clk = square(0.);
+        let outs = [square(20e-12), square(23e-12), square(21e-12)];
+        let lanes = outs
+            .iter()
+            .map(|v| Samples { t: &t, v })
+            .collect::<Vec<_>>();
+
+        let skew = LaneSkew::measure(Samples { t: &t, v: &clk }, &lanes, 0.5);
+        assert!((skew.rise_delay[1] - 23e-12).abs() < 1e-12);
+        assert!((skew.skew() - 3e-12).abs() < 1e-12);
+        assert!(skew.compliance(5e-12).passed());
+        assert!(!skew.compliance(2e-12).passed());
+    }
+}
@@ -0,0 +1,478 @@
+//! Spread-spectrum clocking tolerance testbenches.
+//!
+//! UCIe transmitters may spread their reference clock, so the receive clocking chain must
+//! follow a reference whose frequency ramps down and back up by a fraction of a percent.
+//! [`SscTb`] sends a PRBS burst timed by a triangular down-spread reference through a
+//! receive lane whose sampling clock comes from a [`PhaseTracker`], a behavioral model of
+//! the PLL or DLL, and counts the bits sampled in error.
+//!
+//! Real SSC profiles modulate at around 30 kHz, far too slowly to simulate a full period
+//! at the transistor level. Shrinking [`SscProfile::period`] raises the rate at which the
+//! frequency ramps, which only increases the tracking error, so a burst with a compressed
+//! profile is a conservative check.
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::Vsource;
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::f64::consts::PI;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Node};
+use substrate::io::{Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::lane::RxLaneIo;
+use crate::tb::pi::Samples;
+use crate::tb::SimNoiseOptions;
+
+/// The number of UIs of idle data before the burst, which lets the sampler settle.
+const LEAD_UI: usize = 4;
+/// The largest sampler latency considered when aligning received bits, in UIs.
+const MAX_LATENCY_UI: usize = 3;
+
+/// A triangular down-spread clock modulation profile.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct SscProfile {
+    /// The peak downward frequency deviation, as a fraction of the nominal frequency.
+    pub deviation: Decimal,
+    /// The modulation period, in seconds.
+    pub period: Decimal,
+}
+
+impl SscProfile {
+    /// The 0.5% down-spread profile, modulated with period `period`.
+    pub fn down_spread(period: Decimal) -> Self {
+        Self {
+            deviation: dec!(0.005),
+            period,
+        }
+    }
+
+    /// The frequency at time `t` as a fraction of the nominal frequency.
+    ///
+    /// The frequency starts at nominal, falls linearly to its minimum at half the
+    /// modulation period, and rises back to nominal at the end of the period.
+    pub fn frequency(&self, t: f64) -> f64 {
+        let period = self.period.to_f64().unwrap();
+        let phase = (t / period).rem_euclid(1.);
+        let tri = 1. - (2. * phase - 1.).abs();
+        1. - self.deviation.to_f64().unwrap() * tri
+    }
+
+    /// The times of the first `n + 1` UI boundaries of a clock with nominal unit
+    /// interval `ui`, starting at time zero.
+    pub fn edges(&self, ui: f64, n: usize) -> Vec<f64> {
+        let mut edges = Vec::with_capacity(n + 1);
+        let mut t = 0.;
+        edges.push(t);
+        for _ in 0..n {
+            t += ui / self.frequency(t);
+            edges.push(t);
+        }
+        edges
+    }
+}
+
+/// A first-order behavioral model of a PLL or DLL that follows the phase of its reference.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct PhaseTracker {
+    /// The tracking bandwidth, in hertz.
+    pub bandwidth: Decimal,
+}
+
+impl PhaseTracker {
+    /// Returns the edge times of the tracking clock for the reference edges `reference`,
+    /// given a nominal unit interval `ui`.
+    ///
+    /// The tracker starts locked to the first reference edge and corrects a fixed fraction
+    /// of its timing error each UI.
+    pub fn track(&self, reference: &[f64], ui: f64) -> Vec<f64> {
+        let tau = 1. / (2. * PI * self.bandwidth.to_f64().unwrap());
+        let alpha = 1. - (-ui / tau).exp();
+        let mut offset = 0.;
+        reference
+            .iter()
+            .enumerate()
+            .map(|(k, &t)| {
+                let nominal = k as f64 * ui;
+                offset += alpha * (t - nominal - offset);
+                nominal + offset
+            })
+            .collect()
+    }
+}
+
+/// Returns `n` bits of the PRBS7 sequence `x^7 + x^6 + 1`.
+pub fn prbs7(n: usize) -> Vec<bool> {
+    let mut state = 0x7fu8;
+    (0..n)
+        .map(|_| {
+            let bit = ((state >> 6) ^ (state >> 5)) & 1;
+            state = ((state << 1) | bit) & 0x7f;
+            bit == 1
+        })
+        .collect()
+}
+
+/// Converts a time in seconds to a [`Decimal`] with femtosecond resolution.
+fn to_decimal(t: f64) -> Decimal {
+    Decimal::from_f64(t).unwrap().round_dp(15)
+}
+
+/// A transient testbench that checks that a receive lane samples a PRBS burst without
+/// errors while the reference clock is spread.
+///
+/// Data transitions occur at the UI boundaries of the spread reference. The sampling
+/// clock rises half a UI after each edge of a [`PhaseTracker`] following that reference,
+/// so tracking error moves the sampling point away from the center of the eye.
+#[derive_where::derive_where(Clone, Debug, Hash, PartialEq, Eq; T, C)]
+#[derive(Serialize, Deserialize)]
+pub struct SscTb<T, PDK, C> {
+    /// The receive lane.
+    pub rx: T,
+    /// The nominal unit interval, in seconds.
+    pub ui: Decimal,
+    /// The number of bits in the burst.
+    pub bits: usize,
+    /// The spread profile of the reference clock.
+    pub ssc: SscProfile,
+    /// The model of the receive clocking chain.
+    pub tracker: PhaseTracker,
+    /// The reference voltage, in volts.
+    pub vref: Decimal,
+    /// The time after each sampling edge at which the lane output is read, in UIs.
+    pub dout_delay: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+    /// Transient noise options.
+    pub noise: SimNoiseOptions,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> SscTb<T, PDK, C> {
+    /// Creates a new [`SscTb`] with a 0.5% down-spread profile compressed to a period of
+    /// `bits / 2` UIs, a 50 MHz tracking bandwidth, and a reference at half the supply.
+    pub fn new(rx: T, ui: Decimal, bits: usize, pvt: Pvt<C>) -> Self {
+        Self {
+            rx,
+            ui,
+            bits,
+            ssc: SscProfile::down_spread(ui * Decimal::from(bits) / dec!(2)),
+            tracker: PhaseTracker {
+                bandwidth: dec!(50e6),
+            },
+            vref: pvt.voltage / dec!(2),
+            dout_delay: dec!(0.75),
+            pvt,
+            noise: SimNoiseOptions::default(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the spread profile of the reference clock.
+    pub fn with_ssc(mut self, ssc: SscProfile) -> Self {
+        self.ssc = ssc;
+        self
+    }
+
+    /// Sets the model of the receive clocking chain.
+    pub fn with_tracker(mut self, tracker: PhaseTracker) -> Self {
+        self.tracker = tracker;
+        self
+    }
+
+    /// Sets the transient noise options.
+    pub fn with_noise(mut self, noise: SimNoiseOptions) -> Self {
+        self.noise = noise;
+        self
+    }
+
+    /// The transmitted bits, including the idle lead-in.
+    fn pattern(&self) -> Vec<bool> {
+        let mut bits = vec![false; LEAD_UI];
+        bits.extend(prbs7(self.bits));
+        bits
+    }
+
+    /// The UI boundaries of the data and the rising edges of the sampling clock.
+    fn timing(&self) -> (Vec<f64>, Vec<f64>) {
+        let ui = self.ui.to_f64().unwrap();
+        let n = LEAD_UI + self.bits + MAX_LATENCY_UI;
+        let data = self.ssc.edges(ui, n);
+        let clock = self
+            .tracker
+            .track(&data, ui)
+            .into_iter()
+            .map(|t| t + ui / 2.)
+            .collect();
+        (data, clock)
+    }
+}
+
+impl<
+        T: Block,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for SscTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("ssc_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("ssc_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`SscTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct SscTbNodes {
+    dout: Node,
+}
+
+impl<T, PDK, C> ExportsNestedData for SscTb<T, PDK, C>
+where
+    SscTb<T, PDK, C>: Block,
+{
+    type NestedData = SscTbNodes;
+}
+
+impl<T: Block<Io = RxLaneIo> + Schematic<PDK> + Clone, PDK: Schema, C: Copy> Schematic<Spectre>
+    for SscTb<T, PDK, C>
+where
+    SscTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vss = io.vss;
+        let vdd = cell.signal("vdd", Signal);
+        let dout = cell.signal("dout", Signal);
+        let rx = cell.sub_builder::<PDK>().instantiate(self.rx.clone());
+        cell.connect(rx.io().vdd, vdd);
+        cell.connect(rx.io().vss, vss);
+        cell.connect(rx.io().dout, dout);
+
+        let (data, clock) = self.timing();
+        let ui = self.ui.to_f64().unwrap();
+        let edge = ui / 10.;
+        let high = self.pvt.voltage.to_f64().unwrap();
+
+        let mut din = vec![(dec!(0), dec!(0))];
+        let mut prev = false;
+        for (&t, &bit) in data.iter().zip(self.pattern().iter()) {
+            if bit != prev {
+                let (from, to) = if bit { (0., high) } else { (high, 0.) };
+                din.push((to_decimal(t), to_decimal(from)));
+                din.push((to_decimal(t + edge), to_decimal(to)));
+                prev = bit;
+            }
+        }
+        let mut clk = vec![(dec!(0), dec!(0))];
+        for &t in &clock {
+            clk.extend([
+                (to_decimal(t), dec!(0)),
+                (to_decimal(t + edge), to_decimal(high)),
+                (to_decimal(t + ui / 2.), to_decimal(high)),
+                (to_decimal(t + ui / 2. + edge), dec!(0)),
+            ]);
+        }
+
+        for (source, node) in [
+            (Vsource::dc(self.pvt.voltage), vdd),
+            (Vsource::dc(self.vref), rx.io().vref),
+            (Vsource::pwl(din), rx.io().din),
+            (Vsource::pwl(clk), rx.io().clk),
+        ] {
+            cell.instantiate_connected(source, TwoTerminalIoSchematic { p: node, n: vss });
+        }
+
+        Ok(SscTbNodes { dout })
+    }
+}
+
+/// The resulting waveforms of an [`SscTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct SscSim {
+    /// The simulation time.
+    pub t: tran::Time,
+    /// The lane output.
+    pub dout: tran::Voltage,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, SscSim> for SscTb<T, PDK, C>
+where
+    SscTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <SscSim as FromSaved<Spectre, Tran>>::SavedKey {
+        SscSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            dout: tran::Voltage::save(ctx, &cell.dout, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for SscTb<T, PDK, C>
+where
+    SscTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = SscResult;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let (data, clock) = self.timing();
+        let ui = self.ui.to_f64().unwrap();
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: SscSim = sim
+            .simulate(
+                opts,
+                self.noise.apply(Tran {
+                    stop: to_decimal(clock[clock.len() - 1] + ui),
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                }),
+            )
+            .expect("failed to run simulation");
+
+        let dout = Samples {
+            t: &wav.t,
+            v: &wav.dout,
+        };
+        let threshold = self.pvt.voltage.to_f64().unwrap() / 2.;
+        let delay = self.dout_delay.to_f64().unwrap() * ui;
+        let received = clock
+            .iter()
+            .map(|&t| dout.value_at(t + delay) > threshold)
+            .collect::<Vec<_>>();
+        SscResult::new(
+            &self.pattern()[LEAD_UI..],
+            &received[LEAD_UI..],
+            &data,
+            &clock,
+            ui,
+        )
+    }
+}
+
+/// The outcome of an [`SscTb`] burst.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SscResult {
+    /// The number of bits compared.
+    pub bits: usize,
+    /// The number of bits sampled in error.
+    pub errors: usize,
+    /// The sampler latency that best aligns the received bits with the transmitted bits,
+    /// in UIs.
+    pub latency: usize,
+    /// The largest distance between a sampling edge and the center of its UI, in UIs.
+    pub max_tracking_error: f64,
+}
+
+impl SscResult {
+    /// Compares `received`, one bit per sampling edge, against `sent`, allowing for up to
+    /// [`MAX_LATENCY_UI`] UIs of sampler latency, and measures the sampling point error
+    /// of the sampling edges `clock` against the data edges `data`.
+    pub fn new(sent: &[bool], received: &[bool], data: &[f64], clock: &[f64], ui: f64) -> Self {
+        let (latency, errors) = (0..=MAX_LATENCY_UI)
+            .map(|latency| {
+                let errors = sent
+                    .iter()
+                    .zip(received.iter().skip(latency))
+                    .filter(|(a, b)| a != b)
+                    .count();
+                (latency, errors)
+            })
+            .min_by_key(|&(_, errors)| errors)
+            .unwrap();
+        let max_tracking_error = data
+            .windows(2)
+            .zip(clock)
+            .map(|(edges, &t)| ((t - (edges[0] + edges[1]) / 2.) / ui).abs())
+            .fold(0., f64::max);
+        Self {
+            bits: sent.len(),
+            errors,
+            latency,
+            max_tracking_error,
+        }
+    }
+
+    /// Whether every bit was received correctly.
+    pub fn error_free(&self) -> bool {
+        self.errors == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ssc_tracking() {
+        let ssc = SscProfile::down_spread(dec!(100e-9));
+        assert_eq!(ssc.frequency(0.), 1.);
+        assert!((ssc.frequency(50e-9) - 0.995).abs() < 1e-12);
+
+        let ui = 250e-12;
+        let data = ssc.edges(ui, 1_000);
+        let clock = PhaseTracker {
+            bandwidth: dec!(500e6),
+        }
+        .track(&data, ui)
+        .into_iter()
+        .map(|t| t + ui / 2.)
+        .collect::<Vec<_>>();
+
+        let sent = prbs7(127);
+        assert_eq!(sent.iter().filter(|&&b| b).count(), 64);
+        let mut received = vec![false; 2];
+        received.extend(&sent);
+        let result = SscResult::new(&sent, &received, &data, &clock, ui);
+        assert_eq!((result.errors, result.latency), (0, 2));
+        assert!(result.max_tracking_error < 0.05);
+        assert!(result.error_free());
+    }
+}
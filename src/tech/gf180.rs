@@ -0,0 +1,1516 @@
+//! GF180MCU-specific implementations.
+
+use crate::analysis::dout_straps::WireParasitics;
+use crate::analysis::em_check::{EmLimit, EmRules};
+use crate::analysis::ir_drop::MetalStack;
+use crate::analysis::tap_density::TapRules;
+use crate::bias::TempSensorImpl;
+use crate::driver::DriverLayerMap;
+use crate::export::gds::PinLabelLayers;
+use crate::lane::MomCapParams;
+use crate::pll::loop_filter::LoopFilterRules;
+use crate::power::RcClampRules;
+use crate::report::area::AreaLayers;
+use crate::route::RouterKind;
+use crate::strongarm::array::SamplerArrayImpl;
+use crate::sweep::corners::{CornerLibrary, ModelInclude};
+use crate::tech::UcieTech;
+use crate::tiles::{
+    BjtIo, GuardRingImpl, GuardRingLayers, GuardRingTile, GuardRingTileParams, MosLengthRules,
+    MosTileParams, ResistorConn, ResistorIo, ResistorTileParams, TapIo, TapTileParams, TileKind,
+    WidthSpec,
+};
+use crate::verification::quick_drc::{LayerRules, QuickDrcRules};
+use atoll::{IoBuilder, Orientation, Tile, TileBuilder, TileWrapper};
+use gf180pdk::atoll::{Gf180ViaMaker, MosLength, NmosTile, PmosTile, PolyResistorTile};
+use gf180pdk::layers::{Metal1, Metal2, Metal3};
+use gf180pdk::Gf180Pdk;
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use spectre::Spectre;
+use std::path::{Path, PathBuf};
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::context::Installation;
+use substrate::error::Result;
+use substrate::geometry::align::AlignMode;
+use substrate::geometry::point::Point;
+use substrate::geometry::rect::Rect;
+use substrate::geometry::transform::Translate;
+use substrate::io::{LayoutType, MosIo, MosIoSchematic, Signal};
+use substrate::layout::element::Shape;
+use substrate::layout::{CellBuilder, ExportsLayoutData, Layout};
+use substrate::pdk::layers::{Layer, LayerId};
+use substrate::pdk::PdkLayers;
+use substrate::schematic::primitives::Capacitor;
+use substrate::schematic::ExportsNestedData;
+use substrate::simulation::options::SimOption;
+use substrate::simulation::{SimulationContext, Simulator};
+
+/// The poly pitch of GF180MCU MOS devices.
+const POLY_PITCH: i64 = 860;
+/// The metal 1 pitch of the GF180MCU ATOLL layer stack.
+const METAL1_PITCH: i64 = 560;
+/// The minimum width of a MOS finger.
+const MIN_FINGER_W: i64 = 220;
+
+/// Returns the GF180MCU MOS length for a channel length in nanometers.
+///
+/// Panics if the ATOLL MOS tiles do not support the requested length. Generators check
+/// their lengths with [`MosLengthRules::check_length`] and return an error before
+/// creating any MOS tiles, so only MOS tiles created from unchecked parameters panic.
+fn mos_length(l: Option<i64>) -> MosLength {
+    match l {
+        None | Some(280) => MosLength::L280,
+        Some(l) => panic!("unsupported GF180MCU MOS channel length: {l} nm"),
+    }
+}
+
+/// The width in nanometers of a MOS device of width `w`.
+///
+/// GF180MCU is a planar process, so each fin of a fin count becomes a minimum-width finger.
+fn planar_width(w: WidthSpec) -> i64 {
+    match w {
+        WidthSpec::Nm(w) => w,
+        WidthSpec::Fins(fins) => fins * MIN_FINGER_W,
+    }
+}
+
+/// The width in nanometers of each finger of a MOS device of width `w` with `nf` fingers.
+///
+/// Widths that do not divide evenly are rounded down to a whole finger width, and
+/// fingers are at least [`MIN_FINGER_W`] wide.
+fn finger_width(w: WidthSpec, nf: i64) -> i64 {
+    (planar_width(w) / nf).max(MIN_FINGER_W)
+}
+
+/// The width of the resistor tile used by the vertical driver.
+const VERTICAL_RES_W: i64 = 1_000;
+
+/// A GF180MCU UCIe implementation.
+pub struct Gf180Ucie;
+
+impl UcieTech<Gf180Pdk> for Gf180Ucie {
+    type MosTile = MosTile;
+    type TapTile = TapTile;
+    type ResistorTile = ResistorTile;
+    type GuardRingTile = GuardRingTile<Gf180Ucie>;
+    type Filler = Filler;
+    type ViaMaker = Gf180ViaMaker;
+    type Pin = Metal2;
+    type MomCapModel = Capacitor;
+    const BUFFER_SPACING: i64 = 3;
+    const GUARD_RING_ANNULAR_HEIGHT: i64 = 4;
+    const BUMP_RECT_WIDTH: i64 = 2_400;
+    const NWELL_MIN_SPACING: i64 = 600;
+    // The six-metal GF180MCU stack tops out at ATOLL layer 5.
+    const HORIZONTAL_DRIVER_LAYERS: DriverLayerMap = DriverLayerMap {
+        pin: 3,
+        strap: 4,
+        bump: 5,
+        top: 5,
+    };
+    const VERTICAL_DRIVER_LAYERS: DriverLayerMap = DriverLayerMap {
+        pin: 2,
+        strap: 3,
+        bump: 5,
+        top: 3,
+    };
+    const SAR_ADC_TOP_LAYER: usize = 3;
+
+    fn mos(params: MosTileParams) -> Self::MosTile {
+        MosTile::new(
+            planar_width(params.width()),
+            mos_length(params.l),
+            2,
+            params.tile_kind,
+        )
+    }
+    fn mos_nf(kind: TileKind, nf: i64, w: WidthSpec, l: Option<i64>) -> Self::MosTile {
+        MosTile::new(finger_width(w, nf), mos_length(l), nf, kind)
+    }
+    fn snap_width(_kind: TileKind, width: WidthSpec) -> WidthSpec {
+        WidthSpec::Nm(planar_width(width).max(MIN_FINGER_W))
+    }
+    fn snap_width_nf(_kind: TileKind, nf: i64, width: WidthSpec) -> WidthSpec {
+        WidthSpec::Nm(finger_width(width, nf) * nf)
+    }
+    fn tap(params: TapTileParams) -> Self::TapTile {
+        TapTile::new(params)
+    }
+    fn nf(legs: i64, w: i64) -> i64 {
+        let res_w = legs * (w + ResistorTile::LEG_SPACE);
+        let nf = (res_w + POLY_PITCH - 1) / POLY_PITCH;
+        nf + nf % 2
+    }
+    fn resistor(legs: i64, w: i64, l: i64, conn: ResistorConn) -> Self::ResistorTile {
+        ResistorTile::new(legs, w, l, conn)
+    }
+    fn unit_resistor(params: ResistorTileParams) -> Self::ResistorTile {
+        ResistorTile::new(1, VERTICAL_RES_W, params.l, ResistorConn::Series)
+    }
+    fn mom_cap_model(params: MomCapParams) -> Capacitor {
+        Capacitor::new(Decimal::from_f64(params.capacitance::<Self>()).unwrap())
+    }
+    fn filler(kind: TileKind, height: i64) -> Self::Filler {
+        Filler::new(kind, height)
+    }
+    fn filler_boundary_id(layers: &PdkLayers<Gf180Pdk>) -> LayerId {
+        layers.pr_bndry.drawing.id()
+    }
+    fn guard_ring(kind: TileKind, n_device: i64, nf: i64, height: i64) -> Self::GuardRingTile {
+        let width = (n_device * nf * POLY_PITCH + METAL1_PITCH - 1) / METAL1_PITCH;
+        GuardRingTile::new(GuardRingTileParams::new(
+            kind,
+            Rect::from_sides(0, 0, width, height),
+            2,
+        ))
+    }
+    fn via_maker() -> Self::ViaMaker {
+        Gf180ViaMaker
+    }
+    fn pin(layers: &PdkLayers<Gf180Pdk>) -> Self::Pin {
+        layers.metal2.clone()
+    }
+    fn nwell_id(layers: &PdkLayers<Gf180Pdk>) -> LayerId {
+        layers.nwell.drawing.id()
+    }
+    fn draw_dummy_mos(
+        cell: &mut TileBuilder<'_, Gf180Pdk>,
+        kind: TileKind,
+        nf: i64,
+        w: WidthSpec,
+        loc: Point,
+        orientation: Orientation,
+    ) -> Result<()> {
+        let inst = cell
+            .layout
+            .generate(TileWrapper::new(MosTile::new(
+                planar_width(w),
+                MosLength::L280,
+                nf,
+                kind,
+            )))
+            .orient(orientation.into());
+        let center = inst.bbox_rect().center();
+        cell.layout.draw(inst.translate(loc - center))?;
+        Ok(())
+    }
+}
+
+impl MetalStack for Gf180Ucie {
+    fn sheet_resistance(layer: usize) -> f64 {
+        [0.09, 0.09, 0.09, 0.09, 0.09, 0.04][layer]
+    }
+
+    fn via_resistance(below: usize) -> f64 {
+        [4.5, 4.5, 4.5, 4.5, 4.5][below]
+    }
+}
+
+impl WireParasitics for Gf180Ucie {
+    fn area_capacitance(layer: usize) -> f64 {
+        [37.0, 18.0, 12.0, 9.0, 7.0, 5.5][layer]
+    }
+
+    fn fringe_capacitance(layer: usize) -> f64 {
+        [40.0, 40.0, 40.0, 40.0, 40.0, 42.0][layer]
+    }
+
+    fn thickness(layer: usize) -> f64 {
+        [550., 550., 550., 550., 550., 900.][layer]
+    }
+}
+
+impl EmRules for Gf180Ucie {
+    fn wire_limit(layer: usize) -> EmLimit {
+        let rms = [1.0e-3, 1.0e-3, 1.0e-3, 1.0e-3, 1.0e-3, 3.0e-3][layer];
+        EmLimit {
+            rms,
+            peak: 10. * rms,
+        }
+    }
+
+    fn via_limit(_below: usize) -> EmLimit {
+        EmLimit {
+            rms: 0.5e-3,
+            peak: 5e-3,
+        }
+    }
+}
+
+impl MosLengthRules for Gf180Ucie {
+    const MOS_LENGTHS: &'static [i64] = &[280];
+}
+
+impl TapRules for Gf180Ucie {
+    const MAX_DIFF_TAP_DISTANCE: i64 = 20_000;
+}
+
+impl LoopFilterRules for Gf180Ucie {
+    const RES_SHEET_RESISTANCE: f64 = 1_000.;
+    const RES_W: i64 = 1_000;
+    const RES_MAX_L: i64 = 20_000;
+    const MOM_CAP_PER_LENGTH: f64 = 4e-20;
+    const MOM_MAX_FINGER_LENGTH: i64 = 50_000;
+}
+
+impl RcClampRules for Gf180Ucie {
+    const GATE_CAP_PER_AREA: f64 = 4.6e-21;
+    const MOSCAP_W: i64 = 4_000;
+    const MOSCAP_L: i64 = 4_000;
+}
+
+impl SamplerArrayImpl<Gf180Pdk> for Gf180Ucie {
+    type ClockPin = Metal3;
+    const CLOCK_WIRE_CAP_PER_LENGTH: f64 = 2e-19;
+    const SLICE_SPACING: i64 = 2;
+
+    fn clock_pin(layers: &PdkLayers<Gf180Pdk>) -> Self::ClockPin {
+        layers.metal3.clone()
+    }
+}
+
+impl TempSensorImpl<Gf180Pdk> for Gf180Ucie {
+    type MosTile = MosTile;
+    type TapTile = TapTile;
+    type ResistorTile = ResistorTile;
+    type PnpTile = PnpTile;
+    type ViaMaker = Gf180ViaMaker;
+
+    fn mos(params: MosTileParams) -> Self::MosTile {
+        <Gf180Ucie as UcieTech<Gf180Pdk>>::mos(params)
+    }
+    fn tap(params: TapTileParams) -> Self::TapTile {
+        TapTile::new(params)
+    }
+    fn resistor(legs: i64, w: i64, l: i64, conn: ResistorConn) -> Self::ResistorTile {
+        ResistorTile::new(legs, w, l, conn)
+    }
+    fn pnp(multiplier: i64) -> Self::PnpTile {
+        PnpTile::new(multiplier)
+    }
+    fn via_maker() -> Self::ViaMaker {
+        Gf180ViaMaker
+    }
+    fn snap_width(kind: TileKind, width: WidthSpec) -> WidthSpec {
+        <Gf180Ucie as UcieTech<Gf180Pdk>>::snap_width(kind, width)
+    }
+}
+
+/// The Spectre model library, relative to the PDK root.
+const SPECTRE_MODELS: &str = "libs.tech/spectre/sm141064.scs";
+
+/// A GF180MCU process corner.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum Gf180Corner {
+    /// Typical NMOS and PMOS.
+    #[default]
+    Typical,
+    /// Slow NMOS and PMOS.
+    Ss,
+    /// Fast NMOS and PMOS.
+    Ff,
+    /// Slow NMOS, fast PMOS.
+    Sf,
+    /// Fast NMOS, slow PMOS.
+    Fs,
+}
+
+impl Gf180Corner {
+    /// The name of the model library section for this corner.
+    pub fn section(&self) -> &'static str {
+        match self {
+            Gf180Corner::Typical => "typical",
+            Gf180Corner::Ss => "ss",
+            Gf180Corner::Ff => "ff",
+            Gf180Corner::Sf => "sf",
+            Gf180Corner::Fs => "fs",
+        }
+    }
+}
+
+/// The GF180MCU PDK whose simulation models a context includes.
+///
+/// [`try_gf180_ctx`](crate::try_gf180_ctx) installs the models of the PDK it is
+/// built with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gf180Models {
+    /// The root directory of the PDK.
+    pub pdk_root: PathBuf,
+}
+
+impl Installation for Gf180Models {}
+
+/// Includes the models of the corner from the [`Gf180Models`] installed in the
+/// simulation context.
+///
+/// If no models are installed, logs an error and includes nothing, so the simulator
+/// reports the missing models.
+impl SimOption<Spectre> for Gf180Corner {
+    fn set_option(
+        self,
+        opts: &mut <Spectre as Simulator>::Options,
+        ctx: &SimulationContext<Spectre>,
+    ) {
+        let Some(models) = ctx.ctx.get_installation::<Gf180Models>() else {
+            tracing::error!(corner = ?self, "no GF180MCU models are installed in the context");
+            return;
+        };
+        for include in Gf180Ucie::model_includes(&models.pdk_root, self) {
+            include.apply(opts);
+        }
+    }
+}
+
+impl CornerLibrary for Gf180Ucie {
+    type Corner = Gf180Corner;
+
+    fn corners() -> Vec<(&'static str, Gf180Corner)> {
+        [
+            Gf180Corner::Typical,
+            Gf180Corner::Ss,
+            Gf180Corner::Ff,
+            Gf180Corner::Sf,
+            Gf180Corner::Fs,
+        ]
+        .into_iter()
+        .map(|corner| (corner.section(), corner))
+        .collect()
+    }
+
+    fn nominal_voltage() -> Decimal {
+        dec!(3.3)
+    }
+
+    fn model_includes(pdk_root: &Path, corner: Gf180Corner) -> Vec<ModelInclude> {
+        vec![ModelInclude::section(
+            pdk_root.join(SPECTRE_MODELS),
+            corner.section(),
+        )]
+    }
+}
+
+impl AreaLayers<Gf180Pdk> for Gf180Ucie {
+    fn active_layers(layers: &PdkLayers<Gf180Pdk>) -> Vec<LayerId> {
+        vec![layers.comp.drawing.id()]
+    }
+
+    fn routing_layers(layers: &PdkLayers<Gf180Pdk>) -> Vec<(&'static str, LayerId)> {
+        vec![
+            ("Metal1", layers.metal1.drawing.id()),
+            ("Metal2", layers.metal2.drawing.id()),
+            ("Metal3", layers.metal3.drawing.id()),
+            ("Metal4", layers.metal4.drawing.id()),
+            ("Metal5", layers.metal5.drawing.id()),
+            ("MetalTop", layers.metaltop.drawing.id()),
+        ]
+    }
+}
+
+impl PinLabelLayers<Gf180Pdk> for Gf180Ucie {
+    const PIN_LABEL_LAYERS: &'static [(&'static str, i16, i16)] = &[
+        ("Metal1", 34, 10),
+        ("Metal2", 36, 10),
+        ("Metal3", 42, 10),
+        ("Metal4", 46, 10),
+        ("Metal5", 81, 10),
+        ("MetalTop", 53, 10),
+    ];
+}
+
+impl QuickDrcRules<Gf180Pdk> for Gf180Ucie {
+    const QUICK_DRC_RULES: &'static [(&'static str, LayerRules)] = &[
+        ("Metal1", LayerRules::new(230, 230, 144_400)),
+        ("Metal2", LayerRules::new(280, 280, 144_400)),
+        ("Metal3", LayerRules::new(280, 280, 144_400)),
+        ("Metal4", LayerRules::new(280, 280, 144_400)),
+        ("Metal5", LayerRules::new(280, 280, 144_400)),
+        ("MetalTop", LayerRules::new(440, 460, 562_500)),
+    ];
+}
+
+impl GuardRingImpl<Gf180Pdk> for Gf180Ucie {
+    type Pin = Metal1;
+    const IMPLANT_ENCLOSURE: i64 = 160;
+    const WELL_ENCLOSURE: i64 = 430;
+
+    fn guard_ring_layers(layers: &PdkLayers<Gf180Pdk>, kind: TileKind) -> GuardRingLayers {
+        match kind {
+            TileKind::N => GuardRingLayers {
+                diff: layers.comp.drawing.id(),
+                implant: layers.nplus.drawing.id(),
+                well: Some(layers.nwell.drawing.id()),
+            },
+            TileKind::P => GuardRingLayers {
+                diff: layers.comp.drawing.id(),
+                implant: layers.pplus.drawing.id(),
+                well: None,
+            },
+        }
+    }
+
+    fn guard_ring_pin(layers: &PdkLayers<Gf180Pdk>) -> Self::Pin {
+        layers.metal1.clone()
+    }
+
+    fn draw_guard_ring_contacts(cell: &mut TileBuilder<'_, Gf180Pdk>, rect: Rect) -> Result<()> {
+        const CONTACT_W: i64 = 220;
+        const CONTACT_SPACE: i64 = 250;
+        const CONTACT_ENCLOSURE: i64 = 70;
+
+        let metal1 = cell.ctx().layers.metal1.drawing.id();
+        let contact = cell.ctx().layers.contact.drawing.id();
+        cell.layout.draw(Shape::new(metal1, rect))?;
+
+        let n =
+            |len: i64| (len - 2 * CONTACT_ENCLOSURE + CONTACT_SPACE) / (CONTACT_W + CONTACT_SPACE);
+        let (nx, ny) = (n(rect.width()), n(rect.height()));
+        let span = |n: i64| n * CONTACT_W + (n - 1) * CONTACT_SPACE;
+        let x0 = rect.center().x - span(nx) / 2;
+        let y0 = rect.center().y - span(ny) / 2;
+        for i in 0..nx {
+            for j in 0..ny {
+                let x = x0 + i * (CONTACT_W + CONTACT_SPACE);
+                let y = y0 + j * (CONTACT_W + CONTACT_SPACE);
+                cell.layout.draw(Shape::new(
+                    contact,
+                    Rect::from_sides(x, y, x + CONTACT_W, y + CONTACT_W),
+                ))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A multi-finger MOS tile.
+///
+/// Even source/drain regions are connected to the source
+/// and odd regions are connected to the drain.
+#[derive(Serialize, Deserialize, Block, Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[substrate(io = "MosIo")]
+pub struct MosTile {
+    w: i64,
+    l: MosLength,
+    nf: i64,
+    kind: TileKind,
+}
+
+impl MosTile {
+    /// Creates a new [`MosTile`].
+    pub fn new(w: i64, l: MosLength, nf: i64, kind: TileKind) -> Self {
+        Self { w, l, nf, kind }
+    }
+}
+
+impl ExportsNestedData for MosTile {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for MosTile {
+    type LayoutData = ();
+}
+
+impl Tile<Gf180Pdk> for MosTile {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Gf180Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        cell.flatten();
+        let nf = self.nf as usize;
+        match self.kind {
+            TileKind::P => {
+                let pmos = cell.generate_primitive(PmosTile::new(self.w, self.l, self.nf));
+                for i in 0..nf {
+                    cell.connect(pmos.io().g[i], io.schematic.g);
+                }
+                for i in 0..=nf {
+                    cell.connect(
+                        pmos.io().sd[i],
+                        if i % 2 == 0 {
+                            io.schematic.s
+                        } else {
+                            io.schematic.d
+                        },
+                    );
+                }
+                cell.connect(pmos.io().b, io.schematic.b);
+                let pmos = cell.draw(pmos)?;
+                for i in 0..nf {
+                    io.layout.g.merge(pmos.layout.io().g[i].clone());
+                }
+                for i in 0..=nf {
+                    if i % 2 == 0 {
+                        io.layout.s.merge(pmos.layout.io().sd[i].clone());
+                    } else {
+                        io.layout.d.merge(pmos.layout.io().sd[i].clone());
+                    }
+                }
+                io.layout.b.merge(pmos.layout.io().b);
+            }
+            TileKind::N => {
+                let nmos = cell.generate_primitive(NmosTile::new(self.w, self.l, self.nf));
+                for i in 0..nf {
+                    cell.connect(nmos.io().g[i], io.schematic.g);
+                }
+                for i in 0..=nf {
+                    cell.connect(
+                        nmos.io().sd[i],
+                        if i % 2 == 0 {
+                            io.schematic.s
+                        } else {
+                            io.schematic.d
+                        },
+                    );
+                }
+                cell.connect(nmos.io().b, io.schematic.b);
+                let nmos = cell.draw(nmos)?;
+                for i in 0..nf {
+                    io.layout.g.merge(nmos.layout.io().g[i].clone());
+                }
+                for i in 0..=nf {
+                    if i % 2 == 0 {
+                        io.layout.s.merge(nmos.layout.io().sd[i].clone());
+                    } else {
+                        io.layout.d.merge(nmos.layout.io().sd[i].clone());
+                    }
+                }
+                io.layout.b.merge(nmos.layout.io().b);
+            }
+        }
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), RouterKind::Greedy));
+        cell.set_via_maker(Gf180ViaMaker);
+
+        Ok(((), ()))
+    }
+}
+
+/// A tile containing a N/P tap for biasing an N-well or P-substrate.
+/// These can be used to connect to the body terminals of MOS devices.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TapTile(TapTileParams);
+
+impl TapTile {
+    /// Creates a new [`TapTile`].
+    pub fn new(params: TapTileParams) -> Self {
+        Self(params)
+    }
+}
+
+impl Block for TapTile {
+    type Io = TapIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("tap_tile")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name(
+            match self.0.kind {
+                TileKind::N => "ntap_tile",
+                TileKind::P => "ptap_tile",
+            },
+            &self.0,
+        )
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl ExportsNestedData for TapTile {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for TapTile {
+    type LayoutData = ();
+}
+
+impl Tile<Gf180Pdk> for TapTile {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Gf180Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        cell.flatten();
+        match self.0.kind {
+            TileKind::N => {
+                let inst =
+                    cell.generate_primitive(gf180pdk::atoll::NtapTile::new(2 * self.0.mos_span, 2));
+                cell.connect(io.schematic.x, inst.io().vpb);
+                let inst = cell.draw(inst)?;
+                io.layout.x.merge(inst.layout.io().vpb);
+            }
+            TileKind::P => {
+                let inst =
+                    cell.generate_primitive(gf180pdk::atoll::PtapTile::new(2 * self.0.mos_span, 2));
+                cell.connect(io.schematic.x, inst.io().vnb);
+                let inst = cell.draw(inst)?;
+                io.layout.x.merge(inst.layout.io().vnb);
+            }
+        }
+        cell.set_router(crate::route::router(cell.ctx(), RouterKind::Greedy));
+        Ok(((), ()))
+    }
+}
+
+/// A poly resistor tile made of parallel legs.
+///
+/// Legs are connected in series or in parallel depending on the
+/// [`ResistorConn`] of the tile.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ResistorTile {
+    legs: i64,
+    w: i64,
+    l: i64,
+    conn: ResistorConn,
+}
+
+impl ResistorTile {
+    /// The spacing between adjacent resistor legs.
+    pub const LEG_SPACE: i64 = 400;
+
+    /// Creates a new [`ResistorTile`].
+    pub fn new(legs: i64, w: i64, l: i64, conn: ResistorConn) -> Self {
+        Self { legs, w, l, conn }
+    }
+}
+
+impl Block for ResistorTile {
+    type Io = ResistorIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("resistor_tile")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("resistor_tile", self)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl ExportsNestedData for ResistorTile {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for ResistorTile {
+    type LayoutData = ();
+}
+
+impl Tile<Gf180Pdk> for ResistorTile {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Gf180Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        cell.flatten();
+        let mut prev_n = io.schematic.p;
+        let mut prev_bbox: Option<Rect> = None;
+        for i in 0..self.legs {
+            let (p, n) = match self.conn {
+                ResistorConn::Series => {
+                    let n = if i == self.legs - 1 {
+                        io.schematic.n
+                    } else {
+                        cell.signal(format!("x{i}"), Signal::new())
+                    };
+                    (prev_n, n)
+                }
+                ResistorConn::Parallel => (io.schematic.p, io.schematic.n),
+            };
+            let mut leg = cell.generate_primitive(PolyResistorTile::new(self.w, self.l));
+            cell.connect(leg.io().p, p);
+            cell.connect(leg.io().n, n);
+            cell.connect(leg.io().b, io.schematic.b);
+            if let Some(prev_bbox) = prev_bbox {
+                leg.translate_mut(Point::new(
+                    prev_bbox.right() + Self::LEG_SPACE - leg.lcm_bounds().left(),
+                    0,
+                ));
+            }
+            prev_bbox = Some(leg.lcm_bounds());
+            let leg = cell.draw(leg)?;
+            if i == 0 {
+                io.layout.p.merge(leg.layout.io().p);
+            }
+            if i == self.legs - 1 || self.conn == ResistorConn::Parallel {
+                io.layout.n.merge(leg.layout.io().n);
+            }
+            io.layout.b.merge(leg.layout.io().b);
+            prev_n = n;
+        }
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), RouterKind::Greedy));
+        cell.set_via_maker(Gf180ViaMaker);
+
+        Ok(((), ()))
+    }
+}
+
+/// A vertical substrate PNP built from PMOS source/drain junctions.
+///
+/// The ATOLL tiles of GF180MCU include no bipolar device, so each unit emitter is a
+/// single-finger PMOS whose source, drain, and gate are tied to the emitter. The P+
+/// diffusion, the N-well, and the substrate form the emitter, base, and collector, and
+/// taps beneath the emitters contact the base and collector.
+///
+/// In simulation, only the emitter-base junction is modeled, by the junction diodes of
+/// the PMOS devices, so the tile is only accurate with its base and collector tied
+/// together, as in the PTAT core of a [`TempSensor`](crate::bias::TempSensor).
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PnpTile {
+    multiplier: i64,
+}
+
+impl PnpTile {
+    /// The width of each unit emitter.
+    pub const EMITTER_W: i64 = 2_000;
+
+    /// Creates a new [`PnpTile`] with `multiplier` unit emitters in parallel.
+    pub fn new(multiplier: i64) -> Self {
+        assert!(multiplier > 0, "a PNP needs at least one emitter");
+        Self { multiplier }
+    }
+}
+
+impl Block for PnpTile {
+    type Io = BjtIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("pnp_tile")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("pnp_tile", self)
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl ExportsNestedData for PnpTile {
+    type NestedData = ();
+}
+
+impl ExportsLayoutData for PnpTile {
+    type LayoutData = ();
+}
+
+impl Tile<Gf180Pdk> for PnpTile {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, Gf180Pdk>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let e = io.schematic.e;
+        let mut emitters = Vec::new();
+        let mut left_rect: Option<Rect> = None;
+        for _ in 0..self.multiplier {
+            let mut emitter = cell.generate_connected(
+                MosTile::new(Self::EMITTER_W, MosLength::L280, 1, TileKind::P),
+                MosIoSchematic {
+                    d: e,
+                    g: e,
+                    s: e,
+                    b: io.schematic.b,
+                },
+            );
+            if let Some(left_rect) = left_rect {
+                emitter.align_rect_mut(left_rect, AlignMode::Bottom, 0);
+                emitter.align_rect_mut(left_rect, AlignMode::ToTheRight, 0);
+            }
+            left_rect = Some(emitter.lcm_bounds());
+            emitters.push(emitter);
+        }
+
+        // The N-well tap contacts the base and the substrate tap beneath it the collector.
+        let mut ntap = cell.generate(TapTile::new(TapTileParams::new(
+            TileKind::N,
+            self.multiplier,
+        )));
+        ntap.align_rect_mut(emitters[0].lcm_bounds(), AlignMode::Left, 0);
+        ntap.align_rect_mut(emitters[0].lcm_bounds(), AlignMode::Beneath, 0);
+        let mut ptap = cell.generate(TapTile::new(TapTileParams::new(
+            TileKind::P,
+            self.multiplier,
+        )));
+        ptap.align_rect_mut(ntap.lcm_bounds(), AlignMode::Left, 0);
+        ptap.align_rect_mut(ntap.lcm_bounds(), AlignMode::Beneath, 0);
+        cell.connect(ntap.io().x, io.schematic.b);
+        cell.connect(ptap.io().x, io.schematic.c);
+
+        for emitter in emitters {
+            let emitter = cell.draw(emitter)?;
+            io.layout.e.merge(emitter.layout.io().s);
+        }
+        let ntap = cell.draw(ntap)?;
+        let ptap = cell.draw(ptap)?;
+        io.layout.b.merge(ntap.layout.io().x);
+        io.layout.c.merge(ptap.layout.io().x);
+
+        cell.set_top_layer(1);
+        cell.set_router(crate::route::router(cell.ctx(), RouterKind::Greedy));
+        cell.set_via_maker(Gf180ViaMaker);
+
+        Ok(((), ()))
+    }
+}
+
+/// A filler cell placed around the edge of a guard ring.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize, Block)]
+#[substrate(io = "()")]
+pub struct Filler {
+    kind: TileKind,
+    height: i64,
+}
+
+impl Filler {
+    /// The width of the filler.
+    pub const WIDTH: i64 = 2 * METAL1_PITCH;
+
+    /// Creates a new [`Filler`] with height given in layer 1 tracks.
+    pub fn new(kind: TileKind, height: i64) -> Self {
+        Self { kind, height }
+    }
+}
+
+impl ExportsLayoutData for Filler {
+    type LayoutData = ();
+}
+
+impl Layout<Gf180Pdk> for Filler {
+    fn layout(
+        &self,
+        _io: &mut <<Self as Block>::Io as LayoutType>::Builder,
+        cell: &mut CellBuilder<Gf180Pdk>,
+    ) -> substrate::error::Result<Self::LayoutData> {
+        let rect = Rect::from_sides(0, 0, Self::WIDTH, self.height * METAL1_PITCH);
+        cell.draw(Shape::new(cell.ctx.layers.pr_bndry.drawing.id(), rect))?;
+        if self.kind == TileKind::N {
+            cell.draw(Shape::new(cell.ctx.layers.nwell.drawing.id(), rect))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::adc::tb::SarAdcDacTb;
+    use crate::adc::{SarAdc, SarAdcParams, SarDac};
+    use crate::analysis::straps::StrapBudget;
+    use crate::bias::tb::{TempSensorSummary, TempSensorTb};
+    use crate::bias::{TempSensor, TempSensorParams};
+    use crate::buffer::{Buffer, InverterParams};
+    use crate::cache::GenerationCache;
+    use crate::config::{
+        generate_from_config, generate_from_config_with_cache, BlockConfig, ConfigError, DataRate,
+        LaneConfig, PackageType, PhyConfig, SamplerConfig,
+    };
+    use crate::driver::esd::{EsdSeries, EsdSeriesParams};
+    use crate::driver::{
+        CtlPolarity, DriverParams, DriverUnitParams, HorizontalDriver, SegmentPlacement,
+    };
+    use crate::lane::{
+        ClockLane, ClockLaneParams, ControlRxLane, ControlRxLaneParams, ControlTxLane,
+        ControlTxLaneParams, LaneRole, RxModule, TxModule,
+    };
+    use crate::loadbank::{LoadBank, LoadBankParams};
+    use crate::params::ParamsError;
+    use crate::power::{RcClampParams, RcClampTile};
+    use crate::report::{ArtifactMetadata, SimArtifact};
+    use crate::sideband::{SidebandRx, SidebandRxParams};
+    use crate::strongarm::{DummyPolicy, InputKind, StrongArm, StrongArmParams};
+    use crate::sweep::corners::CornerLibrary;
+    use crate::sweep::CornerSweep;
+    use crate::tech::gf180::{Gf180Ucie, PnpTile};
+    use crate::tech::UcieTech;
+    use crate::testsuite::run_testsuite;
+    use crate::tiles::{MosKind, TileKind, WidthSpec};
+    use crate::trim::{TrimResistor, TrimResistorParams};
+    use crate::verification::lvs::{check_lvs_clean, write_lvs_inputs, LvsParams, LvsTool};
+    use crate::{gf180_ctx, GenerationOptions};
+    use atoll::TileWrapper;
+    use gf180pdk::Gf180Pdk;
+    use rust_decimal_macros::dec;
+    use spectre::Spectre;
+    use std::path::{Path, PathBuf};
+    use substrate::block::Block;
+    use substrate::layout::Layout;
+    use substrate::schematic::Schematic;
+
+    /// Returns the netgen LVS tool configured for the open-source GF180MCU PDK.
+    fn gf180_lvs_tool() -> LvsTool {
+        let pdk_root = PathBuf::from(
+            std::env::var("GF180_PDK_ROOT")
+                .expect("the GF180_PDK_ROOT environment variable must be set"),
+        );
+        LvsTool::Netgen {
+            magicrc: pdk_root.join("libs.tech/magic/gf180mcuD.magicrc"),
+            setup: pdk_root.join("libs.tech/netgen/gf180mcuD_setup.tcl"),
+        }
+    }
+
+    #[test]
+    fn gf180_snaps_widths_to_whole_fingers() {
+        // 1,010 nm does not divide into 4 fingers, so each finger is 252 nm wide.
+        let snapped =
+            <Gf180Ucie as UcieTech<Gf180Pdk>>::snap_width_nf(TileKind::N, 4, WidthSpec::Nm(1_010));
+        assert_eq!(snapped, WidthSpec::Nm(1_008));
+        let mos = <Gf180Ucie as UcieTech<Gf180Pdk>>::mos_nf(TileKind::N, 4, snapped, None);
+        assert_eq!(mos.w * mos.nf, 1_008);
+
+        // Narrow devices are widened to the minimum finger width.
+        assert_eq!(
+            <Gf180Ucie as UcieTech<Gf180Pdk>>::snap_width_nf(TileKind::N, 4, WidthSpec::Nm(500)),
+            WidthSpec::Nm(880)
+        );
+    }
+
+    #[test]
+    fn gf180_strongarm_lvs() {
+        let work_dir = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/gf180_strongarm_lvs"
+        ));
+        let ctx = gf180_ctx();
+
+        let block = TileWrapper::new(StrongArm::<Gf180Ucie>::new(StrongArmParams {
+            nmos_kind: MosKind::Nom,
+            pmos_kind: MosKind::Nom,
+            half_tail_w: WidthSpec::Nm(2_000),
+            input_pair_w: WidthSpec::Nm(2_000),
+            inv_input_w: WidthSpec::Nm(2_000),
+            inv_precharge_w: WidthSpec::Nm(2_000),
+            precharge_w: WidthSpec::Nm(2_000),
+            input_kind: InputKind::P,
+            half_tail_l: None,
+            input_pair_l: None,
+            inv_input_l: None,
+            inv_precharge_l: None,
+            precharge_l: None,
+            dummies: DummyPolicy::default(),
+            precharge_keeper: None,
+            neutralization: None,
+        }));
+
+        let inputs = write_lvs_inputs::<_, Gf180Pdk, _>(&ctx, block, &work_dir)
+            .expect("failed to write LVS inputs");
+        check_lvs_clean(&LvsParams {
+            tool: gf180_lvs_tool(),
+            inputs,
+            work_dir: work_dir.join("lvs"),
+        });
+    }
+
+    #[test]
+    fn gf180_buffer_lvs() {
+        let work_dir = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/gf180_buffer_lvs"
+        ));
+        let ctx = gf180_ctx();
+
+        let block = TileWrapper::new(Buffer::<Gf180Ucie>::new(InverterParams {
+            nmos_kind: MosKind::Nom,
+            pmos_kind: MosKind::Nom,
+            nmos_w: WidthSpec::Nm(2_000),
+            pmos_w: WidthSpec::Nm(2_000),
+            nmos_l: None,
+            pmos_l: None,
+        }));
+
+        let inputs = write_lvs_inputs::<_, Gf180Pdk, _>(&ctx, block, &work_dir)
+            .expect("failed to write LVS inputs");
+        check_lvs_clean(&LvsParams {
+            tool: gf180_lvs_tool(),
+            inputs,
+            work_dir: work_dir.join("lvs"),
+        });
+    }
+
+    /// Generates `block` in the GF180MCU context and asserts that it is LVS clean,
+    /// writing the LVS inputs and results to `build/<name>`.
+    fn assert_lvs_clean<B>(block: B, name: &str)
+    where
+        B: Block + Schematic<Gf180Pdk> + Layout<Gf180Pdk> + Clone,
+    {
+        let work_dir = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/build")).join(name);
+        let ctx = gf180_ctx();
+        let inputs = write_lvs_inputs::<_, Gf180Pdk, _>(&ctx, block, &work_dir)
+            .expect("failed to write LVS inputs");
+        check_lvs_clean(&LvsParams {
+            tool: gf180_lvs_tool(),
+            inputs,
+            work_dir: work_dir.join("lvs"),
+        });
+    }
+
+    #[test]
+    fn gf180_esd_series_lvs() {
+        let block = TileWrapper::new(EsdSeries::<Gf180Ucie>::new(
+            EsdSeriesParams::builder().build().unwrap(),
+        ));
+
+        assert_lvs_clean(block, "gf180_esd_series_lvs");
+    }
+
+    #[test]
+    fn gf180_rc_clamp_lvs() {
+        let block = TileWrapper::new(RcClampTile::<Gf180Ucie>::new(
+            RcClampParams::builder().build().unwrap(),
+        ));
+
+        assert_lvs_clean(block, "gf180_rc_clamp_lvs");
+    }
+
+    #[test]
+    fn gf180_trim_resistor_lvs() {
+        let block = TileWrapper::new(TrimResistor::<Gf180Ucie>::new(
+            TrimResistorParams::builder().build().unwrap(),
+        ));
+
+        assert_lvs_clean(block, "gf180_trim_resistor_lvs");
+    }
+
+    #[test]
+    fn gf180_load_bank_lvs() {
+        let block = TileWrapper::new(LoadBank::<Gf180Ucie>::new(
+            LoadBankParams::builder().build().unwrap(),
+        ));
+
+        assert_lvs_clean(block, "gf180_load_bank_lvs");
+    }
+
+    #[test]
+    fn gf180_sideband_rx_lvs() {
+        let block = TileWrapper::new(SidebandRx::<Gf180Ucie>::new(
+            SidebandRxParams::builder()
+                .design_filter::<Gf180Ucie>(2e-9)
+                .unwrap()
+                .build()
+                .unwrap(),
+        ));
+
+        assert_lvs_clean(block, "gf180_sideband_rx_lvs");
+    }
+
+    #[test]
+    fn gf180_pnp_lvs() {
+        assert_lvs_clean(TileWrapper::new(PnpTile::new(8)), "gf180_pnp_lvs");
+    }
+
+    #[test]
+    fn gf180_temp_sensor_lvs() {
+        let block = TileWrapper::new(TempSensor::<Gf180Ucie>::new(
+            TempSensorParams::builder().build().unwrap(),
+        ));
+
+        assert_lvs_clean(block, "gf180_temp_sensor_lvs");
+    }
+
+    #[test]
+    fn gf180_temp_sensor_tracks_temperature() {
+        let work_dir = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/gf180_temp_sensor_tracks_temperature"
+        );
+        let dut = TileWrapper::new(TempSensor::<Gf180Ucie>::new(
+            TempSensorParams::builder().build().unwrap(),
+        ));
+        let pvts = Gf180Ucie::named_pvts(&["typical"]).unwrap();
+        let sweep = CornerSweep::new(pvts, move |pvt| {
+            TempSensorTb::<_, Gf180Pdk, _>::new(dut, pvt)
+        });
+
+        let summary =
+            TempSensorSummary::from_sweep(&sweep.run::<Spectre, _>(&gf180_ctx(), work_dir));
+        assert!(
+            !summary.curves.is_empty(),
+            "no curve spans two temperatures"
+        );
+        for curve in &summary.curves {
+            assert!(
+                curve.sensitivity > 0.,
+                "output does not rise with temperature at {} V",
+                curve.voltage
+            );
+        }
+    }
+
+    #[test]
+    fn gf180_sar_adc() {
+        let gds = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/gf180_sar_adc/layout.gds"
+        ));
+        gf180_ctx()
+            .write_layout(
+                TileWrapper::new(SarAdc::<Gf180Ucie>::new(
+                    SarAdcParams::builder().build().unwrap(),
+                )),
+                gds,
+            )
+            .expect("failed to write layout");
+    }
+
+    #[test]
+    fn gf180_sar_adc_rejects_invalid_bits() {
+        assert_eq!(
+            SarAdcParams::builder().bits(0).build(),
+            Err(ParamsError::TooSmall {
+                field: "bits",
+                value: 0,
+                min: 1
+            })
+        );
+        assert!(SarAdcParams::builder()
+            .bits(SarAdcParams::MAX_BITS + 1)
+            .build()
+            .is_err());
+
+        let gds = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/gf180_sar_adc_rejects_invalid_bits/layout.gds"
+        ));
+        let params = SarAdcParams {
+            bits: 0,
+            ..SarAdcParams::builder().build().unwrap()
+        };
+        assert!(gf180_ctx()
+            .write_layout(TileWrapper::new(SarAdc::<Gf180Ucie>::new(params)), gds)
+            .is_err());
+    }
+
+    #[test]
+    fn gf180_sar_dac_linearity() {
+        let work_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/build/gf180_sar_dac_linearity");
+        let dut = TileWrapper::new(SarDac::<Gf180Ucie>::new(
+            SarAdcParams::builder().build().unwrap().dac(),
+        ));
+        let pvts = Gf180Ucie::named_pvts(&["typical"]).unwrap();
+        let sweep = CornerSweep::new(pvts, move |pvt| {
+            // Keep the DAC output above VSS across the full code range.
+            let (vcm, vref) = (pvt.voltage / dec!(2), pvt.voltage / dec!(3));
+            SarAdcDacTb::<_, Gf180Pdk, _>::new(dut, dec!(100e-9), vcm, pvt).with_vref(vref)
+        });
+
+        for (pvt, linearity) in sweep.run::<Spectre, _>(&gf180_ctx(), work_dir).outputs {
+            assert!(
+                linearity.lsb < 0.,
+                "DAC output does not fall with increasing code at {pvt:?}"
+            );
+            assert_eq!(linearity.missing_codes(), Vec::<usize>::new());
+            assert!(
+                linearity.compliance(0.5, 0.5).passed(),
+                "DNL {} LSB or INL {} LSB exceeds 0.5 LSB at {pvt:?}",
+                linearity.max_dnl(),
+                linearity.max_inl()
+            );
+        }
+    }
+
+    /// A standard-package configuration with eight data lanes at the given rate.
+    fn gf180_phy_config(data_rate: DataRate) -> PhyConfig {
+        PhyConfig {
+            lanes: 8,
+            data_rate,
+            package: PackageType::Standard,
+            blocks: BlockConfig {
+                driver: gf180_lane_driver(),
+                sampler: SamplerConfig {
+                    strongarm: StrongArmParams::builder().build().unwrap(),
+                    buffer: InverterParams::builder().build().unwrap(),
+                },
+                lanes: LaneConfig {
+                    enabled_segments: 12,
+                    tx_buf: InverterParams::builder().build().unwrap(),
+                    ac_coupling: None,
+                    esd: None,
+                },
+            },
+            generation: GenerationOptions::default(),
+        }
+    }
+
+    fn gf180_lane_driver() -> DriverParams {
+        DriverParams {
+            unit: DriverUnitParams::builder().build().unwrap(),
+            num_segments: 16,
+            banks: 1,
+            supply_budget: None,
+            spare_segment: true,
+            esd: None,
+            placement: SegmentPlacement::Sequential,
+        }
+    }
+
+    #[test]
+    fn gf180_horizontal_driver_rejects_invalid_supply_budget() {
+        let driver = DriverParams {
+            supply_budget: Some(StrapBudget {
+                rms_current: dec!(0.02),
+                peak_current: dec!(0.08),
+                max_ir_drop: dec!(0),
+            }),
+            ..gf180_lane_driver()
+        };
+        let gds = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/gf180_horizontal_driver_rejects_invalid_supply_budget/layout.gds"
+        ));
+        assert!(gf180_ctx()
+            .write_layout(
+                TileWrapper::new(HorizontalDriver::<Gf180Ucie>::new(driver)),
+                gds
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn gf180_inverted_ctl_polarity_driver_lvs() {
+        // Several segments per bank, so that dummies and taps are filled in beside the
+        // control polarity inverters between adjacent units.
+        let block = TileWrapper::new(HorizontalDriver::<Gf180Ucie>::new(DriverParams {
+            unit: DriverUnitParams::builder()
+                .pu_ctl_polarity(CtlPolarity::ActiveLow)
+                .pd_ctl_polarity(CtlPolarity::ActiveHigh)
+                .build()
+                .unwrap(),
+            num_segments: 4,
+            ..gf180_lane_driver()
+        }));
+
+        assert_lvs_clean(block, "gf180_inverted_ctl_polarity_driver_lvs");
+    }
+
+    #[test]
+    fn gf180_horizontal_driver_generates_identical_units_once() {
+        let block = TileWrapper::new(HorizontalDriver::<Gf180Ucie>::new(DriverParams {
+            num_segments: 4,
+            banks: 3,
+            ..gf180_lane_driver()
+        }));
+        let gds = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/gf180_horizontal_driver_generates_identical_units_once/layout.gds"
+        ));
+        gf180_ctx()
+            .write_layout(block.clone(), &gds)
+            .expect("failed to write layout");
+
+        // Every unit of every bank is an instance of one cell.
+        let lib = read_gds_without_dates(&gds);
+        for prefix in [
+            "horizontal_driver_unit_",
+            "horizontal_driver_with_guard_ring_rails_",
+        ] {
+            assert_eq!(
+                lib.structs
+                    .iter()
+                    .filter(|cell| cell.name.starts_with(prefix))
+                    .count(),
+                1,
+                "expected one `{prefix}*` cell"
+            );
+        }
+
+        // The shared cell does not tie together the control nets of its instances.
+        assert_lvs_clean(
+            block,
+            "gf180_horizontal_driver_generates_identical_units_once",
+        );
+    }
+
+    #[test]
+    fn gf180_clock_lane_lvs() {
+        let block = TileWrapper::new(ClockLane::<Gf180Ucie>::new(ClockLaneParams {
+            driver: gf180_lane_driver(),
+            enabled_segments: 12,
+            clk_buf: InverterParams::builder().build().unwrap(),
+        }));
+
+        assert_lvs_clean(block, "gf180_clock_lane_lvs");
+    }
+
+    #[test]
+    fn gf180_control_tx_lane_lvs() {
+        let block = TileWrapper::new(ControlTxLane::<Gf180Ucie>::new(ControlTxLaneParams {
+            role: LaneRole::Valid,
+            driver: gf180_lane_driver(),
+            enabled_segments: 12,
+            buf: InverterParams::builder().build().unwrap(),
+        }));
+
+        assert_lvs_clean(block, "gf180_control_tx_lane_lvs");
+    }
+
+    #[test]
+    fn gf180_control_rx_lane_lvs() {
+        let block = TileWrapper::new(ControlRxLane::<Gf180Ucie>::new(ControlRxLaneParams {
+            role: LaneRole::Track,
+            sampler: StrongArmParams::builder().build().unwrap(),
+            buf: InverterParams::builder().build().unwrap(),
+            ac_coupling: None,
+            esd: None,
+        }));
+
+        assert_lvs_clean(block, "gf180_control_rx_lane_lvs");
+    }
+
+    #[test]
+    fn gf180_generate_from_config() {
+        let out_dir = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/gf180_generate_from_config"
+        ));
+        let ctx = gf180_ctx();
+
+        let manifest =
+            generate_from_config::<_, Gf180Ucie>(&ctx, &gf180_phy_config(DataRate::Gt4), &out_dir)
+                .expect("failed to generate module");
+        let roles = manifest
+            .cells
+            .iter()
+            .map(|cell| cell.role.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(roles, ["tx_module", "rx_module"]);
+        assert!(manifest.cells.iter().all(|cell| cell.gds.exists()));
+
+        assert!(matches!(
+            generate_from_config::<_, Gf180Ucie>(&ctx, &gf180_phy_config(DataRate::Gt8), &out_dir),
+            Err(ConfigError::DataRate {
+                data_rate: DataRate::Gt8,
+                max: DataRate::Gt4,
+            })
+        ));
+
+        let mut config = gf180_phy_config(DataRate::Gt4);
+        config.blocks.sampler.strongarm.input_pair_l = Some(500);
+        assert!(matches!(
+            generate_from_config::<_, Gf180Ucie>(&ctx, &config, &out_dir),
+            Err(ConfigError::Params(ParamsError::Unsupported {
+                field: "input_pair_l",
+                ..
+            }))
+        ));
+    }
+
+    /// Reads the GDS library at `path` with every creation and modification date cleared.
+    fn read_gds_without_dates(path: &Path) -> gds21::GdsLibrary {
+        let mut lib = gds21::GdsLibrary::load(path).expect("failed to read GDS");
+        lib.dates = Default::default();
+        for cell in &mut lib.structs {
+            cell.dates = Default::default();
+        }
+        lib
+    }
+
+    #[test]
+    fn gf180_generate_from_config_matches_serial() {
+        let out_dir = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/gf180_generate_from_config_matches_serial"
+        ));
+        let config = gf180_phy_config(DataRate::Gt4);
+        let manifest =
+            generate_from_config::<_, Gf180Ucie>(&gf180_ctx(), &config, out_dir.join("parallel"))
+                .expect("failed to generate module");
+
+        // A fresh context, so that the serial run shares no cached cells with the parallel one.
+        let ctx = gf180_ctx();
+        let serial_dir = out_dir.join("serial");
+        std::fs::create_dir_all(&serial_dir).unwrap();
+        ctx.write_layout(
+            TileWrapper::new(TxModule::<Gf180Ucie>::new(config.tx_module())),
+            serial_dir.join("tx_module.gds"),
+        )
+        .expect("failed to write TX module");
+        ctx.write_layout(
+            TileWrapper::new(RxModule::<Gf180Ucie>::new(config.rx_module())),
+            serial_dir.join("rx_module.gds"),
+        )
+        .expect("failed to write RX module");
+
+        for cell in &manifest.cells {
+            assert_eq!(
+                read_gds_without_dates(&cell.gds),
+                read_gds_without_dates(&serial_dir.join(format!("{}.gds", cell.role))),
+                "{} differs from serial generation",
+                cell.role
+            );
+        }
+    }
+
+    #[test]
+    fn gf180_generate_from_config_caches_each_module() {
+        let out_dir = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/gf180_generate_from_config_caches_each_module"
+        ));
+        let cache = GenerationCache::new(out_dir.join("cache"));
+        cache.clear().unwrap();
+        let ctx = gf180_ctx();
+        let cached_files = || std::fs::read_dir(cache.dir()).unwrap().count();
+
+        let mut config = gf180_phy_config(DataRate::Gt4);
+        generate_from_config_with_cache::<_, Gf180Ucie>(&ctx, &config, &out_dir, Some(&cache))
+            .expect("failed to generate module");
+        assert_eq!(cached_files(), 2);
+
+        // Only the RX module depends on the sampler, so the TX module is reused.
+        config.blocks.sampler.buffer = InverterParams::builder()
+            .nmos_w(2_000)
+            .pmos_w(4_000)
+            .build()
+            .unwrap();
+        generate_from_config_with_cache::<_, Gf180Ucie>(&ctx, &config, &out_dir, Some(&cache))
+            .expect("failed to generate module");
+        assert_eq!(cached_files(), 3);
+    }
+
+    #[test]
+    fn gf180_testsuite() {
+        let work_dir = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/gf180_testsuite"
+        ));
+        let ctx = gf180_ctx();
+
+        let report = run_testsuite::<_, Gf180Pdk, Gf180Ucie>(&ctx, &work_dir)
+            .expect("failed to run testsuite");
+        report
+            .write_artifact(&work_dir, &ArtifactMetadata::new("testsuite"))
+            .expect("failed to write testsuite report");
+        let failures = report.failures().collect::<Vec<_>>();
+        assert!(failures.is_empty(), "failing variants: {failures:#?}");
+    }
+}
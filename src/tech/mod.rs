@@ -1,3 +1,344 @@
 //! Technology-specific implementations.
 
+use crate::adc::SarAdcImpl;
+use crate::analysis::em_check::EmRules;
+use crate::analysis::ir_drop::MetalStack;
+use crate::analysis::straps::{StrapBudget, StrapPlanError, StrapPlanner};
+use crate::analysis::tap_density::TapRules;
+use crate::buffer::InverterImpl;
+use crate::config::DataRate;
+use crate::driver::{DriverLayerMap, HorizontalDriverImpl, VerticalDriverImpl};
+use crate::lane::{LaneImpl, MomCapParams};
+use crate::route::RouterKind;
+use crate::strongarm::{
+    StrongArmImpl, StrongArmWithClockBufferImpl, StrongArmWithOutputBuffersImpl,
+};
+use crate::tiles::{
+    MosLengthRules, MosTileParams, ResistorConn, ResistorIo, ResistorTileParams, TapIo,
+    TapTileParams, TileKind, WidthSpec,
+};
+use atoll::route::ViaMaker;
+use atoll::{Orientation, Tile, TileBuilder};
+use std::ops::Range;
+use substrate::block::Block;
+use substrate::error::Result;
+use substrate::geometry::dir::Dir;
+use substrate::geometry::point::Point;
+use substrate::geometry::rect::Rect;
+use substrate::io::{MosIo, TwoTerminalIo};
+use substrate::layout::Layout;
+use substrate::pdk::layers::{HasPin, LayerId};
+use substrate::pdk::{Pdk, PdkLayers};
+use substrate::schematic::schema::Schema;
+use substrate::schematic::Schematic;
+
+#[cfg(feature = "gf180")]
+pub mod gf180;
 pub mod sky130;
+
+/// A complete UCIe technology implementation.
+///
+/// Implementing this trait provides [`StrongArmImpl`], [`InverterImpl`],
+/// [`StrongArmWithOutputBuffersImpl`], [`StrongArmWithClockBufferImpl`],
+/// [`HorizontalDriverImpl`], [`VerticalDriverImpl`], [`LaneImpl`], and [`SarAdcImpl`]
+/// using a single consistent set of tiles.
+///
+/// The metal stack and EM rules are used to plan supply straps from a current budget,
+/// the tap rules decide where the vertical driver needs extra taps, and the MOS length
+/// rules reject channel lengths that the MOS tiles cannot draw.
+///
+/// A technology that lacks the driver tiles, such as a resistor or filler tile,
+/// cannot implement this trait and instead implements [`StrongArmImpl`],
+/// [`InverterImpl`], and the other per-block traits it supports directly. The blanket
+/// implementations below would otherwise conflict with those impls. This is the case
+/// for [`Sky130Ucie`](sky130::Sky130Ucie), which has no driver tiles.
+pub trait UcieTech<PDK: Pdk + Schema>: MetalStack + EmRules + TapRules + MosLengthRules {
+    /// The MOS tile.
+    type MosTile: Tile<PDK> + Block<Io = MosIo> + Clone;
+    /// The tap tile.
+    type TapTile: Tile<PDK> + Block<Io = TapIo> + Clone;
+    /// The resistor tile.
+    type ResistorTile: Tile<PDK> + Block<Io = ResistorIo> + Clone;
+    /// A guard ring tile.
+    type GuardRingTile: Tile<PDK> + Block<Io = TapIo> + Clone;
+    /// A filler layout cell.
+    type Filler: Layout<PDK>;
+    /// A PDK-specific via maker.
+    type ViaMaker: ViaMaker<PDK>;
+    /// The pin layer for driver unit cells.
+    type Pin: HasPin;
+    /// The schematic model of a [`MomCap`](crate::lane::MomCap).
+    type MomCapModel: Schematic<PDK> + Block<Io = TwoTerminalIo>;
+    /// The spacing between a StrongARM and its output buffers in ATOLL grid coordinates.
+    const BUFFER_SPACING: i64;
+    /// The spacing between a clock buffer and the block it drives in ATOLL grid coordinates.
+    const CLOCK_BUFFER_SPACING: i64 = Self::BUFFER_SPACING;
+    /// Height of guard ring top and bottom sides in layer 1 tracks.
+    const GUARD_RING_ANNULAR_HEIGHT: i64;
+    /// Width of the bump rectangle.
+    const BUMP_RECT_WIDTH: i64;
+    /// The layers used by the horizontal driver.
+    const HORIZONTAL_DRIVER_LAYERS: DriverLayerMap = DriverLayerMap::HORIZONTAL;
+    /// The layers used by the vertical driver.
+    const VERTICAL_DRIVER_LAYERS: DriverLayerMap = DriverLayerMap::VERTICAL;
+    /// The top layer of the SAR ADC.
+    ///
+    /// Must be at least the pin layer of [`UcieTech::HORIZONTAL_DRIVER_LAYERS`], on which
+    /// the DAC capacitors are drawn.
+    const SAR_ADC_TOP_LAYER: usize;
+    /// Width of the vertical driver bump strap.
+    const VERTICAL_BUMP_RECT_WIDTH: i64 = 1_080;
+    /// The direction in which the vertical driver bump strap runs.
+    const VERTICAL_BUMP_RECT_DIR: Dir = Dir::Vert;
+    /// Minimum n-well spacing.
+    const NWELL_MIN_SPACING: i64 = 1_270;
+    /// The router used by StrongARM tiles.
+    const STRONGARM_ROUTER: RouterKind = RouterKind::Greedy;
+    /// The router used by inverter and buffer tiles.
+    const INVERTER_ROUTER: RouterKind = RouterKind::Greedy;
+    /// The router used by the horizontal driver and its unit cells.
+    const HORIZONTAL_DRIVER_ROUTER: RouterKind = RouterKind::Greedy;
+    /// The router used by the vertical driver.
+    const VERTICAL_DRIVER_ROUTER: RouterKind = RouterKind::Greedy;
+    /// The fastest data rate at which lanes can run.
+    ///
+    /// Defaults to 4 GT/s, the lowest UCIe data rate.
+    const MAX_DATA_RATE: DataRate = DataRate::Gt4;
+
+    /// Creates an instance of the MOS tile.
+    fn mos(params: MosTileParams) -> Self::MosTile;
+    /// Creates an instance of the MOS tile with the given number of fingers.
+    ///
+    /// Here and in [`MosTileParams`], a channel length of `None` selects the minimum length.
+    fn mos_nf(kind: TileKind, nf: i64, w: WidthSpec, l: Option<i64>) -> Self::MosTile;
+    /// Creates an instance of the MOS tile for the driver transistors.
+    fn driver_mos(kind: TileKind, max_nf: i64, w: WidthSpec, l: Option<i64>) -> Self::MosTile {
+        Self::mos_nf(kind, max_nf, w, l)
+    }
+    /// Snaps a requested MOS width to a legal value, returning the achieved width.
+    fn snap_width(_kind: TileKind, width: WidthSpec) -> WidthSpec {
+        width
+    }
+    /// Snaps a requested width of a MOS tile with `nf` fingers to a legal value,
+    /// returning the total width that [`UcieTech::mos_nf`] achieves.
+    fn snap_width_nf(kind: TileKind, _nf: i64, width: WidthSpec) -> WidthSpec {
+        Self::snap_width(kind, width)
+    }
+    /// Creates an instance of the tap tile.
+    fn tap(params: TapTileParams) -> Self::TapTile;
+    /// Creates an instance of the tap tile spanning MOS tiles with `nf` fingers.
+    fn tap_nf(kind: TileKind, nf: i64) -> Self::TapTile {
+        Self::tap(TapTileParams::new(kind, nf / 2))
+    }
+    /// The number of fingers needed for the MOS tile to match the width of the resistor tile.
+    ///
+    /// Must return an even number of fingers.
+    fn nf(legs: i64, w: i64) -> i64;
+    /// Creates an instance of the resistor tile.
+    fn resistor(legs: i64, w: i64, l: i64, conn: ResistorConn) -> Self::ResistorTile;
+    /// Creates an instance of the single-leg resistor tile used by the vertical driver.
+    fn unit_resistor(params: ResistorTileParams) -> Self::ResistorTile;
+    /// Creates the schematic model of a [`MomCap`](crate::lane::MomCap) with the given
+    /// parameters.
+    fn mom_cap_model(params: MomCapParams) -> Self::MomCapModel;
+    /// Creates a filler to be placed around the edge of the guard ring with height given in layer 1 tracks.
+    fn filler(kind: TileKind, height: i64) -> Self::Filler;
+    /// Returns the filler boundary layer ID.
+    fn filler_boundary_id(layers: &PdkLayers<PDK>) -> LayerId;
+    /// Creates a guard ring around the given number of horizontally-arrayed MOS devices,
+    /// each with the given `nf`. `height` gives the height of the contained devices in layer 1 tracks.
+    fn guard_ring(kind: TileKind, n_device: i64, nf: i64, height: i64) -> Self::GuardRingTile;
+    /// Creates a PDK-specific via maker.
+    fn via_maker() -> Self::ViaMaker;
+    /// Returns the driver unit pin layer.
+    fn pin(layers: &PdkLayers<PDK>) -> Self::Pin;
+    /// Returns the n-well layer ID.
+    fn nwell_id(layers: &PdkLayers<PDK>) -> LayerId;
+    /// Transforms the given n-well rectangle to be DRC clean.
+    fn nwell_transform(rect: Rect) -> Rect {
+        rect
+    }
+    /// Draws a dummy MOS with the given position/orientation.
+    fn draw_dummy_mos(
+        cell: &mut TileBuilder<'_, PDK>,
+        kind: TileKind,
+        nf: i64,
+        w: WidthSpec,
+        loc: Point,
+        orientation: Orientation,
+    ) -> Result<()>;
+}
+
+impl<PDK: Pdk + Schema, T: UcieTech<PDK>> StrongArmImpl<PDK> for T {
+    type MosTile = T::MosTile;
+    type TapTile = T::TapTile;
+    type ViaMaker = T::ViaMaker;
+    const ROUTER: RouterKind = <T as UcieTech<PDK>>::STRONGARM_ROUTER;
+
+    fn mos(params: MosTileParams) -> Self::MosTile {
+        <T as UcieTech<PDK>>::mos(params)
+    }
+    fn tap(params: TapTileParams) -> Self::TapTile {
+        <T as UcieTech<PDK>>::tap(params)
+    }
+    fn via_maker() -> Self::ViaMaker {
+        <T as UcieTech<PDK>>::via_maker()
+    }
+    fn snap_width(kind: TileKind, width: WidthSpec) -> WidthSpec {
+        <T as UcieTech<PDK>>::snap_width(kind, width)
+    }
+}
+
+impl<PDK: Pdk + Schema, T: UcieTech<PDK>> InverterImpl<PDK> for T {
+    type MosTile = T::MosTile;
+    type TapTile = T::TapTile;
+    type ViaMaker = T::ViaMaker;
+    const ROUTER: RouterKind = <T as UcieTech<PDK>>::INVERTER_ROUTER;
+
+    fn mos(params: MosTileParams) -> Self::MosTile {
+        <T as UcieTech<PDK>>::mos(params)
+    }
+    fn tap(params: TapTileParams) -> Self::TapTile {
+        <T as UcieTech<PDK>>::tap(params)
+    }
+    fn via_maker() -> Self::ViaMaker {
+        <T as UcieTech<PDK>>::via_maker()
+    }
+    fn snap_width(kind: TileKind, width: WidthSpec) -> WidthSpec {
+        <T as UcieTech<PDK>>::snap_width(kind, width)
+    }
+}
+
+impl<PDK: Pdk + Schema, T: UcieTech<PDK>> StrongArmWithOutputBuffersImpl<PDK> for T {
+    const BUFFER_SPACING: i64 = <T as UcieTech<PDK>>::BUFFER_SPACING;
+}
+
+impl<PDK: Pdk + Schema, T: UcieTech<PDK>> StrongArmWithClockBufferImpl<PDK> for T {
+    const CLOCK_BUFFER_SPACING: i64 = <T as UcieTech<PDK>>::CLOCK_BUFFER_SPACING;
+}
+
+impl<PDK: Pdk + Schema, T: UcieTech<PDK>> HorizontalDriverImpl<PDK> for T {
+    type MosTile = T::MosTile;
+    type TapTile = T::TapTile;
+    type Filler = T::Filler;
+    type GuardRingTile = T::GuardRingTile;
+    type ResistorTile = T::ResistorTile;
+    type ViaMaker = T::ViaMaker;
+    type Pin = T::Pin;
+    const GUARD_RING_ANNULAR_HEIGHT: i64 = <T as UcieTech<PDK>>::GUARD_RING_ANNULAR_HEIGHT;
+    const BUMP_RECT_WIDTH: i64 = <T as UcieTech<PDK>>::BUMP_RECT_WIDTH;
+    const LAYER_MAP: DriverLayerMap = <T as UcieTech<PDK>>::HORIZONTAL_DRIVER_LAYERS;
+    const ROUTER: RouterKind = <T as UcieTech<PDK>>::HORIZONTAL_DRIVER_ROUTER;
+
+    fn mos(kind: TileKind, max_nf: i64, w: impl Into<WidthSpec>, l: Option<i64>) -> Self::MosTile {
+        <T as UcieTech<PDK>>::mos_nf(kind, max_nf, w.into(), l)
+    }
+    fn driver_mos(
+        kind: TileKind,
+        max_nf: i64,
+        w: impl Into<WidthSpec>,
+        l: Option<i64>,
+    ) -> Self::MosTile {
+        <T as UcieTech<PDK>>::driver_mos(kind, max_nf, w.into(), l)
+    }
+    fn snap_width(kind: TileKind, width: WidthSpec) -> WidthSpec {
+        <T as UcieTech<PDK>>::snap_width(kind, width)
+    }
+    fn snap_width_nf(kind: TileKind, max_nf: i64, width: WidthSpec) -> WidthSpec {
+        <T as UcieTech<PDK>>::snap_width_nf(kind, max_nf, width)
+    }
+    fn tap(kind: TileKind, nf: i64) -> Self::TapTile {
+        <T as UcieTech<PDK>>::tap_nf(kind, nf)
+    }
+    fn nf(legs: i64, w: i64) -> i64 {
+        <T as UcieTech<PDK>>::nf(legs, w)
+    }
+    fn resistor(legs: i64, w: i64, l: i64, conn: ResistorConn) -> Self::ResistorTile {
+        <T as UcieTech<PDK>>::resistor(legs, w, l, conn)
+    }
+    fn filler(kind: TileKind, height: i64) -> Self::Filler {
+        <T as UcieTech<PDK>>::filler(kind, height)
+    }
+    fn filler_boundary_id(layers: &PdkLayers<PDK>) -> LayerId {
+        <T as UcieTech<PDK>>::filler_boundary_id(layers)
+    }
+    fn guard_ring(kind: TileKind, n_device: i64, nf: i64, height: i64) -> Self::GuardRingTile {
+        <T as UcieTech<PDK>>::guard_ring(kind, n_device, nf, height)
+    }
+    fn via_maker() -> Self::ViaMaker {
+        <T as UcieTech<PDK>>::via_maker()
+    }
+    fn pin(layers: &PdkLayers<PDK>) -> Self::Pin {
+        <T as UcieTech<PDK>>::pin(layers)
+    }
+    fn plan_straps(
+        planner: &StrapPlanner,
+        layers: Range<usize>,
+        budget: &StrapBudget,
+    ) -> std::result::Result<Vec<i64>, StrapPlanError> {
+        planner.plan::<T>(layers, budget)
+    }
+    fn draw_dummy_mos(
+        cell: &mut TileBuilder<'_, PDK>,
+        kind: TileKind,
+        nf: i64,
+        w: WidthSpec,
+        loc: Point,
+        orientation: Orientation,
+    ) -> Result<()> {
+        <T as UcieTech<PDK>>::draw_dummy_mos(cell, kind, nf, w, loc, orientation)
+    }
+}
+
+impl<PDK: Pdk + Schema, T: UcieTech<PDK>> LaneImpl<PDK> for T {
+    type MomCapModel = <T as UcieTech<PDK>>::MomCapModel;
+    const INPUT_BUFFER_SPACING: i64 = <T as UcieTech<PDK>>::CLOCK_BUFFER_SPACING;
+    const MAX_DATA_RATE: DataRate = <T as UcieTech<PDK>>::MAX_DATA_RATE;
+
+    fn mom_cap_model(params: MomCapParams) -> Self::MomCapModel {
+        <T as UcieTech<PDK>>::mom_cap_model(params)
+    }
+}
+
+impl<PDK: Pdk + Schema, T: UcieTech<PDK>> SarAdcImpl<PDK> for T {
+    const TOP_LAYER: usize = <T as UcieTech<PDK>>::SAR_ADC_TOP_LAYER;
+}
+
+impl<PDK: Pdk + Schema, T: UcieTech<PDK>> VerticalDriverImpl<PDK> for T {
+    type MosTile = T::MosTile;
+    type TapTile = T::TapTile;
+    type ResistorTile = T::ResistorTile;
+    type ViaMaker = T::ViaMaker;
+    type Pin = T::Pin;
+    const LAYER_MAP: DriverLayerMap = <T as UcieTech<PDK>>::VERTICAL_DRIVER_LAYERS;
+    const ROUTER: RouterKind = <T as UcieTech<PDK>>::VERTICAL_DRIVER_ROUTER;
+    const BUMP_RECT_WIDTH: i64 = <T as UcieTech<PDK>>::VERTICAL_BUMP_RECT_WIDTH;
+    const BUMP_RECT_DIR: Dir = <T as UcieTech<PDK>>::VERTICAL_BUMP_RECT_DIR;
+    const NWELL_MIN_SPACING: i64 = <T as UcieTech<PDK>>::NWELL_MIN_SPACING;
+    const MAX_DIFF_TAP_DISTANCE: i64 = <T as TapRules>::MAX_DIFF_TAP_DISTANCE;
+
+    fn mos(params: MosTileParams) -> Self::MosTile {
+        <T as UcieTech<PDK>>::mos(params)
+    }
+    fn tap(params: TapTileParams) -> Self::TapTile {
+        <T as UcieTech<PDK>>::tap(params)
+    }
+    fn resistor(params: ResistorTileParams) -> Self::ResistorTile {
+        <T as UcieTech<PDK>>::unit_resistor(params)
+    }
+    fn via_maker() -> Self::ViaMaker {
+        <T as UcieTech<PDK>>::via_maker()
+    }
+    fn nwell_id(layers: &PdkLayers<PDK>) -> LayerId {
+        <T as UcieTech<PDK>>::nwell_id(layers)
+    }
+    fn nwell_transform(rect: Rect) -> Rect {
+        <T as UcieTech<PDK>>::nwell_transform(rect)
+    }
+    fn pin(layers: &PdkLayers<PDK>) -> Self::Pin {
+        <T as UcieTech<PDK>>::pin(layers)
+    }
+    fn snap_width(kind: TileKind, width: WidthSpec) -> WidthSpec {
+        <T as UcieTech<PDK>>::snap_width(kind, width)
+    }
+}
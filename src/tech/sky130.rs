@@ -1,30 +1,88 @@
 //! SKY130-specific implementations.
 
+use crate::analysis::dout_straps::WireParasitics;
+use crate::analysis::em_check::{EmLimit, EmRules};
+use crate::analysis::ir_drop::MetalStack;
+use crate::analysis::tap_density::TapRules;
 use crate::buffer::InverterImpl;
-use crate::strongarm::{StrongArmImpl, StrongArmWithOutputBuffersImpl};
-use crate::tiles::{MosTileParams, TapIo, TapTileParams, TileKind};
-use atoll::route::GreedyRouter;
+use crate::export::gds::PinLabelLayers;
+use crate::pll::loop_filter::LoopFilterRules;
+use crate::power::RcClampRules;
+use crate::report::area::AreaLayers;
+use crate::route::RouterKind;
+use crate::strongarm::array::SamplerArrayImpl;
+use crate::strongarm::{
+    StrongArmImpl, StrongArmWithClockBufferImpl, StrongArmWithOutputBuffersImpl,
+};
+use crate::sweep::corners::CornerLibrary;
+use crate::tiles::{
+    GuardRingImpl, GuardRingLayers, MosLengthRules, MosTileParams, TapIo, TapTileParams, TileKind,
+    WidthSpec,
+};
+use crate::verification::quick_drc::{LayerRules, QuickDrcRules};
 use atoll::{IoBuilder, Tile, TileBuilder};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 use sky130pdk::atoll::{MosLength, NmosTile, PmosTile, Sky130ViaMaker};
+use sky130pdk::corner::Sky130Corner;
+use sky130pdk::layers::{Li1, Met3};
 use sky130pdk::Sky130Pdk;
 use substrate::arcstr;
 use substrate::arcstr::ArcStr;
 use substrate::block::Block;
+use substrate::error::Result;
+use substrate::geometry::rect::Rect;
 use substrate::io::MosIo;
+use substrate::layout::element::Shape;
 use substrate::layout::ExportsLayoutData;
+use substrate::pdk::layers::{Layer, LayerId};
+use substrate::pdk::PdkLayers;
 use substrate::schematic::ExportsNestedData;
 
 /// A SKY130 UCIe implementation.
+///
+/// SKY130 has no resistor or filler tiles, so it cannot generate the drivers and does
+/// not implement [`UcieTech`](crate::tech::UcieTech). The StrongARM, inverter, and
+/// sampler traits are implemented directly instead.
 pub struct Sky130Ucie;
 
+/// The minimum width of a MOS device.
+const MIN_MOS_W: i64 = 420;
+
+/// Returns the SKY130 MOS length for a channel length in nanometers.
+///
+/// Panics if the ATOLL MOS tiles do not support the requested length. Generators check
+/// their lengths with [`MosLengthRules::check_length`] and return an error before
+/// creating any MOS tiles, so only MOS tiles created from unchecked parameters panic.
+fn mos_length(l: Option<i64>) -> MosLength {
+    match l {
+        None | Some(150) => MosLength::L150,
+        Some(l) => panic!("unsupported SKY130 MOS channel length: {l} nm"),
+    }
+}
+
+/// The width in nanometers of a MOS device of width `w`.
+///
+/// SKY130 is a planar process, so each fin of a fin count becomes a minimum-width device.
+fn planar_width(w: WidthSpec) -> i64 {
+    match w {
+        WidthSpec::Nm(w) => w,
+        WidthSpec::Fins(fins) => fins * MIN_MOS_W,
+    }
+}
+
 impl StrongArmImpl<Sky130Pdk> for Sky130Ucie {
     type MosTile = TwoFingerMosTile;
     type TapTile = TapTile;
     type ViaMaker = Sky130ViaMaker;
 
     fn mos(params: MosTileParams) -> Self::MosTile {
-        TwoFingerMosTile::new(params.w, MosLength::L150, params.tile_kind)
+        TwoFingerMosTile::new(
+            planar_width(params.width()),
+            mos_length(params.l),
+            params.tile_kind,
+        )
     }
     fn tap(params: TapTileParams) -> Self::TapTile {
         TapTile::new(params)
@@ -32,21 +90,28 @@ impl StrongArmImpl<Sky130Pdk> for Sky130Ucie {
     fn via_maker() -> Self::ViaMaker {
         Sky130ViaMaker
     }
+    fn snap_width(_kind: TileKind, width: WidthSpec) -> WidthSpec {
+        WidthSpec::Nm(planar_width(width).max(MIN_MOS_W))
+    }
 }
 
+/// Inverters use the same tiles as the StrongARM.
 impl InverterImpl<Sky130Pdk> for Sky130Ucie {
-    type MosTile = TwoFingerMosTile;
-    type TapTile = TapTile;
-    type ViaMaker = Sky130ViaMaker;
+    type MosTile = <Self as StrongArmImpl<Sky130Pdk>>::MosTile;
+    type TapTile = <Self as StrongArmImpl<Sky130Pdk>>::TapTile;
+    type ViaMaker = <Self as StrongArmImpl<Sky130Pdk>>::ViaMaker;
 
     fn mos(params: MosTileParams) -> Self::MosTile {
-        TwoFingerMosTile::new(params.w, MosLength::L150, params.tile_kind)
+        <Self as StrongArmImpl<Sky130Pdk>>::mos(params)
     }
     fn tap(params: TapTileParams) -> Self::TapTile {
-        TapTile::new(params)
+        <Self as StrongArmImpl<Sky130Pdk>>::tap(params)
     }
     fn via_maker() -> Self::ViaMaker {
-        Sky130ViaMaker
+        <Self as StrongArmImpl<Sky130Pdk>>::via_maker()
+    }
+    fn snap_width(kind: TileKind, width: WidthSpec) -> WidthSpec {
+        <Self as StrongArmImpl<Sky130Pdk>>::snap_width(kind, width)
     }
 }
 
@@ -54,6 +119,195 @@ impl StrongArmWithOutputBuffersImpl<Sky130Pdk> for Sky130Ucie {
     const BUFFER_SPACING: i64 = 3;
 }
 
+impl StrongArmWithClockBufferImpl<Sky130Pdk> for Sky130Ucie {
+    const CLOCK_BUFFER_SPACING: i64 = 3;
+}
+
+impl SamplerArrayImpl<Sky130Pdk> for Sky130Ucie {
+    type ClockPin = Met3;
+    const CLOCK_WIRE_CAP_PER_LENGTH: f64 = 2e-19;
+    const SLICE_SPACING: i64 = 2;
+
+    fn clock_pin(layers: &PdkLayers<Sky130Pdk>) -> Self::ClockPin {
+        layers.met3.clone()
+    }
+}
+
+impl MetalStack for Sky130Ucie {
+    fn sheet_resistance(layer: usize) -> f64 {
+        [12.8, 0.125, 0.125, 0.047, 0.047, 0.0285][layer]
+    }
+
+    fn via_resistance(below: usize) -> f64 {
+        [9.3, 4.5, 3.41, 3.41, 0.38][below]
+    }
+}
+
+impl WireParasitics for Sky130Ucie {
+    fn area_capacitance(layer: usize) -> f64 {
+        [36.99, 25.78, 17.5, 12.37, 8.42, 6.32][layer]
+    }
+
+    fn fringe_capacitance(layer: usize) -> f64 {
+        [40.7, 40.57, 37.6, 40.0, 36.8, 38.1][layer]
+    }
+
+    fn thickness(layer: usize) -> f64 {
+        [100., 360., 360., 845., 845., 1_260.][layer]
+    }
+}
+
+impl EmRules for Sky130Ucie {
+    fn wire_limit(layer: usize) -> EmLimit {
+        let rms = [0.2e-3, 1.8e-3, 1.8e-3, 5.6e-3, 5.6e-3, 11.2e-3][layer];
+        EmLimit {
+            rms,
+            peak: 10. * rms,
+        }
+    }
+
+    fn via_limit(below: usize) -> EmLimit {
+        let rms = [0.29e-3, 0.28e-3, 0.48e-3, 0.48e-3, 2.49e-3][below];
+        EmLimit {
+            rms,
+            peak: 10. * rms,
+        }
+    }
+}
+
+impl MosLengthRules for Sky130Ucie {
+    const MOS_LENGTHS: &'static [i64] = &[150];
+}
+
+impl TapRules for Sky130Ucie {
+    const MAX_DIFF_TAP_DISTANCE: i64 = 15_000;
+}
+
+impl LoopFilterRules for Sky130Ucie {
+    const RES_SHEET_RESISTANCE: f64 = 2_000.;
+    const RES_W: i64 = 1_000;
+    const RES_MAX_L: i64 = 20_000;
+    const MOM_CAP_PER_LENGTH: f64 = 5e-20;
+    const MOM_MAX_FINGER_LENGTH: i64 = 50_000;
+}
+
+impl RcClampRules for Sky130Ucie {
+    const GATE_CAP_PER_AREA: f64 = 8.5e-21;
+    const MOSCAP_W: i64 = 4_000;
+    const MOSCAP_L: i64 = 4_000;
+}
+
+/// The SKY130 corners include their own model sections when set as simulator options.
+impl CornerLibrary for Sky130Ucie {
+    type Corner = Sky130Corner;
+
+    fn corners() -> Vec<(&'static str, Sky130Corner)> {
+        vec![
+            ("tt", Sky130Corner::Tt),
+            ("ss", Sky130Corner::Ss),
+            ("ff", Sky130Corner::Ff),
+            ("sf", Sky130Corner::Sf),
+            ("fs", Sky130Corner::Fs),
+        ]
+    }
+
+    fn nominal_voltage() -> Decimal {
+        dec!(1.8)
+    }
+}
+
+impl AreaLayers<Sky130Pdk> for Sky130Ucie {
+    fn active_layers(layers: &PdkLayers<Sky130Pdk>) -> Vec<LayerId> {
+        vec![layers.diff.drawing.id(), layers.tap.drawing.id()]
+    }
+
+    fn routing_layers(layers: &PdkLayers<Sky130Pdk>) -> Vec<(&'static str, LayerId)> {
+        vec![
+            ("li1", layers.li1.drawing.id()),
+            ("met1", layers.met1.drawing.id()),
+            ("met2", layers.met2.drawing.id()),
+            ("met3", layers.met3.drawing.id()),
+            ("met4", layers.met4.drawing.id()),
+            ("met5", layers.met5.drawing.id()),
+        ]
+    }
+}
+
+impl PinLabelLayers<Sky130Pdk> for Sky130Ucie {
+    const PIN_LABEL_LAYERS: &'static [(&'static str, i16, i16)] = &[
+        ("li1", 67, 5),
+        ("met1", 68, 5),
+        ("met2", 69, 5),
+        ("met3", 70, 5),
+        ("met4", 71, 5),
+        ("met5", 72, 5),
+    ];
+}
+
+impl QuickDrcRules<Sky130Pdk> for Sky130Ucie {
+    const QUICK_DRC_RULES: &'static [(&'static str, LayerRules)] = &[
+        ("li1", LayerRules::new(170, 170, 56_100)),
+        ("met1", LayerRules::new(140, 140, 83_000)),
+        ("met2", LayerRules::new(140, 140, 67_600)),
+        ("met3", LayerRules::new(300, 300, 240_000)),
+        ("met4", LayerRules::new(300, 300, 240_000)),
+        ("met5", LayerRules::new(1_600, 1_600, 4_000_000)),
+    ];
+}
+
+impl GuardRingImpl<Sky130Pdk> for Sky130Ucie {
+    type Pin = Li1;
+    const IMPLANT_ENCLOSURE: i64 = 130;
+    const WELL_ENCLOSURE: i64 = 180;
+
+    fn guard_ring_layers(layers: &PdkLayers<Sky130Pdk>, kind: TileKind) -> GuardRingLayers {
+        match kind {
+            TileKind::N => GuardRingLayers {
+                diff: layers.tap.drawing.id(),
+                implant: layers.nsdm.drawing.id(),
+                well: Some(layers.nwell.drawing.id()),
+            },
+            TileKind::P => GuardRingLayers {
+                diff: layers.tap.drawing.id(),
+                implant: layers.psdm.drawing.id(),
+                well: None,
+            },
+        }
+    }
+
+    fn guard_ring_pin(layers: &PdkLayers<Sky130Pdk>) -> Self::Pin {
+        layers.li1.clone()
+    }
+
+    fn draw_guard_ring_contacts(cell: &mut TileBuilder<'_, Sky130Pdk>, rect: Rect) -> Result<()> {
+        const LICON_W: i64 = 170;
+        const LICON_SPACE: i64 = 170;
+        const LICON_ENCLOSURE: i64 = 120;
+
+        let li1 = cell.ctx().layers.li1.drawing.id();
+        let licon1 = cell.ctx().layers.licon1.drawing.id();
+        cell.layout.draw(Shape::new(li1, rect))?;
+
+        let n = |len: i64| (len - 2 * LICON_ENCLOSURE + LICON_SPACE) / (LICON_W + LICON_SPACE);
+        let (nx, ny) = (n(rect.width()), n(rect.height()));
+        let span = |n: i64| n * LICON_W + (n - 1) * LICON_SPACE;
+        let x0 = rect.center().x - span(nx) / 2;
+        let y0 = rect.center().y - span(ny) / 2;
+        for i in 0..nx {
+            for j in 0..ny {
+                let x = x0 + i * (LICON_W + LICON_SPACE);
+                let y = y0 + j * (LICON_W + LICON_SPACE);
+                cell.layout.draw(Shape::new(
+                    licon1,
+                    Rect::from_sides(x, y, x + LICON_W, y + LICON_W),
+                ))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// A two-finger MOS tile.
 #[derive(Serialize, Deserialize, Block, Copy, Clone, Debug, Hash, PartialEq, Eq)]
 #[substrate(io = "MosIo")]
@@ -79,6 +333,7 @@ impl ExportsLayoutData for TwoFingerMosTile {
 }
 
 impl Tile<Sky130Pdk> for TwoFingerMosTile {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
     fn tile<'a>(
         &self,
         io: IoBuilder<'a, Self>,
@@ -120,7 +375,7 @@ impl Tile<Sky130Pdk> for TwoFingerMosTile {
         }
 
         cell.set_top_layer(1);
-        cell.set_router(GreedyRouter::new());
+        cell.set_router(crate::route::router(cell.ctx(), RouterKind::Greedy));
         cell.set_via_maker(Sky130ViaMaker);
 
         Ok(((), ()))
@@ -147,12 +402,12 @@ impl Block for TapTile {
     }
 
     fn name(&self) -> ArcStr {
-        arcstr::format!(
-            "{}tap_tile",
+        crate::block_name(
             match self.0.kind {
-                TileKind::N => "n",
-                TileKind::P => "p",
-            }
+                TileKind::N => "ntap_tile",
+                TileKind::P => "ptap_tile",
+            },
+            &self.0,
         )
     }
 
@@ -170,6 +425,7 @@ impl ExportsLayoutData for TapTile {
 }
 
 impl Tile<Sky130Pdk> for TapTile {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
     fn tile<'a>(
         &self,
         io: IoBuilder<'a, Self>,
@@ -199,29 +455,192 @@ impl Tile<Sky130Pdk> for TapTile {
                 io.layout.x.merge(inst.layout.io().vnb);
             }
         }
-        cell.set_router(GreedyRouter::new());
+        cell.set_router(crate::route::router(cell.ctx(), RouterKind::Greedy));
         Ok(((), ()))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::buffer::{Buffer, InverterParams};
-    use crate::sky130_ctx;
+    use crate::bias::idac::{Idac, IdacParams};
+    use crate::bias::TempSensorParams;
+    use crate::buffer::{Buffer, InverterImpl, InverterParams};
+    use crate::esd::{RxEsd, RxEsdParams};
+    use crate::gates::{Dff, DffParams};
+    use crate::params::ParamsError;
+    use crate::pll::charge_pump::{ChargePump, ChargePumpParams};
+    use crate::pll::lock_detector::{LockDetector, LockDetectorParams};
+    use crate::report::area::area;
+    use crate::report::{ArtifactMetadata, SimArtifact};
+    use crate::rx::cmfb::{Cmfb, CmfbParams};
+    use crate::scan::{ConfigChain, ConfigChainParams};
+    use crate::strongarm::array::{SamplerArray, SamplerArrayParams};
     use crate::strongarm::tb::{ComparatorDecision, StrongArmTranTb};
-    use crate::strongarm::{InputKind, StrongArm, StrongArmParams, StrongArmWithOutputBuffers};
+    use crate::strongarm::{
+        DummyPolicy, InputKind, Neutralization, PrechargeKeeper, ResetParams, StrongArm,
+        StrongArmImpl, StrongArmParams, StrongArmWithClockBuffer, StrongArmWithOutputBuffers,
+        StrongArmWithReset,
+    };
+    use crate::sweep::corners::CornerLibrary;
+    use crate::sweep::CornerSweep;
     use crate::tech::sky130::Sky130Ucie;
-    use crate::tiles::MosKind;
+    use crate::testsuite::run_cell_testsuite;
+    use crate::tiles::{
+        GuardRingTile, GuardRingTileParams, MosKind, MosTileParams, TileKind, WidthSpec,
+    };
+    use crate::verification::drc::{check_drc_clean, DrcParams, DrcTool};
+    use crate::verification::lvs::{check_lvs_clean, write_lvs_inputs, LvsParams, LvsTool};
+    use crate::verification::quick_drc::{assert_quick_drc_clean, quick_drc};
+    use crate::verification::schematic_only::check_schematic_only;
+    use crate::{sky130_ctx, sky130_open_ctx, GenerationOptions, Sky130CtxBuilder};
     use atoll::TileWrapper;
     use rust_decimal::Decimal;
     use rust_decimal_macros::dec;
     use sky130pdk::corner::Sky130Corner;
-    use sky130pdk::Sky130CommercialSchema;
-    use spice::netlist::NetlistOptions;
-    use spice::Spice;
+    use sky130pdk::{Sky130CommercialSchema, Sky130OpenSchema, Sky130Pdk};
+    use spectre::Spectre;
     use std::path::PathBuf;
+    use substrate::block::Block;
+    use substrate::geometry::rect::Rect;
+    use substrate::layout::Layout;
     use substrate::pdk::corner::Pvt;
-    use substrate::schematic::netlist::ConvertibleNetlister;
+    use substrate::schematic::Schematic;
+
+    fn sky130_open_pdk_root() -> PathBuf {
+        PathBuf::from(
+            std::env::var("SKY130_OPEN_PDK_ROOT")
+                .expect("the SKY130_OPEN_PDK_ROOT environment variable must be set"),
+        )
+    }
+
+    /// Returns parameters for running Magic DRC on `gds` with the open-source SKY130A rules.
+    fn sky130_open_drc_params(gds: PathBuf, work_dir: PathBuf) -> DrcParams {
+        let deck = sky130_open_pdk_root().join("libs.tech/magic/sky130A.magicrc");
+        DrcParams::new(DrcTool::Magic, deck, gds, work_dir)
+    }
+
+    /// Returns the netgen LVS tool configured for the open-source SKY130A PDK.
+    fn sky130_open_lvs_tool() -> LvsTool {
+        let pdk_root = sky130_open_pdk_root();
+        LvsTool::Netgen {
+            magicrc: pdk_root.join("libs.tech/magic/sky130A.magicrc"),
+            setup: pdk_root.join("libs.tech/netgen/sky130A_setup.tcl"),
+        }
+    }
+
+    /// Returns the Calibre LVS tool configured with the commercial SKY130 rule deck.
+    fn sky130_commercial_lvs_tool() -> LvsTool {
+        LvsTool::Calibre {
+            rules: PathBuf::from(
+                std::env::var("SKY130_CALIBRE_LVS_RULES")
+                    .expect("the SKY130_CALIBRE_LVS_RULES environment variable must be set"),
+            ),
+        }
+    }
+
+    /// Generates `block` in the commercial SKY130 context, writes its LVS inputs to
+    /// `build/<name>`, and asserts that it is LVS clean.
+    ///
+    /// Panics if `SKY130_CALIBRE_LVS_RULES` is not set.
+    fn assert_lvs_clean<B>(block: B, name: &str)
+    where
+        B: Block + Schematic<Sky130Pdk> + Layout<Sky130Pdk> + Clone,
+    {
+        let tool = sky130_commercial_lvs_tool();
+        let work_dir = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/build")).join(name);
+        let ctx = sky130_ctx();
+        let inputs = write_lvs_inputs::<_, Sky130CommercialSchema, _>(&ctx, block, &work_dir)
+            .expect("failed to write LVS inputs");
+        check_lvs_clean(&LvsParams {
+            tool,
+            inputs,
+            work_dir: work_dir.join("lvs"),
+        });
+    }
+
+    #[test]
+    fn sky130_snaps_fin_counts_to_planar_widths() {
+        let params = MosTileParams::new(MosKind::Nom, TileKind::N, WidthSpec::Fins(4))
+            .snapped(<Sky130Ucie as InverterImpl<Sky130Pdk>>::snap_width);
+        assert_eq!(params.w, 1_680);
+        assert_eq!(params.width(), WidthSpec::Nm(1_680));
+
+        let params = StrongArmParams::builder()
+            .input_pair_w(WidthSpec::Fins(4))
+            .build()
+            .unwrap()
+            .snapped(<Sky130Ucie as StrongArmImpl<Sky130Pdk>>::snap_width);
+        assert_eq!(params.input_pair_w, WidthSpec::Nm(1_680));
+        assert_eq!(params.precharge_w, WidthSpec::Nm(1_000));
+    }
+
+    #[test]
+    fn sky130_rejects_unsupported_lengths() {
+        let mut params = StrongArmParams::builder().build().unwrap();
+        assert!(params.check_lengths::<Sky130Ucie>().is_ok());
+
+        params.input_pair_l = Some(500);
+        assert!(matches!(
+            params.check_lengths::<Sky130Ucie>(),
+            Err(ParamsError::Unsupported {
+                field: "input_pair_l",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn sky130_strongarm_rejects_unsupported_lengths() {
+        let params = StrongArmParams {
+            input_pair_l: Some(500),
+            ..StrongArmParams::builder().build().unwrap()
+        };
+        let gds = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/sky130_strongarm_rejects_unsupported_lengths/layout.gds"
+        ));
+        assert!(sky130_ctx()
+            .write_layout(TileWrapper::new(StrongArm::<Sky130Ucie>::new(params)), gds)
+            .is_err());
+    }
+
+    #[test]
+    fn sky130_temp_sensor_default_lengths() {
+        // There is no SKY130 bipolar tile yet, so the sensor itself cannot be generated.
+        let mut params = TempSensorParams::builder().build().unwrap();
+        assert!(params.check_lengths::<Sky130Ucie>().is_ok());
+
+        params.startup_l = Some(8_000);
+        assert!(matches!(
+            params.check_lengths::<Sky130Ucie>(),
+            Err(ParamsError::Unsupported {
+                field: "startup_l",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn sky130_guard_ring() {
+        let ctx = sky130_ctx();
+
+        for kind in [TileKind::N, TileKind::P] {
+            let block = TileWrapper::new(GuardRingTile::<Sky130Ucie>::new(
+                GuardRingTileParams::new(kind, Rect::from_sides(0, 0, 20, 10), 2),
+            ));
+
+            assert_quick_drc_clean(&quick_drc::<Sky130Ucie, _, _>(&ctx, block.clone()));
+            let report = area::<Sky130Ucie, _, _>(&ctx, block);
+            assert!(
+                report.active_area > 0,
+                "{kind:?} guard ring has no tap diffusion"
+            );
+            assert!(
+                report.active_utilization() < 1.,
+                "{kind:?} guard ring does not enclose an empty region"
+            );
+        }
+    }
 
     #[test]
     fn sky130_strongarm_sim() {
@@ -230,12 +649,20 @@ mod tests {
         let dut = TileWrapper::new(StrongArm::<Sky130Ucie>::new(StrongArmParams {
             nmos_kind: MosKind::Nom,
             pmos_kind: MosKind::Nom,
-            half_tail_w: 1_000,
-            input_pair_w: 1_000,
-            inv_input_w: 1_000,
-            inv_precharge_w: 1_000,
-            precharge_w: 1_000,
+            half_tail_w: WidthSpec::Nm(1_000),
+            input_pair_w: WidthSpec::Nm(1_000),
+            inv_input_w: WidthSpec::Nm(1_000),
+            inv_precharge_w: WidthSpec::Nm(1_000),
+            precharge_w: WidthSpec::Nm(1_000),
             input_kind,
+            half_tail_l: None,
+            input_pair_l: None,
+            inv_input_l: None,
+            inv_precharge_l: None,
+            precharge_l: None,
+            dummies: DummyPolicy::default(),
+            precharge_keeper: None,
+            neutralization: None,
         }));
         let pvt = Pvt {
             corner: Sky130Corner::Tt,
@@ -290,117 +717,387 @@ mod tests {
     }
 
     #[test]
-    fn sky130_strongarm_lvs() {
-        let work_dir = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/build/strongarm_lvs"));
-        let gds_path = work_dir.join("layout.gds");
-        let netlist_path = work_dir.join("netlist.sp");
+    fn sky130_strongarm_corners() {
+        let work_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/build/strongarm_corners");
+        let dut = TileWrapper::new(StrongArm::<Sky130Ucie>::new(StrongArmParams {
+            nmos_kind: MosKind::Nom,
+            pmos_kind: MosKind::Nom,
+            half_tail_w: WidthSpec::Nm(1_000),
+            input_pair_w: WidthSpec::Nm(1_000),
+            inv_input_w: WidthSpec::Nm(1_000),
+            inv_precharge_w: WidthSpec::Nm(1_000),
+            precharge_w: WidthSpec::Nm(1_000),
+            input_kind: InputKind::P,
+            half_tail_l: None,
+            input_pair_l: None,
+            inv_input_l: None,
+            inv_precharge_l: None,
+            precharge_l: None,
+            dummies: DummyPolicy::default(),
+            precharge_keeper: None,
+            neutralization: None,
+        }));
+        let pvts = Sky130Ucie::named_pvts(&["tt", "ss", "ff"]).unwrap();
+        let sweep = CornerSweep::new(pvts, move |pvt| {
+            StrongArmTranTb::new(dut, dec!(0.65), dec!(0.55), true, pvt)
+        });
         let ctx = sky130_ctx();
 
+        let decisions = sweep.run::<Spectre, _>(&ctx, work_dir);
+        for (pvt, decision) in decisions.outputs {
+            assert_eq!(
+                decision,
+                Some(ComparatorDecision::Pos),
+                "comparator produced incorrect decision at {pvt:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn sky130_strongarm_lvs() {
         let block = TileWrapper::new(StrongArm::<Sky130Ucie>::new(StrongArmParams {
             nmos_kind: MosKind::Nom,
             pmos_kind: MosKind::Nom,
-            half_tail_w: 1_000,
-            input_pair_w: 1_000,
-            inv_input_w: 1_000,
-            inv_precharge_w: 1_000,
-            precharge_w: 1_000,
+            half_tail_w: WidthSpec::Nm(1_000),
+            input_pair_w: WidthSpec::Nm(1_000),
+            inv_input_w: WidthSpec::Nm(1_000),
+            inv_precharge_w: WidthSpec::Nm(1_000),
+            precharge_w: WidthSpec::Nm(1_000),
             input_kind: InputKind::P,
+            half_tail_l: None,
+            input_pair_l: None,
+            inv_input_l: None,
+            inv_precharge_l: None,
+            precharge_l: None,
+            dummies: DummyPolicy::default(),
+            precharge_keeper: None,
+            neutralization: None,
         }));
 
-        let scir = ctx
-            .export_scir(block)
-            .unwrap()
-            .scir
-            .convert_schema::<Sky130CommercialSchema>()
-            .unwrap()
-            .convert_schema::<Spice>()
-            .unwrap()
+        assert_lvs_clean(block, "strongarm_lvs");
+    }
+
+    #[test]
+    fn sky130_idac_lvs() {
+        let params = IdacParams::builder()
+            .binary_bits(2)
+            .thermometer_bits(1)
             .build()
             .unwrap();
-        Spice
-            .write_scir_netlist_to_file(&scir, netlist_path, NetlistOptions::default())
-            .expect("failed to write netlist");
+        assert!(params.check_lengths::<Sky130Ucie>().is_ok());
+        let block = TileWrapper::new(Idac::<Sky130Ucie>::new(params));
 
-        ctx.write_layout(block, gds_path)
-            .expect("failed to write layout");
+        assert_lvs_clean(block, "idac_lvs");
     }
 
     #[test]
-    fn sky130_buffer_lvs() {
-        let work_dir = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/build/buffer_lvs"));
-        let gds_path = work_dir.join("layout.gds");
-        let netlist_path = work_dir.join("netlist.sp");
-        let ctx = sky130_ctx();
+    fn sky130_charge_pump_lvs() {
+        let params = ChargePumpParams::builder().build().unwrap();
+        assert!(params.check_lengths::<Sky130Ucie>().is_ok());
+        let block = TileWrapper::new(ChargePump::<Sky130Ucie>::new(params));
 
+        assert_lvs_clean(block, "charge_pump_lvs");
+    }
+
+    #[test]
+    fn sky130_rx_esd_lvs() {
+        let block = TileWrapper::new(RxEsd::<Sky130Ucie>::new(
+            RxEsdParams::builder().build().unwrap(),
+        ));
+
+        assert_lvs_clean(block, "rx_esd_lvs");
+    }
+
+    #[test]
+    fn sky130_cmfb_lvs() {
+        let block = TileWrapper::new(Cmfb::<Sky130Ucie>::new(
+            CmfbParams::builder().build().unwrap(),
+        ));
+
+        assert_lvs_clean(block, "cmfb_lvs");
+    }
+
+    #[test]
+    fn sky130_lock_detector_lvs() {
+        let params = LockDetectorParams::builder().build().unwrap();
+        assert!(params.check_lengths::<Sky130Ucie>().is_ok());
+        let block = TileWrapper::new(LockDetector::<Sky130Ucie>::new(params));
+
+        assert_lvs_clean(block, "lock_detector_lvs");
+    }
+
+    #[test]
+    fn sky130_strongarm_double_sided_dummies_lvs() {
+        let block = TileWrapper::new(StrongArm::<Sky130Ucie>::new(
+            StrongArmParams::builder()
+                .dummies(DummyPolicy::DoubleSided { count: 2 })
+                .build()
+                .unwrap(),
+        ));
+
+        assert_lvs_clean(block, "strongarm_double_sided_dummies_lvs");
+    }
+
+    #[test]
+    fn sky130_strongarm_with_reset_lvs() {
+        for input_kind in [InputKind::N, InputKind::P] {
+            let block = TileWrapper::new(StrongArmWithReset::<Sky130Ucie>::new(
+                StrongArmParams::builder()
+                    .input_kind(input_kind)
+                    .build()
+                    .unwrap(),
+                ResetParams::default(),
+            ));
+
+            assert_lvs_clean(block, &format!("strongarm_with_reset_lvs/{input_kind:?}"));
+        }
+    }
+
+    #[test]
+    fn sky130_sampler_array_lvs() {
+        let block = TileWrapper::new(SamplerArray::<Sky130Ucie>::new(
+            SamplerArrayParams::builder().slices(4).build().unwrap(),
+        ));
+
+        assert_lvs_clean(block, "sampler_array_lvs");
+    }
+
+    #[test]
+    fn sky130_strongarm_delayed_keeper_lvs() {
+        let block = TileWrapper::new(StrongArm::<Sky130Ucie>::new(
+            StrongArmParams::builder()
+                .precharge_keeper(Some(PrechargeKeeper {
+                    w: 420,
+                    l: None,
+                    delayed_clock: true,
+                }))
+                .build()
+                .unwrap(),
+        ));
+
+        assert_lvs_clean(block, "strongarm_delayed_keeper_lvs");
+    }
+
+    #[test]
+    fn sky130_strongarm_neutralization_lvs() {
+        let block = TileWrapper::new(StrongArm::<Sky130Ucie>::new(
+            StrongArmParams::builder()
+                .neutralization(Some(Neutralization {
+                    percent: 50,
+                    l: None,
+                }))
+                .build()
+                .unwrap(),
+        ));
+
+        assert_lvs_clean(block, "strongarm_neutralization_lvs");
+    }
+
+    #[test]
+    fn sky130_buffer_lvs() {
         let block = TileWrapper::new(Buffer::<Sky130Ucie>::new(InverterParams {
             nmos_kind: MosKind::Nom,
             pmos_kind: MosKind::Nom,
-            nmos_w: 1_000,
-            pmos_w: 1_000,
+            nmos_w: WidthSpec::Nm(1_000),
+            pmos_w: WidthSpec::Nm(1_000),
+            nmos_l: None,
+            pmos_l: None,
         }));
 
-        let scir = ctx
-            .export_scir(block)
-            .unwrap()
-            .scir
-            .convert_schema::<Sky130CommercialSchema>()
-            .unwrap()
-            .convert_schema::<Spice>()
-            .unwrap()
-            .build()
-            .unwrap();
-        Spice
-            .write_scir_netlist_to_file(&scir, netlist_path, NetlistOptions::default())
-            .expect("failed to write netlist");
-
-        ctx.write_layout(block, gds_path)
-            .expect("failed to write layout");
+        assert_lvs_clean(block, "buffer_lvs");
     }
 
     #[test]
     fn sky130_strongarm_with_output_buffers_lvs() {
-        let work_dir = PathBuf::from(concat!(
-            env!("CARGO_MANIFEST_DIR"),
-            "/build/strongarm_with_output_buffers_lvs"
-        ));
-        let gds_path = work_dir.join("layout.gds");
-        let netlist_path = work_dir.join("netlist.sp");
-        let ctx = sky130_ctx();
-
         let block = TileWrapper::new(StrongArmWithOutputBuffers::<Sky130Ucie>::new(
             StrongArmParams {
                 nmos_kind: MosKind::Nom,
                 pmos_kind: MosKind::Nom,
-                half_tail_w: 1_000,
-                input_pair_w: 1_000,
-                inv_input_w: 1_000,
-                inv_precharge_w: 1_000,
-                precharge_w: 1_000,
+                half_tail_w: WidthSpec::Nm(1_000),
+                input_pair_w: WidthSpec::Nm(1_000),
+                inv_input_w: WidthSpec::Nm(1_000),
+                inv_precharge_w: WidthSpec::Nm(1_000),
+                precharge_w: WidthSpec::Nm(1_000),
                 input_kind: InputKind::P,
+                half_tail_l: None,
+                input_pair_l: None,
+                inv_input_l: None,
+                inv_precharge_l: None,
+                precharge_l: None,
+                dummies: DummyPolicy::default(),
+                precharge_keeper: None,
+                neutralization: None,
             },
             InverterParams {
                 nmos_kind: MosKind::Nom,
                 pmos_kind: MosKind::Nom,
-                nmos_w: 1_000,
-                pmos_w: 1_000,
+                nmos_w: WidthSpec::Nm(1_000),
+                pmos_w: WidthSpec::Nm(1_000),
+                nmos_l: None,
+                pmos_l: None,
             },
         ));
 
-        let scir = ctx
-            .export_scir(block)
-            .unwrap()
-            .scir
-            .convert_schema::<Sky130CommercialSchema>()
-            .unwrap()
-            .convert_schema::<Spice>()
-            .unwrap()
-            .build()
-            .unwrap();
-        Spice
-            .write_scir_netlist_to_file(&scir, netlist_path, NetlistOptions::default())
-            .expect("failed to write netlist");
+        assert_lvs_clean(block, "strongarm_with_output_buffers_lvs");
+    }
+
+    #[test]
+    fn sky130_strongarm_with_clock_buffer_lvs() {
+        let block = TileWrapper::new(StrongArmWithClockBuffer::<Sky130Ucie>::new(
+            StrongArmParams::builder().build().unwrap(),
+            InverterParams::builder().build().unwrap(),
+        ));
+
+        assert_lvs_clean(block, "strongarm_with_clock_buffer_lvs");
+    }
+
+    #[test]
+    fn sky130_open_strongarm_lvs() {
+        let work_dir = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/open_strongarm_lvs"
+        ));
+        let ctx = sky130_open_ctx();
+
+        let block = TileWrapper::new(StrongArm::<Sky130Ucie>::new(StrongArmParams {
+            nmos_kind: MosKind::Nom,
+            pmos_kind: MosKind::Nom,
+            half_tail_w: WidthSpec::Nm(1_000),
+            input_pair_w: WidthSpec::Nm(1_000),
+            inv_input_w: WidthSpec::Nm(1_000),
+            inv_precharge_w: WidthSpec::Nm(1_000),
+            precharge_w: WidthSpec::Nm(1_000),
+            input_kind: InputKind::P,
+            half_tail_l: None,
+            input_pair_l: None,
+            inv_input_l: None,
+            inv_precharge_l: None,
+            precharge_l: None,
+            dummies: DummyPolicy::default(),
+            precharge_keeper: None,
+            neutralization: None,
+        }));
+
+        let inputs = write_lvs_inputs::<_, Sky130OpenSchema, _>(&ctx, block, &work_dir)
+            .expect("failed to write LVS inputs");
+        check_drc_clean(&sky130_open_drc_params(
+            inputs.gds.clone(),
+            work_dir.join("drc"),
+        ));
+        check_lvs_clean(&LvsParams {
+            tool: sky130_open_lvs_tool(),
+            inputs,
+            work_dir: work_dir.join("lvs"),
+        });
+    }
+
+    #[test]
+    fn sky130_open_buffer_lvs() {
+        let work_dir = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/open_buffer_lvs"
+        ));
+        let ctx = sky130_open_ctx();
+
+        let block = TileWrapper::new(Buffer::<Sky130Ucie>::new(InverterParams {
+            nmos_kind: MosKind::Nom,
+            pmos_kind: MosKind::Nom,
+            nmos_w: WidthSpec::Nm(1_000),
+            pmos_w: WidthSpec::Nm(1_000),
+            nmos_l: None,
+            pmos_l: None,
+        }));
+
+        let inputs = write_lvs_inputs::<_, Sky130OpenSchema, _>(&ctx, block, &work_dir)
+            .expect("failed to write LVS inputs");
+        check_drc_clean(&sky130_open_drc_params(
+            inputs.gds.clone(),
+            work_dir.join("drc"),
+        ));
+        check_lvs_clean(&LvsParams {
+            tool: sky130_open_lvs_tool(),
+            inputs,
+            work_dir: work_dir.join("lvs"),
+        });
+    }
+
+    #[test]
+    fn sky130_dff_lvs() {
+        let block = TileWrapper::new(Dff::<Sky130Ucie>::new(
+            DffParams::builder().build().unwrap(),
+        ));
+
+        assert_lvs_clean(block, "dff_lvs");
+    }
+
+    #[test]
+    fn sky130_config_chain_lvs() {
+        let block = TileWrapper::new(ConfigChain::<Sky130Ucie>::new(
+            ConfigChainParams::builder().bits(8).build().unwrap(),
+        ));
+
+        assert_lvs_clean(block, "config_chain_lvs");
+    }
+
+    /// Asserts that `block` has the same netlist when generated with and without
+    /// schematic-only mode, writing the netlists to `build/<name>`.
+    fn assert_schematic_only_matches<B>(block: B, name: &str)
+    where
+        B: Block + Schematic<Sky130Pdk> + Clone,
+    {
+        let work_dir = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/build")).join(name);
+        let pdk_root = std::env::var("SKY130_COMMERCIAL_PDK_ROOT")
+            .expect("the SKY130_COMMERCIAL_PDK_ROOT environment variable must be set");
+        let new_ctx = |options| {
+            Sky130CtxBuilder::commercial(&pdk_root)
+                .generation_options(options)
+                .build()
+                .unwrap()
+        };
+
+        let check = check_schematic_only::<_, Sky130CommercialSchema, _>(
+            new_ctx,
+            GenerationOptions::default(),
+            block,
+            &work_dir,
+        )
+        .expect("failed to compare netlists");
+        assert!(check.matched, "{check:?}");
+        assert!(std::fs::metadata(&check.layout_mode).unwrap().len() > 0);
+    }
+
+    #[test]
+    fn sky130_config_chain_schematic_only() {
+        assert_schematic_only_matches(
+            TileWrapper::new(ConfigChain::<Sky130Ucie>::new(
+                ConfigChainParams::builder().bits(8).build().unwrap(),
+            )),
+            "config_chain_schematic_only",
+        );
+    }
+
+    #[test]
+    fn sky130_strongarm_with_output_buffers_schematic_only() {
+        assert_schematic_only_matches(
+            TileWrapper::new(StrongArmWithOutputBuffers::<Sky130Ucie>::new(
+                StrongArmParams::builder().build().unwrap(),
+                InverterParams::builder().build().unwrap(),
+            )),
+            "strongarm_with_output_buffers_schematic_only",
+        );
+    }
+
+    #[test]
+    fn sky130_cell_testsuite() {
+        let work_dir = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/build/testsuite"));
+        let ctx = sky130_ctx();
 
-        ctx.write_layout(block, gds_path)
-            .expect("failed to write layout");
+        let report = run_cell_testsuite::<_, Sky130CommercialSchema, Sky130Ucie>(&ctx, &work_dir)
+            .expect("failed to run testsuite");
+        report
+            .write_artifact(&work_dir, &ArtifactMetadata::new("testsuite"))
+            .expect("failed to write testsuite report");
+        let failures = report.failures().collect::<Vec<_>>();
+        assert!(failures.is_empty(), "failing variants: {failures:#?}");
     }
 }
@@ -0,0 +1,415 @@
+//! A regression corpus of representative generator parameterizations.
+//!
+//! [`variants`] enumerates a grid of parameters for each generator: small, medium, and
+//! large horizontal drivers, StrongARMs with NMOS and PMOS input pairs, and buffers of
+//! increasing strength. [`run_testsuite`] generates every variant, runs the
+//! [quick DRC](crate::verification::quick_drc) on its layout, exports its netlist, and
+//! records how long each step took, so that a change to any generator can be checked
+//! against the whole corpus at once. [`run_cell_testsuite`] checks only the
+//! [cell variants](cell_variants), for technologies that do not provide the driver tiles.
+
+use std::any::Any;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use atoll::TileWrapper;
+use serde::{Deserialize, Serialize};
+use spice::netlist::NetlistOptions;
+use spice::Spice;
+use substrate::block::Block;
+use substrate::context::PdkContext;
+use substrate::layout::Layout;
+use substrate::pdk::Pdk;
+use substrate::schematic::netlist::ConvertibleNetlister;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::Schematic;
+use substrate::scir::schema::FromSchema;
+
+use crate::buffer::{Buffer, InverterParams};
+use crate::driver::{DriverParams, DriverUnitParams, HorizontalDriver, SegmentPlacement};
+use crate::report::SimArtifact;
+use crate::strongarm::{InputKind, StrongArm, StrongArmParams};
+use crate::verification::quick_drc::{quick_drc_of_cell, QuickDrcRules};
+
+/// The parameters of a generator in the corpus.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum VariantParams {
+    /// A [`HorizontalDriver`].
+    Driver(DriverParams),
+    /// A [`StrongArm`].
+    StrongArm(StrongArmParams),
+    /// A [`Buffer`].
+    Buffer(InverterParams),
+}
+
+/// A named parameterization of a generator.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Variant {
+    /// A name unique within the corpus, used for the variant's output directory.
+    pub name: String,
+    /// The generator parameters.
+    pub params: VariantParams,
+}
+
+impl Variant {
+    fn new(name: impl Into<String>, params: VariantParams) -> Self {
+        Self {
+            name: name.into(),
+            params,
+        }
+    }
+}
+
+/// Small, medium, and large horizontal drivers.
+///
+/// The medium driver is the default driver of a [`PhyConfig`](crate::config::PhyConfig).
+/// The large driver has larger output devices, two banks, and a spare segment.
+pub fn driver_variants() -> Vec<Variant> {
+    let driver = |unit: DriverUnitParams, num_segments, banks, spare_segment| DriverParams {
+        unit,
+        num_segments,
+        banks,
+        supply_budget: None,
+        spare_segment,
+        esd: None,
+        placement: SegmentPlacement::Sequential,
+    };
+    let unit = DriverUnitParams::builder().build().unwrap();
+    let large_unit = DriverUnitParams::builder()
+        .driver_pu_w(4_000)
+        .driver_pd_w(4_000)
+        .build()
+        .unwrap();
+    vec![
+        Variant::new(
+            "driver_small",
+            VariantParams::Driver(driver(unit, 4, 1, false)),
+        ),
+        Variant::new(
+            "driver_medium",
+            VariantParams::Driver(driver(unit, 16, 1, false)),
+        ),
+        Variant::new(
+            "driver_large",
+            VariantParams::Driver(driver(large_unit, 16, 2, true)),
+        ),
+    ]
+}
+
+/// StrongARMs with NMOS and PMOS input pairs, each at the default size and with
+/// double-width input pairs.
+pub fn strongarm_variants() -> Vec<Variant> {
+    [("n", InputKind::N), ("p", InputKind::P)]
+        .into_iter()
+        .flat_map(|(kind_name, input_kind)| {
+            [("1x", 1_000), ("2x", 2_000)].map(|(size, input_pair_w)| {
+                Variant::new(
+                    format!("strongarm_{kind_name}_{size}"),
+                    VariantParams::StrongArm(
+                        StrongArmParams::builder()
+                            .input_kind(input_kind)
+                            .input_pair_w(input_pair_w)
+                            .build()
+                            .unwrap(),
+                    ),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Buffers with 1, 2, and 4 um devices.
+pub fn buffer_variants() -> Vec<Variant> {
+    [1, 2, 4]
+        .into_iter()
+        .map(|um| {
+            Variant::new(
+                format!("buffer_{um}x"),
+                VariantParams::Buffer(
+                    InverterParams::builder()
+                        .nmos_w(um * 1_000)
+                        .pmos_w(um * 1_000)
+                        .build()
+                        .unwrap(),
+                ),
+            )
+        })
+        .collect()
+}
+
+/// The StrongARM and buffer variants, which need no driver tiles.
+pub fn cell_variants() -> Vec<Variant> {
+    strongarm_variants()
+        .into_iter()
+        .chain(buffer_variants())
+        .collect()
+}
+
+/// Every variant in the corpus.
+pub fn variants() -> Vec<Variant> {
+    driver_variants()
+        .into_iter()
+        .chain(cell_variants())
+        .collect()
+}
+
+/// The outcome of checking a single variant.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct VariantResult {
+    /// The variant name.
+    pub name: String,
+    /// The name of the generated cell.
+    pub cell: String,
+    /// The time taken to generate the layout.
+    pub layout_time: Duration,
+    /// The number of quick DRC violations.
+    pub drc_violations: usize,
+    /// The time taken to run the quick DRC.
+    pub drc_time: Duration,
+    /// The exported netlist, or a description of why the export failed.
+    pub netlist: Result<PathBuf, String>,
+    /// The time taken to export the netlist.
+    pub netlist_time: Duration,
+}
+
+impl VariantResult {
+    /// Returns `true` if the layout is quick-DRC clean and the netlist was exported.
+    pub fn passed(&self) -> bool {
+        self.drc_violations == 0 && self.netlist.is_ok()
+    }
+}
+
+/// The results of [`run_testsuite`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TestsuiteReport {
+    /// One result per variant, in the order of [`variants`].
+    pub results: Vec<VariantResult>,
+}
+
+impl TestsuiteReport {
+    /// Returns `true` if every variant passed.
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(VariantResult::passed)
+    }
+
+    /// Returns the results of the variants that did not pass.
+    pub fn failures(&self) -> impl Iterator<Item = &VariantResult> {
+        self.results.iter().filter(|result| !result.passed())
+    }
+}
+
+impl SimArtifact for TestsuiteReport {
+    fn csv_header(&self) -> Vec<String> {
+        [
+            "variant",
+            "cell",
+            "layout_s",
+            "drc_violations",
+            "drc_s",
+            "netlist",
+            "netlist_s",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.results
+            .iter()
+            .map(|r| {
+                vec![
+                    r.name.clone(),
+                    r.cell.clone(),
+                    r.layout_time.as_secs_f64().to_string(),
+                    r.drc_violations.to_string(),
+                    r.drc_time.as_secs_f64().to_string(),
+                    match &r.netlist {
+                        Ok(path) => path.display().to_string(),
+                        Err(e) => format!("error: {e}"),
+                    },
+                    r.netlist_time.as_secs_f64().to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Generates every variant in [`variants`] with technology `T`, checks it, and reports
+/// the results.
+///
+/// Each variant's netlist is converted to the schema `S` and written to
+/// `<work_dir>/<variant>/netlist.sp`.
+pub fn run_testsuite<PDK, S, T>(
+    ctx: &PdkContext<PDK>,
+    work_dir: impl AsRef<Path>,
+) -> std::io::Result<TestsuiteReport>
+where
+    PDK: Pdk + Schema,
+    S: Schema + FromSchema<PDK>,
+    Spice: FromSchema<S>,
+    <S as FromSchema<PDK>>::Error: Debug,
+    <Spice as FromSchema<S>>::Error: Debug,
+    T: QuickDrcRules<PDK> + Any,
+    TileWrapper<HorizontalDriver<T>>: Block + Layout<PDK> + Schematic<PDK>,
+    TileWrapper<StrongArm<T>>: Block + Layout<PDK> + Schematic<PDK>,
+    TileWrapper<Buffer<T>>: Block + Layout<PDK> + Schematic<PDK>,
+{
+    let work_dir = work_dir.as_ref();
+    let results = variants()
+        .into_iter()
+        .map(|variant| match variant.params {
+            VariantParams::Driver(params) => check_variant::<PDK, S, T, _>(
+                ctx,
+                variant.name.clone(),
+                TileWrapper::new(HorizontalDriver::<T>::new(params)),
+                &work_dir.join(&variant.name),
+            ),
+            _ => check_cell_variant::<PDK, S, T>(ctx, variant, work_dir),
+        })
+        .collect::<std::io::Result<Vec<_>>>()?;
+    Ok(TestsuiteReport { results })
+}
+
+/// Like [`run_testsuite`], but checks only the [cell variants](cell_variants).
+pub fn run_cell_testsuite<PDK, S, T>(
+    ctx: &PdkContext<PDK>,
+    work_dir: impl AsRef<Path>,
+) -> std::io::Result<TestsuiteReport>
+where
+    PDK: Pdk + Schema,
+    S: Schema + FromSchema<PDK>,
+    Spice: FromSchema<S>,
+    <S as FromSchema<PDK>>::Error: Debug,
+    <Spice as FromSchema<S>>::Error: Debug,
+    T: QuickDrcRules<PDK> + Any,
+    TileWrapper<StrongArm<T>>: Block + Layout<PDK> + Schematic<PDK>,
+    TileWrapper<Buffer<T>>: Block + Layout<PDK> + Schematic<PDK>,
+{
+    let work_dir = work_dir.as_ref();
+    let results = cell_variants()
+        .into_iter()
+        .map(|variant| check_cell_variant::<PDK, S, T>(ctx, variant, work_dir))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    Ok(TestsuiteReport { results })
+}
+
+/// Checks one of the [cell variants](cell_variants), writing its outputs to
+/// `<work_dir>/<variant>`.
+fn check_cell_variant<PDK, S, T>(
+    ctx: &PdkContext<PDK>,
+    variant: Variant,
+    work_dir: &Path,
+) -> std::io::Result<VariantResult>
+where
+    PDK: Pdk + Schema,
+    S: Schema + FromSchema<PDK>,
+    Spice: FromSchema<S>,
+    <S as FromSchema<PDK>>::Error: Debug,
+    <Spice as FromSchema<S>>::Error: Debug,
+    T: QuickDrcRules<PDK> + Any,
+    TileWrapper<StrongArm<T>>: Block + Layout<PDK> + Schematic<PDK>,
+    TileWrapper<Buffer<T>>: Block + Layout<PDK> + Schematic<PDK>,
+{
+    let dir = work_dir.join(&variant.name);
+    let name = variant.name;
+    match variant.params {
+        VariantParams::StrongArm(params) => check_variant::<PDK, S, T, _>(
+            ctx,
+            name,
+            TileWrapper::new(StrongArm::<T>::new(params)),
+            &dir,
+        ),
+        VariantParams::Buffer(params) => check_variant::<PDK, S, T, _>(
+            ctx,
+            name,
+            TileWrapper::new(Buffer::<T>::new(params)),
+            &dir,
+        ),
+        VariantParams::Driver(_) => unreachable!("driver variant {name} is not a cell variant"),
+    }
+}
+
+/// Generates `block`, runs the quick DRC on its layout, and exports its netlist to `dir`.
+fn check_variant<PDK, S, T, B>(
+    ctx: &PdkContext<PDK>,
+    name: String,
+    block: B,
+    dir: &Path,
+) -> std::io::Result<VariantResult>
+where
+    PDK: Pdk + Schema,
+    S: Schema + FromSchema<PDK>,
+    Spice: FromSchema<S>,
+    <S as FromSchema<PDK>>::Error: Debug,
+    <Spice as FromSchema<S>>::Error: Debug,
+    T: QuickDrcRules<PDK>,
+    B: Block + Layout<PDK> + Schematic<PDK> + Clone,
+{
+    std::fs::create_dir_all(dir)?;
+    let cell = block.name().to_string();
+
+    let start = Instant::now();
+    let layout = ctx.generate_layout(block.clone());
+    let raw = layout.raw();
+    let layout_time = start.elapsed();
+
+    let start = Instant::now();
+    let drc_violations = quick_drc_of_cell::<T, PDK>(&ctx.layers, raw)
+        .violations
+        .len();
+    let drc_time = start.elapsed();
+
+    let start = Instant::now();
+    let path = dir.join("netlist.sp");
+    let netlist = ctx
+        .export_scir(block)
+        .map_err(|e| format!("failed to export schematic: {e:?}"))
+        .and_then(|lib| {
+            lib.scir
+                .convert_schema::<S>()
+                .map_err(|e| format!("failed to convert schematic: {e:?}"))?
+                .convert_schema::<Spice>()
+                .map_err(|e| format!("failed to convert schematic: {e:?}"))?
+                .build()
+                .map_err(|e| format!("failed to build netlist: {e:?}"))
+        })
+        .and_then(|scir| {
+            Spice
+                .write_scir_netlist_to_file(&scir, &path, NetlistOptions::default())
+                .map_err(|e| format!("failed to write netlist: {e:?}"))
+        })
+        .map(|_| path);
+    let netlist_time = start.elapsed();
+
+    Ok(VariantResult {
+        name,
+        cell,
+        layout_time,
+        drc_violations,
+        drc_time,
+        netlist,
+        netlist_time,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn variant_names_are_unique() {
+        let variants = variants();
+        let names = variants.iter().map(|v| &v.name).collect::<HashSet<_>>();
+        assert_eq!(names.len(), variants.len());
+        let n_input = |v: &&Variant| match v.params {
+            VariantParams::StrongArm(p) => p.input_kind == InputKind::N,
+            _ => false,
+        };
+        assert_eq!(variants.iter().filter(n_input).count(), 2);
+        assert!(cell_variants()
+            .iter()
+            .all(|v| !matches!(v.params, VariantParams::Driver(_))));
+    }
+}
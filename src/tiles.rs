@@ -1,7 +1,25 @@
 //! Tile definitions.
 
+use crate::params::ParamsError;
+use crate::route::RouterKind;
+use atoll::{IoBuilder, Tile, TileBuilder};
 use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::error::Result;
+use substrate::geometry::point::Point;
+use substrate::geometry::rect::Rect;
+use substrate::geometry::transform::{TransformMut, Transformation, TranslateMut};
+use substrate::io::layout::IoShape;
 use substrate::io::{InOut, Io, Signal};
+use substrate::layout::element::Shape;
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::layers::{HasPin, Layer, LayerId};
+use substrate::pdk::{Pdk, PdkLayers};
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
 
 /// MOS device kind.
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
@@ -30,6 +48,83 @@ pub enum TileKind {
     P,
 }
 
+/// A MOS device width.
+///
+/// Serializes as a bare integer for a width in nanometers and as `{ fins = n }` for a fin
+/// count, so configurations written for planar technologies need no changes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[serde(from = "WidthSpecRepr", into = "WidthSpecRepr")]
+pub enum WidthSpec {
+    /// A continuous width in nanometers.
+    Nm(i64),
+    /// A discrete number of fins.
+    Fins(i64),
+}
+
+impl WidthSpec {
+    /// The width in nanometers, or `None` for a fin count.
+    pub const fn nm(self) -> Option<i64> {
+        match self {
+            WidthSpec::Nm(w) => Some(w),
+            WidthSpec::Fins(_) => None,
+        }
+    }
+
+    /// The width in its own unit: nanometers or fins.
+    pub const fn value(self) -> i64 {
+        match self {
+            WidthSpec::Nm(n) | WidthSpec::Fins(n) => n,
+        }
+    }
+
+    /// Scales the width by `num / den`, rounding down, in its own unit.
+    pub fn scale(self, num: i64, den: i64) -> Self {
+        match self {
+            WidthSpec::Nm(w) => WidthSpec::Nm(w * num / den),
+            WidthSpec::Fins(n) => WidthSpec::Fins(n * num / den),
+        }
+    }
+}
+
+impl From<i64> for WidthSpec {
+    fn from(w: i64) -> Self {
+        WidthSpec::Nm(w)
+    }
+}
+
+impl TranslateMut for WidthSpec {
+    fn translate_mut(&mut self, _p: Point) {}
+}
+
+impl TransformMut for WidthSpec {
+    fn transform_mut(&mut self, _trans: Transformation) {}
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum WidthSpecRepr {
+    Nm(i64),
+    Fins { fins: i64 },
+}
+
+impl From<WidthSpecRepr> for WidthSpec {
+    fn from(repr: WidthSpecRepr) -> Self {
+        match repr {
+            WidthSpecRepr::Nm(w) => WidthSpec::Nm(w),
+            WidthSpecRepr::Fins { fins } => WidthSpec::Fins(fins),
+        }
+    }
+}
+
+impl From<WidthSpec> for WidthSpecRepr {
+    fn from(width: WidthSpec) -> Self {
+        match width {
+            WidthSpec::Nm(w) => WidthSpecRepr::Nm(w),
+            WidthSpec::Fins(fins) => WidthSpecRepr::Fins { fins },
+        }
+    }
+}
+
 /// MOS tile parameters.
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub struct MosTileParams {
@@ -37,17 +132,83 @@ pub struct MosTileParams {
     pub mos_kind: MosKind,
     /// Whether MOS is n-channel or p-channel.
     pub tile_kind: TileKind,
-    /// The MOS device width.
+    /// The MOS device width in nanometers.
     pub w: i64,
+    /// The MOS device width, overriding `w` if present.
+    ///
+    /// Planar technologies convert fin counts to a width in nanometers when snapping.
+    #[serde(default)]
+    pub width: Option<WidthSpec>,
+    /// The MOS channel length in nanometers.
+    ///
+    /// Uses the minimum length supported by the technology if `None`.
+    #[serde(default)]
+    pub l: Option<i64>,
 }
 
 impl MosTileParams {
     /// Creates a new [`MosTileParams`].
-    pub fn new(mos_kind: MosKind, tile_kind: TileKind, w: i64) -> Self {
-        Self {
+    ///
+    /// `width` is either a width in nanometers or a [`WidthSpec`].
+    pub fn new(mos_kind: MosKind, tile_kind: TileKind, width: impl Into<WidthSpec>) -> Self {
+        let params = Self {
             mos_kind,
             tile_kind,
-            w,
+            w: 0,
+            width: None,
+            l: None,
+        };
+        match width.into() {
+            WidthSpec::Nm(w) => Self { w, ..params },
+            width => params.with_width(width),
+        }
+    }
+
+    /// Sets the channel length of the MOS device.
+    pub fn with_length(mut self, l: Option<i64>) -> Self {
+        self.l = l;
+        self
+    }
+
+    /// Sets the width of the MOS device.
+    pub fn with_width(mut self, width: WidthSpec) -> Self {
+        if let WidthSpec::Nm(w) = width {
+            self.w = w;
+        }
+        self.width = Some(width);
+        self
+    }
+
+    /// The requested width of the MOS device.
+    pub fn width(&self) -> WidthSpec {
+        self.width.unwrap_or(WidthSpec::Nm(self.w))
+    }
+
+    /// Snaps the requested width to a legal value using the given technology hook.
+    ///
+    /// The achieved width is stored in the returned parameters.
+    pub fn snapped(self, snap: impl FnOnce(TileKind, WidthSpec) -> WidthSpec) -> Self {
+        let width = snap(self.tile_kind, self.width());
+        self.with_width(width)
+    }
+}
+
+/// The channel lengths supported by the MOS tiles of a technology.
+pub trait MosLengthRules {
+    /// The supported channel lengths in nanometers, starting with the minimum length.
+    const MOS_LENGTHS: &'static [i64];
+
+    /// Returns an error if the MOS tiles do not support the channel length `l` of the
+    /// parameter `field`.
+    ///
+    /// A length of `None` selects the minimum length and is always supported.
+    fn check_length(field: &'static str, l: Option<i64>) -> std::result::Result<(), ParamsError> {
+        match l {
+            Some(l) if !Self::MOS_LENGTHS.contains(&l) => Err(ParamsError::Unsupported {
+                field,
+                generator: "MOS tiles of this technology",
+            }),
+            _ => Ok(()),
         }
     }
 }
@@ -93,6 +254,26 @@ impl ResistorTileParams {
     }
 }
 
+/// The IO of a capacitor.
+#[derive(Default, Debug, Clone, Copy, Io)]
+pub struct CapacitorIo {
+    /// The positive terminal.
+    pub p: InOut<Signal>,
+    /// The negative terminal.
+    pub n: InOut<Signal>,
+}
+
+/// The IO of a bipolar transistor.
+#[derive(Default, Debug, Clone, Copy, Io)]
+pub struct BjtIo {
+    /// The collector.
+    pub c: InOut<Signal>,
+    /// The base.
+    pub b: InOut<Signal>,
+    /// The emitter.
+    pub e: InOut<Signal>,
+}
+
 /// Resistor connection configurations.
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub enum ResistorConn {
@@ -101,3 +282,164 @@ pub enum ResistorConn {
     /// Parallel.
     Parallel,
 }
+
+/// Guard ring tile parameters.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct GuardRingTileParams {
+    /// The kind of tap forming the ring.
+    pub kind: TileKind,
+    /// The region enclosed by the ring in layer 1 tracks.
+    pub enclosed: Rect,
+    /// The width of each side of the ring in layer 1 tracks.
+    pub width: i64,
+}
+
+impl GuardRingTileParams {
+    /// Creates a new [`GuardRingTileParams`].
+    pub fn new(kind: TileKind, enclosed: Rect, width: i64) -> Self {
+        Self {
+            kind,
+            enclosed,
+            width,
+        }
+    }
+}
+
+/// The layers used to draw a [`GuardRingTile`].
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct GuardRingLayers {
+    /// The tap diffusion layer.
+    pub diff: LayerId,
+    /// The implant layer enclosing the tap diffusion.
+    pub implant: LayerId,
+    /// The well layer enclosing the tap diffusion, if any.
+    pub well: Option<LayerId>,
+}
+
+/// A guard ring implementation.
+///
+/// Provides the small set of technology-specific layers and rules
+/// needed by the generic [`GuardRingTile`] generator.
+pub trait GuardRingImpl<PDK: Pdk + Schema> {
+    /// The pin layer of the ring conductor.
+    type Pin: HasPin;
+    /// Enclosure of the tap diffusion by the implant layer.
+    const IMPLANT_ENCLOSURE: i64;
+    /// Enclosure of the tap diffusion by the well layer.
+    const WELL_ENCLOSURE: i64;
+    /// The router used by the guard ring.
+    const ROUTER: RouterKind = RouterKind::Greedy;
+
+    /// Returns the layers used to draw a guard ring of the given kind.
+    fn guard_ring_layers(layers: &PdkLayers<PDK>, kind: TileKind) -> GuardRingLayers;
+    /// Returns the pin layer of the ring conductor.
+    fn guard_ring_pin(layers: &PdkLayers<PDK>) -> Self::Pin;
+    /// Draws the ring conductor and the contacts connecting it to the tap diffusion in `rect`.
+    fn draw_guard_ring_contacts(cell: &mut TileBuilder<'_, PDK>, rect: Rect) -> Result<()>;
+}
+
+/// A generic guard ring tile.
+///
+/// Draws a rectangular tap ring around an enclosed region,
+/// along with the corresponding implant and well layers.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct GuardRingTile<T>(
+    GuardRingTileParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> GuardRingTile<T> {
+    /// Creates a new [`GuardRingTile`].
+    pub fn new(params: GuardRingTileParams) -> Self {
+        Self(params, PhantomData)
+    }
+}
+
+impl<T: Any> Block for GuardRingTile<T> {
+    type Io = TapIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("guard_ring_tile")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name(
+            match self.0.kind {
+                TileKind::N => "nguard_ring_tile",
+                TileKind::P => "pguard_ring_tile",
+            },
+            &self.0,
+        )
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+impl<T: Any> ExportsNestedData for GuardRingTile<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for GuardRingTile<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: GuardRingImpl<PDK> + Any> Tile<PDK> for GuardRingTile<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let pitch = cell.layer_stack.layer(1).pitch();
+        let enclosed = self.0.enclosed;
+        let inner = Rect::from_sides(
+            enclosed.left() * pitch,
+            enclosed.bot() * pitch,
+            enclosed.right() * pitch,
+            enclosed.top() * pitch,
+        );
+        let outer = inner.expand_all(self.0.width * pitch);
+
+        // Sides of the ring, ordered bottom, top, left, right.
+        let sides = [
+            Rect::from_sides(outer.left(), outer.bot(), outer.right(), inner.bot()),
+            Rect::from_sides(outer.left(), inner.top(), outer.right(), outer.top()),
+            Rect::from_sides(outer.left(), inner.bot(), inner.left(), inner.top()),
+            Rect::from_sides(inner.right(), inner.bot(), outer.right(), inner.top()),
+        ];
+
+        let layers = T::guard_ring_layers(&cell.ctx().layers, self.0.kind);
+        for side in sides {
+            cell.layout.draw(Shape::new(layers.diff, side))?;
+            cell.layout.draw(Shape::new(
+                layers.implant,
+                side.expand_all(T::IMPLANT_ENCLOSURE),
+            ))?;
+            if let Some(well) = layers.well {
+                cell.layout
+                    .draw(Shape::new(well, side.expand_all(T::WELL_ENCLOSURE)))?;
+            }
+            T::draw_guard_ring_contacts(cell, side)?;
+            io.layout.x.push(IoShape::with_layers(
+                T::guard_ring_pin(&cell.ctx().layers),
+                side,
+            ));
+        }
+
+        let slice = cell.layer_stack.slice(0..2);
+        let outline = slice.lcm_to_physical_rect(slice.expand_to_lcm_units(outer));
+        let virtual_layers = cell.layout.ctx.install_layers::<atoll::VirtualLayers>();
+        cell.layout
+            .draw(Shape::new(virtual_layers.outline.id(), outline))?;
+
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+
+        Ok(((), ()))
+    }
+}
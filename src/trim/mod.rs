@@ -0,0 +1,297 @@
+//! Trimmable resistors.
+//!
+//! A [`TrimResistor`] is a fixed resistor in parallel with binary-weighted legs, each of
+//! which is switched in by an NMOS device driven from a configuration chain. The
+//! termination bank and the reference ladder use it to trim out process variation of
+//! the resistor sheet resistance.
+
+use crate::driver::HorizontalDriverImpl;
+use crate::params::{check_at_least, check_positive, setters, ParamsError};
+use crate::scan::ConfigField;
+use crate::tiles::{ResistorConn, ResistorIoSchematic, TapIoSchematic, TileKind};
+use atoll::{IoBuilder, Tile, TileBuilder};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::marker::PhantomData;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::geometry::align::AlignMode;
+use substrate::io::{Array, InOut, Input, Io, MosIoSchematic, Signal};
+use substrate::layout::ExportsLayoutData;
+use substrate::pdk::Pdk;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::ExportsNestedData;
+
+pub mod tb;
+
+/// The interface to a [`TrimResistor`].
+#[derive(Debug, Default, Clone, Io)]
+pub struct TrimResistorIo {
+    /// The positive terminal.
+    pub p: InOut<Signal>,
+    /// The negative terminal, to which the leg switches connect.
+    pub n: InOut<Signal>,
+    /// The trim code, least significant bit first.
+    ///
+    /// Setting bit `i` switches in `2^i` unit legs.
+    pub trim: Array<Input<Signal>>,
+    /// The resistor body.
+    pub vdd: InOut<Signal>,
+    /// The switch body.
+    pub vss: InOut<Signal>,
+}
+
+/// The parameters of the [`TrimResistor`] layout generator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct TrimResistorParams {
+    /// The number of unit legs that are always connected.
+    pub base_legs: i64,
+    /// The number of trim bits.
+    pub bits: usize,
+    /// The width of each unit leg.
+    pub res_w: i64,
+    /// The length of each unit leg.
+    pub res_l: i64,
+    /// The width of the switch in series with a single unit leg.
+    ///
+    /// The switch of bit `i` is `2^i` times as wide, so that every switched leg sees
+    /// the same on-resistance.
+    pub switch_w: i64,
+}
+
+impl TrimResistorParams {
+    /// Returns a builder initialized with default parameters.
+    pub fn builder() -> TrimResistorParamsBuilder {
+        TrimResistorParamsBuilder::default()
+    }
+
+    /// The number of trim codes.
+    pub fn codes(&self) -> usize {
+        1 << self.bits
+    }
+
+    /// The number of unit legs switched in by `code`.
+    pub fn switched_legs(&self, code: usize) -> i64 {
+        (code & (self.codes() - 1)) as i64
+    }
+
+    /// The resistance at trim code `code`, given the resistance `r_unit` of one unit leg
+    /// and the on-resistance `r_on` of the switch in series with one unit leg.
+    pub fn resistance(&self, code: usize, r_unit: f64, r_on: f64) -> f64 {
+        let base = self.base_legs as f64 / r_unit;
+        let switched = self.switched_legs(code) as f64 / (r_unit + r_on);
+        1. / (base + switched)
+    }
+
+    /// The configuration chain field that loads the trim code.
+    pub fn config_field(&self, name: impl Into<String>) -> ConfigField {
+        ConfigField::new(name, self.bits)
+    }
+}
+
+/// A builder for [`TrimResistorParams`].
+///
+/// Defaults to four fixed legs and 4 bits of switched legs, each 1 um wide and 2 um
+/// long, so that the full-scale code raises the conductance about fourfold.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct TrimResistorParamsBuilder {
+    params: TrimResistorParams,
+}
+
+impl Default for TrimResistorParamsBuilder {
+    fn default() -> Self {
+        Self {
+            params: TrimResistorParams {
+                base_legs: 4,
+                bits: 4,
+                res_w: 1_000,
+                res_l: 2_000,
+                switch_w: 2_000,
+            },
+        }
+    }
+}
+
+impl TrimResistorParamsBuilder {
+    setters! {
+        /// Sets the number of unit legs that are always connected.
+        base_legs: i64,
+        /// Sets the number of trim bits.
+        bits: usize,
+        /// Sets the width of each unit leg.
+        res_w: i64,
+        /// Sets the length of each unit leg.
+        res_l: i64,
+        /// Sets the width of the switch in series with a single unit leg.
+        switch_w: i64,
+    }
+
+    /// Validates and returns the parameters.
+    pub fn build(self) -> std::result::Result<TrimResistorParams, ParamsError> {
+        let p = &self.params;
+        check_at_least("bits", p.bits as i64, 1)?;
+        for (field, value) in [
+            ("base_legs", p.base_legs),
+            ("res_w", p.res_w),
+            ("res_l", p.res_l),
+            ("switch_w", p.switch_w),
+        ] {
+            check_positive(field, value)?;
+        }
+        Ok(self.params)
+    }
+}
+
+/// A resistor trimmed by switching binary-weighted legs in parallel with a fixed
+/// resistor.
+///
+/// Bit `i` of the trim code connects `2^i` parallel unit legs from `p` to `n` through an
+/// NMOS switch, so a larger code gives a lower resistance. The fixed legs sit on the
+/// left, followed by one column per bit, each with its legs on top and its switch and
+/// substrate tap beneath.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+pub struct TrimResistor<T>(
+    TrimResistorParams,
+    #[serde(bound(deserialize = ""))] PhantomData<fn() -> T>,
+);
+
+impl<T> TrimResistor<T> {
+    /// Creates a new [`TrimResistor`].
+    pub fn new(params: TrimResistorParams) -> Self {
+        Self(params, PhantomData)
+    }
+
+    /// The parameters of this resistor.
+    pub fn params(&self) -> TrimResistorParams {
+        self.0
+    }
+}
+
+impl<T: Any> Block for TrimResistor<T> {
+    type Io = TrimResistorIo;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("trim_resistor")
+    }
+
+    fn name(&self) -> ArcStr {
+        crate::block_name("trim_resistor", &self.0)
+    }
+
+    fn io(&self) -> Self::Io {
+        TrimResistorIo {
+            trim: Array::new(self.0.bits, Default::default()),
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Any> ExportsNestedData for TrimResistor<T> {
+    type NestedData = ();
+}
+
+impl<T: Any> ExportsLayoutData for TrimResistor<T> {
+    type LayoutData = ();
+}
+
+impl<PDK: Pdk + Schema + Sized, T: HorizontalDriverImpl<PDK> + Any> Tile<PDK> for TrimResistor<T> {
+    #[tracing::instrument(skip_all, fields(block = %self.name()))]
+    fn tile<'a>(
+        &self,
+        io: IoBuilder<'a, Self>,
+        cell: &mut TileBuilder<'a, PDK>,
+    ) -> substrate::error::Result<(
+        <Self as ExportsNestedData>::NestedData,
+        <Self as ExportsLayoutData>::LayoutData,
+    )> {
+        let p = self.0;
+        let vss = io.schematic.vss;
+
+        let mut prev = cell.generate_connected(
+            T::resistor(p.base_legs, p.res_w, p.res_l, ResistorConn::Parallel),
+            ResistorIoSchematic {
+                p: io.schematic.p,
+                n: io.schematic.n,
+                b: io.schematic.vdd,
+            },
+        );
+        io.layout.p.merge(prev.layout.io().p);
+        io.layout.n.merge(prev.layout.io().n);
+        io.layout.vdd.merge(prev.layout.io().b);
+
+        for i in 0..p.bits {
+            let legs = 1 << i;
+            let nf = T::nf(legs, p.res_w);
+            let x = cell.signal(format!("x{i}"), Signal);
+
+            let mut res = cell.generate_connected(
+                T::resistor(legs, p.res_w, p.res_l, ResistorConn::Parallel),
+                ResistorIoSchematic {
+                    p: io.schematic.p,
+                    n: x,
+                    b: io.schematic.vdd,
+                },
+            );
+            let mut switch = cell.generate_connected(
+                T::mos(TileKind::N, nf, p.switch_w * legs, None),
+                MosIoSchematic {
+                    d: x,
+                    g: io.schematic.trim[i],
+                    s: io.schematic.n,
+                    b: vss,
+                },
+            );
+            let mut ptap =
+                cell.generate_connected(T::tap(TileKind::P, nf), TapIoSchematic { x: vss });
+
+            res.align_mut(&prev, AlignMode::ToTheRight, 0);
+            res.align_mut(&prev, AlignMode::Top, 0);
+            switch.align_mut(&res, AlignMode::Left, 0);
+            switch.align_mut(&res, AlignMode::Beneath, 0);
+            ptap.align_mut(&switch, AlignMode::Left, 0);
+            ptap.align_mut(&switch, AlignMode::Beneath, 0);
+
+            io.layout.p.merge(res.layout.io().p);
+            io.layout.vdd.merge(res.layout.io().b);
+            io.layout.trim[i].merge(switch.layout.io().g);
+            io.layout.n.merge(switch.layout.io().s);
+            io.layout.vss.merge(ptap.layout.io().x);
+
+            cell.draw(switch)?;
+            cell.draw(ptap)?;
+            cell.draw(std::mem::replace(&mut prev, res))?;
+        }
+        cell.draw(prev)?;
+
+        cell.set_top_layer(T::LAYER_MAP.pin);
+        cell.set_router(crate::route::router(cell.ctx(), T::ROUTER));
+        cell.set_via_maker(T::via_maker());
+
+        T::post_layout_hooks(cell)?;
+
+        Ok(((), ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trim_resistance_decreases_with_code() {
+        let params = TrimResistorParams::builder().build().unwrap();
+        let (r_unit, r_on) = (1_000., 100.);
+        let resistance = (0..params.codes())
+            .map(|code| params.resistance(code, r_unit, r_on))
+            .collect::<Vec<_>>();
+        assert_eq!(resistance[0], 250.);
+        assert!(resistance.windows(2).all(|r| r[1] < r[0]));
+        assert!((1. / resistance[15] - (4. / 1_000. + 15. / 1_100.)).abs() < 1e-12);
+        assert_eq!(params.switched_legs(params.codes()), 0);
+        assert!(matches!(
+            TrimResistorParams::builder().bits(0).build(),
+            Err(ParamsError::TooSmall { field: "bits", .. })
+        ));
+    }
+}
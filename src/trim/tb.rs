@@ -0,0 +1,292 @@
+//! Trimmable resistor characterization.
+//!
+//! [`TrimResistorTb`] forces a DC current through a [`TrimResistor`] while stepping its
+//! trim code from 0 to full scale, and reports the resistance at each code as a
+//! [`TrimSweep`].
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use spectre::analysis::tran::Tran;
+use spectre::blocks::{Isource, Pulse, Vsource};
+use spectre::{ErrPreset, Spectre};
+use std::any::Any;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use substrate::arcstr;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::{HardwareType, Node};
+use substrate::io::{Array, FlatLen, Signal, TestbenchIo, TwoTerminalIoSchematic};
+use substrate::pdk::corner::Pvt;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::{Cell, CellBuilder, ExportsNestedData, NestedData, Schematic};
+use substrate::scir::schema::FromSchema;
+use substrate::simulation::data::{tran, FromSaved, Save, SaveTb};
+use substrate::simulation::options::{SimOption, Temperature};
+use substrate::simulation::{SimController, SimulationContext, Simulator, Testbench};
+
+use crate::report::SimArtifact;
+use crate::tb::pi::Samples;
+use crate::trim::TrimResistor;
+
+/// The rise and fall time of the trim inputs.
+const EDGE: Decimal = dec!(100e-12);
+
+/// A transient testbench that drives a DC current `itest` into the `p` terminal of a
+/// [`TrimResistor`] with `n` grounded, counting the trim code up from 0 to full scale
+/// and holding each code for `step`.
+///
+/// Each code is measured once the resistor has settled, so the result is the DC
+/// resistance at every code.
+#[derive_where::derive_where(Copy, Clone, Debug, Hash, PartialEq, Eq; C)]
+#[derive(Serialize, Deserialize)]
+pub struct TrimResistorTb<T, PDK, C> {
+    /// The device-under-test.
+    pub dut: TrimResistor<T>,
+    /// The test current, in amperes.
+    pub itest: Decimal,
+    /// The time each code is held, in seconds.
+    pub step: Decimal,
+    /// The PVT corner.
+    pub pvt: Pvt<C>,
+
+    #[serde(bound(deserialize = ""))]
+    phantom: PhantomData<fn() -> PDK>,
+}
+
+impl<T, PDK, C> TrimResistorTb<T, PDK, C> {
+    /// Creates a new [`TrimResistorTb`].
+    pub fn new(dut: TrimResistor<T>, itest: Decimal, step: Decimal, pvt: Pvt<C>) -> Self {
+        Self {
+            dut,
+            itest,
+            step,
+            pvt,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        T: Any,
+        PDK: Any,
+        C: Serialize
+            + DeserializeOwned
+            + Copy
+            + Clone
+            + Debug
+            + Hash
+            + PartialEq
+            + Eq
+            + Send
+            + Sync
+            + Any,
+    > Block for TrimResistorTb<T, PDK, C>
+{
+    type Io = TestbenchIo;
+
+    fn id() -> ArcStr {
+        arcstr::literal!("trim_resistor_tb")
+    }
+
+    fn name(&self) -> ArcStr {
+        arcstr::literal!("trim_resistor_tb")
+    }
+
+    fn io(&self) -> Self::Io {
+        Default::default()
+    }
+}
+
+/// Nodes measured by [`TrimResistorTb`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq, NestedData)]
+pub struct TrimResistorTbNodes {
+    p: Node,
+}
+
+impl<T, PDK, C> ExportsNestedData for TrimResistorTb<T, PDK, C>
+where
+    TrimResistorTb<T, PDK, C>: Block,
+{
+    type NestedData = TrimResistorTbNodes;
+}
+
+impl<T, PDK: Schema, C: Copy> Schematic<Spectre> for TrimResistorTb<T, PDK, C>
+where
+    TrimResistorTb<T, PDK, C>: Block<Io = TestbenchIo>,
+    TrimResistor<T>: Schematic<PDK>,
+    Spectre: FromSchema<PDK>,
+{
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spectre>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let vdd = cell.signal("vdd", Signal);
+        let p = cell.signal("p", Signal);
+        let dut = cell.sub_builder::<PDK>().instantiate(self.dut);
+        cell.connect(dut.io().vdd, vdd);
+        cell.connect(dut.io().vss, io.vss);
+        cell.connect(dut.io().n, io.vss);
+        cell.connect(dut.io().p, p);
+
+        cell.instantiate_connected(
+            Vsource::dc(self.pvt.voltage),
+            TwoTerminalIoSchematic { p: vdd, n: io.vss },
+        );
+        cell.instantiate_connected(
+            Isource::dc(self.itest),
+            TwoTerminalIoSchematic { p: vdd, n: p },
+        );
+
+        // Bit `i` toggles every `2^i` steps, so code `k` is applied during step `k + 1`.
+        let trim = cell.signal("trim", Array::new(self.dut.params().bits, Signal));
+        for i in 0..trim.len() {
+            let half = self.step * Decimal::from(1u64 << i);
+            cell.connect(&dut.io().trim[i], &trim[i]);
+            cell.instantiate_connected(
+                Vsource::pulse(Pulse {
+                    val0: dec!(0),
+                    val1: self.pvt.voltage,
+                    period: Some(dec!(2) * half),
+                    width: Some(half - EDGE),
+                    delay: Some(self.step + half),
+                    rise: Some(EDGE),
+                    fall: Some(EDGE),
+                }),
+                TwoTerminalIoSchematic {
+                    p: trim[i],
+                    n: io.vss,
+                },
+            );
+        }
+
+        Ok(TrimResistorTbNodes { p })
+    }
+}
+
+/// The resulting waveforms of a [`TrimResistorTb`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromSaved)]
+pub struct TrimResistorSim {
+    /// The simulation time.
+    pub t: tran::Time,
+    /// The voltage across the resistor.
+    pub p: tran::Voltage,
+}
+
+impl<T, PDK, C> SaveTb<Spectre, Tran, TrimResistorSim> for TrimResistorTb<T, PDK, C>
+where
+    TrimResistorTb<T, PDK, C>: Block<Io = TestbenchIo>,
+{
+    fn save_tb(
+        ctx: &SimulationContext<Spectre>,
+        cell: &Cell<Self>,
+        opts: &mut <Spectre as Simulator>::Options,
+    ) -> <TrimResistorSim as FromSaved<Spectre, Tran>>::SavedKey {
+        TrimResistorSimSavedKey {
+            t: tran::Time::save(ctx, (), opts),
+            p: tran::Voltage::save(ctx, &cell.p, opts),
+        }
+    }
+}
+
+impl<T, PDK, C: SimOption<Spectre> + Copy> Testbench<Spectre> for TrimResistorTb<T, PDK, C>
+where
+    TrimResistorTb<T, PDK, C>: Block<Io = TestbenchIo> + Schematic<Spectre>,
+{
+    type Output = TrimSweep;
+
+    fn run(&self, sim: SimController<Spectre, Self>) -> Self::Output {
+        let codes = self.dut.params().codes();
+        let mut opts = spectre::Options::default();
+        sim.set_option(self.pvt.corner, &mut opts);
+        sim.set_option(Temperature::from(self.pvt.temp), &mut opts);
+        let wav: TrimResistorSim = sim
+            .simulate(
+                opts,
+                Tran {
+                    stop: self.step * Decimal::from(codes + 1),
+                    start: None,
+                    errpreset: Some(ErrPreset::Conservative),
+                    ..Default::default()
+                },
+            )
+            .expect("failed to run simulation");
+        let step = self.step.to_f64().unwrap();
+        let itest = self.itest.to_f64().unwrap();
+        let samples = Samples {
+            t: &wav.t,
+            v: &wav.p,
+        };
+        // Measure each code just before the next one is applied.
+        TrimSweep {
+            resistance: (0..codes)
+                .map(|k| samples.value_at(step * (k as f64 + 1.9)) / itest)
+                .collect(),
+        }
+    }
+}
+
+/// The resistance of a trimmable resistor at each trim code.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TrimSweep {
+    /// The resistance at each code, in ohms, starting from code 0.
+    pub resistance: Vec<f64>,
+}
+
+impl TrimSweep {
+    /// The change in resistance from each code to the next.
+    pub fn step_sizes(&self) -> Vec<f64> {
+        self.resistance.windows(2).map(|r| r[1] - r[0]).collect()
+    }
+
+    /// Whether the resistance strictly decreases as the code increases.
+    pub fn is_monotonic(&self) -> bool {
+        self.step_sizes().iter().all(|&step| step < 0.)
+    }
+
+    /// The code whose resistance is closest to `target`, or `None` if the sweep is
+    /// empty.
+    pub fn best_code(&self, target: f64) -> Option<usize> {
+        self.resistance
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| (*a - target).abs().total_cmp(&(*b - target).abs()))
+            .map(|(code, _)| code)
+    }
+}
+
+impl SimArtifact for TrimSweep {
+    fn csv_header(&self) -> Vec<String> {
+        ["code", "resistance"].map(String::from).to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.resistance
+            .iter()
+            .enumerate()
+            .map(|(code, r)| vec![code.to_string(), r.to_string()])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trim_sweep_picks_closest_code() {
+        let sweep = TrimSweep {
+            resistance: vec![60., 55., 51., 48.],
+        };
+        assert!(sweep.is_monotonic());
+        assert_eq!(sweep.step_sizes(), vec![-5., -4., -3.]);
+        assert_eq!(sweep.best_code(50.), Some(2));
+        assert_eq!(sweep.best_code(100.), Some(0));
+        assert_eq!(TrimSweep::default().best_code(50.), None);
+    }
+}
@@ -0,0 +1,282 @@
+//! Comparison of schematic and extracted simulation results.
+//!
+//! [`compare_views`] runs one testbench on the schematic of a block and another on its
+//! [`ExtractedView`](crate::verification::pex::ExtractedView), reduces both outputs to
+//! named metrics, and reports how much each metric moved. Metrics that degrade by more
+//! than their [`DeltaLimit`] are flagged.
+
+use serde::{Deserialize, Serialize};
+use spectre::Spectre;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use substrate::context::PdkContext;
+use substrate::pdk::Pdk;
+use substrate::simulation::Testbench;
+
+use crate::report::SimArtifact;
+
+/// The direction in which a change in a metric counts as a degradation.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Degradation {
+    /// The metric degrades as it increases, like a delay or an offset.
+    Increase,
+    /// The metric degrades as it decreases, like a bandwidth.
+    Decrease,
+    /// Any change degrades the metric, like an impedance trimmed to a target.
+    Either,
+}
+
+/// The largest allowed degradation of a metric from schematic to extracted.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct DeltaLimit {
+    /// The direction of change that counts as a degradation.
+    pub degrades_on: Degradation,
+    /// The largest allowed degradation, relative to the schematic value.
+    pub max_rel: f64,
+}
+
+impl DeltaLimit {
+    /// Creates a new [`DeltaLimit`].
+    pub fn new(degrades_on: Degradation, max_rel: f64) -> Self {
+        Self {
+            degrades_on,
+            max_rel,
+        }
+    }
+
+    /// Whether a relative change of `rel_delta` exceeds this limit.
+    pub fn exceeded(&self, rel_delta: f64) -> bool {
+        let degradation = match self.degrades_on {
+            Degradation::Increase => rel_delta,
+            Degradation::Decrease => -rel_delta,
+            Degradation::Either => rel_delta.abs(),
+        };
+        degradation > self.max_rel
+    }
+}
+
+/// The limits applied to each metric of a view comparison.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DeltaLimits {
+    /// The limit applied to metrics without their own limit.
+    pub default: DeltaLimit,
+    /// Limits for specific metrics, by name.
+    pub metrics: Vec<(String, DeltaLimit)>,
+}
+
+impl DeltaLimits {
+    /// Creates a new [`DeltaLimits`] that applies `default` to every metric.
+    pub fn new(default: DeltaLimit) -> Self {
+        Self {
+            default,
+            metrics: Vec::new(),
+        }
+    }
+
+    /// Applies `limit` to the metric named `metric`.
+    pub fn with(mut self, metric: impl Into<String>, limit: DeltaLimit) -> Self {
+        self.metrics.push((metric.into(), limit));
+        self
+    }
+
+    /// The limit that applies to the metric named `metric`.
+    pub fn get(&self, metric: &str) -> DeltaLimit {
+        self.metrics
+            .iter()
+            .find(|(name, _)| name == metric)
+            .map_or(self.default, |&(_, limit)| limit)
+    }
+}
+
+impl Default for DeltaLimits {
+    /// Flags any metric that changes by more than 10% in either direction.
+    fn default() -> Self {
+        Self::new(DeltaLimit::new(Degradation::Either, 0.1))
+    }
+}
+
+/// One metric of a [`ViewComparison`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ViewComparisonRow {
+    /// The name of the metric.
+    pub metric: String,
+    /// The value measured on the schematic.
+    pub schematic: f64,
+    /// The value measured on the extracted netlist, if it was measured.
+    pub extracted: Option<f64>,
+    /// The change from schematic to extracted, relative to the schematic value.
+    pub rel_delta: Option<f64>,
+    /// Whether the metric degraded beyond its limit or is missing from the extracted
+    /// results.
+    pub flagged: bool,
+}
+
+/// The per-metric differences between schematic and extracted simulation results.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ViewComparison {
+    /// One row per schematic metric, in the order the metrics were measured.
+    pub rows: Vec<ViewComparisonRow>,
+}
+
+impl ViewComparison {
+    /// Compares `extracted` metrics against `schematic` metrics by name.
+    pub fn new(
+        schematic: &[(String, f64)],
+        extracted: &[(String, f64)],
+        limits: &DeltaLimits,
+    ) -> Self {
+        let rows = schematic
+            .iter()
+            .map(|(metric, sch)| {
+                let extracted = extracted
+                    .iter()
+                    .find(|(name, _)| name == metric)
+                    .map(|&(_, value)| value);
+                let rel_delta = extracted.map(|ext| rel_delta(*sch, ext));
+                let flagged = rel_delta.map_or(true, |rel| limits.get(metric).exceeded(rel));
+                ViewComparisonRow {
+                    metric: metric.clone(),
+                    schematic: *sch,
+                    extracted,
+                    rel_delta,
+                    flagged,
+                }
+            })
+            .collect();
+        Self { rows }
+    }
+
+    /// The rows that were flagged.
+    pub fn flagged(&self) -> impl Iterator<Item = &ViewComparisonRow> {
+        self.rows.iter().filter(|row| row.flagged)
+    }
+
+    /// Returns `true` if no metric was flagged.
+    pub fn passed(&self) -> bool {
+        self.flagged().next().is_none()
+    }
+}
+
+/// The change from `schematic` to `extracted`, relative to `schematic`.
+///
+/// A change from zero is infinitely large.
+fn rel_delta(schematic: f64, extracted: f64) -> f64 {
+    let delta = extracted - schematic;
+    if delta == 0. {
+        0.
+    } else if schematic == 0. {
+        f64::INFINITY.copysign(delta)
+    } else {
+        delta / schematic.abs()
+    }
+}
+
+impl SimArtifact for ViewComparison {
+    fn csv_header(&self) -> Vec<String> {
+        ["metric", "schematic", "extracted", "rel_delta", "flagged"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                vec![
+                    row.metric.clone(),
+                    row.schematic.to_string(),
+                    row.extracted.map(|v| v.to_string()).unwrap_or_default(),
+                    row.rel_delta.map(|v| v.to_string()).unwrap_or_default(),
+                    row.flagged.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+impl Display for ViewComparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows.iter() {
+            let status = if row.flagged { "FLAG" } else { "OK" };
+            match (row.extracted, row.rel_delta) {
+                (Some(extracted), Some(rel_delta)) => writeln!(
+                    f,
+                    "{status:<6}{}: {} -> {} ({:+.1}%)",
+                    row.metric,
+                    row.schematic,
+                    extracted,
+                    100. * rel_delta
+                )?,
+                _ => writeln!(f, "{status:<6}{}: {} -> -", row.metric, row.schematic)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs `schematic` and `extracted`, two instances of the same testbench around the
+/// schematic and extracted views of a block, and compares the metrics that `measure`
+/// derives from their outputs.
+///
+/// The simulations run in the `schematic` and `extracted` subdirectories of `work_dir`.
+pub fn compare_views<PDK, S, E, O>(
+    ctx: &PdkContext<PDK>,
+    schematic: S,
+    extracted: E,
+    measure: impl Fn(&O) -> Vec<(String, f64)>,
+    limits: &DeltaLimits,
+    work_dir: impl AsRef<Path>,
+) -> ViewComparison
+where
+    PDK: Pdk,
+    S: Testbench<Spectre, Output = O>,
+    E: Testbench<Spectre, Output = O>,
+{
+    let work_dir = work_dir.as_ref();
+    let schematic = ctx
+        .simulate(schematic, work_dir.join("schematic"))
+        .expect("failed to run schematic simulation");
+    let extracted = ctx
+        .simulate(extracted, work_dir.join("extracted"))
+        .expect("failed to run extracted simulation");
+    ViewComparison::new(&measure(&schematic), &measure(&extracted), limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_degraded_metrics() {
+        let limits = DeltaLimits::default()
+            .with("delay", DeltaLimit::new(Degradation::Increase, 0.2))
+            .with("bandwidth", DeltaLimit::new(Degradation::Decrease, 0.05));
+        let schematic = [
+            ("delay".to_string(), 10e-12),
+            ("bandwidth".to_string(), 20e9),
+            ("impedance".to_string(), 50.),
+            ("offset".to_string(), 0.),
+            ("missing".to_string(), 1.),
+        ];
+        let extracted = [
+            ("delay".to_string(), 11.5e-12),
+            ("bandwidth".to_string(), 18e9),
+            ("impedance".to_string(), 44.),
+            ("offset".to_string(), 0.),
+        ];
+        let comparison = ViewComparison::new(&schematic, &extracted, &limits);
+
+        let flagged = comparison
+            .flagged()
+            .map(|row| row.metric.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(flagged, ["bandwidth", "impedance", "missing"]);
+        assert!(!comparison.passed());
+        assert!((comparison.rows[0].rel_delta.unwrap() - 0.15).abs() < 1e-9);
+        assert_eq!(comparison.rows[3].rel_delta, Some(0.));
+
+        // A faster extracted delay is not a degradation.
+        assert!(!DeltaLimit::new(Degradation::Increase, 0.1).exceeded(-0.5));
+        assert_eq!(rel_delta(0., 1e-3), f64::INFINITY);
+    }
+}
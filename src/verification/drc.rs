@@ -0,0 +1,346 @@
+//! Design rule checking.
+
+use crate::verification::{check_tool_output, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use substrate::geometry::rect::Rect;
+
+/// The tool used to run DRC.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum DrcTool {
+    /// Magic.
+    ///
+    /// The deck is the `.magicrc` file that loads the technology.
+    Magic,
+    /// KLayout.
+    ///
+    /// The deck is a `.lydrc` runset.
+    /// It must read the `input`, `report`, and `topcell` variables.
+    Klayout,
+}
+
+/// DRC parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct DrcParams {
+    /// The DRC tool.
+    pub tool: DrcTool,
+    /// The path to the rule deck.
+    pub deck: PathBuf,
+    /// The GDS file to check.
+    pub gds: PathBuf,
+    /// The name of the cell to check.
+    ///
+    /// If [`None`], the top cell of the GDS file is checked.
+    pub cell: Option<String>,
+    /// The directory in which to run DRC.
+    pub work_dir: PathBuf,
+}
+
+impl DrcParams {
+    /// Creates a new [`DrcParams`] that checks the top cell of `gds`.
+    pub fn new(
+        tool: DrcTool,
+        deck: impl Into<PathBuf>,
+        gds: impl Into<PathBuf>,
+        work_dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            tool,
+            deck: deck.into(),
+            gds: gds.into(),
+            cell: None,
+            work_dir: work_dir.into(),
+        }
+    }
+
+    /// Sets the name of the cell to check.
+    pub fn with_cell(mut self, cell: impl Into<String>) -> Self {
+        self.cell = Some(cell.into());
+        self
+    }
+}
+
+/// A single DRC violation.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct DrcViolation {
+    /// The name or description of the violated rule.
+    pub rule: String,
+    /// The layer the rule applies to, if it can be inferred from the rule name.
+    pub layer: Option<String>,
+    /// The bounding box of the violation in nanometers.
+    pub bbox: Rect,
+}
+
+impl Display for DrcViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at ({}, {}) to ({}, {})",
+            self.rule,
+            self.bbox.left(),
+            self.bbox.bot(),
+            self.bbox.right(),
+            self.bbox.top()
+        )
+    }
+}
+
+/// The results of a DRC run.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DrcOutput {
+    /// The violations found.
+    pub violations: Vec<DrcViolation>,
+}
+
+impl DrcOutput {
+    /// Returns `true` if there are no violations.
+    pub fn is_clean(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Runs DRC with the given parameters.
+pub fn run_drc(params: &DrcParams) -> Result<DrcOutput> {
+    std::fs::create_dir_all(&params.work_dir)?;
+    match params.tool {
+        DrcTool::Magic => run_magic_drc(params),
+        DrcTool::Klayout => run_klayout_drc(params),
+    }
+}
+
+/// Panics with a list of violations if `output` is not DRC clean.
+pub fn assert_drc_clean(output: &DrcOutput) {
+    if !output.is_clean() {
+        let violations = output
+            .violations
+            .iter()
+            .map(|v| format!("  {v}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        panic!(
+            "found {} DRC violation(s):\n{violations}",
+            output.violations.len()
+        );
+    }
+}
+
+/// Runs DRC and panics if it fails to run or finds any violations.
+pub fn check_drc_clean(params: &DrcParams) {
+    let output = run_drc(params).expect("failed to run DRC");
+    assert_drc_clean(&output);
+}
+
+fn run_magic_drc(params: &DrcParams) -> Result<DrcOutput> {
+    let report = params.work_dir.join("drc_results.txt");
+    let script = params.work_dir.join("drc.tcl");
+    let load = match &params.cell {
+        Some(cell) => format!("load {{{cell}}}"),
+        None => "load [lindex [cellname list top] 0]".to_string(),
+    };
+    std::fs::write(
+        &script,
+        format!(
+            r#"gds read {{{gds}}}
+{load}
+select top cell
+drc euclidean on
+drc style drc(full)
+drc check
+drc catchup
+set scale [cif scale out]
+set f [open {{{report}}} w]
+puts $f "scale $scale"
+foreach {{why boxes}} [drc listall why] {{
+    foreach box $boxes {{
+        puts $f "$why\t[lindex $box 0] [lindex $box 1] [lindex $box 2] [lindex $box 3]"
+    }}
+}}
+close $f
+quit -noprompt
+"#,
+            gds = params.gds.display(),
+            report = report.display(),
+        ),
+    )?;
+    let output = Command::new("magic")
+        .arg("-dnull")
+        .arg("-noconsole")
+        .arg("-rcfile")
+        .arg(&params.deck)
+        .arg(&script)
+        .current_dir(&params.work_dir)
+        .output()?;
+    check_tool_output("magic", output, params.work_dir.join("drc.log"))?;
+    parse_magic_report(&report)
+}
+
+fn run_klayout_drc(params: &DrcParams) -> Result<DrcOutput> {
+    let report = params.work_dir.join("drc_results.lyrdb");
+    let mut cmd = Command::new("klayout");
+    cmd.arg("-b")
+        .arg("-r")
+        .arg(&params.deck)
+        .arg("-rd")
+        .arg(format!("input={}", params.gds.display()))
+        .arg("-rd")
+        .arg(format!("report={}", report.display()));
+    if let Some(cell) = &params.cell {
+        cmd.arg("-rd").arg(format!("topcell={cell}"));
+    }
+    let output = cmd.current_dir(&params.work_dir).output()?;
+    check_tool_output("klayout", output, params.work_dir.join("drc.log"))?;
+    parse_klayout_report(&report)
+}
+
+/// Converts a coordinate in microns to nanometers.
+fn um_to_nm(x: f64) -> i64 {
+    (x * 1000.).round() as i64
+}
+
+/// Infers the layer name from a rule name of the form `<layer>.<rule>`.
+fn rule_layer(rule: &str) -> Option<String> {
+    rule.split_once('.').map(|(layer, _)| layer.to_string())
+}
+
+fn parse_error(path: &Path, message: impl Into<String>) -> Error {
+    Error::Parse {
+        path: path.to_path_buf(),
+        message: message.into(),
+    }
+}
+
+fn parse_magic_report(path: &Path) -> Result<DrcOutput> {
+    parse_magic_results(&std::fs::read_to_string(path)?).map_err(|e| parse_error(path, e))
+}
+
+/// Parses the results written by the Magic DRC script.
+///
+/// Coordinates are in Magic internal units and are scaled to microns using
+/// the `scale` header line.
+fn parse_magic_results(contents: &str) -> std::result::Result<DrcOutput, String> {
+    let mut lines = contents.lines();
+    let scale = lines
+        .next()
+        .and_then(|line| line.strip_prefix("scale "))
+        .and_then(|scale| scale.trim().parse::<f64>().ok())
+        .ok_or("missing scale header")?;
+
+    let mut violations = Vec::new();
+    for line in lines.filter(|line| !line.trim().is_empty()) {
+        let (rule, coords) = line
+            .rsplit_once('\t')
+            .ok_or_else(|| format!("malformed line: {line}"))?;
+        let coords = coords
+            .split_whitespace()
+            .map(|x| x.parse::<f64>().map(|x| um_to_nm(x * scale)))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| format!("malformed coordinate in `{line}`: {e}"))?;
+        let [left, bot, right, top] = coords[..] else {
+            return Err(format!("expected 4 coordinates in `{line}`"));
+        };
+        violations.push(DrcViolation {
+            rule: rule.to_string(),
+            layer: None,
+            bbox: Rect::from_sides(left, bot, right, top),
+        });
+    }
+    Ok(DrcOutput { violations })
+}
+
+fn parse_klayout_report(path: &Path) -> Result<DrcOutput> {
+    parse_lyrdb(&std::fs::read_to_string(path)?).map_err(|e| parse_error(path, e))
+}
+
+/// Returns the text of each `<tag>...</tag>` element in `s`, in order.
+fn elements<'a>(s: &'a str, tag: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let mut rest = s;
+    std::iter::from_fn(move || {
+        let start = rest.find(&open)? + open.len();
+        let end = start + rest[start..].find(&close)?;
+        let inner = &rest[start..end];
+        rest = &rest[end + close.len()..];
+        Some(inner)
+    })
+}
+
+/// Parses a KLayout report database.
+///
+/// Every value attached to an item contributes its points to the violation bounding box.
+fn parse_lyrdb(contents: &str) -> std::result::Result<DrcOutput, String> {
+    let items = elements(contents, "items").next().unwrap_or_default();
+    let mut violations = Vec::new();
+    for item in elements(items, "item") {
+        let rule = elements(item, "category")
+            .next()
+            .ok_or("item is missing a category")?
+            .trim()
+            .trim_matches('\'')
+            .to_string();
+        let mut bbox: Option<Rect> = None;
+        for value in elements(item, "value") {
+            for group in value.split('(').skip(1) {
+                let group = group.split(')').next().unwrap_or_default();
+                for point in group.split(';') {
+                    let (x, y) = point
+                        .split_once(',')
+                        .ok_or_else(|| format!("malformed point `{point}`"))?;
+                    let parse = |v: &str| {
+                        v.trim()
+                            .parse::<f64>()
+                            .map(um_to_nm)
+                            .map_err(|e| format!("malformed coordinate `{v}`: {e}"))
+                    };
+                    let (x, y) = (parse(x)?, parse(y)?);
+                    let pt = Rect::from_sides(x, y, x, y);
+                    bbox = Some(bbox.map_or(pt, |bbox| bbox.union(pt)));
+                }
+            }
+        }
+        violations.push(DrcViolation {
+            layer: rule_layer(&rule),
+            rule,
+            bbox: bbox.ok_or("item has no geometry")?,
+        });
+    }
+    Ok(DrcOutput { violations })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_magic_results() {
+        let output =
+            parse_magic_results("scale 0.005\nMetal1 spacing < 0.14um (met1.2)\t0 0 20 40\n")
+                .unwrap();
+        assert_eq!(output.violations.len(), 1);
+        assert_eq!(output.violations[0].bbox, Rect::from_sides(0, 0, 100, 200));
+    }
+
+    #[test]
+    fn parses_lyrdb() {
+        let output = parse_lyrdb(
+            r#"<report-database>
+ <items>
+  <item>
+   <category>'m1.2'</category>
+   <cell>strong_arm</cell>
+   <values>
+    <value>edge-pair: (0,0;1,0)|(0,0.14;1,0.14)</value>
+   </values>
+  </item>
+ </items>
+</report-database>"#,
+        )
+        .unwrap();
+        assert_eq!(output.violations.len(), 1);
+        assert_eq!(output.violations[0].rule, "m1.2");
+        assert_eq!(output.violations[0].layer.as_deref(), Some("m1"));
+        assert_eq!(output.violations[0].bbox, Rect::from_sides(0, 0, 1000, 140));
+    }
+}
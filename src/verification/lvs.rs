@@ -0,0 +1,430 @@
+//! Layout versus schematic checking.
+
+use crate::verification::{check_tool_output, Error, Result};
+use serde::{Deserialize, Serialize};
+use spice::netlist::NetlistOptions;
+use spice::Spice;
+use std::fmt::{Debug, Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use substrate::block::Block;
+use substrate::context::PdkContext;
+use substrate::layout::Layout;
+use substrate::pdk::Pdk;
+use substrate::schematic::netlist::ConvertibleNetlister;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::Schematic;
+use substrate::scir::schema::FromSchema;
+
+/// The tool used to run LVS.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum LvsTool {
+    /// Extracts the layout with Magic and compares netlists with netgen.
+    Netgen {
+        /// The `.magicrc` file that loads the technology.
+        magicrc: PathBuf,
+        /// The netgen setup file.
+        setup: PathBuf,
+    },
+    /// Calibre.
+    Calibre {
+        /// The LVS rule deck, included by the generated runset.
+        rules: PathBuf,
+    },
+}
+
+/// The files compared by LVS.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct LvsInputs {
+    /// The layout GDS file.
+    pub gds: PathBuf,
+    /// The schematic SPICE netlist.
+    pub netlist: PathBuf,
+    /// The name of the top cell in both the layout and the schematic.
+    pub cell: String,
+}
+
+/// LVS parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct LvsParams {
+    /// The LVS tool.
+    pub tool: LvsTool,
+    /// The layout and schematic to compare.
+    pub inputs: LvsInputs,
+    /// The directory in which to run LVS.
+    pub work_dir: PathBuf,
+}
+
+/// The kind of object that failed to match.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum MismatchKind {
+    /// A net.
+    Net,
+    /// A device or instance.
+    Device,
+}
+
+/// A net or device that could not be matched between layout and schematic.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct LvsMismatch {
+    /// The kind of object that failed to match.
+    pub kind: MismatchKind,
+    /// The report line describing the mismatch.
+    pub description: String,
+}
+
+impl Display for LvsMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            MismatchKind::Net => "net",
+            MismatchKind::Device => "device",
+        };
+        write!(f, "{kind}: {}", self.description)
+    }
+}
+
+/// The results of an LVS run.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LvsOutput {
+    /// Whether the layout and schematic match.
+    pub matched: bool,
+    /// The nets and devices that failed to match.
+    pub mismatches: Vec<LvsMismatch>,
+    /// The path to the full LVS report.
+    pub report: PathBuf,
+}
+
+/// Writes the GDS and SPICE netlist of `block` to `work_dir` for use as LVS inputs.
+///
+/// The schematic is converted to the schema `S` before netlisting.
+pub fn write_lvs_inputs<PDK, S, B>(
+    ctx: &PdkContext<PDK>,
+    block: B,
+    work_dir: impl AsRef<Path>,
+) -> Result<LvsInputs>
+where
+    PDK: Pdk + Schema,
+    S: Schema + FromSchema<PDK>,
+    Spice: FromSchema<S>,
+    <S as FromSchema<PDK>>::Error: Debug,
+    <Spice as FromSchema<S>>::Error: Debug,
+    B: Block + Schematic<PDK> + Layout<PDK> + Clone,
+{
+    let work_dir = work_dir.as_ref();
+    let gds = work_dir.join("layout.gds");
+    let netlist = work_dir.join("netlist.sp");
+
+    let scir = ctx
+        .export_scir(block.clone())
+        .map_err(generation_error("netlist"))?
+        .scir
+        .convert_schema::<S>()
+        .map_err(generation_error("netlist"))?
+        .convert_schema::<Spice>()
+        .map_err(generation_error("netlist"))?
+        .build()
+        .map_err(generation_error("netlist"))?;
+    Spice
+        .write_scir_netlist_to_file(&scir, &netlist, NetlistOptions::default())
+        .map_err(generation_error("netlist"))?;
+
+    let cell = block.name().to_string();
+    ctx.write_layout(block, &gds)
+        .map_err(generation_error("layout"))?;
+
+    Ok(LvsInputs { gds, netlist, cell })
+}
+
+/// Wraps a failure to generate the LVS input `input` in an [`Error::Generation`].
+fn generation_error<E: Debug>(input: &'static str) -> impl FnOnce(E) -> Error {
+    move |e| Error::Generation {
+        input,
+        message: format!("{e:?}"),
+    }
+}
+
+/// Runs LVS with the given parameters.
+pub fn run_lvs(params: &LvsParams) -> Result<LvsOutput> {
+    std::fs::create_dir_all(&params.work_dir)?;
+    match &params.tool {
+        LvsTool::Netgen { magicrc, setup } => run_netgen_lvs(params, magicrc, setup),
+        LvsTool::Calibre { rules } => run_calibre_lvs(params, rules),
+    }
+}
+
+/// Panics with a list of mismatches if `output` does not match.
+pub fn assert_lvs_clean(output: &LvsOutput) {
+    if !output.matched {
+        let mismatches = output
+            .mismatches
+            .iter()
+            .map(|m| format!("  {m}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        panic!(
+            "LVS failed with {} mismatch(es); see {:?}\n{mismatches}",
+            output.mismatches.len(),
+            output.report
+        );
+    }
+}
+
+/// Runs LVS and panics if it fails to run or the layout does not match the schematic.
+pub fn check_lvs_clean(params: &LvsParams) {
+    let output = run_lvs(params).expect("failed to run LVS");
+    assert_lvs_clean(&output);
+}
+
+fn run_netgen_lvs(params: &LvsParams, magicrc: &Path, setup: &Path) -> Result<LvsOutput> {
+    let LvsInputs { gds, netlist, cell } = &params.inputs;
+
+    // Extract a layout netlist with Magic.
+    let extracted = params.work_dir.join("extracted.spice");
+    let script = params.work_dir.join("extract.tcl");
+    std::fs::write(
+        &script,
+        format!(
+            r#"gds read {{{gds}}}
+load {{{cell}}}
+select top cell
+extract all
+ext2spice lvs
+ext2spice -o {{{extracted}}}
+quit -noprompt
+"#,
+            gds = gds.display(),
+            extracted = extracted.display(),
+        ),
+    )?;
+    let output = Command::new("magic")
+        .arg("-dnull")
+        .arg("-noconsole")
+        .arg("-rcfile")
+        .arg(magicrc)
+        .arg(&script)
+        .current_dir(&params.work_dir)
+        .output()?;
+    check_tool_output("magic", output, params.work_dir.join("extract.log"))?;
+
+    let report = params.work_dir.join("lvs.report");
+    let output = Command::new("netgen")
+        .arg("-batch")
+        .arg("lvs")
+        .arg(format!("{} {cell}", extracted.display()))
+        .arg(format!("{} {cell}", netlist.display()))
+        .arg(setup)
+        .arg(&report)
+        .current_dir(&params.work_dir)
+        .output()?;
+    check_tool_output("netgen", output, params.work_dir.join("lvs.log"))?;
+
+    let contents = std::fs::read_to_string(&report)?;
+    Ok(parse_netgen_report(&contents, report))
+}
+
+fn run_calibre_lvs(params: &LvsParams, rules: &Path) -> Result<LvsOutput> {
+    let LvsInputs { gds, netlist, cell } = &params.inputs;
+    let report = params.work_dir.join("lvs.report");
+    let runset = params.work_dir.join("lvs.runset");
+    std::fs::write(
+        &runset,
+        format!(
+            r#"LAYOUT PATH "{gds}"
+LAYOUT PRIMARY "{cell}"
+LAYOUT SYSTEM GDSII
+SOURCE PATH "{netlist}"
+SOURCE PRIMARY "{cell}"
+SOURCE SYSTEM SPICE
+LVS REPORT "{report}"
+LVS REPORT OPTION NONE
+INCLUDE "{rules}"
+"#,
+            gds = gds.display(),
+            netlist = netlist.display(),
+            report = report.display(),
+            rules = rules.display(),
+        ),
+    )?;
+    let output = Command::new("calibre")
+        .arg("-lvs")
+        .arg("-hier")
+        .arg("-turbo")
+        .arg("-nowait")
+        .arg(&runset)
+        .current_dir(&params.work_dir)
+        .output()?;
+    check_tool_output("calibre", output, params.work_dir.join("lvs.log"))?;
+
+    let contents = std::fs::read_to_string(&report)?;
+    parse_calibre_report(&contents, report.clone()).ok_or(Error::Parse {
+        path: report,
+        message: "missing overall comparison result".to_string(),
+    })
+}
+
+/// Parses a netgen LVS report.
+fn parse_netgen_report(contents: &str, report: PathBuf) -> LvsOutput {
+    let matched = contents.contains("Circuits match uniquely.")
+        && !contents.contains("Netlists do not match.");
+    let mismatches = contents
+        .lines()
+        .filter_map(|line| {
+            let kind = if line.contains("(no matching net)") {
+                MismatchKind::Net
+            } else if line.contains("(no matching instance)") {
+                MismatchKind::Device
+            } else {
+                return None;
+            };
+            Some(LvsMismatch {
+                kind,
+                description: line.trim().to_string(),
+            })
+        })
+        .collect();
+    LvsOutput {
+        matched,
+        mismatches,
+        report,
+    }
+}
+
+/// Parses a Calibre LVS report.
+///
+/// Returns [`None`] if the report does not contain an overall comparison result.
+fn parse_calibre_report(contents: &str, report: PathBuf) -> Option<LvsOutput> {
+    let matched = if contents.contains("INCORRECT") {
+        false
+    } else if contents.contains("CORRECT") {
+        true
+    } else {
+        return None;
+    };
+    let mismatches = contents
+        .lines()
+        .filter_map(|line| {
+            let kind = if line.contains("** missing net **") {
+                MismatchKind::Net
+            } else if line.contains("** missing instance **")
+                || line.contains("** missing device **")
+            {
+                MismatchKind::Device
+            } else {
+                return None;
+            };
+            Some(LvsMismatch {
+                kind,
+                description: line.trim().to_string(),
+            })
+        })
+        .collect();
+    Some(LvsOutput {
+        matched,
+        mismatches,
+        report,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_matching_netgen_report() {
+        let output = parse_netgen_report(
+            "Subcircuit summary:\n\
+             Circuit 1: driver_unit                |Circuit 2: driver_unit\n\
+             Number of devices: 12                 |Number of devices: 12\n\
+             Number of nets: 9                     |Number of nets: 9\n\
+             \n\
+             Circuits match uniquely.\n",
+            PathBuf::from("lvs.report"),
+        );
+        assert!(output.matched);
+        assert!(output.mismatches.is_empty());
+        assert_eq!(output.report, PathBuf::from("lvs.report"));
+    }
+
+    #[test]
+    fn parses_mismatched_netgen_report() {
+        let output = parse_netgen_report(
+            "Net: pu_en                            |(no matching net)\n\
+             Instance: nand_pu_en                  |(no matching instance)\n\
+             Netlists do not match.\n",
+            PathBuf::from("lvs.report"),
+        );
+        assert!(!output.matched);
+        assert_eq!(
+            output.mismatches,
+            vec![
+                LvsMismatch {
+                    kind: MismatchKind::Net,
+                    description: "Net: pu_en                            |(no matching net)"
+                        .to_string(),
+                },
+                LvsMismatch {
+                    kind: MismatchKind::Device,
+                    description: "Instance: nand_pu_en                  |(no matching instance)"
+                        .to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_correct_calibre_report() {
+        let output = parse_calibre_report(
+            "                  OVERALL COMPARISON RESULTS\n\
+             \n\
+                                #       ###################       _   _\n\
+                               #        #                 #       *   *\n\
+                          #   #         #     CORRECT     #         |\n\
+                           # #          #                 #       \\___/\n\
+                            #           ###################\n",
+            PathBuf::from("lvs.report"),
+        )
+        .unwrap();
+        assert!(output.matched);
+        assert!(output.mismatches.is_empty());
+    }
+
+    #[test]
+    fn parses_incorrect_calibre_report() {
+        let output = parse_calibre_report(
+            "                  OVERALL COMPARISON RESULTS\n\
+             \n\
+                  #     #   ###################\n\
+                   #   #    #                 #\n\
+                    # #     #    INCORRECT    #\n\
+                   #   #    #                 #\n\
+                  #     #   ###################\n\
+             \n\
+             1  pu_en                      ** missing net **\n\
+             2  M3(1.250,4.100)  nmos      ** missing device **\n\
+             3  X7  inv                    ** missing instance **\n",
+            PathBuf::from("lvs.report"),
+        )
+        .unwrap();
+        assert!(!output.matched);
+        assert_eq!(
+            output.mismatches.iter().map(|m| m.kind).collect::<Vec<_>>(),
+            vec![
+                MismatchKind::Net,
+                MismatchKind::Device,
+                MismatchKind::Device
+            ]
+        );
+        assert_eq!(
+            output.mismatches[0].description,
+            "1  pu_en                      ** missing net **"
+        );
+    }
+
+    #[test]
+    fn rejects_calibre_report_without_result() {
+        assert_eq!(
+            parse_calibre_report("LVS completed.\n", PathBuf::from("lvs.report")),
+            None
+        );
+    }
+}
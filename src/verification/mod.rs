@@ -0,0 +1,64 @@
+//! Physical verification runners.
+
+use std::path::PathBuf;
+use std::process::Output;
+
+pub mod compare;
+pub mod drc;
+pub mod lvs;
+pub mod pex;
+pub mod quick_drc;
+pub mod schematic_only;
+
+/// An error produced while running a verification tool.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// An I/O error, such as a failure to launch the tool or write its inputs.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The tool exited with a non-zero status.
+    #[error("{tool} exited with {status}; see {log:?}")]
+    ToolFailed {
+        /// The name of the tool.
+        tool: &'static str,
+        /// The exit status of the tool.
+        status: std::process::ExitStatus,
+        /// The path to the captured tool output.
+        log: PathBuf,
+    },
+    /// The inputs to the tool could not be generated.
+    #[error("failed to generate {input}: {message}")]
+    Generation {
+        /// The input being generated.
+        input: &'static str,
+        /// A description of the problem.
+        message: String,
+    },
+    /// The tool report could not be parsed.
+    #[error("failed to parse {path:?}: {message}")]
+    Parse {
+        /// The path to the report.
+        path: PathBuf,
+        /// A description of the problem.
+        message: String,
+    },
+}
+
+/// The result type returned by verification runners.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Writes the captured output of `tool` to `log` and returns an error if it failed.
+pub(crate) fn check_tool_output(tool: &'static str, output: Output, log: PathBuf) -> Result<()> {
+    let mut contents = output.stdout;
+    contents.extend_from_slice(&output.stderr);
+    std::fs::write(&log, contents)?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::ToolFailed {
+            tool,
+            status: output.status,
+            log,
+        })
+    }
+}
@@ -0,0 +1,311 @@
+//! Parasitic extraction.
+
+use crate::tb::probe::{ProbePoints, Probes};
+use crate::verification::{check_tool_output, Error, Result};
+use serde::{Deserialize, Serialize};
+use spice::Spice;
+use std::path::PathBuf;
+use std::process::Command;
+use substrate::arcstr::ArcStr;
+use substrate::block::Block;
+use substrate::io::schematic::HardwareType;
+use substrate::io::Flatten;
+use substrate::schematic::{CellBuilder, ExportsNestedData, Schematic};
+
+/// The tool used to run parasitic extraction.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum PexTool {
+    /// Magic, with resistance extraction enabled.
+    Magic {
+        /// The `.magicrc` file that loads the technology.
+        magicrc: PathBuf,
+    },
+    /// StarRC.
+    StarRc {
+        /// The base StarRC command file.
+        ///
+        /// The block name and netlist options are appended to a copy of this file.
+        cmd_file: PathBuf,
+    },
+}
+
+/// Parasitic extraction parameters.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct PexParams {
+    /// The extraction tool.
+    pub tool: PexTool,
+    /// The layout GDS file.
+    pub gds: PathBuf,
+    /// The name of the cell to extract.
+    pub cell: String,
+    /// The directory in which to run extraction.
+    pub work_dir: PathBuf,
+}
+
+/// The result of parasitic extraction.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct PexOutput {
+    /// The extracted netlist.
+    ///
+    /// Contains a SPICE subcircuit named after the extracted cell.
+    pub netlist: PathBuf,
+    /// The name of the extracted subcircuit.
+    pub cell: String,
+}
+
+/// Runs parasitic extraction with the given parameters.
+pub fn run_pex(params: &PexParams) -> Result<PexOutput> {
+    std::fs::create_dir_all(&params.work_dir)?;
+    let netlist = match &params.tool {
+        PexTool::Magic { magicrc } => run_magic_pex(params, magicrc)?,
+        PexTool::StarRc { cmd_file } => run_starrc_pex(params, cmd_file)?,
+    };
+    Ok(PexOutput {
+        netlist,
+        cell: params.cell.clone(),
+    })
+}
+
+fn run_magic_pex(params: &PexParams, magicrc: &PathBuf) -> Result<PathBuf> {
+    let netlist = params.work_dir.join(format!("{}.pex.spice", params.cell));
+    let script = params.work_dir.join("pex.tcl");
+    std::fs::write(
+        &script,
+        format!(
+            r#"gds read {{{gds}}}
+load {{{cell}}}
+select top cell
+extract all
+ext2sim labels on
+ext2sim
+extresist tolerance 10
+extresist
+ext2spice lvs
+ext2spice cthresh 0
+ext2spice extresist on
+ext2spice -o {{{netlist}}}
+quit -noprompt
+"#,
+            gds = params.gds.display(),
+            cell = params.cell,
+            netlist = netlist.display(),
+        ),
+    )?;
+    let output = Command::new("magic")
+        .arg("-dnull")
+        .arg("-noconsole")
+        .arg("-rcfile")
+        .arg(magicrc)
+        .arg(&script)
+        .current_dir(&params.work_dir)
+        .output()?;
+    check_tool_output("magic", output, params.work_dir.join("pex.log"))?;
+    Ok(netlist)
+}
+
+fn run_starrc_pex(params: &PexParams, cmd_file: &PathBuf) -> Result<PathBuf> {
+    let netlist = params.work_dir.join(format!("{}.spf", params.cell));
+    let cmd = params.work_dir.join("star_cmd");
+    let mut contents = std::fs::read_to_string(cmd_file)?;
+    contents.push_str(&format!(
+        "\nBLOCK: {}\nNETLIST_FILE: {}\nNETLIST_FORMAT: SPF\nNETLIST_SUBCKT: YES\n",
+        params.cell,
+        netlist.display()
+    ));
+    std::fs::write(&cmd, contents)?;
+    let output = Command::new("StarXtract")
+        .arg("-clean")
+        .arg(&cmd)
+        .current_dir(&params.work_dir)
+        .output()?;
+    check_tool_output("StarXtract", output, params.work_dir.join("pex.log"))?;
+    Ok(netlist)
+}
+
+/// Returns the ports of the subcircuit named `cell` in the SPICE netlist `source`.
+///
+/// Subcircuit names are compared case-insensitively, and `+` continuation lines are
+/// joined. Parameters following the ports are ignored. Returns [`None`] if `source`
+/// does not define the subcircuit.
+pub fn subckt_ports(source: &str, cell: &str) -> Option<Vec<ArcStr>> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in source.lines() {
+        match (line.trim_start().strip_prefix('+'), lines.last_mut()) {
+            (Some(rest), Some(last)) => {
+                last.push(' ');
+                last.push_str(rest);
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines.iter().find_map(|line| {
+        let mut tokens = line.split_whitespace();
+        let is_subckt = tokens
+            .next()
+            .is_some_and(|t| t.eq_ignore_ascii_case(".subckt"));
+        let is_cell = tokens.next().is_some_and(|t| t.eq_ignore_ascii_case(cell));
+        (is_subckt && is_cell).then(|| {
+            tokens
+                .take_while(|t| !t.contains('=') && !t.eq_ignore_ascii_case("params:"))
+                .map(ArcStr::from)
+                .collect()
+        })
+    })
+}
+
+/// The extracted view of a block.
+///
+/// Has the same IO as the wrapped block, but its schematic is the extracted netlist.
+/// Testbenches that are generic over the DUT schema can simulate it in place of the
+/// original block by using [`Spice`] as the DUT schema, as with
+/// [`DriverAcTb::with_extracted_dut`](crate::driver::tb::DriverAcTb::with_extracted_dut)
+/// and [`StrongArmTranTb::with_extracted_dut`](crate::strongarm::tb::StrongArmTranTb::with_extracted_dut).
+///
+/// The internal nodes of the extracted netlist are not exposed, so the view has no
+/// probe points.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractedView<T> {
+    dut: T,
+    pex: PexOutput,
+    ports: Vec<ArcStr>,
+}
+
+impl<T> ExtractedView<T> {
+    /// Creates a new [`ExtractedView`] of `dut`.
+    ///
+    /// `ports` lists the subcircuit ports corresponding to the flattened IO of `dut`, in order.
+    pub fn new(dut: T, pex: PexOutput, ports: Vec<ArcStr>) -> Self {
+        Self { dut, pex, ports }
+    }
+
+    /// Reads the extracted netlist.
+    ///
+    /// Fails if the netlist cannot be read, does not define the extracted subcircuit,
+    /// or if the subcircuit lacks one of the ports of this view.
+    pub fn read_netlist(&self) -> Result<String> {
+        let source = std::fs::read_to_string(&self.pex.netlist)?;
+        let parse_error = |message| Error::Parse {
+            path: self.pex.netlist.clone(),
+            message,
+        };
+        let ports = subckt_ports(&source, &self.pex.cell)
+            .ok_or_else(|| parse_error(format!("no subcircuit named {}", self.pex.cell)))?;
+        if let Some(port) = self.ports.iter().find(|port| !ports.contains(port)) {
+            return Err(parse_error(format!(
+                "subcircuit {} has no port {port}",
+                self.pex.cell
+            )));
+        }
+        Ok(source)
+    }
+}
+
+impl<T: Block> Block for ExtractedView<T> {
+    type Io = T::Io;
+
+    fn id() -> ArcStr {
+        substrate::arcstr::literal!("extracted_view")
+    }
+
+    fn name(&self) -> ArcStr {
+        substrate::arcstr::format!("{}_extracted", self.dut.name())
+    }
+
+    fn io(&self) -> Self::Io {
+        self.dut.io()
+    }
+}
+
+impl<T: Block> ExportsNestedData for ExtractedView<T> {
+    type NestedData = Probes;
+}
+
+impl<T: Block> ProbePoints for ExtractedView<T> {
+    const PROBES: &'static [&'static str] = &[];
+}
+
+impl<T: Block> Schematic<Spice> for ExtractedView<T> {
+    fn schematic(
+        &self,
+        io: &<<Self as Block>::Io as HardwareType>::Bundle,
+        cell: &mut CellBuilder<Spice>,
+    ) -> substrate::error::Result<Self::NestedData> {
+        let source = self
+            .read_netlist()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let mut scir = Spice::scir_cell_from_str(&source, &self.pex.cell);
+
+        let nodes = io.flatten_vec();
+        assert_eq!(
+            nodes.len(),
+            self.ports.len(),
+            "number of ports must match the flattened IO of the DUT"
+        );
+        for (port, node) in self.ports.iter().zip(nodes) {
+            scir.connect(port, node);
+        }
+
+        cell.set_scir(scir);
+        Ok(Probes::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETLIST: &str = "* extracted by magic\n\
+        .subckt driver_unit din dout pu_ctl\n\
+        + pd_ctlb vdd vss\n\
+        X0 dout pd_en vss vss sky130_fd_pr__nfet_01v8 w=1.68 l=0.15\n\
+        R0 dout dout.n1 12.5\n\
+        C0 dout vss 0.85f\n\
+        .ends\n\
+        .SUBCKT other a b PARAMS: w=1\n\
+        .ends\n";
+
+    #[test]
+    fn parses_extracted_subckt_ports() {
+        assert_eq!(
+            subckt_ports(NETLIST, "driver_unit").unwrap(),
+            ["din", "dout", "pu_ctl", "pd_ctlb", "vdd", "vss"]
+        );
+        assert_eq!(subckt_ports(NETLIST, "OTHER").unwrap(), ["a", "b"]);
+        assert_eq!(subckt_ports(NETLIST, "missing"), None);
+    }
+
+    #[test]
+    fn checks_extracted_netlist_ports() {
+        let work_dir = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/build/checks_extracted_netlist_ports"
+        ));
+        std::fs::create_dir_all(&work_dir).unwrap();
+        let netlist = work_dir.join("driver_unit.pex.spice");
+        std::fs::write(&netlist, NETLIST).unwrap();
+        let pex = |cell: &str| PexOutput {
+            netlist: netlist.clone(),
+            cell: cell.to_string(),
+        };
+        let ports = |names: &[&str]| names.iter().map(|&n| ArcStr::from(n)).collect();
+
+        let view = ExtractedView::new((), pex("driver_unit"), ports(&["din", "dout", "vss"]));
+        assert_eq!(view.read_netlist().unwrap(), NETLIST);
+
+        let view = ExtractedView::new((), pex("driver_unit"), ports(&["din", "pd_en"]));
+        assert!(matches!(view.read_netlist(), Err(Error::Parse { .. })));
+
+        let view = ExtractedView::new((), pex("missing"), ports(&["din"]));
+        assert!(matches!(view.read_netlist(), Err(Error::Parse { .. })));
+
+        let view = ExtractedView::new(
+            (),
+            PexOutput {
+                netlist: work_dir.join("nonexistent.spice"),
+                cell: "driver_unit".to_string(),
+            },
+            ports(&["din"]),
+        );
+        assert!(matches!(view.read_netlist(), Err(Error::Io(_))));
+    }
+}
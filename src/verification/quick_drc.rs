@@ -0,0 +1,438 @@
+//! A lightweight built-in geometric checker.
+//!
+//! The router leaves short stubs where a path ends on a via or a pin, and these are the
+//! most common source of minimum-area violations in generated tiles. [`quick_drc`] checks
+//! minimum width, minimum spacing, and minimum area on the routing layers of a generated
+//! layout without exporting it or running an external DRC tool, so that such problems
+//! surface before GDS export.
+//!
+//! The check works on shape bounding boxes, like the [area report](crate::report::area),
+//! and only covers the rules given by [`QuickDrcRules`]. It is not a substitute for a
+//! signoff DRC run:
+//! - Shapes that touch, including at a corner, are treated as one polygon.
+//! - Spacing is only checked between separate polygons, so notches within a polygon
+//!   are not reported.
+//! - Width is checked per shape, after merging the shapes that fully span it.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+use substrate::context::PdkContext;
+use substrate::geometry::rect::Rect;
+use substrate::layout::element::RawCell;
+use substrate::layout::Layout;
+use substrate::pdk::{Pdk, PdkLayers};
+
+use crate::report::area::{layer_shapes, union_area, AreaLayers};
+use crate::report::SimArtifact;
+
+/// The minimum width, spacing, and area of a layer.
+///
+/// Lengths are in nanometers and areas in square nanometers.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct LayerRules {
+    /// The minimum width of a shape.
+    pub min_width: i64,
+    /// The minimum distance between two separate polygons.
+    pub min_spacing: i64,
+    /// The minimum area of a polygon.
+    pub min_area: i64,
+}
+
+impl LayerRules {
+    /// Creates a new [`LayerRules`].
+    pub const fn new(min_width: i64, min_spacing: i64, min_area: i64) -> Self {
+        Self {
+            min_width,
+            min_spacing,
+            min_area,
+        }
+    }
+}
+
+/// The layer rules of a technology checked by [`quick_drc`].
+pub trait QuickDrcRules<PDK: Pdk>: AreaLayers<PDK> {
+    /// The rules of each routing layer, keyed by the names given by
+    /// [`AreaLayers::routing_layers`].
+    ///
+    /// Routing layers without an entry are not checked.
+    const QUICK_DRC_RULES: &'static [(&'static str, LayerRules)];
+}
+
+/// A rule checked by [`quick_drc`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum QuickDrcRule {
+    /// A shape is narrower than the minimum width.
+    MinWidth,
+    /// Two polygons are closer than the minimum spacing.
+    MinSpacing,
+    /// A polygon is smaller than the minimum area.
+    MinArea,
+}
+
+impl Display for QuickDrcRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::MinWidth => "min_width",
+            Self::MinSpacing => "min_spacing",
+            Self::MinArea => "min_area",
+        })
+    }
+}
+
+/// A violation found by [`quick_drc`].
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct QuickDrcViolation {
+    /// The name of the layer.
+    pub layer: String,
+    /// The violated rule.
+    pub rule: QuickDrcRule,
+    /// The location of the violation.
+    ///
+    /// This is the narrow shape for width violations, the gap between the two polygons
+    /// for spacing violations, and the bounding box of the polygon for area violations.
+    pub rect: Rect,
+    /// The measured width, spacing, or area.
+    pub value: i64,
+    /// The minimum allowed by the rule.
+    pub limit: i64,
+}
+
+impl Display for QuickDrcViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}: {} < {} at ({}, {}) to ({}, {})",
+            self.layer,
+            self.rule,
+            self.value,
+            self.limit,
+            self.rect.left(),
+            self.rect.bot(),
+            self.rect.right(),
+            self.rect.top()
+        )
+    }
+}
+
+/// The results of [`quick_drc`].
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct QuickDrcReport {
+    /// The violations found, grouped by layer from the bottom up.
+    pub violations: Vec<QuickDrcViolation>,
+}
+
+impl QuickDrcReport {
+    /// Returns `true` if there are no violations.
+    pub fn is_clean(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl SimArtifact for QuickDrcReport {
+    fn csv_header(&self) -> Vec<String> {
+        [
+            "layer", "rule", "value", "limit", "left", "bot", "right", "top",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    fn csv_rows(&self) -> Vec<Vec<String>> {
+        self.violations
+            .iter()
+            .map(|v| {
+                vec![
+                    v.layer.clone(),
+                    v.rule.to_string(),
+                    v.value.to_string(),
+                    v.limit.to_string(),
+                    v.rect.left().to_string(),
+                    v.rect.bot().to_string(),
+                    v.rect.right().to_string(),
+                    v.rect.top().to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Generates the layout of `block` and checks it against the rules of technology `T`.
+pub fn quick_drc<T: QuickDrcRules<PDK>, PDK: Pdk, B: Layout<PDK>>(
+    ctx: &PdkContext<PDK>,
+    block: B,
+) -> QuickDrcReport {
+    let cell = ctx.generate_layout(block);
+    quick_drc_of_cell::<T, PDK>(&ctx.layers, cell.raw())
+}
+
+/// Checks an already generated layout cell against the rules of technology `T`.
+pub fn quick_drc_of_cell<T: QuickDrcRules<PDK>, PDK: Pdk>(
+    layers: &PdkLayers<PDK>,
+    cell: &RawCell,
+) -> QuickDrcReport {
+    let shapes = layer_shapes(cell);
+    let violations = T::routing_layers(layers)
+        .into_iter()
+        .filter_map(|(name, id)| {
+            let (_, rules) = T::QUICK_DRC_RULES.iter().find(|(n, _)| *n == name)?;
+            Some(check_layer(name, *rules, shapes.get(&id)?))
+        })
+        .flatten()
+        .collect();
+    QuickDrcReport { violations }
+}
+
+/// Panics with a list of violations if `report` is not clean.
+pub fn assert_quick_drc_clean(report: &QuickDrcReport) {
+    if !report.is_clean() {
+        let violations = report
+            .violations
+            .iter()
+            .map(|v| format!("  {v}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        panic!(
+            "found {} quick DRC violation(s):\n{violations}",
+            report.violations.len()
+        );
+    }
+}
+
+/// Checks the shapes `rects` of the layer named `layer` against `rules`.
+pub fn check_layer(layer: &str, rules: LayerRules, rects: &[Rect]) -> Vec<QuickDrcViolation> {
+    let rects = rects
+        .iter()
+        .copied()
+        .filter(|rect| rect.area() > 0)
+        .collect::<Vec<_>>();
+    let mut order = (0..rects.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| (rects[i].left(), rects[i].bot()));
+
+    let mut polygons = Polygons::new(rects.len());
+    for (k, &i) in order.iter().enumerate() {
+        for &j in order[k + 1..]
+            .iter()
+            .take_while(|&&j| rects[j].left() <= rects[i].right())
+        {
+            if rects[j].bot() <= rects[i].top() && rects[i].bot() <= rects[j].top() {
+                polygons.join(i, j);
+            }
+        }
+    }
+    let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for &i in order.iter() {
+        members.entry(polygons.find(i)).or_default().push(i);
+    }
+
+    let violation = |rule, rect, value, limit| QuickDrcViolation {
+        layer: layer.to_string(),
+        rule,
+        rect,
+        value,
+        limit,
+    };
+    let mut violations = Vec::new();
+
+    let mut seen = HashSet::new();
+    for shapes in members.values() {
+        for &i in shapes {
+            let rect = rects[i];
+            let width = merged_width(rect, shapes.iter().map(|&j| rects[j]));
+            if width < rules.min_width && seen.insert(rect) {
+                violations.push(violation(
+                    QuickDrcRule::MinWidth,
+                    rect,
+                    width,
+                    rules.min_width,
+                ));
+            }
+        }
+    }
+
+    // Keep only the closest pair of shapes between each pair of polygons.
+    let mut gaps: BTreeMap<(usize, usize), (i64, Rect)> = BTreeMap::new();
+    for (k, &i) in order.iter().enumerate() {
+        for &j in order[k + 1..]
+            .iter()
+            .take_while(|&&j| rects[j].left() < rects[i].right() + rules.min_spacing)
+        {
+            let (p, q) = (polygons.find(i), polygons.find(j));
+            if p == q {
+                continue;
+            }
+            let (a, b) = (rects[i], rects[j]);
+            let dx = (b.left() - a.right()).max(a.left() - b.right()).max(0);
+            let dy = (b.bot() - a.top()).max(a.bot() - b.top()).max(0);
+            if dx * dx + dy * dy >= rules.min_spacing * rules.min_spacing {
+                continue;
+            }
+            let distance = ((dx * dx + dy * dy) as f64).sqrt() as i64;
+            let closest = gaps
+                .entry((p.min(q), p.max(q)))
+                .or_insert((distance, gap(a, b)));
+            if distance < closest.0 {
+                *closest = (distance, gap(a, b));
+            }
+        }
+    }
+    violations.extend(gaps.into_values().map(|(distance, rect)| {
+        violation(QuickDrcRule::MinSpacing, rect, distance, rules.min_spacing)
+    }));
+
+    for shapes in members.values() {
+        let area = union_area(shapes.iter().map(|&i| rects[i]));
+        if area < rules.min_area {
+            let bbox = shapes
+                .iter()
+                .map(|&i| rects[i])
+                .reduce(|a, b| {
+                    Rect::from_sides(
+                        a.left().min(b.left()),
+                        a.bot().min(b.bot()),
+                        a.right().max(b.right()),
+                        a.top().max(b.top()),
+                    )
+                })
+                .expect("polygon has at least one shape");
+            violations.push(violation(QuickDrcRule::MinArea, bbox, area, rules.min_area));
+        }
+    }
+
+    violations
+}
+
+/// Returns the width of `rect` after merging it with the shapes of `others` that touch it
+/// and span its full extent perpendicular to the direction of merging.
+fn merged_width(rect: Rect, others: impl Iterator<Item = Rect> + Clone) -> i64 {
+    let horizontal = merged_span(
+        rect,
+        others.clone(),
+        |r| (r.left(), r.right()),
+        |r| (r.bot(), r.top()),
+    );
+    let vertical = merged_span(
+        rect,
+        others,
+        |r| (r.bot(), r.top()),
+        |r| (r.left(), r.right()),
+    );
+    horizontal.min(vertical)
+}
+
+/// Returns the length of `rect` along an axis, given by `along`, after repeatedly merging
+/// it with the shapes of `others` that touch it and cover its extent across the axis,
+/// given by `across`.
+fn merged_span(
+    rect: Rect,
+    others: impl Iterator<Item = Rect> + Clone,
+    along: impl Fn(&Rect) -> (i64, i64),
+    across: impl Fn(&Rect) -> (i64, i64),
+) -> i64 {
+    let (across_lo, across_hi) = across(&rect);
+    let (mut start, mut stop) = along(&rect);
+    loop {
+        let prev = (start, stop);
+        for other in others.clone() {
+            let (lo, hi) = along(&other);
+            let (other_lo, other_hi) = across(&other);
+            if other_lo <= across_lo && other_hi >= across_hi && lo <= stop && hi >= start {
+                start = start.min(lo);
+                stop = stop.max(hi);
+            }
+        }
+        if (start, stop) == prev {
+            return stop - start;
+        }
+    }
+}
+
+/// Returns the rectangle between `a` and `b`.
+///
+/// Along an axis where the two overlap, this spans the overlap.
+fn gap(a: Rect, b: Rect) -> Rect {
+    let span = |a_lo: i64, a_hi: i64, b_lo: i64, b_hi: i64| {
+        let (inner_lo, inner_hi) = (a_lo.max(b_lo), a_hi.min(b_hi));
+        (inner_lo.min(inner_hi), inner_lo.max(inner_hi))
+    };
+    let (left, right) = span(a.left(), a.right(), b.left(), b.right());
+    let (bot, top) = span(a.bot(), a.top(), b.bot(), b.top());
+    Rect::from_sides(left, bot, right, top)
+}
+
+/// A union-find over shape indices, grouping touching shapes into polygons.
+struct Polygons {
+    parent: Vec<usize>,
+}
+
+impl Polygons {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn join(&mut self, i: usize, j: usize) {
+        let (i, j) = (self.find(i), self.find(j));
+        if i != j {
+            self.parent[i.max(j)] = i.min(j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MET1: LayerRules = LayerRules::new(140, 140, 83_000);
+
+    fn rules(violations: &[QuickDrcViolation]) -> Vec<QuickDrcRule> {
+        violations.iter().map(|v| v.rule).collect()
+    }
+
+    #[test]
+    fn finds_router_stubs() {
+        // A wire, an abutting extension that is only wide enough together with the
+        // wire, and an isolated minimum-width stub that is too small.
+        let rects = [
+            Rect::from_sides(0, 0, 2_000, 140),
+            Rect::from_sides(2_000, 0, 2_100, 140),
+            Rect::from_sides(3_000, 0, 3_140, 200),
+        ];
+        let violations = check_layer("met1", MET1, &rects);
+        assert_eq!(rules(&violations), [QuickDrcRule::MinArea]);
+        assert_eq!(violations[0].rect, rects[2]);
+        assert_eq!(violations[0].value, 140 * 200);
+    }
+
+    #[test]
+    fn finds_narrow_shapes_and_close_polygons() {
+        let rects = [
+            Rect::from_sides(0, 0, 1_000, 140),
+            Rect::from_sides(0, 240, 1_000, 380),
+            Rect::from_sides(2_000, 0, 2_100, 1_000),
+            Rect::from_sides(1_100, 480, 1_800, 620),
+        ];
+        let violations = check_layer("met1", MET1, &rects);
+        assert_eq!(
+            rules(&violations),
+            [QuickDrcRule::MinWidth, QuickDrcRule::MinSpacing]
+        );
+        assert_eq!(violations[0].rect, rects[2]);
+        assert_eq!(violations[0].value, 100);
+        assert_eq!(violations[1].rect, Rect::from_sides(0, 140, 1_000, 240));
+        assert_eq!(violations[1].value, 100);
+        // The last shape is 100 nm away from the second in both directions,
+        // so their corners are far enough apart.
+    }
+}
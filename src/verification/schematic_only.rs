@@ -0,0 +1,83 @@
+//! Checks of schematic-only generation.
+//!
+//! [`GenerationOptions::schematic_only`](crate::GenerationOptions::schematic_only) skips
+//! layout work that does not affect the schematic. [`check_schematic_only`] netlists a
+//! block in both modes and confirms that the netlists are identical.
+
+use spice::netlist::NetlistOptions;
+use spice::Spice;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use substrate::block::Block;
+use substrate::context::PdkContext;
+use substrate::pdk::Pdk;
+use substrate::schematic::netlist::ConvertibleNetlister;
+use substrate::schematic::schema::Schema;
+use substrate::schematic::Schematic;
+use substrate::scir::schema::FromSchema;
+
+use crate::GenerationOptions;
+
+/// The netlists of a block generated with and without schematic-only mode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchematicOnlyCheck {
+    /// The netlist generated in layout mode.
+    pub layout_mode: PathBuf,
+    /// The netlist generated in schematic-only mode.
+    pub schematic_only: PathBuf,
+    /// Whether the two netlists are identical.
+    pub matched: bool,
+}
+
+/// Netlists `block` in layout mode and in schematic-only mode, converting the schematic
+/// to the schema `S`, and compares the results.
+///
+/// `new_ctx` is called to build a context for each mode from the given options, which
+/// are `options` with [`GenerationOptions::schematic_only`] cleared and set. The netlists
+/// are written to `work_dir`.
+pub fn check_schematic_only<PDK, S, B>(
+    new_ctx: impl Fn(GenerationOptions) -> PdkContext<PDK>,
+    options: GenerationOptions,
+    block: B,
+    work_dir: impl AsRef<Path>,
+) -> std::io::Result<SchematicOnlyCheck>
+where
+    PDK: Pdk + Schema,
+    S: Schema + FromSchema<PDK>,
+    Spice: FromSchema<S>,
+    <S as FromSchema<PDK>>::Error: Debug,
+    <Spice as FromSchema<S>>::Error: Debug,
+    B: Block + Schematic<PDK> + Clone,
+{
+    let work_dir = work_dir.as_ref();
+    std::fs::create_dir_all(work_dir)?;
+
+    let netlist = |schematic_only: bool, path: PathBuf| {
+        let scir = new_ctx(GenerationOptions {
+            schematic_only,
+            ..options
+        })
+        .export_scir(block.clone())
+        .unwrap()
+        .scir
+        .convert_schema::<S>()
+        .unwrap()
+        .convert_schema::<Spice>()
+        .unwrap()
+        .build()
+        .unwrap();
+        Spice
+            .write_scir_netlist_to_file(&scir, &path, NetlistOptions::default())
+            .expect("failed to write netlist");
+        path
+    };
+    let layout_mode = netlist(false, work_dir.join("layout_mode.sp"));
+    let schematic_only = netlist(true, work_dir.join("schematic_only.sp"));
+
+    let matched = std::fs::read(&layout_mode)? == std::fs::read(&schematic_only)?;
+    Ok(SchematicOnlyCheck {
+        layout_mode,
+        schematic_only,
+        matched,
+    })
+}
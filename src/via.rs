@@ -0,0 +1,187 @@
+//! Redundant vias.
+//!
+//! Single-cut vias on high-current nets are an electromigration and yield risk. A
+//! [`ViaPolicy`] in the [`GenerationOptions`](crate::GenerationOptions) sets the
+//! [`ViaRedundancy`] of each [`NetClass`], and [`via_stack`] replicates the vias drawn by
+//! a technology's via maker accordingly.
+
+use std::ops::Range;
+
+use atoll::abs::TrackCoord;
+use atoll::route::ViaMaker;
+use serde::{Deserialize, Serialize};
+use substrate::context::{Context, PdkContext};
+use substrate::geometry::bbox::Bbox;
+use substrate::geometry::point::Point;
+use substrate::geometry::rect::Rect;
+use substrate::geometry::transform::Translate;
+use substrate::layout::element::Shape;
+use substrate::pdk::Pdk;
+
+/// The number and arrangement of cuts at each via location.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum ViaRedundancy {
+    /// One via.
+    #[default]
+    Single,
+    /// Two vias side by side.
+    Double,
+    /// A grid of vias.
+    Array {
+        /// The number of rows.
+        rows: usize,
+        /// The number of columns.
+        cols: usize,
+    },
+}
+
+impl ViaRedundancy {
+    /// The number of rows and columns of vias.
+    pub fn dims(&self) -> (usize, usize) {
+        match *self {
+            Self::Single => (1, 1),
+            Self::Double => (1, 2),
+            Self::Array { rows, cols } => (rows.max(1), cols.max(1)),
+        }
+    }
+
+    /// The number of vias.
+    pub fn cuts(&self) -> usize {
+        let (rows, cols) = self.dims();
+        rows * cols
+    }
+
+    /// The offsets of each via from the center of the arrangement, given the bounding
+    /// box of a single via.
+    ///
+    /// Adjacent vias abut, so their landing pads merge.
+    pub fn offsets(&self, footprint: Rect) -> Vec<Point> {
+        let (rows, cols) = self.dims();
+        let centers = |n: usize, size: i64| {
+            let n = n as i64;
+            (0..n).map(move |i| i * size - (n - 1) * size / 2)
+        };
+        centers(rows, footprint.height())
+            .flat_map(|y| centers(cols, footprint.width()).map(move |x| Point::new(x, y)))
+            .collect()
+    }
+}
+
+/// A class of nets with a common via policy.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum NetClass {
+    /// Power and ground.
+    Supply,
+    /// Driver outputs, from the driver units up to the bump.
+    Dout,
+}
+
+/// The [`ViaRedundancy`] of each [`NetClass`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ViaPolicy {
+    /// The redundancy of supply vias.
+    ///
+    /// The power grid fills each strap crossing with as many vias as fit, so this only
+    /// adds vias at crossings too small to fit the requested arrangement.
+    pub supply: ViaRedundancy,
+    /// The redundancy of driver output vias.
+    pub dout: ViaRedundancy,
+}
+
+impl ViaPolicy {
+    pub(crate) const DEFAULT: Self = Self {
+        supply: ViaRedundancy::Single,
+        dout: ViaRedundancy::Single,
+    };
+
+    /// The redundancy of vias on nets of class `class`.
+    pub fn get(&self, class: NetClass) -> ViaRedundancy {
+        match class {
+            NetClass::Supply => self.supply,
+            NetClass::Dout => self.dout,
+        }
+    }
+}
+
+impl Default for ViaPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Returns the redundancy of vias on nets of class `class` under the
+/// [`GenerationOptions`](crate::GenerationOptions) of `ctx`.
+pub fn redundancy(ctx: &Context, class: NetClass) -> ViaRedundancy {
+    crate::generation_options(ctx).vias.get(class)
+}
+
+/// Draws a stack of vias connecting each layer in `layers` to the layer beneath it,
+/// replicated according to the policy for `class`.
+///
+/// Returns each shape with the layer of the via that drew it. The vias of each layer
+/// are centered on the origin.
+pub fn via_stack<PDK: Pdk, V: ViaMaker<PDK>>(
+    via_maker: &V,
+    ctx: &PdkContext<PDK>,
+    layers: Range<usize>,
+    class: NetClass,
+) -> Vec<(usize, Shape)> {
+    let redundancy = redundancy(ctx, class);
+    let mut stack = Vec::new();
+    for layer in layers {
+        let shapes = via_maker.draw_via(ctx.clone(), TrackCoord { layer, x: 0, y: 0 });
+        let Some(footprint) = shapes
+            .iter()
+            .map(|shape| shape.bbox_rect())
+            .reduce(|a, b| a.union(b))
+        else {
+            continue;
+        };
+        for offset in redundancy.offsets(footprint) {
+            let offset = offset - footprint.center();
+            stack.extend(
+                shapes
+                    .iter()
+                    .map(|shape| (layer, shape.clone().translate(offset))),
+            );
+        }
+    }
+    stack
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redundant_vias_abut_around_center() {
+        let footprint = Rect::from_sides(-100, -50, 100, 50);
+        assert_eq!(
+            ViaRedundancy::Single.offsets(footprint),
+            vec![Point::new(0, 0)]
+        );
+        assert_eq!(
+            ViaRedundancy::Double.offsets(footprint),
+            vec![Point::new(-100, 0), Point::new(100, 0)]
+        );
+        let array = ViaRedundancy::Array { rows: 3, cols: 2 };
+        assert_eq!(array.cuts(), 6);
+        assert_eq!(
+            array.offsets(footprint),
+            vec![
+                Point::new(-100, -100),
+                Point::new(100, -100),
+                Point::new(-100, 0),
+                Point::new(100, 0),
+                Point::new(-100, 100),
+                Point::new(100, 100),
+            ]
+        );
+        let policy = ViaPolicy {
+            dout: ViaRedundancy::Double,
+            ..Default::default()
+        };
+        assert_eq!(policy.get(NetClass::Dout), ViaRedundancy::Double);
+        assert_eq!(policy.get(NetClass::Supply), ViaRedundancy::Single);
+    }
+}
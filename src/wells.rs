@@ -0,0 +1,120 @@
+//! Merging of well and implant layers across the child tiles of a composite.
+//!
+//! Each tile draws its own n-well and implant rectangles, so abutting tiles leave
+//! seams and narrow gaps between them. [`WellMerger`] collects the per-instance
+//! bounding boxes of a layer and redraws them as the fewest rectangles that bridge
+//! every gap narrower than the layer's minimum spacing.
+
+use atoll::TileBuilder;
+use substrate::error::Result;
+use substrate::geometry::rect::Rect;
+use substrate::layout::bbox::LayerBbox;
+use substrate::layout::element::Shape;
+use substrate::pdk::layers::LayerId;
+use substrate::pdk::Pdk;
+
+/// Returns the gap between two rectangles, or 0 if they overlap or abut.
+///
+/// The gap is measured along whichever axis separates the rectangles the most.
+fn gap(a: Rect, b: Rect) -> i64 {
+    let dx = (b.left() - a.right()).max(a.left() - b.right()).max(0);
+    let dy = (b.bot() - a.top()).max(a.bot() - b.top()).max(0);
+    dx.max(dy)
+}
+
+/// Merges rectangles separated by less than `min_spacing` into their bounding boxes.
+///
+/// Overlapping and abutting rectangles are always merged. Merging repeats until no two
+/// of the returned rectangles are closer than `min_spacing`.
+pub fn merge_rects(rects: impl IntoIterator<Item = Rect>, min_spacing: i64) -> Vec<Rect> {
+    let mut merged: Vec<Rect> = Vec::new();
+    for rect in rects {
+        let mut rect = rect;
+        // Absorbing a rectangle can bring the result within range of ones already checked.
+        while let Some(i) = merged
+            .iter()
+            .position(|&r| gap(r, rect) < min_spacing.max(1))
+        {
+            rect = rect.union(merged.swap_remove(i));
+        }
+        merged.push(rect);
+    }
+    merged
+}
+
+/// Collects the rectangles of a single layer from the child instances of a composite.
+#[derive(Debug, Clone)]
+pub struct WellMerger {
+    layer: LayerId,
+    min_spacing: i64,
+    rects: Vec<Rect>,
+}
+
+impl WellMerger {
+    /// Creates a merger for `layer` that bridges gaps narrower than `min_spacing`.
+    pub fn new(layer: LayerId, min_spacing: i64) -> Self {
+        Self {
+            layer,
+            min_spacing,
+            rects: Vec::new(),
+        }
+    }
+
+    /// Adds the bounding box of `layer` in the given layout, if it draws on that layer.
+    pub fn add(&mut self, layout: &impl LayerBbox) -> &mut Self {
+        if let Some(rect) = layout.layer_bbox(self.layer) {
+            self.rects.push(rect);
+        }
+        self
+    }
+
+    /// Adds a rectangle directly.
+    pub fn add_rect(&mut self, rect: Rect) -> &mut Self {
+        self.rects.push(rect);
+        self
+    }
+
+    /// Returns the merged rectangles without drawing them.
+    pub fn merged(&self) -> Vec<Rect> {
+        merge_rects(self.rects.iter().copied(), self.min_spacing)
+    }
+
+    /// Draws the merged rectangles after applying `transform` to each,
+    /// returning the rectangles drawn.
+    pub fn draw<PDK: Pdk>(
+        &self,
+        cell: &mut TileBuilder<'_, PDK>,
+        transform: impl Fn(Rect) -> Rect,
+    ) -> Result<Vec<Rect>> {
+        let rects = self.merged().into_iter().map(transform).collect::<Vec<_>>();
+        for rect in rects.iter() {
+            cell.layout.draw(Shape::new(self.layer, *rect))?;
+        }
+        Ok(rects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_rects_bridges_small_gaps() {
+        let rects = [
+            Rect::from_sides(0, 0, 100, 100),
+            Rect::from_sides(100, 0, 200, 100),
+            Rect::from_sides(250, 0, 300, 100),
+            Rect::from_sides(1_000, 0, 1_100, 100),
+        ];
+        let mut merged = merge_rects(rects, 100);
+        merged.sort_by_key(|r| r.left());
+        assert_eq!(
+            merged,
+            vec![
+                Rect::from_sides(0, 0, 300, 100),
+                Rect::from_sides(1_000, 0, 1_100, 100),
+            ]
+        );
+        assert_eq!(merge_rects(rects, 0).len(), 3);
+    }
+}